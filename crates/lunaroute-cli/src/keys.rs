@@ -0,0 +1,94 @@
+//! `lunaroute keys`: manage provider secrets in the encrypted credential vault
+
+use anyhow::{Context, Result, bail};
+use clap::Subcommand;
+use lunaroute_storage::CredentialVault;
+use lunaroute_storage::vault::{VAULT_PASSPHRASE_ENV, default_vault_path};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+/// Vault operations
+#[derive(Subcommand)]
+pub enum KeysAction {
+    /// Add a secret (read from stdin); reference it as `api_key: "vault:<name>"`
+    Add {
+        /// Entry name
+        name: String,
+    },
+    /// List entry names and timestamps (never the secrets)
+    List,
+    /// Replace an existing secret (read from stdin)
+    Rotate {
+        /// Entry name
+        name: String,
+    },
+    /// Remove a secret
+    Remove {
+        /// Entry name
+        name: String,
+    },
+}
+
+/// Run the `keys` command against the vault at `vault` (or the default location)
+pub fn run(vault: Option<PathBuf>, action: KeysAction) -> Result<()> {
+    let path = vault.unwrap_or_else(default_vault_path);
+    let passphrase = std::env::var(VAULT_PASSPHRASE_ENV)
+        .ok()
+        .filter(|p| !p.is_empty())
+        .with_context(|| format!("Set {} to unlock the vault", VAULT_PASSPHRASE_ENV))?;
+    let mut vault = CredentialVault::open(&path, &passphrase)
+        .with_context(|| format!("Failed to open vault {}", path.display()))?;
+
+    match action {
+        KeysAction::Add { name } => {
+            let secret = read_secret(&name)?;
+            vault.add(&name, &secret)?;
+            vault.save()?;
+            println!("Added '{}' to {}", name, path.display());
+        }
+        KeysAction::List => {
+            let entries = vault.list();
+            if entries.is_empty() {
+                println!("No secrets in {}", path.display());
+            }
+            for entry in entries {
+                println!(
+                    "{:<32} created {}  rotated {}",
+                    entry.name, entry.created_at, entry.updated_at
+                );
+            }
+        }
+        KeysAction::Rotate { name } => {
+            if vault.get(&name).is_none() {
+                bail!("No vault entry named '{}'", name);
+            }
+            let secret = read_secret(&name)?;
+            vault.rotate(&name, &secret)?;
+            vault.save()?;
+            println!("Rotated '{}'", name);
+        }
+        KeysAction::Remove { name } => {
+            vault.remove(&name)?;
+            vault.save()?;
+            println!("Removed '{}'", name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a secret from the first line of stdin, so it never lands in shell history
+fn read_secret(name: &str) -> Result<String> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        eprint!("Secret for '{}': ", name);
+        std::io::stderr().flush()?;
+    }
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line)?;
+    let secret = line.trim_end_matches(['\r', '\n']).to_string();
+    if secret.is_empty() {
+        bail!("No secret provided on stdin");
+    }
+    Ok(secret)
+}
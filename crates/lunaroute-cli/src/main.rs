@@ -2,6 +2,9 @@
 //!
 //! Command-line interface for managing and operating LunaRoute
 
+mod keys;
+mod route;
+
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -19,12 +22,59 @@ enum Commands {
     Init,
     /// Start the LunaRoute server
     Serve,
-    /// Test routing rules
-    Route,
+    /// Explain how the server would route a request (dry run)
+    Route {
+        /// Server base URL
+        #[arg(long, env = "LUNAROUTE_URL", default_value = "http://127.0.0.1:8081")]
+        server: String,
+
+        /// Admin API token (if the server sets admin.token)
+        #[arg(long, env = "LUNAROUTE_ADMIN_TOKEN")]
+        token: Option<String>,
+
+        /// Sample request body as JSON ("-" reads stdin)
+        #[arg(long, short = 'f', conflicts_with = "request_id")]
+        file: Option<PathBuf>,
+
+        /// Model to route (overrides the file's model, or builds a minimal request)
+        #[arg(long, short = 'm', conflicts_with = "request_id")]
+        model: Option<String>,
+
+        /// Replay a recorded request by ID
+        #[arg(long)]
+        request_id: Option<String>,
+
+        /// Session containing the recorded request (speeds up lookup)
+        #[arg(long, requires = "request_id")]
+        session_id: Option<String>,
+
+        /// Listener receiving the request: anthropic or openai
+        #[arg(long)]
+        listener: Option<String>,
+
+        /// Request header, e.g. "x-user-id: alice" (repeatable)
+        #[arg(long = "header", short = 'H', value_parser = route::parse_header)]
+        headers: Vec<(String, String)>,
+
+        /// Evaluate rule schedules at this RFC 3339 time instead of now
+        #[arg(long)]
+        at: Option<String>,
+
+        /// Print the raw JSON explanation
+        #[arg(long)]
+        json: bool,
+    },
     /// Export session data
     Export,
-    /// Manage API keys
-    Keys,
+    /// Manage provider secrets in the encrypted credential vault
+    Keys {
+        /// Vault file (default: ~/.lunaroute/vault.enc)
+        #[arg(long, env = "LUNAROUTE_VAULT_PATH")]
+        vault: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: keys::KeysAction,
+    },
     /// View metrics
     Metrics,
     /// Import JSONL session logs into SQLite database
@@ -62,9 +112,34 @@ async fn main() -> anyhow::Result<()> {
     match cli.command {
         Commands::Init => println!("Initializing LunaRoute..."),
         Commands::Serve => println!("Starting LunaRoute server..."),
-        Commands::Route => println!("Testing routes..."),
+        Commands::Route {
+            server,
+            token,
+            file,
+            model,
+            request_id,
+            session_id,
+            listener,
+            headers,
+            at,
+            json,
+        } => {
+            route::run(route::RouteArgs {
+                server,
+                token,
+                file,
+                model,
+                request_id,
+                session_id,
+                listener,
+                headers,
+                at,
+                json,
+            })
+            .await?;
+        }
         Commands::Export => println!("Exporting sessions..."),
-        Commands::Keys => println!("Managing keys..."),
+        Commands::Keys { vault, action } => keys::run(vault, action)?,
         Commands::Metrics => println!("Viewing metrics..."),
         Commands::ImportSessions {
             sessions_dir,
@@ -0,0 +1,249 @@
+//! `lunaroute route`: explain routing decisions via the server's admin API
+
+use anyhow::{Context, Result, bail};
+use lunaroute_routing::{RoutingExplanation, RuleOutcome};
+use std::path::PathBuf;
+
+/// Options for the `route` command
+pub struct RouteArgs {
+    pub server: String,
+    pub token: Option<String>,
+    pub file: Option<PathBuf>,
+    pub model: Option<String>,
+    pub request_id: Option<String>,
+    pub session_id: Option<String>,
+    pub listener: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub at: Option<String>,
+    pub json: bool,
+}
+
+/// Parse a `Name: value` or `name=value` header argument
+pub fn parse_header(arg: &str) -> Result<(String, String), String> {
+    arg.split_once(':')
+        .or_else(|| arg.split_once('='))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .filter(|(name, _)| !name.is_empty())
+        .ok_or_else(|| format!("invalid header '{}', expected 'Name: value'", arg))
+}
+
+/// Build the admin API request body from command-line options
+fn build_body(args: &RouteArgs) -> Result<serde_json::Value> {
+    let mut body = serde_json::json!({
+        "listener": args.listener,
+        "request_id": args.request_id,
+        "session_id": args.session_id,
+        "headers": args.headers.iter().cloned().collect::<std::collections::HashMap<_, _>>(),
+        "at": args.at,
+    });
+
+    if args.request_id.is_some() {
+        return Ok(body);
+    }
+
+    let mut request: serde_json::Value = match &args.file {
+        Some(path) if path.as_os_str() == "-" => serde_json::from_reader(std::io::stdin())
+            .context("Failed to parse request from stdin")?,
+        Some(path) => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display()))?
+        }
+        None => {
+            let Some(model) = &args.model else {
+                bail!("Provide --file, --request-id or --model");
+            };
+            serde_json::json!({
+                "model": model,
+                "max_tokens": 16,
+                "messages": [{"role": "user", "content": "ping"}],
+            })
+        }
+    };
+    if let (Some(model), Some(_)) = (&args.model, &args.file) {
+        request["model"] = serde_json::Value::String(model.clone());
+    }
+
+    body["request"] = request;
+    Ok(body)
+}
+
+/// Run the `route` command
+pub async fn run(args: RouteArgs) -> Result<()> {
+    let body = build_body(&args)?;
+    let url = format!(
+        "{}/admin/routing/explain",
+        args.server.trim_end_matches('/')
+    );
+
+    let client = reqwest::Client::new();
+    let mut request = client.post(&url).json(&body);
+    if let Some(token) = &args.token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {}", url))?;
+
+    let status = response.status();
+    let payload: serde_json::Value = response
+        .json()
+        .await
+        .context("Server returned a non-JSON response")?;
+    if !status.is_success() {
+        let message = payload
+            .get("error")
+            .and_then(|e| e.as_str())
+            .unwrap_or("unknown error");
+        bail!("Server returned {}: {}", status, message);
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&payload)?);
+        return Ok(());
+    }
+
+    let explanation: RoutingExplanation =
+        serde_json::from_value(payload).context("Unexpected explain response")?;
+    print!("{}", render(&explanation));
+    Ok(())
+}
+
+/// Render an explanation for the terminal
+pub fn render(explanation: &RoutingExplanation) -> String {
+    let mut out = String::new();
+
+    out.push_str(&format!("Model:     {}", explanation.requested_model));
+    if explanation.resolved_model != explanation.requested_model {
+        out.push_str(&format!(" → {}", explanation.resolved_model));
+    }
+    out.push('\n');
+
+    if !explanation.steps.is_empty() {
+        out.push_str("\nSteps:\n");
+        for step in &explanation.steps {
+            out.push_str(&format!("  {:<11} {}\n", step.stage, step.detail));
+        }
+    }
+
+    if !explanation.rules.is_empty() {
+        out.push_str("\nRules (priority order):\n");
+        for rule in &explanation.rules {
+            let marker = match rule.outcome {
+                RuleOutcome::Matched => "✓ matched",
+                RuleOutcome::NotMatched => "✗ no match",
+                RuleOutcome::Inactive => "◌ inactive",
+                RuleOutcome::Invalid => "! invalid",
+                RuleOutcome::NotEvaluated => "- skipped",
+            };
+            out.push_str(&format!(
+                "  {:<11} [{:>4}] {:<24} {}",
+                marker, rule.priority, rule.name, rule.matcher
+            ));
+            if let Some(schedule) = &rule.schedule {
+                out.push_str(&format!(" @ {}", schedule));
+            }
+            out.push('\n');
+        }
+    }
+
+    if let Some(strategy) = &explanation.strategy {
+        out.push_str(&format!("\nStrategy:  {}", strategy.kind));
+        match (&strategy.selected, &strategy.error) {
+            (Some(selected), _) => out.push_str(&format!(" → {}\n", selected)),
+            (None, Some(error)) => out.push_str(&format!(" failed: {}\n", error)),
+            (None, None) => out.push('\n'),
+        }
+    }
+
+    out.push_str("\nProviders (in order):\n");
+    if explanation.candidates.is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for candidate in &explanation.candidates {
+        let role = serde_json::to_value(candidate.role)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let health = serde_json::to_value(candidate.health)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let status = match &candidate.skipped {
+            Some(reason) => format!("skipped: {}", reason),
+            None => "available".to_string(),
+        };
+        out.push_str(&format!(
+            "  {:<16} {:<12} {:<28} health={:<9} {}\n",
+            candidate.provider, role, candidate.model, health, status
+        ));
+    }
+
+    out.push_str(&format!(
+        "\nSelected:  {}\n",
+        explanation
+            .selected_provider
+            .as_deref()
+            .unwrap_or("none (request would fail)")
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lunaroute_routing::{
+        CandidateRole, HealthStatus, ProviderCandidate, RuleEvaluation, SkipReason,
+    };
+
+    #[test]
+    fn test_parse_header() {
+        assert_eq!(
+            parse_header("x-user-id: u1").unwrap(),
+            ("x-user-id".to_string(), "u1".to_string())
+        );
+        assert_eq!(
+            parse_header("x-user-id=u1").unwrap(),
+            ("x-user-id".to_string(), "u1".to_string())
+        );
+        assert!(parse_header("novalue").is_err());
+    }
+
+    #[test]
+    fn test_render_explanation() {
+        let mut explanation = RoutingExplanation::new("claude-3-haiku");
+        explanation.resolved_model = "claude-haiku-4-5".to_string();
+        explanation.rules.push(RuleEvaluation {
+            name: "claude-to-anthropic".to_string(),
+            priority: 10,
+            matcher: "model =~ /^claude-.*/".to_string(),
+            schedule: None,
+            outcome: RuleOutcome::Matched,
+        });
+        explanation.rules.push(RuleEvaluation {
+            name: "overnight-batch".to_string(),
+            priority: 20,
+            matcher: "model =~ /^claude-.*/".to_string(),
+            schedule: Some("22:00-06:00 UTC (inactive)".to_string()),
+            outcome: RuleOutcome::Inactive,
+        });
+        explanation.candidates.push(ProviderCandidate {
+            provider: "anthropic".to_string(),
+            role: CandidateRole::Primary,
+            model: "claude-haiku-4-5".to_string(),
+            circuit: None,
+            health: HealthStatus::Healthy,
+            skipped: Some(SkipReason::CircuitOpen),
+        });
+
+        let text = render(&explanation);
+        assert!(text.contains("claude-3-haiku → claude-haiku-4-5"));
+        assert!(text.contains("✓ matched"));
+        assert!(text.contains("◌ inactive"));
+        assert!(text.contains("@ 22:00-06:00 UTC (inactive)"));
+        assert!(text.contains("skipped: circuit open"));
+        assert!(text.contains("none (request would fail)"));
+    }
+}
@@ -7,8 +7,8 @@ use std::sync::Arc;
 
 use lunaroute_core::{
     Error, Result,
-    config_store::{ConfigChange, ConfigChangeStream, ConfigStore},
-    tenant::TenantId,
+    config_store::{ConfigChange, ConfigChangeStream, ConfigStore, ConfigVersion},
+    tenant::{TenantId, TenantInfo, TenantStatus, validate_tenant_name},
 };
 
 /// PostgreSQL-backed configuration store for multi-tenant mode
@@ -18,6 +18,7 @@ use lunaroute_core::{
 /// - Version tracking for optimistic concurrency
 /// - Audit history for all changes
 /// - LISTEN/NOTIFY for real-time updates
+/// - Tenant names for resolving tenants from subdomains and headers
 #[derive(Clone)]
 pub struct PostgresConfigStore {
     /// PostgreSQL connection pool
@@ -118,6 +119,21 @@ impl PostgresConfigStore {
         .await
         .map_err(|e| Error::Database(format!("Failed to create history index: {}", e)))?;
 
+        // Create tenants table (names used to resolve tenants from requests)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS tenants (
+                tenant_id UUID PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                status TEXT NOT NULL DEFAULT 'active',
+                created_at TIMESTAMPTZ DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Database(format!("Failed to create tenants table: {}", e)))?;
+
         Ok(())
     }
 
@@ -235,14 +251,8 @@ impl ConfigStore for PostgresConfigStore {
     }
 
     async fn watch_changes(&self, tenant_id: Option<TenantId>) -> Result<ConfigChangeStream<'_>> {
-        // Multi-tenant mode only - require tenant_id
-        let tenant_id = tenant_id.ok_or_else(|| {
-            Error::TenantRequired(
-                "PostgresConfigStore requires a tenant_id (multi-tenant mode only)".to_string(),
-            )
-        })?;
-
-        // Create a listener for PostgreSQL NOTIFY
+        // Create a listener for PostgreSQL NOTIFY; without a tenant_id it
+        // reports every tenant's changes over this one connection
         let mut listener = PgListener::connect_with(&self.pool)
             .await
             .map_err(|e| Error::Database(format!("Failed to create listener: {}", e)))?;
@@ -262,9 +272,11 @@ impl ConfigStore for PostgresConfigStore {
                             serde_json::from_str::<serde_json::Value>(notif.payload())
                         {
                             // Filter by tenant_id and check if it matches
-                            if let Some(notif_tenant_id) =
-                                payload.get("tenant_id").and_then(|v| v.as_str())
-                                && notif_tenant_id == tenant_id.to_string()
+                            if let Some(notif_tenant_id) = payload
+                                .get("tenant_id")
+                                .and_then(|v| v.as_str())
+                                .and_then(|s| TenantId::from_string(s).ok())
+                                && tenant_id.is_none_or(|id| id == notif_tenant_id)
                             {
                                 // Extract version and timestamp
                                 let version =
@@ -278,7 +290,7 @@ impl ConfigStore for PostgresConfigStore {
                                     .unwrap_or_else(chrono::Utc::now);
 
                                 return Some(Ok(ConfigChange {
-                                    tenant_id: Some(tenant_id),
+                                    tenant_id: Some(notif_tenant_id),
                                     timestamp,
                                     version,
                                 }));
@@ -312,10 +324,21 @@ impl ConfigStore for PostgresConfigStore {
     }
 
     async fn list_tenants(&self) -> Result<Vec<TenantId>> {
-        let rows = sqlx::query("SELECT tenant_id FROM tenant_configs ORDER BY created_at ASC")
-            .fetch_all(&*self.pool)
-            .await
-            .map_err(|e| Error::Database(format!("Failed to list tenants: {}", e)))?;
+        // Registered tenants, plus tenants that only have a configuration
+        let rows = sqlx::query(
+            r#"
+            SELECT tenant_id FROM (
+                SELECT tenant_id, created_at FROM tenants
+                UNION ALL
+                SELECT tenant_id, created_at FROM tenant_configs
+                WHERE tenant_id NOT IN (SELECT tenant_id FROM tenants)
+            ) AS all_tenants
+            ORDER BY created_at ASC
+            "#,
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Database(format!("Failed to list tenants: {}", e)))?;
 
         let tenant_ids = rows
             .into_iter()
@@ -329,6 +352,21 @@ impl ConfigStore for PostgresConfigStore {
         Ok(tenant_ids)
     }
 
+    async fn lookup_tenant(&self, name: &str) -> Result<Option<TenantId>> {
+        let row = sqlx::query("SELECT tenant_id FROM tenants WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(|e| Error::Database(format!("Failed to look up tenant: {}", e)))?;
+
+        row.map(|row| {
+            row.try_get::<uuid::Uuid, _>("tenant_id")
+                .map(TenantId::from_uuid)
+                .map_err(|e| Error::Database(format!("Failed to extract tenant_id: {}", e)))
+        })
+        .transpose()
+    }
+
     async fn delete_config(&self, tenant_id: TenantId) -> Result<()> {
         let result = sqlx::query("DELETE FROM tenant_configs WHERE tenant_id = $1")
             .bind(tenant_id.as_uuid())
@@ -345,6 +383,145 @@ impl ConfigStore for PostgresConfigStore {
 
         Ok(())
     }
+
+    async fn create_tenant(&self, name: &str) -> Result<TenantInfo> {
+        validate_tenant_name(name)?;
+
+        let tenant_id = TenantId::new();
+        let row = sqlx::query(
+            r#"
+            INSERT INTO tenants (tenant_id, name)
+            VALUES ($1, $2)
+            RETURNING status, created_at
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(name)
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(db) if db.is_unique_violation() => {
+                Error::TenantExists(format!("A tenant named '{}' already exists", name))
+            }
+            _ => Error::Database(format!("Failed to create tenant: {}", e)),
+        })?;
+
+        tenant_from_row(tenant_id, name.to_string(), &row)
+    }
+
+    async fn get_tenant(&self, tenant_id: TenantId) -> Result<Option<TenantInfo>> {
+        let row = sqlx::query("SELECT name, status, created_at FROM tenants WHERE tenant_id = $1")
+            .bind(tenant_id.as_uuid())
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query tenant: {}", e)))?;
+
+        row.map(|row| {
+            let name: String = row
+                .try_get("name")
+                .map_err(|e| Error::Database(format!("Failed to extract name: {}", e)))?;
+            tenant_from_row(tenant_id, name, &row)
+        })
+        .transpose()
+    }
+
+    async fn set_tenant_status(&self, tenant_id: TenantId, status: TenantStatus) -> Result<()> {
+        let result = sqlx::query("UPDATE tenants SET status = $2 WHERE tenant_id = $1")
+            .bind(tenant_id.as_uuid())
+            .bind(status.as_str())
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update tenant status: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::TenantNotFound(format!(
+                "Tenant not found: {}",
+                tenant_id
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn delete_tenant(&self, tenant_id: TenantId) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        // History rows go with the config (ON DELETE CASCADE)
+        let configs = sqlx::query("DELETE FROM tenant_configs WHERE tenant_id = $1")
+            .bind(tenant_id.as_uuid())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete config: {}", e)))?;
+        let tenants = sqlx::query("DELETE FROM tenants WHERE tenant_id = $1")
+            .bind(tenant_id.as_uuid())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete tenant: {}", e)))?;
+
+        if configs.rows_affected() == 0 && tenants.rows_affected() == 0 {
+            return Err(Error::TenantNotFound(format!(
+                "Tenant not found: {}",
+                tenant_id
+            )));
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))
+    }
+
+    async fn config_history(&self, tenant_id: TenantId) -> Result<Vec<ConfigVersion>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT version, config, changed_by, changed_at
+            FROM tenant_config_history
+            WHERE tenant_id = $1
+            ORDER BY version DESC
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Database(format!("Failed to query config history: {}", e)))?;
+
+        rows.iter()
+            .map(|row| {
+                let extract = |e: sqlx::Error| {
+                    Error::Database(format!("Failed to extract config history: {}", e))
+                };
+                Ok(ConfigVersion {
+                    version: row.try_get::<i32, _>("version").map_err(extract)? as u32,
+                    config: row.try_get("config").map_err(extract)?,
+                    changed_by: row.try_get("changed_by").map_err(extract)?,
+                    changed_at: row.try_get("changed_at").map_err(extract)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Build a `TenantInfo` from a row with `status` and `created_at` columns
+fn tenant_from_row(
+    tenant_id: TenantId,
+    name: String,
+    row: &sqlx::postgres::PgRow,
+) -> Result<TenantInfo> {
+    let status: String = row
+        .try_get("status")
+        .map_err(|e| Error::Database(format!("Failed to extract status: {}", e)))?;
+    let created_at = row
+        .try_get("created_at")
+        .map_err(|e| Error::Database(format!("Failed to extract created_at: {}", e)))?;
+    Ok(TenantInfo {
+        tenant_id,
+        name,
+        status: status.parse()?,
+        created_at,
+    })
 }
 
 #[cfg(test)]
@@ -435,6 +612,85 @@ mod tests {
         store.delete_config(tenant_id2).await.unwrap();
     }
 
+    #[tokio::test]
+    #[ignore] // Requires PostgreSQL instance
+    async fn test_lookup_tenant() {
+        let store = create_test_store().await.unwrap();
+        let tenant_id = TenantId::new();
+        let name = format!("tenant-{}", tenant_id);
+
+        sqlx::query("INSERT INTO tenants (tenant_id, name) VALUES ($1, $2)")
+            .bind(tenant_id.as_uuid())
+            .bind(&name)
+            .execute(store.pool())
+            .await
+            .unwrap();
+
+        assert_eq!(store.lookup_tenant(&name).await.unwrap(), Some(tenant_id));
+        assert_eq!(store.lookup_tenant("no-such-tenant").await.unwrap(), None);
+
+        // Cleanup
+        sqlx::query("DELETE FROM tenants WHERE tenant_id = $1")
+            .bind(tenant_id.as_uuid())
+            .execute(store.pool())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires PostgreSQL instance
+    async fn test_tenant_lifecycle() {
+        let store = create_test_store().await.unwrap();
+        let name = format!("t-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
+
+        let tenant = store.create_tenant(&name).await.unwrap();
+        assert_eq!(tenant.status, TenantStatus::Active);
+        assert!(matches!(
+            store.create_tenant(&name).await,
+            Err(Error::TenantExists(_))
+        ));
+        assert!(
+            store
+                .list_tenants()
+                .await
+                .unwrap()
+                .contains(&tenant.tenant_id)
+        );
+
+        store
+            .set_tenant_status(tenant.tenant_id, TenantStatus::Suspended)
+            .await
+            .unwrap();
+        let stored = store.get_tenant(tenant.tenant_id).await.unwrap().unwrap();
+        assert_eq!(stored.status, TenantStatus::Suspended);
+
+        for n in 1..=2 {
+            store
+                .update_config(Some(tenant.tenant_id), serde_json::json!({ "n": n }))
+                .await
+                .unwrap();
+        }
+        let history = store.config_history(tenant.tenant_id).await.unwrap();
+        assert_eq!(
+            history.iter().map(|v| v.version).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        store.delete_tenant(tenant.tenant_id).await.unwrap();
+        assert!(store.get_tenant(tenant.tenant_id).await.unwrap().is_none());
+        assert!(
+            store
+                .config_history(tenant.tenant_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            store.delete_tenant(tenant.tenant_id).await,
+            Err(Error::TenantNotFound(_))
+        ));
+    }
+
     #[tokio::test]
     #[ignore] // Requires PostgreSQL instance
     async fn test_delete_config() {
@@ -7,7 +7,10 @@
 use async_trait::async_trait;
 use futures::stream::BoxStream;
 
-use crate::{Error, Result, tenant::TenantId};
+use crate::{
+    Error, Result,
+    tenant::{TenantId, TenantInfo, TenantStatus},
+};
 
 /// Type alias for configuration change streams
 pub type ConfigChangeStream<'a> = BoxStream<'a, Result<ConfigChange>>;
@@ -25,6 +28,16 @@ pub struct ConfigChange {
     pub version: u32,
 }
 
+/// One stored version of a tenant's configuration
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ConfigVersion {
+    pub version: u32,
+    pub config: serde_json::Value,
+    /// Who made the change, when recorded
+    pub changed_by: Option<String>,
+    pub changed_at: chrono::DateTime<chrono::Utc>,
+}
+
 /// Configuration store trait
 ///
 /// Implementations:
@@ -84,7 +97,8 @@ pub trait ConfigStore: Send + Sync {
     /// The stream should emit whenever the configuration is updated.
     ///
     /// # Arguments
-    /// * `tenant_id` - Optional tenant ID (None for single-tenant mode)
+    /// * `tenant_id` - Optional tenant ID (None for single-tenant mode, or
+    ///   every tenant's changes in multi-tenant stores)
     ///
     /// # Implementation Notes
     /// - File-based: Use `notify` crate to watch file changes
@@ -110,6 +124,15 @@ pub trait ConfigStore: Send + Sync {
         Ok(Vec::new())
     }
 
+    /// Look up a tenant by name (multi-tenant only)
+    ///
+    /// Names are the human-readable handles used in subdomains and headers.
+    /// Returns `None` when no tenant has the name, and always in
+    /// single-tenant mode.
+    async fn lookup_tenant(&self, _name: &str) -> Result<Option<TenantId>> {
+        Ok(None)
+    }
+
     /// Delete configuration for a tenant (multi-tenant only)
     ///
     /// # Arguments
@@ -123,6 +146,55 @@ pub trait ConfigStore: Send + Sync {
             "Delete not supported in single-tenant mode".to_string(),
         ))
     }
+
+    /// Register a tenant under a name (multi-tenant only)
+    ///
+    /// The tenant starts active and without configuration.
+    ///
+    /// # Errors
+    /// - `Error::InvalidTenant` if the name is not valid
+    /// - `Error::TenantExists` if the name is taken
+    /// - `Error::Database` for database errors
+    async fn create_tenant(&self, _name: &str) -> Result<TenantInfo> {
+        Err(Error::Internal(
+            "Tenants not supported in single-tenant mode".to_string(),
+        ))
+    }
+
+    /// Get a registered tenant (multi-tenant only)
+    ///
+    /// Returns `None` for unregistered tenants, including tenants that only
+    /// have a configuration, and always in single-tenant mode.
+    async fn get_tenant(&self, _tenant_id: TenantId) -> Result<Option<TenantInfo>> {
+        Ok(None)
+    }
+
+    /// Suspend or reactivate a tenant (multi-tenant only)
+    ///
+    /// # Errors
+    /// - `Error::TenantNotFound` if the tenant is not registered
+    /// - `Error::Database` for database errors
+    async fn set_tenant_status(&self, _tenant_id: TenantId, _status: TenantStatus) -> Result<()> {
+        Err(Error::Internal(
+            "Tenants not supported in single-tenant mode".to_string(),
+        ))
+    }
+
+    /// Delete a tenant with its configuration and history (multi-tenant only)
+    ///
+    /// # Errors
+    /// - `Error::TenantNotFound` if tenant doesn't exist
+    /// - `Error::Database` for database errors
+    async fn delete_tenant(&self, tenant_id: TenantId) -> Result<()> {
+        self.delete_config(tenant_id).await
+    }
+
+    /// Stored versions of a tenant's configuration, newest first
+    ///
+    /// Returns empty vec in single-tenant mode.
+    async fn config_history(&self, _tenant_id: TenantId) -> Result<Vec<ConfigVersion>> {
+        Ok(Vec::new())
+    }
 }
 
 #[cfg(test)]
@@ -29,6 +29,12 @@ pub enum Error {
     #[error("Tenant not found: {0}")]
     TenantNotFound(String),
 
+    #[error("Tenant already exists: {0}")]
+    TenantExists(String),
+
+    #[error("Tenant suspended: {0}")]
+    TenantSuspended(String),
+
     // Configuration errors
     #[error("Configuration error: {0}")]
     Config(String),
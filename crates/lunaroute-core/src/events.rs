@@ -104,6 +104,9 @@ pub enum SessionEvent {
         has_refusal: bool,
         /// User agent of the client that made the request
         user_agent: Option<String>,
+        /// Hashed id of the upstream API key that served the request
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        upstream_key: Option<String>,
     },
 }
 
@@ -144,6 +147,15 @@ pub struct SessionMetadata {
     pub request_headers: HashMap<String, String>,
     #[serde(default)]
     pub session_tags: Vec<String>,
+    /// A/B experiment the request was enrolled in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub experiment: Option<String>,
+    /// Arm of `experiment` the request was assigned to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub experiment_arm: Option<String>,
+    /// Authenticated caller (e.g. the TLS client certificate subject)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_identity: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
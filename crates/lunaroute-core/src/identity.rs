@@ -0,0 +1,165 @@
+//! Caller identity established at ingress
+//!
+//! A [`ClientIdentity`] describes who is calling, independent of any API
+//! key: the subject of a verified TLS client certificate or the claims of a
+//! validated JWT. Ingress inserts it as a request extension; normalized
+//! requests carry it in their metadata under [`IDENTITY_METADATA_KEY`] so the
+//! router and session recording see it too.
+//!
+//! An API key's [`RequestScope`] travels the same way, under
+//! [`SCOPE_METADATA_KEY`], so the router only sends the request to providers
+//! and models the key may use.
+
+use crate::normalized::NormalizedRequest;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key a normalized request's identity is stored under
+pub const IDENTITY_METADATA_KEY: &str = "lunaroute_identity";
+
+/// Metadata key a normalized request's scope is stored under
+pub const SCOPE_METADATA_KEY: &str = "lunaroute_scope";
+
+/// How a [`ClientIdentity`] was established
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentitySource {
+    /// Verified TLS client certificate
+    ClientCert,
+    /// Validated bearer JWT
+    Jwt,
+}
+
+impl IdentitySource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IdentitySource::ClientCert => "cert",
+            IdentitySource::Jwt => "jwt",
+        }
+    }
+}
+
+/// Who is calling
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientIdentity {
+    /// Stable name of the caller (certificate common name or JWT subject)
+    pub subject: String,
+    pub source: IdentitySource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+    /// Tenant the caller belongs to, when the identity provider says so
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+impl ClientIdentity {
+    /// Identity taken from a verified client certificate
+    pub fn client_cert(subject: impl Into<String>) -> Self {
+        Self::new(subject, IdentitySource::ClientCert)
+    }
+
+    /// Identity taken from a validated JWT
+    pub fn jwt(subject: impl Into<String>) -> Self {
+        Self::new(subject, IdentitySource::Jwt)
+    }
+
+    fn new(subject: impl Into<String>, source: IdentitySource) -> Self {
+        Self {
+            subject: subject.into(),
+            source,
+            email: None,
+            groups: Vec::new(),
+            tenant: None,
+        }
+    }
+
+    /// Whether the caller is a member of `group`
+    pub fn in_group(&self, group: &str) -> bool {
+        self.groups.iter().any(|g| g == group)
+    }
+
+    /// Identity attached to a normalized request, if any
+    pub fn from_request(request: &NormalizedRequest) -> Option<Self> {
+        request
+            .metadata
+            .get(IDENTITY_METADATA_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Attach the identity to a normalized request's metadata
+    pub fn attach(&self, request: &mut NormalizedRequest) {
+        if let Ok(value) = serde_json::to_value(self) {
+            request
+                .metadata
+                .insert(IDENTITY_METADATA_KEY.to_string(), value);
+        }
+    }
+}
+
+/// Providers and models a request may be sent to (empty lists allow everything)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestScope {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<String>,
+    /// Model name patterns; `*` matches any run of characters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+}
+
+impl RequestScope {
+    /// Whether the scope lets the request go to `provider`
+    pub fn allows_provider(&self, provider: &str) -> bool {
+        self.providers.is_empty() || self.providers.iter().any(|p| p == provider)
+    }
+
+    /// Whether the scope lets the request use `model`
+    pub fn allows_model(&self, model: &str) -> bool {
+        self.models.is_empty() || self.models.iter().any(|p| wildcard_match(p, model))
+    }
+
+    /// Whether the scope restricts anything
+    pub fn is_unrestricted(&self) -> bool {
+        self.providers.is_empty() && self.models.is_empty()
+    }
+
+    /// Scope attached to a normalized request (unrestricted if none)
+    pub fn from_request(request: &NormalizedRequest) -> Self {
+        request
+            .metadata
+            .get(SCOPE_METADATA_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Attach the scope to a normalized request's metadata
+    pub fn attach(&self, request: &mut NormalizedRequest) {
+        if self.is_unrestricted() {
+            return;
+        }
+        if let Ok(value) = serde_json::to_value(self) {
+            request
+                .metadata
+                .insert(SCOPE_METADATA_KEY.to_string(), value);
+        }
+    }
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match remaining.find(part) {
+            Some(at) => remaining = &remaining[at + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
@@ -12,8 +12,12 @@
 //! ## Existing Core Types
 //! - [`normalized`]: Normalized request/response types
 //! - [`provider`]: Provider trait abstractions
+//! - [`quota`]: Upstream quota parsed from rate-limit headers
+//! - [`secret_source`]: Provider credentials from a command or file
+//! - [`identity`]: Caller identity from client certificates or JWTs
 //! - [`error`]: Core error types
 //! - [`template`]: Template engine for variable substitution
+//! - [`tls`]: TLS listener settings
 //!
 //! # Multi-Tenancy Architecture
 //!
@@ -56,9 +60,13 @@ pub mod types;
 
 // Existing modules
 pub mod error;
+pub mod identity;
 pub mod normalized;
 pub mod provider;
+pub mod quota;
+pub mod secret_source;
 pub mod template;
+pub mod tls;
 
 // Re-exports
 pub use config_store::ConfigStore;
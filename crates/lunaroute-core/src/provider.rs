@@ -3,6 +3,7 @@
 use crate::{
     Result,
     normalized::{NormalizedRequest, NormalizedResponse, NormalizedStreamEvent},
+    quota::ProviderQuota,
 };
 use futures::Stream;
 
@@ -24,6 +25,20 @@ pub trait Provider: Send + Sync {
     fn get_notification_message(&self) -> Option<&str> {
         None // Default implementation
     }
+
+    /// Cheap upstream availability check, such as listing models
+    ///
+    /// Returns None if the provider has no such check (e.g. it has no
+    /// credentials of its own); active health probes then skip it unless a
+    /// probe model is configured.
+    async fn health_check(&self) -> Option<Result<()>> {
+        None
+    }
+
+    /// Latest upstream quota reported in rate-limit response headers
+    fn quota(&self) -> Option<ProviderQuota> {
+        None
+    }
 }
 
 #[derive(Debug, Clone)]
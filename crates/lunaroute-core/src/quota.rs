@@ -0,0 +1,407 @@
+//! Upstream quota from rate-limit response headers
+//!
+//! Anthropic and OpenAI report the remaining budget of every rate limit on
+//! each response:
+//!
+//! - Anthropic: `anthropic-ratelimit-{requests,tokens,input-tokens,output-tokens}-{limit,remaining,reset}`
+//!   (reset is an RFC 3339 timestamp)
+//! - OpenAI: `x-ratelimit-{limit,remaining,reset}-{requests,tokens}`
+//!   (reset is a duration such as `6m0s` or `20ms`)
+//!
+//! [`ProviderQuota::from_headers`] parses either family; [`QuotaTracker`]
+//! keeps the latest snapshot per provider so routing can divert traffic
+//! before a 429.
+
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a window without a reset time is trusted
+const UNTIMED_WINDOW_TTL: Duration = Duration::from_secs(60);
+
+/// Kind of rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaKind {
+    /// Requests per window
+    Requests,
+    /// Combined tokens per window (OpenAI, older Anthropic limits)
+    Tokens,
+    /// Input tokens per window
+    InputTokens,
+    /// Output tokens per window
+    OutputTokens,
+}
+
+impl QuotaKind {
+    /// All kinds, in reporting order
+    pub const ALL: [QuotaKind; 4] = [
+        QuotaKind::Requests,
+        QuotaKind::Tokens,
+        QuotaKind::InputTokens,
+        QuotaKind::OutputTokens,
+    ];
+
+    /// Label used in metrics and logs
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QuotaKind::Requests => "requests",
+            QuotaKind::Tokens => "tokens",
+            QuotaKind::InputTokens => "input_tokens",
+            QuotaKind::OutputTokens => "output_tokens",
+        }
+    }
+
+    fn anthropic_name(self) -> &'static str {
+        match self {
+            QuotaKind::Requests => "requests",
+            QuotaKind::Tokens => "tokens",
+            QuotaKind::InputTokens => "input-tokens",
+            QuotaKind::OutputTokens => "output-tokens",
+        }
+    }
+}
+
+/// One rate-limit window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaWindow {
+    /// Window size, if reported
+    pub limit: Option<u64>,
+    /// Budget left in the current window
+    pub remaining: u64,
+    /// Unix time (ms) the window resets, if reported
+    pub reset_at_ms: Option<u64>,
+}
+
+impl QuotaWindow {
+    /// Whether the window has reset (or is too old to trust) at `now_ms`
+    pub fn is_stale(&self, observed_at_ms: u64, now_ms: u64) -> bool {
+        match self.reset_at_ms {
+            Some(reset) => reset <= now_ms,
+            None => now_ms >= observed_at_ms + UNTIMED_WINDOW_TTL.as_millis() as u64,
+        }
+    }
+
+    /// Whether the remaining budget is within `headroom_percent` of the limit
+    /// (or exhausted, when the limit is unknown)
+    pub fn is_low(&self, headroom_percent: u8) -> bool {
+        match self.limit {
+            Some(limit) if limit > 0 => {
+                self.remaining == 0
+                    || (self.remaining as u128) * 100 < (limit as u128) * headroom_percent as u128
+            }
+            _ => self.remaining == 0,
+        }
+    }
+}
+
+/// Remaining upstream quota reported by one response
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderQuota {
+    /// Request window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests: Option<QuotaWindow>,
+    /// Combined token window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<QuotaWindow>,
+    /// Input token window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<QuotaWindow>,
+    /// Output token window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<QuotaWindow>,
+    /// Unix time (ms) the headers were seen
+    pub observed_at_ms: u64,
+}
+
+impl ProviderQuota {
+    /// Parse Anthropic or OpenAI rate-limit headers
+    ///
+    /// Header names are matched case-insensitively. Returns None when the
+    /// response carried no rate-limit headers.
+    pub fn from_headers<'a>(headers: impl IntoIterator<Item = (&'a str, &'a str)>) -> Option<Self> {
+        let now = now_ms();
+        let mut quota = ProviderQuota {
+            observed_at_ms: now,
+            ..Default::default()
+        };
+        let mut limits = [None; 4];
+        let mut resets = [None; 4];
+
+        for (name, value) in headers {
+            let name = name.to_ascii_lowercase();
+            let value = value.trim();
+            let Some((kind, field, anthropic)) = classify_header(&name) else {
+                continue;
+            };
+            let i = kind as usize;
+            match field {
+                "remaining" => {
+                    if let Ok(remaining) = value.parse() {
+                        quota
+                            .window_mut(kind)
+                            .get_or_insert(QuotaWindow {
+                                limit: None,
+                                remaining: 0,
+                                reset_at_ms: None,
+                            })
+                            .remaining = remaining;
+                    }
+                }
+                "limit" => limits[i] = value.parse().ok(),
+                _ if anthropic => {
+                    resets[i] = chrono::DateTime::parse_from_rfc3339(value)
+                        .ok()
+                        .map(|t| t.timestamp_millis().max(0) as u64);
+                }
+                _ => resets[i] = parse_reset_duration(value).map(|d| now + d.as_millis() as u64),
+            }
+        }
+
+        for kind in QuotaKind::ALL {
+            let i = kind as usize;
+            if let Some(window) = quota.window_mut(kind) {
+                window.limit = limits[i];
+                window.reset_at_ms = resets[i];
+            }
+        }
+        let reported = quota.windows().next().is_some();
+        reported.then_some(quota)
+    }
+
+    /// Reported windows
+    pub fn windows(&self) -> impl Iterator<Item = (QuotaKind, &QuotaWindow)> {
+        QuotaKind::ALL
+            .into_iter()
+            .filter_map(|kind| self.window(kind).map(|w| (kind, w)))
+    }
+
+    /// Window of one kind
+    pub fn window(&self, kind: QuotaKind) -> Option<&QuotaWindow> {
+        match kind {
+            QuotaKind::Requests => self.requests.as_ref(),
+            QuotaKind::Tokens => self.tokens.as_ref(),
+            QuotaKind::InputTokens => self.input_tokens.as_ref(),
+            QuotaKind::OutputTokens => self.output_tokens.as_ref(),
+        }
+    }
+
+    fn window_mut(&mut self, kind: QuotaKind) -> &mut Option<QuotaWindow> {
+        match kind {
+            QuotaKind::Requests => &mut self.requests,
+            QuotaKind::Tokens => &mut self.tokens,
+            QuotaKind::InputTokens => &mut self.input_tokens,
+            QuotaKind::OutputTokens => &mut self.output_tokens,
+        }
+    }
+
+    /// Time until every exhausted window (remaining 0) has reset, if any
+    /// window is exhausted and all of them report a reset time
+    pub fn exhausted_for(&self) -> Option<Duration> {
+        let now = now_ms();
+        let mut resets = self
+            .windows()
+            .filter(|(_, w)| w.remaining == 0 && !w.is_stale(self.observed_at_ms, now))
+            .map(|(_, w)| w.reset_at_ms)
+            .peekable();
+        resets.peek()?;
+        resets
+            .try_fold(0, |latest, reset| reset.map(|r| latest.max(r)))
+            .map(|reset| Duration::from_millis(reset.saturating_sub(now)))
+    }
+
+    /// First window that is still current and within `headroom_percent` of
+    /// its limit
+    pub fn low_window(&self, headroom_percent: u8) -> Option<QuotaKind> {
+        let now = now_ms();
+        self.windows()
+            .find(|(_, w)| !w.is_stale(self.observed_at_ms, now) && w.is_low(headroom_percent))
+            .map(|(kind, _)| kind)
+    }
+}
+
+/// Latest quota seen for one provider
+#[derive(Debug, Default)]
+pub struct QuotaTracker {
+    latest: RwLock<Option<ProviderQuota>>,
+}
+
+impl QuotaTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the quota reported by a response's headers (ignored if the
+    /// response had none)
+    pub fn observe<'a>(&self, headers: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        if let Some(quota) = ProviderQuota::from_headers(headers)
+            && let Ok(mut latest) = self.latest.write()
+        {
+            *latest = Some(quota);
+        }
+    }
+
+    /// Latest quota, if any
+    pub fn get(&self) -> Option<ProviderQuota> {
+        self.latest.read().ok().and_then(|latest| latest.clone())
+    }
+}
+
+/// Split a rate-limit header name into (kind, field, is_anthropic)
+fn classify_header(name: &str) -> Option<(QuotaKind, &str, bool)> {
+    if let Some(rest) = name.strip_prefix("anthropic-ratelimit-") {
+        let (kind, field) = rest.rsplit_once('-')?;
+        let kind = QuotaKind::ALL
+            .into_iter()
+            .find(|k| k.anthropic_name() == kind)?;
+        return matches!(field, "limit" | "remaining" | "reset").then_some((kind, field, true));
+    }
+    let (field, kind) = name.strip_prefix("x-ratelimit-")?.split_once('-')?;
+    let kind = match kind {
+        "requests" => QuotaKind::Requests,
+        "tokens" => QuotaKind::Tokens,
+        _ => return None,
+    };
+    matches!(field, "limit" | "remaining" | "reset").then_some((kind, field, false))
+}
+
+/// Parse an OpenAI reset duration such as `1s`, `6m0s`, `1h2m3.5s` or `20ms`
+pub fn parse_reset_duration(value: &str) -> Option<Duration> {
+    let mut total = 0f64;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let (unit_secs, len) = if rest.starts_with("ms") {
+            (0.001, 2)
+        } else if rest.starts_with('h') {
+            (3600.0, 1)
+        } else if rest.starts_with('m') {
+            (60.0, 1)
+        } else if rest.starts_with('s') {
+            (1.0, 1)
+        } else {
+            return None;
+        };
+        total += number * unit_secs;
+        rest = &rest[len..];
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_anthropic_headers() {
+        let reset = (chrono::Utc::now() + chrono::Duration::seconds(30)).to_rfc3339();
+        let headers = vec![
+            ("anthropic-ratelimit-requests-limit", "50"),
+            ("anthropic-ratelimit-requests-remaining", "49"),
+            ("anthropic-ratelimit-requests-reset", reset.as_str()),
+            ("Anthropic-RateLimit-Input-Tokens-Limit", "40000"),
+            ("anthropic-ratelimit-input-tokens-remaining", "1000"),
+            ("anthropic-ratelimit-output-tokens-remaining", "8000"),
+            ("content-type", "application/json"),
+        ];
+        let quota = ProviderQuota::from_headers(headers).unwrap();
+
+        let requests = quota.requests.unwrap();
+        assert_eq!(requests.limit, Some(50));
+        assert_eq!(requests.remaining, 49);
+        assert!(requests.reset_at_ms.unwrap() > now_ms());
+        assert_eq!(quota.input_tokens.unwrap().remaining, 1000);
+        assert_eq!(quota.output_tokens.unwrap().limit, None);
+        assert!(quota.tokens.is_none());
+
+        // 1000 of 40000 input tokens is 2.5%
+        assert_eq!(quota.low_window(5), Some(QuotaKind::InputTokens));
+        assert_eq!(quota.low_window(2), None);
+    }
+
+    #[test]
+    fn test_parse_openai_headers() {
+        let headers = vec![
+            ("x-ratelimit-limit-requests", "10000"),
+            ("x-ratelimit-remaining-requests", "0"),
+            ("x-ratelimit-reset-requests", "6m0s"),
+            ("x-ratelimit-limit-tokens", "2000000"),
+            ("x-ratelimit-remaining-tokens", "1999000"),
+            ("x-ratelimit-reset-tokens", "30ms"),
+        ];
+        let quota = ProviderQuota::from_headers(headers).unwrap();
+        let requests = quota.requests.unwrap();
+        assert_eq!(requests.remaining, 0);
+        assert!(requests.reset_at_ms.unwrap() >= quota.observed_at_ms + 360_000);
+        assert_eq!(quota.tokens.unwrap().limit, Some(2_000_000));
+        assert_eq!(quota.low_window(0), Some(QuotaKind::Requests));
+        let exhausted = quota.exhausted_for().unwrap();
+        assert!(exhausted > Duration::from_secs(350) && exhausted <= Duration::from_secs(360));
+    }
+
+    #[test]
+    fn test_no_rate_limit_headers() {
+        assert!(ProviderQuota::from_headers(vec![("content-type", "text/plain")]).is_none());
+
+        let tracker = QuotaTracker::new();
+        tracker.observe(vec![("x-ratelimit-remaining-requests", "5")]);
+        tracker.observe(vec![("content-type", "text/plain")]);
+        assert_eq!(tracker.get().unwrap().requests.unwrap().remaining, 5);
+    }
+
+    #[test]
+    fn test_stale_windows_are_ignored() {
+        let window = QuotaWindow {
+            limit: Some(100),
+            remaining: 0,
+            reset_at_ms: Some(1_000),
+        };
+        assert!(window.is_stale(0, 1_000));
+        assert!(!window.is_stale(0, 999));
+
+        let untimed = QuotaWindow {
+            reset_at_ms: None,
+            ..window
+        };
+        assert!(!untimed.is_stale(0, 59_999));
+        assert!(untimed.is_stale(0, 60_000));
+
+        let quota = ProviderQuota {
+            requests: Some(window),
+            observed_at_ms: 0,
+            ..Default::default()
+        };
+        assert_eq!(quota.low_window(5), None);
+    }
+
+    #[test]
+    fn test_parse_reset_duration() {
+        assert_eq!(parse_reset_duration("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_reset_duration("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(
+            parse_reset_duration("20ms"),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            parse_reset_duration("1h2m3.5s"),
+            Some(Duration::from_millis(3_723_500))
+        );
+        assert_eq!(parse_reset_duration(""), None);
+        assert_eq!(parse_reset_duration("soon"), None);
+    }
+}
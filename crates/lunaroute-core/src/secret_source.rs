@@ -0,0 +1,374 @@
+//! Provider credentials obtained from a command or a file
+//!
+//! Instead of a literal key, provider config can name a command to run (for
+//! example a password-manager CLI) or a file to read. [`SecretSource`] caches
+//! the result for a TTL, can be invalidated after the upstream rejects the
+//! key with a 401, and keeps the secret out of `Debug` output and errors.
+//! When a refresh fails after the TTL expired, the previous value is kept
+//! rather than dropping the provider's credentials.
+//!
+//! Fetching is async (the command runs under `tokio::process`) and
+//! single-flight: when the TTL expires under load, one caller runs the
+//! command and the others wait for its result.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+/// How long a fetched secret is reused before running the source again
+pub const DEFAULT_SECRET_TTL: Duration = Duration::from_secs(300);
+
+/// How long a secret command may run before it is killed
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest stderr excerpt kept in a command error
+const MAX_STDERR_CHARS: usize = 200;
+
+/// Errors obtaining a secret; never contain the secret itself
+#[derive(Debug, Error)]
+pub enum SecretSourceError {
+    #[error("Failed to run secret command `{program}`: {source}")]
+    Spawn {
+        program: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Secret command `{program}` timed out after {secs}s")]
+    Timeout { program: String, secs: u64 },
+
+    #[error("Secret command `{program}` exited with {status}: {stderr}")]
+    Failed {
+        program: String,
+        status: String,
+        stderr: String,
+    },
+
+    #[error("Failed to read secret file {path}: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Secret source {0} produced an empty value")]
+    Empty(String),
+}
+
+#[derive(Clone)]
+enum SourceKind {
+    /// Shell command whose trimmed stdout is the secret
+    Command(String),
+    /// File whose trimmed contents are the secret
+    File(PathBuf),
+}
+
+#[derive(Default)]
+struct Cached {
+    value: Option<String>,
+    fetched_at: Option<Instant>,
+}
+
+/// A secret obtained from a command or file, cached for a TTL
+pub struct SecretSource {
+    kind: SourceKind,
+    ttl: Duration,
+    timeout: Duration,
+    cache: RwLock<Cached>,
+    /// Held while fetching, so concurrent callers don't all run the source
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+impl std::fmt::Debug for SecretSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretSource")
+            .field("source", &self.describe())
+            .field("ttl", &self.ttl)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecretSource {
+    /// Run `command` through the shell; its stdout is the secret
+    pub fn command(command: impl Into<String>) -> Self {
+        Self::with_kind(SourceKind::Command(command.into()))
+    }
+
+    /// Read the secret from `path` (a leading `~/` is expanded)
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self::with_kind(SourceKind::File(path.into()))
+    }
+
+    fn with_kind(kind: SourceKind) -> Self {
+        Self {
+            kind,
+            ttl: DEFAULT_SECRET_TTL,
+            timeout: DEFAULT_COMMAND_TIMEOUT,
+            cache: RwLock::new(Cached::default()),
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Set how long a fetched secret is reused
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set how long the command may run
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Loggable description: the command's program name (arguments may carry
+    /// tokens) or the file path
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            SourceKind::Command(command) => format!("command `{}`", program_name(command)),
+            SourceKind::File(path) => format!("file {}", path.display()),
+        }
+    }
+
+    /// Last fetched secret, even if its TTL expired (None before the first fetch)
+    pub fn cached(&self) -> Option<String> {
+        self.cache
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .value
+            .clone()
+    }
+
+    fn fresh_cached(&self) -> Option<String> {
+        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        match (&cache.value, cache.fetched_at) {
+            (Some(value), Some(at)) if at.elapsed() < self.ttl => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /// Current secret, fetching it when the cache is empty or expired
+    pub async fn get(&self) -> Result<String, SecretSourceError> {
+        if let Some(value) = self.fresh_cached() {
+            return Ok(value);
+        }
+        let _guard = self.refreshing.lock().await;
+        // Another caller may have refreshed while we waited
+        if let Some(value) = self.fresh_cached() {
+            return Ok(value);
+        }
+
+        match self.fetch().await {
+            Ok(value) => {
+                let mut cache = self.cache.write().unwrap_or_else(|e| e.into_inner());
+                cache.value = Some(value.clone());
+                cache.fetched_at = Some(Instant::now());
+                debug!("Refreshed secret from {}", self.describe());
+                Ok(value)
+            }
+            Err(e) => {
+                let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+                match &cache.value {
+                    Some(stale) => {
+                        warn!("{}; keeping the previous secret", e);
+                        Ok(stale.clone())
+                    }
+                    None => Err(e),
+                }
+            }
+        }
+    }
+
+    /// Drop the cached secret so the next [`get`](Self::get) fetches a new one
+    /// (used after upstream rejects the key)
+    pub fn invalidate(&self) {
+        *self.cache.write().unwrap_or_else(|e| e.into_inner()) = Cached::default();
+    }
+
+    /// Fetch the secret, bypassing the cache
+    pub async fn fetch(&self) -> Result<String, SecretSourceError> {
+        let value = match &self.kind {
+            SourceKind::Command(command) => run_command(command, self.timeout).await?,
+            SourceKind::File(path) => {
+                let path = expand_tilde(path);
+                tokio::fs::read_to_string(&path)
+                    .await
+                    .map_err(|source| SecretSourceError::Read { path, source })?
+            }
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(SecretSourceError::Empty(self.describe()));
+        }
+        Ok(value.to_string())
+    }
+}
+
+fn program_name(command: &str) -> &str {
+    command.split_whitespace().next().unwrap_or("")
+}
+
+fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn shell(command: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// Run `command`, returning stdout; the child is killed after `timeout`
+async fn run_command(command: &str, timeout: Duration) -> Result<String, SecretSourceError> {
+    let program = program_name(command).to_string();
+    let child = shell(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|source| SecretSourceError::Spawn {
+            program: program.clone(),
+            source,
+        })?;
+
+    // Dropping the child on timeout kills it
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => output.map_err(|source| SecretSourceError::Spawn {
+            program: program.clone(),
+            source,
+        })?,
+        Err(_) => {
+            return Err(SecretSourceError::Timeout {
+                program,
+                secs: timeout.as_secs(),
+            });
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(SecretSourceError::Failed {
+            program,
+            status: output.status.to_string(),
+            stderr: stderr.trim().chars().take(MAX_STDERR_CHARS).collect(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_command_source_trims_output() {
+        let source = SecretSource::command("printf '  sk-from-cmd\\n'");
+        assert_eq!(source.get().await.unwrap(), "sk-from-cmd");
+    }
+
+    #[tokio::test]
+    async fn test_file_source_and_cache() {
+        let dir = std::env::temp_dir().join(format!("lunaroute-secret-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("key");
+        std::fs::write(&path, "sk-one\n").unwrap();
+
+        let source = SecretSource::file(&path);
+        assert_eq!(source.get().await.unwrap(), "sk-one");
+
+        // Cached until invalidated (or the TTL expires)
+        std::fs::write(&path, "sk-two\n").unwrap();
+        assert_eq!(source.get().await.unwrap(), "sk-one");
+        source.invalidate();
+        assert_eq!(source.get().await.unwrap(), "sk-two");
+
+        let expiring = SecretSource::file(&path).with_ttl(Duration::ZERO);
+        assert_eq!(expiring.get().await.unwrap(), "sk-two");
+        std::fs::write(&path, "sk-three").unwrap();
+        assert_eq!(expiring.get().await.unwrap(), "sk-three");
+
+        // A failed refresh keeps the previous value
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(expiring.get().await.unwrap(), "sk-three");
+        expiring.invalidate();
+        assert!(matches!(
+            expiring.get().await,
+            Err(SecretSourceError::Read { .. })
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_refresh_runs_command_once() {
+        let dir = std::env::temp_dir().join(format!("lunaroute-secret-sf-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let runs = dir.join("runs");
+        let source = std::sync::Arc::new(SecretSource::command(format!(
+            "echo run >> {}; sleep 0.2; printf sk-shared",
+            runs.display()
+        )));
+
+        let tasks: Vec<_> = (0..5)
+            .map(|_| {
+                let source = source.clone();
+                tokio::spawn(async move { source.get().await.unwrap() })
+            })
+            .collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), "sk-shared");
+        }
+        assert_eq!(std::fs::read_to_string(&runs).unwrap().lines().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_command_timeout_kills_child() {
+        let source = SecretSource::command("sleep 5").with_timeout(Duration::from_millis(100));
+        let started = Instant::now();
+        assert!(matches!(
+            source.get().await,
+            Err(SecretSourceError::Timeout { .. })
+        ));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_errors_and_debug_hide_secrets() {
+        let source = SecretSource::command("echo sk-leak; echo oops >&2; exit 3");
+        let err = source.get().await.unwrap_err().to_string();
+        assert!(err.contains("`echo`"));
+        assert!(err.contains("oops"));
+        assert!(!err.contains("sk-leak"));
+
+        let source = SecretSource::command("op read op://vault/item --token tok-123");
+        let debug = format!("{:?}", source);
+        assert!(debug.contains("`op`"));
+        assert!(!debug.contains("tok-123"));
+
+        let empty = SecretSource::command("true");
+        assert!(matches!(
+            empty.get().await,
+            Err(SecretSourceError::Empty(_))
+        ));
+    }
+}
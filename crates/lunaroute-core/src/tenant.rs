@@ -6,8 +6,14 @@ use std::str::FromStr;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::config_store::ConfigStore;
+use crate::normalized::NormalizedRequest;
+use crate::session_store::SessionStore;
 use crate::{Error, Result};
 
+/// Metadata key a normalized request's tenant is stored under
+pub const TENANT_METADATA_KEY: &str = "lunaroute_tenant";
+
 /// Unique identifier for a tenant in multi-tenant deployments.
 ///
 /// In single-tenant mode, this is `None` in `TenantContext`.
@@ -38,19 +44,80 @@ impl TenantId {
         Ok(Self(uuid))
     }
 
-    /// Create a tenant ID from a subdomain
+    /// Resolve a tenant reference: a tenant ID, or a tenant name looked up
+    /// in the config store
+    ///
+    /// # Errors
+    /// - `Error::TenantNotFound` if no tenant has this name
+    pub async fn resolve(reference: &str, store: &dyn ConfigStore) -> Result<Self> {
+        if let Ok(uuid) = Uuid::parse_str(reference) {
+            return Ok(Self(uuid));
+        }
+        store
+            .lookup_tenant(reference)
+            .await?
+            .ok_or_else(|| Error::TenantNotFound(format!("No tenant named '{}'", reference)))
+    }
+
+    /// Resolve the tenant a request host belongs to
     ///
-    /// This performs a lookup from subdomain to tenant ID.
-    /// In production, this would query a cache or database.
-    pub fn from_subdomain(_subdomain: &str) -> Result<Self> {
-        // TODO: Implement actual subdomain -> tenant_id mapping
-        // For now, return an error
-        Err(Error::InvalidTenant(
-            "Subdomain mapping not implemented".to_string(),
-        ))
+    /// `host` (an optional port is ignored) must be a direct subdomain of
+    /// `base_domain`: with base domain `lunaroute.example.com`, the host
+    /// `acme.lunaroute.example.com` resolves the tenant named `acme`.
+    ///
+    /// # Errors
+    /// - `Error::InvalidTenant` if the host is not a subdomain of `base_domain`
+    /// - `Error::TenantNotFound` if no tenant has the subdomain's name
+    pub async fn from_subdomain(
+        host: &str,
+        base_domain: &str,
+        store: &dyn ConfigStore,
+    ) -> Result<Self> {
+        let subdomain = subdomain_of(host, base_domain).ok_or_else(|| {
+            Error::InvalidTenant(format!(
+                "Host '{}' is not a subdomain of '{}'",
+                host, base_domain
+            ))
+        })?;
+        Self::resolve(subdomain, store).await
+    }
+
+    /// Tenant attached to a normalized request, if any
+    pub fn from_request(request: &NormalizedRequest) -> Option<Self> {
+        request
+            .metadata
+            .get(TENANT_METADATA_KEY)
+            .and_then(|value| value.as_str())
+            .and_then(|s| Self::from_string(s).ok())
+    }
+
+    /// Attach the tenant to a normalized request's metadata
+    pub fn attach(&self, request: &mut NormalizedRequest) {
+        request.metadata.insert(
+            TENANT_METADATA_KEY.to_string(),
+            serde_json::Value::String(self.to_string()),
+        );
     }
 }
 
+/// The single leading label of `host` below `base_domain`
+///
+/// Matching is case-insensitive and ignores a port on `host`. Nested
+/// subdomains (`a.b.base`) and the base domain itself yield `None`.
+pub fn subdomain_of<'a>(host: &'a str, base_domain: &str) -> Option<&'a str> {
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    let base_domain = base_domain.trim_matches('.');
+    let prefix_len = host.len().checked_sub(base_domain.len() + 1)?;
+    let (label, rest) = host.split_at(prefix_len);
+    if !rest.starts_with('.') || !rest[1..].eq_ignore_ascii_case(base_domain) {
+        return None;
+    }
+    (!label.is_empty() && !label.contains('.')).then_some(label)
+}
+
 impl Default for TenantId {
     fn default() -> Self {
         Self::new()
@@ -71,6 +138,80 @@ impl FromStr for TenantId {
     }
 }
 
+/// Lifecycle state of a tenant
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TenantStatus {
+    /// Requests are served
+    #[default]
+    Active,
+    /// Requests are rejected; configuration and keys are kept
+    Suspended,
+}
+
+impl TenantStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TenantStatus::Active => "active",
+            TenantStatus::Suspended => "suspended",
+        }
+    }
+}
+
+impl FromStr for TenantStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "active" => Ok(TenantStatus::Active),
+            "suspended" => Ok(TenantStatus::Suspended),
+            other => Err(Error::InvalidTenant(format!(
+                "Unknown tenant status '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+/// A registered tenant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantInfo {
+    pub tenant_id: TenantId,
+    /// Handle used in subdomains, headers and key/JWT tenant fields
+    pub name: String,
+    pub status: TenantStatus,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Check that a tenant name can serve as a subdomain label
+///
+/// Names are 1-63 lowercase ASCII letters, digits and inner hyphens, and
+/// must not parse as a tenant ID.
+///
+/// # Errors
+/// - `Error::InvalidTenant` describing the problem
+pub fn validate_tenant_name(name: &str) -> Result<()> {
+    let valid = (1..=63).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && !name.starts_with('-')
+        && !name.ends_with('-');
+    if !valid {
+        return Err(Error::InvalidTenant(format!(
+            "Tenant name '{}' must be 1-63 lowercase letters, digits or inner hyphens",
+            name
+        )));
+    }
+    if Uuid::parse_str(name).is_ok() {
+        return Err(Error::InvalidTenant(format!(
+            "Tenant name '{}' looks like a tenant ID",
+            name
+        )));
+    }
+    Ok(())
+}
+
 /// Tenant context containing configuration and session stores.
 ///
 /// This struct is the core of the dependency injection pattern used
@@ -81,47 +222,50 @@ impl FromStr for TenantId {
 /// # Single-Tenant Mode
 /// ```no_run
 /// # use lunaroute_core::tenant::{TenantContext, TenantId};
+/// # use lunaroute_core::{ConfigStore, SessionStore};
 /// # use std::sync::Arc;
-/// # fn get_stores() -> (Arc<dyn std::any::Any + Send + Sync>, Arc<dyn std::any::Any + Send + Sync>) { todo!() }
+/// # fn get_stores() -> (Arc<dyn ConfigStore>, Arc<dyn SessionStore>) { todo!() }
 /// let (config_store, session_store) = get_stores();
 /// let context = TenantContext {
 ///     tenant_id: None,  // Single-tenant mode
+///     config: Arc::new(serde_json::json!({})),
 ///     config_store,
-///     session_store,
+///     session_store: Some(session_store),
 /// };
 /// ```
 ///
 /// # Multi-Tenant Mode
+///
+/// Ingress resolves the tenant of each request (subdomain, header, API key
+/// or JWT claim) and builds the context with the tenant's configuration and
+/// a session store scoped to the tenant.
 /// ```no_run
 /// # use lunaroute_core::tenant::{TenantContext, TenantId};
+/// # use lunaroute_core::{ConfigStore, SessionStore};
 /// # use std::sync::Arc;
-/// # fn get_stores() -> (Arc<dyn std::any::Any + Send + Sync>, Arc<dyn std::any::Any + Send + Sync>) { todo!() }
-/// # fn extract_tenant_from_request() -> TenantId { todo!() }
-/// let tenant_id = extract_tenant_from_request();
-/// let (config_store, session_store) = get_stores();
+/// # async fn example(config_store: Arc<dyn ConfigStore>, session_store: Arc<dyn SessionStore>) -> lunaroute_core::Result<()> {
+/// let tenant_id = TenantId::resolve("acme", config_store.as_ref()).await?;
 /// let context = TenantContext {
 ///     tenant_id: Some(tenant_id),
+///     config: Arc::new(config_store.get_config(Some(tenant_id)).await?),
 ///     config_store,
-///     session_store,
+///     session_store: Some(session_store),
 /// };
+/// # Ok(())
+/// # }
 /// ```
 pub struct TenantContext {
     /// Optional tenant ID (None = single-tenant mode)
     pub tenant_id: Option<TenantId>,
 
-    /// Configuration store (trait object)
-    ///
-    /// This will be `Arc<dyn ConfigStore>` but we can't reference
-    /// ConfigStore here due to module organization. The actual type
-    /// is defined in the `config_store` module.
-    pub config_store: Arc<dyn std::any::Any + Send + Sync>,
+    /// The tenant's configuration as loaded from `config_store`
+    pub config: Arc<serde_json::Value>,
 
-    /// Session store (trait object)
-    ///
-    /// This will be `Arc<dyn SessionStore>` but we can't reference
-    /// SessionStore here due to module organization. The actual type
-    /// is defined in the `session_store` module.
-    pub session_store: Arc<dyn std::any::Any + Send + Sync>,
+    /// Configuration store
+    pub config_store: Arc<dyn ConfigStore>,
+
+    /// Session store writing into this tenant (None when recording is off)
+    pub session_store: Option<Arc<dyn SessionStore>>,
 }
 
 impl TenantContext {
@@ -166,6 +310,18 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_validate_tenant_name() {
+        assert!(validate_tenant_name("acme").is_ok());
+        assert!(validate_tenant_name("acme-2").is_ok());
+        assert!(validate_tenant_name("").is_err());
+        assert!(validate_tenant_name("Acme").is_err());
+        assert!(validate_tenant_name("-acme").is_err());
+        assert!(validate_tenant_name("acme.example").is_err());
+        assert!(validate_tenant_name(&"a".repeat(64)).is_err());
+        assert!(validate_tenant_name("550e8400-e29b-41d4-a716-446655440000").is_err());
+    }
+
     #[test]
     fn test_tenant_id_display() {
         let uuid = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
@@ -175,4 +331,81 @@ mod tests {
             "550e8400-e29b-41d4-a716-446655440000"
         );
     }
+
+    #[test]
+    fn test_subdomain_of() {
+        let base = "lunaroute.example.com";
+        assert_eq!(
+            subdomain_of("acme.lunaroute.example.com", base),
+            Some("acme")
+        );
+        assert_eq!(
+            subdomain_of("Acme.LunaRoute.example.com:8443", base),
+            Some("Acme")
+        );
+        assert_eq!(subdomain_of("lunaroute.example.com", base), None);
+        assert_eq!(subdomain_of("a.b.lunaroute.example.com", base), None);
+        assert_eq!(subdomain_of("acme.evil-lunaroute.example.com", base), None);
+        assert_eq!(subdomain_of("acme.other.com", base), None);
+    }
+
+    struct NamedTenants(TenantId);
+
+    #[async_trait::async_trait]
+    impl ConfigStore for NamedTenants {
+        async fn get_config(&self, _tenant_id: Option<TenantId>) -> Result<serde_json::Value> {
+            Err(Error::ConfigNotFound)
+        }
+
+        async fn update_config(
+            &self,
+            _tenant_id: Option<TenantId>,
+            _config: serde_json::Value,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn watch_changes(
+            &self,
+            _tenant_id: Option<TenantId>,
+        ) -> Result<crate::config_store::ConfigChangeStream<'_>> {
+            Ok(Box::pin(futures::stream::empty()))
+        }
+
+        async fn validate_config(&self, _config: &serde_json::Value) -> Result<()> {
+            Ok(())
+        }
+
+        async fn lookup_tenant(&self, name: &str) -> Result<Option<TenantId>> {
+            Ok((name == "acme").then_some(self.0))
+        }
+    }
+
+    #[test]
+    fn test_tenant_id_from_subdomain() {
+        let acme = TenantId::new();
+        let store = NamedTenants(acme);
+        let base = "lunaroute.example.com";
+        futures::executor::block_on(async {
+            let resolved = TenantId::from_subdomain("acme.lunaroute.example.com", base, &store)
+                .await
+                .unwrap();
+            assert_eq!(resolved, acme);
+
+            // Tenant IDs work as subdomains without a lookup
+            let other = TenantId::new();
+            let host = format!("{}.{}", other, base);
+            let resolved = TenantId::from_subdomain(&host, base, &store).await;
+            assert_eq!(resolved.unwrap(), other);
+
+            assert!(matches!(
+                TenantId::from_subdomain("globex.lunaroute.example.com", base, &store).await,
+                Err(Error::TenantNotFound(_))
+            ));
+            assert!(matches!(
+                TenantId::from_subdomain("acme.other.com", base, &store).await,
+                Err(Error::InvalidTenant(_))
+            ));
+        });
+    }
 }
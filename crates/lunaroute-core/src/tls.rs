@@ -0,0 +1,68 @@
+//! TLS listener settings
+//!
+//! Shared by the proxy and UI listeners. The listener itself (rustls
+//! acceptor, certificate reloading, client-certificate identity) lives in
+//! `lunaroute-ingress`; this module only holds the configuration so crates
+//! without a TLS stack can carry it.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Whether connecting clients must present a certificate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientCertMode {
+    /// Reject handshakes without a certificate signed by `client_ca_path`
+    #[default]
+    Required,
+    /// Verify a certificate when one is offered, accept clients without one
+    Optional,
+}
+
+/// TLS termination for a listener
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsSettings {
+    /// PEM certificate chain (leaf first)
+    pub cert_path: PathBuf,
+
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1)
+    pub key_path: PathBuf,
+
+    /// PEM CA bundle used to verify client certificates (mTLS).
+    /// Client certificates are not requested when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ca_path: Option<PathBuf>,
+
+    /// Whether a client certificate is mandatory (default: required).
+    /// Only used with `client_ca_path`.
+    #[serde(default)]
+    pub client_cert_mode: ClientCertMode,
+
+    /// Reload the certificate and key when either file changes (default: true)
+    #[serde(default = "default_reload")]
+    pub reload: bool,
+}
+
+fn default_reload() -> bool {
+    true
+}
+
+impl TlsSettings {
+    /// Settings for a certificate/key pair with no client verification
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        Self {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+            client_ca_path: None,
+            client_cert_mode: ClientCertMode::default(),
+            reload: default_reload(),
+        }
+    }
+
+    /// Verify client certificates against a CA bundle
+    pub fn with_client_ca(mut self, path: impl Into<PathBuf>, mode: ClientCertMode) -> Self {
+        self.client_ca_path = Some(path.into());
+        self.client_cert_mode = mode;
+        self
+    }
+}
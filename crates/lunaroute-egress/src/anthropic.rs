@@ -2,7 +2,9 @@
 
 use crate::{
     EgressError, Result,
-    client::{HttpClientConfig, create_client, with_retry},
+    claude_oauth::with_oauth_beta,
+    client::{HttpClientConfig, create_client, header_pairs, with_retry},
+    key_pool::{KeyPool, PooledKey, UPSTREAM_KEY_HEADER},
 };
 use async_trait::async_trait;
 use futures::Stream;
@@ -12,10 +14,12 @@ use lunaroute_core::{
         NormalizedRequest, NormalizedResponse, NormalizedStreamEvent, Role, ToolCall, Usage,
     },
     provider::{Provider, ProviderCapabilities},
+    quota::{ProviderQuota, QuotaTracker},
 };
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::pin::Pin;
+use std::sync::Arc;
 use tracing::{debug, instrument};
 
 /// Format a header for debug logging, redacting auth values.
@@ -32,6 +36,31 @@ fn redact_header_line(name: &str, value: &str) -> String {
     }
 }
 
+/// Set upstream auth for `key`: `x-api-key` for API keys, or a bearer token
+/// plus the OAuth `anthropic-beta` flag (merged with the client's flags) for
+/// Claude subscription accounts
+fn apply_key_auth(
+    request_builder: reqwest::RequestBuilder,
+    key: &PooledKey,
+    client_beta: Option<&str>,
+) -> reqwest::RequestBuilder {
+    if key.is_oauth() {
+        request_builder
+            .bearer_auth(key.secret())
+            .header("anthropic-beta", with_oauth_beta(client_beta))
+    } else {
+        request_builder.header("x-api-key", key.secret())
+    }
+}
+
+/// Value of the client's `anthropic-beta` header, if any
+fn client_beta(headers: &std::collections::HashMap<String, String>) -> Option<&str> {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("anthropic-beta"))
+        .map(|(_, value)| value.as_str())
+}
+
 /// Anthropic connector configuration
 #[derive(Debug, Clone)]
 pub struct AnthropicConfig {
@@ -80,13 +109,59 @@ impl AnthropicConfig {
 pub struct AnthropicConnector {
     config: AnthropicConfig,
     client: Client,
+    /// Quota from the latest rate-limit response headers
+    quota: QuotaTracker,
+    /// Upstream keys (None when requests carry the client's credentials)
+    keys: Option<Arc<KeyPool>>,
 }
 
 impl AnthropicConnector {
     /// Create a new Anthropic connector
     pub fn new(config: AnthropicConfig) -> Result<Self> {
         let client = create_client(&config.client_config)?;
-        Ok(Self { config, client })
+        let keys = (!config.api_key.is_empty()).then(|| {
+            Arc::new(KeyPool::new(
+                "anthropic",
+                vec![config.api_key.clone()],
+                Default::default(),
+            ))
+        });
+        Ok(Self {
+            config,
+            client,
+            quota: QuotaTracker::new(),
+            keys,
+        })
+    }
+
+    /// Use a pool of upstream keys instead of the single configured `api_key`
+    pub fn with_key_pool(mut self, pool: Arc<KeyPool>) -> Self {
+        self.keys = (!pool.is_empty()).then_some(pool);
+        self
+    }
+
+    /// Upstream key pool, if the connector authenticates with its own keys
+    pub fn key_pool(&self) -> Option<&Arc<KeyPool>> {
+        self.keys.as_ref()
+    }
+
+    /// Pick the upstream key for the next request
+    async fn select_key(&self) -> Option<PooledKey> {
+        match &self.keys {
+            Some(pool) => pool.select_fresh().await,
+            None => None,
+        }
+    }
+
+    /// Record quota and key health from a response and tag it with the key id
+    fn observe_response(&self, key: Option<&PooledKey>, response: &mut reqwest::Response) {
+        self.quota.observe(header_pairs(response.headers()));
+        if let (Some(pool), Some(key)) = (&self.keys, key) {
+            pool.report(key, response.status().as_u16(), response.headers());
+            if let Ok(value) = reqwest::header::HeaderValue::from_str(key.id()) {
+                response.headers_mut().insert(UPSTREAM_KEY_HEADER, value);
+            }
+        }
     }
 
     /// Send a raw JSON request directly to Anthropic (passthrough mode)
@@ -121,7 +196,7 @@ impl AnthropicConnector {
 
         // No retry wrapping in passthrough mode - the client handles retries.
         // Retrying here would hide errors from the client and break transparent proxying.
-        let config_api_key = self.config.api_key.clone();
+        let key = self.select_key().await;
 
         let mut request_builder = self
             .client
@@ -130,19 +205,22 @@ impl AnthropicConnector {
 
         // If we have a configured API key, filter out client auth headers and use our key
         // If not, pass through all headers including client's auth
-        if !config_api_key.is_empty() {
+        if let Some(key) = &key {
             // Add all headers except authorization headers
             for (name, value) in &headers {
                 let name_lower = name.to_lowercase();
-                if name_lower != "authorization" && name_lower != "x-api-key" {
-                    request_builder = request_builder.header(name, value);
-                } else {
+                if name_lower == "authorization" || name_lower == "x-api-key" {
                     debug!("│ [FILTERED] {}: <removed>", name);
+                } else if !(key.is_oauth() && name_lower == "anthropic-beta") {
+                    request_builder = request_builder.header(name, value);
                 }
             }
             // Use configured API key
-            request_builder = request_builder.header("x-api-key", &config_api_key);
-            debug!("│ [OVERRIDE] x-api-key: <configured_api_key>");
+            request_builder = apply_key_auth(request_builder, key, client_beta(&headers));
+            debug!(
+                "│ [OVERRIDE] x-api-key: <configured_api_key> ({})",
+                key.id()
+            );
         } else {
             // No configured API key, pass through all headers
             for (name, value) in &headers {
@@ -154,7 +232,8 @@ impl AnthropicConnector {
         debug!("│ Final headers being sent to Anthropic API");
         debug!("└─────────────────────────────────────────────────────────");
 
-        let response = request_builder.json(&request_json).send().await?;
+        let mut response = request_builder.json(&request_json).send().await?;
+        self.observe_response(key.as_ref(), &mut response);
 
         // Log response headers at debug level
         debug!("┌─────────────────────────────────────────────────────────");
@@ -280,7 +359,8 @@ impl AnthropicConnector {
         // `accept-encoding: gzip`, Anthropic responds with a gzip-compressed
         // SSE stream that the eventsource parser cannot read, causing the
         // stream to hang indefinitely.
-        if !self.config.api_key.is_empty() {
+        let key = self.select_key().await;
+        if let Some(key) = &key {
             // Add all headers except authorization and encoding headers
             for (name, value) in &headers {
                 let name_lower = name.to_lowercase();
@@ -291,13 +371,16 @@ impl AnthropicConnector {
                         "│ [FILTERED] {}: {} (streaming requires uncompressed SSE)",
                         name, value
                     );
-                } else {
+                } else if !(key.is_oauth() && name_lower == "anthropic-beta") {
                     request_builder = request_builder.header(name, value);
                 }
             }
             // Use configured API key
-            request_builder = request_builder.header("x-api-key", &self.config.api_key);
-            debug!("│ [OVERRIDE] x-api-key: <configured_api_key>");
+            request_builder = apply_key_auth(request_builder, key, client_beta(&headers));
+            debug!(
+                "│ [OVERRIDE] x-api-key: <configured_api_key> ({})",
+                key.id()
+            );
         } else {
             // No configured API key, pass through all headers (except accept-encoding)
             for (name, value) in &headers {
@@ -317,11 +400,12 @@ impl AnthropicConnector {
         debug!("│ Final headers being sent to Anthropic API");
         debug!("└─────────────────────────────────────────────────────────");
 
-        let response = request_builder
+        let mut response = request_builder
             .json(&request_json)
             .send()
             .await
             .map_err(EgressError::from)?;
+        self.observe_response(key.as_ref(), &mut response);
 
         // Log response headers at debug level
         debug!("┌─────────────────────────────────────────────────────────");
@@ -369,15 +453,21 @@ impl Provider for AnthropicConnector {
         let result = with_retry(max_retries, || {
             let anthropic_req = anthropic_req.clone();
             async move {
-                let response = self
+                let key = self.select_key().await;
+                let request_builder = self
                     .client
-                    .post(format!("{}/v1/messages", self.config.base_url))
-                    .header("x-api-key", &self.config.api_key)
+                    .post(format!("{}/v1/messages", self.config.base_url));
+                let request_builder = match &key {
+                    Some(key) => apply_key_auth(request_builder, key, None),
+                    None => request_builder.header("x-api-key", ""),
+                };
+                let mut response = request_builder
                     .header("anthropic-version", &self.config.api_version)
                     .header("Content-Type", "application/json")
                     .json(&anthropic_req)
                     .send()
                     .await?;
+                self.observe_response(key.as_ref(), &mut response);
 
                 // Log response headers at debug level
                 debug!("┌─────────────────────────────────────────────────────────");
@@ -420,16 +510,22 @@ impl Provider for AnthropicConnector {
         debug!("│ Content-Type: application/json");
         debug!("└─────────────────────────────────────────────────────────");
 
-        let response = self
+        let key = self.select_key().await;
+        let request_builder = self
             .client
-            .post(format!("{}/v1/messages", self.config.base_url))
-            .header("x-api-key", &self.config.api_key)
+            .post(format!("{}/v1/messages", self.config.base_url));
+        let request_builder = match &key {
+            Some(key) => apply_key_auth(request_builder, key, None),
+            None => request_builder.header("x-api-key", ""),
+        };
+        let mut response = request_builder
             .header("anthropic-version", &self.config.api_version)
             .header("Content-Type", "application/json")
             .json(&anthropic_req)
             .send()
             .await
             .map_err(EgressError::from)?;
+        self.observe_response(key.as_ref(), &mut response);
 
         // Log response headers at debug level
         debug!("┌─────────────────────────────────────────────────────────");
@@ -471,6 +567,33 @@ impl Provider for AnthropicConnector {
     fn get_notification_message(&self) -> Option<&str> {
         self.config.switch_notification_message.as_deref()
     }
+
+    fn quota(&self) -> Option<ProviderQuota> {
+        // With several keys, the provider is only as constrained as its best key
+        match &self.keys {
+            Some(pool) if pool.len() > 1 => pool.quota(),
+            _ => self.quota.get(),
+        }
+    }
+
+    async fn health_check(&self) -> Option<lunaroute_core::Result<()>> {
+        // Without a configured key, requests carry the client's credentials
+        let key = self.select_key().await?;
+
+        let result = async {
+            let request_builder = self
+                .client
+                .get(format!("{}/v1/models?limit=1", self.config.base_url));
+            let mut response = apply_key_auth(request_builder, &key, None)
+                .header("anthropic-version", &self.config.api_version)
+                .send()
+                .await?;
+            self.observe_response(Some(&key), &mut response);
+            crate::client::check_probe_response(response).await
+        }
+        .await;
+        Some(result.map_err(Into::into))
+    }
 }
 
 // Anthropic API types
@@ -1421,6 +1544,161 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_health_check_lists_models() {
+        use wiremock::{
+            Mock, MockServer, ResponseTemplate,
+            matchers::{header, method, path},
+        };
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .and(header("x-api-key", "good-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"data":[]}"#))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .and(header("x-api-key", "busy-key"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "7"))
+            .mount(&mock_server)
+            .await;
+
+        let connector = |key: &str| {
+            AnthropicConnector::new(AnthropicConfig::new(key).with_base_url(mock_server.uri()))
+                .unwrap()
+        };
+
+        assert!(matches!(
+            connector("good-key").health_check().await,
+            Some(Ok(()))
+        ));
+        assert!(matches!(
+            connector("busy-key").health_check().await,
+            Some(Err(lunaroute_core::Error::RateLimitExceeded {
+                retry_after_secs: Some(7)
+            }))
+        ));
+        // No configured key: nothing to probe with
+        assert!(connector("").health_check().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_passthrough_records_quota_headers() {
+        use wiremock::{
+            Mock, MockServer, ResponseTemplate,
+            matchers::{method, path},
+        };
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("anthropic-ratelimit-requests-limit", "50")
+                    .insert_header("anthropic-ratelimit-requests-remaining", "2")
+                    .insert_header("anthropic-ratelimit-output-tokens-remaining", "900")
+                    .set_body_string("{}"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let connector =
+            AnthropicConnector::new(AnthropicConfig::new("key").with_base_url(mock_server.uri()))
+                .unwrap();
+        assert!(connector.quota().is_none());
+
+        connector
+            .send_passthrough(serde_json::json!({}), Default::default())
+            .await
+            .unwrap();
+        let quota = connector.quota().unwrap();
+        assert_eq!(quota.requests.unwrap().limit, Some(50));
+        assert_eq!(quota.requests.unwrap().remaining, 2);
+        assert_eq!(quota.output_tokens.unwrap().remaining, 900);
+        assert_eq!(
+            quota.low_window(5),
+            Some(lunaroute_core::quota::QuotaKind::Requests)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_passthrough_rotates_claude_oauth_accounts() {
+        use crate::claude_oauth::{ClaudeOAuthAccount, OAUTH_BETA};
+        use std::time::{SystemTime, UNIX_EPOCH};
+        use wiremock::{
+            Mock, MockServer, ResponseTemplate,
+            matchers::{header, header_regex, method, path},
+        };
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let account = |name: &str| {
+            let file = temp_dir.path().join(format!("{}.json", name));
+            std::fs::write(
+                &file,
+                serde_json::json!({"claudeAiOauth": {
+                    "accessToken": format!("tok-{}", name),
+                    "refreshToken": "unused",
+                    "expiresAt": (now.as_millis() as u64) + 3_600_000
+                }})
+                .to_string(),
+            )
+            .unwrap();
+            Arc::new(ClaudeOAuthAccount::new(file, reqwest::Client::new()))
+        };
+
+        let mock_server = MockServer::start().await;
+        // Account a has used up its subscription window for the next hour
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header("authorization", "Bearer tok-a"))
+            .respond_with(ResponseTemplate::new(429).insert_header(
+                "anthropic-ratelimit-unified-reset",
+                (now.as_secs() + 3600).to_string().as_str(),
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header("authorization", "Bearer tok-b"))
+            .and(header_regex("anthropic-beta", OAUTH_BETA))
+            .and(header_regex("anthropic-beta", "tools-2024-04-04"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+            .mount(&mock_server)
+            .await;
+
+        let pool = KeyPool::new("anthropic", vec![], Default::default())
+            .with_oauth_account(account("a"))
+            .with_oauth_account(account("b"));
+        let connector =
+            AnthropicConnector::new(AnthropicConfig::new("").with_base_url(mock_server.uri()))
+                .unwrap()
+                .with_key_pool(Arc::new(pool));
+
+        let headers: std::collections::HashMap<String, String> = [
+            ("authorization", "Bearer client-token"),
+            ("anthropic-beta", "tools-2024-04-04"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let (status, _, _) = connector
+                .send_passthrough(serde_json::json!({}), headers.clone())
+                .await
+                .unwrap();
+            statuses.push(status);
+        }
+        assert_eq!(statuses, vec![429, 200, 200]);
+        let status = connector.key_pool().unwrap().status();
+        assert_eq!(status[0].state, "backoff");
+        assert!(status[0].available_in_secs.unwrap() > 3500);
+    }
+
     #[test]
     fn test_from_anthropic_response_basic() {
         let anthropic_resp = AnthropicResponse {
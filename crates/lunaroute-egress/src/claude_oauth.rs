@@ -0,0 +1,457 @@
+//! Claude subscription OAuth credentials
+//!
+//! Claude Code stores the OAuth tokens of a Claude subscription login in
+//! `~/.claude/.credentials.json`. [`ClaudeOAuthAccount`] reads that file so
+//! requests can be billed to the subscription instead of an API key, refreshes
+//! the access token shortly before `expiresAt`, and writes the rotated tokens
+//! back atomically under `FileLock` so Claude Code keeps working with the same
+//! file. Several accounts can be pooled in a [`KeyPool`](crate::KeyPool).
+
+use crate::codex_auth::expand_tilde;
+use crate::{EgressError, Result};
+use lunaroute_storage::{AtomicWriter, FileLock};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+/// Where Claude Code keeps its OAuth credentials
+pub const DEFAULT_CREDENTIALS_FILE: &str = "~/.claude/.credentials.json";
+
+/// OAuth token endpoint used by Claude Code
+pub const DEFAULT_TOKEN_ENDPOINT: &str = "https://console.anthropic.com/v1/oauth/token";
+
+/// Claude Code client ID
+pub const CLAUDE_CODE_CLIENT_ID: &str = "9d1c250a-e61b-44d9-88ed-5944d1962f5e";
+
+/// `anthropic-beta` flag required for OAuth bearer tokens
+pub const OAUTH_BETA: &str = "oauth-2025-04-20";
+
+/// Default time before expiry at which the access token is refreshed
+pub const DEFAULT_REFRESH_BEFORE_EXPIRY: Duration = Duration::from_secs(300);
+
+/// Top-level field holding the OAuth tokens in the credentials file
+const OAUTH_FIELD: &str = "claudeAiOauth";
+
+/// Response from the OAuth refresh-token grant
+#[derive(Deserialize)]
+struct RefreshResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+#[derive(Clone)]
+struct AccessToken {
+    value: String,
+    expires_at: Option<SystemTime>,
+}
+
+#[derive(Default)]
+struct TokenState {
+    cached: Option<AccessToken>,
+    /// Token upstream rejected with 401, never reused from the file
+    rejected: Option<String>,
+}
+
+/// One Claude subscription login, backed by a Claude Code credentials file
+pub struct ClaudeOAuthAccount {
+    credentials_file: PathBuf,
+    token_endpoint: String,
+    client_id: String,
+    refresh_before_expiry: Duration,
+    client: reqwest::Client,
+    state: Mutex<TokenState>,
+    /// Serializes refreshes within this process
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+impl std::fmt::Debug for ClaudeOAuthAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClaudeOAuthAccount")
+            .field("credentials_file", &self.credentials_file)
+            .field("token_endpoint", &self.token_endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClaudeOAuthAccount {
+    /// Create an account for `credentials_file` with Claude Code's defaults
+    pub fn new(credentials_file: impl Into<PathBuf>, client: reqwest::Client) -> Self {
+        Self {
+            credentials_file: credentials_file.into(),
+            token_endpoint: DEFAULT_TOKEN_ENDPOINT.to_string(),
+            client_id: CLAUDE_CODE_CLIENT_ID.to_string(),
+            refresh_before_expiry: DEFAULT_REFRESH_BEFORE_EXPIRY,
+            client,
+            state: Mutex::new(TokenState::default()),
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Set the OAuth token endpoint
+    pub fn with_token_endpoint(mut self, token_endpoint: impl Into<String>) -> Self {
+        self.token_endpoint = token_endpoint.into();
+        self
+    }
+
+    /// Set the OAuth client ID
+    pub fn with_client_id(mut self, client_id: impl Into<String>) -> Self {
+        self.client_id = client_id.into();
+        self
+    }
+
+    /// Set how long before expiry the access token is refreshed
+    pub fn with_refresh_before_expiry(mut self, before: Duration) -> Self {
+        self.refresh_before_expiry = before;
+        self
+    }
+
+    /// Loggable description, also hashed into the pool's key id
+    pub fn describe(&self) -> String {
+        format!("claude-oauth {}", self.credentials_file.display())
+    }
+
+    fn is_fresh(&self, token: &AccessToken) -> bool {
+        token.expires_at.is_none_or(|exp| {
+            exp.checked_sub(self.refresh_before_expiry)
+                .is_some_and(|at| at > SystemTime::now())
+        })
+    }
+
+    fn fresh_cached(&self) -> Option<String> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .cached
+            .as_ref()
+            .filter(|token| self.is_fresh(token))
+            .map(|token| token.value.clone())
+    }
+
+    /// Cached access token, without refreshing (None before the first load)
+    pub fn cached_token(&self) -> Option<String> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.cached.as_ref().map(|token| token.value.clone())
+    }
+
+    /// Forget the current token after upstream rejected it, so the next
+    /// [`access_token`](Self::access_token) refreshes
+    pub fn invalidate(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(token) = state.cached.take() {
+            state.rejected = Some(token.value);
+        }
+    }
+
+    /// A usable access token, refreshing it when it is about to expire
+    ///
+    /// The credentials file is re-read under its lock first, so a token
+    /// Claude Code (or another LunaRoute) already refreshed is reused
+    /// instead of spending the rotating refresh token twice.
+    pub async fn access_token(&self) -> Result<String> {
+        if let Some(token) = self.fresh_cached() {
+            return Ok(token);
+        }
+        let _guard = self.refreshing.lock().await;
+        if let Some(token) = self.fresh_cached() {
+            return Ok(token);
+        }
+
+        let path = expand_tilde(&self.credentials_file)?;
+        let lock_path = path.clone();
+        let _lock = tokio::task::spawn_blocking(move || FileLock::acquire(&lock_path))
+            .await
+            .map_err(|e| {
+                EgressError::ConfigError(format!("Claude credentials lock task failed: {}", e))
+            })?
+            .map_err(|e| {
+                EgressError::ConfigError(format!(
+                    "Failed to lock Claude credentials file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+        let read_path = path.clone();
+        let contents = tokio::task::spawn_blocking(move || fs::read_to_string(&read_path))
+            .await
+            .map_err(|e| {
+                EgressError::ConfigError(format!("Claude credentials read task failed: {}", e))
+            })?
+            .map_err(|e| {
+                EgressError::ConfigError(format!(
+                    "Failed to read Claude credentials file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        let mut json: Value = serde_json::from_str(&contents).map_err(|e| {
+            EgressError::ConfigError(format!(
+                "Failed to parse Claude credentials JSON from {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let oauth = json
+            .get_mut(OAUTH_FIELD)
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| {
+                EgressError::ConfigError(format!(
+                    "No {} in Claude credentials file {}; run `claude login`",
+                    OAUTH_FIELD,
+                    path.display()
+                ))
+            })?;
+
+        let rejected = self
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .rejected
+            .clone();
+        if let Some(value) = oauth.get("accessToken").and_then(Value::as_str) {
+            let token = AccessToken {
+                value: value.to_string(),
+                expires_at: oauth
+                    .get("expiresAt")
+                    .and_then(Value::as_u64)
+                    .map(|ms| UNIX_EPOCH + Duration::from_millis(ms)),
+            };
+            if !token.value.is_empty()
+                && Some(&token.value) != rejected.as_ref()
+                && self.is_fresh(&token)
+            {
+                debug!("Loaded Claude OAuth token from {}", path.display());
+                return Ok(self.store(token));
+            }
+        }
+
+        let refresh_token = oauth
+            .get("refreshToken")
+            .and_then(Value::as_str)
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| {
+                EgressError::ConfigError(format!(
+                    "No refreshToken in Claude credentials file {}; run `claude login`",
+                    path.display()
+                ))
+            })?
+            .to_string();
+
+        debug!("Refreshing Claude OAuth token at {}", self.token_endpoint);
+        let response = self
+            .client
+            .post(&self.token_endpoint)
+            .json(&serde_json::json!({
+                "grant_type": "refresh_token",
+                "refresh_token": refresh_token,
+                "client_id": self.client_id,
+            }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                "Claude OAuth refresh for {} failed with status {}",
+                path.display(),
+                status
+            );
+            return Err(EgressError::ProviderError {
+                status_code: status.as_u16(),
+                message: format!("Claude OAuth refresh failed: {}", body),
+            });
+        }
+        let refreshed: RefreshResponse = response.json().await.map_err(|e| {
+            EgressError::ParseError(format!("Invalid Claude OAuth refresh response: {}", e))
+        })?;
+
+        // Merge into the existing file so fields we don't know about survive
+        let expires_at = refreshed
+            .expires_in
+            .map(|secs| SystemTime::now() + Duration::from_secs(secs));
+        oauth.insert(
+            "accessToken".to_string(),
+            Value::String(refreshed.access_token.clone()),
+        );
+        if let Some(refresh_token) = refreshed.refresh_token {
+            oauth.insert("refreshToken".to_string(), Value::String(refresh_token));
+        }
+        if let Some(ms) = expires_at
+            .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+        {
+            oauth.insert("expiresAt".to_string(), Value::from(ms));
+        }
+
+        let body = serde_json::to_string_pretty(&json)?;
+        let write_path = path.clone();
+        let write = move || -> lunaroute_storage::StorageResult<()> {
+            let mut writer = AtomicWriter::new(&write_path)?;
+            writer.write(body.as_bytes())?;
+            writer.commit()
+        };
+        tokio::task::spawn_blocking(write)
+            .await
+            .map_err(|e| {
+                EgressError::ConfigError(format!("Claude credentials write task failed: {}", e))
+            })?
+            .map_err(|e| {
+                EgressError::ConfigError(format!(
+                    "Failed to write Claude credentials file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+        info!("Refreshed Claude OAuth token in {}", path.display());
+        Ok(self.store(AccessToken {
+            value: refreshed.access_token,
+            expires_at,
+        }))
+    }
+
+    fn store(&self, token: AccessToken) -> String {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let value = token.value.clone();
+        state.cached = Some(token);
+        state.rejected = None;
+        value
+    }
+}
+
+/// `anthropic-beta` value with the OAuth flag added to the client's flags
+pub fn with_oauth_beta(client_beta: Option<&str>) -> String {
+    match client_beta.map(str::trim).filter(|b| !b.is_empty()) {
+        Some(beta) if beta.split(',').any(|flag| flag.trim() == OAUTH_BETA) => beta.to_string(),
+        Some(beta) => format!("{},{}", beta, OAUTH_BETA),
+        None => OAUTH_BETA.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn now_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+
+    fn write_credentials(path: &std::path::Path, access: &str, expires_at_ms: u64) {
+        fs::write(
+            path,
+            serde_json::json!({
+                "claudeAiOauth": {
+                    "accessToken": access,
+                    "refreshToken": "sk-ant-ort01-old",
+                    "expiresAt": expires_at_ms,
+                    "subscriptionType": "max"
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_uses_unexpired_token_from_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join(".credentials.json");
+        write_credentials(&file, "sk-ant-oat01-live", now_ms() + 3_600_000);
+
+        let account = ClaudeOAuthAccount::new(&file, reqwest::Client::new())
+            .with_token_endpoint("http://127.0.0.1:1/oauth/token");
+        assert_eq!(account.access_token().await.unwrap(), "sk-ant-oat01-live");
+        assert_eq!(account.cached_token().as_deref(), Some("sk-ant-oat01-live"));
+        assert!(!format!("{:?}", account).contains("sk-ant-oat01"));
+    }
+
+    #[tokio::test]
+    async fn test_refreshes_expiring_and_rejected_tokens() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/oauth/token"))
+            .and(body_partial_json(serde_json::json!({
+                "grant_type": "refresh_token",
+                "refresh_token": "sk-ant-ort01-old",
+                "client_id": CLAUDE_CODE_CLIENT_ID,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "sk-ant-oat01-new",
+                "refresh_token": "sk-ant-ort01-new",
+                "expires_in": 28800
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/oauth/token"))
+            .and(body_partial_json(serde_json::json!({
+                "refresh_token": "sk-ant-ort01-new",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "sk-ant-oat01-newer",
+                "expires_in": 28800
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join(".credentials.json");
+        // Expires inside the refresh window
+        write_credentials(&file, "sk-ant-oat01-old", now_ms() + 60_000);
+
+        let account = ClaudeOAuthAccount::new(&file, reqwest::Client::new())
+            .with_token_endpoint(format!("{}/v1/oauth/token", server.uri()));
+        assert_eq!(account.access_token().await.unwrap(), "sk-ant-oat01-new");
+
+        let written: Value = serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(written["claudeAiOauth"]["accessToken"], "sk-ant-oat01-new");
+        assert_eq!(written["claudeAiOauth"]["refreshToken"], "sk-ant-ort01-new");
+        assert_eq!(written["claudeAiOauth"]["subscriptionType"], "max");
+        assert!(written["claudeAiOauth"]["expiresAt"].as_u64().unwrap() > now_ms());
+
+        // Cached until upstream rejects it; the rejected token in the file is
+        // not reused even though it has not expired
+        assert_eq!(account.access_token().await.unwrap(), "sk-ant-oat01-new");
+        account.invalidate();
+        assert_eq!(account.access_token().await.unwrap(), "sk-ant-oat01-newer");
+    }
+
+    #[tokio::test]
+    async fn test_missing_oauth_section_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join(".credentials.json");
+        fs::write(&file, "{}").unwrap();
+
+        let account = ClaudeOAuthAccount::new(&file, reqwest::Client::new());
+        let err = account.access_token().await.unwrap_err();
+        assert!(err.to_string().contains("claude login"));
+    }
+
+    #[test]
+    fn test_with_oauth_beta() {
+        assert_eq!(with_oauth_beta(None), OAUTH_BETA);
+        assert_eq!(
+            with_oauth_beta(Some("prompt-caching-2024-07-31")),
+            format!("prompt-caching-2024-07-31,{}", OAUTH_BETA)
+        );
+        assert_eq!(
+            with_oauth_beta(Some(&format!("x, {}", OAUTH_BETA))),
+            format!("x, {}", OAUTH_BETA)
+        );
+    }
+}
@@ -182,6 +182,44 @@ where
         .unwrap_or_else(|| EgressError::ConfigError("Retry loop exited unexpectedly".to_string())))
 }
 
+/// Response headers as `(name, value)` pairs, for
+/// [`QuotaTracker::observe`](lunaroute_core::quota::QuotaTracker::observe)
+///
+/// Values that are not valid UTF-8 are skipped.
+pub fn header_pairs(headers: &reqwest::header::HeaderMap) -> impl Iterator<Item = (&str, &str)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| value.to_str().ok().map(|v| (name.as_str(), v)))
+}
+
+/// Map a health-check response to Ok or the matching error
+///
+/// 429 becomes [`EgressError::RateLimitExceeded`] so probes feed rate-limit
+/// handling the same way live traffic does.
+pub async fn check_probe_response(response: reqwest::Response) -> Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let retry_after_secs = response
+        .headers()
+        .get("retry-after")
+        .and_then(|v| v.to_str().ok())
+        .and_then(crate::parse_retry_after);
+    if status.as_u16() == 429 {
+        return Err(EgressError::RateLimitExceeded { retry_after_secs });
+    }
+
+    Err(EgressError::ProviderError {
+        status_code: status.as_u16(),
+        message: response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unable to read error body".to_string()),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
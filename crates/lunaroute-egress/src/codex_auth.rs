@@ -1,13 +1,17 @@
 //! Codex authentication token reading
 //!
 //! This module provides functionality to read authentication tokens from Codex's
-//! auth.json file, with support for file watching and automatic token refresh.
+//! auth.json file, and to refresh an expiring access token with the stored
+//! refresh token, writing the result back for Codex CLI to pick up.
 
 use crate::{EgressError, Result};
+use lunaroute_storage::{AtomicWriter, FileLock};
+use serde::Deserialize;
 use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
-use tracing::{debug, warn};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
 
 /// Read Codex authentication token from JSON file
 ///
@@ -104,8 +108,25 @@ fn extract_nested_field<'a>(json: &'a Value, path: &str) -> Option<&'a Value> {
     Some(current)
 }
 
+/// Set a nested field using dot notation, creating objects along the way
+fn set_nested_field(json: &mut Value, path: &str, value: Value) -> Option<()> {
+    let mut current = json;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let object = current.as_object_mut()?;
+        if parts.peek().is_none() {
+            object.insert(part.to_string(), value);
+            return Some(());
+        }
+        current = object
+            .entry(part)
+            .or_insert_with(|| Value::Object(Default::default()));
+    }
+    None
+}
+
 /// Expand tilde (~) in path to home directory
-fn expand_tilde(path: &Path) -> Result<PathBuf> {
+pub(crate) fn expand_tilde(path: &Path) -> Result<PathBuf> {
     let path_str = path
         .to_str()
         .ok_or_else(|| EgressError::ConfigError("Invalid UTF-8 in path".to_string()))?;
@@ -130,16 +151,15 @@ fn expand_tilde(path: &Path) -> Result<PathBuf> {
 
 /// Check if a JWT token appears to be expired
 ///
-/// This does basic heuristic checks without actually parsing the JWT:
-/// - Returns true if token is too short to be valid
-/// - Could be extended to parse expiration claim in the future
+/// Returns true if the token is malformed or its `exp` claim is in the past.
+/// Tokens without an `exp` claim are assumed to be valid.
 ///
 /// # Arguments
 /// * `token` - The JWT token string
 ///
 /// # Returns
 /// * `true` if token appears expired or invalid
-/// * `false` if token looks valid (does not guarantee it's not expired)
+/// * `false` if token looks valid (does not guarantee the upstream accepts it)
 pub fn is_token_likely_expired(token: &str) -> bool {
     // Basic sanity check: JWT should be at least 3 parts separated by dots
     let parts: Vec<&str> = token.split('.').collect();
@@ -154,173 +174,221 @@ pub fn is_token_likely_expired(token: &str) -> bool {
         }
     }
 
-    // TODO: Could parse payload and check 'exp' claim for actual expiration
-    // For now, assume token structure is valid means it's not expired
-    false
+    token_expiry(token).is_some_and(|exp| exp <= SystemTime::now())
+}
+
+/// Expiry time from a JWT's `exp` claim, if present
+pub fn token_expiry(token: &str) -> Option<SystemTime> {
+    use base64::prelude::*;
+
+    let payload = token.split('.').nth(1)?;
+    let bytes = BASE64_URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let claims: Value = serde_json::from_slice(&bytes).ok()?;
+    let exp = claims.get("exp")?.as_u64()?;
+    Some(UNIX_EPOCH + Duration::from_secs(exp))
+}
+
+/// OAuth token endpoint used by Codex CLI
+pub const DEFAULT_TOKEN_ENDPOINT: &str = "https://auth.openai.com/oauth/token";
+
+/// Codex CLI client ID
+pub const CODEX_CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
+
+/// Response from the OAuth refresh-token grant
+#[derive(Deserialize)]
+struct RefreshResponse {
+    access_token: String,
+    #[serde(default)]
+    id_token: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+/// Refreshes the Codex access token and writes it back to auth.json
+///
+/// The file is rewritten atomically while holding `auth.json.lock`, and
+/// re-read under the lock first so a token already refreshed by another
+/// process (Codex CLI or another LunaRoute) is reused instead of spending
+/// the refresh token twice. The access token is read from and written to
+/// `token_field` (default `tokens.access_token`); the refresh and ID tokens
+/// stay under `tokens` as Codex CLI keeps them.
+pub struct CodexTokenRefresher {
+    auth_file: PathBuf,
+    token_field: String,
+    token_endpoint: String,
+    client_id: String,
+    client: reqwest::Client,
+    /// Serializes refreshes within this process
+    refreshing: tokio::sync::Mutex<()>,
 }
 
-// Token exchange functionality - currently unused, kept for reference
-// The access_token from auth.json can be used directly without exchange
-//
-// /// OAuth issuer endpoint for OpenAI
-// const OAUTH_ISSUER: &str = "https://auth.openai.com";
-//
-// /// Codex CLI client ID
-// const CODEX_CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
-//
-// /// Extract the default organization ID from an OpenAI id_token JWT
-// ///
-// /// Parses the JWT payload and extracts the organization_id from the
-// /// `https://api.openai.com/auth.organizations` array, looking for the
-// /// organization with `is_default: true`.
-// ///
-// /// # Arguments
-// /// * `id_token` - The JWT id_token string
-// ///
-// /// # Returns
-// /// * `Ok(String)` - The organization ID (e.g., "org-...")
-// /// * `Err(...)` - Error parsing JWT or extracting organization
-// #[allow(dead_code)]
-// fn extract_default_org_id(id_token: &str) -> Result<String> {
-//     use base64::prelude::*;
-//
-//     // JWT format: header.payload.signature
-//     let parts: Vec<&str> = id_token.split('.').collect();
-//     if parts.len() != 3 {
-//         return Err(EgressError::ConfigError(
-//             "Invalid JWT format: expected 3 parts".to_string(),
-//         ));
-//     }
-//
-//     // Decode the payload (second part)
-//     let payload_b64 = parts[1];
-//     let payload_bytes = BASE64_URL_SAFE_NO_PAD
-//         .decode(payload_b64)
-//         .map_err(|e| EgressError::ConfigError(format!("Failed to decode JWT payload: {}", e)))?;
-//
-//     let payload_str = String::from_utf8(payload_bytes)
-//         .map_err(|e| EgressError::ConfigError(format!("Invalid UTF-8 in JWT payload: {}", e)))?;
-//
-//     // Parse JSON payload
-//     let payload: Value = serde_json::from_str(&payload_str).map_err(|e| {
-//         EgressError::ConfigError(format!("Failed to parse JWT payload JSON: {}", e))
-//     })?;
-//
-//     // Extract organizations array from https://api.openai.com/auth.organizations
-//     let organizations = payload
-//         .get("https://api.openai.com/auth")
-//         .and_then(|auth| auth.get("organizations"))
-//         .and_then(|orgs| orgs.as_array())
-//         .ok_or_else(|| {
-//             EgressError::ConfigError(
-//                 "JWT payload missing 'https://api.openai.com/auth.organizations'".to_string(),
-//             )
-//         })?;
-//
-//     // Find the default organization
-//     for org in organizations {
-//         if let Some(is_default) = org.get("is_default").and_then(|v| v.as_bool())
-//             && is_default
-//                 && let Some(org_id) = org.get("id").and_then(|v| v.as_str()) {
-//                     return Ok(org_id.to_string());
-//                 }
-//     }
-//
-//     Err(EgressError::ConfigError(
-//         "No default organization found in JWT".to_string(),
-//     ))
-// }
-//
-// /// Response from OAuth token exchange
-// #[derive(Debug, Deserialize, Serialize)]
-// struct TokenExchangeResponse {
-//     access_token: String,
-//     #[serde(default)]
-//     token_type: Option<String>,
-//     #[serde(default)]
-//     expires_in: Option<u64>,
-// }
-//
-// /// Exchange an OpenAI id_token for an OpenAI API key
-// ///
-// /// Performs OAuth token exchange using the same flow as Codex CLI login.
-// /// This exchanges an id_token (from ChatGPT OAuth) for an openai-api-key
-// /// that has the necessary scopes for the Responses API.
-// ///
-// /// # Arguments
-// /// * `id_token` - The OpenAI id_token from auth.json (tokens.id_token)
-// ///
-// /// # Returns
-// /// * `Ok(String)` - The exchanged API key
-// /// * `Err(...)` - Error performing exchange or parsing response
-// ///
-// /// # Reference
-// /// This mirrors the token exchange logic from:
-// /// https://github.com/openai/codex/blob/main/codex-rs/login/src/server.rs
-// pub async fn exchange_id_token_for_api_key(id_token: &str) -> Result<String> {
-//     debug!("Attempting to exchange id_token for OpenAI API key");
-//
-//     // Create HTTP client
-//     let client = reqwest::Client::builder()
-//         .timeout(std::time::Duration::from_secs(30))
-//         .build()
-//         .map_err(|e| EgressError::ConfigError(format!("Failed to create HTTP client: {}", e)))?;
-//
-//     // Build form data manually (exactly matches Codex CLI implementation)
-//     use serde_urlencoded::to_string as urlencode_pairs;
-//
-//     let form_params = [
-//         ("grant_type", "urn:ietf:params:oauth:grant-type:token-exchange"),
-//         ("client_id", CODEX_CLIENT_ID),
-//         ("requested_token", "openai-api-key"),
-//         ("subject_token", id_token),
-//         ("subject_token_type", "urn:ietf:params:oauth:token-type:id_token"),
-//     ];
-//
-//     let body = urlencode_pairs(form_params).map_err(|e| {
-//         EgressError::ConfigError(format!("Failed to URL encode form data: {}", e))
-//     })?;
-//
-//     // Perform token exchange
-//     let url = format!("{}/oauth/token", OAUTH_ISSUER);
-//     debug!("Sending token exchange request to: {}", url);
-//
-//     let response = client
-//         .post(&url)
-//         .header("Content-Type", "application/x-www-form-urlencoded")
-//         .body(body)
-//         .send()
-//         .await
-//         .map_err(|e| {
-//             warn!("Token exchange request failed: {}", e);
-//             EgressError::ConfigError(format!("Token exchange request failed: {}", e))
-//         })?;
-//
-//     let status = response.status();
-//     if !status.is_success() {
-//         let error_body = response
-//             .text()
-//             .await
-//             .unwrap_or_else(|_| "Unable to read error body".to_string());
-//         warn!(
-//             "Token exchange failed with status {}: {}",
-//             status, error_body
-//         );
-//         return Err(EgressError::ConfigError(format!(
-//             "Token exchange failed with status {}: {}",
-//             status, error_body
-//         )));
-//     }
-//
-//     // Parse response
-//     let exchange_response: TokenExchangeResponse = response.json().await.map_err(|e| {
-//         warn!("Failed to parse token exchange response: {}", e);
-//         EgressError::ConfigError(format!("Failed to parse token exchange response: {}", e))
-//     })?;
-//
-//     debug!("Successfully exchanged id_token for OpenAI API key");
-//     Ok(exchange_response.access_token)
-// }
+impl std::fmt::Debug for CodexTokenRefresher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CodexTokenRefresher")
+            .field("auth_file", &self.auth_file)
+            .field("token_endpoint", &self.token_endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CodexTokenRefresher {
+    /// Create a refresher for `auth_file` using the given token endpoint
+    pub fn new(
+        auth_file: impl Into<PathBuf>,
+        token_endpoint: impl Into<String>,
+        client_id: impl Into<String>,
+        client: reqwest::Client,
+    ) -> Self {
+        Self {
+            auth_file: auth_file.into(),
+            token_field: "tokens.access_token".to_string(),
+            token_endpoint: token_endpoint.into(),
+            client_id: client_id.into(),
+            client,
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Read and write the access token at `token_field` (dot notation)
+    pub fn with_token_field(mut self, token_field: impl Into<String>) -> Self {
+        self.token_field = token_field.into();
+        self
+    }
+
+    /// Obtain a fresh access token to replace `stale`
+    ///
+    /// If auth.json already holds a different, unexpired access token it is
+    /// returned without contacting the token endpoint.
+    pub async fn refresh(&self, stale: Option<&str>) -> Result<String> {
+        let _guard = self.refreshing.lock().await;
+        let path = expand_tilde(&self.auth_file)?;
+
+        let lock_path = path.clone();
+        let _lock = tokio::task::spawn_blocking(move || FileLock::acquire(&lock_path))
+            .await
+            .map_err(|e| EgressError::ConfigError(format!("Codex auth lock task failed: {}", e)))?
+            .map_err(|e| {
+                EgressError::ConfigError(format!(
+                    "Failed to lock Codex auth file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+        let read_path = path.clone();
+        let contents = tokio::task::spawn_blocking(move || fs::read_to_string(&read_path))
+            .await
+            .map_err(|e| EgressError::ConfigError(format!("Codex auth read task failed: {}", e)))?
+            .map_err(|e| {
+                EgressError::ConfigError(format!(
+                    "Failed to read Codex auth file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        let mut json: Value = serde_json::from_str(&contents).map_err(|e| {
+            EgressError::ConfigError(format!(
+                "Failed to parse Codex auth JSON from {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        if let Some(current) = extract_nested_field(&json, &self.token_field)
+            .and_then(Value::as_str)
+            .filter(|t| {
+                Some(*t) != stale && token_expiry(t).is_none_or(|exp| exp > SystemTime::now())
+            })
+        {
+            debug!("Codex access token was already refreshed by another process");
+            return Ok(current.to_string());
+        }
+
+        let refresh_token = extract_nested_field(&json, "tokens.refresh_token")
+            .and_then(Value::as_str)
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| {
+                EgressError::ConfigError(format!(
+                    "No refresh_token in Codex auth file {}; run `codex login`",
+                    path.display()
+                ))
+            })?;
+
+        debug!("Refreshing Codex access token at {}", self.token_endpoint);
+        let response = self
+            .client
+            .post(&self.token_endpoint)
+            .json(&serde_json::json!({
+                "client_id": self.client_id,
+                "grant_type": "refresh_token",
+                "refresh_token": refresh_token,
+                "scope": "openid profile email",
+            }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            warn!("Codex token refresh failed with status {}", status);
+            return Err(EgressError::ProviderError {
+                status_code: status.as_u16(),
+                message: format!("Codex token refresh failed: {}", body),
+            });
+        }
+        let refreshed: RefreshResponse = response.json().await.map_err(|e| {
+            EgressError::ParseError(format!("Invalid Codex token refresh response: {}", e))
+        })?;
+
+        // Merge into the existing file so fields we don't know about survive
+        let mut updates = vec![(
+            self.token_field.as_str(),
+            Value::String(refreshed.access_token.clone()),
+        )];
+        if let Some(id_token) = refreshed.id_token {
+            updates.push(("tokens.id_token", Value::String(id_token)));
+        }
+        if let Some(refresh_token) = refreshed.refresh_token {
+            updates.push(("tokens.refresh_token", Value::String(refresh_token)));
+        }
+        updates.push((
+            "last_refresh",
+            Value::String(chrono::Utc::now().to_rfc3339()),
+        ));
+        for (field, value) in updates {
+            set_nested_field(&mut json, field, value).ok_or_else(|| {
+                EgressError::ConfigError(format!(
+                    "Cannot write {} in Codex auth file {}: not a JSON object",
+                    field,
+                    path.display()
+                ))
+            })?;
+        }
+
+        let body = serde_json::to_string_pretty(&json)?;
+        let write_path = path.clone();
+        let write = move || -> lunaroute_storage::StorageResult<()> {
+            let mut writer = AtomicWriter::new(&write_path)?;
+            writer.write(body.as_bytes())?;
+            writer.commit()
+        };
+        tokio::task::spawn_blocking(write)
+            .await
+            .map_err(|e| EgressError::ConfigError(format!("Codex auth write task failed: {}", e)))?
+            .map_err(|e| {
+                EgressError::ConfigError(format!(
+                    "Failed to write Codex auth file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+        info!("Refreshed Codex access token in {}", path.display());
+        Ok(refreshed.access_token)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -439,6 +507,146 @@ mod tests {
         assert!(is_token_likely_expired("...")); // Empty parts
     }
 
+    fn jwt_with_exp(exp: u64) -> String {
+        use base64::prelude::*;
+        let payload = BASE64_URL_SAFE_NO_PAD.encode(format!(r#"{{"exp":{}}}"#, exp));
+        format!("eyJhbGciOiJIUzI1NiJ9.{}.c2lnbmF0dXJl", payload)
+    }
+
+    #[test]
+    fn test_token_expiry() {
+        let expired = jwt_with_exp(1_000);
+        assert_eq!(
+            token_expiry(&expired),
+            Some(UNIX_EPOCH + Duration::from_secs(1_000))
+        );
+        assert!(is_token_likely_expired(&expired));
+
+        let future = SystemTime::now() + Duration::from_secs(3600);
+        let exp = future.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert!(!is_token_likely_expired(&jwt_with_exp(exp)));
+
+        assert_eq!(token_expiry("not-a-jwt"), None);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_writes_back_auth_file() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .and(body_partial_json(serde_json::json!({
+                "grant_type": "refresh_token",
+                "refresh_token": "rt-old",
+                "client_id": CODEX_CLIENT_ID,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "at-new",
+                "refresh_token": "rt-new",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let auth_file = temp_dir.path().join("auth.json");
+        let stale = jwt_with_exp(1_000);
+        fs::write(
+            &auth_file,
+            serde_json::json!({
+                "OPENAI_API_KEY": null,
+                "tokens": {
+                    "access_token": stale,
+                    "refresh_token": "rt-old",
+                    "account_id": "acct-1"
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let refresher = CodexTokenRefresher::new(
+            &auth_file,
+            format!("{}/oauth/token", server.uri()),
+            CODEX_CLIENT_ID,
+            reqwest::Client::new(),
+        );
+        let token = refresher.refresh(Some(&stale)).await.unwrap();
+        assert_eq!(token, "at-new");
+
+        let written: Value =
+            serde_json::from_str(&fs::read_to_string(&auth_file).unwrap()).unwrap();
+        assert_eq!(written["tokens"]["access_token"], "at-new");
+        assert_eq!(written["tokens"]["refresh_token"], "rt-new");
+        assert_eq!(written["tokens"]["account_id"], "acct-1");
+        assert!(written["last_refresh"].is_string());
+
+        // The file now holds a token other than the stale one, so a second
+        // caller reuses it (the mock expects exactly one exchange)
+        let token = refresher.refresh(Some(&stale)).await.unwrap();
+        assert_eq!(token, "at-new");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_writes_configured_token_field() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "at-new",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let auth_file = temp_dir.path().join("auth.json");
+        fs::write(
+            &auth_file,
+            r#"{"oauth": {"token": "at-old"}, "tokens": {"refresh_token": "rt"}}"#,
+        )
+        .unwrap();
+
+        let refresher = CodexTokenRefresher::new(
+            &auth_file,
+            format!("{}/oauth/token", server.uri()),
+            CODEX_CLIENT_ID,
+            reqwest::Client::new(),
+        )
+        .with_token_field("oauth.token");
+        assert_eq!(refresher.refresh(Some("at-old")).await.unwrap(), "at-new");
+
+        let written: Value =
+            serde_json::from_str(&fs::read_to_string(&auth_file).unwrap()).unwrap();
+        assert_eq!(written["oauth"]["token"], "at-new");
+        assert!(written["tokens"].get("access_token").is_none());
+        assert_eq!(
+            read_codex_token(&auth_file, "oauth.token").unwrap(),
+            Some("at-new".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_without_refresh_token_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth_file = temp_dir.path().join("auth.json");
+        fs::write(&auth_file, r#"{"tokens": {"access_token": "at"}}"#).unwrap();
+
+        let refresher = CodexTokenRefresher::new(
+            &auth_file,
+            "http://127.0.0.1:1/oauth/token",
+            CODEX_CLIENT_ID,
+            reqwest::Client::new(),
+        );
+        let err = refresher.refresh(Some("at")).await.unwrap_err();
+        assert!(err.to_string().contains("refresh_token"));
+    }
+
     #[test]
     fn test_expand_tilde() {
         // Test tilde expansion
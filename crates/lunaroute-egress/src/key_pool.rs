@@ -0,0 +1,676 @@
+//! Upstream API key pools
+//!
+//! A provider can be given several upstream keys (for example one per org
+//! account). [`KeyPool`] hands them out round-robin or least-used and keeps
+//! health per key, so one key hitting its org limit shifts load to the others:
+//!
+//! - a 429, or a rate-limit window reported as exhausted, backs the key off
+//!   until the window resets
+//! - a 401 quarantines the key and fires the quarantine alert
+//!
+//! A key can also come from a [`SecretSource`] (`api_key_command` /
+//! `api_key_file`) or be a Claude subscription login ([`ClaudeOAuthAccount`]);
+//! a 401 on such a key refreshes it instead of quarantining. Both need
+//! [`KeyPool::select_fresh`] so their secret can be fetched or refreshed first.
+//!
+//! Backed-off and quarantined keys are only used when no other key is left.
+//! Keys are identified by [`key_id`], a short hash that is safe to log and to
+//! record in sessions for spend attribution.
+
+use crate::claude_oauth::ClaudeOAuthAccount;
+use crate::client::header_pairs;
+use lunaroute_core::quota::ProviderQuota;
+use lunaroute_core::secret_source::SecretSource;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, warn};
+
+/// Response header carrying the id of the upstream key that served a request
+pub const UPSTREAM_KEY_HEADER: &str = "x-lunaroute-upstream-key";
+
+/// How long a key that returned 401 is kept out of rotation
+const QUARANTINE_PERIOD: Duration = Duration::from_secs(15 * 60);
+
+/// First backoff after a 429 without a usable reset time (doubles per repeat)
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest backoff applied without a reset time from upstream
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How the next key is picked from a pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySelection {
+    /// Cycle through the keys in order
+    #[default]
+    RoundRobin,
+    /// Pick the key that has served the fewest requests
+    LeastUsed,
+}
+
+/// Called with `(provider, key_id)` when a key is quarantined
+pub type QuarantineAlert = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Stable, non-reversible identifier for an upstream key
+pub fn key_id(secret: &str) -> String {
+    let digest = Sha256::digest(secret.as_bytes());
+    let hex: String = digest
+        .iter()
+        .take(6)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("key_{}", hex)
+}
+
+/// A key handed out for one request
+#[derive(Debug, Clone)]
+pub struct PooledKey {
+    index: usize,
+    secret: String,
+    id: String,
+    oauth: bool,
+}
+
+impl PooledKey {
+    /// The key itself, for the auth header
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    /// Whether the secret is an OAuth bearer token rather than an API key
+    pub fn is_oauth(&self) -> bool {
+        self.oauth
+    }
+
+    /// Hashed key id (see [`key_id`])
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Health of one key, as reported by [`KeyPool::status`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyStatus {
+    /// Hashed key id
+    pub id: String,
+    /// Requests sent with this key since startup
+    pub requests: u64,
+    /// `healthy`, `backoff` or `quarantined`
+    pub state: &'static str,
+    /// Seconds until a backed-off or quarantined key is used again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_in_secs: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct KeyState {
+    requests: u64,
+    consecutive_limits: u32,
+    backoff_until: Option<Instant>,
+    quarantined_until: Option<Instant>,
+    quota: Option<ProviderQuota>,
+}
+
+impl KeyState {
+    fn quarantined(&self, now: Instant) -> bool {
+        self.quarantined_until.is_some_and(|until| until > now)
+    }
+
+    fn backing_off(&self, now: Instant) -> bool {
+        self.backoff_until.is_some_and(|until| until > now)
+    }
+
+    /// When the key becomes usable again (None if it is usable now)
+    fn available_at(&self, now: Instant) -> Option<Instant> {
+        [self.quarantined_until, self.backoff_until]
+            .into_iter()
+            .flatten()
+            .filter(|until| *until > now)
+            .max()
+    }
+}
+
+/// Where a pooled key's secret comes from
+enum KeySecret {
+    Static(String),
+    Source(Arc<SecretSource>),
+    OAuth(Arc<ClaudeOAuthAccount>),
+}
+
+/// Upstream keys for one provider, with per-key rate-limit and health state
+pub struct KeyPool {
+    provider: String,
+    selection: KeySelection,
+    secrets: Vec<(KeySecret, String)>,
+    state: Mutex<Vec<KeyState>>,
+    next: AtomicUsize,
+    alert: OnceLock<QuarantineAlert>,
+}
+
+impl std::fmt::Debug for KeyPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyPool")
+            .field("provider", &self.provider)
+            .field("selection", &self.selection)
+            .field(
+                "keys",
+                &self.secrets.iter().map(|(_, id)| id).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl KeyPool {
+    /// Create a pool from the configured keys (empty and duplicate keys are dropped)
+    pub fn new(provider: impl Into<String>, keys: Vec<String>, selection: KeySelection) -> Self {
+        let mut secrets: Vec<(KeySecret, String)> = Vec::with_capacity(keys.len());
+        for key in keys {
+            let duplicate = secrets
+                .iter()
+                .any(|(secret, _)| matches!(secret, KeySecret::Static(s) if *s == key));
+            if !key.is_empty() && !duplicate {
+                let id = key_id(&key);
+                secrets.push((KeySecret::Static(key), id));
+            }
+        }
+        let state = secrets.iter().map(|_| KeyState::default()).collect();
+        Self {
+            provider: provider.into(),
+            selection,
+            secrets,
+            state: Mutex::new(state),
+            next: AtomicUsize::new(0),
+            alert: OnceLock::new(),
+        }
+    }
+
+    /// Put a command- or file-backed key first in the pool
+    ///
+    /// Its id hashes the source description, so it stays stable across
+    /// refreshes.
+    pub fn with_source(mut self, source: Arc<SecretSource>) -> Self {
+        let id = key_id(&source.describe());
+        self.secrets.insert(0, (KeySecret::Source(source), id));
+        self.state
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(0, KeyState::default());
+        self
+    }
+
+    /// Add a Claude subscription account after the configured keys
+    ///
+    /// Its id hashes the credentials file path, so it stays stable across
+    /// token refreshes.
+    pub fn with_oauth_account(mut self, account: Arc<ClaudeOAuthAccount>) -> Self {
+        let id = key_id(&account.describe());
+        self.secrets.push((KeySecret::OAuth(account), id));
+        self.state
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(KeyState::default());
+        self
+    }
+
+    /// Set the callback fired when a key is quarantined (first call wins)
+    pub fn on_quarantine(&self, alert: QuarantineAlert) {
+        let _ = self.alert.set(alert);
+    }
+
+    /// Provider the keys belong to
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    /// Number of keys in the pool
+    pub fn len(&self) -> usize {
+        self.secrets.len()
+    }
+
+    /// Whether the pool has no keys
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
+    /// Pick the key for the next request
+    ///
+    /// Healthy keys are preferred; when every key is backed off or
+    /// quarantined, the one that becomes available first is used so requests
+    /// still reach upstream. Returns None for an empty pool.
+    ///
+    /// Secret sources and OAuth accounts get their cached value, which may be
+    /// stale or empty; use [`select_fresh`](Self::select_fresh) for pools that
+    /// hold them.
+    pub fn select(&self) -> Option<PooledKey> {
+        if self.secrets.is_empty() {
+            return None;
+        }
+        let picked = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let len = self.secrets.len();
+            let start = self.next.fetch_add(1, Ordering::Relaxed) % len;
+            let order = (0..len).map(|offset| (start + offset) % len);
+
+            let mut healthy = order
+                .clone()
+                .filter(|&i| !state[i].quarantined(now) && !state[i].backing_off(now));
+            let picked = match self.selection {
+                KeySelection::RoundRobin => healthy.next(),
+                KeySelection::LeastUsed => healthy.min_by_key(|&i| state[i].requests),
+            }
+            .or_else(|| order.min_by_key(|&i| state[i].available_at(now)))?;
+            state[picked].requests += 1;
+            picked
+        };
+
+        let (secret, id) = &self.secrets[picked];
+        let oauth = matches!(secret, KeySecret::OAuth(_));
+        let secret = match secret {
+            KeySecret::Static(secret) => secret.clone(),
+            KeySecret::OAuth(account) => account.cached_token().unwrap_or_default(),
+            KeySecret::Source(source) => source.cached().unwrap_or_default(),
+        };
+        Some(PooledKey {
+            index: picked,
+            secret,
+            id: id.clone(),
+            oauth,
+        })
+    }
+
+    /// Pick the key for the next request, fetching secret sources and
+    /// refreshing OAuth tokens first
+    ///
+    /// Returns None when a secret source fails with no previous value to fall
+    /// back on. An account whose token cannot be refreshed is quarantined and
+    /// the next key is tried, so one broken login doesn't fail requests while
+    /// other accounts are usable.
+    pub async fn select_fresh(&self) -> Option<PooledKey> {
+        for _ in 0..self.secrets.len() {
+            let mut key = self.select()?;
+            let account = match self.secrets.get(key.index) {
+                Some((KeySecret::OAuth(account), _)) => account,
+                Some((KeySecret::Source(source), _)) => match source.get().await {
+                    Ok(secret) => {
+                        key.secret = secret;
+                        return Some(key);
+                    }
+                    Err(e) => {
+                        error!(provider = %self.provider, key = %key.id, "{}", e);
+                        return None;
+                    }
+                },
+                _ => return Some(key),
+            };
+            match account.access_token().await {
+                Ok(token) => {
+                    key.secret = token;
+                    return Some(key);
+                }
+                Err(e) => {
+                    error!(provider = %self.provider, key = %key.id, "{}", e);
+                    self.quarantine(&key);
+                }
+            }
+        }
+        None
+    }
+
+    fn quarantine(&self, key: &PooledKey) {
+        let newly_quarantined = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(key_state) = state.get_mut(key.index) else {
+                return;
+            };
+            let now = Instant::now();
+            let newly = !key_state.quarantined(now);
+            key_state.quarantined_until = Some(now + QUARANTINE_PERIOD);
+            newly
+        };
+        if newly_quarantined {
+            error!(
+                provider = %self.provider,
+                key = %key.id,
+                "Upstream key quarantined for {}s",
+                QUARANTINE_PERIOD.as_secs()
+            );
+            if let Some(alert) = self.alert.get() {
+                alert(&self.provider, &key.id);
+            }
+        }
+    }
+
+    /// Record the outcome of a request made with `key`
+    pub fn report(&self, key: &PooledKey, status: u16, headers: &reqwest::header::HeaderMap) {
+        let quota = ProviderQuota::from_headers(header_pairs(headers));
+        if status == 401 {
+            match self.secrets.get(key.index) {
+                Some((KeySecret::Source(source), _)) => {
+                    warn!(
+                        provider = %self.provider,
+                        key = %key.id,
+                        "Upstream rejected key from {} with 401, refreshing it",
+                        source.describe()
+                    );
+                    source.invalidate();
+                    return;
+                }
+                Some((KeySecret::OAuth(account), _)) => {
+                    warn!(
+                        provider = %self.provider,
+                        key = %key.id,
+                        "Upstream rejected token from {} with 401, refreshing it",
+                        account.describe()
+                    );
+                    account.invalidate();
+                    return;
+                }
+                _ => {}
+            }
+        }
+        let mut newly_quarantined = false;
+        {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(key_state) = state.get_mut(key.index) else {
+                return;
+            };
+            let now = Instant::now();
+            if quota.is_some() {
+                key_state.quota = quota.clone();
+            }
+
+            match status {
+                401 => {
+                    newly_quarantined = !key_state.quarantined(now);
+                    key_state.quarantined_until = Some(now + QUARANTINE_PERIOD);
+                }
+                429 => {
+                    key_state.consecutive_limits = key_state.consecutive_limits.saturating_add(1);
+                    let wait = headers
+                        .get("retry-after")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(crate::parse_retry_after)
+                        .map(Duration::from_secs)
+                        .or_else(|| unified_reset_in(headers))
+                        .or_else(|| quota.as_ref().and_then(|q| q.exhausted_for()))
+                        .unwrap_or_else(|| {
+                            let exp = key_state.consecutive_limits.saturating_sub(1).min(6);
+                            (BASE_BACKOFF * 2u32.pow(exp)).min(MAX_BACKOFF)
+                        });
+                    key_state.backoff_until = Some(now + wait);
+                    warn!(
+                        provider = %self.provider,
+                        key = %key.id,
+                        backoff_secs = wait.as_secs(),
+                        "Upstream key rate limited, shifting load to other keys"
+                    );
+                }
+                200..=299 => {
+                    key_state.consecutive_limits = 0;
+                    key_state.quarantined_until = None;
+                    // Back off before the 429 when upstream says the window is spent
+                    key_state.backoff_until = quota
+                        .as_ref()
+                        .and_then(|q| q.exhausted_for())
+                        .map(|wait| now + wait);
+                }
+                _ => {}
+            }
+        }
+
+        if newly_quarantined {
+            error!(
+                provider = %self.provider,
+                key = %key.id,
+                "Upstream key rejected with 401, quarantined for {}s",
+                QUARANTINE_PERIOD.as_secs()
+            );
+            if let Some(alert) = self.alert.get() {
+                alert(&self.provider, &key.id);
+            }
+        } else {
+            debug!(provider = %self.provider, key = %key.id, status, "Recorded upstream key outcome");
+        }
+    }
+
+    /// Latest quota of the key the next request would most likely use
+    pub fn quota(&self) -> Option<ProviderQuota> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        state
+            .iter()
+            .filter(|s| s.available_at(now).is_none())
+            .filter_map(|s| s.quota.clone())
+            .find(|q| q.exhausted_for().is_none())
+            .or_else(|| state.iter().find_map(|s| s.quota.clone()))
+    }
+
+    /// Health of every key, in configured order
+    pub fn status(&self) -> Vec<KeyStatus> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        self.secrets
+            .iter()
+            .zip(state.iter())
+            .map(|((_, id), s)| KeyStatus {
+                id: id.clone(),
+                requests: s.requests,
+                state: if s.quarantined(now) {
+                    "quarantined"
+                } else if s.backing_off(now) {
+                    "backoff"
+                } else {
+                    "healthy"
+                },
+                available_in_secs: s
+                    .available_at(now)
+                    .map(|at| at.saturating_duration_since(now).as_secs()),
+            })
+            .collect()
+    }
+}
+
+/// Time until a Claude subscription usage window resets, from the
+/// `anthropic-ratelimit-unified-reset` header (Unix seconds)
+fn unified_reset_in(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let reset = headers
+        .get("anthropic-ratelimit-unified-reset")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    let reset = std::time::UNIX_EPOCH + Duration::from_secs(reset);
+    reset.duration_since(std::time::SystemTime::now()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+    use std::sync::atomic::AtomicU32;
+
+    fn pool(selection: KeySelection) -> KeyPool {
+        KeyPool::new(
+            "anthropic",
+            vec!["sk-a".into(), "sk-b".into(), "sk-c".into()],
+            selection,
+        )
+    }
+
+    #[test]
+    fn test_key_id_is_stable_and_hides_secret() {
+        let id = key_id("sk-ant-secret");
+        assert_eq!(id, key_id("sk-ant-secret"));
+        assert_ne!(id, key_id("sk-ant-other"));
+        assert!(id.starts_with("key_"));
+        assert_eq!(id.len(), 16);
+        assert!(!id.contains("secret"));
+    }
+
+    #[test]
+    fn test_new_drops_empty_and_duplicate_keys() {
+        let pool = KeyPool::new(
+            "openai",
+            vec!["a".into(), "".into(), "a".into(), "b".into()],
+            KeySelection::RoundRobin,
+        );
+        assert_eq!(pool.len(), 2);
+        assert!(
+            KeyPool::new("openai", vec![], KeySelection::RoundRobin)
+                .select()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_round_robin_cycles_keys() {
+        let pool = pool(KeySelection::RoundRobin);
+        let picked: Vec<String> = (0..6)
+            .map(|_| pool.select().unwrap().secret().to_string())
+            .collect();
+        assert_eq!(picked, ["sk-a", "sk-b", "sk-c", "sk-a", "sk-b", "sk-c"]);
+    }
+
+    #[test]
+    fn test_least_used_balances_requests() {
+        let pool = pool(KeySelection::LeastUsed);
+        for _ in 0..9 {
+            pool.select().unwrap();
+        }
+        assert!(pool.status().iter().all(|s| s.requests == 3));
+    }
+
+    #[test]
+    fn test_rate_limited_key_is_skipped_until_reset() {
+        let pool = pool(KeySelection::RoundRobin);
+        let key = pool.select().unwrap();
+        assert_eq!(key.secret(), "sk-a");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", "30".parse().unwrap());
+        pool.report(&key, 429, &headers);
+
+        for _ in 0..4 {
+            assert_ne!(pool.select().unwrap().secret(), "sk-a");
+        }
+        let status = pool.status();
+        assert_eq!(status[0].state, "backoff");
+        assert!(status[0].available_in_secs.unwrap() >= 29);
+
+        // A success clears the backoff
+        pool.report(&key, 200, &HeaderMap::new());
+        assert_eq!(pool.status()[0].state, "healthy");
+    }
+
+    #[test]
+    fn test_unauthorized_key_is_quarantined_and_alerted_once() {
+        let pool = pool(KeySelection::RoundRobin);
+        let alerts = Arc::new(AtomicU32::new(0));
+        let counter = alerts.clone();
+        pool.on_quarantine(Arc::new(move |provider, id| {
+            assert_eq!(provider, "anthropic");
+            assert_eq!(id, key_id("sk-a"));
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        let key = pool.select().unwrap();
+        pool.report(&key, 401, &HeaderMap::new());
+        pool.report(&key, 401, &HeaderMap::new());
+        assert_eq!(alerts.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.status()[0].state, "quarantined");
+
+        for _ in 0..6 {
+            assert_ne!(pool.select().unwrap().secret(), "sk-a");
+        }
+    }
+
+    #[test]
+    fn test_all_keys_unavailable_uses_first_to_recover() {
+        let pool = KeyPool::new(
+            "openai",
+            vec!["a".into(), "b".into()],
+            KeySelection::RoundRobin,
+        );
+        let a = pool.select().unwrap();
+        let b = pool.select().unwrap();
+        let mut long = HeaderMap::new();
+        long.insert("retry-after", "120".parse().unwrap());
+        let mut short = HeaderMap::new();
+        short.insert("retry-after", "5".parse().unwrap());
+        pool.report(&a, 429, &long);
+        pool.report(&b, 429, &short);
+
+        assert_eq!(pool.select().unwrap().secret(), "b");
+        assert_eq!(pool.select().unwrap().secret(), "b");
+    }
+
+    #[test]
+    fn test_exhausted_quota_backs_off_before_429() {
+        let pool = pool(KeySelection::RoundRobin);
+        let key = pool.select().unwrap();
+        let reset = (chrono::Utc::now() + chrono::Duration::seconds(20)).to_rfc3339();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "anthropic-ratelimit-requests-remaining",
+            "0".parse().unwrap(),
+        );
+        headers.insert("anthropic-ratelimit-requests-reset", reset.parse().unwrap());
+        pool.report(&key, 200, &headers);
+
+        assert_eq!(pool.status()[0].state, "backoff");
+        assert_ne!(pool.select().unwrap().secret(), "sk-a");
+    }
+
+    #[tokio::test]
+    async fn test_select_fresh_skips_broken_oauth_account() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // No credentials file: the account can't produce a token
+        let broken = Arc::new(ClaudeOAuthAccount::new(
+            dir.path().join("missing.json"),
+            reqwest::Client::new(),
+        ));
+        let pool = KeyPool::new("anthropic", vec!["sk-a".into()], KeySelection::RoundRobin)
+            .with_oauth_account(broken);
+        let alerts = Arc::new(AtomicU32::new(0));
+        let counter = alerts.clone();
+        pool.on_quarantine(Arc::new(move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }));
+
+        for _ in 0..3 {
+            let key = pool.select_fresh().await.unwrap();
+            assert_eq!(key.secret(), "sk-a");
+            assert!(!key.is_oauth());
+        }
+        assert_eq!(pool.status()[1].state, "quarantined");
+        assert_eq!(alerts.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_source_key_refreshes_after_401_instead_of_quarantine() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("key");
+        std::fs::write(&path, "sk-old\n").unwrap();
+        let source = Arc::new(SecretSource::file(&path));
+        let pool =
+            KeyPool::new("anthropic", vec![], KeySelection::RoundRobin).with_source(source.clone());
+        assert_eq!(pool.len(), 1);
+
+        let key = pool.select_fresh().await.unwrap();
+        assert_eq!(key.secret(), "sk-old");
+        assert!(!format!("{:?}", pool).contains("sk-old"));
+
+        std::fs::write(&path, "sk-new\n").unwrap();
+        pool.report(&key, 401, &HeaderMap::new());
+
+        assert_eq!(pool.status()[0].state, "healthy");
+        let key = pool.select_fresh().await.unwrap();
+        assert_eq!(key.secret(), "sk-new");
+        assert_eq!(key.id(), key_id(&source.describe()));
+    }
+}
@@ -2,19 +2,22 @@
 //!
 //! This crate provides connectors to downstream LLM providers:
 //! - OpenAI connector
-//! - Anthropic connector
+//! - Anthropic connector (API keys or Claude subscription OAuth)
 
 use thiserror::Error;
 
 pub mod anthropic;
+pub mod claude_oauth;
 pub mod client;
 pub mod codex_auth;
 pub mod codex_headers;
+pub mod key_pool;
 pub mod openai;
 mod retry_after;
 
 // Re-export commonly used types
 pub use client::HttpClientConfig;
+pub use key_pool::{KeyPool, KeySelection};
 pub use retry_after::parse_retry_after;
 
 /// Egress-specific errors
@@ -63,6 +66,11 @@ impl From<EgressError> for lunaroute_core::Error {
             EgressError::RateLimitExceeded { retry_after_secs } => {
                 lunaroute_core::Error::RateLimitExceeded { retry_after_secs }
             }
+            // reqwest only names the timeout in the error source; keep it visible
+            // for error classification
+            EgressError::HttpError(e) if e.is_timeout() => {
+                lunaroute_core::Error::Provider(format!("Request timed out: {}", e))
+            }
             other => lunaroute_core::Error::Provider(other.to_string()),
         }
     }
@@ -1,8 +1,10 @@
 //! OpenAI egress connector
 
+use crate::codex_auth::{CodexTokenRefresher, token_expiry};
 use crate::{
     EgressError, Result,
-    client::{HttpClientConfig, create_client, with_retry},
+    client::{HttpClientConfig, create_client, header_pairs, with_retry},
+    key_pool::{KeyPool, PooledKey, UPSTREAM_KEY_HEADER},
 };
 use async_trait::async_trait;
 use futures::Stream;
@@ -13,12 +15,14 @@ use lunaroute_core::{
         Usage,
     },
     provider::{Provider, ProviderCapabilities},
+    quota::{ProviderQuota, QuotaTracker},
 };
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::pin::Pin;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, SystemTime};
 use tracing::{debug, instrument, warn};
 
 /// Codex authentication configuration
@@ -38,6 +42,20 @@ pub struct CodexAuthConfig {
     /// If set, will override client's chatgpt-account-id header
     /// If not set, client's header will pass through unchanged
     pub account_id: Option<String>,
+
+    /// Refresh the access token with the stored refresh token before it
+    /// expires or after upstream rejects it, writing it back to auth_file
+    pub auto_refresh: bool,
+
+    /// OAuth token endpoint used for refresh
+    /// (default: https://auth.openai.com/oauth/token)
+    pub token_endpoint: String,
+
+    /// OAuth client ID used for refresh (default: Codex CLI's client ID)
+    pub client_id: String,
+
+    /// How long before the token's expiry to refresh it (default: 5 minutes)
+    pub refresh_before_expiry: Duration,
 }
 
 impl Default for CodexAuthConfig {
@@ -47,10 +65,20 @@ impl Default for CodexAuthConfig {
             auth_file: PathBuf::from("~/.codex/auth.json"),
             token_field: "tokens.access_token".to_string(),
             account_id: None,
+            auto_refresh: true,
+            token_endpoint: crate::codex_auth::DEFAULT_TOKEN_ENDPOINT.to_string(),
+            client_id: crate::codex_auth::CODEX_CLIENT_ID.to_string(),
+            refresh_before_expiry: Duration::from_secs(300),
         }
     }
 }
 
+/// How often the refresh task re-reads a token whose expiry is unknown or far off
+const CODEX_REFRESH_RECHECK: Duration = Duration::from_secs(300);
+
+/// Delay before retrying a failed proactive refresh
+const CODEX_REFRESH_RETRY: Duration = Duration::from_secs(60);
+
 /// OpenAI connector configuration
 #[derive(Debug, Clone)]
 pub struct OpenAIConfig {
@@ -135,18 +163,68 @@ impl OpenAIConfig {
     }
 }
 
+fn store_codex_token(cache: &RwLock<Option<String>>, token: &str) {
+    if let Ok(mut guard) = cache.write() {
+        *guard = Some(token.to_string());
+    }
+}
+
+/// Refresh the cached Codex token `before` its expiry until the connector
+/// (the only strong owner of the cache) is dropped
+fn spawn_codex_refresh_task(
+    cache: Weak<RwLock<Option<String>>>,
+    refresher: Arc<CodexTokenRefresher>,
+    before: Duration,
+) {
+    tokio::spawn(async move {
+        while let Some(strong) = cache.upgrade() {
+            let token = strong.read().ok().and_then(|guard| guard.clone());
+            let refresh_in = token
+                .as_deref()
+                .and_then(token_expiry)
+                .map(|exp| {
+                    exp.checked_sub(before)
+                        .and_then(|at| at.duration_since(SystemTime::now()).ok())
+                        .unwrap_or(Duration::ZERO)
+                })
+                .unwrap_or(CODEX_REFRESH_RECHECK);
+
+            if refresh_in.is_zero() {
+                match refresher.refresh(token.as_deref()).await {
+                    Ok(token) => store_codex_token(&strong, &token),
+                    Err(e) => warn!("Proactive Codex token refresh failed: {}", e),
+                }
+                // Pause either way so a short-lived token or a failing
+                // endpoint can't turn this into a busy loop
+                drop(strong);
+                tokio::time::sleep(CODEX_REFRESH_RETRY).await;
+                continue;
+            }
+            drop(strong);
+            tokio::time::sleep(refresh_in.min(CODEX_REFRESH_RECHECK)).await;
+        }
+    });
+}
+
 /// OpenAI connector
 pub struct OpenAIConnector {
     config: OpenAIConfig,
     client: Client,
     codex_token_cache: Option<Arc<RwLock<Option<String>>>>,
+    /// Refreshes the Codex token when auto_refresh is enabled
+    codex_refresher: Option<Arc<CodexTokenRefresher>>,
+    /// Quota from the latest rate-limit response headers
+    quota: QuotaTracker,
+    /// Upstream keys (None when requests carry the client's credentials)
+    keys: Option<Arc<KeyPool>>,
 }
 
 impl OpenAIConnector {
     /// Create a new OpenAI connector
     ///
     /// If Codex authentication is enabled, reads and caches the access_token
-    /// from auth.json at startup.
+    /// from auth.json at startup, and with auto_refresh starts a task that
+    /// refreshes it shortly before it expires.
     pub async fn new(config: OpenAIConfig) -> Result<Self> {
         let client = create_client(&config.client_config)?;
 
@@ -193,13 +271,56 @@ impl OpenAIConnector {
             None
         };
 
+        let codex_refresher = match (&config.codex_auth, &codex_token_cache) {
+            (Some(codex_auth), Some(cache)) if codex_auth.auto_refresh => {
+                let refresher = Arc::new(
+                    CodexTokenRefresher::new(
+                        codex_auth.auth_file.clone(),
+                        codex_auth.token_endpoint.clone(),
+                        codex_auth.client_id.clone(),
+                        client.clone(),
+                    )
+                    .with_token_field(codex_auth.token_field.clone()),
+                );
+                spawn_codex_refresh_task(
+                    Arc::downgrade(cache),
+                    refresher.clone(),
+                    codex_auth.refresh_before_expiry,
+                );
+                Some(refresher)
+            }
+            _ => None,
+        };
+
+        let keys = (!config.api_key.is_empty()).then(|| {
+            Arc::new(KeyPool::new(
+                "openai",
+                vec![config.api_key.clone()],
+                Default::default(),
+            ))
+        });
+
         Ok(Self {
             config,
             client,
             codex_token_cache,
+            codex_refresher,
+            quota: QuotaTracker::new(),
+            keys,
         })
     }
 
+    /// Use a pool of upstream keys instead of the single configured `api_key`
+    pub fn with_key_pool(mut self, pool: Arc<KeyPool>) -> Self {
+        self.keys = (!pool.is_empty()).then_some(pool);
+        self
+    }
+
+    /// Upstream key pool, if the connector authenticates with its own keys
+    pub fn key_pool(&self) -> Option<&Arc<KeyPool>> {
+        self.keys.as_ref()
+    }
+
     /// Get Codex authentication token (with caching)
     ///
     /// Reads token from configured Codex auth file and caches it in memory.
@@ -213,9 +334,11 @@ impl OpenAIConnector {
 
         let cache = self.codex_token_cache.as_ref()?;
 
-        // Try to read from cache first
+        // Try to read from cache first; an expired token is re-read from the
+        // file in case Codex CLI refreshed it
         if let Ok(cache_guard) = cache.read()
             && let Some(ref token) = *cache_guard
+            && token_expiry(token).is_none_or(|exp| exp > SystemTime::now())
         {
             debug!("Using cached Codex token");
             return Some(token.clone());
@@ -313,30 +436,89 @@ impl OpenAIConnector {
     ///
     /// Priority order:
     /// 1. Codex auth token (if enabled and available)
-    /// 2. Configured API key (if not empty)
+    /// 2. Configured API key from the key pool (returned alongside the header)
     /// 3. None (no fallback available)
-    fn get_fallback_auth_header(&self) -> Option<String> {
+    ///
+    /// The flag is set when the header carries the Codex token.
+    async fn fallback_auth(&self) -> (Option<String>, Option<PooledKey>, bool) {
         // 1. Try Codex auth first
         if let Some(token) = self.get_codex_token() {
             debug!("Using Codex authentication as fallback");
-            return Some(format!("Bearer {}", token));
+            return (Some(format!("Bearer {}", token)), None, true);
         }
 
         // 2. Fall back to configured API key
-        if !self.config.api_key.is_empty() {
-            debug!("Using configured API key as fallback");
-            return Some(format!("Bearer {}", self.config.api_key));
+        let key = match &self.keys {
+            Some(pool) => pool.select_fresh().await,
+            None => None,
+        };
+        if let Some(key) = key {
+            debug!("Using configured API key {} as fallback", key.id());
+            return (Some(format!("Bearer {}", key.secret())), Some(key), false);
         }
 
         // 3. No fallback auth available
         debug!("No fallback auth available");
-        None
+        (None, None, false)
     }
 
     /// Check if proxy has a configured API key that should override client auth
     /// (Note: Codex auth is a fallback, not an override - client auth takes precedence)
     fn has_override_auth(&self) -> bool {
-        !self.config.api_key.is_empty()
+        self.keys.is_some()
+    }
+
+    /// Refresh the Codex access token now, replacing the cached one
+    ///
+    /// Returns an error when Codex auth or auto_refresh is disabled.
+    pub async fn refresh_codex_token(&self) -> Result<String> {
+        let (Some(refresher), Some(cache)) = (&self.codex_refresher, &self.codex_token_cache)
+        else {
+            return Err(EgressError::ConfigError(
+                "Codex token refresh is not enabled".to_string(),
+            ));
+        };
+        let stale = cache.read().ok().and_then(|guard| guard.clone());
+        let token = refresher.refresh(stale.as_deref()).await?;
+        store_codex_token(cache, &token);
+        Ok(token)
+    }
+
+    /// Record quota and key health from a response and tag it with the key id
+    ///
+    /// A 401 on a request that carried the Codex token may mean the token
+    /// was revoked early, so a background refresh is started.
+    fn observe_response(
+        &self,
+        key: Option<&PooledKey>,
+        codex_token: bool,
+        response: &mut reqwest::Response,
+    ) {
+        self.quota.observe(header_pairs(response.headers()));
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED
+            && codex_token
+            && let (Some(refresher), Some(cache)) = (&self.codex_refresher, &self.codex_token_cache)
+        {
+            let stale = cache.read().ok().and_then(|guard| guard.clone());
+            let refresher = refresher.clone();
+            let cache = Arc::downgrade(cache);
+            tokio::spawn(async move {
+                match refresher.refresh(stale.as_deref()).await {
+                    Ok(token) => {
+                        if let Some(cache) = cache.upgrade() {
+                            store_codex_token(&cache, &token);
+                        }
+                    }
+                    Err(e) => warn!("Codex token refresh after 401 failed: {}", e),
+                }
+            });
+        }
+        if let (Some(pool), Some(key)) = (&self.keys, key) {
+            pool.report(key, response.status().as_u16(), response.headers());
+            if let Ok(value) = reqwest::header::HeaderValue::from_str(key.id()) {
+                response.headers_mut().insert(UPSTREAM_KEY_HEADER, value);
+            }
+        }
     }
 
     /// Send a raw JSON request directly to OpenAI (passthrough mode)
@@ -443,11 +625,15 @@ impl OpenAIConnector {
                     request_builder = request_builder.header(name, value);
                 }
 
-                // Use fallback auth only if client didn't provide auth
-                if !client_provided_auth
-                    && let Some(auth_header) = self.get_fallback_auth_header() {
-                        request_builder = request_builder.header("Authorization", auth_header);
-                    }
+                // Use configured auth unless the client's own header is forwarded
+                let (auth_header, key, codex_token) = if client_provided_auth && !has_override_auth {
+                    (None, None, false)
+                } else {
+                    self.fallback_auth().await
+                };
+                if let Some(auth_header) = auth_header {
+                    request_builder = request_builder.header("Authorization", auth_header);
+                }
 
                 // Add chatgpt-account-id header if configured or available from auth.json
                 if let Some(account_id) = self.get_codex_account_id() {
@@ -466,7 +652,8 @@ impl OpenAIConnector {
                 // Send raw JSON body without .json() to avoid modifying headers
                 let json_string = serde_json::to_string(&request_json)?;
 
-                let response = request_builder.body(json_string).send().await?;
+                let mut response = request_builder.body(json_string).send().await?;
+                self.observe_response(key.as_ref(), codex_token, &mut response);
 
                 debug!("┌─────────────────────────────────────────────────────────");
                 debug!("│ OpenAI Passthrough Response Headers");
@@ -545,11 +732,15 @@ impl OpenAIConnector {
                     request_builder = request_builder.header(name, value);
                 }
 
-                // Use fallback auth only if client didn't provide auth
-                if !client_provided_auth
-                    && let Some(auth_header) = self.get_fallback_auth_header() {
-                        request_builder = request_builder.header("Authorization", auth_header);
-                    }
+                // Use configured auth unless the client's own header is forwarded
+                let (auth_header, key, codex_token) = if client_provided_auth && !has_override_auth {
+                    (None, None, false)
+                } else {
+                    self.fallback_auth().await
+                };
+                if let Some(auth_header) = auth_header {
+                    request_builder = request_builder.header("Authorization", auth_header);
+                }
 
                 // Add chatgpt-account-id header if configured or available from auth.json
                 if let Some(account_id) = self.get_codex_account_id() {
@@ -569,7 +760,8 @@ impl OpenAIConnector {
                 // request_builder = request_builder.apply_organization_header(&config);
 
                 // Send raw body bytes without any parsing/re-serialization
-                let response = request_builder.body(body).send().await?;
+                let mut response = request_builder.body(body).send().await?;
+                self.observe_response(key.as_ref(), codex_token, &mut response);
 
                 // Log response headers at debug level
                 debug!("┌─────────────────────────────────────────────────────────");
@@ -709,8 +901,13 @@ impl OpenAIConnector {
             request_builder = request_builder.header(name, value);
         }
 
-        // Use fallback auth only if client didn't provide auth
-        if !client_provided_auth && let Some(auth_header) = self.get_fallback_auth_header() {
+        // Use configured auth unless the client's own header is forwarded
+        let (auth_header, key, codex_token) = if client_provided_auth && !has_override_auth {
+            (None, None, false)
+        } else {
+            self.fallback_auth().await
+        };
+        if let Some(auth_header) = auth_header {
             request_builder = request_builder.header("Authorization", auth_header);
         }
 
@@ -731,11 +928,12 @@ impl OpenAIConnector {
         // Send raw JSON body without .json() to avoid modifying headers
         let json_string = serde_json::to_string(&request_json)?;
 
-        let response = request_builder
+        let mut response = request_builder
             .body(json_string)
             .send()
             .await
             .map_err(EgressError::from)?;
+        self.observe_response(key.as_ref(), codex_token, &mut response);
 
         debug!("┌─────────────────────────────────────────────────────────");
         debug!("│ OpenAI Streaming Passthrough Response Headers");
@@ -814,8 +1012,13 @@ impl OpenAIConnector {
             request_builder = request_builder.header(name, value);
         }
 
-        // Use fallback auth only if client didn't provide auth
-        if !client_provided_auth && let Some(auth_header) = self.get_fallback_auth_header() {
+        // Use configured auth unless the client's own header is forwarded
+        let (auth_header, key, codex_token) = if client_provided_auth && !has_override_auth {
+            (None, None, false)
+        } else {
+            self.fallback_auth().await
+        };
+        if let Some(auth_header) = auth_header {
             request_builder = request_builder.header("Authorization", auth_header);
         }
 
@@ -833,7 +1036,8 @@ impl OpenAIConnector {
                 .header("originator", crate::codex_headers::CODEX_ORIGINATOR);
         }
 
-        let response = request_builder.send().await?;
+        let mut response = request_builder.send().await?;
+        self.observe_response(key.as_ref(), codex_token, &mut response);
 
         if !response.status().is_success() {
             let status = response.status();
@@ -905,8 +1109,13 @@ impl OpenAIConnector {
             request_builder = request_builder.header(name, value);
         }
 
-        // Use fallback auth only if client didn't provide auth
-        if !client_provided_auth && let Some(auth_header) = self.get_fallback_auth_header() {
+        // Use configured auth unless the client's own header is forwarded
+        let (auth_header, key, codex_token) = if client_provided_auth && !has_override_auth {
+            (None, None, false)
+        } else {
+            self.fallback_auth().await
+        };
+        if let Some(auth_header) = auth_header {
             debug!("Using fallback authentication for OpenAI");
             request_builder = request_builder.header("Authorization", auth_header);
         }
@@ -945,11 +1154,12 @@ impl OpenAIConnector {
         // request_builder = request_builder.apply_organization_header(&self.config);
 
         // Send raw body bytes without any parsing/re-serialization
-        let response = request_builder
+        let mut response = request_builder
             .body(body)
             .send()
             .await
             .map_err(EgressError::from)?;
+        self.observe_response(key.as_ref(), codex_token, &mut response);
 
         // Log response headers at debug level
         debug!("┌─────────────────────────────────────────────────────────");
@@ -1166,7 +1376,8 @@ impl Provider for OpenAIConnector {
                         .apply_organization_header(&self.config);
 
                     // Apply fallback authentication (Codex auth → Configured API key)
-                    if let Some(auth_header) = self.get_fallback_auth_header() {
+                    let (auth_header, key, codex_token) = self.fallback_auth().await;
+                    if let Some(auth_header) = auth_header {
                         request_builder = request_builder.header("Authorization", auth_header);
                     }
 
@@ -1175,7 +1386,8 @@ impl Provider for OpenAIConnector {
                         request_builder = request_builder.header(name, value);
                     }
 
-                    let response = request_builder.json(&request_json).send().await?;
+                    let mut response = request_builder.json(&request_json).send().await?;
+                    self.observe_response(key.as_ref(), codex_token, &mut response);
 
                     // Log response headers at debug level
                     debug!("┌─────────────────────────────────────────────────────────");
@@ -1219,11 +1431,13 @@ impl Provider for OpenAIConnector {
                         .apply_organization_header(&self.config);
 
                     // Apply fallback authentication (Codex auth → Configured API key)
-                    if let Some(auth_header) = self.get_fallback_auth_header() {
+                    let (auth_header, key, codex_token) = self.fallback_auth().await;
+                    if let Some(auth_header) = auth_header {
                         request_builder = request_builder.header("Authorization", auth_header);
                     }
 
-                    let response = request_builder.json(&openai_req).send().await?;
+                    let mut response = request_builder.json(&openai_req).send().await?;
+                    self.observe_response(key.as_ref(), codex_token, &mut response);
 
                     debug!("┌─────────────────────────────────────────────────────────");
                     debug!("│ OpenAI Response Headers");
@@ -1302,7 +1516,8 @@ impl Provider for OpenAIConnector {
             .apply_organization_header(&self.config);
 
         // Apply fallback authentication (Codex auth → Configured API key)
-        if let Some(auth_header) = self.get_fallback_auth_header() {
+        let (auth_header, key, codex_token) = self.fallback_auth().await;
+        if let Some(auth_header) = auth_header {
             request_builder = request_builder.header("Authorization", auth_header);
         }
 
@@ -1311,11 +1526,12 @@ impl Provider for OpenAIConnector {
             request_builder = request_builder.header(name, value);
         }
 
-        let response = request_builder
+        let mut response = request_builder
             .json(&request_json)
             .send()
             .await
             .map_err(EgressError::from)?;
+        self.observe_response(key.as_ref(), codex_token, &mut response);
 
         // Log response headers at debug level
         debug!("┌─────────────────────────────────────────────────────────");
@@ -1357,6 +1573,36 @@ impl Provider for OpenAIConnector {
     fn get_notification_message(&self) -> Option<&str> {
         self.config.switch_notification_message.as_deref()
     }
+
+    fn quota(&self) -> Option<ProviderQuota> {
+        // With several keys, the provider is only as constrained as its best key
+        match &self.keys {
+            Some(pool) if pool.len() > 1 => pool.quota(),
+            _ => self.quota.get(),
+        }
+    }
+
+    async fn health_check(&self) -> Option<lunaroute_core::Result<()>> {
+        // The Codex backend has no models listing, and without a configured
+        // key requests carry the client's credentials
+        if self.uses_codex_auth() {
+            return None;
+        }
+        let key = self.keys.as_ref()?.select_fresh().await?;
+
+        let result = async {
+            let mut response = self
+                .client
+                .get(format!("{}/models", self.config.base_url))
+                .bearer_auth(key.secret())
+                .send()
+                .await?;
+            self.observe_response(Some(&key), false, &mut response);
+            crate::client::check_probe_response(response).await
+        }
+        .await;
+        Some(result.map_err(Into::into))
+    }
 }
 
 // OpenAI API types (simplified, matching ingress types)
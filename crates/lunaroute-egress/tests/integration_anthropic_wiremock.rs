@@ -411,3 +411,64 @@ async fn test_anthropic_send_with_tool_calls() {
         "get_weather"
     );
 }
+
+#[tokio::test]
+async fn test_anthropic_passthrough_quarantines_rejected_pool_key() {
+    use lunaroute_egress::key_pool::{KeyPool, KeySelection, UPSTREAM_KEY_HEADER, key_id};
+    use std::sync::Arc;
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("x-api-key", "revoked-key"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "type": "error",
+            "error": {"type": "authentication_error", "message": "invalid x-api-key"}
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("x-api-key", "good-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "msg_123",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-3-opus",
+            "content": [{"type": "text", "text": "ok"}],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 1, "output_tokens": 1}
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let pool = Arc::new(KeyPool::new(
+        "anthropic",
+        vec!["revoked-key".to_string(), "good-key".to_string()],
+        KeySelection::RoundRobin,
+    ));
+    let connector =
+        AnthropicConnector::new(AnthropicConfig::new("").with_base_url(mock_server.uri()))
+            .unwrap()
+            .with_key_pool(pool.clone());
+
+    let body = serde_json::json!({"model": "claude-3-opus", "max_tokens": 10, "messages": []});
+    let (status, _, headers) = connector
+        .send_passthrough(body.clone(), Default::default())
+        .await
+        .unwrap();
+    assert_eq!(status, 401);
+    assert_eq!(headers[UPSTREAM_KEY_HEADER], key_id("revoked-key"));
+
+    // The revoked key stays out of rotation
+    for _ in 0..3 {
+        let (status, _, headers) = connector
+            .send_passthrough(body.clone(), Default::default())
+            .await
+            .unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(headers[UPSTREAM_KEY_HEADER], key_id("good-key"));
+    }
+    assert_eq!(pool.status()[0].state, "quarantined");
+}
@@ -501,6 +501,7 @@ async fn test_openai_codex_auth_with_valid_token() {
             enabled: true,
             auth_file,
             token_field: "tokens.access_token".to_string(),
+            ..Default::default()
         }),
         switch_notification_message: None,
     };
@@ -583,6 +584,7 @@ async fn test_openai_codex_auth_fallback_to_api_key() {
             enabled: true,
             auth_file,
             token_field: "tokens.access_token".to_string(),
+            ..Default::default()
         }),
         switch_notification_message: None,
     };
@@ -672,6 +674,7 @@ async fn test_openai_codex_auth_disabled() {
             enabled: false, // Disabled
             auth_file,
             token_field: "tokens.access_token".to_string(),
+            ..Default::default()
         }),
         switch_notification_message: None,
     };
@@ -760,6 +763,7 @@ async fn test_openai_codex_auth_with_invalid_json() {
             enabled: true,
             auth_file,
             token_field: "tokens.access_token".to_string(),
+            ..Default::default()
         }),
         switch_notification_message: None,
     };
@@ -850,6 +854,7 @@ async fn test_openai_codex_auth_flat_json_structure() {
             enabled: true,
             auth_file,
             token_field: "access_token".to_string(), // Flat path
+            ..Default::default()
         }),
         switch_notification_message: None,
     };
@@ -883,3 +888,220 @@ async fn test_openai_codex_auth_flat_json_structure() {
         MessageContent::Text("Response with flat token".to_string())
     );
 }
+
+#[tokio::test]
+async fn test_openai_codex_auth_refreshes_expired_token() {
+    use base64::prelude::*;
+    use lunaroute_egress::openai::CodexAuthConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    let mock_server = MockServer::start().await;
+    let temp_dir = TempDir::new().unwrap();
+    let auth_file = temp_dir.path().join("auth.json");
+
+    // Access token whose exp claim is long past
+    let expired = format!(
+        "eyJhbGciOiJIUzI1NiJ9.{}.c2lnbmF0dXJl",
+        BASE64_URL_SAFE_NO_PAD.encode(r#"{"exp":1000}"#)
+    );
+    fs::write(
+        &auth_file,
+        serde_json::json!({
+            "tokens": {"access_token": expired, "refresh_token": "rt-1"}
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "fresh-token",
+            "refresh_token": "rt-2"
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Mock expects the refreshed token
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("authorization", "Bearer fresh-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "created": 1234567890,
+            "model": "gpt-4",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "Response with refreshed token"
+                },
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": 10,
+                "completion_tokens": 5,
+                "total_tokens": 15
+            }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let config = OpenAIConfig {
+        api_key: String::new(),
+        base_url: mock_server.uri(),
+        organization: None,
+        client_config: Default::default(),
+        custom_headers: None,
+        request_body_config: None,
+        response_body_config: None,
+        codex_auth: Some(CodexAuthConfig {
+            enabled: true,
+            auth_file: auth_file.clone(),
+            token_endpoint: format!("{}/oauth/token", mock_server.uri()),
+            ..Default::default()
+        }),
+        switch_notification_message: None,
+    };
+    let connector = OpenAIConnector::new(config).await.unwrap();
+
+    // The refresh token is exchanged once, whether the background task or
+    // this call gets there first
+    let token = connector.refresh_codex_token().await.unwrap();
+    assert_eq!(token, "fresh-token");
+    let written: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&auth_file).unwrap()).unwrap();
+    assert_eq!(written["tokens"]["refresh_token"], "rt-2");
+
+    let request = NormalizedRequest {
+        messages: vec![Message {
+            role: Role::User,
+            content: MessageContent::Text("Test".to_string()),
+            name: None,
+            tool_calls: vec![],
+            tool_call_id: None,
+        }],
+        system: None,
+        model: "gpt-4".to_string(),
+        max_tokens: None,
+        temperature: None,
+        top_p: None,
+        top_k: None,
+        stop_sequences: vec![],
+        stream: false,
+        tools: vec![],
+        tool_results: vec![],
+        tool_choice: None,
+        metadata: std::collections::HashMap::new(),
+    };
+
+    let response = connector.send(request).await.unwrap();
+    assert_eq!(
+        response.choices[0].message.content,
+        MessageContent::Text("Response with refreshed token".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_openai_client_auth_401_keeps_codex_token() {
+    use base64::prelude::*;
+    use lunaroute_egress::openai::CodexAuthConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    let mock_server = MockServer::start().await;
+    let temp_dir = TempDir::new().unwrap();
+    let auth_file = temp_dir.path().join("auth.json");
+
+    let valid = format!(
+        "eyJhbGciOiJIUzI1NiJ9.{}.c2lnbmF0dXJl",
+        BASE64_URL_SAFE_NO_PAD.encode(r#"{"exp":4102444800}"#)
+    );
+    fs::write(
+        &auth_file,
+        serde_json::json!({
+            "tokens": {"access_token": valid, "refresh_token": "rt-1"}
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "fresh-token",
+            "refresh_token": "rt-2"
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "error": {"message": "Invalid API key", "type": "invalid_request_error"}
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let config = OpenAIConfig {
+        api_key: String::new(),
+        base_url: mock_server.uri(),
+        organization: None,
+        client_config: Default::default(),
+        custom_headers: None,
+        request_body_config: None,
+        response_body_config: None,
+        codex_auth: Some(CodexAuthConfig {
+            enabled: true,
+            auth_file: auth_file.clone(),
+            token_endpoint: format!("{}/oauth/token", mock_server.uri()),
+            ..Default::default()
+        }),
+        switch_notification_message: None,
+    };
+    let connector = OpenAIConnector::new(config).await.unwrap();
+    let body = serde_json::json!({
+        "model": "gpt-4",
+        "messages": [{"role": "user", "content": "Test"}]
+    });
+    let token_requests = || async {
+        mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|r| r.url.path() == "/oauth/token")
+            .count()
+    };
+
+    // The client's own key was rejected; the Codex token is left alone
+    let client_headers = std::collections::HashMap::from([(
+        "authorization".to_string(),
+        "Bearer sk-client".to_string(),
+    )]);
+    assert!(
+        connector
+            .send_passthrough(body.clone(), client_headers)
+            .await
+            .is_err()
+    );
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(token_requests().await, 0);
+
+    // A 401 to the Codex token itself starts a refresh
+    assert!(
+        connector
+            .send_passthrough(body, Default::default())
+            .await
+            .is_err()
+    );
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        while token_requests().await == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Codex token was not refreshed");
+}
@@ -3,7 +3,7 @@
 use crate::types::{IngressError, IngressResult};
 use axum::{
     Router,
-    extract::{Json, State},
+    extract::{Extension, Json, State},
     response::{
         IntoResponse, Response,
         sse::{Event, KeepAlive, Sse},
@@ -39,7 +39,7 @@ const MAX_COLLECTED_EVENTS: usize = 10_000;
 /// Returns `None` if no shape matches or the candidate fails validation
 /// (length / charset). Validation enforces alphanumeric, dash, underscore
 /// only to prevent path traversal in downstream session-id consumers.
-fn extract_session_id_from_user_id(value: &serde_json::Value) -> Option<String> {
+pub(crate) fn extract_session_id_from_user_id(value: &serde_json::Value) -> Option<String> {
     // Shape 1: object literal
     if let Some(obj) = value.as_object() {
         let candidate = obj.get("session_id").and_then(|v| v.as_str())?;
@@ -174,6 +174,8 @@ pub struct AnthropicMessagesRequest {
     pub stop_sequences: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<AnthropicTool>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
 }
 
 /// Anthropic message
@@ -650,6 +652,20 @@ pub fn to_normalized(req: AnthropicMessagesRequest) -> IngressResult<NormalizedR
         }
     });
 
+    // Carry the client session through for per-session routing decisions
+    let mut metadata = std::collections::HashMap::new();
+    if let Some(session_id) = req
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("user_id"))
+        .and_then(extract_session_id_from_user_id)
+    {
+        metadata.insert(
+            "session_id".to_string(),
+            serde_json::Value::String(session_id),
+        );
+    }
+
     Ok(NormalizedRequest {
         messages: messages?,
         system,
@@ -663,7 +679,7 @@ pub fn to_normalized(req: AnthropicMessagesRequest) -> IngressResult<NormalizedR
         tools,
         tool_choice: None, // Anthropic doesn't have tool_choice in same way
         tool_results,      // Tool results extracted from messages
-        metadata: std::collections::HashMap::new(),
+        metadata,
     })
 }
 
@@ -1006,14 +1022,27 @@ fn stream_event_to_anthropic_events(
 /// Messages handler
 pub async fn messages(
     State(provider): State<Arc<dyn Provider>>,
+    auth_key: Option<Extension<crate::auth::AuthenticatedKey>>,
+    identity: Option<Extension<crate::auth::ClientIdentity>>,
+    tenant: Option<Extension<lunaroute_core::tenant::TenantId>>,
     Json(req): Json<AnthropicMessagesRequest>,
 ) -> Result<Response, IngressError> {
     let start_time = std::time::Instant::now();
     let is_streaming = req.stream.unwrap_or(false);
     let model = req.model.clone();
+    let auth_key = auth_key.as_ref().map(|Extension(k)| k);
+    crate::auth::authorize_model(auth_key, &model)?;
 
     // Convert to normalized format (includes validation)
-    let normalized = to_normalized(req)?;
+    let mut normalized = to_normalized(req)?;
+    // The router only picks providers and models the key may use
+    crate::auth::attach_scope(auth_key, &mut normalized);
+    if let Some(Extension(identity)) = &identity {
+        identity.attach(&mut normalized);
+    }
+    if let Some(Extension(tenant)) = &tenant {
+        tenant.attach(&mut normalized);
+    }
 
     if is_streaming {
         // Log streaming request for observability
@@ -1157,6 +1186,7 @@ pub async fn messages(
 }
 
 /// State for passthrough handler (connector + optional stats tracker + metrics + session store)
+#[derive(Clone)]
 pub struct PassthroughState {
     pub connector: Arc<lunaroute_egress::anthropic::AnthropicConnector>,
     pub stats_tracker: Option<Arc<dyn crate::types::SessionStatsTracker>>,
@@ -1171,92 +1201,195 @@ pub struct PassthroughState {
 /// Passthrough handler for Anthropic→Anthropic routing (no normalization)
 /// Takes raw JSON, sends directly to Anthropic, returns raw JSON
 /// Preserves 100% API fidelity while still extracting metrics
+#[allow(clippy::too_many_arguments)]
 pub async fn messages_passthrough(
     State(state): State<Arc<PassthroughState>>,
     headers: axum::http::HeaderMap,
+    model_aliases: Option<Extension<Arc<lunaroute_routing::ModelAliasTable>>>,
+    experiments: Option<Extension<Arc<lunaroute_routing::ExperimentSet>>>,
+    sticky_markers: Option<Extension<Arc<crate::marker::StickyMarkers>>>,
+    auth_key: Option<Extension<crate::auth::AuthenticatedKey>>,
+    identity: Option<Extension<crate::auth::ClientIdentity>>,
+    tenant: Option<Extension<Arc<lunaroute_core::tenant::TenantContext>>>,
     Json(req): Json<serde_json::Value>,
 ) -> Result<Response, IngressError> {
     let start_time = std::time::Instant::now();
     tracing::debug!("Anthropic passthrough mode: skipping normalization");
+    // Record into the request's tenant
+    let state = match crate::tenant::tenant_session_store(tenant.as_ref()) {
+        Some(store) => Arc::new(PassthroughState {
+            session_store: Some(store),
+            ..(*state).clone()
+        }),
+        None => state,
+    };
+    let client_identity =
+        crate::auth::client_identity_subject(identity.as_ref().map(|Extension(i)| i));
 
     let mut req = req;
 
-    // LUNAROUTE marker detection — check for provider override
-    let marker_result = crate::marker::extract_marker(&req);
+    // Model as the client asked for it, before markers, experiments and
+    // aliases rewrite the body (recorded as model_requested)
+    let requested_model = req
+        .get("model")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    // Extract session ID from metadata.user_id. The shape has changed over time
+    // (legacy "user_..._session_<uuid>" flat string, current JSON-encoded object);
+    // extract_session_id_from_user_id handles all known shapes.
+    let session_id = req
+        .get("metadata")
+        .and_then(|m| m.get("user_id"))
+        .and_then(extract_session_id_from_user_id);
+
+    // LUNAROUTE marker detection — check for provider override (or the
+    // session's sticky selection)
+    let marker = crate::marker::resolve_marker(
+        &mut req,
+        sticky_markers.as_ref().map(|Extension(s)| s.as_ref()),
+        session_id.as_deref(),
+    );
+    let mut marker_tags = marker.session_tags;
     let mut override_connector: Option<Arc<lunaroute_egress::anthropic::AnthropicConnector>> = None;
     let mut cross_dialect_connector: Option<Arc<lunaroute_egress::openai::OpenAIConnector>> = None;
     let mut marker_provider_name: Option<String> = None;
 
-    match &marker_result {
-        crate::marker::MarkerResult::Provider(name) => {
-            if let Some(registry) = &state.provider_registry {
-                if let Some(entry) = registry.get(name) {
-                    if entry.connector_type != crate::ProviderType::Anthropic {
-                        // Cross-dialect: Anthropic request → OpenAI provider
-                        if let Some(ref connector) = entry.openai_connector {
-                            tracing::info!(
-                                "LUNAROUTE marker: cross-dialect routing to OpenAI provider '{}', model_override={:?}",
-                                name,
-                                entry.model_override
-                            );
-                            cross_dialect_connector = Some(connector.clone());
-                            marker_provider_name = Some(name.clone());
-
-                            // Apply model override
-                            if let Some(ref model) = entry.model_override {
-                                req["model"] = serde_json::Value::String(model.clone());
-                            }
-                        } else {
-                            tracing::warn!(
-                                "LUNAROUTE marker '{}' targets OpenAI provider but no OpenAI connector available",
-                                name
-                            );
-                            return Err(IngressError::InvalidRequest(format!(
-                                "LUNAROUTE marker targets provider '{}' but no OpenAI connector is configured for it",
-                                name
-                            )));
-                        }
-                    } else if let Some(ref connector) = entry.anthropic_connector {
+    if let Some(selection) = &marker.selection {
+        let name = &selection.provider;
+        if let Some(registry) = &state.provider_registry {
+            if let Some(entry) = registry.get(name) {
+                let model_override = selection.model.as_ref().or(entry.model_override.as_ref());
+                if entry.connector_type != crate::ProviderType::Anthropic {
+                    // Cross-dialect: Anthropic request → OpenAI provider
+                    if let Some(ref connector) = entry.openai_connector {
                         tracing::info!(
-                            "LUNAROUTE marker: routing to provider '{}', model_override={:?}",
+                            "LUNAROUTE marker: cross-dialect routing to OpenAI provider '{}', model_override={:?}",
                             name,
-                            entry.model_override
+                            model_override
                         );
-                        override_connector = Some(connector.clone());
+                        cross_dialect_connector = Some(connector.clone());
                         marker_provider_name = Some(name.clone());
-
-                        // Apply model override
-                        if let Some(ref model) = entry.model_override {
-                            req["model"] = serde_json::Value::String(model.clone());
-                        }
+                    } else {
+                        tracing::warn!(
+                            "LUNAROUTE marker '{}' targets OpenAI provider but no OpenAI connector available",
+                            name
+                        );
+                        return Err(IngressError::InvalidRequest(format!(
+                            "LUNAROUTE marker targets provider '{}' but no OpenAI connector is configured for it",
+                            name
+                        )));
                     }
-                } else {
-                    tracing::warn!(
-                        "LUNAROUTE marker references unknown provider '{}', using default",
-                        name
+                } else if let Some(ref connector) = entry.anthropic_connector {
+                    tracing::info!(
+                        "LUNAROUTE marker: routing to provider '{}', model_override={:?}",
+                        name,
+                        model_override
                     );
+                    override_connector = Some(connector.clone());
+                    marker_provider_name = Some(name.clone());
                 }
+
+                // Apply model and parameter overrides
+                if marker_provider_name.is_some() {
+                    marker_tags.push(format!("lunaroute:{}", name));
+                    if let Some(model) = model_override {
+                        req["model"] = serde_json::Value::String(model.clone());
+                        marker_tags.push(format!("model_override:{}", model));
+                    }
+                    selection.params.apply_to_json(&mut req);
+                }
+            } else if let Some(Extension(table)) = &model_aliases
+                && table.get_alias(name).is_some()
+            {
+                tracing::info!("LUNAROUTE marker: selecting model alias '{}'", name);
+                req["model"] = serde_json::Value::String(name.clone());
             } else {
-                tracing::debug!(
-                    "LUNAROUTE marker '{}' found but no provider registry available, using default",
+                tracing::warn!(
+                    "LUNAROUTE marker references unknown provider '{}', using default",
                     name
                 );
             }
-            crate::marker::strip_marker(&mut req);
+        } else {
+            tracing::debug!(
+                "LUNAROUTE marker '{}' found but no provider registry available, using default",
+                name
+            );
         }
-        crate::marker::MarkerResult::Clear => {
-            crate::marker::strip_marker(&mut req);
+    }
+
+    // A/B experiment assignment (a marker-selected provider opts the request out)
+    let mut experiment = crate::experiment::ExperimentSelection::default();
+    if let Some(Extension(set)) = &experiments
+        && marker_provider_name.is_none()
+    {
+        let user_id = req
+            .get("metadata")
+            .and_then(|m| m.get("user_id"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        experiment = crate::experiment::apply_experiments(
+            set,
+            state.provider_registry.as_deref(),
+            &mut req,
+            crate::ProviderType::Anthropic,
+            true,
+            crate::experiment::StickyKeys {
+                session: session_id.as_deref(),
+                user: user_id.as_deref(),
+                headers: &headers,
+            },
+        );
+        if let Some(entry) = &experiment.entry {
+            if entry.connector_type == crate::ProviderType::Anthropic {
+                override_connector = entry.anthropic_connector.clone();
+            } else {
+                cross_dialect_connector = entry.openai_connector.clone();
+            }
         }
-        crate::marker::MarkerResult::None => {}
     }
 
-    // Extract session ID from metadata.user_id. The shape has changed over time
-    // (legacy "user_..._session_<uuid>" flat string, current JSON-encoded object);
-    // extract_session_id_from_user_id handles all known shapes.
-    let session_id = req
-        .get("metadata")
-        .and_then(|m| m.get("user_id"))
-        .and_then(extract_session_id_from_user_id);
+    // Virtual model aliases and retired-model rewrites (a marker- or
+    // experiment-selected provider wins)
+    let mut alias_provider_name: Option<String> = None;
+    let mut alias_tags: Vec<String> = Vec::new();
+    if let Some(Extension(table)) = &model_aliases
+        && marker_provider_name.is_none()
+        && experiment.provider.is_none()
+    {
+        let selection = crate::model_alias::apply_model_aliases(
+            table,
+            state.provider_registry.as_deref(),
+            &mut req,
+            crate::ProviderType::Anthropic,
+            true,
+        )?;
+        if let Some(entry) = &selection.entry {
+            if entry.connector_type == crate::ProviderType::Anthropic {
+                override_connector = entry.anthropic_connector.clone();
+            } else {
+                cross_dialect_connector = entry.openai_connector.clone();
+            }
+        }
+        alias_provider_name = selection.provider;
+        alias_tags = selection.session_tags;
+    }
+    let quota_provider = marker_provider_name
+        .as_deref()
+        .or(experiment.provider.as_deref())
+        .or(alias_provider_name.as_deref())
+        .unwrap_or("anthropic")
+        .to_string();
+    let auth_key = auth_key.as_ref().map(|Extension(k)| k);
+    crate::auth::authorize_provider(auth_key, &quota_provider)?;
+    // Markers, experiments and aliases may have rewritten the model
+    crate::auth::authorize_model(
+        auth_key,
+        req.get("model")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default(),
+    )?;
 
     // Pass through ALL headers from the client (except hop-by-hop headers)
     // This allows client to provide auth headers if no API key is configured
@@ -1346,19 +1479,11 @@ pub async fn messages_passthrough(
         let store_clone = session_store.clone();
         let session_id_clone = session_id.clone();
         let request_id_clone = request_id.clone();
-        let model_clone = model.clone();
+        let model_clone = requested_model;
         let user_agent_clone = user_agent.clone();
         let session_tags_clone = {
-            let mut tags = vec![];
-            if let Some(ref provider_name) = marker_provider_name {
-                tags.push(format!("lunaroute:{}", provider_name));
-                if let Some(registry) = &state.provider_registry
-                    && let Some(entry) = registry.get(provider_name)
-                    && let Some(ref model) = entry.model_override
-                {
-                    tags.push(format!("model_override:{}", model));
-                }
-            }
+            let mut tags = marker_tags;
+            tags.extend(alias_tags);
             tags
         };
         tokio::spawn(async move {
@@ -1369,6 +1494,8 @@ pub async fn messages_passthrough(
                 model_requested: model_clone,
                 provider: marker_provider_name
                     .as_deref()
+                    .or(experiment.provider.as_deref())
+                    .or(alias_provider_name.as_deref())
                     .unwrap_or("anthropic")
                     .to_string(),
                 listener: "anthropic".to_string(),
@@ -1379,6 +1506,9 @@ pub async fn messages_passthrough(
                     api_version: None,
                     request_headers: Default::default(),
                     session_tags: session_tags_clone,
+                    experiment: experiment.experiment,
+                    experiment_arm: experiment.arm,
+                    client_identity,
                 },
             };
             if let Ok(json) = serde_json::to_value(event) {
@@ -1520,6 +1650,9 @@ pub async fn messages_passthrough(
         if is_streaming {
             let mut normalized = to_normalized(typed_req)?;
             normalized.stream = true;
+            if let Some(Extension(identity)) = &identity {
+                identity.attach(&mut normalized);
+            }
 
             let event_stream = cd_connector
                 .stream(normalized)
@@ -1617,6 +1750,9 @@ pub async fn messages_passthrough(
         } else {
             let mut normalized = to_normalized(typed_req)?;
             normalized.stream = false;
+            if let Some(Extension(identity)) = &identity {
+                identity.attach(&mut normalized);
+            }
 
             let normalized_resp = cd_connector
                 .send(normalized)
@@ -1635,6 +1771,7 @@ pub async fn messages_passthrough(
             .stream_passthrough(req, passthrough_headers)
             .await
             .map_err(|e| IngressError::ProviderError(e.to_string()))?;
+        crate::record_quota_metrics(state.metrics.as_deref(), &quota_provider, connector.quota());
 
         // If the response is an error (non-2xx), pass it through as a raw response
         // instead of trying to set up SSE streaming. Error responses from Anthropic
@@ -1781,6 +1918,7 @@ pub async fn messages_passthrough(
         let start_clone = start_time;
         let before_provider_clone = before_provider;
         let tool_call_mapper_for_parser = state.tool_call_mapper.clone();
+        let upstream_key = crate::upstream_key(&response_headers);
 
         // Clone SSE config before state is moved
         let sse_keepalive_interval = state.sse_keepalive_interval_secs;
@@ -1862,6 +2000,7 @@ pub async fn messages_passthrough(
                             request_id.clone(),
                             session_store.clone(),
                             user_agent.clone(),
+                            upstream_key.clone(),
                             Some(tool_call_mapper_for_parser.clone()), // Pass mapper to record tool calls
                         );
                     }
@@ -1907,6 +2046,7 @@ pub async fn messages_passthrough(
     // Send directly to Anthropic API (non-streaming)
     let connector = override_connector.as_ref().unwrap_or(&state.connector);
     let response_result = connector.send_passthrough(req, passthrough_headers).await;
+    crate::record_quota_metrics(state.metrics.as_deref(), &quota_provider, connector.quota());
 
     let (response_status, raw_response_bytes, response_headers) = match response_result {
         Ok((status, bytes, headers)) => (status, bytes, headers),
@@ -2195,6 +2335,9 @@ pub async fn messages_passthrough(
                     let sid = session_id.clone();
                     let rid = request_id.clone();
                     let user_agent_for_stats = user_agent_clone.clone();
+                    let upstream_key = response_headers
+                        .get(lunaroute_egress::key_pool::UPSTREAM_KEY_HEADER)
+                        .cloned();
                     tokio::spawn(async move {
                         let event = lunaroute_session::SessionEvent::StatsUpdated {
                             session_id: sid,
@@ -2207,6 +2350,7 @@ pub async fn messages_passthrough(
                             content_blocks,
                             has_refusal,
                             user_agent: user_agent_for_stats,
+                            upstream_key,
                         };
                         if let Ok(json) = serde_json::to_value(event) {
                             let _ = store_for_stats.write_event(None, json).await;
@@ -2350,6 +2494,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_to_normalized_carries_session_id() {
+        let req: AnthropicMessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "metadata": {"user_id": "user_abc_account_def_session_0f3c9a1e-2b7d-4c55-9e61-7a2d8b4f1c03"}
+        }))
+        .unwrap();
+
+        let normalized = to_normalized(req).unwrap();
+        assert_eq!(
+            normalized.metadata.get("session_id"),
+            Some(&serde_json::json!("0f3c9a1e-2b7d-4c55-9e61-7a2d8b4f1c03"))
+        );
+    }
+
     #[test]
     fn test_to_normalized() {
         let req = AnthropicMessagesRequest {
@@ -2368,6 +2528,7 @@ mod tests {
             stream: Some(false),
             stop_sequences: None,
             tools: None,
+            metadata: None,
         };
 
         let normalized = to_normalized(req).unwrap();
@@ -2429,6 +2590,7 @@ mod tests {
             stream: None,
             stop_sequences: None,
             tools: None,
+            metadata: None,
         };
 
         let result = to_normalized(req);
@@ -2452,9 +2614,10 @@ mod tests {
             stream: None,
             stop_sequences: None,
             tools: None,
+            metadata: None,
         };
 
-        let response = messages(State(provider), Json(req)).await;
+        let response = messages(State(provider), None, None, None, Json(req)).await;
         assert!(response.is_ok());
     }
 
@@ -2512,6 +2675,7 @@ mod tests {
             stream: None,
             stop_sequences: None,
             tools: None,
+            metadata: None,
         };
 
         // Validation should reject empty messages array
@@ -2543,6 +2707,7 @@ mod tests {
             stream: Some(true),
             stop_sequences: Some(vec!["STOP".to_string()]),
             tools: None,
+            metadata: None,
         };
 
         let normalized = to_normalized(req).unwrap();
@@ -2660,6 +2825,7 @@ mod tests {
             stream: Some(false),
             stop_sequences: None,
             tools: None,
+            metadata: None,
         };
 
         let normalized = to_normalized(req).unwrap();
@@ -2849,6 +3015,7 @@ mod tests {
             stop_sequences: None,
             stream: None,
             tools: None,
+            metadata: None,
         };
 
         let normalized = to_normalized(req).unwrap();
@@ -2899,6 +3066,7 @@ mod tests {
             stop_sequences: None,
             stream: None,
             tools: None,
+            metadata: None,
         };
 
         let normalized = to_normalized(req).unwrap();
@@ -2962,6 +3130,7 @@ mod tests {
             stop_sequences: None,
             stream: None,
             tools: None,
+            metadata: None,
         };
 
         let normalized = to_normalized(req).unwrap();
@@ -3028,6 +3197,7 @@ mod tests {
             stop_sequences: None,
             stream: None,
             tools: None,
+            metadata: None,
         };
 
         let normalized = to_normalized(req).unwrap();
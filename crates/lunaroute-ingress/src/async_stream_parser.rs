@@ -379,6 +379,7 @@ pub fn spawn_anthropic_parser<E>(
     request_id: String,
     session_store: Arc<dyn SessionStore>,
     user_agent: Option<String>,
+    upstream_key: Option<String>,
     tool_call_mapper: Option<Arc<lunaroute_session::ToolCallMapper>>,
 ) {
     tokio::spawn(async move {
@@ -454,6 +455,7 @@ pub fn spawn_anthropic_parser<E>(
                         content_blocks: parsed.content_blocks,
                         has_refusal: parsed.has_refusal,
                         user_agent,
+                        upstream_key,
                     };
                     if let Ok(json) = serde_json::to_value(event) {
                         let _ = store_clone.write_event(None, json).await;
@@ -488,6 +490,7 @@ pub fn spawn_openai_parser<E>(
     request_id: String,
     session_store: Arc<dyn SessionStore>,
     user_agent: Option<String>,
+    upstream_key: Option<String>,
     tool_call_mapper: Option<Arc<lunaroute_session::ToolCallMapper>>,
 ) {
     tokio::spawn(async move {
@@ -563,6 +566,7 @@ pub fn spawn_openai_parser<E>(
                         content_blocks: parsed.content_blocks,
                         has_refusal: parsed.has_refusal,
                         user_agent,
+                        upstream_key,
                     };
                     if let Ok(json) = serde_json::to_value(event) {
                         let _ = store_clone.write_event(None, json).await;
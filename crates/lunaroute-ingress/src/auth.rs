@@ -0,0 +1,1073 @@
+//! Ingress API keys
+//!
+//! LunaRoute-issued keys authenticate clients before any request reaches an
+//! upstream provider. Keys look like `lr_<id>_<secret>`: the ID locates the
+//! key record in the [`StateStore`] (shared across instances with the
+//! PostgreSQL backend) and only an Argon2id hash of the secret is stored.
+//!
+//! Clients send the key as `Authorization: Bearer lr_...`, `x-api-key: lr_...`
+//! or `x-lunaroute-api-key: lr_...`; the last one leaves `Authorization` free
+//! for clients that bring their own upstream credentials. The header carrying
+//! the key is removed before the request is forwarded.
+//!
+//! Each key carries scopes (listeners, model patterns, providers), an optional
+//! expiry, and a last-used timestamp. Rotation issues a new key with the same
+//! metadata and lets the old one keep working for a grace period.
+//!
+//! The middleware checks the listener scope and the model in the request
+//! body; handlers check the model again once markers, aliases, experiments
+//! and budget reroutes have rewritten it. Model- and provider-scoped keys also
+//! travel into normalized requests as a [`RequestScope`], so the router only
+//! picks providers and models the key may use.
+//!
+//! On a TLS listener with client-certificate verification, a verified
+//! certificate can stand in for a key when [`ApiKeyAuth::client_certs`] is
+//! set: the request is authenticated as the certificate's subject (see
+//! [`ClientIdentity`]) with unrestricted scopes. A key sent alongside a
+//! certificate still takes precedence.
+//!
+//! With [`ApiKeyAuth::jwt`] configured, a bearer token that is not a
+//! LunaRoute key is validated as a JWT (see [`crate::jwt`]) and its claims
+//! become the request's [`ClientIdentity`], also with unrestricted scopes.
+
+use crate::jwt::JwtVerifier;
+use crate::types::IngressError;
+use argon2::password_hash::{
+    PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng,
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderMap, Method, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+pub(crate) use lunaroute_core::identity::wildcard_match;
+pub use lunaroute_core::identity::{ClientIdentity, IdentitySource, RequestScope};
+use lunaroute_core::normalized::NormalizedRequest;
+use lunaroute_storage::StateStore;
+use rand::TryRng;
+use rand::rngs::SysRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Prefix of every LunaRoute-issued key
+pub const KEY_TOKEN_PREFIX: &str = "lr_";
+
+/// Dedicated key header (leaves `Authorization` to the client)
+pub const API_KEY_HEADER: &str = "x-lunaroute-api-key";
+
+/// State store key prefix for key records
+pub const KEY_RECORD_PREFIX: &str = "auth:key:";
+
+/// State store key prefix for last-used timestamps (kept apart from the
+/// record so usage updates never race with revocation)
+pub const LAST_USED_PREFIX: &str = "auth:last_used:";
+
+/// Minimum time between last-used writes for one key
+const LAST_USED_WRITE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long a successful hash verification is remembered
+const VERIFIED_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Key ID length in bytes (hex-encoded in the token)
+const KEY_ID_BYTES: usize = 6;
+
+/// Key secret length in bytes (hex-encoded in the token)
+const KEY_SECRET_BYTES: usize = 32;
+
+/// What a key may be used for (empty lists allow everything)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyScopes {
+    /// Listeners (dialects) the key may call: `anthropic`, `openai`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub listeners: Vec<String>,
+    /// Model name patterns; `*` matches any run of characters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+    /// Provider IDs requests may be sent to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<String>,
+    /// Projects the key may bill through `x-lunaroute-project`; the first is
+    /// billed when the header is absent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    /// May send `x-lunaroute-budget-override` to go past hard budget limits
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub budget_override: bool,
+}
+
+impl KeyScopes {
+    /// Whether the key may call the given listener
+    pub fn allows_listener(&self, listener: &str) -> bool {
+        self.listeners.is_empty()
+            || self
+                .listeners
+                .iter()
+                .any(|l| l.eq_ignore_ascii_case(listener))
+    }
+
+    /// Whether the key may request the given model
+    pub fn allows_model(&self, model: &str) -> bool {
+        self.models.is_empty() || self.models.iter().any(|p| wildcard_match(p, model))
+    }
+
+    /// Whether the key may be routed to the given provider
+    pub fn allows_provider(&self, provider: &str) -> bool {
+        self.providers.is_empty() || self.providers.iter().any(|p| p == provider)
+    }
+
+    /// The model and provider scopes, for the router
+    pub fn request_scope(&self) -> RequestScope {
+        RequestScope {
+            providers: self.providers.clone(),
+            models: self.models.clone(),
+        }
+    }
+}
+
+/// Key metadata (never includes the secret or its hash)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// Key ID (the `<id>` part of the token)
+    pub id: String,
+    /// Human-readable name
+    pub name: String,
+    /// Person or service the key was issued to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Tenant the key belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// What the key may be used for
+    #[serde(default)]
+    pub scopes: KeyScopes,
+    pub created_at: DateTime<Utc>,
+    /// Key stops working at this time (None = never)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Last successful authentication (updated at most once a minute)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// ID of the key that replaced this one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotated_to: Option<String>,
+}
+
+impl ApiKey {
+    /// Whether the key can authenticate at the given time
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|at| at > now)
+    }
+}
+
+/// Metadata for a key to create
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NewApiKey {
+    pub name: String,
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub scopes: KeyScopes,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Key record as persisted
+#[derive(Serialize, Deserialize)]
+struct StoredKey {
+    #[serde(flatten)]
+    key: ApiKey,
+    /// Argon2id PHC string of the secret
+    hash: String,
+}
+
+/// API key errors
+#[derive(Debug, thiserror::Error)]
+pub enum ApiKeyError {
+    /// Malformed token, unknown ID or wrong secret
+    #[error("Invalid API key")]
+    Invalid,
+
+    #[error("API key has expired")]
+    Expired,
+
+    #[error("API key has been revoked")]
+    Revoked,
+
+    #[error("API key not found: {0}")]
+    NotFound(String),
+
+    /// Rejected key metadata (e.g. an empty name)
+    #[error("Invalid key metadata: {0}")]
+    InvalidMetadata(String),
+
+    #[error("Key store error: {0}")]
+    Store(String),
+}
+
+impl From<lunaroute_storage::StorageError> for ApiKeyError {
+    fn from(err: lunaroute_storage::StorageError) -> Self {
+        ApiKeyError::Store(err.to_string())
+    }
+}
+
+/// Issued keys, backed by a [`StateStore`]
+pub struct ApiKeyStore {
+    store: Arc<dyn StateStore>,
+    /// SHA-256 of recently verified tokens → hash they verified against
+    verified: Mutex<HashMap<[u8; 32], (String, Instant)>>,
+    /// Last last-used write per key ID
+    last_used_written: Mutex<HashMap<String, Instant>>,
+}
+
+impl ApiKeyStore {
+    /// Create a key store on top of a state store
+    pub fn new(store: Arc<dyn StateStore>) -> Self {
+        Self {
+            store,
+            verified: Mutex::new(HashMap::new()),
+            last_used_written: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Issue a new key; returns the token (shown once) and its metadata
+    pub async fn create(&self, new: NewApiKey) -> Result<(String, ApiKey), ApiKeyError> {
+        if new.name.trim().is_empty() {
+            return Err(ApiKeyError::InvalidMetadata(
+                "name cannot be empty".to_string(),
+            ));
+        }
+        let id = random_hex(KEY_ID_BYTES)?;
+        let secret = random_hex(KEY_SECRET_BYTES)?;
+        let hash = hash_secret(secret.clone()).await?;
+
+        let key = ApiKey {
+            id: id.clone(),
+            name: new.name,
+            owner: new.owner,
+            tenant: new.tenant,
+            scopes: new.scopes,
+            created_at: Utc::now(),
+            expires_at: new.expires_at,
+            last_used_at: None,
+            revoked_at: None,
+            rotated_to: None,
+        };
+        self.save(&StoredKey {
+            key: key.clone(),
+            hash,
+        })
+        .await?;
+        self.store.persist().await?;
+
+        Ok((format!("{}{}_{}", KEY_TOKEN_PREFIX, id, secret), key))
+    }
+
+    /// Look up a key's metadata
+    pub async fn get(&self, id: &str) -> Result<Option<ApiKey>, ApiKeyError> {
+        let Some(mut stored) = self.load(id).await? else {
+            return Ok(None);
+        };
+        stored.key.last_used_at = self.last_used(id).await?;
+        Ok(Some(stored.key))
+    }
+
+    /// All keys, oldest first
+    pub async fn list(&self) -> Result<Vec<ApiKey>, ApiKeyError> {
+        let mut keys = Vec::new();
+        for record in self.store.list_keys(KEY_RECORD_PREFIX).await? {
+            let id = &record[KEY_RECORD_PREFIX.len()..];
+            if let Some(key) = self.get(id).await? {
+                keys.push(key);
+            }
+        }
+        keys.sort_by_key(|k| k.created_at);
+        Ok(keys)
+    }
+
+    /// Revoke a key immediately
+    pub async fn revoke(&self, id: &str) -> Result<ApiKey, ApiKeyError> {
+        let mut stored = self
+            .load(id)
+            .await?
+            .ok_or_else(|| ApiKeyError::NotFound(id.to_string()))?;
+        if stored.key.revoked_at.is_none() {
+            stored.key.revoked_at = Some(Utc::now());
+            self.save(&stored).await?;
+            self.store.persist().await?;
+        }
+        Ok(stored.key)
+    }
+
+    /// Replace a key with a new one carrying the same metadata
+    ///
+    /// The old key keeps working for `grace` (or until its own expiry, if
+    /// sooner) so clients can switch over without downtime.
+    pub async fn rotate(&self, id: &str, grace: Duration) -> Result<(String, ApiKey), ApiKeyError> {
+        let mut old = self
+            .load(id)
+            .await?
+            .ok_or_else(|| ApiKeyError::NotFound(id.to_string()))?;
+        let now = Utc::now();
+        if !old.key.is_active_at(now) {
+            return Err(if old.key.revoked_at.is_some() {
+                ApiKeyError::Revoked
+            } else {
+                ApiKeyError::Expired
+            });
+        }
+
+        let (token, new) = self
+            .create(NewApiKey {
+                name: old.key.name.clone(),
+                owner: old.key.owner.clone(),
+                tenant: old.key.tenant.clone(),
+                scopes: old.key.scopes.clone(),
+                expires_at: old.key.expires_at,
+            })
+            .await?;
+
+        let grace_end = now + chrono::Duration::from_std(grace).unwrap_or(chrono::Duration::MAX);
+        old.key.expires_at = Some(old.key.expires_at.map_or(grace_end, |e| e.min(grace_end)));
+        old.key.rotated_to = Some(new.id.clone());
+        self.save(&old).await?;
+        self.store.persist().await?;
+
+        Ok((token, new))
+    }
+
+    /// Authenticate a token and return its key
+    pub async fn verify(&self, token: &str) -> Result<ApiKey, ApiKeyError> {
+        let (id, secret) = token
+            .strip_prefix(KEY_TOKEN_PREFIX)
+            .and_then(|rest| rest.split_once('_'))
+            .ok_or(ApiKeyError::Invalid)?;
+        let stored = self.load(id).await?.ok_or(ApiKeyError::Invalid)?;
+
+        let digest: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+        let cached = self.verified.lock().is_ok_and(|cache| {
+            cache
+                .get(&digest)
+                .is_some_and(|(hash, at)| *hash == stored.hash && at.elapsed() < VERIFIED_CACHE_TTL)
+        });
+        if !cached {
+            if !verify_secret(secret.to_string(), stored.hash.clone()).await {
+                return Err(ApiKeyError::Invalid);
+            }
+            if let Ok(mut cache) = self.verified.lock() {
+                cache.retain(|_, (_, at)| at.elapsed() < VERIFIED_CACHE_TTL);
+                cache.insert(digest, (stored.hash.clone(), Instant::now()));
+            }
+        }
+
+        if stored.key.revoked_at.is_some() {
+            return Err(ApiKeyError::Revoked);
+        }
+        if !stored.key.is_active_at(Utc::now()) {
+            return Err(ApiKeyError::Expired);
+        }
+        Ok(stored.key)
+    }
+
+    /// Record that a key was just used (throttled per key)
+    pub async fn touch(&self, id: &str) {
+        let due = self.last_used_written.lock().is_ok_and(|mut written| {
+            let due = written
+                .get(id)
+                .is_none_or(|at| at.elapsed() >= LAST_USED_WRITE_INTERVAL);
+            if due {
+                written.insert(id.to_string(), Instant::now());
+            }
+            due
+        });
+        if !due {
+            return;
+        }
+        let now = Utc::now().timestamp_millis().to_le_bytes().to_vec();
+        if let Err(e) = self
+            .store
+            .set(&format!("{}{}", LAST_USED_PREFIX, id), now)
+            .await
+        {
+            tracing::warn!("Failed to record last use of API key {}: {}", id, e);
+        }
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<StoredKey>, ApiKeyError> {
+        let Some(bytes) = self
+            .store
+            .get(&format!("{}{}", KEY_RECORD_PREFIX, id))
+            .await?
+        else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| ApiKeyError::Store(format!("corrupt key record {}: {}", id, e)))
+    }
+
+    async fn save(&self, stored: &StoredKey) -> Result<(), ApiKeyError> {
+        let bytes = serde_json::to_vec(stored).map_err(|e| ApiKeyError::Store(e.to_string()))?;
+        self.store
+            .set(&format!("{}{}", KEY_RECORD_PREFIX, stored.key.id), bytes)
+            .await?;
+        Ok(())
+    }
+
+    async fn last_used(&self, id: &str) -> Result<Option<DateTime<Utc>>, ApiKeyError> {
+        let bytes = self
+            .store
+            .get(&format!("{}{}", LAST_USED_PREFIX, id))
+            .await?;
+        Ok(bytes
+            .and_then(|b| <[u8; 8]>::try_from(b.as_slice()).ok())
+            .and_then(|b| DateTime::from_timestamp_millis(i64::from_le_bytes(b))))
+    }
+}
+
+/// Argon2id parameters for key secrets
+///
+/// Secrets are 256-bit random values, so the OWASP minimum (19 MiB, 2
+/// passes) is plenty and keeps uncached verification in the low milliseconds.
+fn argon2() -> Argon2<'static> {
+    let params = Params::new(19 * 1024, 2, 1, None).expect("valid Argon2 params");
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+}
+
+async fn hash_secret(secret: String) -> Result<String, ApiKeyError> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        argon2()
+            .hash_password(secret.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| ApiKeyError::Store(format!("failed to hash key: {}", e)))
+    })
+    .await
+    .map_err(|e| ApiKeyError::Store(e.to_string()))?
+}
+
+async fn verify_secret(secret: String, hash: String) -> bool {
+    tokio::task::spawn_blocking(move || {
+        PasswordHash::new(&hash)
+            .is_ok_and(|parsed| argon2().verify_password(secret.as_bytes(), &parsed).is_ok())
+    })
+    .await
+    .unwrap_or(false)
+}
+
+fn random_hex(len: usize) -> Result<String, ApiKeyError> {
+    let mut bytes = vec![0u8; len];
+    SysRng
+        .try_fill_bytes(&mut bytes)
+        .map_err(|e| ApiKeyError::Store(format!("random generator failed: {}", e)))?;
+    Ok(hex::encode(bytes))
+}
+
+/// Key record standing in for an API key when an identity authenticates a
+/// request on its own. The owner and tenant come from the identity so user
+/// and tenant budgets apply.
+fn identity_key(identity: &ClientIdentity) -> ApiKey {
+    ApiKey {
+        id: format!("{}:{}", identity.source.as_str(), identity.subject),
+        name: identity.subject.clone(),
+        owner: Some(
+            identity
+                .email
+                .clone()
+                .unwrap_or_else(|| identity.subject.clone()),
+        ),
+        tenant: identity.tenant.clone(),
+        scopes: KeyScopes::default(),
+        created_at: Utc::now(),
+        expires_at: None,
+        last_used_at: None,
+        revoked_at: None,
+        rotated_to: None,
+    }
+}
+
+/// Subject of the request's client identity, for session recording
+pub fn client_identity_subject(identity: Option<&ClientIdentity>) -> Option<String> {
+    identity.map(|identity| identity.subject.clone())
+}
+
+/// The key that authenticated a request (inserted as a request extension)
+#[derive(Debug, Clone)]
+pub struct AuthenticatedKey(pub Arc<ApiKey>);
+
+impl AuthenticatedKey {
+    /// Check the key's provider scope
+    pub fn authorize_provider(&self, provider: &str) -> Result<(), IngressError> {
+        if self.0.scopes.allows_provider(provider) {
+            Ok(())
+        } else {
+            Err(IngressError::Forbidden(format!(
+                "API key '{}' may not use provider '{}'",
+                self.0.name, provider
+            )))
+        }
+    }
+
+    /// Check the key's model scope
+    pub fn authorize_model(&self, model: &str) -> Result<(), IngressError> {
+        if self.0.scopes.allows_model(model) {
+            Ok(())
+        } else {
+            Err(IngressError::Forbidden(format!(
+                "API key '{}' may not use model '{}'",
+                self.0.name, model
+            )))
+        }
+    }
+}
+
+/// Check a handler's resolved provider against the request's key, if any
+pub fn authorize_provider(
+    key: Option<&AuthenticatedKey>,
+    provider: &str,
+) -> Result<(), IngressError> {
+    key.map_or(Ok(()), |key| key.authorize_provider(provider))
+}
+
+/// Check a handler's final model against the request's key, if any
+pub fn authorize_model(key: Option<&AuthenticatedKey>, model: &str) -> Result<(), IngressError> {
+    key.map_or(Ok(()), |key| key.authorize_model(model))
+}
+
+/// Carry the request's key scopes into a normalized request for the router
+pub fn attach_scope(key: Option<&AuthenticatedKey>, request: &mut NormalizedRequest) {
+    if let Some(key) = key {
+        key.0.scopes.request_scope().attach(request);
+    }
+}
+
+/// Listener a request path belongs to (None for dialect-neutral paths)
+///
+/// `/v1/messages*` and `/v1/complete` are Anthropic; `/v1/models` serves
+/// both dialects; every other path (including bypassed ones) is OpenAI.
+pub fn listener_for_path(path: &str) -> Option<&'static str> {
+    if path.starts_with("/v1/messages") || path == "/v1/complete" {
+        Some("anthropic")
+    } else if path == "/v1/models" || path.starts_with("/v1/models/") {
+        None
+    } else {
+        Some("openai")
+    }
+}
+
+/// State for [`api_key_middleware`]
+pub struct ApiKeyAuth {
+    pub keys: Arc<ApiKeyStore>,
+    /// Largest body buffered to check model scopes
+    pub max_body_bytes: usize,
+    /// Accept a verified TLS client certificate in place of a key
+    pub client_certs: bool,
+    /// Accept bearer JWTs in place of a key
+    pub jwt: Option<Arc<JwtVerifier>>,
+}
+
+/// Find the LunaRoute key among the request headers
+fn extract_key(headers: &HeaderMap) -> Option<(header::HeaderName, String)> {
+    let candidates = [
+        header::HeaderName::from_static(API_KEY_HEADER),
+        header::AUTHORIZATION,
+        header::HeaderName::from_static("x-api-key"),
+    ];
+    candidates.into_iter().find_map(|name| {
+        let value = headers.get(&name)?.to_str().ok()?.trim();
+        let value = if name == header::AUTHORIZATION {
+            value.strip_prefix("Bearer ")?.trim()
+        } else {
+            value
+        };
+        value
+            .starts_with(KEY_TOKEN_PREFIX)
+            .then(|| (name, value.to_string()))
+    })
+}
+
+/// Bearer token that is not a LunaRoute key (a JWT candidate)
+fn extract_bearer(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let token = value.trim().strip_prefix("Bearer ")?.trim();
+    (!token.is_empty() && !token.starts_with(KEY_TOKEN_PREFIX)).then(|| token.to_string())
+}
+
+/// Require a valid LunaRoute API key on every request
+pub async fn api_key_middleware(
+    State(auth): State<Arc<ApiKeyAuth>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let cert_identity = req
+        .extensions()
+        .get::<ClientIdentity>()
+        .filter(|identity| auth.client_certs && identity.source == IdentitySource::ClientCert)
+        .cloned();
+
+    // A JWT is only considered when no LunaRoute key is present
+    let jwt_identity = match (&auth.jwt, extract_key(req.headers())) {
+        (Some(verifier), None) => match extract_bearer(req.headers()) {
+            Some(token) => match verifier.verify(&token).await {
+                Ok(identity) => Some(identity),
+                Err(e) => {
+                    tracing::debug!("Rejected JWT: {}", e);
+                    return IngressError::AuthenticationFailed(e.to_string()).into_response();
+                }
+            },
+            None => None,
+        },
+        _ => None,
+    };
+    let identity = match jwt_identity {
+        Some(identity) => {
+            req.headers_mut().remove(header::AUTHORIZATION);
+            req.extensions_mut().insert(identity.clone());
+            Some(identity)
+        }
+        None => cert_identity,
+    };
+
+    let (header_name, key) = match extract_key(req.headers()) {
+        Some((header_name, token)) => match auth.keys.verify(&token).await {
+            Ok(key) => (Some(header_name), key),
+            Err(ApiKeyError::Store(e)) => {
+                tracing::error!("API key lookup failed: {}", e);
+                return IngressError::Internal("API key lookup failed".to_string()).into_response();
+            }
+            Err(e) => {
+                tracing::debug!("Rejected API key: {}", e);
+                return IngressError::AuthenticationFailed(e.to_string()).into_response();
+            }
+        },
+        None => match &identity {
+            Some(identity) => (None, identity_key(identity)),
+            None => {
+                return IngressError::AuthenticationFailed("Missing LunaRoute API key".to_string())
+                    .into_response();
+            }
+        },
+    };
+
+    if let Some(listener) = listener_for_path(req.uri().path())
+        && !key.scopes.allows_listener(listener)
+    {
+        return IngressError::Forbidden(format!(
+            "API key '{}' may not use the {} API",
+            key.name, listener
+        ))
+        .into_response();
+    }
+
+    let (mut parts, body) = req.into_parts();
+    if let Some(header_name) = &header_name {
+        parts.headers.remove(header_name);
+    }
+
+    // Model scopes need the request body; only buffer it when they apply
+    let body = if !key.scopes.models.is_empty() && parts.method == Method::POST {
+        let bytes = match axum::body::to_bytes(body, auth.max_body_bytes).await {
+            Ok(bytes) => bytes,
+            Err(_) => return IngressError::RequestTooLarge(auth.max_body_bytes).into_response(),
+        };
+        let model = serde_json::from_slice::<serde_json::Value>(&bytes)
+            .ok()
+            .and_then(|v| v.get("model").and_then(|m| m.as_str()).map(str::to_string));
+        let Some(model) = model else {
+            return IngressError::Forbidden(format!(
+                "API key '{}' is limited to specific models; request has no readable model",
+                key.name
+            ))
+            .into_response();
+        };
+        if !key.scopes.allows_model(&model) {
+            return IngressError::Forbidden(format!(
+                "API key '{}' may not use model '{}'",
+                key.name, model
+            ))
+            .into_response();
+        }
+        Body::from(bytes)
+    } else {
+        body
+    };
+
+    // Certificate and JWT identities have no stored record to touch
+    if header_name.is_some() {
+        let keys = auth.keys.clone();
+        let id = key.id.clone();
+        tokio::spawn(async move { keys.touch(&id).await });
+    }
+
+    parts.extensions.insert(AuthenticatedKey(Arc::new(key)));
+    next.run(Request::from_parts(parts, body)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Extension, Router, http::StatusCode, routing::post};
+    use lunaroute_storage::FileStateStore;
+    use tower::ServiceExt;
+
+    async fn store() -> (tempfile::TempDir, ApiKeyStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let state = FileStateStore::new(dir.path().join("state.json"))
+            .await
+            .unwrap();
+        (dir, ApiKeyStore::new(Arc::new(state)))
+    }
+
+    fn new_key(name: &str) -> NewApiKey {
+        NewApiKey {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_scopes() {
+        let scopes = KeyScopes {
+            listeners: vec!["anthropic".to_string()],
+            models: vec!["claude-*-4-5".to_string(), "gpt-5".to_string()],
+            providers: vec![],
+            projects: vec![],
+            budget_override: false,
+        };
+        assert!(scopes.allows_listener("Anthropic"));
+        assert!(!scopes.allows_listener("openai"));
+        assert!(scopes.allows_model("claude-sonnet-4-5"));
+        assert!(scopes.allows_model("gpt-5"));
+        assert!(!scopes.allows_model("gpt-5-mini"));
+        assert!(!scopes.allows_model("claude-opus-4-1"));
+        assert!(scopes.allows_provider("anything"));
+
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("a*b*c", "abc"));
+        assert!(!wildcard_match("a*bc", "abc-bc-"));
+        assert!(!wildcard_match("ab*b", "ab"));
+    }
+
+    #[tokio::test]
+    async fn test_create_verify_revoke() {
+        let (_dir, keys) = store().await;
+        let (token, key) = keys.create(new_key("ci")).await.unwrap();
+        assert!(token.starts_with("lr_"));
+
+        assert_eq!(keys.verify(&token).await.unwrap().id, key.id);
+        // Cached verification still sees the stored record
+        assert_eq!(keys.verify(&token).await.unwrap().id, key.id);
+
+        let wrong = format!("{}0", &token[..token.len() - 1]);
+        assert!(matches!(
+            keys.verify(&wrong).await,
+            Err(ApiKeyError::Invalid)
+        ));
+        assert!(matches!(
+            keys.verify("lr_nope_nope").await,
+            Err(ApiKeyError::Invalid)
+        ));
+
+        keys.touch(&key.id).await;
+        assert!(
+            keys.get(&key.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .last_used_at
+                .is_some()
+        );
+
+        keys.revoke(&key.id).await.unwrap();
+        assert!(matches!(
+            keys.verify(&token).await,
+            Err(ApiKeyError::Revoked)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rotation_grace_period_and_expiry() {
+        let (_dir, keys) = store().await;
+        let (old_token, old) = keys
+            .create(NewApiKey {
+                name: "svc".to_string(),
+                tenant: Some("acme".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let (new_token, new) = keys
+            .rotate(&old.id, Duration::from_secs(3600))
+            .await
+            .unwrap();
+        assert_ne!(new.id, old.id);
+        assert_eq!(new.tenant.as_deref(), Some("acme"));
+        // Both work during the grace period
+        assert!(keys.verify(&old_token).await.is_ok());
+        assert!(keys.verify(&new_token).await.is_ok());
+        let old = keys.get(&old.id).await.unwrap().unwrap();
+        assert_eq!(old.rotated_to.as_deref(), Some(new.id.as_str()));
+        assert!(old.expires_at.is_some());
+
+        // Zero grace retires the old key immediately
+        let (newest_token, _) = keys.rotate(&new.id, Duration::ZERO).await.unwrap();
+        assert!(matches!(
+            keys.verify(&new_token).await,
+            Err(ApiKeyError::Expired)
+        ));
+        assert!(keys.verify(&newest_token).await.is_ok());
+        assert_eq!(keys.list().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_enforces_key_and_scopes() {
+        let (_dir, keys) = store().await;
+        let keys = Arc::new(keys);
+        let (token, _) = keys
+            .create(NewApiKey {
+                name: "scoped".to_string(),
+                scopes: KeyScopes {
+                    listeners: vec!["anthropic".to_string()],
+                    models: vec!["claude-*".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let auth = Arc::new(ApiKeyAuth {
+            keys,
+            max_body_bytes: 1024 * 1024,
+            client_certs: false,
+            jwt: None,
+        });
+        let handler = |key: Option<Extension<AuthenticatedKey>>, headers: HeaderMap| async move {
+            // The LunaRoute key must not reach the upstream
+            assert!(headers.get("x-api-key").is_none());
+            key.map(|Extension(k)| k.0.name.clone()).unwrap_or_default()
+        };
+        let app = Router::new()
+            .route("/v1/messages", post(handler))
+            .route("/v1/chat/completions", post(handler))
+            .layer(axum::middleware::from_fn_with_state(
+                auth,
+                api_key_middleware,
+            ));
+
+        let send = |path: &str, key: Option<&str>, model: &str| {
+            let mut req = Request::post(path);
+            if let Some(key) = key {
+                req = req.header("x-api-key", key);
+            }
+            let body = serde_json::json!({ "model": model }).to_string();
+            app.clone().oneshot(req.body(Body::from(body)).unwrap())
+        };
+
+        let ok = send("/v1/messages", Some(&token), "claude-sonnet-4-5")
+            .await
+            .unwrap();
+        assert_eq!(ok.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(ok.into_body(), 1024).await.unwrap();
+        assert_eq!(&body[..], b"scoped");
+
+        let cases = [
+            (
+                "/v1/messages",
+                None,
+                "claude-sonnet-4-5",
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                "/v1/messages",
+                Some("lr_bad_key"),
+                "claude-sonnet-4-5",
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                "/v1/messages",
+                Some(token.as_str()),
+                "gpt-5",
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                "/v1/chat/completions",
+                Some(token.as_str()),
+                "claude-sonnet-4-5",
+                StatusCode::FORBIDDEN,
+            ),
+        ];
+        for (path, key, model, status) in cases {
+            let response = send(path, key, model).await.unwrap();
+            assert_eq!(response.status(), status, "{} {:?} {}", path, key, model);
+        }
+        // A model-scoped key cannot skip the check with an unreadable body
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/v1/messages")
+                    .header("x-api-key", &token)
+                    .body(Body::from("not json"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_accepts_client_cert_identity() {
+        let (_dir, keys) = store().await;
+        let keys = Arc::new(keys);
+        let (token, key) = keys.create(new_key("ci")).await.unwrap();
+
+        let app = |client_certs: bool, identity: Option<ClientIdentity>| {
+            let auth = Arc::new(ApiKeyAuth {
+                keys: keys.clone(),
+                max_body_bytes: 1024,
+                client_certs,
+                jwt: None,
+            });
+            Router::new()
+                .route(
+                    "/v1/messages",
+                    post(|Extension(key): Extension<AuthenticatedKey>| async move {
+                        key.0.id.clone()
+                    }),
+                )
+                .layer(axum::middleware::from_fn_with_state(
+                    auth,
+                    api_key_middleware,
+                ))
+                .layer(axum::middleware::from_fn(
+                    move |mut req: Request, next: Next| {
+                        if let Some(identity) = identity.clone() {
+                            req.extensions_mut().insert(identity);
+                        }
+                        next.run(req)
+                    },
+                ))
+        };
+        let send = |app: Router, key: Option<&str>| {
+            let mut req = Request::post("/v1/messages");
+            if let Some(key) = key {
+                req = req.header("x-api-key", key);
+            }
+            app.oneshot(req.body(Body::empty()).unwrap())
+        };
+        let alice = Some(ClientIdentity::client_cert("alice"));
+
+        let response = send(app(true, alice.clone()), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"cert:alice");
+
+        // A key still wins over the certificate
+        let response = send(app(true, alice.clone()), Some(&token)).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), 1024)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], key.id.as_bytes());
+
+        // Certificates only count when enabled
+        let response = send(app(false, alice), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(app(true, None), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_accepts_jwt() {
+        use base64::Engine;
+        use jsonwebtoken::{EncodingKey, Header};
+
+        let (dir, keys) = store().await;
+        let pair = rcgen::KeyPair::generate_for(&rcgen::PKCS_ED25519).unwrap();
+        let jwks = serde_json::json!({ "keys": [{
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "kid": "k1",
+            "x": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(pair.public_key_raw()),
+        }]});
+        let jwks_path = dir.path().join("jwks.json");
+        std::fs::write(&jwks_path, jwks.to_string()).unwrap();
+        let mut config = crate::jwt::JwtConfig::new("https://idp/", "unused");
+        config.jwks_url = None;
+        config.jwks_file = Some(jwks_path);
+        let verifier = JwtVerifier::new(config, reqwest::Client::new()).unwrap();
+
+        let auth = Arc::new(ApiKeyAuth {
+            keys: Arc::new(keys),
+            max_body_bytes: 1024,
+            client_certs: false,
+            jwt: Some(Arc::new(verifier)),
+        });
+        let app = Router::new()
+            .route(
+                "/v1/messages",
+                post(
+                    |Extension(key): Extension<AuthenticatedKey>,
+                     Extension(identity): Extension<ClientIdentity>,
+                     headers: HeaderMap| async move {
+                        assert!(headers.get(header::AUTHORIZATION).is_none());
+                        format!(
+                            "{} {} {}",
+                            key.0.id,
+                            key.0.owner.clone().unwrap_or_default(),
+                            identity.tenant.unwrap_or_default()
+                        )
+                    },
+                ),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                auth,
+                api_key_middleware,
+            ));
+
+        let mut header = Header::new(jsonwebtoken::Algorithm::EdDSA);
+        header.kid = Some("k1".to_string());
+        let key = EncodingKey::from_ed_pem(pair.serialize_pem().as_bytes()).unwrap();
+        let sign = |iss: &str| {
+            let claims = serde_json::json!({
+                "iss": iss,
+                "sub": "user-1",
+                "email": "alice@example.com",
+                "tenant": "acme",
+                "exp": Utc::now().timestamp() + 600,
+            });
+            jsonwebtoken::encode(&header, &claims, &key).unwrap()
+        };
+        let send = |token: String| {
+            let req = Request::post("/v1/messages")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(req)
+        };
+
+        let response = send(sign("https://idp/")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), 1024)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"jwt:user-1 alice@example.com acme");
+
+        let response = send(sign("https://other/")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send("upstream-key".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
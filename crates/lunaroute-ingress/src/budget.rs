@@ -0,0 +1,1669 @@
+//! Budgets
+//!
+//! A budget caps the tokens, requests or estimated dollars one subject may
+//! use in a window. Subjects come from the authenticated ingress key (the key
+//! itself, its `owner` as the user, its `tenant`) or from the
+//! `x-lunaroute-project` header. Keys with `projects` scopes may only bill
+//! those projects (the first when the header is absent); for other keys, and
+//! without auth, the header is chosen by the client, so project budgets are
+//! advisory unless every key that uses them is scoped.
+//!
+//! ```yaml
+//! budgets:
+//!   enabled: true
+//!   webhook_url: https://hooks.example.com/lunaroute
+//!   prices:
+//!     - { model: "claude-opus-*", input_per_million: 15.0, output_per_million: 75.0 }
+//!     - { model: "claude-sonnet-*", input_per_million: 3.0, output_per_million: 15.0 }
+//!   budgets:
+//!     - name: tenant-monthly
+//!       subject: tenant
+//!       metric: dollars
+//!       window: monthly
+//!       limit: 500
+//!       warn_at: [0.5, 0.8]
+//!       action: reroute
+//!       reroute_model: claude-haiku-4-5
+//!     - name: key-daily-tokens
+//!       subject: key
+//!       metric: tokens
+//!       window: daily
+//!       limit: 2000000
+//!       action: throttle
+//!       throttle_requests_per_minute: 2
+//! ```
+//!
+//! A budget without `ids` applies to every subject of its kind separately;
+//! with `ids` it only applies to those subjects. Windows are `daily`,
+//! `monthly` (calendar, UTC) and `rolling_30d`.
+//!
+//! Crossing a `warn_at` fraction adds an `x-lunaroute-budget-warning` header,
+//! counts a `warning` in `lunaroute_budget_events_total` and posts to the
+//! webhook once per threshold and window. At the limit the budget's action
+//! applies: `reject` returns a 429, `throttle` admits a few requests per
+//! minute, and `reroute` rewrites the request's model. Keys with the
+//! `budget_override` scope skip the action by sending
+//! `x-lunaroute-budget-override: true`.
+//!
+//! A `session_cap` bounds a single conversation instead, so a runaway agent
+//! loop stops on its own. Conversations are identified by the `session_id`,
+//! `session-id` or `x-session-id` header, or the session in Anthropic
+//! `metadata.user_id` (Claude Code). Past the cap, `reject` answers with a
+//! dialect-native 403, and `notify` first forwards `grace_requests` more
+//! requests with a notice prepended to the conversation (like provider switch
+//! notifications) so the agent can wrap up, then rejects.
+//!
+//! ```yaml
+//! budgets:
+//!   enabled: true
+//!   session_cap:
+//!     max_dollars: 25
+//!     max_requests: 2000
+//!     action: notify
+//!     grace_requests: 3
+//! ```
+//!
+//! Usage is counted per UTC day in the [`StateStore`], so replicas sharing the
+//! PostgreSQL backend see one total. Token usage is read from the `usage`
+//! reported in responses (cache reads and writes count as input tokens), and
+//! dollars are estimated from the `prices` table; models without a price cost
+//! nothing.
+
+use crate::auth::{AuthenticatedKey, listener_for_path, wildcard_match};
+use crate::rate_limit::{take_tokens, too_many_requests};
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use futures::StreamExt;
+use lunaroute_observability::Metrics;
+use lunaroute_storage::{StateStore, StorageResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Prefix of every budget key in the state store
+pub const BUDGET_PREFIX: &str = "budget:";
+
+/// Project a request is billed to
+pub const PROJECT_HEADER: &str = "x-lunaroute-project";
+
+/// Lets keys with the `budget_override` scope go past hard limits
+pub const OVERRIDE_HEADER: &str = "x-lunaroute-budget-override";
+
+/// Budgets past a warning threshold (response header)
+pub const WARNING_HEADER: &str = "x-lunaroute-budget-warning";
+
+/// Actions taken because of exhausted budgets (response header)
+pub const ACTION_HEADER: &str = "x-lunaroute-budget-action";
+
+/// Day counters outlive the longest window
+const COUNTER_TTL: Duration = Duration::from_secs(32 * 24 * 3600);
+
+/// Dollars are counted in micro-dollars so counters stay integers
+const MICRO_DOLLARS: f64 = 1_000_000.0;
+
+/// Largest non-streaming response body scanned for usage
+const MAX_SCAN_BYTES: usize = 4 * 1024 * 1024;
+
+/// End of a larger body kept to find its `usage`, which comes last
+const TAIL_SCAN_BYTES: usize = 64 * 1024;
+
+/// What a budget is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetSubject {
+    /// Ingress API key ID
+    Key,
+    /// Key owner
+    User,
+    /// Key tenant
+    Tenant,
+    /// `x-lunaroute-project` header
+    Project,
+}
+
+impl BudgetSubject {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BudgetSubject::Key => "key",
+            BudgetSubject::User => "user",
+            BudgetSubject::Tenant => "tenant",
+            BudgetSubject::Project => "project",
+        }
+    }
+}
+
+/// What a budget counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetMetric {
+    /// Input plus output tokens
+    Tokens,
+    Requests,
+    /// Estimated from the price table
+    Dollars,
+}
+
+impl BudgetMetric {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BudgetMetric::Tokens => "tokens",
+            BudgetMetric::Requests => "requests",
+            BudgetMetric::Dollars => "dollars",
+        }
+    }
+
+    /// Counter units per unit of the metric
+    fn scale(self) -> f64 {
+        match self {
+            BudgetMetric::Dollars => MICRO_DOLLARS,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Period usage is summed over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetWindow {
+    /// Current UTC day
+    Daily,
+    /// Current calendar month (UTC)
+    Monthly,
+    /// Today and the 29 days before it
+    #[serde(rename = "rolling_30d")]
+    Rolling30d,
+}
+
+impl BudgetWindow {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BudgetWindow::Daily => "daily",
+            BudgetWindow::Monthly => "monthly",
+            BudgetWindow::Rolling30d => "rolling_30d",
+        }
+    }
+
+    /// Days whose usage counts toward the window containing `today`
+    fn days(self, today: NaiveDate) -> Vec<NaiveDate> {
+        let first = match self {
+            BudgetWindow::Daily => today,
+            BudgetWindow::Monthly => today.with_day(1).unwrap_or(today),
+            BudgetWindow::Rolling30d => today - chrono::Days::new(29),
+        };
+        first.iter_days().take_while(|day| *day <= today).collect()
+    }
+
+    /// When the oldest usage in the window stops counting
+    pub fn resets_at(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let today = now.date_naive();
+        let next = match self {
+            BudgetWindow::Daily | BudgetWindow::Rolling30d => today.succ_opt(),
+            BudgetWindow::Monthly => today
+                .with_day(1)
+                .and_then(|first| first.checked_add_months(chrono::Months::new(1))),
+        };
+        next.and_then(|day| day.and_hms_opt(0, 0, 0))
+            .map(|at| at.and_utc())
+            .unwrap_or(now)
+    }
+}
+
+/// What happens once a budget is used up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    /// Reject with a 429 until the window resets
+    #[default]
+    Reject,
+    /// Admit `throttle_requests_per_minute`
+    Throttle,
+    /// Send requests to `reroute_model`
+    Reroute,
+}
+
+/// A spending limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    /// Unique name (used in headers, metrics and state keys)
+    pub name: String,
+    pub subject: BudgetSubject,
+    /// Subjects the budget applies to; empty applies it to each subject separately
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ids: Vec<String>,
+    pub metric: BudgetMetric,
+    pub window: BudgetWindow,
+    /// In units of `metric` (dollars for `dollars`)
+    pub limit: f64,
+    /// Fractions of the limit that trigger warnings (default: 0.8)
+    #[serde(default = "default_warn_at")]
+    pub warn_at: Vec<f64>,
+    #[serde(default)]
+    pub action: BudgetAction,
+    /// Model to send requests to once the limit is reached (`action: reroute`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reroute_model: Option<String>,
+    /// Requests admitted per minute once the limit is reached (`action: throttle`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throttle_requests_per_minute: Option<u32>,
+}
+
+fn default_warn_at() -> Vec<f64> {
+    vec![0.8]
+}
+
+/// What happens once a conversation reaches its cap
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionCapAction {
+    /// Stop forwarding with a dialect-native error
+    #[default]
+    Reject,
+    /// Forward `grace_requests` more requests with a wrap-up notice, then reject
+    Notify,
+}
+
+/// Per-conversation spend cap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionCap {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_dollars: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_requests: Option<u64>,
+    #[serde(default)]
+    pub action: SessionCapAction,
+    /// Requests forwarded with the notice before rejecting (`action: notify`)
+    #[serde(default = "default_grace_requests")]
+    pub grace_requests: u64,
+    /// Notice prepended to the conversation; `${usage}` is replaced with the
+    /// limit that was reached
+    #[serde(default = "default_session_notice")]
+    pub notice: String,
+    /// How long a conversation's usage is remembered after it starts (default: 24)
+    #[serde(default = "default_session_hours")]
+    pub window_hours: u64,
+}
+
+fn default_grace_requests() -> u64 {
+    3
+}
+
+fn default_session_notice() -> String {
+    "IMPORTANT: This conversation has reached its spending limit (${usage}). \
+     Do not start any new work. Briefly summarize what has been done and what \
+     remains, then end your turn."
+        .to_string()
+}
+
+fn default_session_hours() -> u64 {
+    24
+}
+
+/// Usage of one conversation
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct SessionUsage {
+    requests: u64,
+    tokens: u64,
+    dollars: f64,
+    /// Requests forwarded with the wrap-up notice
+    notices: u64,
+}
+
+impl SessionCap {
+    /// The limit a conversation has reached, described for the client
+    fn reached(&self, usage: &SessionUsage) -> Option<String> {
+        if let Some(max) = self.max_dollars
+            && usage.dollars >= max
+        {
+            return Some(format!("${:.2} of ${:.2}", usage.dollars, max));
+        }
+        if let Some(max) = self.max_tokens
+            && usage.tokens >= max
+        {
+            return Some(format!("{} of {} tokens", usage.tokens, max));
+        }
+        if let Some(max) = self.max_requests
+            && usage.requests >= max
+        {
+            return Some(format!("{} of {} requests", usage.requests, max));
+        }
+        None
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::from_secs(self.window_hours.max(1) * 3600)
+    }
+}
+
+impl Budget {
+    /// Whether the budget covers a subject ID
+    fn covers(&self, id: &str) -> bool {
+        self.ids.is_empty() || self.ids.iter().any(|i| i == id)
+    }
+
+    /// Highest warning threshold `used` has reached
+    fn crossed_threshold(&self, used: f64) -> Option<f64> {
+        self.warn_at
+            .iter()
+            .copied()
+            .filter(|t| used >= t * self.limit)
+            .max_by(f64::total_cmp)
+    }
+
+    fn state(&self, used: f64) -> BudgetState {
+        if used >= self.limit {
+            BudgetState::Exceeded
+        } else if self.crossed_threshold(used).is_some() {
+            BudgetState::Warning
+        } else {
+            BudgetState::Ok
+        }
+    }
+}
+
+/// USD per million tokens for models matching a pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Model name pattern; `*` matches any run of characters
+    pub model: String,
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+/// Budget configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Track and enforce the budgets below (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub budgets: Vec<Budget>,
+    /// Price table for `dollars` budgets; the first matching pattern wins
+    #[serde(default)]
+    pub prices: Vec<ModelPrice>,
+    /// Receives a JSON POST when a budget crosses a warning threshold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Cap on what a single conversation may use
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_cap: Option<SessionCap>,
+}
+
+impl BudgetConfig {
+    /// Whether any budget or the session cap is enforced
+    pub fn is_active(&self) -> bool {
+        self.enabled && (!self.budgets.is_empty() || self.session_cap.is_some())
+    }
+
+    /// Check for settings that can't work
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(cap) = &self.session_cap
+            && cap.max_dollars.is_none()
+            && cap.max_tokens.is_none()
+            && cap.max_requests.is_none()
+        {
+            return Err("session_cap needs max_dollars, max_tokens or max_requests".to_string());
+        }
+        let mut names = BTreeSet::new();
+        for budget in &self.budgets {
+            if budget.name.is_empty() || budget.name.contains([':', '/']) {
+                return Err(format!(
+                    "budget name '{}' must be non-empty without ':' or '/'",
+                    budget.name
+                ));
+            }
+            if !names.insert(&budget.name) {
+                return Err(format!("duplicate budget name '{}'", budget.name));
+            }
+            if budget.limit.is_nan() || budget.limit <= 0.0 {
+                return Err(format!("budget '{}' needs a positive limit", budget.name));
+            }
+            if budget
+                .warn_at
+                .iter()
+                .any(|t| t.is_nan() || *t <= 0.0 || *t >= 1.0)
+            {
+                return Err(format!(
+                    "budget '{}' warn_at fractions must be between 0 and 1",
+                    budget.name
+                ));
+            }
+            match budget.action {
+                BudgetAction::Reroute if budget.reroute_model.is_none() => {
+                    return Err(format!(
+                        "budget '{}' reroutes but has no reroute_model",
+                        budget.name
+                    ));
+                }
+                BudgetAction::Throttle if budget.throttle_requests_per_minute.is_none() => {
+                    return Err(format!(
+                        "budget '{}' throttles but has no throttle_requests_per_minute",
+                        budget.name
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Price of a model, if listed
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices.iter().find(|p| wildcard_match(&p.model, model))
+    }
+}
+
+/// Tokens reported by a response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl Usage {
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Merge usage from one response object or stream event
+    ///
+    /// Streams report cumulative counts, so the larger value wins.
+    fn merge_from(&mut self, usage: &serde_json::Value) {
+        let count = |names: &[&str]| {
+            names
+                .iter()
+                .filter_map(|n| usage.get(*n).and_then(|v| v.as_u64()))
+                .sum::<u64>()
+        };
+        let input = count(&[
+            "input_tokens",
+            "prompt_tokens",
+            "cache_creation_input_tokens",
+            "cache_read_input_tokens",
+        ]);
+        let output = count(&["output_tokens", "completion_tokens"]);
+        self.input_tokens = self.input_tokens.max(input);
+        self.output_tokens = self.output_tokens.max(output);
+    }
+}
+
+/// Where a budget stands for one subject
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetState {
+    Ok,
+    Warning,
+    Exceeded,
+}
+
+impl BudgetState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BudgetState::Ok => "ok",
+            BudgetState::Warning => "warning",
+            BudgetState::Exceeded => "exceeded",
+        }
+    }
+}
+
+/// Current usage of a budget by one subject
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub budget: String,
+    pub subject: BudgetSubject,
+    pub subject_id: String,
+    pub metric: BudgetMetric,
+    pub window: BudgetWindow,
+    pub limit: f64,
+    pub used: f64,
+    pub state: BudgetState,
+    pub resets_at: DateTime<Utc>,
+}
+
+/// Subjects a request is billed to
+#[derive(Debug, Default)]
+struct Subjects {
+    key: Option<String>,
+    user: Option<String>,
+    tenant: Option<String>,
+    project: Option<String>,
+}
+
+impl Subjects {
+    fn get(&self, subject: BudgetSubject) -> Option<&str> {
+        match subject {
+            BudgetSubject::Key => self.key.as_deref(),
+            BudgetSubject::User => self.user.as_deref(),
+            BudgetSubject::Tenant => self.tenant.as_deref(),
+            BudgetSubject::Project => self.project.as_deref(),
+        }
+    }
+}
+
+/// Tracks budget usage in a [`StateStore`] and enforces the limits
+pub struct BudgetTracker {
+    config: BudgetConfig,
+    store: Arc<dyn StateStore>,
+    metrics: Option<Arc<Metrics>>,
+    http: reqwest::Client,
+    /// Largest request body buffered to reroute a request
+    max_body_bytes: usize,
+}
+
+impl BudgetTracker {
+    pub fn new(config: BudgetConfig, store: Arc<dyn StateStore>, max_body_bytes: usize) -> Self {
+        Self {
+            config,
+            store,
+            metrics: None,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            max_body_bytes,
+        }
+    }
+
+    /// Export usage ratios and budget events
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn config(&self) -> &BudgetConfig {
+        &self.config
+    }
+
+    /// Budgets (by index) and subject IDs that apply to a request
+    fn applicable(&self, subjects: &Subjects) -> Vec<(usize, String)> {
+        self.config
+            .budgets
+            .iter()
+            .enumerate()
+            .filter_map(|(index, budget)| {
+                let id = subjects.get(budget.subject)?;
+                budget.covers(id).then(|| (index, id.to_string()))
+            })
+            .collect()
+    }
+
+    fn counter_prefix(budget: &Budget) -> String {
+        format!("{}usage:{}:", BUDGET_PREFIX, budget.name)
+    }
+
+    fn counter_key(budget: &Budget, id: &str, day: NaiveDate) -> String {
+        format!("{}{}/{}", Self::counter_prefix(budget), id, day)
+    }
+
+    /// Usage of a budget by one subject in the window containing `now`
+    pub async fn used(&self, budget: &Budget, id: &str, now: DateTime<Utc>) -> StorageResult<f64> {
+        let keys: Vec<String> = budget
+            .window
+            .days(now.date_naive())
+            .into_iter()
+            .map(|day| Self::counter_key(budget, id, day))
+            .collect();
+        let units: i64 = self
+            .store
+            .get_many(&keys)
+            .await?
+            .into_iter()
+            .flatten()
+            .filter_map(|raw| raw.try_into().ok().map(i64::from_le_bytes))
+            .sum();
+        Ok(units as f64 / budget.metric.scale())
+    }
+
+    /// Add usage to today's counter
+    async fn record(&self, budget: &Budget, id: &str, amount: f64, now: DateTime<Utc>) {
+        let units = (amount * budget.metric.scale()).round() as i64;
+        if units <= 0 {
+            return;
+        }
+        let key = Self::counter_key(budget, id, now.date_naive());
+        if let Err(e) = self.add(&key, units, COUNTER_TTL).await {
+            tracing::warn!("Failed to record usage for budget '{}': {}", budget.name, e);
+        }
+    }
+
+    /// Add to a counter, creating it with a TTL first (increment keeps it)
+    async fn add(&self, key: &str, units: i64, ttl: Duration) -> StorageResult<i64> {
+        self.store
+            .compare_and_swap(key, None, 0i64.to_le_bytes().to_vec(), Some(ttl))
+            .await?;
+        self.store.increment(key, units).await
+    }
+
+    fn session_key(id: &str, field: &str) -> String {
+        format!("{}session:{}/{}", BUDGET_PREFIX, id, field)
+    }
+
+    /// What a conversation has used so far
+    async fn session_usage(&self, id: &str) -> StorageResult<SessionUsage> {
+        let keys: Vec<String> = ["requests", "tokens", "micro_dollars", "notices"]
+            .iter()
+            .map(|field| Self::session_key(id, field))
+            .collect();
+        let values: Vec<u64> = self
+            .store
+            .get_many(&keys)
+            .await?
+            .into_iter()
+            .map(|raw| {
+                raw.and_then(|raw| raw.try_into().ok())
+                    .map(i64::from_le_bytes)
+                    .unwrap_or(0)
+                    .max(0) as u64
+            })
+            .collect();
+        Ok(SessionUsage {
+            requests: values[0],
+            tokens: values[1],
+            dollars: values[2] as f64 / MICRO_DOLLARS,
+            notices: values[3],
+        })
+    }
+
+    /// Add to one of a conversation's counters
+    async fn record_session(&self, cap: &SessionCap, id: &str, field: &str, units: i64) {
+        if units <= 0 {
+            return;
+        }
+        if let Err(e) = self
+            .add(&Self::session_key(id, field), units, cap.ttl())
+            .await
+        {
+            tracing::warn!("Failed to record usage for session {}: {}", id, e);
+        }
+    }
+
+    /// Record what a finished response used against every applicable budget
+    async fn record_usage(
+        &self,
+        targets: &[(usize, String)],
+        session: Option<&str>,
+        model: Option<&str>,
+        usage: Usage,
+    ) {
+        let cost = model.and_then(|m| self.config.price(m)).map(|price| {
+            (usage.input_tokens as f64 * price.input_per_million
+                + usage.output_tokens as f64 * price.output_per_million)
+                / 1_000_000.0
+        });
+        if let (Some(cap), Some(id)) = (&self.config.session_cap, session) {
+            self.record_session(cap, id, "tokens", usage.total() as i64)
+                .await;
+            if let Some(cost) = cost {
+                let units = (cost * MICRO_DOLLARS).round() as i64;
+                self.record_session(cap, id, "micro_dollars", units).await;
+            }
+        }
+        let now = Utc::now();
+        for (index, id) in targets {
+            let budget = &self.config.budgets[*index];
+            let amount = match budget.metric {
+                BudgetMetric::Tokens => usage.total() as f64,
+                BudgetMetric::Dollars => match cost {
+                    Some(cost) => cost,
+                    None => {
+                        tracing::debug!(
+                            "No price for model {:?}; budget '{}' not charged",
+                            model,
+                            budget.name
+                        );
+                        continue;
+                    }
+                },
+                BudgetMetric::Requests => continue,
+            };
+            self.record(budget, id, amount, now).await;
+        }
+    }
+
+    /// Usage of every budget by every subject that has used it
+    pub async fn status(&self) -> StorageResult<Vec<BudgetStatus>> {
+        let now = Utc::now();
+        let mut statuses = Vec::new();
+        for budget in &self.config.budgets {
+            let ids: BTreeSet<String> = if budget.ids.is_empty() {
+                let prefix = Self::counter_prefix(budget);
+                self.store
+                    .list_keys(&prefix)
+                    .await?
+                    .iter()
+                    .filter_map(|key| key.strip_prefix(&prefix)?.rsplit_once('/'))
+                    .map(|(id, _)| id.to_string())
+                    .collect()
+            } else {
+                budget.ids.iter().cloned().collect()
+            };
+            for id in ids {
+                let used = self.used(budget, &id, now).await?;
+                statuses.push(BudgetStatus {
+                    budget: budget.name.clone(),
+                    subject: budget.subject,
+                    subject_id: id,
+                    metric: budget.metric,
+                    window: budget.window,
+                    limit: budget.limit,
+                    used,
+                    state: budget.state(used),
+                    resets_at: budget.window.resets_at(now),
+                });
+            }
+        }
+        Ok(statuses)
+    }
+
+    fn event(&self, budget: &Budget, event: &str) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_budget_event(&budget.name, event);
+        }
+    }
+
+    /// Count and announce a crossed warning threshold, once per window
+    async fn warn(&self, budget: &Budget, id: &str, threshold: f64, used: f64, now: DateTime<Utc>) {
+        let window_start = budget.window.days(now.date_naive())[0];
+        let key = format!(
+            "{}warned:{}:{}/{}:{}",
+            BUDGET_PREFIX, budget.name, id, window_start, threshold
+        );
+        let ttl = (budget.window.resets_at(now) - now)
+            .to_std()
+            .unwrap_or_default()
+            .max(Duration::from_secs(1));
+        match self
+            .store
+            .compare_and_swap(&key, None, Vec::new(), Some(ttl))
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                tracing::warn!("Failed to record budget warning: {}", e);
+                return;
+            }
+        }
+
+        tracing::warn!(
+            "Budget '{}' for {} '{}' reached {:.0}% ({} of {} {})",
+            budget.name,
+            budget.subject.as_str(),
+            id,
+            threshold * 100.0,
+            used,
+            budget.limit,
+            budget.metric.as_str()
+        );
+        self.event(budget, "warning");
+
+        if let Some(url) = &self.config.webhook_url {
+            let request = self.http.post(url).json(&serde_json::json!({
+                "event": "budget_warning",
+                "budget": budget.name,
+                "subject": budget.subject,
+                "subject_id": id,
+                "metric": budget.metric,
+                "window": budget.window,
+                "limit": budget.limit,
+                "used": used,
+                "threshold": threshold,
+            }));
+            tokio::spawn(async move {
+                if let Err(e) = request.send().await.and_then(|r| r.error_for_status()) {
+                    tracing::warn!("Budget webhook failed: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// Scans a response body for the usage it reports; charges budgets when dropped
+struct UsageRecorder {
+    tracker: Arc<BudgetTracker>,
+    targets: Vec<(usize, String)>,
+    session: Option<String>,
+    model: Option<String>,
+    sse: bool,
+    buffer: Vec<u8>,
+    /// End of a non-streaming body past `MAX_SCAN_BYTES`
+    tail: Option<Vec<u8>>,
+    usage: Usage,
+}
+
+impl UsageRecorder {
+    fn feed(&mut self, chunk: &[u8]) {
+        if self.buffer.len() + chunk.len() > MAX_SCAN_BYTES {
+            if !self.sse {
+                let tail = self.tail.get_or_insert_with(Vec::new);
+                tail.extend_from_slice(chunk);
+                tail.drain(..tail.len().saturating_sub(TAIL_SCAN_BYTES));
+            }
+            return;
+        }
+        self.buffer.extend_from_slice(chunk);
+        if !self.sse {
+            return;
+        }
+        while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            if let Some(data) = line.strip_prefix(b"data:")
+                && let Ok(event) = serde_json::from_slice::<serde_json::Value>(data.trim_ascii())
+            {
+                self.scan(&event);
+            }
+        }
+    }
+
+    /// Pick up usage and model from a response object or stream event
+    fn scan(&mut self, value: &serde_json::Value) {
+        // Anthropic stream events nest the message; Responses API events nest the response
+        for object in [Some(value), value.get("message"), value.get("response")]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(usage) = object.get("usage") {
+                self.usage.merge_from(usage);
+            }
+            if self.model.is_none()
+                && let Some(model) = object.get("model").and_then(|m| m.as_str())
+            {
+                self.model = Some(model.to_string());
+            }
+        }
+    }
+}
+
+impl Drop for UsageRecorder {
+    fn drop(&mut self) {
+        if let Some(tail) = self.tail.take() {
+            // Too large to parse whole: take the last `usage` object, and the
+            // model from the start of the body
+            if let Some(usage) = last_field(&tail, "usage") {
+                self.usage.merge_from(&usage);
+            }
+            if self.model.is_none() {
+                self.model =
+                    first_field(&self.buffer, "model").and_then(|m| m.as_str().map(str::to_string));
+            }
+            if self.usage.total() == 0 {
+                tracing::warn!(
+                    "No usage found in a response larger than {} bytes; budgets were not charged",
+                    MAX_SCAN_BYTES
+                );
+            }
+        } else if !self.sse
+            && let Ok(body) = serde_json::from_slice::<serde_json::Value>(&self.buffer)
+        {
+            self.scan(&body);
+        }
+        if self.usage.total() == 0 {
+            return;
+        }
+        let tracker = self.tracker.clone();
+        let targets = std::mem::take(&mut self.targets);
+        let session = self.session.take();
+        let model = self.model.take();
+        let usage = self.usage;
+        tokio::spawn(async move {
+            tracker
+                .record_usage(&targets, session.as_deref(), model.as_deref(), usage)
+                .await;
+        });
+    }
+}
+
+/// Value of a JSON field `"name": ...` starting at `at` in raw body bytes
+fn field_at(bytes: &[u8], at: usize, name: &str) -> Option<serde_json::Value> {
+    let rest = bytes[at + name.len() + 2..].trim_ascii_start();
+    let rest = rest.strip_prefix(b":")?.trim_ascii_start();
+    serde_json::Deserializer::from_slice(rest)
+        .into_iter::<serde_json::Value>()
+        .next()?
+        .ok()
+}
+
+/// Positions of `"name"` in raw body bytes
+fn field_positions<'a>(
+    bytes: &'a [u8],
+    name: &str,
+) -> impl DoubleEndedIterator<Item = usize> + use<'a> {
+    let needle = format!("\"{}\"", name).into_bytes();
+    bytes
+        .windows(needle.len())
+        .enumerate()
+        .filter(move |(_, window)| *window == needle.as_slice())
+        .map(|(at, _)| at)
+}
+
+/// First readable `"name": value` in a truncated body
+fn first_field(bytes: &[u8], name: &str) -> Option<serde_json::Value> {
+    field_positions(bytes, name).find_map(|at| field_at(bytes, at, name))
+}
+
+/// Last `"name": {...}` object in the end of a body
+fn last_field(bytes: &[u8], name: &str) -> Option<serde_json::Value> {
+    field_positions(bytes, name)
+        .rev()
+        .find_map(|at| field_at(bytes, at, name).filter(|v| v.is_object()))
+}
+
+/// Conversation a request belongs to: a session header, or the Claude Code
+/// session in Anthropic `metadata.user_id`
+fn session_id(headers: &HeaderMap, body: Option<&serde_json::Value>) -> Option<String> {
+    ["session_id", "session-id", "x-session-id"]
+        .iter()
+        .find_map(|name| headers.get(*name))
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 256)
+        .map(str::to_string)
+        .or_else(|| {
+            body?
+                .get("metadata")?
+                .get("user_id")
+                .and_then(crate::anthropic::extract_session_id_from_user_id)
+        })
+}
+
+/// Prepend a notice to the conversation, as provider switch notifications do
+fn inject_notice(body: &mut serde_json::Value, notice: &str) {
+    let message = serde_json::json!({"role": "user", "content": notice});
+    if let Some(messages) = body.get_mut("messages").and_then(|m| m.as_array_mut()) {
+        messages.insert(0, message);
+    } else if let Some(input) = body.get_mut("input") {
+        // Responses API: `input` is a message list or a bare string
+        match input {
+            serde_json::Value::Array(items) => items.insert(0, message),
+            serde_json::Value::String(text) => {
+                let original = serde_json::json!({"role": "user", "content": text.clone()});
+                *input = serde_json::json!([message, original]);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Error for a conversation past its cap, in the dialect of the endpoint
+fn session_cap_error(path: &str, message: String) -> Response {
+    let body = match listener_for_path(path) {
+        Some("anthropic") => serde_json::json!({
+            "type": "error",
+            "error": {"type": "permission_error", "message": message}
+        }),
+        _ => serde_json::json!({
+            "error": {
+                "message": message,
+                "type": "insufficient_quota",
+                "param": null,
+                "code": "session_cap_exceeded"
+            }
+        }),
+    };
+    (StatusCode::FORBIDDEN, axum::Json(body)).into_response()
+}
+
+/// Budget middleware; runs inside the API key middleware so key, user and
+/// tenant budgets see the authenticated key
+pub async fn budget_middleware(
+    State(tracker): State<Arc<BudgetTracker>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let key = req
+        .extensions()
+        .get::<AuthenticatedKey>()
+        .map(|AuthenticatedKey(key)| key.clone());
+    let requested_project = req
+        .headers()
+        .get(PROJECT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    // Keys scoped to projects bill only those; others bill what they name
+    let project = match key.as_ref().map(|k| &k.scopes.projects) {
+        Some(projects) if !projects.is_empty() => match requested_project {
+            None => projects.first().cloned(),
+            Some(project) if projects.contains(&project) => Some(project),
+            Some(project) => {
+                return crate::types::IngressError::Forbidden(format!(
+                    "API key may not bill project '{}'",
+                    project
+                ))
+                .into_response();
+            }
+        },
+        _ => requested_project,
+    };
+    let subjects = Subjects {
+        key: key.as_ref().map(|k| k.id.clone()),
+        user: key.as_ref().and_then(|k| k.owner.clone()),
+        tenant: key.as_ref().and_then(|k| k.tenant.clone()),
+        project,
+    };
+    let may_override = req
+        .headers()
+        .get(OVERRIDE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        && key.as_ref().is_some_and(|k| k.scopes.budget_override);
+
+    let (mut parts, body) = req.into_parts();
+    parts.headers.remove(OVERRIDE_HEADER);
+    let targets = tracker.applicable(&subjects);
+    let session_cap = tracker.config.session_cap.as_ref();
+    if targets.is_empty() && session_cap.is_none() {
+        return next.run(Request::from_parts(parts, body)).await;
+    }
+
+    // The session cap and rerouting work on the JSON body
+    let needs_body = parts.method == Method::POST
+        && (session_cap.is_some()
+            || targets
+                .iter()
+                .any(|(index, _)| tracker.config.budgets[*index].action == BudgetAction::Reroute));
+    let (body, mut json) = if needs_body {
+        let bytes = match axum::body::to_bytes(body, tracker.max_body_bytes).await {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                return crate::types::IngressError::RequestTooLarge(tracker.max_body_bytes)
+                    .into_response();
+            }
+        };
+        let json = serde_json::from_slice::<serde_json::Value>(&bytes).ok();
+        (Body::from(bytes), json)
+    } else {
+        (body, None)
+    };
+    let mut rewrite = false;
+    let mut rerouted = false;
+
+    let path = parts.uri.path().to_string();
+    let now = Utc::now();
+    let mut warnings = Vec::new();
+    let mut actions = Vec::new();
+
+    let session = session_cap.and_then(|_| session_id(&parts.headers, json.as_ref()));
+    if let (Some(cap), Some(id)) = (session_cap, &session) {
+        match tracker.session_usage(id).await {
+            Ok(usage) => {
+                if let Some(reached) = cap.reached(&usage) {
+                    if cap.action == SessionCapAction::Notify
+                        && usage.notices < cap.grace_requests
+                        && let Some(json) = json.as_mut()
+                    {
+                        inject_notice(json, &cap.notice.replace("${usage}", &reached));
+                        rewrite = true;
+                        tracker.record_session(cap, id, "notices", 1).await;
+                        if let Some(metrics) = &tracker.metrics {
+                            metrics.record_budget_event("session_cap", "notified");
+                        }
+                        actions.push("notify:session_cap".to_string());
+                    } else {
+                        tracing::warn!("Session {} reached its cap ({})", id, reached);
+                        if let Some(metrics) = &tracker.metrics {
+                            metrics.record_budget_event("session_cap", "rejected");
+                        }
+                        return session_cap_error(
+                            &path,
+                            format!(
+                                "This conversation reached its spending cap ({}); \
+                                 start a new session to continue",
+                                reached
+                            ),
+                        );
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Session cap check failed for {}: {}", id, e),
+        }
+    }
+
+    for (index, id) in &targets {
+        let budget = &tracker.config.budgets[*index];
+        let used = match tracker.used(budget, id, now).await {
+            Ok(used) => used,
+            Err(e) => {
+                // Budgets never block traffic because the store is down
+                tracing::warn!("Budget check failed for '{}': {}", budget.name, e);
+                continue;
+            }
+        };
+        if let Some(metrics) = &tracker.metrics {
+            metrics.update_budget_usage(
+                &budget.name,
+                &format!("{}:{}", budget.subject.as_str(), id),
+                used / budget.limit,
+            );
+        }
+
+        if used < budget.limit {
+            if let Some(threshold) = budget.crossed_threshold(used) {
+                warnings.push(format!(
+                    "{} {:.0}% of {} {} {}",
+                    budget.name,
+                    used / budget.limit * 100.0,
+                    budget.limit,
+                    budget.metric.as_str(),
+                    budget.window.as_str()
+                ));
+                tracker.warn(budget, id, threshold, used, now).await;
+            }
+            continue;
+        }
+
+        if may_override {
+            tracker.event(budget, "overridden");
+            actions.push(format!("override:{}", budget.name));
+            continue;
+        }
+        let exhausted = format!(
+            "Budget '{}' exhausted for {} '{}' ({} of {} {} {})",
+            budget.name,
+            budget.subject.as_str(),
+            id,
+            used,
+            budget.limit,
+            budget.metric.as_str(),
+            budget.window.as_str()
+        );
+        match budget.action {
+            BudgetAction::Reject => {
+                tracker.event(budget, "rejected");
+                let retry_after = (budget.window.resets_at(now) - now)
+                    .to_std()
+                    .unwrap_or_default();
+                return too_many_requests(
+                    &path,
+                    exhausted,
+                    "insufficient_quota",
+                    "budget_exceeded",
+                    retry_after,
+                );
+            }
+            BudgetAction::Throttle => {
+                let per_minute = budget.throttle_requests_per_minute.unwrap_or(1);
+                let bucket = format!("budget:{}:{}", budget.name, id);
+                match take_tokens(tracker.store.as_ref(), &bucket, per_minute, 1).await {
+                    Ok(take) if !take.allowed => {
+                        tracker.event(budget, "throttled");
+                        return too_many_requests(
+                            &path,
+                            format!("{}; throttled to {}/min", exhausted, per_minute),
+                            "insufficient_quota",
+                            "budget_exceeded",
+                            take.retry_after,
+                        );
+                    }
+                    Ok(_) => actions.push(format!("throttle:{}", budget.name)),
+                    Err(e) => tracing::warn!("Budget throttle failed: {}", e),
+                }
+            }
+            BudgetAction::Reroute => {
+                // The first rerouting budget picks the model
+                if !rerouted
+                    && let Some(json) = json.as_mut().filter(|j| j.get("model").is_some())
+                    && let Some(model) = &budget.reroute_model
+                {
+                    tracing::info!("Budget '{}' exhausted; rerouting to {}", budget.name, model);
+                    json["model"] = serde_json::Value::String(model.clone());
+                    rewrite = true;
+                    rerouted = true;
+                    tracker.event(budget, "rerouted");
+                    actions.push(format!("reroute:{}", budget.name));
+                }
+            }
+        }
+    }
+
+    let body = match json.filter(|_| rewrite) {
+        Some(json) => match serde_json::to_vec(&json) {
+            Ok(bytes) => {
+                parts
+                    .headers
+                    .insert(header::CONTENT_LENGTH, HeaderValue::from(bytes.len()));
+                Body::from(bytes)
+            }
+            Err(_) => body,
+        },
+        None => body,
+    };
+
+    for (index, id) in &targets {
+        let budget = &tracker.config.budgets[*index];
+        if budget.metric == BudgetMetric::Requests {
+            tracker.record(budget, id, 1.0, now).await;
+        }
+    }
+    if let (Some(cap), Some(id)) = (session_cap, &session) {
+        tracker.record_session(cap, id, "requests", 1).await;
+    }
+
+    let mut response = next.run(Request::from_parts(parts, body)).await;
+    let headers = response.headers_mut();
+    if !warnings.is_empty()
+        && let Ok(value) = HeaderValue::try_from(warnings.join("; "))
+    {
+        headers.insert(WARNING_HEADER, value);
+    }
+    if !actions.is_empty()
+        && let Ok(value) = HeaderValue::try_from(actions.join(", "))
+    {
+        headers.insert(ACTION_HEADER, value);
+    }
+
+    let usage_targets: Vec<(usize, String)> = targets
+        .into_iter()
+        .filter(|(index, _)| tracker.config.budgets[*index].metric != BudgetMetric::Requests)
+        .collect();
+    if (usage_targets.is_empty() && session.is_none()) || !response.status().is_success() {
+        return response;
+    }
+
+    let sse = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    let mut recorder = UsageRecorder {
+        tracker: tracker.clone(),
+        targets: usage_targets,
+        session,
+        model: None,
+        sse,
+        buffer: Vec::new(),
+        tail: None,
+        usage: Usage::default(),
+    };
+    let (parts, body) = response.into_parts();
+    let body = body.into_data_stream().map(move |chunk| {
+        if let Ok(bytes) = &chunk {
+            recorder.feed(bytes);
+        }
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{ApiKey, KeyScopes};
+    use axum::{Router, http::StatusCode, routing::post};
+    use lunaroute_storage::FileStateStore;
+    use tower::ServiceExt;
+
+    fn budget(yaml: &str) -> Budget {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    async fn tracker(config: BudgetConfig) -> (tempfile::TempDir, Arc<BudgetTracker>) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStateStore::new(dir.path().join("state.json"))
+            .await
+            .unwrap();
+        (
+            dir,
+            Arc::new(BudgetTracker::new(config, Arc::new(store), 1024 * 1024)),
+        )
+    }
+
+    fn key(id: &str, budget_override: bool) -> AuthenticatedKey {
+        AuthenticatedKey(Arc::new(ApiKey {
+            id: id.to_string(),
+            name: id.to_string(),
+            owner: Some("alice".to_string()),
+            tenant: Some("acme".to_string()),
+            scopes: KeyScopes {
+                budget_override,
+                ..Default::default()
+            },
+            created_at: Utc::now(),
+            expires_at: None,
+            last_used_at: None,
+            revoked_at: None,
+            rotated_to: None,
+        }))
+    }
+
+    /// Echoes the requested model back with fixed usage
+    fn app(tracker: Arc<BudgetTracker>, key: AuthenticatedKey) -> Router {
+        let handler = |body: axum::body::Bytes| async move {
+            let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            axum::Json(serde_json::json!({
+                "model": request["model"],
+                "first_message": request["messages"][0]["content"],
+                "usage": {"input_tokens": 600, "output_tokens": 400}
+            }))
+        };
+        Router::new()
+            .route("/v1/messages", post(handler))
+            .layer(axum::middleware::from_fn_with_state(
+                tracker,
+                budget_middleware,
+            ))
+            .layer(axum::Extension(key))
+    }
+
+    fn request(model: &str, headers: &[(&str, &str)]) -> Request {
+        let mut builder = Request::builder().method("POST").uri("/v1/messages");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder
+            .body(Body::from(format!(r#"{{"model":"{}"}}"#, model)))
+            .unwrap()
+    }
+
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    #[test]
+    fn test_windows_and_validation() {
+        let today = NaiveDate::from_ymd_opt(2026, 12, 15).unwrap();
+        assert_eq!(BudgetWindow::Daily.days(today), vec![today]);
+        assert_eq!(BudgetWindow::Monthly.days(today).len(), 15);
+        assert_eq!(BudgetWindow::Rolling30d.days(today).len(), 30);
+
+        let now = today.and_hms_opt(13, 0, 0).unwrap().and_utc();
+        assert_eq!(
+            BudgetWindow::Monthly.resets_at(now).to_rfc3339(),
+            "2027-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            BudgetWindow::Daily.resets_at(now).to_rfc3339(),
+            "2026-12-16T00:00:00+00:00"
+        );
+
+        let valid =
+            budget("{name: b, subject: key, metric: dollars, window: rolling_30d, limit: 5}");
+        assert_eq!(valid.warn_at, vec![0.8]);
+        assert_eq!(valid.action, BudgetAction::Reject);
+        let config = |budgets| BudgetConfig {
+            budgets,
+            ..Default::default()
+        };
+        assert!(config(vec![valid.clone()]).validate().is_ok());
+        assert!(
+            config(vec![valid.clone(), valid.clone()])
+                .validate()
+                .is_err()
+        );
+        let reroute = budget(
+            "{name: r, subject: user, metric: tokens, window: daily, limit: 5, action: reroute}",
+        );
+        assert!(config(vec![reroute]).validate().is_err());
+    }
+
+    #[test]
+    fn test_usage_from_stream_events() {
+        let mut usage = Usage::default();
+        // Anthropic message_start, then a cumulative message_delta
+        usage.merge_from(&serde_json::json!({"input_tokens": 10, "cache_read_input_tokens": 90, "output_tokens": 1}));
+        usage.merge_from(&serde_json::json!({"output_tokens": 25}));
+        assert_eq!(usage.input_tokens, 100);
+        assert_eq!(usage.output_tokens, 25);
+
+        let mut usage = Usage::default();
+        usage.merge_from(&serde_json::json!({"prompt_tokens": 7, "completion_tokens": 3}));
+        assert_eq!(usage.total(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_reroute_and_override() {
+        let config = BudgetConfig {
+            enabled: true,
+            budgets: vec![budget(
+                r#"
+name: tenant-dollars
+subject: tenant
+metric: dollars
+window: monthly
+limit: 0.01
+warn_at: [0.5]
+action: reroute
+reroute_model: cheap-model
+"#,
+            )],
+            prices: vec![ModelPrice {
+                model: "big-*".to_string(),
+                input_per_million: 10.0,
+                output_per_million: 10.0,
+            }],
+            webhook_url: None,
+            session_cap: None,
+        };
+        config.validate().unwrap();
+        let (_dir, tracker) = tracker(config).await;
+
+        // 1000 tokens at $10/M = $0.01: used up after one request
+        let response = app(tracker.clone(), key("k1", false))
+            .oneshot(request("big-model", &[]))
+            .await
+            .unwrap();
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        settle().await;
+
+        let status = tracker.status().await.unwrap();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].subject_id, "acme");
+        assert!((status[0].used - 0.01).abs() < 1e-9);
+        assert_eq!(status[0].state, BudgetState::Exceeded);
+
+        let response = app(tracker.clone(), key("k2", false))
+            .oneshot(request("big-model", &[]))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[ACTION_HEADER], "reroute:tenant-dollars");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["model"], "cheap-model");
+
+        // The override header only counts for keys with the budget_override scope
+        let response = app(tracker.clone(), key("k3", true))
+            .oneshot(request("big-model", &[(OVERRIDE_HEADER, "true")]))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[ACTION_HEADER], "override:tenant-dollars");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["model"], "big-model");
+    }
+
+    #[tokio::test]
+    async fn test_large_response_usage_charged() {
+        let config = BudgetConfig {
+            enabled: true,
+            budgets: vec![budget(
+                "{name: key-tokens, subject: key, metric: tokens, window: daily, limit: 1000000}",
+            )],
+            ..Default::default()
+        };
+        let (_dir, tracker) = tracker(config).await;
+
+        // Usage follows more content than the scan buffer holds
+        let handler = || async {
+            axum::Json(serde_json::json!({
+                "model": "big-model",
+                "content": [{"type": "text", "text": "x".repeat(MAX_SCAN_BYTES + 1024)}],
+                "usage": {"input_tokens": 600, "output_tokens": 400}
+            }))
+        };
+        let app = Router::new()
+            .route("/v1/messages", post(handler))
+            .layer(axum::middleware::from_fn_with_state(
+                tracker.clone(),
+                budget_middleware,
+            ))
+            .layer(axum::Extension(key("k1", false)));
+        let response = app.oneshot(request("big-model", &[])).await.unwrap();
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        settle().await;
+
+        let status = tracker.status().await.unwrap();
+        assert_eq!(status[0].used, 1000.0);
+    }
+
+    #[tokio::test]
+    async fn test_project_scopes_bind_projects() {
+        let config = BudgetConfig {
+            enabled: true,
+            budgets: vec![budget(
+                "{name: project-requests, subject: project, metric: requests, window: daily, limit: 10}",
+            )],
+            ..Default::default()
+        };
+        let (_dir, tracker) = tracker(config).await;
+        let AuthenticatedKey(base) = key("k1", false);
+        let mut scoped = (*base).clone();
+        scoped.scopes.projects = vec!["web".to_string()];
+        let scoped = AuthenticatedKey(Arc::new(scoped));
+
+        let response = app(tracker.clone(), scoped.clone())
+            .oneshot(request("m", &[(PROJECT_HEADER, "billing")]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Without the header the key's first project is billed
+        let response = app(tracker.clone(), scoped)
+            .oneshot(request("m", &[]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let status = tracker.status().await.unwrap();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].subject_id, "web");
+    }
+
+    #[tokio::test]
+    async fn test_request_budget_warns_then_rejects() {
+        let config = BudgetConfig {
+            enabled: true,
+            budgets: vec![budget(
+                "{name: key-requests, subject: key, metric: requests, window: daily, limit: 2, warn_at: [0.5]}",
+            )],
+            ..Default::default()
+        };
+        let (_dir, tracker) = tracker(config).await;
+
+        let first = app(tracker.clone(), key("k1", false))
+            .oneshot(request("m", &[]))
+            .await
+            .unwrap();
+        assert!(first.headers().get(WARNING_HEADER).is_none());
+        let second = app(tracker.clone(), key("k1", false))
+            .oneshot(request("m", &[]))
+            .await
+            .unwrap();
+        assert_eq!(second.status(), StatusCode::OK);
+        assert!(
+            second.headers()[WARNING_HEADER]
+                .to_str()
+                .unwrap()
+                .starts_with("key-requests 50%")
+        );
+
+        let third = app(tracker.clone(), key("k1", false))
+            .oneshot(request("m", &[]))
+            .await
+            .unwrap();
+        assert_eq!(third.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(third.headers().contains_key(header::RETRY_AFTER));
+
+        // Another key has its own budget
+        let other = app(tracker, key("k2", false))
+            .oneshot(request("m", &[]))
+            .await
+            .unwrap();
+        assert_eq!(other.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_session_cap_notifies_then_rejects() {
+        let config: BudgetConfig = serde_yaml::from_str(
+            r#"
+enabled: true
+session_cap:
+  max_tokens: 1500
+  action: notify
+  grace_requests: 1
+"#,
+        )
+        .unwrap();
+        assert!(config.is_active());
+        config.validate().unwrap();
+        let (_dir, tracker) = tracker(config).await;
+
+        // Claude Code puts its session in metadata.user_id
+        let body = serde_json::json!({
+            "model": "m",
+            "metadata": {"user_id": r#"{"session_id":"run-1"}"#},
+            "messages": [{"role": "user", "content": "keep going"}],
+        })
+        .to_string();
+        let send = |body: String, session: Option<&str>| {
+            let mut builder = Request::builder().method("POST").uri("/v1/messages");
+            if let Some(session) = session {
+                builder = builder.header("x-session-id", session);
+            }
+            app(tracker.clone(), key("k1", false)).oneshot(builder.body(Body::from(body)).unwrap())
+        };
+        async fn json(response: Response) -> serde_json::Value {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice(&bytes).unwrap()
+        }
+
+        // Two requests at 1000 tokens each take the conversation past 1500
+        for _ in 0..2 {
+            let response = send(body.clone(), None).await.unwrap();
+            assert_eq!(json(response).await["first_message"], "keep going");
+            settle().await;
+        }
+
+        let response = send(body.clone(), None).await.unwrap();
+        assert_eq!(response.headers()[ACTION_HEADER], "notify:session_cap");
+        let notice = json(response).await["first_message"].clone();
+        assert!(notice.as_str().unwrap().starts_with(
+            "IMPORTANT: This conversation has reached its spending limit (2000 of 1500 tokens)"
+        ));
+
+        let response = send(body.clone(), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(json(response).await["error"]["type"], "permission_error");
+
+        // Other conversations are unaffected
+        let response = send(body, Some("run-2")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_inject_notice() {
+        let mut chat = serde_json::json!({"messages": [{"role": "user", "content": "hi"}]});
+        inject_notice(&mut chat, "stop");
+        assert_eq!(chat["messages"][0]["content"], "stop");
+        assert_eq!(chat["messages"][1]["content"], "hi");
+
+        let mut responses = serde_json::json!({"input": "hi"});
+        inject_notice(&mut responses, "stop");
+        assert_eq!(responses["input"][0]["content"], "stop");
+        assert_eq!(responses["input"][1]["content"], "hi");
+    }
+}
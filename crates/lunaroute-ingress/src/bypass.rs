@@ -146,6 +146,13 @@ async fn bypass_handler_impl(
         }
     };
 
+    // API key provider scope (when ingress auth is enabled)
+    if let Some(key) = req.extensions().get::<crate::auth::AuthenticatedKey>()
+        && let Err(e) = key.authorize_provider(&provider.name)
+    {
+        return Ok(e.into_response());
+    }
+
     debug!(
         "Bypassing path {} to provider {} (bypass enabled)",
         path, provider.name
@@ -0,0 +1,325 @@
+//! A/B experiment assignment for passthrough handlers
+//!
+//! Assigns requests to an arm of the routing crate's [`ExperimentSet`] and
+//! applies the arm to the raw JSON body: model swap, system prompt patch and
+//! parameter overrides. An arm that names a provider also selects the
+//! connector from the [`ProviderRegistry`].
+
+use crate::provider_registry::{ProviderEntry, ProviderRegistry, ProviderType};
+use axum::http::HeaderMap;
+use lunaroute_routing::{ExperimentSet, PatchMode, StickyKey, SystemPromptPatch};
+
+/// Request attributes experiments can be made sticky on
+#[derive(Debug, Clone, Copy)]
+pub struct StickyKeys<'a> {
+    /// Client session ID (Anthropic `metadata.user_id` session, OpenAI session header)
+    pub session: Option<&'a str>,
+    /// End-user identifier (Anthropic `metadata.user_id`, OpenAI `user`)
+    pub user: Option<&'a str>,
+    /// Incoming request headers
+    pub headers: &'a HeaderMap,
+}
+
+/// Outcome of experiment assignment
+#[derive(Debug, Default)]
+pub struct ExperimentSelection {
+    /// Experiment the request was enrolled in
+    pub experiment: Option<String>,
+    /// Assigned arm ID
+    pub arm: Option<String>,
+    /// Registry provider chosen by the arm (None = normal routing)
+    pub provider: Option<String>,
+    /// Registry entry for the chosen provider
+    pub entry: Option<ProviderEntry>,
+}
+
+/// Assign the request to an experiment arm and apply the arm to `req`.
+///
+/// Arms that target a provider missing from the registry, or one of another
+/// dialect when `allow_cross_dialect` is unset, cannot be served by this
+/// handler; such requests are left unenrolled rather than recorded under an
+/// arm they did not receive.
+pub fn apply_experiments(
+    experiments: &ExperimentSet,
+    registry: Option<&ProviderRegistry>,
+    req: &mut serde_json::Value,
+    dialect: ProviderType,
+    allow_cross_dialect: bool,
+    keys: StickyKeys<'_>,
+) -> ExperimentSelection {
+    let Some(model) = req.get("model").and_then(|m| m.as_str()) else {
+        return ExperimentSelection::default();
+    };
+
+    let Some(assignment) = experiments.assign(model, |experiment| match experiment.sticky_key {
+        StickyKey::Session => keys.session.map(str::to_string),
+        StickyKey::User => keys.user.map(str::to_string),
+        StickyKey::Header => experiment
+            .header
+            .as_deref()
+            .and_then(|name| keys.headers.get(name))
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+    }) else {
+        return ExperimentSelection::default();
+    };
+
+    let experiment = &assignment.experiment.name;
+    let arm = assignment.arm;
+
+    let entry = match &arm.provider {
+        Some(provider) => {
+            let Some(entry) = registry.and_then(|r| r.get(provider)) else {
+                tracing::warn!(
+                    "Experiment '{}' arm '{}': provider '{}' not registered, request not enrolled",
+                    experiment,
+                    arm.id,
+                    provider
+                );
+                return ExperimentSelection::default();
+            };
+            if entry.connector_type != dialect && !allow_cross_dialect {
+                tracing::debug!(
+                    "Experiment '{}' arm '{}': provider '{}' speaks {:?}, not enrolling {:?} request",
+                    experiment,
+                    arm.id,
+                    provider,
+                    entry.connector_type,
+                    dialect
+                );
+                return ExperimentSelection::default();
+            }
+            Some(entry.clone())
+        }
+        None => None,
+    };
+
+    tracing::info!("Experiment '{}': assigned arm '{}'", experiment, arm.id);
+
+    if let Some(model) = &arm.model {
+        req["model"] = serde_json::Value::String(model.clone());
+    }
+    if let Some(patch) = &arm.system_prompt {
+        match dialect {
+            ProviderType::Anthropic => patch_anthropic_system(req, patch),
+            ProviderType::OpenAI => patch_openai_system(req, patch),
+        }
+    }
+    arm.params.apply_to_json(req);
+
+    ExperimentSelection {
+        experiment: Some(experiment.clone()),
+        arm: Some(arm.id.clone()),
+        provider: arm.provider.clone(),
+        entry,
+    }
+}
+
+/// Patch the top-level `system` field of an Anthropic request (string or block array)
+fn patch_anthropic_system(req: &mut serde_json::Value, patch: &SystemPromptPatch) {
+    match req.get_mut("system") {
+        Some(serde_json::Value::Array(blocks)) if patch.mode != PatchMode::Replace => {
+            patch_text_blocks(blocks, patch)
+        }
+        existing => {
+            let current = existing.as_ref().and_then(|v| v.as_str());
+            req["system"] = serde_json::Value::String(patch.apply(current));
+        }
+    }
+}
+
+/// Patch the first system/developer message of an OpenAI request, inserting
+/// one if the conversation has none
+fn patch_openai_system(req: &mut serde_json::Value, patch: &SystemPromptPatch) {
+    let Some(messages) = req.get_mut("messages").and_then(|m| m.as_array_mut()) else {
+        return;
+    };
+
+    let system = messages.iter_mut().find(|m| {
+        matches!(
+            m.get("role").and_then(|r| r.as_str()),
+            Some("system") | Some("developer")
+        )
+    });
+
+    match system {
+        Some(message) => match message.get_mut("content") {
+            Some(serde_json::Value::Array(parts)) if patch.mode != PatchMode::Replace => {
+                patch_text_blocks(parts, patch)
+            }
+            content => {
+                let current = content.as_ref().and_then(|v| v.as_str());
+                message["content"] = serde_json::Value::String(patch.apply(current));
+            }
+        },
+        None => messages.insert(
+            0,
+            serde_json::json!({"role": "system", "content": patch.apply(None)}),
+        ),
+    }
+}
+
+/// Prepend or append a text block to a content block array
+fn patch_text_blocks(blocks: &mut Vec<serde_json::Value>, patch: &SystemPromptPatch) {
+    let block = serde_json::json!({"type": "text", "text": patch.text});
+    match patch.mode {
+        PatchMode::Prepend => blocks.insert(0, block),
+        _ => blocks.push(block),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lunaroute_routing::{Experiment, ExperimentArm, ParameterOverrides};
+    use serde_json::json;
+
+    fn set(arm: ExperimentArm) -> ExperimentSet {
+        ExperimentSet::new(vec![Experiment {
+            name: "exp".to_string(),
+            description: None,
+            enabled: true,
+            models: vec![],
+            sticky_key: StickyKey::Header,
+            header: Some("x-user-id".to_string()),
+            arms: vec![arm],
+        }])
+        .unwrap()
+    }
+
+    fn keys(headers: &HeaderMap) -> StickyKeys<'_> {
+        StickyKeys {
+            session: None,
+            user: None,
+            headers,
+        }
+    }
+
+    fn registry() -> ProviderRegistry {
+        let mut registry = ProviderRegistry::new();
+        registry.insert(
+            "openai".to_string(),
+            ProviderEntry {
+                connector_type: ProviderType::OpenAI,
+                openai_connector: None,
+                anthropic_connector: None,
+                model_override: None,
+            },
+        );
+        registry
+    }
+
+    #[test]
+    fn test_arm_patches_anthropic_request() {
+        let arm = ExperimentArm {
+            model: Some("claude-haiku-4-5".to_string()),
+            system_prompt: Some(SystemPromptPatch {
+                mode: PatchMode::Append,
+                text: "Be brief.".to_string(),
+            }),
+            params: ParameterOverrides {
+                temperature: Some(0.5),
+                ..Default::default()
+            },
+            ..ExperimentArm::control("terse", 100)
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("x-user-id", "u1".parse().unwrap());
+        let mut req = json!({
+            "model": "claude-sonnet-4-5",
+            "system": [{"type": "text", "text": "You help."}],
+        });
+
+        let selection = apply_experiments(
+            &set(arm),
+            None,
+            &mut req,
+            ProviderType::Anthropic,
+            true,
+            keys(&headers),
+        );
+
+        assert_eq!(selection.experiment.as_deref(), Some("exp"));
+        assert_eq!(selection.arm.as_deref(), Some("terse"));
+        assert!(selection.provider.is_none());
+        assert_eq!(req["model"], "claude-haiku-4-5");
+        assert_eq!(req["system"][1]["text"], "Be brief.");
+        assert_eq!(req["temperature"], 0.5);
+    }
+
+    #[test]
+    fn test_arm_inserts_openai_system_message() {
+        let arm = ExperimentArm {
+            system_prompt: Some(SystemPromptPatch {
+                mode: PatchMode::Prepend,
+                text: "Be brief.".to_string(),
+            }),
+            ..ExperimentArm::control("terse", 100)
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("x-user-id", "u1".parse().unwrap());
+        let mut req = json!({"model": "gpt-5", "messages": [{"role": "user", "content": "hi"}]});
+
+        apply_experiments(
+            &set(arm),
+            None,
+            &mut req,
+            ProviderType::OpenAI,
+            false,
+            keys(&headers),
+        );
+
+        assert_eq!(req["messages"][0]["role"], "system");
+        assert_eq!(req["messages"][0]["content"], "Be brief.");
+        assert_eq!(req["messages"][1]["content"], "hi");
+    }
+
+    #[test]
+    fn test_not_enrolled_without_key_or_servable_provider() {
+        let arm = ExperimentArm {
+            provider: Some("openai".to_string()),
+            model: Some("gpt-5".to_string()),
+            ..ExperimentArm::control("gpt5", 100)
+        };
+        let set = set(arm);
+        let registry = registry();
+
+        // No sticky header
+        let headers = HeaderMap::new();
+        let mut req = json!({"model": "claude-sonnet-4-5"});
+        let selection = apply_experiments(
+            &set,
+            Some(&registry),
+            &mut req,
+            ProviderType::Anthropic,
+            true,
+            keys(&headers),
+        );
+        assert!(selection.arm.is_none());
+
+        // Cross-dialect arm on a handler that cannot serve it
+        let mut headers = HeaderMap::new();
+        headers.insert("x-user-id", "u1".parse().unwrap());
+        let selection = apply_experiments(
+            &set,
+            Some(&registry),
+            &mut req,
+            ProviderType::Anthropic,
+            false,
+            keys(&headers),
+        );
+        assert!(selection.arm.is_none());
+        assert_eq!(req["model"], "claude-sonnet-4-5");
+
+        let selection = apply_experiments(
+            &set,
+            Some(&registry),
+            &mut req,
+            ProviderType::Anthropic,
+            true,
+            keys(&headers),
+        );
+        assert_eq!(selection.provider.as_deref(), Some("openai"));
+        assert_eq!(req["model"], "gpt-5");
+    }
+}
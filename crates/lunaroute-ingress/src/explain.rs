@@ -0,0 +1,280 @@
+//! Routing explanations for passthrough handlers
+//!
+//! Passthrough handlers never consult the route table: a request goes to the
+//! listener's own provider unless a LUNAROUTE marker, experiment arm or model
+//! alias picks another one from the [`ProviderRegistry`]. [`explain_passthrough`]
+//! replays those steps on a copy of the request body and reports the outcome
+//! as a [`RoutingExplanation`] without contacting any provider.
+
+use crate::marker::{MarkerResult, extract_marker};
+use crate::provider_registry::{ProviderRegistry, ProviderType};
+use crate::types::{IngressError, IngressResult};
+use axum::http::HeaderMap;
+use lunaroute_routing::{
+    CandidateRole, ExperimentSet, HealthStatus, ModelAliasTable, ProviderCandidate,
+    RoutingExplanation,
+};
+
+/// Explain how a passthrough handler of `dialect` would route `req`.
+///
+/// Mirrors the handler order: marker, then experiment assignment, then model
+/// aliases. Errors the handler would return (e.g. an OpenAI request whose
+/// marker targets an Anthropic provider) are returned unchanged.
+pub fn explain_passthrough(
+    mut req: serde_json::Value,
+    dialect: ProviderType,
+    registry: Option<&ProviderRegistry>,
+    model_aliases: Option<&ModelAliasTable>,
+    experiments: Option<&ExperimentSet>,
+    headers: &HeaderMap,
+) -> IngressResult<RoutingExplanation> {
+    let requested = req
+        .get("model")
+        .and_then(|m| m.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let mut explanation = RoutingExplanation::new(&requested);
+    let allow_cross_dialect = dialect == ProviderType::Anthropic;
+
+    let (default_provider, dialect_name) = match dialect {
+        ProviderType::Anthropic => ("anthropic", "Anthropic"),
+        ProviderType::OpenAI => ("openai", "OpenAI"),
+    };
+    explanation.push_step(
+        "passthrough",
+        format!(
+            "{} listener forwards to '{}' unless a marker, experiment or alias selects a provider",
+            dialect_name, default_provider
+        ),
+    );
+
+    let mut selected: Option<(String, CandidateRole)> = None;
+
+    if let MarkerResult::Provider(selection) = extract_marker(&req) {
+        let name = selection.provider.clone();
+        match registry.and_then(|r| r.get(&name)) {
+            Some(entry) => {
+                if entry.connector_type != dialect && !allow_cross_dialect {
+                    return Err(IngressError::InvalidRequest(format!(
+                        "LUNAROUTE marker targets provider '{}' ({:?}) but request uses OpenAI format. Cross-dialect routing requires normalized mode.",
+                        name, entry.connector_type
+                    )));
+                }
+                if let Some(model) = selection.model.as_ref().or(entry.model_override.as_ref()) {
+                    req["model"] = serde_json::Value::String(model.clone());
+                }
+                selection.params.apply_to_json(&mut req);
+                explanation.push_step(
+                    "marker",
+                    format!("[LUNAROUTE:{}] selects provider", selection),
+                );
+                selected = Some((name, CandidateRole::Override));
+            }
+            None if model_aliases.is_some_and(|t| t.get_alias(&name).is_some()) => {
+                explanation.push_step(
+                    "marker",
+                    format!("[LUNAROUTE:{}] selects model alias", name),
+                );
+                req["model"] = serde_json::Value::String(name);
+            }
+            None => explanation.push_step(
+                "marker",
+                format!("[LUNAROUTE:{}] names an unknown provider, ignored", name),
+            ),
+        }
+    }
+
+    if let Some(set) = experiments
+        && selected.is_none()
+    {
+        let (session, user) = match dialect {
+            ProviderType::Anthropic => {
+                let user_id = req.get("metadata").and_then(|m| m.get("user_id"));
+                (
+                    user_id.and_then(crate::anthropic::extract_session_id_from_user_id),
+                    user_id.and_then(|v| v.as_str()).map(str::to_string),
+                )
+            }
+            ProviderType::OpenAI => (
+                ["session_id", "session-id", "x-session-id"]
+                    .iter()
+                    .find_map(|name| headers.get(*name))
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string),
+                req.get("user")
+                    .or_else(|| req.get("safety_identifier"))
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+            ),
+        };
+
+        let assignment = crate::experiment::apply_experiments(
+            set,
+            registry,
+            &mut req,
+            dialect,
+            allow_cross_dialect,
+            crate::experiment::StickyKeys {
+                session: session.as_deref(),
+                user: user.as_deref(),
+                headers,
+            },
+        );
+        if let (Some(experiment), Some(arm)) = (&assignment.experiment, &assignment.arm) {
+            explanation.push_step(
+                "experiment",
+                format!("'{}' assigned arm '{}'", experiment, arm),
+            );
+        }
+        if let Some(provider) = assignment.provider {
+            selected = Some((provider, CandidateRole::Override));
+        }
+    }
+
+    if let Some(table) = model_aliases
+        && selected.is_none()
+    {
+        let before = req
+            .get("model")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let selection = crate::model_alias::apply_model_aliases(
+            table,
+            registry,
+            &mut req,
+            dialect,
+            allow_cross_dialect,
+        )?;
+        let after = req
+            .get("model")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default();
+        match &selection.provider {
+            Some(provider) => {
+                explanation.push_step(
+                    "alias",
+                    format!("'{}' resolves to {} on '{}'", before, after, provider),
+                );
+                selected = Some((provider.clone(), CandidateRole::AliasTarget));
+            }
+            None if before != after => {
+                explanation.push_step("rewrite", format!("{} → {}", before, after))
+            }
+            None => {}
+        }
+    }
+
+    explanation.resolved_model = req
+        .get("model")
+        .and_then(|m| m.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let (provider, role) =
+        selected.unwrap_or_else(|| (default_provider.to_string(), CandidateRole::Primary));
+    explanation.candidates.push(ProviderCandidate {
+        provider: provider.clone(),
+        role,
+        model: explanation.resolved_model.clone(),
+        circuit: None,
+        health: HealthStatus::Unknown,
+        skipped: None,
+    });
+    explanation.selected_provider = Some(provider);
+
+    Ok(explanation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider_registry::ProviderEntry;
+    use lunaroute_routing::{AliasTarget, ModelAlias, ParameterOverrides};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn registry() -> ProviderRegistry {
+        let mut registry = ProviderRegistry::new();
+        registry.insert(
+            "sonnet".to_string(),
+            ProviderEntry {
+                connector_type: ProviderType::Anthropic,
+                openai_connector: None,
+                anthropic_connector: None,
+                model_override: Some("claude-sonnet-4-5".to_string()),
+            },
+        );
+        registry
+    }
+
+    fn aliases() -> ModelAliasTable {
+        let mut aliases = HashMap::new();
+        aliases.insert(
+            "fast".to_string(),
+            ModelAlias {
+                description: None,
+                targets: vec![AliasTarget {
+                    provider: "sonnet".to_string(),
+                    model: "claude-haiku-4-5".to_string(),
+                    params: ParameterOverrides::default(),
+                }],
+            },
+        );
+        let mut rewrites = HashMap::new();
+        rewrites.insert("claude-3-haiku".to_string(), "fast".to_string());
+        ModelAliasTable::new(aliases, rewrites).unwrap()
+    }
+
+    #[test]
+    fn test_marker_overrides_default_provider() {
+        let req = json!({
+            "model": "claude-opus-4-1",
+            "messages": [{"role": "user", "content": [{"type": "text", "text": "[LUNAROUTE:sonnet] hi"}]}],
+        });
+        let explanation = explain_passthrough(
+            req,
+            ProviderType::Anthropic,
+            Some(&registry()),
+            None,
+            None,
+            &HeaderMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(explanation.steps[1].stage, "marker");
+        assert_eq!(explanation.resolved_model, "claude-sonnet-4-5");
+        assert_eq!(explanation.candidates[0].role, CandidateRole::Override);
+        assert_eq!(explanation.selected_provider.as_deref(), Some("sonnet"));
+    }
+
+    #[test]
+    fn test_alias_and_default_provider() {
+        let table = aliases();
+        let explanation = explain_passthrough(
+            json!({"model": "claude-3-haiku", "messages": []}),
+            ProviderType::Anthropic,
+            Some(&registry()),
+            Some(&table),
+            None,
+            &HeaderMap::new(),
+        )
+        .unwrap();
+        assert_eq!(explanation.requested_model, "claude-3-haiku");
+        assert_eq!(explanation.resolved_model, "claude-haiku-4-5");
+        assert_eq!(explanation.candidates[0].role, CandidateRole::AliasTarget);
+        assert_eq!(explanation.selected_provider.as_deref(), Some("sonnet"));
+
+        let explanation = explain_passthrough(
+            json!({"model": "claude-opus-4-1", "messages": []}),
+            ProviderType::Anthropic,
+            Some(&registry()),
+            Some(&table),
+            None,
+            &HeaderMap::new(),
+        )
+        .unwrap();
+        assert_eq!(explanation.candidates[0].role, CandidateRole::Primary);
+        assert_eq!(explanation.selected_provider.as_deref(), Some("anthropic"));
+    }
+}
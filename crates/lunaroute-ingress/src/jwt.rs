@@ -0,0 +1,654 @@
+//! Bearer JWT authentication
+//!
+//! An alternative to LunaRoute-issued keys for deployments behind an
+//! OIDC identity provider: clients send `Authorization: Bearer <jwt>` and
+//! the token is validated against the provider's JSON Web Key Set.
+//!
+//! ```yaml
+//! auth:
+//!   enabled: true
+//!   jwt:
+//!     issuer: "https://login.example.com/"
+//!     audience: ["lunaroute"]
+//!     jwks_url: "https://login.example.com/.well-known/jwks.json"
+//!     claims:
+//!       groups: "realm_access.roles"
+//! ```
+//!
+//! Only asymmetric algorithms are accepted (RS256, ES256 and EdDSA by
+//! default). The key set comes from `jwks_file` or `jwks_url`; fetched sets
+//! are cached for `jwks_cache_secs` and refetched early when a token names a
+//! key ID the cache does not know, so provider key rotation needs no restart.
+//! A failed refetch keeps serving the previous set.
+//!
+//! Validated claims become a [`ClientIdentity`] (subject, email, groups,
+//! tenant) that routing rules, budgets and session recording can use. Claim
+//! names are configurable and may be dotted paths into nested objects.
+
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use lunaroute_core::identity::ClientIdentity;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Shortest gap between key set fetches triggered by unknown key IDs
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Timeout for fetching the key set
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+pub enum JwtError {
+    #[error("invalid JWT configuration: {0}")]
+    Config(String),
+
+    #[error("failed to load JWKS: {0}")]
+    Jwks(String),
+
+    #[error("malformed token: {0}")]
+    Malformed(String),
+
+    #[error("algorithm {0:?} is not accepted")]
+    Algorithm(Algorithm),
+
+    #[error("no key matches key ID {0:?}")]
+    UnknownKey(Option<String>),
+
+    #[error("token rejected: {0}")]
+    Invalid(String),
+
+    #[error("token has no '{0}' claim")]
+    MissingClaim(String),
+}
+
+/// Which claims carry the identity; dotted names address nested objects
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimNames {
+    #[serde(default = "default_subject_claim")]
+    pub subject: String,
+    #[serde(default = "default_email_claim")]
+    pub email: String,
+    /// An array of strings or a space/comma-separated string
+    #[serde(default = "default_groups_claim")]
+    pub groups: String,
+    #[serde(default = "default_tenant_claim")]
+    pub tenant: String,
+}
+
+fn default_subject_claim() -> String {
+    "sub".to_string()
+}
+
+fn default_email_claim() -> String {
+    "email".to_string()
+}
+
+fn default_groups_claim() -> String {
+    "groups".to_string()
+}
+
+fn default_tenant_claim() -> String {
+    "tenant".to_string()
+}
+
+impl Default for ClaimNames {
+    fn default() -> Self {
+        Self {
+            subject: default_subject_claim(),
+            email: default_email_claim(),
+            groups: default_groups_claim(),
+            tenant: default_tenant_claim(),
+        }
+    }
+}
+
+/// JWT validation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtConfig {
+    /// Required `iss` claim
+    pub issuer: String,
+
+    /// Accepted `aud` values; the claim is required when any are listed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audience: Vec<String>,
+
+    /// Local JWKS file (read once at startup)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwks_file: Option<PathBuf>,
+
+    /// JWKS endpoint of the identity provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwks_url: Option<String>,
+
+    /// How long a fetched key set is used before refetching (default: 300)
+    #[serde(default = "default_jwks_cache_secs")]
+    pub jwks_cache_secs: u64,
+
+    /// Clock skew tolerated for `exp` and `nbf` (default: 60)
+    #[serde(default = "default_leeway_secs")]
+    pub leeway_secs: u64,
+
+    /// Accepted signing algorithms (default: RS256, ES256, EdDSA)
+    #[serde(default = "default_algorithms")]
+    pub algorithms: Vec<Algorithm>,
+
+    #[serde(default)]
+    pub claims: ClaimNames,
+}
+
+fn default_jwks_cache_secs() -> u64 {
+    300
+}
+
+fn default_leeway_secs() -> u64 {
+    60
+}
+
+fn default_algorithms() -> Vec<Algorithm> {
+    vec![Algorithm::RS256, Algorithm::ES256, Algorithm::EdDSA]
+}
+
+impl JwtConfig {
+    /// Settings for an issuer with its key set at `jwks_url`
+    pub fn new(issuer: impl Into<String>, jwks_url: impl Into<String>) -> Self {
+        Self {
+            issuer: issuer.into(),
+            audience: Vec::new(),
+            jwks_file: None,
+            jwks_url: Some(jwks_url.into()),
+            jwks_cache_secs: default_jwks_cache_secs(),
+            leeway_secs: default_leeway_secs(),
+            algorithms: default_algorithms(),
+            claims: ClaimNames::default(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), JwtError> {
+        if self.issuer.is_empty() {
+            return Err(JwtError::Config("issuer is required".to_string()));
+        }
+        match (&self.jwks_file, &self.jwks_url) {
+            (None, None) => {
+                return Err(JwtError::Config(
+                    "one of jwks_file or jwks_url is required".to_string(),
+                ));
+            }
+            (Some(_), Some(_)) => {
+                return Err(JwtError::Config(
+                    "jwks_file and jwks_url are mutually exclusive".to_string(),
+                ));
+            }
+            _ => {}
+        }
+        if self.algorithms.is_empty() {
+            return Err(JwtError::Config("no algorithms accepted".to_string()));
+        }
+        // Shared-secret algorithms would let anyone holding a published
+        // `oct` key mint tokens
+        if let Some(alg) = self
+            .algorithms
+            .iter()
+            .find(|alg| matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512))
+        {
+            return Err(JwtError::Config(format!(
+                "symmetric algorithm {:?} is not supported",
+                alg
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Cached key set
+struct KeyCache {
+    keys: JwkSet,
+    fetched_at: Option<Instant>,
+}
+
+/// Validates bearer JWTs and maps their claims to a [`ClientIdentity`]
+pub struct JwtVerifier {
+    config: JwtConfig,
+    client: reqwest::Client,
+    cache: Mutex<KeyCache>,
+    /// Held while fetching the key set, so one task fetches at a time
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+impl JwtVerifier {
+    /// Create a verifier, reading `jwks_file` now if configured. URL key
+    /// sets are fetched on first use.
+    pub fn new(config: JwtConfig, client: reqwest::Client) -> Result<Self, JwtError> {
+        config.validate()?;
+        let keys = match &config.jwks_file {
+            Some(path) => {
+                let data = std::fs::read(path)
+                    .map_err(|e| JwtError::Jwks(format!("{}: {}", path.display(), e)))?;
+                serde_json::from_slice(&data)
+                    .map_err(|e| JwtError::Jwks(format!("{}: {}", path.display(), e)))?
+            }
+            None => JwkSet { keys: Vec::new() },
+        };
+        Ok(Self {
+            config,
+            client,
+            cache: Mutex::new(KeyCache {
+                keys,
+                fetched_at: None,
+            }),
+            refreshing: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    pub fn config(&self) -> &JwtConfig {
+        &self.config
+    }
+
+    /// Validate a token and return the caller's identity
+    pub async fn verify(&self, token: &str) -> Result<ClientIdentity, JwtError> {
+        let header = decode_header(token).map_err(|e| JwtError::Malformed(e.to_string()))?;
+        if !self.config.algorithms.contains(&header.alg) {
+            return Err(JwtError::Algorithm(header.alg));
+        }
+        let jwk = self.find_key(header.kid.as_deref()).await?;
+        let key = DecodingKey::from_jwk(&jwk).map_err(|e| JwtError::Jwks(e.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = self.config.leeway_secs;
+        validation.validate_nbf = true;
+        validation.set_issuer(&[&self.config.issuer]);
+        let mut required = vec!["exp", "iss"];
+        if self.config.audience.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&self.config.audience);
+            required.push("aud");
+        }
+        validation.set_required_spec_claims(&required);
+
+        let claims = decode::<serde_json::Value>(token, &key, &validation)
+            .map_err(|e| JwtError::Invalid(e.to_string()))?
+            .claims;
+        self.identity(&claims)
+    }
+
+    /// Map validated claims to an identity
+    fn identity(&self, claims: &serde_json::Value) -> Result<ClientIdentity, JwtError> {
+        let names = &self.config.claims;
+        let subject = claim(claims, &names.subject)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| JwtError::MissingClaim(names.subject.clone()))?;
+
+        let mut identity = ClientIdentity::jwt(subject);
+        identity.email = claim(claims, &names.email)
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        identity.tenant = claim(claims, &names.tenant)
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        identity.groups = match claim(claims, &names.groups) {
+            Some(serde_json::Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect(),
+            Some(serde_json::Value::String(s)) => s
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|g| !g.is_empty())
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(identity)
+    }
+
+    /// Key for a token, refreshing the cached set when it is stale or does
+    /// not know the key ID
+    ///
+    /// The cache is not locked during the fetch: while one task refreshes,
+    /// others use a cached key they find and only wait for keys they lack.
+    async fn find_key(&self, kid: Option<&str>) -> Result<Jwk, JwtError> {
+        if self.config.jwks_url.is_some() && self.needs_refresh(kid) {
+            let known = select_key(&self.cache.lock().unwrap().keys, kid).cloned();
+            let _refreshing = match (self.refreshing.try_lock(), known) {
+                (Ok(guard), _) => guard,
+                (Err(_), Some(key)) => return Ok(key),
+                (Err(_), None) => self.refreshing.lock().await,
+            };
+            // Another task may have refreshed while this one waited
+            if self.needs_refresh(kid) {
+                let fetched = self.fetch().await;
+                let mut cache = self.cache.lock().unwrap();
+                match fetched {
+                    Ok(keys) => {
+                        cache.keys = keys;
+                        cache.fetched_at = Some(Instant::now());
+                    }
+                    Err(e) if cache.fetched_at.is_some() => {
+                        // Keep the previous set; retry after the minimum interval
+                        tracing::warn!("JWKS refresh failed, using cached keys: {}", e);
+                        let ttl = Duration::from_secs(self.config.jwks_cache_secs);
+                        cache.fetched_at = Some(
+                            Instant::now()
+                                .checked_sub(ttl.saturating_sub(MIN_REFRESH_INTERVAL))
+                                .unwrap_or_else(Instant::now),
+                        );
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        select_key(&self.cache.lock().unwrap().keys, kid)
+            .cloned()
+            .ok_or_else(|| JwtError::UnknownKey(kid.map(str::to_string)))
+    }
+
+    /// Whether the cached set is stale or lacks the key ID (and may be
+    /// refetched for it yet)
+    fn needs_refresh(&self, kid: Option<&str>) -> bool {
+        let cache = self.cache.lock().unwrap();
+        let ttl = Duration::from_secs(self.config.jwks_cache_secs);
+        let stale = cache.fetched_at.is_none_or(|at| at.elapsed() >= ttl);
+        let missing = select_key(&cache.keys, kid).is_none();
+        let may_refetch = cache
+            .fetched_at
+            .is_none_or(|at| at.elapsed() >= MIN_REFRESH_INTERVAL);
+        stale || (missing && may_refetch)
+    }
+
+    async fn fetch(&self) -> Result<JwkSet, JwtError> {
+        let url = self.config.jwks_url.as_deref().unwrap_or_default();
+        let response = self
+            .client
+            .get(url)
+            .timeout(JWKS_FETCH_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| JwtError::Jwks(format!("{}: {}", url, e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(JwtError::Jwks(format!("{}: HTTP {}", url, status)));
+        }
+        response
+            .json::<JwkSet>()
+            .await
+            .map_err(|e| JwtError::Jwks(format!("{}: {}", url, e)))
+    }
+}
+
+/// Key for `kid`, or the only key of a single-key set when the token has no
+/// key ID
+fn select_key<'a>(keys: &'a JwkSet, kid: Option<&str>) -> Option<&'a Jwk> {
+    match kid {
+        Some(kid) => keys.find(kid),
+        None if keys.keys.len() == 1 => keys.keys.first(),
+        None => None,
+    }
+}
+
+/// Look up a dotted claim path
+fn claim<'a>(claims: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    if let Some(value) = claims.get(path) {
+        return Some(value);
+    }
+    path.split('.')
+        .try_fold(claims, |value, segment| value.get(segment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use rcgen::{KeyPair, PKCS_ECDSA_P256_SHA256, PKCS_ED25519};
+    use serde_json::json;
+    use std::sync::Arc;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const ISSUER: &str = "https://login.example.com/";
+
+    struct Signer {
+        kid: String,
+        alg: Algorithm,
+        key: EncodingKey,
+        jwk: serde_json::Value,
+    }
+
+    impl Signer {
+        fn es256(kid: &str) -> Self {
+            let pair = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+            // Uncompressed point: 0x04 || x || y
+            let point = pair.public_key_raw();
+            Self {
+                kid: kid.to_string(),
+                alg: Algorithm::ES256,
+                key: EncodingKey::from_ec_pem(pair.serialize_pem().as_bytes()).unwrap(),
+                jwk: json!({
+                    "kty": "EC",
+                    "crv": "P-256",
+                    "kid": kid,
+                    "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+                    "y": URL_SAFE_NO_PAD.encode(&point[33..]),
+                }),
+            }
+        }
+
+        fn ed25519(kid: &str) -> Self {
+            let pair = KeyPair::generate_for(&PKCS_ED25519).unwrap();
+            Self {
+                kid: kid.to_string(),
+                alg: Algorithm::EdDSA,
+                key: EncodingKey::from_ed_pem(pair.serialize_pem().as_bytes()).unwrap(),
+                jwk: json!({
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "kid": kid,
+                    "x": URL_SAFE_NO_PAD.encode(pair.public_key_raw()),
+                }),
+            }
+        }
+
+        fn sign(&self, claims: serde_json::Value) -> String {
+            let mut header = Header::new(self.alg);
+            header.kid = Some(self.kid.clone());
+            encode(&header, &claims, &self.key).unwrap()
+        }
+    }
+
+    fn jwks(signers: &[&Signer]) -> serde_json::Value {
+        json!({ "keys": signers.iter().map(|s| s.jwk.clone()).collect::<Vec<_>>() })
+    }
+
+    fn claims(extra: serde_json::Value) -> serde_json::Value {
+        let mut claims = json!({
+            "iss": ISSUER,
+            "aud": "lunaroute",
+            "sub": "user-1",
+            "exp": chrono::Utc::now().timestamp() + 600,
+        });
+        for (k, v) in extra.as_object().unwrap() {
+            claims[k] = v.clone();
+        }
+        claims
+    }
+
+    fn file_verifier(signers: &[&Signer]) -> (tempfile::TempDir, JwtVerifier) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jwks.json");
+        std::fs::write(&path, jwks(signers).to_string()).unwrap();
+        let mut config = JwtConfig::new(ISSUER, "unused");
+        config.jwks_url = None;
+        config.jwks_file = Some(path);
+        config.audience = vec!["lunaroute".to_string()];
+        let verifier = JwtVerifier::new(config, reqwest::Client::new()).unwrap();
+        (dir, verifier)
+    }
+
+    #[tokio::test]
+    async fn test_verify_maps_claims_to_identity() {
+        let es = Signer::es256("es");
+        let ed = Signer::ed25519("ed");
+        let (_dir, verifier) = file_verifier(&[&es, &ed]);
+
+        let token = es.sign(claims(json!({
+            "email": "alice@example.com",
+            "groups": ["eng", "ml"],
+            "tenant": "acme",
+        })));
+        let identity = verifier.verify(&token).await.unwrap();
+        assert_eq!(identity.subject, "user-1");
+        assert_eq!(
+            identity.source,
+            lunaroute_core::identity::IdentitySource::Jwt
+        );
+        assert_eq!(identity.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(identity.groups, vec!["eng", "ml"]);
+        assert_eq!(identity.tenant.as_deref(), Some("acme"));
+
+        let token = ed.sign(claims(json!({ "groups": "eng ml" })));
+        let identity = verifier.verify(&token).await.unwrap();
+        assert_eq!(identity.groups, vec!["eng", "ml"]);
+        assert_eq!(identity.email, None);
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_invalid_tokens() {
+        let es = Signer::es256("es");
+        let (_dir, verifier) = file_verifier(&[&es]);
+        let past = chrono::Utc::now().timestamp() - 3600;
+
+        let cases = [
+            ("issuer", es.sign(claims(json!({ "iss": "https://evil/" })))),
+            ("audience", es.sign(claims(json!({ "aud": "other" })))),
+            ("expired", es.sign(claims(json!({ "exp": past })))),
+            (
+                "unknown key",
+                Signer::es256("other").sign(claims(json!({}))),
+            ),
+            // Same key ID, different key
+            ("forged", Signer::es256("es").sign(claims(json!({})))),
+        ];
+        for (name, token) in cases {
+            assert!(verifier.verify(&token).await.is_err(), "{}", name);
+        }
+
+        let mut no_aud = claims(json!({}));
+        no_aud.as_object_mut().unwrap().remove("aud");
+        assert!(verifier.verify(&es.sign(no_aud)).await.is_err());
+        assert!(verifier.verify("not-a-jwt").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_jwks_url_refetches_unknown_key_ids() {
+        let old = Signer::es256("old");
+        let new = Signer::ed25519("new");
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/jwks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(jwks(&[&old])))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+
+        let mut config = JwtConfig::new(ISSUER, format!("{}/jwks", server.uri()));
+        config.claims.groups = "realm_access.roles".to_string();
+        let verifier = JwtVerifier::new(config, reqwest::Client::new()).unwrap();
+
+        let identity = verifier
+            .verify(&old.sign(claims(json!({
+                "realm_access": { "roles": ["admin"] },
+            }))))
+            .await
+            .unwrap();
+        assert!(identity.in_group("admin"));
+
+        // The provider rotates keys; an unknown kid triggers a refetch once
+        // the minimum interval has passed
+        Mock::given(method("GET"))
+            .and(path("/jwks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(jwks(&[&old, &new])))
+            .mount(&server)
+            .await;
+        let token = new.sign(claims(json!({})));
+        assert!(matches!(
+            verifier.verify(&token).await,
+            Err(JwtError::UnknownKey(_))
+        ));
+        verifier.cache.lock().unwrap().fetched_at = Some(Instant::now() - MIN_REFRESH_INTERVAL);
+        assert_eq!(verifier.verify(&token).await.unwrap().subject, "user-1");
+    }
+
+    #[tokio::test]
+    async fn test_cached_keys_served_during_refresh() {
+        let es = Signer::es256("es");
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/jwks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(jwks(&[&es])))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        let config = JwtConfig::new(ISSUER, format!("{}/jwks", server.uri()));
+        let verifier = Arc::new(JwtVerifier::new(config, reqwest::Client::new()).unwrap());
+        let token = es.sign(claims(json!({})));
+        verifier.verify(&token).await.unwrap();
+
+        // The cached set goes stale and the provider answers slowly
+        Mock::given(method("GET"))
+            .and(path("/jwks"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(jwks(&[&es]))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+        verifier.cache.lock().unwrap().fetched_at =
+            Some(Instant::now() - Duration::from_secs(3600));
+        let refreshing = tokio::spawn({
+            let verifier = verifier.clone();
+            let token = token.clone();
+            async move { verifier.verify(&token).await }
+        });
+        // Wait until the refresh is in flight
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while server.received_requests().await.unwrap().len() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let identity = tokio::time::timeout(Duration::from_millis(200), verifier.verify(&token))
+            .await
+            .expect("verification waited for the refresh")
+            .unwrap();
+        assert_eq!(identity.subject, "user-1");
+        refreshing.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let yaml = r#"
+issuer: "https://login.example.com/"
+jwks_url: "https://login.example.com/jwks"
+"#;
+        let config: JwtConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.algorithms, default_algorithms());
+        assert_eq!(config.claims, ClaimNames::default());
+        assert!(config.validate().is_ok());
+
+        let mut hs = config.clone();
+        hs.algorithms = vec![Algorithm::HS256];
+        assert!(matches!(hs.validate(), Err(JwtError::Config(_))));
+
+        let mut none = config;
+        none.jwks_url = None;
+        assert!(matches!(none.validate(), Err(JwtError::Config(_))));
+    }
+}
@@ -5,17 +5,31 @@
 //! - Anthropic-compatible endpoints
 //! - Dual-dialect mode (both OpenAI and Anthropic endpoints)
 //! - Bypass proxy for unknown paths
+//! - API key authentication with scopes
+//! - Bearer JWT authentication against an OIDC key set
+//! - Rate limiting per key, client IP and globally
+//! - Budgets per key, user, tenant or project
+//! - Tenant resolution for multi-tenant mode
 
 pub mod anthropic;
 pub mod async_stream_parser;
+pub mod auth;
+pub mod budget;
 pub mod bypass;
+pub mod experiment;
+pub mod explain;
+pub mod jwt;
 pub mod marker;
 pub mod middleware;
+pub mod model_alias;
 pub mod multi_dialect;
 pub mod openai;
 pub mod provider_registry;
+pub mod rate_limit;
 pub mod responses_ws;
 pub mod streaming_metrics;
+pub mod tenant;
+pub mod tls;
 pub mod types;
 
 pub use bypass::{BypassError, BypassProvider, proxy_request, with_bypass};
@@ -25,3 +39,27 @@ pub use responses_ws::responses_ws_handler;
 pub use types::{
     IngressError, IngressResult, RequestId, RequestMetadata, StreamEvent, TraceContext,
 };
+
+/// Export a connector's latest upstream quota (parsed from the rate-limit
+/// headers of the response just received) as Prometheus gauges
+pub(crate) fn record_quota_metrics(
+    metrics: Option<&lunaroute_observability::Metrics>,
+    provider: &str,
+    quota: Option<lunaroute_core::quota::ProviderQuota>,
+) {
+    let (Some(metrics), Some(quota)) = (metrics, quota) else {
+        return;
+    };
+    for (kind, window) in quota.windows() {
+        metrics.update_provider_quota(provider, kind.as_str(), window.remaining, window.limit);
+    }
+}
+
+/// Hashed id of the pooled upstream key that served a response, for session
+/// spend attribution
+pub(crate) fn upstream_key(headers: &axum::http::HeaderMap) -> Option<String> {
+    headers
+        .get(lunaroute_egress::key_pool::UPSTREAM_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
@@ -1,15 +1,109 @@
+//! `[LUNAROUTE:...]` routing markers
+//!
+//! Marker grammar: `[LUNAROUTE:provider]`, optionally with a model
+//! (`provider/model`) and request parameters (`provider?temperature=0.2`,
+//! `provider/model?max_tokens=4096&top_p=0.9`). `[LUNAROUTE:clear]` drops any
+//! override.
+//!
+//! A marker normally applies to its own message and the tool-result
+//! follow-ups it triggers. With [`StickyMarkers`] the selection is remembered
+//! per client session until `[LUNAROUTE:clear]`.
+
+use lunaroute_routing::ParameterOverrides;
 use regex::Regex;
-use std::sync::LazyLock;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
 
-/// Regex to extract the provider name from a [LUNAROUTE:xxx] marker
+/// Marker body: provider, optional `/model`, optional `?key=value&...`
+const MARKER_BODY: &str = r"[a-zA-Z0-9._-]+(?:/[a-zA-Z0-9._:/-]+)?(?:\?[a-zA-Z0-9._=&-]+)?";
+
+/// Regex to extract the marker body from a [LUNAROUTE:xxx] marker
 static MARKER_EXTRACT_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"\[LUNAROUTE:([a-zA-Z0-9._-]+)\]").unwrap());
+    LazyLock::new(|| Regex::new(&format!(r"\[LUNAROUTE:({})\]", MARKER_BODY)).unwrap());
+
+/// Sessions remembered by [`StickyMarkers`] before the least recently used is evicted
+const MAX_STICKY_SESSIONS: usize = 10_000;
+
+/// Provider, model and parameters selected by a marker
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MarkerSelection {
+    /// Registry provider (or model alias) name
+    pub provider: String,
+    /// Model to send instead of the requested one (wins over the provider's
+    /// configured model override)
+    pub model: Option<String>,
+    /// Request parameter overrides
+    pub params: ParameterOverrides,
+}
+
+impl MarkerSelection {
+    /// Parse a marker body (the text between `[LUNAROUTE:` and `]`)
+    ///
+    /// Unknown parameters and unparseable values are logged and ignored.
+    pub fn parse(body: &str) -> Self {
+        let (target, query) = body.split_once('?').unwrap_or((body, ""));
+        let (provider, model) = match target.split_once('/') {
+            Some((provider, model)) if !model.is_empty() => (provider, Some(model.to_string())),
+            _ => (target.trim_end_matches('/'), None),
+        };
+
+        let mut params = ParameterOverrides::default();
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let applied = match key {
+                "max_tokens" => value.parse().map(|v| params.max_tokens = Some(v)).is_ok(),
+                "temperature" => value.parse().map(|v| params.temperature = Some(v)).is_ok(),
+                "top_p" => value.parse().map(|v| params.top_p = Some(v)).is_ok(),
+                _ => false,
+            };
+            if !applied {
+                tracing::warn!("Ignoring LUNAROUTE marker parameter '{}'", pair);
+            }
+        }
+
+        Self {
+            provider: provider.to_string(),
+            model,
+            params,
+        }
+    }
+}
+
+impl From<&str> for MarkerSelection {
+    fn from(body: &str) -> Self {
+        Self::parse(body)
+    }
+}
+
+impl std::fmt::Display for MarkerSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.provider)?;
+        if let Some(model) = &self.model {
+            write!(f, "/{}", model)?;
+        }
+        let params: Vec<String> = [
+            self.params.max_tokens.map(|v| format!("max_tokens={}", v)),
+            self.params
+                .temperature
+                .map(|v| format!("temperature={}", v)),
+            self.params.top_p.map(|v| format!("top_p={}", v)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !params.is_empty() {
+            write!(f, "?{}", params.join("&"))?;
+        }
+        Ok(())
+    }
+}
 
 /// Result of scanning a request body for a LUNAROUTE marker
 #[derive(Debug, Clone, PartialEq)]
 pub enum MarkerResult {
     /// A provider override marker was found
-    Provider(String),
+    Provider(MarkerSelection),
     /// A "clear" marker was found — strip and route normally
     Clear,
     /// No marker found
@@ -86,22 +180,157 @@ pub fn extract_marker(req: &serde_json::Value) -> MarkerResult {
 
     match found.into_iter().next() {
         Some(name) if name.eq_ignore_ascii_case("clear") => MarkerResult::Clear,
-        Some(name) => MarkerResult::Provider(name),
+        Some(body) => MarkerResult::Provider(MarkerSelection::parse(&body)),
         None => MarkerResult::None,
     }
 }
 
+struct StickyEntry {
+    selection: MarkerSelection,
+    last_used: Instant,
+}
+
+/// Marker selections remembered per client session (sticky marker mode)
+///
+/// Entries expire after `ttl` without use; at most [`MAX_STICKY_SESSIONS`]
+/// sessions are kept.
+pub struct StickyMarkers {
+    ttl: Duration,
+    sessions: Mutex<HashMap<String, StickyEntry>>,
+}
+
+impl StickyMarkers {
+    /// Create an empty store
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The session's active selection, if any
+    pub fn get(&self, session_id: &str) -> Option<MarkerSelection> {
+        let mut sessions = self.sessions.lock().ok()?;
+        let entry = sessions.get_mut(session_id)?;
+        if entry.last_used.elapsed() > self.ttl {
+            sessions.remove(session_id);
+            return None;
+        }
+        entry.last_used = Instant::now();
+        Some(entry.selection.clone())
+    }
+
+    /// Remember a selection for the session
+    pub fn set(&self, session_id: &str, selection: MarkerSelection) {
+        let Ok(mut sessions) = self.sessions.lock() else {
+            return;
+        };
+        if !sessions.contains_key(session_id) && sessions.len() >= MAX_STICKY_SESSIONS {
+            let ttl = self.ttl;
+            sessions.retain(|_, entry| entry.last_used.elapsed() <= ttl);
+            if sessions.len() >= MAX_STICKY_SESSIONS
+                && let Some(oldest) = sessions
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(id, _)| id.clone())
+            {
+                sessions.remove(&oldest);
+            }
+        }
+        sessions.insert(
+            session_id.to_string(),
+            StickyEntry {
+                selection,
+                last_used: Instant::now(),
+            },
+        );
+    }
+
+    /// Forget the session's selection
+    pub fn clear(&self, session_id: &str) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(session_id);
+        }
+    }
+
+    /// Number of sessions with a remembered selection
+    pub fn len(&self) -> usize {
+        self.sessions.lock().map(|s| s.len()).unwrap_or(0)
+    }
+
+    /// Whether no session has a remembered selection
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Routing override in effect for one request
+#[derive(Debug, Default)]
+pub struct ResolvedMarker {
+    /// Selection to apply (None = route normally)
+    pub selection: Option<MarkerSelection>,
+    /// Session tags recording the marker seen and the override applied
+    pub session_tags: Vec<String>,
+}
+
+/// Find the request's marker, strip it, and combine it with sticky state.
+///
+/// A marker in the request wins and, with `sticky` and a session ID, is
+/// remembered for the session; `[LUNAROUTE:clear]` forgets it. Requests
+/// without a marker inherit the session's sticky selection.
+pub fn resolve_marker(
+    req: &mut serde_json::Value,
+    sticky: Option<&StickyMarkers>,
+    session_id: Option<&str>,
+) -> ResolvedMarker {
+    let sticky_session = sticky.zip(session_id);
+    match extract_marker(req) {
+        MarkerResult::Provider(selection) => {
+            strip_marker(req);
+            if let Some((store, session)) = sticky_session {
+                store.set(session, selection.clone());
+            }
+            ResolvedMarker {
+                session_tags: vec![format!("marker:{}", selection)],
+                selection: Some(selection),
+            }
+        }
+        MarkerResult::Clear => {
+            strip_marker(req);
+            if let Some((store, session)) = sticky_session {
+                store.clear(session);
+            }
+            ResolvedMarker {
+                selection: None,
+                session_tags: vec!["marker:clear".to_string()],
+            }
+        }
+        MarkerResult::None => {
+            let Some(selection) = sticky_session.and_then(|(store, session)| store.get(session))
+            else {
+                return ResolvedMarker::default();
+            };
+            tracing::debug!("Applying sticky LUNAROUTE selection '{}'", selection);
+            ResolvedMarker {
+                session_tags: vec![format!("marker_sticky:{}", selection)],
+                selection: Some(selection),
+            }
+        }
+    }
+}
+
 /// Regex matching a standalone system-reminder block containing only a LUNAROUTE marker
 static STANDALONE_STRIP_RE: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(
-        r"(?s)^\s*<system-reminder>\s*\[LUNAROUTE:[a-zA-Z0-9._-]+\]\s*</system-reminder>\s*$",
-    )
+    Regex::new(&format!(
+        r"(?s)^\s*<system-reminder>\s*\[LUNAROUTE:{}\]\s*</system-reminder>\s*$",
+        MARKER_BODY
+    ))
     .unwrap()
 });
 
 /// Regex matching just the marker text (for inline stripping)
 static INLINE_STRIP_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"\[LUNAROUTE:[a-zA-Z0-9._-]+\]").unwrap());
+    LazyLock::new(|| Regex::new(&format!(r"\[LUNAROUTE:{}\]", MARKER_BODY)).unwrap());
 
 /// Remove [LUNAROUTE:xxx] marker text from the request body.
 /// Uses the same message selection logic as extract_marker: walks backward
@@ -197,7 +426,7 @@ mod tests {
         });
         assert_eq!(
             extract_marker(&req),
-            MarkerResult::Provider("sonnet".to_string())
+            MarkerResult::Provider("sonnet".into())
         );
     }
 
@@ -236,10 +465,7 @@ mod tests {
                 "content": "rewrite this [LUNAROUTE:gpt4o] using streams"
             }]
         });
-        assert_eq!(
-            extract_marker(&req),
-            MarkerResult::Provider("gpt4o".to_string())
-        );
+        assert_eq!(extract_marker(&req), MarkerResult::Provider("gpt4o".into()));
     }
 
     #[test]
@@ -258,7 +484,7 @@ mod tests {
         });
         assert_eq!(
             extract_marker(&req),
-            MarkerResult::Provider("my-provider.v2".to_string())
+            MarkerResult::Provider("my-provider.v2".into())
         );
     }
 
@@ -275,7 +501,7 @@ mod tests {
         });
         assert_eq!(
             extract_marker(&req),
-            MarkerResult::Provider("sonnet".to_string())
+            MarkerResult::Provider("sonnet".into())
         );
     }
 
@@ -315,10 +541,7 @@ mod tests {
                 }
             ]
         });
-        assert_eq!(
-            extract_marker(&req),
-            MarkerResult::Provider("gpt4o".to_string())
-        );
+        assert_eq!(extract_marker(&req), MarkerResult::Provider("gpt4o".into()));
     }
 
     #[test]
@@ -411,7 +634,7 @@ mod tests {
 
         // Extract
         let result = extract_marker(&req);
-        assert_eq!(result, MarkerResult::Provider("sonnet".to_string()));
+        assert_eq!(result, MarkerResult::Provider("sonnet".into()));
 
         // Strip
         strip_marker(&mut req);
@@ -500,7 +723,7 @@ mod tests {
         });
         assert_eq!(
             extract_marker(&req),
-            MarkerResult::Provider("kimik25".to_string())
+            MarkerResult::Provider("kimik25".into())
         );
     }
 
@@ -536,7 +759,7 @@ mod tests {
         });
         assert_eq!(
             extract_marker(&req),
-            MarkerResult::Provider("kimik25".to_string())
+            MarkerResult::Provider("kimik25".into())
         );
     }
 
@@ -602,7 +825,7 @@ mod tests {
         // Extract should find the marker in the first user message
         assert_eq!(
             extract_marker(&req),
-            MarkerResult::Provider("kimik25".to_string())
+            MarkerResult::Provider("kimik25".into())
         );
 
         // Strip should also target the first user message (not the tool_result message)
@@ -620,4 +843,100 @@ mod tests {
         let tool_msg = &req["messages"][2]["content"];
         assert!(tool_msg.as_array().unwrap()[0].get("tool_use_id").is_some());
     }
+
+    #[test]
+    fn test_extract_marker_with_model_and_params() {
+        let req = json!({
+            "messages": [{
+                "role": "user",
+                "content": "<system-reminder>\n[LUNAROUTE:openrouter/moonshotai/kimi-k2?temperature=0.2&max_tokens=4096&seed=1]\n</system-reminder>"
+            }]
+        });
+        let MarkerResult::Provider(selection) = extract_marker(&req) else {
+            panic!("expected a provider marker");
+        };
+        assert_eq!(selection.provider, "openrouter");
+        assert_eq!(selection.model.as_deref(), Some("moonshotai/kimi-k2"));
+        assert_eq!(selection.params.temperature, Some(0.2));
+        assert_eq!(selection.params.max_tokens, Some(4096));
+        assert_eq!(
+            selection.to_string(),
+            "openrouter/moonshotai/kimi-k2?max_tokens=4096&temperature=0.2"
+        );
+
+        let params_only = MarkerSelection::parse("sonnet?top_p=0.9");
+        assert_eq!(params_only.provider, "sonnet");
+        assert_eq!(params_only.model, None);
+        assert_eq!(params_only.params.top_p, Some(0.9));
+    }
+
+    #[test]
+    fn test_strip_marker_with_model_and_params() {
+        let mut req = json!({
+            "messages": [{
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": "hello"},
+                    {"type": "text", "text": "<system-reminder>\n[LUNAROUTE:sonnet/claude-sonnet-4-5?temperature=0]\n</system-reminder>"}
+                ]
+            }]
+        });
+        strip_marker(&mut req);
+        let content = req["messages"][0]["content"].as_array().unwrap();
+        assert_eq!(content.len(), 1);
+        assert_eq!(content[0]["text"], "hello");
+    }
+
+    #[test]
+    fn test_resolve_marker_sticky_until_clear() {
+        let sticky = StickyMarkers::new(Duration::from_secs(60));
+        let request = |text: &str| {
+            json!({
+                "messages": [{"role": "user", "content": text}]
+            })
+        };
+
+        // Without sticky mode the selection applies to its own turn only
+        let mut req = request("[LUNAROUTE:sonnet/claude-sonnet-4-5] hi");
+        let resolved = resolve_marker(&mut req, None, Some("s1"));
+        assert_eq!(resolved.selection.unwrap().provider, "sonnet");
+        assert_eq!(
+            resolved.session_tags,
+            vec!["marker:sonnet/claude-sonnet-4-5"]
+        );
+        assert_eq!(req["messages"][0]["content"], " hi");
+        let resolved = resolve_marker(&mut request("next"), None, Some("s1"));
+        assert!(resolved.selection.is_none());
+
+        // Sticky: later turns of the same session inherit the selection
+        resolve_marker(
+            &mut request("[LUNAROUTE:sonnet/claude-sonnet-4-5] hi"),
+            Some(&sticky),
+            Some("s1"),
+        );
+        let resolved = resolve_marker(&mut request("next"), Some(&sticky), Some("s1"));
+        assert_eq!(
+            resolved.selection.unwrap().model.as_deref(),
+            Some("claude-sonnet-4-5")
+        );
+        assert_eq!(
+            resolved.session_tags,
+            vec!["marker_sticky:sonnet/claude-sonnet-4-5"]
+        );
+        assert!(
+            resolve_marker(&mut request("other"), Some(&sticky), Some("s2"))
+                .selection
+                .is_none()
+        );
+
+        // Clear forgets it
+        let resolved = resolve_marker(&mut request("[LUNAROUTE:clear]"), Some(&sticky), Some("s1"));
+        assert_eq!(resolved.session_tags, vec!["marker:clear"]);
+        assert!(
+            resolve_marker(&mut request("next"), Some(&sticky), Some("s1"))
+                .selection
+                .is_none()
+        );
+        assert!(sticky.is_empty());
+    }
 }
@@ -0,0 +1,262 @@
+//! Model alias resolution for passthrough handlers
+//!
+//! Applies the routing crate's [`ModelAliasTable`] to raw JSON request bodies:
+//! retired model IDs are rewritten in place, and virtual aliases select the
+//! first target whose provider is present in the [`ProviderRegistry`].
+
+use crate::provider_registry::{ProviderEntry, ProviderRegistry, ProviderType};
+use crate::types::{IngressError, IngressResult};
+use lunaroute_routing::ModelAliasTable;
+
+/// Outcome of applying the alias table to a request body
+#[derive(Debug, Default)]
+pub struct AliasSelection {
+    /// Registry provider chosen for an alias target (None = default connector)
+    pub provider: Option<String>,
+    /// Registry entry for the chosen provider
+    pub entry: Option<ProviderEntry>,
+    /// Session tags recording the requested and resolved model names
+    pub session_tags: Vec<String>,
+}
+
+/// Rewrite and resolve the `model` field of a passthrough request body.
+///
+/// `dialect` is the ingress dialect of the request. Targets on a provider of a
+/// different dialect are only eligible when `allow_cross_dialect` is set.
+pub fn apply_model_aliases(
+    table: &ModelAliasTable,
+    registry: Option<&ProviderRegistry>,
+    req: &mut serde_json::Value,
+    dialect: ProviderType,
+    allow_cross_dialect: bool,
+) -> IngressResult<AliasSelection> {
+    let Some(requested) = req.get("model").and_then(|m| m.as_str()) else {
+        return Ok(AliasSelection::default());
+    };
+
+    let resolution = table.resolve(requested);
+    if resolution.is_unchanged() {
+        return Ok(AliasSelection::default());
+    }
+
+    let mut selection = AliasSelection {
+        session_tags: vec![format!("model_requested:{}", resolution.requested)],
+        ..Default::default()
+    };
+
+    if resolution.targets.is_empty() {
+        // Plain rewrite of a retired model ID
+        let rewritten = resolution.effective_model().to_string();
+        tracing::info!(
+            "Rewrote retired model '{}' to '{}'",
+            resolution.requested,
+            rewritten
+        );
+        selection
+            .session_tags
+            .push(format!("model_resolved:{}", rewritten));
+        req["model"] = serde_json::Value::String(rewritten);
+        return Ok(selection);
+    }
+
+    let alias = resolution.effective_model().to_string();
+    for target in resolution.targets {
+        let Some(entry) = registry.and_then(|r| r.get(&target.provider)) else {
+            tracing::debug!(
+                "Model alias '{}': provider '{}' not registered, trying next target",
+                alias,
+                target.provider
+            );
+            continue;
+        };
+        if entry.connector_type != dialect && !allow_cross_dialect {
+            tracing::debug!(
+                "Model alias '{}': provider '{}' speaks {:?}, skipping for {:?} request",
+                alias,
+                target.provider,
+                entry.connector_type,
+                dialect
+            );
+            continue;
+        }
+
+        tracing::info!(
+            "Model alias '{}' resolved to provider '{}', model '{}'",
+            alias,
+            target.provider,
+            target.model
+        );
+        req["model"] = serde_json::Value::String(target.model.clone());
+        target.params.apply_to_json(req);
+
+        selection
+            .session_tags
+            .push(format!("model_alias:{}", alias));
+        selection
+            .session_tags
+            .push(format!("model_resolved:{}", target.model));
+        selection.provider = Some(target.provider.clone());
+        selection.entry = Some(entry.clone());
+        return Ok(selection);
+    }
+
+    Err(IngressError::InvalidRequest(format!(
+        "No available provider for model alias '{}'",
+        alias
+    )))
+}
+
+/// OpenAI-format model objects for every configured alias (for `/v1/models`)
+pub fn alias_model_objects(table: &ModelAliasTable) -> Vec<serde_json::Value> {
+    let mut names: Vec<&String> = table.aliases().map(|(name, _)| name).collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| {
+            serde_json::json!({
+                "id": name,
+                "object": "model",
+                "created": 0,
+                "owned_by": "lunaroute",
+            })
+        })
+        .collect()
+}
+
+/// Append alias entries to an upstream `/v1/models` list response
+pub fn merge_alias_models(table: &ModelAliasTable, response: &mut serde_json::Value) {
+    let Some(data) = response.get_mut("data").and_then(|d| d.as_array_mut()) else {
+        return;
+    };
+    for model in alias_model_objects(table) {
+        let exists = data.iter().any(|m| m.get("id") == model.get("id"));
+        if !exists {
+            data.push(model);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lunaroute_routing::{AliasTarget, ModelAlias, ParameterOverrides};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn entry(connector_type: ProviderType) -> ProviderEntry {
+        ProviderEntry {
+            connector_type,
+            openai_connector: None,
+            anthropic_connector: None,
+            model_override: None,
+        }
+    }
+
+    fn table() -> ModelAliasTable {
+        let mut aliases = HashMap::new();
+        aliases.insert(
+            "fast".to_string(),
+            ModelAlias {
+                description: None,
+                targets: vec![
+                    AliasTarget {
+                        provider: "openai".to_string(),
+                        model: "gpt-5-mini".to_string(),
+                        params: ParameterOverrides::default(),
+                    },
+                    AliasTarget {
+                        provider: "anthropic".to_string(),
+                        model: "claude-haiku-4-5".to_string(),
+                        params: ParameterOverrides {
+                            max_tokens: Some(1024),
+                            ..Default::default()
+                        },
+                    },
+                ],
+            },
+        );
+        let mut rewrites = HashMap::new();
+        rewrites.insert(
+            "claude-3-5-sonnet".to_string(),
+            "claude-sonnet-4-5".to_string(),
+        );
+        ModelAliasTable::new(aliases, rewrites).unwrap()
+    }
+
+    fn registry() -> ProviderRegistry {
+        let mut registry = ProviderRegistry::new();
+        registry.insert("openai".to_string(), entry(ProviderType::OpenAI));
+        registry.insert("anthropic".to_string(), entry(ProviderType::Anthropic));
+        registry
+    }
+
+    #[test]
+    fn test_alias_skips_cross_dialect_target() {
+        let mut req = json!({"model": "fast", "max_tokens": 4096});
+        let selection = apply_model_aliases(
+            &table(),
+            Some(&registry()),
+            &mut req,
+            ProviderType::Anthropic,
+            false,
+        )
+        .unwrap();
+        assert_eq!(selection.provider.as_deref(), Some("anthropic"));
+        assert_eq!(req["model"], "claude-haiku-4-5");
+        assert_eq!(req["max_tokens"], 1024);
+        assert!(
+            selection
+                .session_tags
+                .contains(&"model_requested:fast".to_string())
+        );
+        assert!(
+            selection
+                .session_tags
+                .contains(&"model_resolved:claude-haiku-4-5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_alias_cross_dialect_allowed() {
+        let mut req = json!({"model": "fast"});
+        let selection = apply_model_aliases(
+            &table(),
+            Some(&registry()),
+            &mut req,
+            ProviderType::Anthropic,
+            true,
+        )
+        .unwrap();
+        assert_eq!(selection.provider.as_deref(), Some("openai"));
+        assert_eq!(req["model"], "gpt-5-mini");
+    }
+
+    #[test]
+    fn test_rewrite_only() {
+        let mut req = json!({"model": "claude-3-5-sonnet"});
+        let selection =
+            apply_model_aliases(&table(), None, &mut req, ProviderType::Anthropic, false).unwrap();
+        assert!(selection.provider.is_none());
+        assert_eq!(req["model"], "claude-sonnet-4-5");
+    }
+
+    #[test]
+    fn test_alias_without_available_provider() {
+        let mut req = json!({"model": "fast"});
+        let result = apply_model_aliases(&table(), None, &mut req, ProviderType::OpenAI, false);
+        assert!(matches!(result, Err(IngressError::InvalidRequest(_))));
+    }
+
+    #[test]
+    fn test_merge_alias_models() {
+        let mut response = json!({"object": "list", "data": [{"id": "gpt-5", "object": "model"}]});
+        merge_alias_models(&table(), &mut response);
+        let ids: Vec<&str> = response["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["gpt-5", "fast"]);
+    }
+}
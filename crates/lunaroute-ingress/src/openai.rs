@@ -3,7 +3,7 @@
 use crate::types::{IngressError, IngressResult};
 use axum::{
     Router,
-    extract::{Json, State},
+    extract::{Extension, Json, State},
     response::{
         IntoResponse, Response,
         sse::{Event, KeepAlive, Sse},
@@ -517,6 +517,11 @@ pub fn to_normalized(req: OpenAIChatRequest) -> IngressResult<NormalizedRequest>
         }),
     });
 
+    let mut metadata = std::collections::HashMap::new();
+    if let Some(user) = req.user {
+        metadata.insert("user".to_string(), serde_json::Value::String(user));
+    }
+
     Ok(NormalizedRequest {
         messages,
         system: None,
@@ -530,7 +535,7 @@ pub fn to_normalized(req: OpenAIChatRequest) -> IngressResult<NormalizedRequest>
         tools,
         tool_choice,
         tool_results,
-        metadata: std::collections::HashMap::new(),
+        metadata,
     })
 }
 
@@ -750,14 +755,27 @@ fn stream_event_to_openai_chunk(
 /// Chat completion handler
 pub async fn chat_completions(
     State(provider): State<Arc<dyn Provider>>,
+    auth_key: Option<Extension<crate::auth::AuthenticatedKey>>,
+    identity: Option<Extension<crate::auth::ClientIdentity>>,
+    tenant: Option<Extension<lunaroute_core::tenant::TenantId>>,
     Json(req): Json<OpenAIChatRequest>,
 ) -> Result<Response, IngressError> {
     let start_time = std::time::Instant::now();
     let is_streaming = req.stream.unwrap_or(false);
     let model = req.model.clone();
+    let auth_key = auth_key.as_ref().map(|Extension(k)| k);
+    crate::auth::authorize_model(auth_key, &model)?;
 
     // Convert to normalized format (includes validation)
-    let normalized = to_normalized(req)?;
+    let mut normalized = to_normalized(req)?;
+    // The router only picks providers and models the key may use
+    crate::auth::attach_scope(auth_key, &mut normalized);
+    if let Some(Extension(identity)) = &identity {
+        identity.attach(&mut normalized);
+    }
+    if let Some(Extension(tenant)) = &tenant {
+        tenant.attach(&mut normalized);
+    }
 
     if is_streaming {
         // Log streaming request for observability
@@ -923,6 +941,7 @@ pub(crate) async fn responses_sse_stream(
     state: Arc<OpenAIPassthroughState>,
     headers: axum::http::HeaderMap,
     body: axum::body::Bytes,
+    client_identity: Option<String>,
 ) -> Result<futures::stream::BoxStream<'static, Result<SseEvent, IngressError>>, IngressError> {
     let start_time = std::time::Instant::now();
 
@@ -1011,6 +1030,7 @@ pub(crate) async fn responses_sse_stream(
         let rid = request_id.clone();
         let m = model.clone();
         let ua = user_agent.clone();
+        let identity = client_identity.clone();
         tokio::spawn(async move {
             let event = serde_json::to_value(SessionEvent::Started {
                 session_id: sid,
@@ -1026,6 +1046,9 @@ pub(crate) async fn responses_sse_stream(
                     api_version: None,
                     request_headers: Default::default(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: identity,
                 },
             })
             .ok();
@@ -1162,11 +1185,12 @@ pub(crate) async fn responses_sse_stream(
     }
 
     // Handle streaming passthrough for responses endpoint - pass raw bytes
-    let stream_response = match state
+    let stream_response = state
         .connector
         .stream_passthrough_to_endpoint_bytes("responses", body, passthrough_headers)
-        .await
-    {
+        .await;
+    crate::record_quota_metrics(state.metrics.as_deref(), "openai", state.connector.quota());
+    let stream_response = match stream_response {
         Ok(response) => response,
         Err(e) => {
             // Record error in session if recording is enabled
@@ -1239,6 +1263,7 @@ pub(crate) async fn responses_sse_stream(
     };
 
     use futures::StreamExt;
+    let upstream_key = crate::upstream_key(stream_response.headers());
     let byte_stream = stream_response.bytes_stream();
     let sse_stream = eventsource_stream::EventStream::new(byte_stream);
 
@@ -1279,6 +1304,7 @@ pub(crate) async fn responses_sse_stream(
         let session_id_for_async = session_id_clone.clone();
         let request_id_for_async = request_id_clone.clone();
         let user_agent_for_async = user_agent_clone.clone();
+        let upstream_key_for_async = upstream_key.clone();
         let start_time_for_async = start_time_clone;
         let tool_call_mapper_for_async = tool_call_mapper_clone.clone();
 
@@ -1369,6 +1395,7 @@ pub(crate) async fn responses_sse_stream(
                                 let request_id_task = request_id_for_async.clone();
                                 let start_time_task = start_time_for_async;
                                 let user_agent_task = user_agent_for_async.clone();
+                                let upstream_key_task = upstream_key_for_async.clone();
                                 let prompt_tokens = stats_guard.prompt_tokens;
                                 let completion_tokens = stats_guard.completion_tokens;
                                 let total_tokens = stats_guard.total_tokens;
@@ -1414,6 +1441,7 @@ pub(crate) async fn responses_sse_stream(
                                             content_blocks: 1,
                                             has_refusal: false,
                                             user_agent: user_agent_task.clone(),
+                                            upstream_key: upstream_key_task.clone(),
                                         };
 
                                         if let Ok(json) = serde_json::to_value(stats_event) {
@@ -1539,10 +1567,23 @@ pub(crate) async fn responses_sse_stream(
 async fn responses_passthrough(
     State(state): State<Arc<OpenAIPassthroughState>>,
     headers: axum::http::HeaderMap,
+    auth_key: Option<Extension<crate::auth::AuthenticatedKey>>,
+    identity: Option<Extension<crate::auth::ClientIdentity>>,
+    tenant: Option<Extension<Arc<lunaroute_core::tenant::TenantContext>>>,
     body: axum::body::Bytes,
 ) -> Result<Response, IngressError> {
     let start_time = std::time::Instant::now();
     tracing::debug!("OpenAI Responses API passthrough mode");
+    // Record into the request's tenant
+    let state = match crate::tenant::tenant_session_store(tenant.as_ref()) {
+        Some(store) => Arc::new(OpenAIPassthroughState {
+            session_store: Some(store),
+            ..(*state).clone()
+        }),
+        None => state,
+    };
+    let client_identity =
+        crate::auth::client_identity_subject(identity.as_ref().map(|Extension(i)| i));
 
     // Extract user-agent from headers for session tracking
     // Truncate to 255 chars to prevent database issues with extremely long user agents
@@ -1644,6 +1685,18 @@ async fn responses_passthrough(
             (false, "unknown".to_string(), None)
         };
 
+    // The Responses API always goes to the default OpenAI provider
+    let auth_key = auth_key.as_ref().map(|Extension(k)| k);
+    crate::auth::authorize_provider(auth_key, "openai")?;
+    crate::auth::authorize_model(
+        auth_key,
+        req_json
+            .as_ref()
+            .and_then(|r| r.get("model"))
+            .and_then(|m| m.as_str())
+            .unwrap_or_default(),
+    )?;
+
     let before_provider = std::time::Instant::now();
     let pre_provider_overhead = before_provider.duration_since(start_time);
 
@@ -1678,6 +1731,7 @@ async fn responses_passthrough(
         let rid = request_id.clone();
         let m = model.clone();
         let ua = user_agent.clone();
+        let identity = client_identity.clone();
         tokio::spawn(async move {
             let event = serde_json::to_value(SessionEvent::Started {
                 session_id: sid,
@@ -1693,6 +1747,9 @@ async fn responses_passthrough(
                     api_version: None,
                     request_headers: Default::default(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: identity,
                 },
             })
             .ok();
@@ -1848,8 +1905,13 @@ async fn responses_passthrough(
         let sse_keepalive_interval = state.sse_keepalive_interval_secs;
         let sse_keepalive_enabled_flag = state.sse_keepalive_enabled;
 
-        let event_stream =
-            responses_sse_stream(state.clone(), headers.clone(), body.clone()).await?;
+        let event_stream = responses_sse_stream(
+            state.clone(),
+            headers.clone(),
+            body.clone(),
+            client_identity,
+        )
+        .await?;
 
         use axum::response::sse::{Event, KeepAlive, Sse};
         use futures::StreamExt as _;
@@ -1877,12 +1939,13 @@ async fn responses_passthrough(
         Ok(Sse::new(axum_stream).keep_alive(keepalive).into_response())
     } else {
         // Send directly to OpenAI API (non-streaming) - pass raw bytes
-        match state
+        let response = state
             .connector
             .send_passthrough_to_endpoint_bytes("responses", body, passthrough_headers)
-            .await
-        {
-            Ok((response, _response_headers)) => {
+            .await;
+        crate::record_quota_metrics(state.metrics.as_deref(), "openai", state.connector.quota());
+        match response {
+            Ok((response, response_headers)) => {
                 // Record successful response if recording is enabled
                 if let (Some(session_store), Some(session_id), Some(request_id)) = (
                     &state.session_store,
@@ -1928,6 +1991,9 @@ async fn responses_passthrough(
                     let sid_stats = session_id.clone();
                     let rid_stats = request_id.clone();
                     let ua_clone = user_agent.clone();
+                    let upstream_key = response_headers
+                        .get(lunaroute_egress::key_pool::UPSTREAM_KEY_HEADER)
+                        .cloned();
                     tokio::spawn(async move {
                         use lunaroute_session::events::TokenTotals;
 
@@ -1960,6 +2026,7 @@ async fn responses_passthrough(
                             content_blocks,
                             has_refusal: false,
                             user_agent: ua_clone,
+                            upstream_key,
                         };
                         if let Ok(json) = serde_json::to_value(event) {
                             let _ = store_stats.write_event(None, json).await;
@@ -2100,9 +2167,11 @@ async fn responses_passthrough(
 }
 
 /// Handler for /v1/models endpoint (stub for non-passthrough mode)
-async fn list_models() -> Result<Json<ModelsListResponse>, IngressError> {
+async fn list_models(
+    model_aliases: Option<Extension<Arc<lunaroute_routing::ModelAliasTable>>>,
+) -> Result<Json<ModelsListResponse>, IngressError> {
     // Return a basic list of common models
-    let models = vec![
+    let mut models = vec![
         ModelObject {
             id: "gpt-4".to_string(),
             object: "model".to_string(),
@@ -2123,6 +2192,15 @@ async fn list_models() -> Result<Json<ModelsListResponse>, IngressError> {
         },
     ];
 
+    // Virtual model aliases are listed alongside the built-in models
+    if let Some(Extension(table)) = &model_aliases {
+        models.extend(
+            crate::model_alias::alias_model_objects(table)
+                .into_iter()
+                .filter_map(|m| serde_json::from_value::<ModelObject>(m).ok()),
+        );
+    }
+
     Ok(Json(ModelsListResponse {
         object: "list".to_string(),
         data: models,
@@ -2133,6 +2211,7 @@ async fn list_models() -> Result<Json<ModelsListResponse>, IngressError> {
 async fn models_passthrough(
     State(state): State<Arc<OpenAIPassthroughState>>,
     headers: axum::http::HeaderMap,
+    model_aliases: Option<Extension<Arc<lunaroute_routing::ModelAliasTable>>>,
 ) -> Result<Response, IngressError> {
     tracing::debug!("OpenAI models API passthrough mode");
 
@@ -2150,7 +2229,13 @@ async fn models_passthrough(
         .get_passthrough("models", passthrough_headers)
         .await
     {
-        Ok(response) => Ok(Json(response).into_response()),
+        Ok(mut response) => {
+            // Virtual model aliases are listed alongside the upstream models
+            if let Some(Extension(table)) = &model_aliases {
+                crate::model_alias::merge_alias_models(table, &mut response);
+            }
+            Ok(Json(response).into_response())
+        }
         Err(e) => {
             // Handle provider errors by returning proper status codes
             use lunaroute_egress::EgressError;
@@ -2209,6 +2294,7 @@ pub fn router_with_session_store(
 }
 
 /// State for OpenAI passthrough handler (connector + optional stats tracker + metrics + session store)
+#[derive(Clone)]
 pub struct OpenAIPassthroughState {
     pub connector: Arc<lunaroute_egress::openai::OpenAIConnector>,
     pub stats_tracker: Option<Arc<dyn crate::types::SessionStatsTracker>>,
@@ -2263,67 +2349,179 @@ pub fn passthrough_router(
 /// Passthrough handler for OpenAI→OpenAI routing (no normalization)
 /// Takes raw JSON, sends directly to OpenAI, returns raw JSON
 /// Preserves 100% API fidelity while still extracting metrics
+#[allow(clippy::too_many_arguments)]
 pub async fn chat_completions_passthrough(
     State(state): State<Arc<OpenAIPassthroughState>>,
     headers: axum::http::HeaderMap,
+    model_aliases: Option<Extension<Arc<lunaroute_routing::ModelAliasTable>>>,
+    experiments: Option<Extension<Arc<lunaroute_routing::ExperimentSet>>>,
+    sticky_markers: Option<Extension<Arc<crate::marker::StickyMarkers>>>,
+    auth_key: Option<Extension<crate::auth::AuthenticatedKey>>,
+    identity: Option<Extension<crate::auth::ClientIdentity>>,
+    tenant: Option<Extension<Arc<lunaroute_core::tenant::TenantContext>>>,
     Json(req): Json<serde_json::Value>,
 ) -> Result<Response, IngressError> {
     let start_time = std::time::Instant::now();
     tracing::debug!("OpenAI passthrough mode: skipping normalization");
+    // Record into the request's tenant
+    let state = match crate::tenant::tenant_session_store(tenant.as_ref()) {
+        Some(store) => Arc::new(OpenAIPassthroughState {
+            session_store: Some(store),
+            ..(*state).clone()
+        }),
+        None => state,
+    };
+    let client_identity =
+        crate::auth::client_identity_subject(identity.as_ref().map(|Extension(i)| i));
 
     let mut req = req;
 
-    // LUNAROUTE marker detection — check for provider override
-    let marker_result = crate::marker::extract_marker(&req);
+    // Model as the client asked for it, before markers, experiments and
+    // aliases rewrite the body (recorded as model_requested)
+    let requested_model = req
+        .get("model")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    // Extract session_id header for session grouping (before filtering)
+    let client_session_id = headers
+        .get("session_id")
+        .or_else(|| headers.get("session-id"))
+        .or_else(|| headers.get("x-session-id"))
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    if let Some(ref sid) = client_session_id {
+        tracing::debug!("📍 Using client-provided session ID: {}", sid);
+    }
+
+    // LUNAROUTE marker detection — check for provider override (or the
+    // session's sticky selection)
+    let marker = crate::marker::resolve_marker(
+        &mut req,
+        sticky_markers.as_ref().map(|Extension(s)| s.as_ref()),
+        client_session_id.as_deref(),
+    );
+    let mut marker_tags = marker.session_tags;
     let mut override_connector: Option<Arc<lunaroute_egress::openai::OpenAIConnector>> = None;
     let mut marker_provider_name: Option<String> = None;
 
-    match &marker_result {
-        crate::marker::MarkerResult::Provider(name) => {
-            if let Some(registry) = &state.provider_registry {
-                if let Some(entry) = registry.get(name) {
-                    if entry.connector_type != crate::ProviderType::OpenAI {
-                        tracing::warn!(
-                            "LUNAROUTE marker '{}' targets {:?} provider but request uses OpenAI format",
-                            name,
-                            entry.connector_type
-                        );
-                        return Err(IngressError::InvalidRequest(format!(
-                            "LUNAROUTE marker targets provider '{}' ({:?}) but request uses OpenAI format. Cross-dialect routing requires normalized mode.",
-                            name, entry.connector_type
-                        )));
-                    }
-                    if let Some(ref connector) = entry.openai_connector {
-                        tracing::info!(
-                            "LUNAROUTE marker: routing to provider '{}', model_override={:?}",
-                            name,
-                            entry.model_override
-                        );
-                        override_connector = Some(connector.clone());
-                        marker_provider_name = Some(name.clone());
-                        if let Some(ref model) = entry.model_override {
-                            req["model"] = serde_json::Value::String(model.clone());
-                        }
-                    }
-                } else {
+    if let Some(selection) = &marker.selection {
+        let name = &selection.provider;
+        if let Some(registry) = &state.provider_registry {
+            if let Some(entry) = registry.get(name) {
+                if entry.connector_type != crate::ProviderType::OpenAI {
                     tracing::warn!(
-                        "LUNAROUTE marker references unknown provider '{}', using default",
-                        name
+                        "LUNAROUTE marker '{}' targets {:?} provider but request uses OpenAI format",
+                        name,
+                        entry.connector_type
                     );
+                    return Err(IngressError::InvalidRequest(format!(
+                        "LUNAROUTE marker targets provider '{}' ({:?}) but request uses OpenAI format. Cross-dialect routing requires normalized mode.",
+                        name, entry.connector_type
+                    )));
+                }
+                if let Some(ref connector) = entry.openai_connector {
+                    let model_override = selection.model.as_ref().or(entry.model_override.as_ref());
+                    tracing::info!(
+                        "LUNAROUTE marker: routing to provider '{}', model_override={:?}",
+                        name,
+                        model_override
+                    );
+                    override_connector = Some(connector.clone());
+                    marker_provider_name = Some(name.clone());
+                    marker_tags.push(format!("lunaroute:{}", name));
+                    if let Some(model) = model_override {
+                        req["model"] = serde_json::Value::String(model.clone());
+                        marker_tags.push(format!("model_override:{}", model));
+                    }
+                    selection.params.apply_to_json(&mut req);
                 }
+            } else if let Some(Extension(table)) = &model_aliases
+                && table.get_alias(name).is_some()
+            {
+                tracing::info!("LUNAROUTE marker: selecting model alias '{}'", name);
+                req["model"] = serde_json::Value::String(name.clone());
             } else {
-                tracing::debug!(
-                    "LUNAROUTE marker '{}' found but no provider registry available, using default",
+                tracing::warn!(
+                    "LUNAROUTE marker references unknown provider '{}', using default",
                     name
                 );
             }
-            crate::marker::strip_marker(&mut req);
+        } else {
+            tracing::debug!(
+                "LUNAROUTE marker '{}' found but no provider registry available, using default",
+                name
+            );
+        }
+    }
+
+    // A/B experiment assignment (a marker-selected provider opts the request out).
+    // Only OpenAI-dialect arms are eligible: this handler has no cross-dialect path.
+    let mut experiment = crate::experiment::ExperimentSelection::default();
+    if let Some(Extension(set)) = &experiments
+        && marker_provider_name.is_none()
+    {
+        let user_id = req
+            .get("user")
+            .or_else(|| req.get("safety_identifier"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        experiment = crate::experiment::apply_experiments(
+            set,
+            state.provider_registry.as_deref(),
+            &mut req,
+            crate::ProviderType::OpenAI,
+            false,
+            crate::experiment::StickyKeys {
+                session: client_session_id.as_deref(),
+                user: user_id.as_deref(),
+                headers: &headers,
+            },
+        );
+        if let Some(entry) = &experiment.entry {
+            override_connector = entry.openai_connector.clone();
         }
-        crate::marker::MarkerResult::Clear => {
-            crate::marker::strip_marker(&mut req);
+    }
+
+    // Virtual model aliases and retired-model rewrites (a marker- or
+    // experiment-selected provider wins). Only OpenAI-dialect targets are
+    // eligible: this handler has no cross-dialect path.
+    let mut alias_provider_name: Option<String> = None;
+    let mut alias_tags: Vec<String> = Vec::new();
+    if let Some(Extension(table)) = &model_aliases
+        && marker_provider_name.is_none()
+        && experiment.provider.is_none()
+    {
+        let selection = crate::model_alias::apply_model_aliases(
+            table,
+            state.provider_registry.as_deref(),
+            &mut req,
+            crate::ProviderType::OpenAI,
+            false,
+        )?;
+        if let Some(entry) = &selection.entry {
+            override_connector = entry.openai_connector.clone();
         }
-        crate::marker::MarkerResult::None => {}
+        alias_provider_name = selection.provider;
+        alias_tags = selection.session_tags;
     }
+    let quota_provider = marker_provider_name
+        .as_deref()
+        .or(experiment.provider.as_deref())
+        .or(alias_provider_name.as_deref())
+        .unwrap_or("openai")
+        .to_string();
+    let auth_key = auth_key.as_ref().map(|Extension(k)| k);
+    crate::auth::authorize_provider(auth_key, &quota_provider)?;
+    // Markers, experiments and aliases may have rewritten the model
+    crate::auth::authorize_model(
+        auth_key,
+        req.get("model")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default(),
+    )?;
 
     // Extract user-agent from headers for session tracking
     // Truncate to 255 chars to prevent database issues with extremely long user agents
@@ -2338,18 +2536,6 @@ pub async fn chat_completions_passthrough(
             }
         });
 
-    // Extract session_id header for session grouping (before filtering)
-    let client_session_id = headers
-        .get("session_id")
-        .or_else(|| headers.get("session-id"))
-        .or_else(|| headers.get("x-session-id"))
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
-
-    if let Some(ref sid) = client_session_id {
-        tracing::debug!("📍 Using client-provided session ID: {}", sid);
-    }
-
     // Pass through ALL headers from the client (except hop-by-hop headers)
     // This allows client to provide auth headers if no API key is configured
     let mut passthrough_headers = std::collections::HashMap::new();
@@ -2446,17 +2632,18 @@ pub async fn chat_completions_passthrough(
         let store = session_store.clone();
         let sid = session_id.clone();
         let rid = request_id.clone();
-        let m = model.clone();
+        let m = requested_model;
         let ua = user_agent.clone();
-        let marker_provider_name_for_spawn = marker_provider_name.clone();
-        let state_for_spawn = state.clone();
         tokio::spawn(async move {
             let event = serde_json::to_value(SessionEvent::Started {
                 session_id: sid,
                 request_id: rid,
                 timestamp: chrono::Utc::now(),
                 model_requested: m,
-                provider: "openai".to_string(),
+                provider: experiment
+                    .provider
+                    .or(alias_provider_name)
+                    .unwrap_or_else(|| "openai".to_string()),
                 listener: "openai".to_string(),
                 is_streaming,
                 metadata: V2Metadata {
@@ -2465,18 +2652,13 @@ pub async fn chat_completions_passthrough(
                     api_version: None,
                     request_headers: Default::default(),
                     session_tags: {
-                        let mut tags = vec![];
-                        if let Some(ref provider_name) = marker_provider_name_for_spawn {
-                            tags.push(format!("lunaroute:{}", provider_name));
-                            if let Some(registry) = &state_for_spawn.provider_registry
-                                && let Some(entry) = registry.get(provider_name)
-                                && let Some(ref model) = entry.model_override
-                            {
-                                tags.push(format!("model_override:{}", model));
-                            }
-                        }
+                        let mut tags = marker_tags;
+                        tags.extend(alias_tags);
                         tags
                     },
+                    experiment: experiment.experiment,
+                    experiment_arm: experiment.arm,
+                    client_identity,
                 },
             })
             .ok();
@@ -2718,6 +2900,7 @@ pub async fn chat_completions_passthrough(
             .stream_passthrough(req, passthrough_headers)
             .await
             .map_err(|e| IngressError::ProviderError(e.to_string()))?;
+        crate::record_quota_metrics(state.metrics.as_deref(), &quota_provider, connector.quota());
 
         // Track streaming metrics using shared module
         use crate::streaming_metrics::StreamingMetricsTracker;
@@ -2728,6 +2911,7 @@ pub async fn chat_completions_passthrough(
         let tracker_for_finalize = tracker.clone();
         let metrics_clone = state.metrics.clone();
         let model_name_clone = model.clone();
+        let upstream_key = crate::upstream_key(stream_response.headers());
 
         let byte_stream = stream_response.bytes_stream();
         let sse_stream = eventsource_stream::EventStream::new(byte_stream);
@@ -2908,6 +3092,7 @@ pub async fn chat_completions_passthrough(
                             request_id.clone(),
                             session_store.clone(),
                             user_agent.clone(),
+                            upstream_key.clone(),
                             Some(state.tool_call_mapper.clone()),
                         );
                     }
@@ -2949,6 +3134,7 @@ pub async fn chat_completions_passthrough(
     // Send directly to OpenAI API (non-streaming)
     let connector = override_connector.as_ref().unwrap_or(&state.connector);
     let response_result = connector.send_passthrough(req, passthrough_headers).await;
+    crate::record_quota_metrics(state.metrics.as_deref(), &quota_provider, connector.quota());
 
     let (response, response_headers) = match response_result {
         Ok((resp, headers)) => (resp, headers),
@@ -3023,6 +3209,9 @@ pub async fn chat_completions_passthrough(
     ) {
         let response_clone = response.clone();
         let user_agent_clone = user_agent.clone();
+        let upstream_key = response_headers
+            .get(lunaroute_egress::key_pool::UPSTREAM_KEY_HEADER)
+            .cloned();
         let tool_call_mapper = state.tool_call_mapper.clone();
 
         tokio::spawn(async move {
@@ -3236,6 +3425,7 @@ pub async fn chat_completions_passthrough(
                             content_blocks,
                             has_refusal,
                             user_agent: user_agent_clone,
+                            upstream_key,
                         };
                         if let Ok(json) = serde_json::to_value(event) {
                             let _ = store_clone.write_event(None, json).await;
@@ -3502,7 +3692,7 @@ mod tests {
             tool_choice: None,
         };
 
-        let response = chat_completions(State(provider), Json(req)).await;
+        let response = chat_completions(State(provider), None, None, None, Json(req)).await;
         assert!(response.is_ok());
     }
 
@@ -0,0 +1,780 @@
+//! Ingress rate limiting
+//!
+//! Token buckets applied before a request reaches the router, at three
+//! scopes: globally, per ingress API key (see [`crate::auth`]) and per client
+//! IP. Each scope can limit requests per minute, estimated input tokens per
+//! minute and concurrent streaming responses.
+//!
+//! ```yaml
+//! rate_limits:
+//!   enabled: true
+//!   global:
+//!     requests_per_minute: 600
+//!   per_key:
+//!     requests_per_minute: 60
+//!     input_tokens_per_minute: 200000
+//!     concurrent_streams: 4
+//!   per_ip:
+//!     requests_per_minute: 120
+//! ```
+//!
+//! Buckets live in the [`StateStore`] and are updated with compare-and-swap,
+//! so replicas sharing the PostgreSQL backend enforce one limit together. A
+//! bucket holds a minute's worth of capacity (the burst) and refills
+//! continuously. Input tokens are estimated from the request body size.
+//! A request only counts against the buckets if every scope admits it: the
+//! narrow scopes are checked before the global one, and whatever a rejected
+//! request already took is refunded, so a client hammering its own limit
+//! cannot drain the shared global bucket.
+//!
+//! Rejected requests get a 429 in the dialect of the endpoint they called,
+//! with `Retry-After`. Admitted requests get `x-ratelimit-*` headers for the
+//! most constrained scope; these replace the upstream provider's headers of
+//! the same name. Store failures never block traffic: the limiter fails open.
+
+use crate::auth::{AuthenticatedKey, listener_for_path};
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt;
+use lunaroute_storage::{StateStore, StorageError, StorageResult};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Prefix of every rate-limit key in the state store
+pub const RATE_LIMIT_PREFIX: &str = "ratelimit:";
+
+/// How long a stream slot survives if its replica dies without releasing it
+const STREAM_SLOT_TTL: Duration = Duration::from_secs(3600);
+
+/// Compare-and-swap attempts before a contended bucket fails open
+const CAS_ATTEMPTS: usize = 5;
+
+/// Rough bytes-per-token ratio of JSON request bodies
+const BYTES_PER_TOKEN: usize = 4;
+
+/// Limits for one scope; unset limits are not enforced
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Limits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    /// Estimated from the request body size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens_per_minute: Option<u32>,
+    /// Streaming requests (`"stream": true`) in flight at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrent_streams: Option<u32>,
+}
+
+impl Limits {
+    pub fn is_empty(&self) -> bool {
+        self.requests_per_minute.is_none()
+            && self.input_tokens_per_minute.is_none()
+            && self.concurrent_streams.is_none()
+    }
+}
+
+/// Rate limit configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Enforce the limits below (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Shared by all traffic
+    #[serde(default)]
+    pub global: Limits,
+    /// Per ingress API key; only applies when `auth` is enabled
+    #[serde(default)]
+    pub per_key: Limits,
+    /// Per client IP address
+    #[serde(default)]
+    pub per_ip: Limits,
+    /// Take the client IP from the first `X-Forwarded-For` entry. Only enable
+    /// behind a proxy that sets the header, or clients can pick their own IP.
+    #[serde(default)]
+    pub trust_forwarded_for: bool,
+}
+
+impl RateLimitConfig {
+    /// Whether any limit is configured
+    pub fn is_active(&self) -> bool {
+        self.enabled
+            && !(self.global.is_empty() && self.per_key.is_empty() && self.per_ip.is_empty())
+    }
+}
+
+/// Persisted bucket state
+#[derive(Debug, Serialize, Deserialize)]
+struct Bucket {
+    tokens: f64,
+    updated_ms: i64,
+}
+
+/// Result of taking from one bucket
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Take {
+    pub(crate) allowed: bool,
+    pub(crate) limit: u32,
+    pub(crate) remaining: u32,
+    /// Until the request would be admitted (zero when allowed)
+    pub(crate) retry_after: Duration,
+    /// Until the bucket is full again
+    pub(crate) reset: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Requests,
+    Tokens,
+}
+
+impl Kind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Kind::Requests => "requests",
+            Kind::Tokens => "tokens",
+        }
+    }
+}
+
+/// Token-bucket rate limiter backed by a [`StateStore`]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    store: Arc<dyn StateStore>,
+    /// Largest body buffered to estimate tokens or detect streaming
+    max_body_bytes: usize,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, store: Arc<dyn StateStore>, max_body_bytes: usize) -> Self {
+        Self {
+            config,
+            store,
+            max_body_bytes,
+        }
+    }
+
+    /// Scopes that apply to a request, with their limits (global last)
+    fn scopes<'a>(&'a self, key: Option<&str>, ip: Option<IpAddr>) -> Vec<(String, &'a Limits)> {
+        let mut scopes = Vec::new();
+        if let Some(key) = key
+            && !self.config.per_key.is_empty()
+        {
+            scopes.push((format!("key:{}", key), &self.config.per_key));
+        }
+        if let Some(ip) = ip
+            && !self.config.per_ip.is_empty()
+        {
+            scopes.push((format!("ip:{}", ip), &self.config.per_ip));
+        }
+        if !self.config.global.is_empty() {
+            scopes.push(("global".to_string(), &self.config.global));
+        }
+        scopes
+    }
+
+    async fn take(&self, bucket: &str, per_minute: u32, cost: u64) -> StorageResult<Take> {
+        take_tokens(self.store.as_ref(), bucket, per_minute, cost).await
+    }
+
+    /// Return what a rejected request took from earlier buckets
+    async fn refund(&self, charged: &[(String, u32, u64)]) {
+        for (bucket, per_minute, cost) in charged {
+            if let Err(e) = refund_tokens(self.store.as_ref(), bucket, *per_minute, *cost).await {
+                tracing::warn!("Rate limit refund failed for {}: {}", bucket, e);
+            }
+        }
+    }
+
+    /// Claim a stream slot in every scope, or none if any scope is full
+    async fn acquire_streams(
+        &self,
+        scopes: &[(String, &Limits)],
+    ) -> StorageResult<Result<StreamSlots, String>> {
+        let mut slots = StreamSlots {
+            store: self.store.clone(),
+            keys: Vec::new(),
+        };
+        for (scope, limits) in scopes {
+            let Some(max) = limits.concurrent_streams else {
+                continue;
+            };
+            // '/' never appears in a scope, so one scope's prefix can't match another's
+            let prefix = format!("{}stream:{}/", RATE_LIMIT_PREFIX, scope);
+            let key = format!("{}{}", prefix, uuid::Uuid::new_v4());
+            // Claim first, then count, so two racing requests can't both fit in the last slot
+            self.store
+                .set_with_ttl(&key, Vec::new(), STREAM_SLOT_TTL)
+                .await?;
+            slots.keys.push(key);
+            if self.store.list_keys(&prefix).await?.len() > max as usize {
+                return Ok(Err(scope.clone()));
+            }
+        }
+        Ok(Ok(slots))
+    }
+}
+
+/// Take `cost` from a bucket holding `per_minute` tokens
+pub(crate) async fn take_tokens(
+    store: &dyn StateStore,
+    bucket: &str,
+    per_minute: u32,
+    cost: u64,
+) -> StorageResult<Take> {
+    let key = format!("{}{}", RATE_LIMIT_PREFIX, bucket);
+    // A zero limit would never refill; treat it as the smallest real one
+    let per_minute = per_minute.max(1);
+    let capacity = per_minute as f64;
+    let rate = capacity / 60.0;
+    // A request larger than the bucket could never pass; charge a full bucket
+    let cost = (cost as f64).min(capacity);
+
+    for _ in 0..CAS_ATTEMPTS {
+        let current = store.get(&key).await?;
+        let now = chrono::Utc::now().timestamp_millis();
+        let tokens = current
+            .as_deref()
+            .and_then(|raw| serde_json::from_slice::<Bucket>(raw).ok())
+            .map(|b| {
+                let elapsed = (now - b.updated_ms).max(0) as f64 / 1000.0;
+                (b.tokens + elapsed * rate).min(capacity)
+            })
+            .unwrap_or(capacity);
+
+        if tokens < cost {
+            return Ok(Take {
+                allowed: false,
+                limit: per_minute,
+                remaining: tokens as u32,
+                retry_after: Duration::from_secs_f64((cost - tokens) / rate),
+                reset: Duration::from_secs_f64((capacity - tokens) / rate),
+            });
+        }
+
+        let left = tokens - cost;
+        let reset = Duration::from_secs_f64((capacity - left) / rate);
+        let value = serde_json::to_vec(&Bucket {
+            tokens: left,
+            updated_ms: now,
+        })
+        .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        // A full bucket is the same as no bucket, so it can expire once refilled
+        let ttl = reset.max(Duration::from_secs(1));
+        if store
+            .compare_and_swap(&key, current.as_deref(), value, Some(ttl))
+            .await?
+        {
+            return Ok(Take {
+                allowed: true,
+                limit: per_minute,
+                remaining: left as u32,
+                retry_after: Duration::ZERO,
+                reset,
+            });
+        }
+    }
+
+    tracing::warn!(
+        "Rate limit bucket '{}' is contended; admitting request",
+        bucket
+    );
+    Ok(Take {
+        allowed: true,
+        limit: per_minute,
+        remaining: 0,
+        retry_after: Duration::ZERO,
+        reset: Duration::ZERO,
+    })
+}
+
+/// Give `cost` back to a bucket after the request that paid it was rejected
+pub(crate) async fn refund_tokens(
+    store: &dyn StateStore,
+    bucket: &str,
+    per_minute: u32,
+    cost: u64,
+) -> StorageResult<()> {
+    let key = format!("{}{}", RATE_LIMIT_PREFIX, bucket);
+    let per_minute = per_minute.max(1);
+    let capacity = per_minute as f64;
+    let rate = capacity / 60.0;
+    let cost = (cost as f64).min(capacity);
+
+    for _ in 0..CAS_ATTEMPTS {
+        // A missing bucket is already full
+        let Some(current) = store.get(&key).await? else {
+            return Ok(());
+        };
+        let Ok(bucket) = serde_json::from_slice::<Bucket>(&current) else {
+            return Ok(());
+        };
+        let now = chrono::Utc::now().timestamp_millis();
+        let elapsed = (now - bucket.updated_ms).max(0) as f64 / 1000.0;
+        let tokens = (bucket.tokens + elapsed * rate + cost).min(capacity);
+        let reset = Duration::from_secs_f64((capacity - tokens) / rate);
+        let value = serde_json::to_vec(&Bucket {
+            tokens,
+            updated_ms: now,
+        })
+        .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        if store
+            .compare_and_swap(
+                &key,
+                Some(&current),
+                value,
+                Some(reset.max(Duration::from_secs(1))),
+            )
+            .await?
+        {
+            return Ok(());
+        }
+    }
+
+    tracing::warn!(
+        "Rate limit bucket '{}' is contended; refund dropped",
+        bucket
+    );
+    Ok(())
+}
+
+/// Stream slots held until the response body is dropped
+struct StreamSlots {
+    store: Arc<dyn StateStore>,
+    keys: Vec<String>,
+}
+
+impl Drop for StreamSlots {
+    fn drop(&mut self) {
+        if self.keys.is_empty() {
+            return;
+        }
+        let store = self.store.clone();
+        let keys = std::mem::take(&mut self.keys);
+        tokio::spawn(async move {
+            for key in keys {
+                if let Err(e) = store.delete(&key).await {
+                    tracing::warn!("Failed to release stream slot {}: {}", key, e);
+                }
+            }
+        });
+    }
+}
+
+/// Client IP, from the connection or a trusted `X-Forwarded-For`
+fn client_ip(req: &Request, trust_forwarded_for: bool) -> Option<IpAddr> {
+    if trust_forwarded_for
+        && let Some(ip) = req
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .and_then(|ip| ip.trim().parse().ok())
+    {
+        return Some(ip);
+    }
+    req.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// 429 response in the dialect of the endpoint that was called
+///
+/// `openai_type` and `openai_code` fill the OpenAI error object; Anthropic
+/// endpoints always get a `rate_limit_error`.
+pub(crate) fn too_many_requests(
+    path: &str,
+    message: String,
+    openai_type: &str,
+    openai_code: &str,
+    retry_after: Duration,
+) -> Response {
+    let body = match listener_for_path(path) {
+        Some("anthropic") => serde_json::json!({
+            "type": "error",
+            "error": {"type": "rate_limit_error", "message": message}
+        }),
+        _ => serde_json::json!({
+            "error": {
+                "message": message,
+                "type": openai_type,
+                "param": null,
+                "code": openai_code
+            }
+        }),
+    };
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, axum::Json(body)).into_response();
+    // Round up so a client honouring the header never retries early
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs.max(1)));
+    response
+}
+
+fn rate_limited(path: &str, scope: &str, kind: &str, retry_after: Duration) -> Response {
+    let message = format!("Rate limit exceeded ({} {}); retry later", scope, kind);
+    too_many_requests(path, message, kind, "rate_limit_exceeded", retry_after)
+}
+
+/// Add `x-ratelimit-*` headers for the most constrained bucket of a kind
+fn insert_headers(headers: &mut HeaderMap, kind: Kind, take: &Take) {
+    let kind = kind.as_str();
+    let mut set = |name: String, value: String| {
+        if let (Ok(name), Ok(value)) = (
+            header::HeaderName::try_from(name),
+            HeaderValue::try_from(value),
+        ) {
+            headers.insert(name, value);
+        }
+    };
+    set(
+        format!("x-ratelimit-limit-{}", kind),
+        take.limit.to_string(),
+    );
+    set(
+        format!("x-ratelimit-remaining-{}", kind),
+        take.remaining.to_string(),
+    );
+    set(
+        format!("x-ratelimit-reset-{}", kind),
+        format!("{}s", take.reset.as_secs()),
+    );
+}
+
+/// Rate-limit middleware; runs inside the API key middleware so per-key
+/// limits see the authenticated key
+pub async fn rate_limit_middleware(
+    State(limiter): State<Arc<RateLimiter>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let key = req
+        .extensions()
+        .get::<AuthenticatedKey>()
+        .map(|AuthenticatedKey(key)| key.id.clone());
+    let ip = client_ip(&req, limiter.config.trust_forwarded_for);
+    let scopes = limiter.scopes(key.as_deref(), ip);
+    if scopes.is_empty() {
+        return next.run(req).await;
+    }
+
+    let path = req.uri().path().to_string();
+    let (parts, body) = req.into_parts();
+
+    // Token estimates and stream detection need the body
+    let needs_body = parts.method == Method::POST
+        && scopes
+            .iter()
+            .any(|(_, l)| l.input_tokens_per_minute.is_some() || l.concurrent_streams.is_some());
+    let (body, input_tokens, streaming) = if needs_body {
+        let bytes = match axum::body::to_bytes(body, limiter.max_body_bytes).await {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                return crate::types::IngressError::RequestTooLarge(limiter.max_body_bytes)
+                    .into_response();
+            }
+        };
+        let streaming = serde_json::from_slice::<serde_json::Value>(&bytes)
+            .ok()
+            .and_then(|v| v.get("stream").and_then(|s| s.as_bool()))
+            .unwrap_or(false);
+        let tokens = (bytes.len() / BYTES_PER_TOKEN) as u64;
+        (Body::from(bytes), tokens, streaming)
+    } else {
+        (body, 0, false)
+    };
+
+    // Most constrained admitted bucket per kind, for the response headers
+    let mut tightest: [Option<Take>; 2] = [None, None];
+    // Buckets charged so far, refunded if a later bucket rejects the request
+    let mut charged: Vec<(String, u32, u64)> = Vec::new();
+    for (scope, limits) in &scopes {
+        let buckets = [
+            (Kind::Requests, limits.requests_per_minute, 1),
+            (Kind::Tokens, limits.input_tokens_per_minute, input_tokens),
+        ];
+        for (kind, limit, cost) in buckets {
+            let Some(limit) = limit else { continue };
+            if kind == Kind::Tokens && !needs_body {
+                continue;
+            }
+            let bucket = format!("{}:{}", kind.as_str(), scope);
+            match limiter.take(&bucket, limit, cost).await {
+                Ok(take) if !take.allowed => {
+                    tracing::debug!("Rate limited by {} ({})", scope, kind.as_str());
+                    limiter.refund(&charged).await;
+                    let mut response = rate_limited(&path, scope, kind.as_str(), take.retry_after);
+                    insert_headers(response.headers_mut(), kind, &take);
+                    return response;
+                }
+                Ok(take) => {
+                    charged.push((bucket, limit, cost));
+                    let slot = &mut tightest[kind as usize];
+                    if slot.is_none_or(|t| take.remaining < t.remaining) {
+                        *slot = Some(take);
+                    }
+                }
+                Err(e) => tracing::warn!("Rate limit check failed for {}: {}", bucket, e),
+            }
+        }
+    }
+
+    let slots = if streaming {
+        match limiter.acquire_streams(&scopes).await {
+            Ok(Ok(slots)) => Some(slots),
+            Ok(Err(scope)) => {
+                limiter.refund(&charged).await;
+                let message = format!(
+                    "Rate limit exceeded ({} concurrent streams); retry later",
+                    scope
+                );
+                return too_many_requests(
+                    &path,
+                    message,
+                    "rate_limit_exceeded",
+                    "rate_limit_exceeded",
+                    Duration::from_secs(1),
+                );
+            }
+            Err(e) => {
+                tracing::warn!("Stream slot check failed: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut response = next.run(Request::from_parts(parts, body)).await;
+    for (kind, take) in [Kind::Requests, Kind::Tokens].into_iter().zip(tightest) {
+        if let Some(take) = take {
+            insert_headers(response.headers_mut(), kind, &take);
+        }
+    }
+
+    // Hold the stream slots until the response body is finished or dropped
+    match slots {
+        Some(slots) => {
+            let (parts, body) = response.into_parts();
+            let body = body.into_data_stream().map(move |chunk| {
+                let _ = &slots;
+                chunk
+            });
+            Response::from_parts(parts, Body::from_stream(body))
+        }
+        None => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, routing::post};
+    use lunaroute_storage::FileStateStore;
+    use tower::ServiceExt;
+
+    async fn limiter(config: RateLimitConfig) -> (tempfile::TempDir, Arc<RateLimiter>) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStateStore::new(dir.path().join("state.json"))
+            .await
+            .unwrap();
+        (
+            dir,
+            Arc::new(RateLimiter::new(config, Arc::new(store), 1024 * 1024)),
+        )
+    }
+
+    fn app(limiter: Arc<RateLimiter>) -> Router {
+        let handler = || async { "ok" };
+        Router::new()
+            .route("/v1/messages", post(handler))
+            .route("/v1/chat/completions", post(handler))
+            .layer(axum::middleware::from_fn_with_state(
+                limiter,
+                rate_limit_middleware,
+            ))
+    }
+
+    fn request(path: &str, body: &str) -> Request {
+        Request::builder()
+            .method("POST")
+            .uri(path)
+            .header("x-forwarded-for", "203.0.113.9")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_bucket_refill_and_retry_after() {
+        let (_dir, limiter) = limiter(RateLimitConfig::default()).await;
+
+        for remaining in [1, 0] {
+            let take = limiter.take("requests:global", 2, 1).await.unwrap();
+            assert!(take.allowed);
+            assert_eq!(take.remaining, remaining);
+        }
+        let take = limiter.take("requests:global", 2, 1).await.unwrap();
+        assert!(!take.allowed);
+        // 2/min refills one request every 30s
+        assert!(take.retry_after > Duration::from_secs(29));
+        assert!(take.retry_after <= Duration::from_secs(30));
+
+        // Oversized costs are charged a full bucket instead of never passing
+        let take = limiter.take("tokens:global", 100, 5000).await.unwrap();
+        assert!(take.allowed);
+        assert_eq!(take.remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dialect_429_and_headers() {
+        let config = RateLimitConfig {
+            enabled: true,
+            per_ip: Limits {
+                requests_per_minute: Some(1),
+                ..Default::default()
+            },
+            trust_forwarded_for: true,
+            ..Default::default()
+        };
+        assert!(config.is_active());
+        let (_dir, limiter) = limiter(config).await;
+
+        let ok = app(limiter.clone())
+            .oneshot(request("/v1/messages", "{}"))
+            .await
+            .unwrap();
+        assert_eq!(ok.status(), StatusCode::OK);
+        assert_eq!(ok.headers()["x-ratelimit-limit-requests"], "1");
+        assert_eq!(ok.headers()["x-ratelimit-remaining-requests"], "0");
+        assert_eq!(ok.headers()["x-ratelimit-reset-requests"], "60s");
+
+        let limited = app(limiter.clone())
+            .oneshot(request("/v1/messages", "{}"))
+            .await
+            .unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "60");
+        let body = axum::body::to_bytes(limited.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["type"], "error");
+        assert_eq!(body["error"]["type"], "rate_limit_error");
+
+        let limited = app(limiter)
+            .oneshot(request("/v1/chat/completions", "{}"))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(limited.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "rate_limit_exceeded");
+    }
+
+    #[tokio::test]
+    async fn test_rejected_requests_do_not_drain_global() {
+        let config = RateLimitConfig {
+            enabled: true,
+            global: Limits {
+                requests_per_minute: Some(3),
+                ..Default::default()
+            },
+            per_ip: Limits {
+                requests_per_minute: Some(2),
+                input_tokens_per_minute: Some(10),
+                ..Default::default()
+            },
+            trust_forwarded_for: true,
+            ..Default::default()
+        };
+        let (_dir, limiter) = limiter(config).await;
+        let send = |body: &'static str| {
+            app(limiter.clone()).oneshot(request("/v1/chat/completions", body))
+        };
+        // 32 bytes, estimated at 8 tokens
+        let body = r#"{"model":"gpt-5","input":"abcd"}"#;
+
+        assert_eq!(send(body).await.unwrap().status(), StatusCode::OK);
+        // Out of tokens: the IP's requests bucket keeps no charge
+        assert_eq!(
+            send(body).await.unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(send("{}").await.unwrap().status(), StatusCode::OK);
+
+        // The IP is now out of requests; retries never touch the global bucket
+        for _ in 0..5 {
+            assert_eq!(
+                send("{}").await.unwrap().status(),
+                StatusCode::TOO_MANY_REQUESTS
+            );
+        }
+        let take = limiter.take("requests:global", 3, 1).await.unwrap();
+        assert!(take.allowed);
+        assert_eq!(take.remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_stream_slots() {
+        let config = RateLimitConfig {
+            enabled: true,
+            global: Limits {
+                concurrent_streams: Some(1),
+                input_tokens_per_minute: Some(1000),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (_dir, limiter) = limiter(config).await;
+        let stream = r#"{"model":"gpt-5","stream":true}"#;
+
+        let first = app(limiter.clone())
+            .oneshot(request("/v1/chat/completions", stream))
+            .await
+            .unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(first.headers()["x-ratelimit-limit-tokens"], "1000");
+
+        // The first response body still holds the only slot
+        let second = app(limiter.clone())
+            .oneshot(request("/v1/chat/completions", stream))
+            .await
+            .unwrap();
+        assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+        let body = axum::body::to_bytes(second.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["type"], "rate_limit_exceeded");
+        assert!(
+            body["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("concurrent streams")
+        );
+        // Non-streaming requests don't need a slot
+        let plain = app(limiter.clone())
+            .oneshot(request("/v1/chat/completions", "{}"))
+            .await
+            .unwrap();
+        assert_eq!(plain.status(), StatusCode::OK);
+
+        axum::body::to_bytes(first.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let third = app(limiter)
+            .oneshot(request("/v1/chat/completions", stream))
+            .await
+            .unwrap();
+        assert_eq!(third.status(), StatusCode::OK);
+    }
+}
@@ -7,13 +7,14 @@
 //!
 //! See `docs/superpowers/specs/2026-04-16-codex-websocket-responses-design.md`.
 
-use axum::extract::State;
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Extension, State};
 use axum::http::HeaderMap;
-use axum::response::Response;
+use axum::response::{IntoResponse, Response};
 use futures::StreamExt as _;
 use std::sync::Arc;
 
+use crate::auth::{AuthenticatedKey, ClientIdentity, client_identity_subject};
 use crate::openai::{OpenAIPassthroughState, SseEvent, responses_sse_stream};
 
 /// Parsed client-to-server WebSocket frame.
@@ -153,9 +154,26 @@ pub async fn responses_ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<OpenAIPassthroughState>>,
     headers: HeaderMap,
+    auth_key: Option<Extension<AuthenticatedKey>>,
+    identity: Option<Extension<ClientIdentity>>,
+    tenant: Option<Extension<Arc<lunaroute_core::tenant::TenantContext>>>,
 ) -> Response {
     tracing::debug!("Responses API WebSocket upgrade");
-    ws.on_upgrade(move |socket| run_ws_session(socket, state, headers))
+    // Like the HTTP path, the socket always goes to the default OpenAI provider
+    let auth_key = auth_key.map(|Extension(key)| key);
+    if let Err(e) = crate::auth::authorize_provider(auth_key.as_ref(), "openai") {
+        return e.into_response();
+    }
+    // Record into the request's tenant
+    let state = match crate::tenant::tenant_session_store(tenant.as_ref()) {
+        Some(store) => Arc::new(OpenAIPassthroughState {
+            session_store: Some(store),
+            ..(*state).clone()
+        }),
+        None => state,
+    };
+    let client_identity = client_identity_subject(identity.as_ref().map(|Extension(i)| i));
+    ws.on_upgrade(move |socket| run_ws_session(socket, state, headers, client_identity, auth_key))
 }
 
 /// Own the socket for a single WebSocket connection. Reads client frames,
@@ -166,6 +184,8 @@ async fn run_ws_session(
     mut socket: WebSocket,
     state: Arc<OpenAIPassthroughState>,
     upgrade_headers: HeaderMap,
+    client_identity: Option<String>,
+    auth_key: Option<AuthenticatedKey>,
 ) {
     const ENDPOINT: &str = "responses";
     let started = std::time::Instant::now();
@@ -191,8 +211,15 @@ async fn run_ws_session(
         // since they aren't useful signals for application dashboards.
         match msg {
             Message::Text(text) => {
-                if let Err(e) =
-                    handle_client_text(&mut socket, &state, &upgrade_headers, text.as_ref()).await
+                if let Err(e) = handle_client_text(
+                    &mut socket,
+                    &state,
+                    &upgrade_headers,
+                    client_identity.as_deref(),
+                    auth_key.as_ref(),
+                    text.as_ref(),
+                )
+                .await
                 {
                     tracing::warn!("WS text handling error: {e}");
                     // Connection stays open unless the error indicates a send failure
@@ -232,6 +259,8 @@ async fn handle_client_text(
     socket: &mut WebSocket,
     state: &Arc<OpenAIPassthroughState>,
     upgrade_headers: &HeaderMap,
+    client_identity: Option<&str>,
+    auth_key: Option<&AuthenticatedKey>,
     text: &str,
 ) -> Result<(), axum::Error> {
     const ENDPOINT: &str = "responses";
@@ -292,6 +321,11 @@ async fn handle_client_text(
                     .send(Message::Text(warmup_completed_frame().into()))
                     .await;
             }
+            // Each frame names its own model, so model scopes apply per frame
+            let model = response.get("model").and_then(|m| m.as_str());
+            if let Err(e) = crate::auth::authorize_model(auth_key, model.unwrap_or_default()) {
+                return send_error(socket, state, "forbidden", &e.to_string()).await;
+            }
             // Force stream=true unconditionally: the WebSocket transport is
             // inherently streaming; a client-supplied {"stream": false} would
             // break the pipeline.
@@ -312,7 +346,14 @@ async fn handle_client_text(
                 axum::http::HeaderValue::from_static("application/json"),
             );
 
-            let stream = match responses_sse_stream(state.clone(), ws_headers, body_bytes).await {
+            let stream = match responses_sse_stream(
+                state.clone(),
+                ws_headers,
+                body_bytes,
+                client_identity.map(str::to_string),
+            )
+            .await
+            {
                 Ok(s) => s,
                 Err(e) => {
                     return send_error(socket, state, "upstream_error", &e.to_string()).await;
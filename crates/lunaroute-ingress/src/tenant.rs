@@ -0,0 +1,725 @@
+//! Tenant resolution for multi-tenant mode
+//!
+//! Every request served from a multi-tenant config store belongs to a
+//! tenant. [`tenant_middleware`] works out which one from the request and
+//! attaches a [`TenantContext`] holding the tenant's configuration and a
+//! session store scoped to it.
+//!
+//! ```yaml
+//! tenancy:
+//!   sources: [api_key, jwt, header, subdomain]
+//!   header: x-lunaroute-tenant
+//!   base_domain: lunaroute.example.com
+//! ```
+//!
+//! A tenant can come from:
+//! - `api_key`: the tenant of the ingress API key (see [`crate::auth`])
+//! - `jwt`: the tenant claim of a validated JWT (see [`crate::jwt`])
+//! - `header`: a request header
+//! - `subdomain`: the host's label below `base_domain`
+//!   (`acme.lunaroute.example.com`)
+//!
+//! Each gives a tenant ID or a tenant name, which is looked up in the config
+//! store. Every source that is present must name the same tenant, so a
+//! client cannot use the header to leave the tenant of its credentials.
+//! When auth is enabled the credential itself must name a tenant: keys and
+//! JWTs without one are refused rather than left to pick any tenant through
+//! the header or subdomain, unless `allow_unbound_credentials` is set.
+//! Names and configurations are cached for `cache_ttl_secs`, including
+//! names that do not exist.
+
+use crate::auth::{AuthenticatedKey, ClientIdentity, IdentitySource};
+use crate::types::IngressError;
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use lunaroute_core::config_store::ConfigStore;
+use lunaroute_core::session_store::SessionStore;
+use lunaroute_core::tenant::{TenantContext, TenantId, TenantStatus, subdomain_of};
+use lunaroute_session::TenantScopedStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default header naming the tenant
+pub const TENANT_HEADER: &str = "x-lunaroute-tenant";
+
+/// Cached names beyond which expired entries are dropped
+const MAX_CACHED_NAMES: usize = 10_000;
+
+/// Where a request's tenant can come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TenantSource {
+    /// Tenant of the authenticated ingress API key
+    ApiKey,
+    /// Tenant claim of the validated JWT
+    Jwt,
+    /// Request header (`header`)
+    Header,
+    /// Host label below `base_domain`
+    Subdomain,
+}
+
+impl TenantSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TenantSource::ApiKey => "api_key",
+            TenantSource::Jwt => "jwt",
+            TenantSource::Header => "header",
+            TenantSource::Subdomain => "subdomain",
+        }
+    }
+}
+
+/// Tenant resolution settings (multi-tenant mode only)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenancyConfig {
+    /// Sources consulted for each request (default: all of them)
+    #[serde(default = "default_sources")]
+    pub sources: Vec<TenantSource>,
+
+    /// Header naming the tenant (default: x-lunaroute-tenant)
+    #[serde(default = "default_header")]
+    pub header: String,
+
+    /// Domain whose subdomains name tenants; the `subdomain` source is
+    /// skipped when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_domain: Option<String>,
+
+    /// How long tenant names and configurations are cached (default: 60)
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+
+    /// How long a tenant's providers and router are kept without requests
+    /// (default: 900)
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+
+    /// Let authenticated requests whose key or JWT names no tenant choose
+    /// one through the header or subdomain (default: false)
+    #[serde(default)]
+    pub allow_unbound_credentials: bool,
+}
+
+fn default_sources() -> Vec<TenantSource> {
+    vec![
+        TenantSource::ApiKey,
+        TenantSource::Jwt,
+        TenantSource::Header,
+        TenantSource::Subdomain,
+    ]
+}
+
+fn default_header() -> String {
+    TENANT_HEADER.to_string()
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    60
+}
+
+fn default_idle_timeout_secs() -> u64 {
+    900
+}
+
+impl Default for TenancyConfig {
+    fn default() -> Self {
+        Self {
+            sources: default_sources(),
+            header: default_header(),
+            base_domain: None,
+            cache_ttl_secs: default_cache_ttl_secs(),
+            idle_timeout_secs: default_idle_timeout_secs(),
+            allow_unbound_credentials: false,
+        }
+    }
+}
+
+/// Resolves tenants and builds their contexts, with caching
+pub struct TenantResolver {
+    config: TenancyConfig,
+    header: header::HeaderName,
+    config_store: Arc<dyn ConfigStore>,
+    session_store: Option<Arc<dyn SessionStore>>,
+    names: Mutex<HashMap<String, (Option<TenantId>, Instant)>>,
+    contexts: Mutex<HashMap<TenantId, (Arc<TenantContext>, Instant)>>,
+    /// Requests carry a credential, which must name the tenant
+    auth_enabled: bool,
+}
+
+impl TenantResolver {
+    /// Create a resolver; recordings go to `session_store` scoped per tenant
+    pub fn new(
+        config: TenancyConfig,
+        config_store: Arc<dyn ConfigStore>,
+        session_store: Option<Arc<dyn SessionStore>>,
+    ) -> Result<Self, String> {
+        let header = header::HeaderName::from_bytes(config.header.as_bytes())
+            .map_err(|e| format!("invalid tenancy.header '{}': {}", config.header, e))?;
+        Ok(Self {
+            config,
+            header,
+            config_store,
+            session_store,
+            names: Mutex::new(HashMap::new()),
+            contexts: Mutex::new(HashMap::new()),
+            auth_enabled: false,
+        })
+    }
+
+    /// Require the tenant to come from the request's credential
+    pub fn with_auth(mut self, enabled: bool) -> Self {
+        self.auth_enabled = enabled;
+        self
+    }
+
+    /// Store the tenants' configurations are loaded from
+    pub fn config_store(&self) -> &Arc<dyn ConfigStore> {
+        &self.config_store
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::from_secs(self.config.cache_ttl_secs)
+    }
+
+    /// Tenant a reference (ID or name) points to, if it exists
+    pub async fn resolve(&self, reference: &str) -> lunaroute_core::Result<Option<TenantId>> {
+        if let Ok(id) = TenantId::from_string(reference) {
+            return Ok(Some(id));
+        }
+        if let Some((id, at)) = self.names.lock().unwrap().get(reference)
+            && at.elapsed() < self.ttl()
+        {
+            return Ok(*id);
+        }
+
+        let id = self.config_store.lookup_tenant(reference).await?;
+        let mut names = self.names.lock().unwrap();
+        if names.len() >= MAX_CACHED_NAMES {
+            let ttl = self.ttl();
+            names.retain(|_, (_, at)| at.elapsed() < ttl);
+            if names.len() >= MAX_CACHED_NAMES {
+                names.clear();
+            }
+        }
+        names.insert(reference.to_string(), (id, Instant::now()));
+        Ok(id)
+    }
+
+    /// Context of a tenant, loading its configuration on a cache miss
+    ///
+    /// # Errors
+    /// - `Error::TenantSuspended` if the tenant is suspended
+    /// - `Error::ConfigNotFound` if the tenant has no configuration
+    pub async fn context(&self, tenant_id: TenantId) -> lunaroute_core::Result<Arc<TenantContext>> {
+        if let Some((context, at)) = self.contexts.lock().unwrap().get(&tenant_id)
+            && at.elapsed() < self.ttl()
+        {
+            return Ok(context.clone());
+        }
+
+        if let Some(tenant) = self.config_store.get_tenant(tenant_id).await?
+            && tenant.status == TenantStatus::Suspended
+        {
+            return Err(lunaroute_core::Error::TenantSuspended(tenant.name));
+        }
+        let config = self.config_store.get_config(Some(tenant_id)).await?;
+        let context = Arc::new(TenantContext {
+            tenant_id: Some(tenant_id),
+            config: Arc::new(config),
+            config_store: self.config_store.clone(),
+            session_store: self.session_store.clone().map(|store| {
+                Arc::new(TenantScopedStore::new(store, Some(tenant_id))) as Arc<dyn SessionStore>
+            }),
+        });
+        let ttl = self.ttl();
+        let mut contexts = self.contexts.lock().unwrap();
+        contexts.retain(|_, (_, at)| at.elapsed() < ttl);
+        contexts.insert(tenant_id, (context.clone(), Instant::now()));
+        Ok(context)
+    }
+
+    /// Drop a tenant's cached context so the next request reloads it
+    pub fn invalidate(&self, tenant_id: TenantId) {
+        self.contexts.lock().unwrap().remove(&tenant_id);
+    }
+
+    /// Drop a cached name lookup, such as a miss for a newly created tenant
+    pub fn forget_name(&self, name: &str) {
+        self.names.lock().unwrap().remove(name);
+    }
+
+    /// Whether the request's tenant may come only from the header or
+    /// subdomain
+    fn allows_unbound(&self) -> bool {
+        !self.auth_enabled || self.config.allow_unbound_credentials
+    }
+
+    /// Tenant references the request carries, per configured source
+    fn references(&self, req: &Request) -> Vec<(TenantSource, String)> {
+        self.config
+            .sources
+            .iter()
+            .filter_map(|&source| {
+                let reference = match source {
+                    TenantSource::ApiKey => req
+                        .extensions()
+                        .get::<AuthenticatedKey>()
+                        .and_then(|key| key.0.tenant.clone()),
+                    TenantSource::Jwt => req
+                        .extensions()
+                        .get::<ClientIdentity>()
+                        .filter(|identity| identity.source == IdentitySource::Jwt)
+                        .and_then(|identity| identity.tenant.clone()),
+                    TenantSource::Header => req
+                        .headers()
+                        .get(&self.header)
+                        .and_then(|v| v.to_str().ok())
+                        .map(|v| v.trim().to_string()),
+                    TenantSource::Subdomain => {
+                        let base_domain = self.config.base_domain.as_deref()?;
+                        let host = req.uri().host().or_else(|| {
+                            req.headers()
+                                .get(header::HOST)
+                                .and_then(|v| v.to_str().ok())
+                        })?;
+                        subdomain_of(host, base_domain).map(str::to_string)
+                    }
+                };
+                reference
+                    .filter(|r| !r.is_empty())
+                    .map(|reference| (source, reference))
+            })
+            .collect()
+    }
+}
+
+/// Resolve the request's tenant and attach its [`TenantContext`]
+///
+/// Runs after authentication so key and JWT tenants are known. Requests
+/// without a tenant get a 400; unknown and suspended tenants, conflicting
+/// sources and (with auth enabled) credentials naming no tenant get a 403.
+pub async fn tenant_middleware(
+    State(resolver): State<Arc<TenantResolver>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let references = resolver.references(&req);
+    let bound = references
+        .iter()
+        .any(|(source, _)| matches!(source, TenantSource::ApiKey | TenantSource::Jwt));
+    if !bound && !resolver.allows_unbound() {
+        return IngressError::Forbidden("Credential is not bound to a tenant".to_string())
+            .into_response();
+    }
+    if references.is_empty() {
+        return IngressError::InvalidRequest("Could not determine the tenant".to_string())
+            .into_response();
+    }
+
+    let mut tenant: Option<(TenantSource, TenantId)> = None;
+    for (source, reference) in references {
+        let id = match resolver.resolve(&reference).await {
+            Ok(Some(id)) => id,
+            Ok(None) => {
+                return IngressError::Forbidden(format!("Unknown tenant '{}'", reference))
+                    .into_response();
+            }
+            Err(e) => {
+                tracing::error!("Tenant lookup failed: {}", e);
+                return IngressError::Internal("Tenant lookup failed".to_string()).into_response();
+            }
+        };
+        match tenant {
+            Some((first, first_id)) if first_id != id => {
+                return IngressError::Forbidden(format!(
+                    "Tenant from {} does not match tenant from {}",
+                    source.as_str(),
+                    first.as_str()
+                ))
+                .into_response();
+            }
+            Some(_) => {}
+            None => tenant = Some((source, id)),
+        }
+    }
+    let Some((_, tenant_id)) = tenant else {
+        unreachable!("at least one reference was resolved");
+    };
+
+    let context = match resolver.context(tenant_id).await {
+        Ok(context) => context,
+        Err(lunaroute_core::Error::ConfigNotFound) => {
+            return IngressError::Forbidden(format!("Unknown tenant '{}'", tenant_id))
+                .into_response();
+        }
+        Err(lunaroute_core::Error::TenantSuspended(name)) => {
+            return IngressError::Forbidden(format!("Tenant '{}' is suspended", name))
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to load config for tenant {}: {}", tenant_id, e);
+            return IngressError::Internal("Failed to load tenant configuration".to_string())
+                .into_response();
+        }
+    };
+
+    req.headers_mut().remove(&resolver.header);
+    req.extensions_mut().insert(tenant_id);
+    req.extensions_mut().insert(context);
+    next.run(req).await
+}
+
+/// Session store a handler records into: the tenant's when one was resolved
+pub(crate) fn tenant_session_store(
+    tenant: Option<&axum::Extension<Arc<TenantContext>>>,
+) -> Option<Arc<dyn SessionStore>> {
+    tenant.and_then(|axum::Extension(context)| context.session_store.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{ApiKey, KeyScopes};
+    use axum::{Extension, Router, body::Body, http::StatusCode, routing::post};
+    use chrono::Utc;
+    use lunaroute_core::config_store::ConfigChangeStream;
+    use lunaroute_core::tenant::TenantInfo;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    /// Config store with named tenants that counts lookups
+    struct Tenants {
+        named: HashMap<String, TenantId>,
+        configs: HashMap<TenantId, serde_json::Value>,
+        suspended: Option<TenantId>,
+        lookups: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl ConfigStore for Tenants {
+        async fn get_config(
+            &self,
+            tenant_id: Option<TenantId>,
+        ) -> lunaroute_core::Result<serde_json::Value> {
+            tenant_id
+                .and_then(|id| self.configs.get(&id).cloned())
+                .ok_or(lunaroute_core::Error::ConfigNotFound)
+        }
+
+        async fn update_config(
+            &self,
+            _tenant_id: Option<TenantId>,
+            _config: serde_json::Value,
+        ) -> lunaroute_core::Result<()> {
+            Ok(())
+        }
+
+        async fn watch_changes(
+            &self,
+            _tenant_id: Option<TenantId>,
+        ) -> lunaroute_core::Result<ConfigChangeStream<'_>> {
+            Ok(Box::pin(futures::stream::empty()))
+        }
+
+        async fn validate_config(&self, _config: &serde_json::Value) -> lunaroute_core::Result<()> {
+            Ok(())
+        }
+
+        async fn lookup_tenant(&self, name: &str) -> lunaroute_core::Result<Option<TenantId>> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(self.named.get(name).copied())
+        }
+
+        async fn get_tenant(
+            &self,
+            tenant_id: TenantId,
+        ) -> lunaroute_core::Result<Option<TenantInfo>> {
+            Ok(self
+                .named
+                .iter()
+                .find(|(_, id)| **id == tenant_id)
+                .map(|(name, _)| TenantInfo {
+                    tenant_id,
+                    name: name.clone(),
+                    status: if self.suspended == Some(tenant_id) {
+                        TenantStatus::Suspended
+                    } else {
+                        TenantStatus::Active
+                    },
+                    created_at: Utc::now(),
+                }))
+        }
+    }
+
+    fn key(tenant: Option<&str>) -> AuthenticatedKey {
+        AuthenticatedKey(Arc::new(ApiKey {
+            id: "k".to_string(),
+            name: "k".to_string(),
+            owner: None,
+            tenant: tenant.map(str::to_string),
+            scopes: KeyScopes::default(),
+            created_at: Utc::now(),
+            expires_at: None,
+            last_used_at: None,
+            revoked_at: None,
+            rotated_to: None,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_tenant_middleware_resolves_sources() {
+        let acme = TenantId::new();
+        let globex = TenantId::new();
+        let hooli = TenantId::new();
+        let store = Arc::new(Tenants {
+            named: HashMap::from([
+                ("acme".to_string(), acme),
+                ("globex".to_string(), globex),
+                ("hooli".to_string(), hooli),
+            ]),
+            configs: HashMap::from([
+                (acme, serde_json::json!({ "name": "acme" })),
+                (hooli, serde_json::json!({ "name": "hooli" })),
+            ]),
+            suspended: Some(hooli),
+            lookups: AtomicUsize::new(0),
+        });
+        let resolver = Arc::new(
+            TenantResolver::new(
+                TenancyConfig {
+                    base_domain: Some("lunaroute.example.com".to_string()),
+                    ..Default::default()
+                },
+                store.clone(),
+                None,
+            )
+            .unwrap(),
+        );
+
+        let app = Router::new()
+            .route(
+                "/v1/messages",
+                post(
+                    |Extension(context): Extension<Arc<TenantContext>>,
+                     headers: axum::http::HeaderMap| async move {
+                        assert!(headers.get(TENANT_HEADER).is_none());
+                        context.config["name"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string()
+                    },
+                ),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                resolver,
+                tenant_middleware,
+            ));
+        let send = |host: &str, tenant: Option<&str>, key_tenant: Option<&str>| {
+            let mut req = Request::post("/v1/messages").header(header::HOST, host);
+            if let Some(tenant) = tenant {
+                req = req.header(TENANT_HEADER, tenant);
+            }
+            let mut req = req.body(Body::empty()).unwrap();
+            if let Some(key_tenant) = key_tenant {
+                req.extensions_mut().insert(key(Some(key_tenant)));
+            }
+            app.clone().oneshot(req)
+        };
+
+        let acme_id = acme.to_string();
+        let cases = [
+            ("acme.lunaroute.example.com", None, None, StatusCode::OK),
+            ("localhost", Some("acme"), None, StatusCode::OK),
+            ("localhost", Some(acme_id.as_str()), None, StatusCode::OK),
+            ("localhost", None, Some("acme"), StatusCode::OK),
+            (
+                "acme.lunaroute.example.com",
+                Some("acme"),
+                Some(acme_id.as_str()),
+                StatusCode::OK,
+            ),
+            ("localhost", None, None, StatusCode::BAD_REQUEST),
+            ("localhost", Some("initech"), None, StatusCode::FORBIDDEN),
+            // Exists by name but has no configuration
+            (
+                "globex.lunaroute.example.com",
+                None,
+                None,
+                StatusCode::FORBIDDEN,
+            ),
+            // Suspended tenants keep their configuration but are refused
+            ("localhost", Some("hooli"), None, StatusCode::FORBIDDEN),
+            // The header cannot override the key's tenant
+            (
+                "localhost",
+                Some("globex"),
+                Some("acme"),
+                StatusCode::FORBIDDEN,
+            ),
+        ];
+        for (host, tenant, key_tenant, status) in cases {
+            let response = send(host, tenant, key_tenant).await.unwrap();
+            assert_eq!(
+                response.status(),
+                status,
+                "{} {:?} {:?}",
+                host,
+                tenant,
+                key_tenant
+            );
+            if status == StatusCode::OK {
+                let body = axum::body::to_bytes(response.into_body(), 1024)
+                    .await
+                    .unwrap();
+                assert_eq!(&body[..], b"acme");
+            }
+        }
+
+        // Names, including unknown ones, are looked up once per TTL
+        let lookups = store.lookups.load(Ordering::SeqCst);
+        send("localhost", Some("initech"), None).await.unwrap();
+        send("localhost", Some("acme"), None).await.unwrap();
+        assert_eq!(store.lookups.load(Ordering::SeqCst), lookups);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_middleware_requires_bound_credential() {
+        let acme = TenantId::new();
+        let store = Arc::new(Tenants {
+            named: HashMap::from([("acme".to_string(), acme)]),
+            configs: HashMap::from([(acme, serde_json::json!({}))]),
+            suspended: None,
+            lookups: AtomicUsize::new(0),
+        });
+        let app = |config: TenancyConfig| {
+            let resolver = Arc::new(
+                TenantResolver::new(config, store.clone(), None)
+                    .unwrap()
+                    .with_auth(true),
+            );
+            Router::new()
+                .route("/v1/messages", post(|| async { "ok" }))
+                .layer(axum::middleware::from_fn_with_state(
+                    resolver,
+                    tenant_middleware,
+                ))
+        };
+        let send = |app: Router, key_tenant: Option<&str>| {
+            let mut req = Request::post("/v1/messages")
+                .header(TENANT_HEADER, "acme")
+                .body(Body::empty())
+                .unwrap();
+            req.extensions_mut().insert(key(key_tenant));
+            app.oneshot(req)
+        };
+
+        // A key without a tenant cannot choose one through the header
+        let strict = app(TenancyConfig::default());
+        let response = send(strict.clone(), None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = send(strict, Some("acme")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let opted_in = app(TenancyConfig {
+            allow_unbound_credentials: true,
+            ..Default::default()
+        });
+        let response = send(opted_in, None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_context_scopes_session_store() {
+        struct Recording(Mutex<Vec<Option<TenantId>>>);
+
+        #[async_trait::async_trait]
+        impl SessionStore for Recording {
+            async fn write_event(
+                &self,
+                tenant_id: Option<TenantId>,
+                _event: serde_json::Value,
+            ) -> lunaroute_core::Result<()> {
+                self.0.lock().unwrap().push(tenant_id);
+                Ok(())
+            }
+
+            async fn search(
+                &self,
+                _tenant_id: Option<TenantId>,
+                _query: serde_json::Value,
+            ) -> lunaroute_core::Result<serde_json::Value> {
+                Ok(serde_json::Value::Null)
+            }
+
+            async fn get_session(
+                &self,
+                _tenant_id: Option<TenantId>,
+                _session_id: &str,
+            ) -> lunaroute_core::Result<serde_json::Value> {
+                Ok(serde_json::Value::Null)
+            }
+
+            async fn cleanup(
+                &self,
+                _tenant_id: Option<TenantId>,
+                _retention: serde_json::Value,
+            ) -> lunaroute_core::Result<serde_json::Value> {
+                Ok(serde_json::Value::Null)
+            }
+
+            async fn get_stats(
+                &self,
+                _tenant_id: Option<TenantId>,
+                _time_range: serde_json::Value,
+            ) -> lunaroute_core::Result<serde_json::Value> {
+                Ok(serde_json::Value::Null)
+            }
+
+            async fn list_sessions(
+                &self,
+                _tenant_id: Option<TenantId>,
+                _limit: usize,
+                _offset: usize,
+            ) -> lunaroute_core::Result<Vec<serde_json::Value>> {
+                Ok(Vec::new())
+            }
+        }
+
+        let acme = TenantId::new();
+        let store = Arc::new(Tenants {
+            named: HashMap::new(),
+            configs: HashMap::from([(acme, serde_json::json!({}))]),
+            suspended: None,
+            lookups: AtomicUsize::new(0),
+        });
+        let sessions = Arc::new(Recording(Mutex::new(Vec::new())));
+        let resolver = TenantResolver::new(
+            TenancyConfig::default(),
+            store,
+            Some(sessions.clone() as Arc<dyn SessionStore>),
+        )
+        .unwrap();
+
+        let context = resolver.context(acme).await.unwrap();
+        assert!(Arc::ptr_eq(
+            &context,
+            &resolver.context(acme).await.unwrap()
+        ));
+        let scoped = tenant_session_store(Some(&Extension(context))).unwrap();
+        scoped
+            .write_event(None, serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(*sessions.0.lock().unwrap(), vec![Some(acme)]);
+
+        resolver.invalidate(acme);
+        assert!(resolver.contexts.lock().unwrap().is_empty());
+    }
+}
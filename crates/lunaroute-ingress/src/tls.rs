@@ -0,0 +1,620 @@
+//! TLS termination for ingress listeners
+//!
+//! [`TlsListener`] wraps a TCP listener with a rustls acceptor so axum can
+//! serve HTTPS directly. Handshakes run on their own tasks, so a slow or
+//! stalled client never holds up `accept()` for everyone else.
+//!
+//! The certificate and key are re-read when either file changes (see
+//! [`ReloadingCertResolver`]); a broken or half-written pair is logged and
+//! the previous certificate keeps serving. With `client_ca_path` set, client
+//! certificates are verified against that CA bundle and the verified
+//! certificate's subject becomes the request's [`ClientIdentity`].
+//!
+//! Serve with `into_make_service_with_connect_info::<TlsConnectInfo>()` and
+//! [`tls_connect_info_middleware`] as the outermost layer: it exposes the
+//! peer address as `ConnectInfo<SocketAddr>` (as on a plain listener) and
+//! the client identity as an extension for auth and session recording.
+
+use crate::auth::ClientIdentity;
+use axum::{
+    extract::{ConnectInfo, Request, connect_info::Connected},
+    middleware::Next,
+    response::Response,
+    serve::{IncomingStream, Listener},
+};
+use lunaroute_core::tls::{ClientCertMode, TlsSettings};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rustls::RootCertStore;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use rustls::sign::CertifiedKey;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::server::TlsStream;
+
+/// How long a client may take to complete the TLS handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Completed handshakes waiting for `accept()`
+const ACCEPT_QUEUE: usize = 128;
+
+/// Errors setting up TLS
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("Failed to read {path}: {source}")]
+    Pem {
+        path: PathBuf,
+        #[source]
+        source: rustls::pki_types::pem::Error,
+    },
+
+    #[error("No certificates found in {0}")]
+    NoCertificates(PathBuf),
+
+    #[error("Invalid certificate or key: {0}")]
+    Rustls(#[from] rustls::Error),
+
+    #[error("Invalid client CA bundle: {0}")]
+    ClientVerifier(#[from] rustls::server::VerifierBuilderError),
+
+    #[error("Failed to watch certificate files: {0}")]
+    Watch(#[from] notify::Error),
+
+    #[error("Failed to bind TLS listener: {0}")]
+    Bind(#[from] std::io::Error),
+}
+
+/// Crypto provider for every TLS listener (named explicitly because other
+/// dependencies enable a second rustls backend)
+fn crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|source| TlsError::Pem {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificates(path.to_path_buf()));
+    }
+    Ok(certs)
+}
+
+fn read_certified_key(
+    cert_path: &Path,
+    key_path: &Path,
+    provider: &CryptoProvider,
+) -> Result<CertifiedKey, TlsError> {
+    let certs = read_certs(cert_path)?;
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(|source| TlsError::Pem {
+        path: key_path.to_path_buf(),
+        source,
+    })?;
+    Ok(CertifiedKey::from_der(certs, key, provider)?)
+}
+
+/// Serves the current certificate and swaps in a new one on [`reload`]
+///
+/// [`reload`]: ReloadingCertResolver::reload
+#[derive(Debug)]
+pub struct ReloadingCertResolver {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    provider: Arc<CryptoProvider>,
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ReloadingCertResolver {
+    /// Load the certificate chain and key, failing if either is unusable
+    pub fn load(
+        cert_path: impl Into<PathBuf>,
+        key_path: impl Into<PathBuf>,
+        provider: Arc<CryptoProvider>,
+    ) -> Result<Self, TlsError> {
+        let cert_path = cert_path.into();
+        let key_path = key_path.into();
+        let key = read_certified_key(&cert_path, &key_path, &provider)?;
+        Ok(Self {
+            cert_path,
+            key_path,
+            provider,
+            current: RwLock::new(Arc::new(key)),
+        })
+    }
+
+    /// Re-read both files; on error the previous certificate stays in use
+    pub fn reload(&self) -> Result<(), TlsError> {
+        let key = read_certified_key(&self.cert_path, &self.key_path, &self.provider)?;
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(key);
+        Ok(())
+    }
+
+    /// Certificate currently handed to new connections
+    pub fn current(&self) -> Arc<CertifiedKey> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Reload whenever the certificate or key file is written or replaced
+    ///
+    /// Watches the parent directories rather than the files so renames
+    /// (how most tools rotate certificates) are seen. Dropping the returned
+    /// watcher stops reloading.
+    pub fn watch(self: &Arc<Self>) -> Result<RecommendedWatcher, TlsError> {
+        let resolver = Arc::downgrade(self);
+        let watched: Vec<PathBuf> = [&self.cert_path, &self.key_path]
+            .into_iter()
+            .map(|path| path.canonicalize().unwrap_or_else(|_| path.clone()))
+            .collect();
+        let names: Vec<_> = watched
+            .iter()
+            .filter_map(|path| path.file_name().map(|n| n.to_os_string()))
+            .collect();
+
+        let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
+            let event = match res {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::warn!("Certificate watch error: {}", e);
+                    return;
+                }
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            let relevant = event.paths.iter().any(|path| {
+                path.file_name()
+                    .is_some_and(|name| names.iter().any(|n| n == name))
+            });
+            if !relevant {
+                return;
+            }
+            let Some(resolver) = resolver.upgrade() else {
+                return;
+            };
+            match resolver.reload() {
+                Ok(()) => tracing::info!(
+                    "🔐 Reloaded TLS certificate from {}",
+                    resolver.cert_path.display()
+                ),
+                // Often just the first of two writes (cert before key)
+                Err(e) => tracing::warn!("TLS certificate reload failed, keeping previous: {}", e),
+            }
+        })?;
+
+        let mut dirs: Vec<&Path> = watched.iter().filter_map(|p| p.parent()).collect();
+        dirs.dedup();
+        for dir in dirs {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        Ok(watcher)
+    }
+}
+
+impl ResolvesServerCert for ReloadingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+/// Loaded TLS configuration for one listener
+pub struct ServerTls {
+    config: Arc<rustls::ServerConfig>,
+    resolver: Arc<ReloadingCertResolver>,
+    watcher: Option<RecommendedWatcher>,
+}
+
+impl ServerTls {
+    /// Load certificates and build the rustls server config
+    pub fn load(settings: &TlsSettings) -> Result<Self, TlsError> {
+        let provider = crypto_provider();
+        let resolver = Arc::new(ReloadingCertResolver::load(
+            &settings.cert_path,
+            &settings.key_path,
+            provider.clone(),
+        )?);
+
+        let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()?;
+        let builder = match &settings.client_ca_path {
+            Some(ca_path) => {
+                let mut roots = RootCertStore::empty();
+                for cert in read_certs(ca_path)? {
+                    roots.add(cert)?;
+                }
+                let verifier =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+                let verifier = match settings.client_cert_mode {
+                    ClientCertMode::Required => verifier,
+                    ClientCertMode::Optional => verifier.allow_unauthenticated(),
+                };
+                builder.with_client_cert_verifier(verifier.build()?)
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let mut config = builder.with_cert_resolver(resolver.clone());
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        let watcher = if settings.reload {
+            Some(resolver.watch()?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            config: Arc::new(config),
+            resolver,
+            watcher,
+        })
+    }
+
+    /// Certificate resolver (for manual reloads)
+    pub fn resolver(&self) -> &Arc<ReloadingCertResolver> {
+        &self.resolver
+    }
+
+    /// The rustls server config
+    pub fn config(&self) -> Arc<rustls::ServerConfig> {
+        self.config.clone()
+    }
+}
+
+/// HTTPS listener for `axum::serve`
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    incoming: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    accept_task: JoinHandle<()>,
+    resolver: Arc<ReloadingCertResolver>,
+    _watcher: Option<RecommendedWatcher>,
+}
+
+impl TlsListener {
+    /// Bind `addr` and terminate TLS on every accepted connection
+    pub async fn bind(addr: SocketAddr, tls: ServerTls) -> Result<Self, TlsError> {
+        let listener = TcpListener::bind(addr).await?;
+        Self::from_tcp(listener, tls)
+    }
+
+    /// Terminate TLS on connections accepted by an existing listener
+    pub fn from_tcp(listener: TcpListener, tls: ServerTls) -> Result<Self, TlsError> {
+        let local_addr = listener.local_addr()?;
+        let acceptor = TlsAcceptor::from(tls.config.clone());
+        let (tx, incoming) = mpsc::channel(ACCEPT_QUEUE);
+        let accept_task = tokio::spawn(accept_loop(listener, acceptor, tx));
+        Ok(Self {
+            local_addr,
+            incoming,
+            accept_task,
+            resolver: tls.resolver,
+            _watcher: tls.watcher,
+        })
+    }
+
+    /// Certificate resolver (for manual reloads)
+    pub fn resolver(&self) -> &Arc<ReloadingCertResolver> {
+        &self.resolver
+    }
+}
+
+impl Drop for TlsListener {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    tx: mpsc::Sender<(TlsStream<TcpStream>, SocketAddr)>,
+) {
+    loop {
+        let (stream, remote_addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                // Same policy as axum's TCP listener: per-connection errors
+                // are harmless, anything else (e.g. EMFILE) needs a pause
+                if !matches!(
+                    e.kind(),
+                    std::io::ErrorKind::ConnectionRefused
+                        | std::io::ErrorKind::ConnectionAborted
+                        | std::io::ErrorKind::ConnectionReset
+                ) {
+                    tracing::error!("TLS listener accept error: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(tls_stream)) => {
+                    let _ = tx.send((tls_stream, remote_addr)).await;
+                }
+                Ok(Err(e)) => tracing::debug!("TLS handshake with {} failed: {}", remote_addr, e),
+                Err(_) => tracing::debug!("TLS handshake with {} timed out", remote_addr),
+            }
+        });
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.incoming.recv().await {
+            Some(accepted) => accepted,
+            // The accept task only stops when the listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+/// Connection info for TLS connections
+#[derive(Debug, Clone)]
+pub struct TlsConnectInfo {
+    pub remote_addr: SocketAddr,
+    /// Identity from the verified client certificate, if one was presented
+    pub identity: Option<ClientIdentity>,
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for TlsConnectInfo {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        let identity = stream
+            .io()
+            .get_ref()
+            .1
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(|cert| certificate_subject(cert))
+            .map(ClientIdentity::client_cert);
+        Self {
+            remote_addr: *stream.remote_addr(),
+            identity,
+        }
+    }
+}
+
+/// Name a client certificate identifies: its common name, or the full
+/// subject when it has none
+pub fn certificate_subject(cert: &CertificateDer<'_>) -> Option<String> {
+    let (_, parsed) = x509_parser::parse_x509_certificate(cert.as_ref()).ok()?;
+    let subject = parsed.subject();
+    let common_name = subject
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(str::to_string);
+    common_name.or_else(|| {
+        let full = subject.to_string();
+        (!full.is_empty()).then_some(full)
+    })
+}
+
+/// Expose TLS connection info the way the rest of the stack expects it:
+/// the peer address as `ConnectInfo<SocketAddr>` and the certificate
+/// identity as a [`ClientIdentity`] extension
+pub async fn tls_connect_info_middleware(
+    ConnectInfo(info): ConnectInfo<TlsConnectInfo>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    req.extensions_mut().insert(ConnectInfo(info.remote_addr));
+    if let Some(identity) = info.identity {
+        req.extensions_mut().insert(identity);
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Extension, Router, routing::get};
+    use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, Issuer, KeyPair};
+
+    struct Pki {
+        dir: tempfile::TempDir,
+        ca_pem: String,
+        issuer: Issuer<'static, KeyPair>,
+    }
+
+    impl Pki {
+        fn new() -> Self {
+            let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            params
+                .distinguished_name
+                .push(DnType::CommonName, "LunaRoute Test CA");
+            let key = KeyPair::generate().unwrap();
+            let ca = params.self_signed(&key).unwrap();
+            let dir = tempfile::tempdir().unwrap();
+            std::fs::write(dir.path().join("ca.pem"), ca.pem()).unwrap();
+            Self {
+                dir,
+                ca_pem: ca.pem(),
+                issuer: Issuer::new(params, key),
+            }
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.dir.path().join(name)
+        }
+
+        /// Leaf certificate signed by the CA, as (cert PEM, key PEM)
+        fn leaf(&self, common_name: &str, sans: &[&str]) -> (String, String) {
+            let sans: Vec<String> = sans.iter().map(|s| s.to_string()).collect();
+            let mut params = CertificateParams::new(sans).unwrap();
+            params
+                .distinguished_name
+                .push(DnType::CommonName, common_name);
+            let key = KeyPair::generate().unwrap();
+            let cert = params.signed_by(&key, &self.issuer).unwrap();
+            (cert.pem(), key.serialize_pem())
+        }
+
+        fn write_server_cert(&self, common_name: &str) {
+            let (cert, key) = self.leaf(common_name, &["localhost"]);
+            std::fs::write(self.path("server.pem"), cert).unwrap();
+            std::fs::write(self.path("server.key"), key).unwrap();
+        }
+
+        fn settings(&self) -> TlsSettings {
+            TlsSettings::new(self.path("server.pem"), self.path("server.key"))
+        }
+
+        fn client(&self, identity: Option<&str>) -> reqwest::Client {
+            let ca = reqwest::Certificate::from_pem(self.ca_pem.as_bytes()).unwrap();
+            let mut builder = reqwest::Client::builder().tls_certs_only([ca]);
+            if let Some(common_name) = identity {
+                let (cert, key) = self.leaf(common_name, &[]);
+                let pem = format!("{}{}", cert, key);
+                builder = builder.identity(reqwest::Identity::from_pem(pem.as_bytes()).unwrap());
+            }
+            builder.build().unwrap()
+        }
+    }
+
+    async fn serve(tls: ServerTls) -> SocketAddr {
+        let listener = TlsListener::bind("127.0.0.1:0".parse().unwrap(), tls)
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route(
+                "/whoami",
+                get(
+                    |ConnectInfo(peer): ConnectInfo<SocketAddr>,
+                     identity: Option<Extension<ClientIdentity>>| async move {
+                        assert!(peer.ip().is_loopback());
+                        identity
+                            .map(|Extension(identity)| identity.subject)
+                            .unwrap_or_else(|| "anonymous".to_string())
+                    },
+                ),
+            )
+            .layer(axum::middleware::from_fn(tls_connect_info_middleware));
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<TlsConnectInfo>(),
+            )
+            .await
+            .unwrap();
+        });
+        addr
+    }
+
+    async fn whoami(client: &reqwest::Client, addr: SocketAddr) -> reqwest::Result<String> {
+        client
+            .get(format!("https://localhost:{}/whoami", addr.port()))
+            .send()
+            .await?
+            .text()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_serves_https_and_maps_client_cert_identity() {
+        let pki = Pki::new();
+        pki.write_server_cert("server");
+        let settings = pki
+            .settings()
+            .with_client_ca(pki.path("ca.pem"), ClientCertMode::Optional);
+        let addr = serve(ServerTls::load(&settings).unwrap()).await;
+
+        assert_eq!(
+            whoami(&pki.client(Some("alice")), addr).await.unwrap(),
+            "alice"
+        );
+        assert_eq!(whoami(&pki.client(None), addr).await.unwrap(), "anonymous");
+    }
+
+    #[tokio::test]
+    async fn test_required_client_cert_rejects_anonymous_and_foreign_clients() {
+        let pki = Pki::new();
+        pki.write_server_cert("server");
+        let settings = pki
+            .settings()
+            .with_client_ca(pki.path("ca.pem"), ClientCertMode::Required);
+        let addr = serve(ServerTls::load(&settings).unwrap()).await;
+
+        assert!(whoami(&pki.client(None), addr).await.is_err());
+
+        // A certificate from another CA is not accepted either
+        let other = Pki::new();
+        let (cert, key) = other.leaf("mallory", &[]);
+        let client = reqwest::Client::builder()
+            .tls_certs_only([reqwest::Certificate::from_pem(pki.ca_pem.as_bytes()).unwrap()])
+            .identity(reqwest::Identity::from_pem(format!("{}{}", cert, key).as_bytes()).unwrap())
+            .build()
+            .unwrap();
+        assert!(whoami(&client, addr).await.is_err());
+
+        assert_eq!(whoami(&pki.client(Some("bob")), addr).await.unwrap(), "bob");
+    }
+
+    #[tokio::test]
+    async fn test_reloads_certificate_on_file_change() {
+        let pki = Pki::new();
+        pki.write_server_cert("first");
+        let tls = ServerTls::load(&pki.settings()).unwrap();
+        let resolver = tls.resolver().clone();
+        let first = resolver.current();
+        let addr = serve(tls).await;
+        assert_eq!(whoami(&pki.client(None), addr).await.unwrap(), "anonymous");
+
+        // A mismatched pair is rejected and the old certificate kept
+        let (_, stray_key) = pki.leaf("stray", &[]);
+        std::fs::write(pki.path("server.key"), stray_key).unwrap();
+        assert!(resolver.reload().is_err());
+        assert_eq!(resolver.current().cert, first.cert);
+
+        pki.write_server_cert("second");
+        let mut reloaded = false;
+        for _ in 0..50 {
+            if resolver.current().cert != first.cert {
+                reloaded = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(
+            reloaded,
+            "certificate was not reloaded after the files changed"
+        );
+        let subject = certificate_subject(&resolver.current().cert[0]);
+        assert_eq!(subject.as_deref(), Some("second"));
+        assert_eq!(whoami(&pki.client(None), addr).await.unwrap(), "anonymous");
+    }
+
+    #[test]
+    fn test_missing_files_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = TlsSettings::new(dir.path().join("nope.pem"), dir.path().join("nope.key"));
+        assert!(matches!(
+            ServerTls::load(&settings),
+            Err(TlsError::Pem { .. })
+        ));
+    }
+}
@@ -122,6 +122,10 @@ pub enum IngressError {
     #[error("Authentication failed: {0}")]
     AuthenticationFailed(String),
 
+    /// Authenticated, but not allowed to make this request
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     /// Request too large
     #[error("Request too large: {0} bytes")]
     RequestTooLarge(usize),
@@ -168,6 +172,7 @@ impl axum::response::IntoResponse for IngressError {
             IngressError::InvalidRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             IngressError::MissingHeader(msg) => (StatusCode::BAD_REQUEST, msg),
             IngressError::AuthenticationFailed(msg) => (StatusCode::UNAUTHORIZED, msg),
+            IngressError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             IngressError::RequestTooLarge(size) => (
                 StatusCode::PAYLOAD_TOO_LARGE,
                 format!("Request too large: {} bytes", size),
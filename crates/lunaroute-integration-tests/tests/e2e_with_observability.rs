@@ -114,6 +114,8 @@ async fn test_e2e_openai_request_with_metrics() {
         strategy: None,
         primary: Some("openai".to_string()),
         fallbacks: vec!["anthropic".to_string()],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -173,6 +175,8 @@ async fn test_e2e_health_checks_with_router() {
         strategy: None,
         primary: Some("openai".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -255,6 +259,8 @@ async fn test_e2e_fallback_with_observability() {
         strategy: None,
         primary: Some("openai".to_string()),
         fallbacks: vec!["anthropic".to_string()],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -318,6 +324,8 @@ async fn test_e2e_multiple_models_routing() {
             strategy: None,
             primary: Some("openai".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         },
         RoutingRule {
             priority: 10,
@@ -326,6 +334,8 @@ async fn test_e2e_multiple_models_routing() {
             strategy: None,
             primary: Some("anthropic".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         },
     ];
 
@@ -399,6 +409,8 @@ async fn test_e2e_invalid_request_handling() {
         strategy: None,
         primary: Some("test".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -141,9 +141,12 @@ async fn test_basic_rate_limit_switch() {
             primary_providers: vec!["primary".to_string()],
             alternative_providers: vec!["alternative".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         }),
         primary: None,
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
@@ -288,9 +291,12 @@ async fn test_cross_dialect_alternative() {
             primary_providers: vec!["openai".to_string()],
             alternative_providers: vec!["anthropic".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         }),
         primary: None,
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
@@ -403,9 +409,12 @@ async fn test_cascade_through_alternatives() {
             primary_providers: vec!["primary".to_string()],
             alternative_providers: vec!["alt1".to_string(), "alt2".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         }),
         primary: None,
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
@@ -521,9 +530,12 @@ async fn test_auto_recovery_to_primary() {
             primary_providers: vec!["primary".to_string()],
             alternative_providers: vec!["alternative".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         }),
         primary: None,
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
@@ -611,9 +623,12 @@ async fn test_all_providers_rate_limited() {
             primary_providers: vec!["primary".to_string()],
             alternative_providers: vec!["alternative".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         }),
         primary: None,
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
@@ -707,9 +722,12 @@ async fn test_exponential_backoff_without_retry_after() {
             primary_providers: vec!["primary".to_string()],
             alternative_providers: vec!["alternative".to_string()],
             exponential_backoff_base_secs: 30, // Custom base delay
+            quota_headroom_percent: 5,
         }),
         primary: None,
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
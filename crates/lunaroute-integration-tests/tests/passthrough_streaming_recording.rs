@@ -653,3 +653,96 @@ async fn test_anthropic_passthrough_non_streaming_with_recording() {
         eprintln!("Warning: SessionEvent::Completed not found for non-streaming request");
     }
 }
+
+#[tokio::test]
+async fn test_passthrough_records_model_before_rewrite() {
+    let mock_server = MockServer::start().await;
+
+    // Upstream sees the rewritten model
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_json(json!({
+            "model": "gpt-4",
+            "messages": [{"role": "user", "content": "Hello"}]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "created": 1234567890,
+            "model": "gpt-4",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": "Hi" },
+                "finish_reason": "stop"
+            }],
+            "usage": { "prompt_tokens": 5, "completion_tokens": 1, "total_tokens": 6 }
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let store = Arc::new(InMemorySessionStore::new());
+    let config = OpenAIConfig {
+        api_key: "test-api-key".to_string(),
+        base_url: mock_server.uri(),
+        organization: None,
+        client_config: Default::default(),
+        custom_headers: None,
+        request_body_config: None,
+        response_body_config: None,
+        codex_auth: None,
+        switch_notification_message: None,
+    };
+    let connector = Arc::new(OpenAIConnector::new(config).await.unwrap());
+    let aliases = lunaroute_routing::ModelAliasTable::new(
+        Default::default(),
+        [("gpt-4-legacy".to_string(), "gpt-4".to_string())].into(),
+    )
+    .unwrap();
+    let app = lunaroute_ingress::openai::passthrough_router(
+        connector,
+        None,
+        None,
+        Some(store.clone()),
+        15,
+        true,
+        None,
+    )
+    .layer(axum::Extension(Arc::new(aliases)));
+
+    let request = json!({
+        "model": "gpt-4-legacy",
+        "messages": [{"role": "user", "content": "Hello"}]
+    });
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/v1/chat/completions")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_vec(&request).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    let start = std::time::Instant::now();
+    let started = loop {
+        let events = store.get_events();
+        if let Some(SessionEvent::Started {
+            model_requested, ..
+        }) = events
+            .iter()
+            .find(|e| matches!(e, SessionEvent::Started { .. }))
+        {
+            break model_requested.clone();
+        }
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(5),
+            "Timeout waiting for Started event"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    };
+    assert_eq!(started, "gpt-4-legacy");
+}
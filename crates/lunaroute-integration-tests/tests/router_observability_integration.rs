@@ -147,6 +147,8 @@ async fn test_router_with_metrics_integration() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -220,6 +222,8 @@ async fn test_circuit_breaker_with_metrics_tracking() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -302,6 +306,8 @@ async fn test_fallback_with_metrics_tracking() {
         strategy: None,
         primary: Some("primary".to_string()),
         fallbacks: vec!["fallback".to_string()],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -350,6 +356,8 @@ async fn test_high_concurrency_with_metrics() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -423,6 +431,8 @@ async fn test_provider_latency_tracking() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -470,6 +480,8 @@ async fn test_health_status_with_metrics() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -556,6 +568,8 @@ async fn test_provider_timeout_scenario() {
         strategy: None,
         primary: Some("slow-provider".to_string()),
         fallbacks: vec!["fast-fallback".to_string()],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -589,6 +603,8 @@ async fn test_mixed_success_failure_metrics() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -132,6 +132,8 @@ async fn test_e2e_streaming_basic() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -234,6 +236,8 @@ async fn test_e2e_streaming_multiple_chunks() {
         strategy: None,
         primary: Some("multi-chunk".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -308,6 +312,8 @@ async fn test_e2e_streaming_with_router_fallback() {
         strategy: None,
         primary: Some("primary".to_string()),
         fallbacks: vec!["fallback".to_string()],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -373,6 +379,8 @@ async fn test_e2e_streaming_concurrent_clients() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -479,6 +487,8 @@ async fn test_e2e_streaming_non_streaming_provider_error() {
         strategy: None,
         primary: Some("non-streaming".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
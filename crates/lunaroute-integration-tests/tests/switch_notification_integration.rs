@@ -141,9 +141,12 @@ async fn test_rate_limit_switch_injects_notification() {
             primary_providers: vec!["primary".to_string()],
             alternative_providers: vec!["alternative".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         }),
         primary: None,
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
@@ -261,9 +264,12 @@ async fn test_notification_disabled() {
             primary_providers: vec!["primary".to_string()],
             alternative_providers: vec!["alternative".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         }),
         primary: None,
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
@@ -375,6 +381,8 @@ async fn test_cross_dialect_notification() {
         strategy: None,
         primary: Some("openai".to_string()),
         fallbacks: vec!["anthropic".to_string()],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
@@ -497,9 +505,12 @@ async fn test_custom_provider_notification_message() {
             primary_providers: vec!["primary".to_string()],
             alternative_providers: vec!["alternative".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         }),
         primary: None,
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
@@ -639,6 +650,8 @@ async fn test_notification_idempotency_cascading_fallbacks() {
         strategy: None,
         primary: Some("primary".to_string()),
         fallbacks: vec!["alt1".to_string(), "alt2".to_string()],
+        on_error: Default::default(),
+        schedule: None,
     };
 
     let route_table = RouteTable::with_rules(vec![rule]);
@@ -42,7 +42,7 @@ pub struct ReadinessResponse {
 }
 
 /// Provider status in readiness check
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProviderStatus {
     /// Provider name
     pub name: String,
@@ -51,6 +51,12 @@ pub struct ProviderStatus {
     /// Success rate (0.0-1.0)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub success_rate: Option<f64>,
+    /// Circuit breaker state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit: Option<String>,
+    /// Outcome of the latest active health probe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_probe: Option<String>,
 }
 
 /// Readiness checker trait
@@ -201,6 +207,7 @@ mod tests {
                     "unhealthy".to_string()
                 },
                 success_rate: Some(if self.ready { 0.95 } else { 0.0 }),
+                ..Default::default()
             }]
         }
     }
@@ -324,6 +331,7 @@ mod tests {
                 name: "openai".to_string(),
                 status: "healthy".to_string(),
                 success_rate: Some(0.95),
+                ..Default::default()
             }]),
             message: None,
         };
@@ -338,6 +346,7 @@ mod tests {
             name: "openai".to_string(),
             status: "healthy".to_string(),
             success_rate: Some(0.95),
+            ..Default::default()
         };
         let json = serde_json::to_string(&status).unwrap();
         assert!(json.contains("\"name\":\"openai\""));
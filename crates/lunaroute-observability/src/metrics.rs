@@ -67,6 +67,18 @@ pub struct Metrics {
     pub rate_limit_alternatives_used: CounterVec,
     /// Rate limit backoff duration
     pub rate_limit_backoff_seconds: HistogramVec,
+    /// Remaining upstream quota from rate-limit headers
+    pub provider_quota_remaining: GaugeVec,
+    /// Upstream quota window size from rate-limit headers
+    pub provider_quota_limit: GaugeVec,
+    /// Upstream API keys quarantined after a 401
+    pub upstream_key_quarantines_total: CounterVec,
+
+    // Budget metrics
+    /// Budget usage as a fraction of its limit
+    pub budget_usage_ratio: GaugeVec,
+    /// Budget warnings and enforcement actions
+    pub budget_events_total: CounterVec,
 
     // Tool call metrics
     /// Tool calls made during requests
@@ -294,6 +306,47 @@ impl Metrics {
             &["provider"],
         )?;
 
+        let provider_quota_remaining = GaugeVec::new(
+            Opts::new(
+                "lunaroute_provider_quota_remaining",
+                "Remaining upstream quota reported in rate-limit headers",
+            ),
+            &["provider", "kind"],
+        )?;
+
+        let provider_quota_limit = GaugeVec::new(
+            Opts::new(
+                "lunaroute_provider_quota_limit",
+                "Upstream quota window size reported in rate-limit headers",
+            ),
+            &["provider", "kind"],
+        )?;
+
+        // Budget metrics
+        let budget_usage_ratio = GaugeVec::new(
+            Opts::new(
+                "lunaroute_budget_usage_ratio",
+                "Budget usage as a fraction of its limit",
+            ),
+            &["budget", "subject"],
+        )?;
+
+        let upstream_key_quarantines_total = CounterVec::new(
+            Opts::new(
+                "lunaroute_upstream_key_quarantines_total",
+                "Upstream API keys quarantined after being rejected with 401",
+            ),
+            &["provider", "key"],
+        )?;
+
+        let budget_events_total = CounterVec::new(
+            Opts::new(
+                "lunaroute_budget_events_total",
+                "Budget warnings and enforcement actions",
+            ),
+            &["budget", "event"],
+        )?;
+
         // Tool call metrics
         let tool_calls_total = CounterVec::new(
             Opts::new(
@@ -477,6 +530,11 @@ impl Metrics {
         registry.register(Box::new(rate_limits_total.clone()))?;
         registry.register(Box::new(rate_limit_alternatives_used.clone()))?;
         registry.register(Box::new(rate_limit_backoff_seconds.clone()))?;
+        registry.register(Box::new(provider_quota_remaining.clone()))?;
+        registry.register(Box::new(provider_quota_limit.clone()))?;
+        registry.register(Box::new(budget_usage_ratio.clone()))?;
+        registry.register(Box::new(budget_events_total.clone()))?;
+        registry.register(Box::new(upstream_key_quarantines_total.clone()))?;
         registry.register(Box::new(tool_calls_total.clone()))?;
         registry.register(Box::new(tool_result_failures_total.clone()))?;
         registry.register(Box::new(post_processing_duration_seconds.clone()))?;
@@ -517,6 +575,11 @@ impl Metrics {
             rate_limits_total,
             rate_limit_alternatives_used,
             rate_limit_backoff_seconds,
+            provider_quota_remaining,
+            provider_quota_limit,
+            budget_usage_ratio,
+            budget_events_total,
+            upstream_key_quarantines_total,
             tool_calls_total,
             tool_result_failures_total,
             post_processing_duration_seconds,
@@ -632,6 +695,46 @@ impl Metrics {
             .inc();
     }
 
+    /// Update remaining upstream quota for one rate-limit kind
+    /// (`requests`, `tokens`, `input_tokens`, `output_tokens`)
+    pub fn update_provider_quota(
+        &self,
+        provider: &str,
+        kind: &str,
+        remaining: u64,
+        limit: Option<u64>,
+    ) {
+        self.provider_quota_remaining
+            .with_label_values(&[provider, kind])
+            .set(remaining as f64);
+        if let Some(limit) = limit {
+            self.provider_quota_limit
+                .with_label_values(&[provider, kind])
+                .set(limit as f64);
+        }
+    }
+
+    /// Update a budget's usage for one subject (key, user, tenant or project)
+    pub fn update_budget_usage(&self, budget: &str, subject: &str, ratio: f64) {
+        self.budget_usage_ratio
+            .with_label_values(&[budget, subject])
+            .set(ratio);
+    }
+
+    /// Record a budget event (`warning`, `rejected`, `throttled`, `rerouted`, `overridden`)
+    pub fn record_budget_event(&self, budget: &str, event: &str) {
+        self.budget_events_total
+            .with_label_values(&[budget, event])
+            .inc();
+    }
+
+    /// Record an upstream key being quarantined (`key` is the hashed key id)
+    pub fn record_upstream_key_quarantine(&self, provider: &str, key: &str) {
+        self.upstream_key_quarantines_total
+            .with_label_values(&[provider, key])
+            .inc();
+    }
+
     /// Update circuit breaker state
     pub fn update_circuit_breaker_state(&self, provider: &str, state: CircuitBreakerState) {
         self.circuit_breaker_state
@@ -1062,6 +1165,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_budget_metrics() {
+        let metrics = Metrics::new().unwrap();
+        metrics.update_budget_usage("team-monthly", "tenant:acme", 0.85);
+        metrics.record_budget_event("team-monthly", "warning");
+        metrics.record_budget_event("team-monthly", "warning");
+
+        let gathered = metrics.registry().gather();
+        let ratio = gathered
+            .iter()
+            .find(|m| m.name() == "lunaroute_budget_usage_ratio")
+            .expect("budget_usage_ratio metric not found");
+        assert_eq!(ratio.metric[0].gauge.as_ref().unwrap().value.unwrap(), 0.85);
+        let events = gathered
+            .iter()
+            .find(|m| m.name() == "lunaroute_budget_events_total")
+            .expect("budget_events_total metric not found");
+        assert_eq!(
+            events.metric[0].counter.as_ref().unwrap().value.unwrap(),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_record_upstream_key_quarantine() {
+        let metrics = Metrics::new().unwrap();
+        metrics.record_upstream_key_quarantine("anthropic", "key_0123456789ab");
+
+        let gathered = metrics.registry().gather();
+        let quarantines = gathered
+            .iter()
+            .find(|m| m.name() == "lunaroute_upstream_key_quarantines_total")
+            .expect("upstream_key_quarantines_total metric not found");
+        assert_eq!(
+            quarantines.metric[0]
+                .counter
+                .as_ref()
+                .unwrap()
+                .value
+                .unwrap(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_update_provider_quota() {
+        let metrics = Metrics::new().unwrap();
+        metrics.update_provider_quota("anthropic", "input_tokens", 1200, Some(40000));
+        metrics.update_provider_quota("openai", "requests", 3, None);
+
+        let gathered = metrics.registry().gather();
+        let remaining = gathered
+            .iter()
+            .find(|m| m.name() == "lunaroute_provider_quota_remaining")
+            .expect("provider_quota_remaining metric not found");
+        assert_eq!(remaining.metric.len(), 2);
+        let limit = gathered
+            .iter()
+            .find(|m| m.name() == "lunaroute_provider_quota_limit")
+            .expect("provider_quota_limit metric not found");
+        assert_eq!(limit.metric.len(), 1);
+        assert_eq!(
+            limit.metric[0].gauge.as_ref().unwrap().value.unwrap(),
+            40000.0
+        );
+    }
+
     #[test]
     fn test_circuit_breaker_state_as_str() {
         assert_eq!(CircuitBreakerState::Closed.as_str(), "closed");
@@ -164,11 +164,13 @@ async fn test_health_and_metrics_integration() {
         name: "openai".to_string(),
         status: "healthy".to_string(),
         success_rate: Some(0.98),
+        ..Default::default()
     });
     checker.add_provider(ProviderStatus {
         name: "anthropic".to_string(),
         status: "healthy".to_string(),
         success_rate: Some(0.95),
+        ..Default::default()
     });
 
     let health_state = HealthState::with_readiness_checker(metrics.clone(), checker.clone());
@@ -12,12 +12,15 @@
 //! - HalfOpen → Closed: After consecutive successes exceed threshold
 //! - HalfOpen → Open: On any failure during testing
 
+use crate::persistence::{CircuitSnapshot, instant_to_ms, ms_to_instant};
+use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU8, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 /// Circuit breaker states
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CircuitState {
     /// Normal operation - requests pass through
     Closed = 0,
@@ -186,6 +189,72 @@ impl CircuitBreaker {
         }
     }
 
+    /// Check whether [`allow_request`](Self::allow_request) would let a request
+    /// through, without transitioning an expired open circuit to half-open
+    pub fn would_allow_request(&self) -> bool {
+        match self.state() {
+            CircuitState::Closed | CircuitState::HalfOpen => true,
+            CircuitState::Open => {
+                self.last_state_change
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .elapsed()
+                    >= self.config.timeout
+            }
+        }
+    }
+
+    /// Capture the breaker state for persistence
+    pub fn snapshot(&self) -> CircuitSnapshot {
+        let changed_at = *self
+            .last_state_change
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        CircuitSnapshot {
+            state: self.state(),
+            consecutive_failures: self.consecutive_failures.load(Ordering::Acquire),
+            consecutive_successes: self.consecutive_successes.load(Ordering::Acquire),
+            changed_at_ms: instant_to_ms(changed_at),
+        }
+    }
+
+    /// Restore persisted state, e.g. after a restart
+    ///
+    /// An open circuit keeps its original open time, so the remaining timeout
+    /// is honoured rather than restarted.
+    pub fn restore(&self, snapshot: &CircuitSnapshot) {
+        let now = Instant::now();
+        let changed_at = ms_to_instant(snapshot.changed_at_ms)
+            .unwrap_or_else(|| now.checked_sub(self.config.timeout).unwrap_or(now));
+        *self
+            .last_state_change
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = changed_at;
+        self.consecutive_failures
+            .store(snapshot.consecutive_failures, Ordering::Release);
+        self.consecutive_successes
+            .store(snapshot.consecutive_successes, Ordering::Release);
+        self.state.store(snapshot.state as u8, Ordering::Release);
+    }
+
+    /// Adopt a snapshot written by another instance sharing the state store
+    ///
+    /// The snapshot wins if it is in a different state and changed more
+    /// recently, or if this breaker is closed and has not seen any failures
+    /// yet. Returns true if the local state changed.
+    pub fn merge(&self, snapshot: &CircuitSnapshot) -> bool {
+        let local = self.snapshot();
+        if snapshot.state == local.state {
+            return false;
+        }
+        let pristine = local.state == CircuitState::Closed && local.consecutive_failures == 0;
+        if snapshot.changed_at_ms > local.changed_at_ms || pristine {
+            self.restore(snapshot);
+            return true;
+        }
+        false
+    }
+
     /// Record a successful operation
     pub fn record_success(&self) {
         let current_state = self.state();
@@ -406,6 +475,80 @@ mod tests {
         assert_eq!(cb.state(), CircuitState::HalfOpen);
     }
 
+    #[test]
+    fn test_would_allow_request_does_not_transition() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            success_threshold: 2,
+            timeout: Duration::from_millis(100),
+        };
+        let cb = CircuitBreaker::new(config);
+        assert!(cb.would_allow_request());
+
+        cb.record_failure();
+        assert!(!cb.would_allow_request());
+
+        thread::sleep(Duration::from_millis(150));
+
+        // Reports the request would pass but leaves the circuit open
+        assert!(cb.would_allow_request());
+        assert_eq!(cb.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_snapshot_restore_keeps_open_timeout() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            success_threshold: 1,
+            timeout: Duration::from_secs(600),
+        };
+        let cb = CircuitBreaker::new(config.clone());
+        cb.record_failure();
+        let snapshot = cb.snapshot();
+        assert_eq!(snapshot.state, CircuitState::Open);
+
+        let restored = CircuitBreaker::new(config);
+        restored.restore(&snapshot);
+        assert_eq!(restored.state(), CircuitState::Open);
+        assert!(!restored.allow_request());
+    }
+
+    #[test]
+    fn test_merge_prefers_newer_state() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            success_threshold: 1,
+            timeout: Duration::from_secs(600),
+        };
+        let remote = CircuitBreaker::new(config.clone());
+        remote.record_failure();
+        let open = remote.snapshot();
+
+        // A breaker created after the remote opened still adopts it
+        let local = CircuitBreaker::new(config.clone());
+        assert!(local.merge(&open));
+        assert_eq!(local.state(), CircuitState::Open);
+        assert!(!local.merge(&open));
+
+        // An older closed snapshot does not close a newer open circuit
+        let stale = CircuitSnapshot {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            consecutive_successes: 0,
+            changed_at_ms: open.changed_at_ms - 1_000,
+        };
+        assert!(!local.merge(&stale));
+        assert_eq!(local.state(), CircuitState::Open);
+
+        // A newer closed snapshot does
+        let recovered = CircuitSnapshot {
+            changed_at_ms: open.changed_at_ms + 1_000,
+            ..stale
+        };
+        assert!(local.merge(&recovered));
+        assert_eq!(local.state(), CircuitState::Closed);
+    }
+
     #[test]
     fn test_half_open_to_closed_on_success_threshold() {
         let config = CircuitBreakerConfig {
@@ -0,0 +1,453 @@
+//! A/B experiments with sticky traffic bucketing
+//!
+//! An experiment splits matching traffic by percentage across named arms.
+//! Each arm can swap the provider/model, patch the system prompt, and apply
+//! parameter overrides. An arm with no changes acts as the control.
+//!
+//! ## Assignment
+//!
+//! Assignment is sticky: the experiment name and a sticky key (session ID,
+//! user ID or a request header) are hashed into one of 10,000 buckets, and
+//! the arms claim consecutive bucket ranges according to their weights.
+//! Weights are percentages; if they sum to less than 100 the remaining
+//! traffic is not enrolled. Requests without a sticky key are never enrolled,
+//! so a client cannot flip between arms mid-conversation.
+//!
+//! Experiments are evaluated in configuration order and a request is enrolled
+//! in at most one experiment.
+//!
+//! ```rust
+//! use lunaroute_routing::{Experiment, ExperimentArm, ExperimentSet, StickyKey};
+//!
+//! let experiment = Experiment {
+//!     name: "sonnet-vs-gpt5".to_string(),
+//!     description: None,
+//!     enabled: true,
+//!     models: vec!["claude-sonnet-4-5".to_string()],
+//!     sticky_key: StickyKey::Session,
+//!     header: None,
+//!     arms: vec![
+//!         ExperimentArm::control("control", 50),
+//!         ExperimentArm {
+//!             provider: Some("openai".to_string()),
+//!             model: Some("gpt-5".to_string()),
+//!             ..ExperimentArm::control("gpt5", 50)
+//!         },
+//!     ],
+//! };
+//!
+//! let set = ExperimentSet::new(vec![experiment]).unwrap();
+//! let assignment = set
+//!     .assign("claude-sonnet-4-5", |_| Some("session-123".to_string()))
+//!     .unwrap();
+//! assert!(["control", "gpt5"].contains(&assignment.arm.id.as_str()));
+//! ```
+
+use crate::model_alias::ParameterOverrides;
+use lunaroute_core::normalized::NormalizedRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Number of hash buckets traffic is split into (0.01% granularity)
+const BUCKET_COUNT: u64 = 10_000;
+
+/// A named traffic split across arms
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Experiment {
+    /// Unique experiment name (also salts the bucketing hash)
+    pub name: String,
+
+    /// Human-readable description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Disabled experiments enroll no traffic
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Requested model names this experiment applies to (empty = all models)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+
+    /// Which request attribute keeps assignment sticky
+    #[serde(default)]
+    pub sticky_key: StickyKey,
+
+    /// Header name used when `sticky_key` is `header`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+
+    /// Arms in bucket order
+    pub arms: Vec<ExperimentArm>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Request attribute used for sticky assignment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StickyKey {
+    /// Client session ID
+    #[default]
+    Session,
+    /// End-user identifier supplied by the client
+    User,
+    /// Value of the header named by [`Experiment::header`]
+    Header,
+}
+
+/// One arm of an experiment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentArm {
+    /// Arm identifier recorded on the session (e.g. "control", "gpt5")
+    pub id: String,
+
+    /// Share of traffic in percent
+    pub weight: u32,
+
+    /// Provider to route to (None = keep normal routing)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+
+    /// Model to send upstream (None = keep the requested model)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// System prompt modification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<SystemPromptPatch>,
+
+    /// Parameter overrides applied to requests in this arm
+    #[serde(default, skip_serializing_if = "ParameterOverrides::is_empty")]
+    pub params: ParameterOverrides,
+}
+
+impl ExperimentArm {
+    /// An arm that leaves requests unchanged
+    pub fn control(id: impl Into<String>, weight: u32) -> Self {
+        Self {
+            id: id.into(),
+            weight,
+            provider: None,
+            model: None,
+            system_prompt: None,
+            params: ParameterOverrides::default(),
+        }
+    }
+
+    /// True if this arm does not modify requests
+    pub fn is_control(&self) -> bool {
+        self.provider.is_none()
+            && self.model.is_none()
+            && self.system_prompt.is_none()
+            && self.params.is_empty()
+    }
+
+    /// Apply the arm's model, system prompt and parameter changes to a
+    /// normalized request
+    pub fn apply_to_request(&self, request: &mut NormalizedRequest) {
+        if let Some(model) = &self.model {
+            request.model = model.clone();
+        }
+        if let Some(patch) = &self.system_prompt {
+            request.system = Some(patch.apply(request.system.as_deref()));
+        }
+        self.params.apply_to_request(request);
+    }
+}
+
+/// How a system prompt patch is combined with the client's system prompt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatchMode {
+    /// Insert the text before the existing prompt
+    Prepend,
+    /// Add the text after the existing prompt
+    #[default]
+    Append,
+    /// Replace the existing prompt entirely
+    Replace,
+}
+
+/// System prompt modification applied by an arm
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemPromptPatch {
+    #[serde(default)]
+    pub mode: PatchMode,
+    pub text: String,
+}
+
+impl SystemPromptPatch {
+    /// Combine the patch with an existing system prompt
+    pub fn apply(&self, existing: Option<&str>) -> String {
+        match (self.mode, existing.filter(|s| !s.is_empty())) {
+            (PatchMode::Replace, _) | (_, None) => self.text.clone(),
+            (PatchMode::Prepend, Some(existing)) => format!("{}\n\n{}", self.text, existing),
+            (PatchMode::Append, Some(existing)) => format!("{}\n\n{}", existing, self.text),
+        }
+    }
+}
+
+/// Errors detected while validating experiment configuration
+#[derive(Debug, Error, PartialEq)]
+pub enum ExperimentError {
+    #[error("Duplicate experiment name '{0}'")]
+    DuplicateName(String),
+
+    #[error("Experiment '{0}' has no arms")]
+    NoArms(String),
+
+    #[error("Experiment '{experiment}' has duplicate arm '{arm}'")]
+    DuplicateArm { experiment: String, arm: String },
+
+    #[error("Experiment '{experiment}' arm weights sum to {total}%, must not exceed 100%")]
+    WeightOverflow { experiment: String, total: u32 },
+
+    #[error("Experiment '{0}' uses sticky_key 'header' but no header name is configured")]
+    MissingHeader(String),
+}
+
+/// The arm a request was assigned to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExperimentAssignment<'a> {
+    pub experiment: &'a Experiment,
+    pub arm: &'a ExperimentArm,
+}
+
+/// Validated, ordered set of experiments
+#[derive(Debug, Clone, Default)]
+pub struct ExperimentSet {
+    experiments: Vec<Experiment>,
+}
+
+impl ExperimentSet {
+    /// Validate and build an experiment set
+    pub fn new(experiments: Vec<Experiment>) -> Result<Self, ExperimentError> {
+        let mut names = HashSet::new();
+        for experiment in &experiments {
+            if !names.insert(experiment.name.as_str()) {
+                return Err(ExperimentError::DuplicateName(experiment.name.clone()));
+            }
+            if experiment.arms.is_empty() {
+                return Err(ExperimentError::NoArms(experiment.name.clone()));
+            }
+            if experiment.sticky_key == StickyKey::Header && experiment.header.is_none() {
+                return Err(ExperimentError::MissingHeader(experiment.name.clone()));
+            }
+
+            let mut arm_ids = HashSet::new();
+            for arm in &experiment.arms {
+                if !arm_ids.insert(arm.id.as_str()) {
+                    return Err(ExperimentError::DuplicateArm {
+                        experiment: experiment.name.clone(),
+                        arm: arm.id.clone(),
+                    });
+                }
+            }
+
+            let total: u32 = experiment.arms.iter().map(|a| a.weight).sum();
+            if total > 100 {
+                return Err(ExperimentError::WeightOverflow {
+                    experiment: experiment.name.clone(),
+                    total,
+                });
+            }
+        }
+
+        Ok(Self { experiments })
+    }
+
+    /// True if no experiments are configured
+    pub fn is_empty(&self) -> bool {
+        self.experiments.is_empty()
+    }
+
+    /// All configured experiments in evaluation order
+    pub fn experiments(&self) -> &[Experiment] {
+        &self.experiments
+    }
+
+    /// Assign a request for `model` to an experiment arm.
+    ///
+    /// `sticky_key` looks up the value of an experiment's sticky key on the
+    /// current request; returning None leaves the request unenrolled.
+    pub fn assign<F>(&self, model: &str, sticky_key: F) -> Option<ExperimentAssignment<'_>>
+    where
+        F: Fn(&Experiment) -> Option<String>,
+    {
+        self.experiments
+            .iter()
+            .filter(|e| e.enabled && (e.models.is_empty() || e.models.iter().any(|m| m == model)))
+            .find_map(|experiment| {
+                let key = sticky_key(experiment)?;
+                let arm = experiment.arm_for_bucket(bucket(&experiment.name, &key))?;
+                Some(ExperimentAssignment { experiment, arm })
+            })
+    }
+}
+
+impl Experiment {
+    /// Arm owning `bucket`, or None if the bucket falls in unenrolled traffic
+    fn arm_for_bucket(&self, bucket: u64) -> Option<&ExperimentArm> {
+        let mut upper = 0u64;
+        for arm in &self.arms {
+            upper += arm.weight as u64 * (BUCKET_COUNT / 100);
+            if bucket < upper {
+                return Some(arm);
+            }
+        }
+        None
+    }
+}
+
+/// Stable bucket for a sticky key within an experiment.
+///
+/// Uses FNV-1a so the assignment is identical across processes and releases
+/// (unlike `DefaultHasher`, whose output is not guaranteed to be stable).
+fn bucket(experiment: &str, key: &str) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET;
+    for byte in experiment
+        .bytes()
+        .chain(std::iter::once(0))
+        .chain(key.bytes())
+    {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash % BUCKET_COUNT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn experiment(arms: Vec<ExperimentArm>) -> Experiment {
+        Experiment {
+            name: "exp".to_string(),
+            description: None,
+            enabled: true,
+            models: vec![],
+            sticky_key: StickyKey::Session,
+            header: None,
+            arms,
+        }
+    }
+
+    #[test]
+    fn test_assignment_is_sticky() {
+        let set = ExperimentSet::new(vec![experiment(vec![
+            ExperimentArm::control("a", 50),
+            ExperimentArm::control("b", 50),
+        ])])
+        .unwrap();
+
+        for i in 0..100 {
+            let key = format!("session-{}", i);
+            let first = set
+                .assign("m", |_| Some(key.clone()))
+                .unwrap()
+                .arm
+                .id
+                .clone();
+            let second = set
+                .assign("m", |_| Some(key.clone()))
+                .unwrap()
+                .arm
+                .id
+                .clone();
+            assert_eq!(first, second);
+        }
+    }
+
+    #[test]
+    fn test_split_follows_weights() {
+        let set = ExperimentSet::new(vec![experiment(vec![
+            ExperimentArm::control("a", 20),
+            ExperimentArm::control("b", 30),
+        ])])
+        .unwrap();
+
+        let (mut a, mut b, mut none) = (0, 0, 0);
+        for i in 0..10_000 {
+            match set.assign("m", |_| Some(format!("user-{}", i))) {
+                Some(assignment) if assignment.arm.id == "a" => a += 1,
+                Some(_) => b += 1,
+                None => none += 1,
+            }
+        }
+        // Loose bounds: FNV spreads sequential keys well but not perfectly
+        assert!((1_700..2_300).contains(&a), "a = {}", a);
+        assert!((2_700..3_300).contains(&b), "b = {}", b);
+        assert!((4_700..5_300).contains(&none), "none = {}", none);
+    }
+
+    #[test]
+    fn test_missing_key_or_model_not_enrolled() {
+        let mut exp = experiment(vec![ExperimentArm::control("a", 100)]);
+        exp.models = vec!["claude-sonnet-4-5".to_string()];
+        let set = ExperimentSet::new(vec![exp]).unwrap();
+
+        assert!(set.assign("claude-sonnet-4-5", |_| None).is_none());
+        assert!(set.assign("gpt-5", |_| Some("k".to_string())).is_none());
+        assert!(
+            set.assign("claude-sonnet-4-5", |_| Some("k".to_string()))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        assert_eq!(
+            ExperimentSet::new(vec![experiment(vec![])]).unwrap_err(),
+            ExperimentError::NoArms("exp".to_string())
+        );
+        assert!(matches!(
+            ExperimentSet::new(vec![experiment(vec![
+                ExperimentArm::control("a", 60),
+                ExperimentArm::control("b", 60),
+            ])]),
+            Err(ExperimentError::WeightOverflow { total: 120, .. })
+        ));
+        assert!(matches!(
+            ExperimentSet::new(vec![experiment(vec![
+                ExperimentArm::control("a", 10),
+                ExperimentArm::control("a", 10),
+            ])]),
+            Err(ExperimentError::DuplicateArm { .. })
+        ));
+
+        let mut header_exp = experiment(vec![ExperimentArm::control("a", 10)]);
+        header_exp.sticky_key = StickyKey::Header;
+        assert_eq!(
+            ExperimentSet::new(vec![header_exp]).unwrap_err(),
+            ExperimentError::MissingHeader("exp".to_string())
+        );
+    }
+
+    #[test]
+    fn test_system_prompt_patch() {
+        let patch = |mode| SystemPromptPatch {
+            mode,
+            text: "Be brief.".to_string(),
+        };
+        assert_eq!(
+            patch(PatchMode::Append).apply(Some("You help.")),
+            "You help.\n\nBe brief."
+        );
+        assert_eq!(
+            patch(PatchMode::Prepend).apply(Some("You help.")),
+            "Be brief.\n\nYou help."
+        );
+        assert_eq!(
+            patch(PatchMode::Replace).apply(Some("You help.")),
+            "Be brief."
+        );
+        assert_eq!(patch(PatchMode::Append).apply(None), "Be brief.");
+    }
+}
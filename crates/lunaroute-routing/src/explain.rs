@@ -0,0 +1,197 @@
+//! Routing explanations (dry runs)
+//!
+//! [`Router::explain`](crate::Router::explain) walks the same steps as a real
+//! request — model rewrites and aliases, rule evaluation in priority order,
+//! strategy selection and the fallback chain — without sending anything or
+//! mutating router state. The result is a [`RoutingExplanation`] that can be
+//! serialized for the admin API and rendered by `lunaroute route`.
+
+use crate::circuit_breaker::CircuitState;
+use crate::health::HealthStatus;
+use serde::{Deserialize, Serialize};
+
+/// Full account of how a request would be routed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingExplanation {
+    /// Model name as sent by the client
+    pub requested_model: String,
+
+    /// Model name after rewrites (alias name or real model)
+    pub resolved_model: String,
+
+    /// Steps applied before the route table (markers, experiments, rewrites, aliases)
+    #[serde(default)]
+    pub steps: Vec<ExplainStep>,
+
+    /// Rules in evaluation (priority) order
+    #[serde(default)]
+    pub rules: Vec<RuleEvaluation>,
+
+    /// Name of the rule that matched (`provider_override` for overrides)
+    pub matched_rule: Option<String>,
+
+    /// Strategy selection, if the matched rule uses one
+    pub strategy: Option<StrategyChoice>,
+
+    /// Providers in the order they would be tried
+    #[serde(default)]
+    pub candidates: Vec<ProviderCandidate>,
+
+    /// First candidate that would actually be tried (None = request would fail)
+    pub selected_provider: Option<String>,
+}
+
+impl RoutingExplanation {
+    /// Create an empty explanation for a requested model
+    pub fn new(requested_model: impl Into<String>) -> Self {
+        let requested_model = requested_model.into();
+        Self {
+            resolved_model: requested_model.clone(),
+            requested_model,
+            steps: Vec::new(),
+            rules: Vec::new(),
+            matched_rule: None,
+            strategy: None,
+            candidates: Vec::new(),
+            selected_provider: None,
+        }
+    }
+
+    /// Record a pre-routing step
+    pub fn push_step(&mut self, stage: impl Into<String>, detail: impl Into<String>) {
+        self.steps.push(ExplainStep {
+            stage: stage.into(),
+            detail: detail.into(),
+        });
+    }
+
+    /// Recompute `selected_provider` from the candidate list
+    pub(crate) fn select_first_available(&mut self) {
+        self.selected_provider = self
+            .candidates
+            .iter()
+            .find(|c| c.skipped.is_none())
+            .map(|c| c.provider.clone());
+    }
+}
+
+/// A pre-routing step such as a marker override or model rewrite
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExplainStep {
+    /// Stage name (e.g. `marker`, `experiment`, `rewrite`, `alias`)
+    pub stage: String,
+    /// Human-readable description
+    pub detail: String,
+}
+
+/// Result of evaluating one routing rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleEvaluation {
+    /// Rule name (or `rule_priority_<n>` for unnamed rules)
+    pub name: String,
+    /// Rule priority (higher = evaluated first)
+    pub priority: i32,
+    /// Matcher description
+    pub matcher: String,
+    /// Schedule description and whether it is active at evaluation time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    /// Evaluation outcome
+    pub outcome: RuleOutcome,
+}
+
+/// Outcome of a single rule evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleOutcome {
+    /// The matcher hit and this rule decides the route
+    Matched,
+    /// The matcher did not hit
+    NotMatched,
+    /// The matcher hit but the rule's schedule is not active
+    Inactive,
+    /// The matcher hit but the rule has neither strategy nor primary
+    Invalid,
+    /// Not evaluated: an earlier rule, override or alias decided the route
+    NotEvaluated,
+}
+
+/// Provider chosen by a routing strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyChoice {
+    /// Strategy type (`round-robin`, `weighted-round-robin`, `limits-alternative`)
+    pub kind: String,
+    /// Provider the strategy would pick next
+    pub selected: Option<String>,
+    /// Selection error (e.g. every provider rate-limited)
+    pub error: Option<String>,
+}
+
+/// Why a provider appears in the candidate list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateRole {
+    /// Forced by a provider override (header, marker or experiment arm)
+    Override,
+    /// Rule primary (or limits-alternative primary)
+    Primary,
+    /// Picked by a round-robin strategy
+    Strategy,
+    /// Limits-alternative alternative provider
+    Alternative,
+    /// Rule fallback
+    Fallback,
+    /// Model alias target
+    AliasTarget,
+}
+
+/// A provider the request could be sent to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderCandidate {
+    /// Provider ID
+    pub provider: String,
+    /// Why the provider is a candidate
+    pub role: CandidateRole,
+    /// Model that would be sent upstream
+    pub model: String,
+    /// Circuit breaker state (None if the breaker has never been used)
+    pub circuit: Option<CircuitState>,
+    /// Health monitor status
+    pub health: HealthStatus,
+    /// Reason the provider would be skipped (None = would be tried)
+    pub skipped: Option<SkipReason>,
+}
+
+/// Reason a candidate provider would not be tried
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum SkipReason {
+    /// No provider with this ID is configured
+    NotRegistered,
+    /// The provider's circuit breaker is open
+    CircuitOpen,
+    /// The provider is in rate-limit backoff
+    RateLimited {
+        /// Seconds until the backoff expires
+        retry_after_secs: u64,
+    },
+    /// Limits-alternative diverts: the provider's upstream quota is nearly
+    /// used up
+    QuotaLow {
+        /// Rate-limit window that is low (`requests`, `input_tokens`, ...)
+        kind: String,
+    },
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::NotRegistered => write!(f, "not registered"),
+            SkipReason::CircuitOpen => write!(f, "circuit open"),
+            SkipReason::RateLimited { retry_after_secs } => {
+                write!(f, "rate limited ({}s left)", retry_after_secs)
+            }
+            SkipReason::QuotaLow { kind } => write!(f, "{} quota low", kind),
+        }
+    }
+}
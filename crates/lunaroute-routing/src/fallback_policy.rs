@@ -0,0 +1,275 @@
+//! Error-class-specific fallback policies
+//!
+//! A rule's `fallbacks` list is tried for any failure. `on_error` refines that
+//! per normalized error class: a `content_filter` refusal can be sent to a
+//! different model, an `overloaded` provider retried after a short backoff,
+//! and an `auth` error returned immediately instead of cascading through every
+//! fallback. Classes without a policy keep the default behaviour.
+//!
+//! ```yaml
+//! on_error:
+//!   overloaded: { action: retry, max_retries: 2, backoff_ms: 1000 }
+//!   content_filter: { action: switch, provider: openai, model: gpt-5 }
+//!   auth: { action: fail }
+//! ```
+
+use lunaroute_core::Error;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Maximum retries a `retry` policy may configure
+pub const MAX_POLICY_RETRIES: u32 = 10;
+
+/// Normalized class of an upstream failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    /// 429 or a rate-limit error body
+    RateLimit,
+    /// Provider overloaded (Anthropic 529, `overloaded_error`)
+    Overloaded,
+    /// Prompt exceeds the model's context window
+    ContextLength,
+    /// Request or output refused by a safety filter
+    ContentFilter,
+    /// 401/403 or an authentication/permission error body
+    Auth,
+    /// Upstream or connection timeout
+    Timeout,
+    /// Any other 5xx
+    #[serde(rename = "5xx")]
+    ServerError,
+}
+
+/// `<status> - ` as formatted by the egress connectors' provider errors
+static STATUS_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b([1-5]\d\d) - ").expect("valid status regex"));
+
+impl ErrorClass {
+    /// Classify a provider error (None if it fits no class)
+    pub fn classify(err: &Error) -> Option<Self> {
+        match err {
+            Error::RateLimitExceeded { .. } => Some(Self::RateLimit),
+            Error::Provider(message) => Self::classify_message(message),
+            _ => None,
+        }
+    }
+
+    fn classify_message(message: &str) -> Option<Self> {
+        let status = STATUS_RE
+            .captures(message)
+            .and_then(|c| c[1].parse::<u16>().ok());
+        let lower = message.to_ascii_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|n| lower.contains(n));
+
+        if status == Some(429) || mentions(&["rate_limit_error", "rate limit exceeded"]) {
+            Some(Self::RateLimit)
+        } else if mentions(&[
+            "context_length_exceeded",
+            "prompt is too long",
+            "maximum context length",
+            "context window",
+        ]) {
+            Some(Self::ContextLength)
+        } else if mentions(&[
+            "content_filter",
+            "content_policy",
+            "content management policy",
+        ]) {
+            Some(Self::ContentFilter)
+        } else if matches!(status, Some(401 | 403))
+            || mentions(&[
+                "authentication_error",
+                "permission_error",
+                "invalid_api_key",
+            ])
+        {
+            Some(Self::Auth)
+        } else if status == Some(529) || mentions(&["overloaded"]) {
+            Some(Self::Overloaded)
+        } else if matches!(status, Some(408 | 504)) || mentions(&["timeout", "timed out"]) {
+            Some(Self::Timeout)
+        } else if status.is_some_and(|s| (500..600).contains(&s)) {
+            Some(Self::ServerError)
+        } else {
+            None
+        }
+    }
+
+    /// Config name of the class
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RateLimit => "rate_limit",
+            Self::Overloaded => "overloaded",
+            Self::ContextLength => "context_length",
+            Self::ContentFilter => "content_filter",
+            Self::Auth => "auth",
+            Self::Timeout => "timeout",
+            Self::ServerError => "5xx",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What to do when a request fails with a given error class
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum FallbackAction {
+    /// Retry the same provider with exponential backoff, then continue with
+    /// the rule's fallbacks
+    Retry {
+        #[serde(default = "default_max_retries")]
+        max_retries: u32,
+        /// Delay before the first retry; doubled for each further retry
+        #[serde(default = "default_backoff_ms")]
+        backoff_ms: u64,
+    },
+    /// Send the request to a specific provider (and model) instead
+    Switch {
+        provider: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+    },
+    /// Return the error without trying fallbacks
+    Fail,
+}
+
+fn default_max_retries() -> u32 {
+    1
+}
+
+fn default_backoff_ms() -> u64 {
+    500
+}
+
+impl FallbackAction {
+    /// Delay before retry number `attempt` (0-based)
+    pub fn retry_delay(backoff_ms: u64, attempt: u32) -> Duration {
+        Duration::from_millis(backoff_ms.saturating_mul(1u64 << attempt.min(16)))
+    }
+}
+
+/// Per-class actions for one routing rule
+pub type ErrorPolicies = HashMap<ErrorClass, FallbackAction>;
+
+/// Validate a rule's policies
+pub fn validate_policies(policies: &ErrorPolicies) -> Result<(), String> {
+    for (class, action) in policies {
+        match action {
+            FallbackAction::Retry { max_retries, .. } if *max_retries > MAX_POLICY_RETRIES => {
+                return Err(format!(
+                    "on_error.{}: max_retries must be at most {}",
+                    class, MAX_POLICY_RETRIES
+                ));
+            }
+            FallbackAction::Switch { provider, .. } if provider.is_empty() => {
+                return Err(format!(
+                    "on_error.{}: switch provider cannot be empty",
+                    class
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider_error(message: &str) -> Error {
+        Error::Provider(message.to_string())
+    }
+
+    #[test]
+    fn test_classify_upstream_errors() {
+        let cases = [
+            (
+                Error::RateLimitExceeded {
+                    retry_after_secs: None,
+                },
+                Some(ErrorClass::RateLimit),
+            ),
+            (
+                provider_error(
+                    r#"Provider error: 529 - {"type":"error","error":{"type":"overloaded_error"}}"#,
+                ),
+                Some(ErrorClass::Overloaded),
+            ),
+            (
+                provider_error(
+                    r#"Provider error: 400 - {"error":{"code":"context_length_exceeded"}}"#,
+                ),
+                Some(ErrorClass::ContextLength),
+            ),
+            (
+                provider_error(r#"Provider error: 400 - {"error":{"code":"content_filter"}}"#),
+                Some(ErrorClass::ContentFilter),
+            ),
+            (
+                provider_error(
+                    r#"Provider error: 401 - {"error":{"type":"authentication_error"}}"#,
+                ),
+                Some(ErrorClass::Auth),
+            ),
+            (
+                provider_error("Request timeout after 600s"),
+                Some(ErrorClass::Timeout),
+            ),
+            (
+                provider_error("Provider error: 502 - Bad Gateway"),
+                Some(ErrorClass::ServerError),
+            ),
+            (provider_error("Provider error: 400 - bad request"), None),
+            (Error::Internal("boom".to_string()), None),
+        ];
+        for (err, expected) in cases {
+            assert_eq!(ErrorClass::classify(&err), expected, "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_policies_deserialize_and_validate() {
+        let yaml = r#"
+overloaded: { action: retry, max_retries: 2, backoff_ms: 1000 }
+content_filter: { action: switch, provider: openai, model: gpt-5 }
+auth: { action: fail }
+5xx: { action: retry }
+"#;
+        let policies: ErrorPolicies = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            policies[&ErrorClass::Overloaded],
+            FallbackAction::Retry {
+                max_retries: 2,
+                backoff_ms: 1000
+            }
+        );
+        assert_eq!(
+            policies[&ErrorClass::ServerError],
+            FallbackAction::Retry {
+                max_retries: 1,
+                backoff_ms: 500
+            }
+        );
+        assert_eq!(policies[&ErrorClass::Auth], FallbackAction::Fail);
+        assert!(validate_policies(&policies).is_ok());
+
+        let too_many: ErrorPolicies =
+            serde_yaml::from_str("timeout: { action: retry, max_retries: 50 }").unwrap();
+        assert!(validate_policies(&too_many).is_err());
+
+        assert_eq!(
+            FallbackAction::retry_delay(500, 2),
+            Duration::from_millis(2000)
+        );
+    }
+}
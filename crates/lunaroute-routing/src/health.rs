@@ -4,13 +4,16 @@
 //! latencies, and availability. Used to influence routing decisions
 //! and provide observability.
 
+use crate::persistence::{HealthSnapshot, instant_to_ms, ms_to_instant};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
 /// Health status of a provider
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum HealthStatus {
     /// Provider is healthy (success rate above threshold)
     Healthy,
@@ -117,6 +120,39 @@ impl ProviderHealth {
             .map(|instant| instant.elapsed())
     }
 
+    /// Capture counters and last-event times for persistence
+    fn snapshot(&self) -> HealthSnapshot {
+        let read = |lock: &RwLock<Option<Instant>>| {
+            lock.read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .map(instant_to_ms)
+        };
+        HealthSnapshot {
+            success_count: self.success_count(),
+            failure_count: self.failure_count(),
+            last_success_ms: read(&self.last_success),
+            last_failure_ms: read(&self.last_failure),
+        }
+    }
+
+    /// Restore persisted counters and last-event times
+    fn restore(&self, snapshot: &HealthSnapshot) {
+        self.success_count
+            .store(snapshot.success_count, Ordering::Release);
+        self.failure_count
+            .store(snapshot.failure_count, Ordering::Release);
+        *self
+            .last_success
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) =
+            snapshot.last_success_ms.and_then(ms_to_instant);
+        *self
+            .last_failure
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) =
+            snapshot.last_failure_ms.and_then(ms_to_instant);
+    }
+
     // Note: reset() method removed - not used and would require interior mutability with Arc
     // Consider adding back if needed for future reset functionality
 }
@@ -346,6 +382,31 @@ impl HealthMonitor {
         }
     }
 
+    /// Capture the health window of every monitored provider
+    pub fn snapshot(&self) -> Vec<(String, HealthSnapshot)> {
+        let providers = self
+            .providers
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        providers
+            .iter()
+            .map(|(id, health)| (id.clone(), health.snapshot()))
+            .collect()
+    }
+
+    /// Restore a persisted health window for a registered provider
+    ///
+    /// Snapshots for providers that are no longer registered are ignored.
+    pub fn restore(&self, provider_id: &str, snapshot: &HealthSnapshot) {
+        let providers = self
+            .providers
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(health) = providers.get(provider_id) {
+            health.restore(snapshot);
+        }
+    }
+
     /// Get all healthy providers
     pub fn get_healthy_providers(&self) -> Vec<String> {
         self.get_provider_ids()
@@ -826,4 +887,28 @@ mod tests {
         let status = monitor.get_status("provider1");
         assert_eq!(status, HealthStatus::Degraded); // Below 95% but above 75%
     }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let config = HealthMonitorConfig {
+            min_requests: 2,
+            ..Default::default()
+        };
+        let monitor = HealthMonitor::new(config.clone());
+        monitor.register_provider("provider1");
+        monitor.record_failure("provider1");
+        monitor.record_failure("provider1");
+        assert_eq!(monitor.get_status("provider1"), HealthStatus::Unhealthy);
+
+        let snapshot = monitor.snapshot();
+        assert_eq!(snapshot.len(), 1);
+
+        let restored = HealthMonitor::new(config);
+        restored.register_provider("provider1");
+        restored.restore("provider1", &snapshot[0].1);
+        restored.restore("gone", &snapshot[0].1);
+        assert_eq!(restored.get_status("provider1"), HealthStatus::Unhealthy);
+        assert_eq!(restored.get_metrics("provider1").unwrap().failure_count, 2);
+        assert!(restored.get_metrics("gone").is_none());
+    }
 }
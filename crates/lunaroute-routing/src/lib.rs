@@ -8,6 +8,14 @@
 //! - **Route Table**: Rule-based routing with model patterns and listener matching
 //! - **Health Monitoring**: Track provider success rates and health states
 //! - **Circuit Breakers**: Automatic failover with state machine (Closed/Open/Half-Open)
+//! - **Model Aliases**: Virtual model names and retired-model rewrites
+//! - **Experiments**: Sticky A/B traffic splits across providers, models and prompts
+//! - **Explain**: Dry-run routing decisions without sending traffic
+//! - **State Persistence**: Breaker, health and rate-limit state survive restarts via `StateStore`
+//! - **Active Health Probes**: Background provider checks feed health, breakers and `/readyz`
+//! - **Error Policies**: Retry, switch or fail fast per error class (rate limit, auth, 5xx, ...)
+//! - **Rate-Limit Queue**: Park requests until the earliest reset instead of failing
+//! - **Schedules**: Activate rules by weekday, time of day and date range in a timezone
 //! - **Provider Configuration**: Type-based API detection, env var resolution, custom headers
 //! - **Thread Safety**: Lock-free concurrent access with DashMap and atomic operations
 //!
@@ -34,6 +42,8 @@
 //!     }),
 //!     primary: None,
 //!     fallbacks: vec![],
+//!     on_error: Default::default(),
+//!     schedule: None,
 //! };
 //!
 //! // Create route table
@@ -60,24 +70,48 @@
 //! See the [README](https://github.com/yourusername/lunaroute/blob/main/crates/lunaroute-routing/README.md) for detailed documentation.
 
 pub mod circuit_breaker;
+pub mod experiment;
+pub mod explain;
+pub mod fallback_policy;
 pub mod health;
+pub mod model_alias;
 pub mod notification;
 pub mod path_classifier;
+pub mod persistence;
+pub mod probe;
 pub mod provider_config;
 pub mod provider_router;
+pub mod queue;
 pub mod router;
+pub mod schedule;
 pub mod strategy;
 
 // Re-export commonly used types
 pub use circuit_breaker::{
     CircuitBreaker, CircuitBreakerConfig, CircuitState, SharedCircuitBreaker,
 };
+pub use experiment::{
+    Experiment, ExperimentArm, ExperimentAssignment, ExperimentError, ExperimentSet, PatchMode,
+    StickyKey, SystemPromptPatch,
+};
+pub use explain::{
+    CandidateRole, ExplainStep, ProviderCandidate, RoutingExplanation, RuleEvaluation, RuleOutcome,
+    SkipReason, StrategyChoice,
+};
+pub use fallback_policy::{ErrorClass, ErrorPolicies, FallbackAction};
 pub use health::{HealthMetrics, HealthMonitor, HealthMonitorConfig, HealthStatus};
+pub use model_alias::{
+    AliasTarget, ModelAlias, ModelAliasError, ModelAliasTable, ModelResolution, ParameterOverrides,
+};
 pub use notification::{ProviderSwitchNotificationConfig, SwitchReason};
 pub use path_classifier::PathClassifier;
+pub use persistence::{CircuitSnapshot, HealthSnapshot, RateLimitSnapshot};
+pub use probe::{HealthProbeConfig, ProbeMethod, ProbeOutcome, ProbeResult, ProbeTarget};
 pub use provider_config::{ProviderConfig, ProviderConfigError, ProviderType};
 pub use provider_router::Router;
+pub use queue::{QueueRejection, RateLimitQueue, RateLimitQueueConfig};
 pub use router::{
     ListenerType, RouteTable, RoutingContext, RoutingDecision, RoutingRule, RuleMatcher,
 };
+pub use schedule::{DateRange, Schedule, TimeRange};
 pub use strategy::{RoutingStrategy, StrategyError, StrategyState, WeightedProvider};
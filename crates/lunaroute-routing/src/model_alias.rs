@@ -0,0 +1,407 @@
+//! Virtual model aliases and model rewrite tables
+//!
+//! Lets clients request a stable virtual name (e.g. `team-default`, `fast`)
+//! that resolves to an ordered list of real `(provider, model)` targets, and
+//! transparently rewrites retired model IDs to their successors.
+//!
+//! ## Resolution order
+//!
+//! 1. **Rewrite**: the requested model is followed through the rewrite table
+//!    (`claude-3-opus` → `claude-opus-4-1` → ...) until no rewrite applies.
+//! 2. **Alias**: if the (rewritten) name is an alias, its targets are returned
+//!    in priority order. The first available target should be used, the rest
+//!    act as the fallback chain.
+//!
+//! ```rust
+//! use lunaroute_routing::{AliasTarget, ModelAlias, ModelAliasTable};
+//! use std::collections::HashMap;
+//!
+//! let mut aliases = HashMap::new();
+//! aliases.insert(
+//!     "fast".to_string(),
+//!     ModelAlias {
+//!         description: None,
+//!         targets: vec![AliasTarget {
+//!             provider: "anthropic".to_string(),
+//!             model: "claude-haiku-4-5".to_string(),
+//!             params: Default::default(),
+//!         }],
+//!     },
+//! );
+//! let mut rewrites = HashMap::new();
+//! rewrites.insert("claude-3-haiku".to_string(), "fast".to_string());
+//!
+//! let table = ModelAliasTable::new(aliases, rewrites).unwrap();
+//! let resolution = table.resolve("claude-3-haiku");
+//! assert_eq!(resolution.effective_model(), "fast");
+//! assert_eq!(resolution.targets[0].model, "claude-haiku-4-5");
+//! ```
+
+use lunaroute_core::normalized::NormalizedRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+/// Maximum length of a rewrite chain (guards against misconfiguration)
+const MAX_REWRITE_DEPTH: usize = 16;
+
+/// A virtual model name that resolves to one or more real targets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelAlias {
+    /// Human-readable description (shown in `/v1/models`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Ordered targets: the first available one is used, the rest are fallbacks
+    pub targets: Vec<AliasTarget>,
+}
+
+/// A concrete (provider, model) target of an alias
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AliasTarget {
+    /// Provider ID (e.g. "anthropic", "openai", or an extra provider name)
+    pub provider: String,
+
+    /// Real model ID sent upstream
+    pub model: String,
+
+    /// Parameter overrides applied when this target is selected
+    #[serde(default, skip_serializing_if = "ParameterOverrides::is_empty")]
+    pub params: ParameterOverrides,
+}
+
+/// Per-target request parameter overrides
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParameterOverrides {
+    /// Upper bound for max_tokens (clamps larger client values, fills in when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+
+    /// Temperature to force for this target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Nucleus sampling threshold to force for this target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+}
+
+impl ParameterOverrides {
+    /// True if no overrides are configured
+    pub fn is_empty(&self) -> bool {
+        self.max_tokens.is_none() && self.temperature.is_none() && self.top_p.is_none()
+    }
+
+    /// Apply overrides to a normalized request
+    pub fn apply_to_request(&self, request: &mut NormalizedRequest) {
+        if let Some(limit) = self.max_tokens {
+            request.max_tokens = Some(request.max_tokens.map_or(limit, |v| v.min(limit)));
+        }
+        if let Some(temperature) = self.temperature {
+            request.temperature = Some(temperature);
+        }
+        if let Some(top_p) = self.top_p {
+            request.top_p = Some(top_p);
+        }
+    }
+
+    /// Apply overrides to a raw JSON request body (passthrough mode)
+    ///
+    /// The max_tokens clamp is applied to whichever token limit field the
+    /// dialect uses (`max_tokens`, `max_completion_tokens`, `max_output_tokens`).
+    /// Without one, the limit is added as `max_output_tokens` for Responses
+    /// API bodies (which carry `input` instead of `messages`) and as
+    /// `max_tokens` otherwise.
+    pub fn apply_to_json(&self, body: &mut serde_json::Value) {
+        let Some(obj) = body.as_object_mut() else {
+            return;
+        };
+
+        if let Some(limit) = self.max_tokens {
+            let mut clamped_any = false;
+            for field in ["max_tokens", "max_completion_tokens", "max_output_tokens"] {
+                if let Some(current) = obj.get(field).and_then(|v| v.as_u64()) {
+                    obj.insert(field.to_string(), (current.min(limit as u64)).into());
+                    clamped_any = true;
+                }
+            }
+            if !clamped_any {
+                let responses_api = obj.contains_key("input") && !obj.contains_key("messages");
+                let field = if responses_api {
+                    "max_output_tokens"
+                } else {
+                    "max_tokens"
+                };
+                obj.insert(field.to_string(), limit.into());
+            }
+        }
+        if let Some(temperature) = self.temperature {
+            obj.insert("temperature".to_string(), temperature.into());
+        }
+        if let Some(top_p) = self.top_p {
+            obj.insert("top_p".to_string(), top_p.into());
+        }
+    }
+}
+
+/// Result of resolving a requested model name
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelResolution<'a> {
+    /// Model name exactly as requested by the client
+    pub requested: String,
+
+    /// Name after following the rewrite table (None if no rewrite applied)
+    pub rewritten: Option<String>,
+
+    /// Alias name, if the effective model is a virtual alias
+    pub alias: Option<String>,
+
+    /// Alias targets in priority order (empty if not an alias)
+    pub targets: &'a [AliasTarget],
+}
+
+impl ModelResolution<'_> {
+    /// The model name after rewrites (alias name or real model)
+    pub fn effective_model(&self) -> &str {
+        self.rewritten.as_deref().unwrap_or(&self.requested)
+    }
+
+    /// True if neither a rewrite nor an alias applied
+    pub fn is_unchanged(&self) -> bool {
+        self.rewritten.is_none() && self.alias.is_none()
+    }
+}
+
+/// Validated alias and rewrite tables
+#[derive(Debug, Clone, Default)]
+pub struct ModelAliasTable {
+    aliases: HashMap<String, ModelAlias>,
+    rewrites: HashMap<String, String>,
+}
+
+impl ModelAliasTable {
+    /// Build a table, validating aliases and rewrite chains
+    pub fn new(
+        aliases: HashMap<String, ModelAlias>,
+        rewrites: HashMap<String, String>,
+    ) -> Result<Self, ModelAliasError> {
+        for (name, alias) in &aliases {
+            if alias.targets.is_empty() {
+                return Err(ModelAliasError::EmptyTargets(name.clone()));
+            }
+            if rewrites.contains_key(name) {
+                return Err(ModelAliasError::AliasRewritten(name.clone()));
+            }
+        }
+
+        // Every rewrite chain must terminate
+        for start in rewrites.keys() {
+            let mut seen = HashSet::new();
+            let mut current = start.as_str();
+            while let Some(next) = rewrites.get(current) {
+                if !seen.insert(current) || seen.len() > MAX_REWRITE_DEPTH {
+                    return Err(ModelAliasError::RewriteCycle(start.clone()));
+                }
+                current = next;
+            }
+        }
+
+        Ok(Self { aliases, rewrites })
+    }
+
+    /// True if no aliases or rewrites are configured
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty() && self.rewrites.is_empty()
+    }
+
+    /// Look up an alias by name
+    pub fn get_alias(&self, name: &str) -> Option<&ModelAlias> {
+        self.aliases.get(name)
+    }
+
+    /// Iterate over all aliases (for `/v1/models` listings)
+    pub fn aliases(&self) -> impl Iterator<Item = (&String, &ModelAlias)> {
+        self.aliases.iter()
+    }
+
+    /// Follow the rewrite table for a model ID (None if no rewrite applies)
+    pub fn rewrite(&self, model: &str) -> Option<&str> {
+        let mut current = self.rewrites.get(model)?;
+        while let Some(next) = self.rewrites.get(current) {
+            current = next;
+        }
+        Some(current)
+    }
+
+    /// Resolve a requested model through rewrites and aliases
+    pub fn resolve(&self, requested: &str) -> ModelResolution<'_> {
+        let rewritten = self.rewrite(requested).map(|s| s.to_string());
+        let effective = rewritten.as_deref().unwrap_or(requested);
+        let (alias, targets) = match self.aliases.get_key_value(effective) {
+            Some((name, alias)) => (Some(name.clone()), alias.targets.as_slice()),
+            None => (None, &[][..]),
+        };
+
+        ModelResolution {
+            requested: requested.to_string(),
+            rewritten,
+            alias,
+            targets,
+        }
+    }
+}
+
+/// Model alias configuration errors
+#[derive(Debug, Error, PartialEq)]
+pub enum ModelAliasError {
+    #[error("Model alias '{0}' has no targets")]
+    EmptyTargets(String),
+
+    #[error("Model alias '{0}' is also the source of a model rewrite")]
+    AliasRewritten(String),
+
+    #[error("Model rewrite chain starting at '{0}' does not terminate")]
+    RewriteCycle(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn target(provider: &str, model: &str) -> AliasTarget {
+        AliasTarget {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            params: ParameterOverrides::default(),
+        }
+    }
+
+    fn table() -> ModelAliasTable {
+        let mut aliases = HashMap::new();
+        aliases.insert(
+            "team-default".to_string(),
+            ModelAlias {
+                description: Some("Default team model".to_string()),
+                targets: vec![
+                    target("anthropic", "claude-sonnet-4-5"),
+                    target("openai", "gpt-5"),
+                ],
+            },
+        );
+        let mut rewrites = HashMap::new();
+        rewrites.insert("claude-3-opus".to_string(), "claude-opus-4".to_string());
+        rewrites.insert("claude-opus-4".to_string(), "claude-opus-4-1".to_string());
+        rewrites.insert("gpt-4".to_string(), "team-default".to_string());
+        ModelAliasTable::new(aliases, rewrites).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_alias() {
+        let table = table();
+        let resolution = table.resolve("team-default");
+        assert_eq!(resolution.alias.as_deref(), Some("team-default"));
+        assert_eq!(resolution.targets.len(), 2);
+        assert_eq!(resolution.targets[0].provider, "anthropic");
+        assert!(resolution.rewritten.is_none());
+    }
+
+    #[test]
+    fn test_resolve_rewrite_chain() {
+        let table = table();
+        let resolution = table.resolve("claude-3-opus");
+        assert_eq!(resolution.rewritten.as_deref(), Some("claude-opus-4-1"));
+        assert_eq!(resolution.effective_model(), "claude-opus-4-1");
+        assert!(resolution.alias.is_none());
+        assert!(resolution.targets.is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_to_alias() {
+        let table = table();
+        let resolution = table.resolve("gpt-4");
+        assert_eq!(resolution.alias.as_deref(), Some("team-default"));
+        assert_eq!(resolution.targets[1].model, "gpt-5");
+    }
+
+    #[test]
+    fn test_unknown_model_unchanged() {
+        let table = table();
+        let resolution = table.resolve("gpt-5-mini");
+        assert!(resolution.is_unchanged());
+        assert_eq!(resolution.effective_model(), "gpt-5-mini");
+    }
+
+    #[test]
+    fn test_rewrite_cycle_rejected() {
+        let mut rewrites = HashMap::new();
+        rewrites.insert("a".to_string(), "b".to_string());
+        rewrites.insert("b".to_string(), "a".to_string());
+        assert!(matches!(
+            ModelAliasTable::new(HashMap::new(), rewrites),
+            Err(ModelAliasError::RewriteCycle(_))
+        ));
+    }
+
+    #[test]
+    fn test_empty_alias_rejected() {
+        let mut aliases = HashMap::new();
+        aliases.insert(
+            "fast".to_string(),
+            ModelAlias {
+                description: None,
+                targets: vec![],
+            },
+        );
+        assert_eq!(
+            ModelAliasTable::new(aliases, HashMap::new()).unwrap_err(),
+            ModelAliasError::EmptyTargets("fast".to_string())
+        );
+    }
+
+    #[test]
+    fn test_params_clamp_json() {
+        let params = ParameterOverrides {
+            max_tokens: Some(1000),
+            temperature: Some(0.2),
+            top_p: None,
+        };
+
+        let mut body = json!({"model": "x", "max_tokens": 4096});
+        params.apply_to_json(&mut body);
+        assert_eq!(body["max_tokens"], 1000);
+        assert!((body["temperature"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+
+        let mut body = json!({"model": "x", "max_completion_tokens": 500});
+        params.apply_to_json(&mut body);
+        assert_eq!(body["max_completion_tokens"], 500);
+        assert!(body.get("max_tokens").is_none());
+
+        let mut body = json!({"model": "x"});
+        params.apply_to_json(&mut body);
+        assert_eq!(body["max_tokens"], 1000);
+
+        // Responses API bodies take the limit as max_output_tokens
+        let mut body = json!({"model": "x", "input": "hi"});
+        params.apply_to_json(&mut body);
+        assert_eq!(body["max_output_tokens"], 1000);
+        assert!(body.get("max_tokens").is_none());
+    }
+
+    #[test]
+    fn test_alias_yaml() {
+        let yaml = r#"
+targets:
+  - provider: anthropic
+    model: claude-haiku-4-5
+    params:
+      max_tokens: 2048
+  - provider: openai
+    model: gpt-5-mini
+"#;
+        let alias: ModelAlias = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(alias.targets.len(), 2);
+        assert_eq!(alias.targets[0].params.max_tokens, Some(2048));
+        assert!(alias.targets[1].params.is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+//! Routing state persistence
+//!
+//! Circuit breakers, health windows and rate-limit backoffs live in memory and
+//! use monotonic [`Instant`]s. This module defines wall-clock snapshots of that
+//! state so a [`Router`](crate::Router) can save it to a
+//! [`StateStore`](lunaroute_storage::StateStore) and restore it after a
+//! restart. Snapshots are JSON-encoded under these key prefixes:
+//!
+//! - `routing:circuit:<provider>`
+//! - `routing:health:<provider>`
+//! - `routing:rate_limit:<rule>:<provider>`
+
+use crate::circuit_breaker::CircuitState;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Key prefix for circuit breaker snapshots
+pub const CIRCUIT_KEY_PREFIX: &str = "routing:circuit:";
+
+/// Key prefix for health snapshots
+pub const HEALTH_KEY_PREFIX: &str = "routing:health:";
+
+/// Key prefix for rate-limit snapshots
+pub const RATE_LIMIT_KEY_PREFIX: &str = "routing:rate_limit:";
+
+/// Persisted circuit breaker state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitSnapshot {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub consecutive_successes: u32,
+    /// Unix time (ms) of the last state change
+    pub changed_at_ms: u64,
+}
+
+/// Persisted provider health window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthSnapshot {
+    pub success_count: u64,
+    pub failure_count: u64,
+    /// Unix time (ms) of the last success
+    pub last_success_ms: Option<u64>,
+    /// Unix time (ms) of the last failure
+    pub last_failure_ms: Option<u64>,
+}
+
+/// Persisted rate-limit backoff for one provider under one rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitSnapshot {
+    pub rule: String,
+    pub provider: String,
+    /// Unix time (ms) when the backoff expires
+    pub until_ms: u64,
+    pub consecutive_rate_limits: u32,
+    /// Unix time (ms) of the last rate-limit response
+    pub last_rate_limit_ms: u64,
+}
+
+impl RateLimitSnapshot {
+    /// Whether the backoff has already expired
+    pub fn is_expired(&self) -> bool {
+        self.until_ms <= now_ms()
+    }
+}
+
+/// Current Unix time in milliseconds
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Convert a monotonic instant to Unix milliseconds
+pub(crate) fn instant_to_ms(instant: Instant) -> u64 {
+    let now = Instant::now();
+    let wall = now_ms();
+    if instant <= now {
+        wall.saturating_sub(now.duration_since(instant).as_millis() as u64)
+    } else {
+        wall.saturating_add(instant.duration_since(now).as_millis() as u64)
+    }
+}
+
+/// Convert Unix milliseconds back to a monotonic instant
+///
+/// Returns None when the time predates what `Instant` can represent on this
+/// host (shortly after boot); such timestamps are old enough to ignore.
+pub(crate) fn ms_to_instant(ms: u64) -> Option<Instant> {
+    let now = Instant::now();
+    let wall = now_ms();
+    if ms >= wall {
+        Some(now + Duration::from_millis(ms - wall))
+    } else {
+        now.checked_sub(Duration::from_millis(wall - ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instant_round_trip() {
+        let past = Instant::now() - Duration::from_millis(500);
+        let restored = ms_to_instant(instant_to_ms(past)).unwrap();
+        let drift = if restored > past {
+            restored - past
+        } else {
+            past - restored
+        };
+        assert!(drift < Duration::from_millis(50));
+
+        let future = Instant::now() + Duration::from_secs(600);
+        let remaining = ms_to_instant(instant_to_ms(future)).unwrap() - Instant::now();
+        assert!(remaining > Duration::from_secs(599));
+    }
+}
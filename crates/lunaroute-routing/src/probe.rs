@@ -0,0 +1,217 @@
+//! Active health probes
+//!
+//! The [`HealthMonitor`](crate::HealthMonitor) and circuit breakers normally
+//! learn only from live traffic. A prober checks each provider in the
+//! background — with the provider's cheap
+//! [`health_check`](lunaroute_core::provider::Provider::health_check) (e.g.
+//! `GET /v1/models`) or a one-token completion — and feeds the result into the
+//! same health monitor and breakers. See
+//! [`Router::spawn_health_probes`](crate::Router::spawn_health_probes).
+//!
+//! Once a provider has been probed successfully, an open circuit is tested by
+//! the prober instead of by a user request: user traffic only resumes after
+//! the probes have closed the circuit.
+
+use crate::persistence::now_ms;
+use lunaroute_core::normalized::{Message, MessageContent, NormalizedRequest, Role};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+/// How a provider is probed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeMethod {
+    /// The provider's cheap health check (model listing); falls back to a
+    /// completion when the provider has none and a model is configured
+    #[default]
+    Models,
+    /// A one-token completion against the configured model
+    Completion,
+}
+
+/// A provider to probe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeTarget {
+    /// Provider ID
+    pub provider: String,
+    /// Probe method
+    pub method: ProbeMethod,
+    /// Model for completion probes
+    pub model: Option<String>,
+}
+
+impl ProbeTarget {
+    /// Probe a provider with its cheap health check
+    pub fn models(provider: impl Into<String>) -> Self {
+        Self {
+            provider: provider.into(),
+            method: ProbeMethod::Models,
+            model: None,
+        }
+    }
+
+    /// Probe a provider with a one-token completion
+    pub fn completion(provider: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            provider: provider.into(),
+            method: ProbeMethod::Completion,
+            model: Some(model.into()),
+        }
+    }
+
+    /// Minimal request used by completion probes
+    pub(crate) fn completion_request(model: &str) -> NormalizedRequest {
+        NormalizedRequest {
+            messages: vec![Message {
+                role: Role::User,
+                content: MessageContent::Text("ping".to_string()),
+                name: None,
+                tool_calls: vec![],
+                tool_call_id: None,
+            }],
+            system: None,
+            model: model.to_string(),
+            max_tokens: Some(1),
+            temperature: None,
+            top_p: None,
+            top_k: None,
+            stop_sequences: vec![],
+            stream: false,
+            tools: vec![],
+            tool_choice: None,
+            tool_results: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+}
+
+/// Prober timing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthProbeConfig {
+    /// Time between probes of one provider
+    pub interval: Duration,
+    /// Random extra delay (0..=jitter) added to each interval, so replicas and
+    /// providers are not probed in lockstep
+    pub jitter: Duration,
+    /// Per-probe timeout
+    pub timeout: Duration,
+}
+
+impl Default for HealthProbeConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            jitter: Duration::from_secs(5),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl HealthProbeConfig {
+    /// Delay before the next probe: interval plus a random share of jitter
+    pub(crate) fn next_delay(&self) -> Duration {
+        let jitter_ms = self.jitter.as_millis() as u64;
+        if jitter_ms == 0 {
+            return self.interval;
+        }
+        let random = std::collections::hash_map::RandomState::new().hash_one(now_ms());
+        self.interval + Duration::from_millis(random % (jitter_ms + 1))
+    }
+}
+
+/// Result of one probe
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum ProbeOutcome {
+    /// The provider answered
+    Ok,
+    /// The provider failed or timed out
+    Failed {
+        /// Error message
+        error: String,
+    },
+    /// The circuit is open and its timeout has not expired yet
+    CircuitOpen,
+    /// The provider was not probed
+    Skipped {
+        /// Why the probe did not run
+        reason: String,
+    },
+}
+
+/// Latest probe of a provider
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeResult {
+    /// Provider ID
+    pub provider: String,
+    /// Probe outcome
+    #[serde(flatten)]
+    pub outcome: ProbeOutcome,
+    /// Probe duration in milliseconds
+    pub latency_ms: u64,
+    /// Unix time (ms) the probe finished
+    pub at_ms: u64,
+}
+
+impl ProbeResult {
+    /// Whether the provider is under active probing (the probe reached it,
+    /// or is waiting out an open circuit)
+    pub fn ran(&self) -> bool {
+        !matches!(self.outcome, ProbeOutcome::Skipped { .. })
+    }
+}
+
+impl std::fmt::Display for ProbeOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProbeOutcome::Ok => write!(f, "ok"),
+            ProbeOutcome::CircuitOpen => write!(f, "circuit open"),
+            ProbeOutcome::Failed { error } => write!(f, "failed: {}", error),
+            ProbeOutcome::Skipped { reason } => write!(f, "skipped: {}", reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_delay_within_jitter() {
+        let config = HealthProbeConfig {
+            interval: Duration::from_secs(10),
+            jitter: Duration::from_millis(500),
+            timeout: Duration::from_secs(1),
+        };
+        for _ in 0..50 {
+            let delay = config.next_delay();
+            assert!(delay >= Duration::from_secs(10));
+            assert!(delay <= Duration::from_millis(10_500));
+        }
+
+        let no_jitter = HealthProbeConfig {
+            jitter: Duration::ZERO,
+            ..config
+        };
+        assert_eq!(no_jitter.next_delay(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_probe_result_serialization() {
+        let result = ProbeResult {
+            provider: "openai".to_string(),
+            outcome: ProbeOutcome::Failed {
+                error: "timeout".to_string(),
+            },
+            latency_ms: 10_000,
+            at_ms: 1,
+        };
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["result"], "failed");
+        assert_eq!(json["error"], "timeout");
+        assert!(result.ran());
+        assert_eq!(result.outcome.to_string(), "failed: timeout");
+    }
+}
@@ -2,8 +2,10 @@
 //!
 //! Defines provider types, configuration, and environment variable resolution.
 
+use lunaroute_core::secret_source::{SecretSource, SecretSourceError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// Provider type determines API format/dialect
@@ -34,8 +36,17 @@ pub struct ProviderConfig {
     pub provider_type: ProviderType,
 
     /// API key (supports env var syntax: $VAR_NAME or ${VAR_NAME})
+    #[serde(default)]
     pub api_key: String,
 
+    /// Command printing the API key, used when `api_key` is empty
+    #[serde(default)]
+    pub api_key_command: Option<String>,
+
+    /// File containing the API key, used when `api_key` and `api_key_command` are empty
+    #[serde(default)]
+    pub api_key_file: Option<PathBuf>,
+
     /// Base URL (optional, defaults based on provider type)
     #[serde(default)]
     pub base_url: Option<String>,
@@ -57,11 +68,26 @@ impl ProviderConfig {
             .unwrap_or_else(|| self.provider_type.default_base_url())
     }
 
+    /// Command or file the API key is read from, if configured
+    pub fn secret_source(&self) -> Option<SecretSource> {
+        match (&self.api_key_command, &self.api_key_file) {
+            (Some(command), _) => Some(SecretSource::command(command.clone())),
+            (None, Some(path)) => Some(SecretSource::file(path.clone())),
+            (None, None) => None,
+        }
+    }
+
     /// Resolve environment variables in configuration
-    /// Replaces $VAR_NAME or ${VAR_NAME} with actual env var values
-    pub fn resolve_env_vars(&mut self) -> Result<(), ProviderConfigError> {
+    /// Replaces $VAR_NAME or ${VAR_NAME} with actual env var values, and
+    /// fills an empty API key from `api_key_command` or `api_key_file`
+    pub async fn resolve_env_vars(&mut self) -> Result<(), ProviderConfigError> {
         // Resolve API key
         self.api_key = resolve_env_var(&self.api_key)?;
+        if self.api_key.is_empty()
+            && let Some(source) = self.secret_source()
+        {
+            self.api_key = source.fetch().await?;
+        }
 
         // Resolve headers
         for (key, value) in self.headers.iter_mut() {
@@ -109,6 +135,9 @@ pub enum ProviderConfigError {
         #[source]
         source: Box<ProviderConfigError>,
     },
+
+    #[error(transparent)]
+    SecretSource(#[from] SecretSourceError),
 }
 
 #[cfg(test)]
@@ -132,6 +161,8 @@ mod tests {
         let config = ProviderConfig {
             provider_type: ProviderType::OpenAI,
             api_key: "test-key".to_string(),
+            api_key_command: None,
+            api_key_file: None,
             base_url: None,
             headers: HashMap::new(),
             timeout_secs: None,
@@ -145,6 +176,8 @@ mod tests {
         let config = ProviderConfig {
             provider_type: ProviderType::OpenAI,
             api_key: "test-key".to_string(),
+            api_key_command: None,
+            api_key_file: None,
             base_url: Some("https://custom.com/v1".to_string()),
             headers: HashMap::new(),
             timeout_secs: None,
@@ -188,9 +221,9 @@ mod tests {
         );
     }
 
-    #[test]
+    #[tokio::test]
     #[serial_test::serial]
-    fn test_provider_config_resolve_api_key() {
+    async fn test_provider_config_resolve_api_key() {
         unsafe {
             std::env::set_var("TEST_API_KEY_456", "secret-key");
         }
@@ -198,12 +231,14 @@ mod tests {
         let mut config = ProviderConfig {
             provider_type: ProviderType::OpenAI,
             api_key: "$TEST_API_KEY_456".to_string(),
+            api_key_command: None,
+            api_key_file: None,
             base_url: None,
             headers: HashMap::new(),
             timeout_secs: None,
         };
 
-        config.resolve_env_vars().unwrap();
+        config.resolve_env_vars().await.unwrap();
         assert_eq!(config.api_key, "secret-key");
 
         unsafe {
@@ -211,9 +246,9 @@ mod tests {
         }
     }
 
-    #[test]
+    #[tokio::test]
     #[serial_test::serial]
-    fn test_provider_config_resolve_headers() {
+    async fn test_provider_config_resolve_headers() {
         unsafe {
             std::env::set_var("TEST_HEADER_VAL", "header-value");
         }
@@ -225,12 +260,14 @@ mod tests {
         let mut config = ProviderConfig {
             provider_type: ProviderType::OpenAI,
             api_key: "key".to_string(),
+            api_key_command: None,
+            api_key_file: None,
             base_url: None,
             headers,
             timeout_secs: None,
         };
 
-        config.resolve_env_vars().unwrap();
+        config.resolve_env_vars().await.unwrap();
         assert_eq!(config.headers.get("X-Custom").unwrap(), "header-value");
         assert_eq!(config.headers.get("X-Static").unwrap(), "static-value");
 
@@ -239,11 +276,31 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_provider_config_api_key_from_command() {
+        let mut config: ProviderConfig = serde_json::from_str(
+            r#"{"type": "anthropic", "api_key_command": "echo sk-from-command"}"#,
+        )
+        .unwrap();
+        config.resolve_env_vars().await.unwrap();
+        assert_eq!(config.api_key, "sk-from-command");
+
+        let mut failing: ProviderConfig =
+            serde_json::from_str(r#"{"type": "openai", "api_key_command": "false"}"#).unwrap();
+        assert!(matches!(
+            failing.resolve_env_vars().await,
+            Err(ProviderConfigError::SecretSource(_))
+        ));
+    }
+
     #[test]
     fn test_provider_config_serde() {
         let config = ProviderConfig {
             provider_type: ProviderType::Anthropic,
             api_key: "$ANTHROPIC_KEY".to_string(),
+            api_key_command: None,
+            api_key_file: None,
             base_url: Some("https://custom.anthropic.com".to_string()),
             headers: {
                 let mut h = HashMap::new();
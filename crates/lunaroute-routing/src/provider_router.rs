@@ -7,27 +7,44 @@
 //! - Fallback chains for resilience
 
 use crate::{
-    circuit_breaker::{CircuitBreaker, CircuitBreakerConfig},
-    health::{HealthMonitor, HealthMonitorConfig},
+    circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState},
+    explain::{
+        CandidateRole, ProviderCandidate, RoutingExplanation, RuleEvaluation, RuleOutcome,
+        SkipReason, StrategyChoice,
+    },
+    fallback_policy::{ErrorClass, ErrorPolicies, FallbackAction},
+    health::{HealthMonitor, HealthMonitorConfig, HealthStatus},
+    model_alias::{AliasTarget, ModelAliasTable},
     notification::{
         ProviderSwitchNotificationConfig, SwitchReason, build_notification_message,
         has_notification_already,
     },
-    router::{RouteTable, RoutingContext},
-    strategy::{RoutingStrategy, StrategyState},
+    persistence::{
+        CIRCUIT_KEY_PREFIX, CircuitSnapshot, HEALTH_KEY_PREFIX, HealthSnapshot,
+        RATE_LIMIT_KEY_PREFIX, RateLimitSnapshot,
+    },
+    probe::{HealthProbeConfig, ProbeOutcome, ProbeResult, ProbeTarget},
+    queue::{FAIRNESS_METADATA_KEYS, RateLimitQueue, RateLimitQueueConfig},
+    router::{RouteTable, RoutingContext, RoutingDecision},
+    strategy::{RoutingStrategy, StrategyError, StrategyState},
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use lunaroute_core::{
     error::{Error, Result},
+    identity::RequestScope,
     normalized::{
         Message, MessageContent, NormalizedRequest, NormalizedResponse, NormalizedStreamEvent, Role,
     },
     provider::{Provider, ProviderCapabilities},
 };
-use lunaroute_observability::metrics::Metrics;
+use lunaroute_observability::{ProviderStatus, ReadinessChecker, metrics::Metrics};
+use lunaroute_storage::{StateStore, StorageError, StorageResult};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
 use tokio_stream::Stream;
 use tracing::{debug, info, warn};
 
@@ -56,6 +73,18 @@ pub struct Router {
 
     /// Provider switch notification configuration
     notification_config: Option<ProviderSwitchNotificationConfig>,
+
+    /// Virtual model aliases and retired-model rewrites (optional)
+    model_aliases: Option<Arc<ModelAliasTable>>,
+
+    /// Store for circuit breaker, health and rate-limit state (optional)
+    state_store: Option<Arc<dyn StateStore>>,
+
+    /// Latest active health probe per provider
+    probe_results: DashMap<String, ProbeResult>,
+
+    /// Wait queue for requests that find every provider rate-limited (optional)
+    rate_limit_queue: Option<Arc<RateLimitQueue>>,
 }
 
 impl Router {
@@ -84,9 +113,437 @@ impl Router {
             strategy_states: DashMap::new(),
             metrics,
             notification_config,
+            model_aliases: None,
+            state_store: None,
+            probe_results: DashMap::new(),
+            rate_limit_queue: None,
+        }
+    }
+
+    /// Attach a model alias table
+    ///
+    /// Requests for an alias bypass the route table and are sent to the alias
+    /// targets in order; retired model IDs are rewritten before routing.
+    pub fn with_model_aliases(mut self, model_aliases: Arc<ModelAliasTable>) -> Self {
+        self.model_aliases = Some(model_aliases);
+        self
+    }
+
+    /// Attach a state store
+    ///
+    /// Circuit breaker transitions and rate-limit backoffs are written through
+    /// to the store as they happen; call [`restore_state`](Self::restore_state)
+    /// at startup and [`spawn_state_sync`](Self::spawn_state_sync) to save
+    /// health windows periodically and, with a shared store, pick up state
+    /// written by other instances.
+    pub fn with_state_store(mut self, state_store: Arc<dyn StateStore>) -> Self {
+        self.state_store = Some(state_store);
+        self
+    }
+
+    /// Park requests instead of failing when every provider is rate-limited
+    ///
+    /// Applies to limits-alternative rules without fallbacks: the request
+    /// waits for the earliest known reset, up to `config.max_wait` in total.
+    /// Streaming requests return their stream right away and start it once a
+    /// provider is available, so the ingress keepalive comments keep the
+    /// client connection open while parked.
+    pub fn with_rate_limit_queue(mut self, config: RateLimitQueueConfig) -> Self {
+        self.rate_limit_queue = Some(Arc::new(RateLimitQueue::new(config)));
+        self
+    }
+
+    /// Restore routing state saved by a previous process
+    ///
+    /// Returns the number of entries restored. State for providers that are no
+    /// longer configured and expired rate-limit backoffs are skipped.
+    pub async fn restore_state(&self) -> StorageResult<usize> {
+        let Some(store) = &self.state_store else {
+            return Ok(0);
+        };
+
+        let mut restored = self.merge_shared_state(store.as_ref()).await?;
+        for (key, value) in Self::load_prefix(store.as_ref(), HEALTH_KEY_PREFIX).await? {
+            let provider = &key[HEALTH_KEY_PREFIX.len()..];
+            if !self.providers.contains_key(provider) {
+                continue;
+            }
+            let snapshot: HealthSnapshot = Self::decode(&key, &value)?;
+            self.health_monitor.restore(provider, &snapshot);
+            restored += 1;
+        }
+
+        Ok(restored)
+    }
+
+    /// Pull circuit breaker and rate-limit state written by other instances,
+    /// then save local state
+    ///
+    /// Newer breaker transitions and longer backoffs win. Health windows are
+    /// per instance and only restored at startup. Returns the number of
+    /// entries adopted from the store.
+    pub async fn sync_state(&self) -> StorageResult<usize> {
+        let Some(store) = &self.state_store else {
+            return Ok(0);
+        };
+        let merged = self.merge_shared_state(store.as_ref()).await?;
+        self.persist_state().await?;
+        Ok(merged)
+    }
+
+    /// Merge circuit breakers and rate-limit backoffs from the store
+    async fn merge_shared_state(&self, store: &dyn StateStore) -> StorageResult<usize> {
+        let mut merged = 0;
+
+        for (key, value) in Self::load_prefix(store, CIRCUIT_KEY_PREFIX).await? {
+            let provider = &key[CIRCUIT_KEY_PREFIX.len()..];
+            if !self.providers.contains_key(provider) {
+                continue;
+            }
+            let snapshot: CircuitSnapshot = Self::decode(&key, &value)?;
+            if self.get_circuit_breaker(provider).merge(&snapshot) {
+                merged += 1;
+            }
+        }
+
+        for (key, value) in Self::load_prefix(store, RATE_LIMIT_KEY_PREFIX).await? {
+            let snapshot: RateLimitSnapshot = Self::decode(&key, &value)?;
+            if !self.providers.contains_key(&snapshot.provider) {
+                continue;
+            }
+            if self
+                .get_strategy_state(&snapshot.rule)
+                .restore_rate_limit(&snapshot)
+            {
+                merged += 1;
+            }
+        }
+
+        Ok(merged)
+    }
+
+    /// Save circuit breaker, health and rate-limit state to the store
+    ///
+    /// Rate-limit backoffs are stored with a TTL matching their remaining
+    /// duration, so they disappear from the store once they expire.
+    pub async fn persist_state(&self) -> StorageResult<()> {
+        let Some(store) = &self.state_store else {
+            return Ok(());
+        };
+
+        let mut items = Vec::new();
+        for entry in self.circuit_breakers.iter() {
+            items.push((
+                format!("{}{}", CIRCUIT_KEY_PREFIX, entry.key()),
+                Self::encode(&entry.value().snapshot())?,
+            ));
+        }
+        for (provider, snapshot) in self.health_monitor.snapshot() {
+            items.push((
+                format!("{}{}", HEALTH_KEY_PREFIX, provider),
+                Self::encode(&snapshot)?,
+            ));
+        }
+        store.set_many(items).await?;
+
+        for entry in self.strategy_states.iter() {
+            for snapshot in entry.value().rate_limit_snapshots(entry.key()) {
+                Self::store_rate_limit(store.as_ref(), &snapshot).await?;
+            }
+        }
+
+        store.persist().await
+    }
+
+    /// Write one provider's breaker and backoff through to the store
+    ///
+    /// Only the entries that changed are written, so a stale view of other
+    /// providers never overwrites state saved by another instance.
+    async fn write_through(&self, provider_id: &str, rule_name: Option<&str>) {
+        let Some(store) = &self.state_store else {
+            return;
+        };
+        let result = async {
+            let circuit = self.get_circuit_breaker(provider_id).snapshot();
+            store
+                .set(
+                    &format!("{}{}", CIRCUIT_KEY_PREFIX, provider_id),
+                    Self::encode(&circuit)?,
+                )
+                .await?;
+            if let Some(rule) = rule_name
+                && let Some(state) = self.strategy_states.get(rule)
+            {
+                for snapshot in state.rate_limit_snapshots(rule) {
+                    if snapshot.provider == provider_id {
+                        Self::store_rate_limit(store.as_ref(), &snapshot).await?;
+                    }
+                }
+            }
+            store.persist().await
+        }
+        .await;
+
+        if let Err(e) = result {
+            warn!(provider = provider_id, error = %e, "Failed to persist routing state");
+        }
+    }
+
+    /// Sync state in the background every `interval`
+    ///
+    /// Returns None when no state store is attached.
+    pub fn spawn_state_sync(
+        self: &Arc<Self>,
+        interval: Duration,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        self.state_store.as_ref()?;
+        let router = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await; // first tick completes immediately
+            loop {
+                ticker.tick().await;
+                match router.sync_state().await {
+                    Ok(0) => {}
+                    Ok(merged) => {
+                        debug!(merged, "Adopted routing state from other instances")
+                    }
+                    Err(e) => warn!(error = %e, "Failed to sync routing state"),
+                }
+            }
+        }))
+    }
+
+    /// Probe every target in the background
+    ///
+    /// Each target gets its own task that probes, then sleeps for the
+    /// configured interval plus jitter.
+    pub fn spawn_health_probes(
+        self: &Arc<Self>,
+        targets: Vec<ProbeTarget>,
+        config: HealthProbeConfig,
+    ) -> Vec<tokio::task::JoinHandle<()>> {
+        targets
+            .into_iter()
+            .map(|target| {
+                let router = Arc::clone(self);
+                tokio::spawn(async move {
+                    loop {
+                        let result = router.probe_provider(&target, config.timeout).await;
+                        debug!(
+                            provider = %target.provider,
+                            outcome = %result.outcome,
+                            latency_ms = result.latency_ms,
+                            "Health probe finished"
+                        );
+                        tokio::time::sleep(config.next_delay()).await;
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Probe one provider now and record the result
+    ///
+    /// Successes and failures update the health monitor and circuit breaker
+    /// like live traffic. An open circuit is left alone until its timeout
+    /// expires; then the probe moves it to half-open and repeats until the
+    /// breaker closes or a probe fails.
+    pub async fn probe_provider(&self, target: &ProbeTarget, timeout: Duration) -> ProbeResult {
+        let started = std::time::Instant::now();
+        let outcome = self.run_probe(target, timeout).await;
+        if let ProbeOutcome::Failed { error } = &outcome {
+            warn!(provider = %target.provider, error = %error, "Health probe failed");
+        }
+
+        let result = ProbeResult {
+            provider: target.provider.clone(),
+            outcome,
+            latency_ms: started.elapsed().as_millis() as u64,
+            at_ms: crate::persistence::now_ms(),
+        };
+        self.probe_results
+            .insert(target.provider.clone(), result.clone());
+        result
+    }
+
+    /// IDs of the providers this router can send to, sorted
+    pub fn provider_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.providers.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Latest probe result for a provider
+    pub fn probe_result(&self, provider_id: &str) -> Option<ProbeResult> {
+        self.probe_results.get(provider_id).map(|r| r.clone())
+    }
+
+    async fn run_probe(&self, target: &ProbeTarget, timeout: Duration) -> ProbeOutcome {
+        let Some(provider) = self.providers.get(&target.provider) else {
+            return ProbeOutcome::Skipped {
+                reason: "not registered".to_string(),
+            };
+        };
+        let breaker = self.get_circuit_breaker(&target.provider);
+        if !breaker.would_allow_request() {
+            return ProbeOutcome::CircuitOpen;
+        }
+        let state_before = breaker.state();
+
+        let mut outcome = ProbeOutcome::Ok;
+        let attempts = self.circuit_breaker_config.success_threshold.max(1);
+        for attempt in 0..attempts {
+            let Some(result) = Self::check_provider(provider.as_ref(), target, timeout).await
+            else {
+                return ProbeOutcome::Skipped {
+                    reason: "no health check available; configure a probe model".to_string(),
+                };
+            };
+            if attempt == 0 {
+                // Moves an expired open circuit to half-open
+                breaker.allow_request();
+            }
+
+            match result {
+                Ok(()) => {
+                    breaker.record_success();
+                    self.health_monitor.record_success(&target.provider);
+                }
+                Err(e) => {
+                    breaker.record_failure();
+                    self.health_monitor.record_failure(&target.provider);
+                    outcome = ProbeOutcome::Failed {
+                        error: e.to_string(),
+                    };
+                }
+            }
+            if breaker.state() != CircuitState::HalfOpen {
+                break;
+            }
+        }
+
+        if breaker.state() != state_before {
+            info!(
+                provider = %target.provider,
+                from = ?state_before,
+                to = ?breaker.state(),
+                "Health probe changed circuit state"
+            );
+            self.write_through(&target.provider, None).await;
+        }
+        outcome
+    }
+
+    async fn check_provider(
+        provider: &dyn Provider,
+        target: &ProbeTarget,
+        timeout: Duration,
+    ) -> Option<Result<()>> {
+        let check = async {
+            if target.method == crate::probe::ProbeMethod::Models
+                && let Some(result) = provider.health_check().await
+            {
+                return Some(result);
+            }
+            let model = target.model.as_deref()?;
+            Some(
+                provider
+                    .send(ProbeTarget::completion_request(model))
+                    .await
+                    .map(|_| ()),
+            )
+        };
+        match tokio::time::timeout(timeout, check).await {
+            Ok(result) => result,
+            Err(_) => Some(Err(Error::Provider(format!(
+                "probe timed out after {:?}",
+                timeout
+            )))),
+        }
+    }
+
+    /// Whether an actively probed provider's breaker gates user traffic
+    ///
+    /// Probed providers only take user requests while their circuit is
+    /// closed; recovery from open is tested by the prober.
+    fn is_probed(&self, provider_id: &str) -> bool {
+        self.probe_results
+            .get(provider_id)
+            .is_some_and(|result| result.ran())
+    }
+
+    /// Let a user request through the provider's circuit breaker
+    fn circuit_allows(&self, provider_id: &str, breaker: &CircuitBreaker) -> bool {
+        if self.is_probed(provider_id) {
+            breaker.state() == CircuitState::Closed
+        } else {
+            breaker.allow_request()
         }
     }
 
+    /// Like [`circuit_allows`](Self::circuit_allows), without side effects
+    fn circuit_would_allow(&self, provider_id: &str, breaker: &CircuitBreaker) -> bool {
+        if self.is_probed(provider_id) {
+            breaker.state() == CircuitState::Closed
+        } else {
+            breaker.would_allow_request()
+        }
+    }
+
+    /// Whether a provider would take a user request right now
+    fn is_available(&self, provider_id: &str) -> bool {
+        let circuit_ok = self
+            .circuit_breakers
+            .get(provider_id)
+            .is_none_or(|cb| self.circuit_would_allow(provider_id, &cb));
+        circuit_ok && self.health_monitor.get_status(provider_id) != HealthStatus::Unhealthy
+    }
+
+    async fn store_rate_limit(
+        store: &dyn StateStore,
+        snapshot: &RateLimitSnapshot,
+    ) -> StorageResult<()> {
+        let remaining = snapshot
+            .until_ms
+            .saturating_sub(crate::persistence::now_ms());
+        if remaining == 0 {
+            return Ok(());
+        }
+        store
+            .set_with_ttl(
+                &format!(
+                    "{}{}:{}",
+                    RATE_LIMIT_KEY_PREFIX, snapshot.rule, snapshot.provider
+                ),
+                Self::encode(snapshot)?,
+                Duration::from_millis(remaining),
+            )
+            .await
+    }
+
+    async fn load_prefix(
+        store: &dyn StateStore,
+        prefix: &str,
+    ) -> StorageResult<Vec<(String, Vec<u8>)>> {
+        let keys = store.list_keys(prefix).await?;
+        let values = store.get_many(&keys).await?;
+        Ok(keys
+            .into_iter()
+            .zip(values)
+            .filter_map(|(key, value)| value.map(|v| (key, v)))
+            .collect())
+    }
+
+    fn encode<T: serde::Serialize>(value: &T) -> StorageResult<Vec<u8>> {
+        serde_json::to_vec(value).map_err(|e| StorageError::Serialization(e.to_string()))
+    }
+
+    fn decode<T: serde::de::DeserializeOwned>(key: &str, value: &[u8]) -> StorageResult<T> {
+        serde_json::from_slice(value)
+            .map_err(|e| StorageError::Serialization(format!("Invalid state '{}': {}", key, e)))
+    }
+
     /// Create a router with default configurations
     pub fn with_defaults(
         route_table: RouteTable,
@@ -184,16 +641,596 @@ impl Router {
         true
     }
 
-    /// Select provider using strategy
-    fn select_provider_from_strategy(
+    /// Select provider using strategy, parking in the rate-limit queue (if
+    /// configured and `may_wait`) while every provider is backing off
+    ///
+    /// Returns the provider and whether the request had to wait.
+    async fn select_provider_or_wait(
         &self,
-        strategy: &crate::strategy::RoutingStrategy,
+        strategy: &RoutingStrategy,
         rule_name: &str,
-    ) -> Result<String> {
+        request: &NormalizedRequest,
+        started: Instant,
+        may_wait: bool,
+    ) -> Result<(String, bool)> {
         let state = self.get_strategy_state(rule_name);
-        state
-            .select_provider(strategy)
-            .map_err(|e| Error::Provider(format!("Strategy selection failed: {}", e)))
+        let mut waited = false;
+        loop {
+            Self::refresh_quotas(&self.providers, strategy, &state);
+            match (state.select_provider(strategy), &self.rate_limit_queue) {
+                (Ok(provider), _) => return Ok((provider, waited)),
+                (Err(StrategyError::AllProvidersRateLimited), Some(queue)) if may_wait => {
+                    wait_for_reset(queue, &state, strategy, &fairness_key(request), started)
+                        .await?;
+                    waited = true;
+                }
+                (Err(e), _) => return Err(strategy_error(e)),
+            }
+        }
+    }
+
+    /// Stream that parks in the rate-limit queue, then streams from the first
+    /// provider to leave backoff
+    ///
+    /// The stream is returned before waiting so the ingress can start the SSE
+    /// response and send keepalive comments. Circuit breakers and fallbacks
+    /// are not consulted once the request has parked.
+    fn deferred_stream(
+        &self,
+        strategy: &RoutingStrategy,
+        state: Arc<StrategyState>,
+        request: NormalizedRequest,
+    ) -> Result<Box<dyn Stream<Item = Result<NormalizedStreamEvent>> + Send + Unpin>> {
+        use futures::StreamExt;
+
+        let queue = self
+            .rate_limit_queue
+            .clone()
+            .ok_or_else(|| Error::Internal("rate-limit queue not configured".to_string()))?;
+        let strategy = strategy.clone();
+        let providers: HashMap<String, Arc<dyn Provider>> = strategy
+            .provider_ids()
+            .into_iter()
+            .filter_map(|id| Some((id.to_string(), self.providers.get(id)?.clone())))
+            .collect();
+        let started = Instant::now();
+
+        info!(
+            model = %request.model,
+            "All providers rate-limited, parking streaming request"
+        );
+
+        let stream = futures::stream::once(async move {
+            let session = fairness_key(&request);
+            let result = async {
+                loop {
+                    wait_for_reset(&queue, &state, &strategy, &session, started).await?;
+                    Self::refresh_quotas(&providers, &strategy, &state);
+                    match state.select_provider(&strategy) {
+                        Ok(selected) => {
+                            let provider = providers.get(&selected).ok_or_else(|| {
+                                Error::Provider(format!("Provider '{}' not found", selected))
+                            })?;
+                            info!(provider = %selected, "Starting parked streaming request");
+                            return provider.stream(request).await;
+                        }
+                        Err(StrategyError::AllProvidersRateLimited) => continue,
+                        Err(e) => return Err(strategy_error(e)),
+                    }
+                }
+            }
+            .await;
+            match result {
+                Ok(stream) => stream,
+                Err(err) => Box::new(futures::stream::iter([Err(err)]))
+                    as Box<dyn Stream<Item = Result<NormalizedStreamEvent>> + Send + Unpin>,
+            }
+        })
+        .flatten();
+
+        Ok(Box::new(Box::pin(stream)))
+    }
+
+    /// Copy the connectors' latest upstream quota into a limits-alternative
+    /// strategy's state, so selection can divert before a 429
+    fn refresh_quotas(
+        providers: &HashMap<String, Arc<dyn Provider>>,
+        strategy: &RoutingStrategy,
+        state: &StrategyState,
+    ) {
+        if !matches!(strategy, RoutingStrategy::LimitsAlternative { .. }) {
+            return;
+        }
+        for provider_id in strategy.provider_ids() {
+            if let Some(quota) = providers.get(provider_id).and_then(|p| p.quota()) {
+                state.record_quota(provider_id, quota);
+            }
+        }
+    }
+
+    /// Export a provider's latest upstream quota as Prometheus gauges
+    fn record_quota_metrics(&self, provider_id: &str) {
+        let (Some(metrics), Some(provider)) = (&self.metrics, self.providers.get(provider_id))
+        else {
+            return;
+        };
+        if let Some(quota) = provider.quota() {
+            for (kind, window) in quota.windows() {
+                metrics.update_provider_quota(
+                    provider_id,
+                    kind.as_str(),
+                    window.remaining,
+                    window.limit,
+                );
+            }
+        }
+    }
+
+    /// Apply model rewrites and return alias targets (empty if not an alias)
+    fn resolve_model_alias(&self, request: &mut NormalizedRequest) -> Vec<AliasTarget> {
+        let Some(table) = &self.model_aliases else {
+            return vec![];
+        };
+
+        let resolution = table.resolve(&request.model);
+        if let Some(rewritten) = &resolution.rewritten {
+            info!(
+                requested = %resolution.requested,
+                rewritten = %rewritten,
+                "Rewrote retired model ID"
+            );
+            request.model = rewritten.clone();
+        }
+        resolution.targets.to_vec()
+    }
+
+    /// Build the request for a specific alias target
+    fn request_for_target(request: &NormalizedRequest, target: &AliasTarget) -> NormalizedRequest {
+        let mut target_request = request.clone();
+        target_request.model = target.model.clone();
+        target.params.apply_to_request(&mut target_request);
+        target_request
+    }
+
+    /// Send a request to alias targets in priority order
+    async fn send_to_alias_targets(
+        &self,
+        request: NormalizedRequest,
+        targets: &[AliasTarget],
+    ) -> Result<NormalizedResponse> {
+        let alias = request.model.clone();
+        let mut last_error = None;
+
+        for target in targets {
+            let target_request = Self::request_for_target(&request, target);
+            info!(
+                alias = %alias,
+                provider = %target.provider,
+                model = %target.model,
+                "Resolved model alias"
+            );
+
+            match self
+                .try_provider(&target.provider, &target_request, None, None)
+                .await
+            {
+                Ok(response) => return Ok(response),
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        Err(Error::Provider(format!(
+            "All targets failed for model alias '{}'{}",
+            alias,
+            last_error
+                .map(|e| format!(" (last error: {})", e))
+                .unwrap_or_default()
+        )))
+    }
+
+    /// Explain how a request would be routed, without sending it
+    ///
+    /// Follows the same steps as [`Provider::send`]: model rewrites and
+    /// aliases, then the route table, strategy selection and fallbacks.
+    /// Strategy counters, circuit breakers and rate-limit state are read but
+    /// never advanced, so explaining a request does not affect live traffic.
+    pub fn explain(
+        &self,
+        request: &NormalizedRequest,
+        context: &RoutingContext,
+    ) -> RoutingExplanation {
+        let mut explanation = RoutingExplanation::new(&request.model);
+        let mut request = request.clone();
+
+        if let Some(table) = &self.model_aliases {
+            let resolution = table.resolve(&request.model);
+            if let Some(rewritten) = &resolution.rewritten {
+                explanation.push_step(
+                    "rewrite",
+                    format!("{} → {}", resolution.requested, rewritten),
+                );
+                request.model = rewritten.clone();
+            }
+            if !resolution.targets.is_empty() {
+                explanation.push_step(
+                    "alias",
+                    format!(
+                        "'{}' resolves to {} target(s), route table bypassed",
+                        request.model,
+                        resolution.targets.len()
+                    ),
+                );
+                explanation.resolved_model = request.model.clone();
+                explanation.rules = self.unevaluated_rules(context.time());
+                for target in resolution.targets {
+                    explanation.candidates.push(self.candidate(
+                        &target.provider,
+                        CandidateRole::AliasTarget,
+                        &target.model,
+                        None,
+                    ));
+                }
+                explanation.select_first_available();
+                return explanation;
+            }
+        }
+        explanation.resolved_model = request.model.clone();
+
+        if let Some(provider) = &context.provider_override {
+            explanation.matched_rule = Some("provider_override".to_string());
+            explanation.rules = self.unevaluated_rules(context.time());
+            explanation.candidates.push(self.candidate(
+                provider,
+                CandidateRole::Override,
+                &request.model,
+                None,
+            ));
+            explanation.select_first_available();
+            return explanation;
+        }
+
+        let at = context.time();
+        for rule in self.route_table.rules() {
+            let name = rule
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("rule_priority_{}", rule.priority));
+
+            let outcome = if explanation.matched_rule.is_some() {
+                RuleOutcome::NotEvaluated
+            } else if !rule.matcher.matches(&request, context) {
+                RuleOutcome::NotMatched
+            } else if !rule.is_active_at(at) {
+                RuleOutcome::Inactive
+            } else if rule.strategy.is_none() && rule.primary.is_none() {
+                RuleOutcome::Invalid
+            } else {
+                self.explain_rule(rule, &name, &request.model, &mut explanation);
+                explanation.matched_rule = Some(name.clone());
+                RuleOutcome::Matched
+            };
+
+            explanation.rules.push(RuleEvaluation {
+                name,
+                priority: rule.priority,
+                matcher: rule.matcher.to_string(),
+                schedule: describe_schedule(rule, at),
+                outcome,
+            });
+        }
+
+        explanation.select_first_available();
+        explanation
+    }
+
+    /// Fill in strategy choice and candidates for the matched rule
+    fn explain_rule(
+        &self,
+        rule: &crate::router::RoutingRule,
+        rule_name: &str,
+        model: &str,
+        explanation: &mut RoutingExplanation,
+    ) {
+        if let Some(strategy) = &rule.strategy {
+            // Peek at live state if the rule has served traffic, else a fresh state
+            let state = self
+                .strategy_states
+                .get(rule_name)
+                .map(|s| s.clone())
+                .unwrap_or_default();
+            Self::refresh_quotas(&self.providers, strategy, &state);
+            let choice = state.peek_provider(strategy);
+
+            let kind = match strategy {
+                RoutingStrategy::RoundRobin { .. } => "round-robin",
+                RoutingStrategy::WeightedRoundRobin { .. } => "weighted-round-robin",
+                RoutingStrategy::LimitsAlternative { .. } => "limits-alternative",
+            };
+            explanation.strategy = Some(StrategyChoice {
+                kind: kind.to_string(),
+                selected: choice.as_ref().ok().cloned(),
+                error: choice.as_ref().err().map(|e| e.to_string()),
+            });
+
+            if let RoutingStrategy::LimitsAlternative {
+                primary_providers,
+                alternative_providers,
+                quota_headroom_percent,
+                ..
+            } = strategy
+            {
+                // Providers the strategy passes over on its way to the selection
+                let ordered = primary_providers
+                    .iter()
+                    .map(|p| (p, CandidateRole::Primary))
+                    .chain(
+                        alternative_providers
+                            .iter()
+                            .map(|p| (p, CandidateRole::Alternative)),
+                    );
+                for (provider, role) in ordered {
+                    let selected = choice.as_ref().is_ok_and(|s| s == provider);
+                    let mut candidate = self.candidate(provider, role, model, Some(&state));
+                    if !selected
+                        && candidate.skipped.is_none()
+                        && let Some(kind) = state.quota_low(provider, *quota_headroom_percent)
+                    {
+                        candidate.skipped = Some(SkipReason::QuotaLow {
+                            kind: kind.as_str().to_string(),
+                        });
+                    }
+                    explanation.candidates.push(candidate);
+                    if selected {
+                        break;
+                    }
+                }
+            } else if let Ok(selected) = &choice {
+                explanation.candidates.push(self.candidate(
+                    selected,
+                    CandidateRole::Strategy,
+                    model,
+                    None,
+                ));
+            }
+
+            // A failed strategy selection aborts the request before fallbacks
+            if choice.is_err() {
+                return;
+            }
+        } else if let Some(primary) = &rule.primary {
+            explanation.candidates.push(self.candidate(
+                primary,
+                CandidateRole::Primary,
+                model,
+                None,
+            ));
+        }
+
+        for fallback in &rule.fallbacks {
+            explanation.candidates.push(self.candidate(
+                fallback,
+                CandidateRole::Fallback,
+                model,
+                None,
+            ));
+        }
+    }
+
+    /// Describe a candidate provider and whether it would be skipped
+    fn candidate(
+        &self,
+        provider: &str,
+        role: CandidateRole,
+        model: &str,
+        strategy_state: Option<&StrategyState>,
+    ) -> ProviderCandidate {
+        let breaker = self.circuit_breakers.get(provider).map(|cb| cb.clone());
+
+        let skipped = if !self.providers.contains_key(provider) {
+            Some(SkipReason::NotRegistered)
+        } else if breaker
+            .as_ref()
+            .is_some_and(|cb| !self.circuit_would_allow(provider, cb))
+        {
+            Some(SkipReason::CircuitOpen)
+        } else {
+            strategy_state
+                .and_then(|state| state.rate_limit_remaining(provider))
+                .map(|remaining| SkipReason::RateLimited {
+                    retry_after_secs: remaining.as_secs_f64().ceil() as u64,
+                })
+        };
+
+        ProviderCandidate {
+            provider: provider.to_string(),
+            role,
+            model: model.to_string(),
+            circuit: breaker.map(|cb| cb.state()),
+            health: self.health_monitor.get_status(provider),
+            skipped,
+        }
+    }
+
+    /// All rules, marked as not evaluated (route decided before the table)
+    fn unevaluated_rules(&self, at: DateTime<Utc>) -> Vec<RuleEvaluation> {
+        self.route_table
+            .rules()
+            .iter()
+            .map(|rule| RuleEvaluation {
+                name: rule
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("rule_priority_{}", rule.priority)),
+                priority: rule.priority,
+                matcher: rule.matcher.to_string(),
+                schedule: describe_schedule(rule, at),
+                outcome: RuleOutcome::NotEvaluated,
+            })
+            .collect()
+    }
+
+    /// Apply the matched rule's policy for a failed request's error class
+    async fn apply_error_policy(
+        &self,
+        policies: &ErrorPolicies,
+        provider_id: &str,
+        request: &NormalizedRequest,
+        strategy: Option<&RoutingStrategy>,
+        rule_name: &str,
+        err: Error,
+    ) -> PolicyOutcome {
+        let Some((class, action)) = error_policy(policies, &err) else {
+            return PolicyOutcome::Continue(err);
+        };
+        info!(
+            provider = provider_id,
+            class = %class,
+            action = ?action,
+            "Applying error policy"
+        );
+
+        match action {
+            FallbackAction::Fail => PolicyOutcome::Settled(Err(err)),
+            FallbackAction::Retry {
+                max_retries,
+                backoff_ms,
+            } => {
+                let mut last_err = err;
+                for attempt in 0..*max_retries {
+                    tokio::time::sleep(FallbackAction::retry_delay(*backoff_ms, attempt)).await;
+                    match self
+                        .try_provider(provider_id, request, strategy, Some(rule_name))
+                        .await
+                    {
+                        Ok(response) => return PolicyOutcome::Settled(Ok(response)),
+                        Err(retry_err) => {
+                            let same_class = ErrorClass::classify(&retry_err) == Some(class);
+                            last_err = retry_err;
+                            if !same_class {
+                                break;
+                            }
+                        }
+                    }
+                }
+                PolicyOutcome::Continue(last_err)
+            }
+            FallbackAction::Switch { provider, model } => {
+                let mut switched_request = request.clone();
+                if let Some(model) = model {
+                    switched_request.model = model.clone();
+                }
+                self.inject_notification_if_needed(
+                    &mut switched_request,
+                    provider_id,
+                    provider,
+                    switch_reason(class),
+                );
+                PolicyOutcome::Settled(
+                    self.try_provider(provider, &switched_request, None, Some(rule_name))
+                        .await,
+                )
+            }
+        }
+    }
+
+    /// Apply the matched rule's policy when a stream fails to start
+    async fn apply_stream_error_policy(
+        &self,
+        policies: &ErrorPolicies,
+        provider_id: &str,
+        request: NormalizedRequest,
+        err: Error,
+    ) -> Result<Box<dyn Stream<Item = Result<NormalizedStreamEvent>> + Send + Unpin>> {
+        let Some((class, action)) = error_policy(policies, &err) else {
+            return Err(err);
+        };
+        info!(
+            provider = provider_id,
+            class = %class,
+            action = ?action,
+            "Applying error policy to streaming request"
+        );
+
+        match action {
+            FallbackAction::Fail => Err(err),
+            FallbackAction::Retry {
+                max_retries,
+                backoff_ms,
+            } => {
+                Self::check_scope(provider_id, &request)?;
+                let provider = self.providers.get(provider_id).ok_or_else(|| {
+                    Error::Provider(format!("Provider '{}' not found", provider_id))
+                })?;
+                let mut last_err = err;
+                for attempt in 0..*max_retries {
+                    tokio::time::sleep(FallbackAction::retry_delay(*backoff_ms, attempt)).await;
+                    match provider.stream(request.clone()).await {
+                        Ok(stream) => return Ok(stream),
+                        Err(retry_err) => last_err = retry_err,
+                    }
+                }
+                Err(last_err)
+            }
+            FallbackAction::Switch { provider, model } => {
+                let target = self
+                    .providers
+                    .get(provider)
+                    .ok_or_else(|| Error::Provider(format!("Provider '{}' not found", provider)))?;
+                let mut switched_request = request;
+                if let Some(model) = model {
+                    switched_request.model = model.clone();
+                }
+                Self::check_scope(provider, &switched_request)?;
+                self.inject_notification_if_needed(
+                    &mut switched_request,
+                    provider_id,
+                    provider,
+                    switch_reason(class),
+                );
+                target.stream(switched_request).await
+            }
+        }
+    }
+
+    /// Find a request's route, narrowed to the providers its scope allows
+    fn find_route(
+        &self,
+        request: &NormalizedRequest,
+        context: &RoutingContext,
+    ) -> Result<RoutingDecision> {
+        let decision = self
+            .route_table
+            .find_route(request, context)
+            .ok_or_else(|| {
+                Error::Provider(format!("No route found for model '{}'", request.model))
+            })?;
+        let scope = RequestScope::from_request(request);
+        decision
+            .retain_providers(|id| scope.allows_provider(id))
+            .ok_or_else(|| {
+                Error::Provider(format!(
+                    "No provider the API key may use is routed for model '{}'",
+                    request.model
+                ))
+            })
+    }
+
+    /// Refuse a provider or model outside the request's scope
+    fn check_scope(provider_id: &str, request: &NormalizedRequest) -> Result<()> {
+        let scope = RequestScope::from_request(request);
+        if !scope.allows_provider(provider_id) {
+            return Err(Error::Provider(format!(
+                "API key may not use provider '{}'",
+                provider_id
+            )));
+        }
+        if !scope.allows_model(&request.model) {
+            return Err(Error::Provider(format!(
+                "API key may not use model '{}'",
+                request.model
+            )));
+        }
+        Ok(())
     }
 
     /// Try to send request to a provider, respecting circuit breaker
@@ -205,10 +1242,11 @@ impl Router {
         strategy: Option<&RoutingStrategy>,
         rule_name: Option<&str>,
     ) -> Result<NormalizedResponse> {
+        Self::check_scope(provider_id, request)?;
         let circuit_breaker = self.get_circuit_breaker(provider_id);
 
         // Check circuit breaker
-        if !circuit_breaker.allow_request() {
+        if !self.circuit_allows(provider_id, &circuit_breaker) {
             warn!(
                 provider = provider_id,
                 state = ?circuit_breaker.state(),
@@ -231,11 +1269,19 @@ impl Router {
             "Attempting request to provider"
         );
 
-        match provider.send(request.clone()).await {
+        let circuit_before = circuit_breaker.state();
+
+        let result = provider.send(request.clone()).await;
+        self.record_quota_metrics(provider_id);
+
+        match result {
             Ok(response) => {
                 // Record success
                 circuit_breaker.record_success();
                 self.health_monitor.record_success(provider_id);
+                if circuit_breaker.state() != circuit_before {
+                    self.write_through(provider_id, None).await;
+                }
 
                 info!(
                     provider = provider_id,
@@ -250,6 +1296,7 @@ impl Router {
                 // Record failure
                 circuit_breaker.record_failure();
                 self.health_monitor.record_failure(provider_id);
+                let mut state_changed = circuit_breaker.state() != circuit_before;
 
                 // Check if this is a rate limit error
                 if let Error::RateLimitExceeded { retry_after_secs } = &err {
@@ -262,12 +1309,22 @@ impl Router {
                         Some(rule),
                     ) = (strategy, rule_name)
                     {
+                        // Without Retry-After, the rate-limit headers may say when the
+                        // exhausted window resets
+                        let retry_after_secs = retry_after_secs.or_else(|| {
+                            provider
+                                .quota()
+                                .and_then(|q| q.exhausted_for())
+                                .map(|d| d.as_secs_f64().ceil() as u64)
+                        });
+                        let retry_after_secs = &retry_after_secs;
                         let state = self.get_strategy_state(rule);
                         state.record_rate_limit(
                             provider_id,
                             *retry_after_secs,
                             *exponential_backoff_base_secs,
                         );
+                        state_changed = true;
 
                         // Record rate limit metrics
                         if let Some(metrics) = &self.metrics {
@@ -292,31 +1349,133 @@ impl Router {
                     "Request failed"
                 );
 
+                if state_changed {
+                    self.write_through(provider_id, rule_name).await;
+                }
+
                 Err(err)
             }
         }
     }
 }
 
+/// Result of applying an error policy to a failed request
+enum PolicyOutcome {
+    /// The policy produced the request's final result
+    Settled(Result<NormalizedResponse>),
+    /// No policy applied, or retries were exhausted: continue with the
+    /// default handling (alternatives, then fallbacks)
+    Continue(Error),
+}
+
+/// The policy configured for an error's class, if any
+fn error_policy<'a>(
+    policies: &'a ErrorPolicies,
+    err: &Error,
+) -> Option<(ErrorClass, &'a FallbackAction)> {
+    let class = ErrorClass::classify(err)?;
+    policies.get(&class).map(|action| (class, action))
+}
+
+/// Explain text for a rule's schedule at the evaluation time
+fn describe_schedule(rule: &crate::router::RoutingRule, at: DateTime<Utc>) -> Option<String> {
+    rule.schedule.as_ref().map(|schedule| {
+        let state = if schedule.is_active_at(at) {
+            "active"
+        } else {
+            "inactive"
+        };
+        format!("{} ({})", schedule, state)
+    })
+}
+
+/// Switch notification reason for an error class
+fn switch_reason(class: ErrorClass) -> SwitchReason {
+    match class {
+        ErrorClass::RateLimit => SwitchReason::RateLimit,
+        _ => SwitchReason::ServiceIssue,
+    }
+}
+
+/// Map a strategy selection failure to a provider error
+fn strategy_error(err: StrategyError) -> Error {
+    Error::Provider(format!("Strategy selection failed: {}", err))
+}
+
+/// Session a request belongs to, for fair release from the rate-limit queue
+fn fairness_key(request: &NormalizedRequest) -> String {
+    FAIRNESS_METADATA_KEYS
+        .iter()
+        .find_map(|key| request.metadata.get(*key))
+        .map(|value| match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .unwrap_or_default()
+}
+
+/// Shortest wait between selection attempts, so a reset that has just passed
+/// cannot spin the loop
+const MIN_QUEUE_WAIT: Duration = Duration::from_millis(50);
+
+/// Park until the earliest rate-limit reset of a strategy's providers
+///
+/// Fails with [`Error::RateLimitExceeded`] when the reset is beyond what is
+/// left of the request's wait budget or the queue is full.
+async fn wait_for_reset(
+    queue: &Arc<RateLimitQueue>,
+    state: &StrategyState,
+    strategy: &RoutingStrategy,
+    session: &str,
+    started: Instant,
+) -> Result<()> {
+    let wait = state
+        .earliest_rate_limit_reset(strategy)
+        .unwrap_or_default()
+        .max(MIN_QUEUE_WAIT);
+    let budget = queue.config().max_wait.saturating_sub(started.elapsed());
+
+    debug!(
+        wait_ms = wait.as_millis() as u64,
+        session, "Parking rate-limited request"
+    );
+    queue
+        .wait(session, wait, budget)
+        .await
+        .map_err(|rejection| {
+            warn!(reason = %rejection, "Rate-limited request not queued");
+            Error::RateLimitExceeded {
+                retry_after_secs: Some(wait.as_secs_f64().ceil() as u64),
+            }
+        })
+}
+
 #[async_trait]
 impl Provider for Router {
-    async fn send(&self, request: NormalizedRequest) -> Result<NormalizedResponse> {
-        // Create routing context (simplified - can be extended with headers, etc.)
-        let context = RoutingContext::new();
+    async fn send(&self, mut request: NormalizedRequest) -> Result<NormalizedResponse> {
+        // Resolve virtual model aliases before consulting the route table
+        let alias_targets = self.resolve_model_alias(&mut request);
+        if !alias_targets.is_empty() {
+            return self.send_to_alias_targets(request, &alias_targets).await;
+        }
 
-        // Find route
-        let decision = self
-            .route_table
-            .find_route(&request, &context)
-            .ok_or_else(|| {
-                Error::Provider(format!("No route found for model '{}'", request.model))
-            })?;
+        // Create routing context (carries the caller identity, if any)
+        let context = RoutingContext::for_request(&request);
+
+        // Find route (only providers the request's scope allows)
+        let decision = self.find_route(&request, &context)?;
 
         let rule_name = decision.matched_rule.as_deref().unwrap_or("unknown");
 
+        // Only park rate-limited requests when there is nothing to fall back to
+        let started = Instant::now();
+        let may_wait = decision.fallbacks.is_empty();
+
         // Determine primary provider (from strategy or direct)
         let (primary_provider, strategy_ref) = if let Some(strategy) = &decision.strategy {
-            let selected = self.select_provider_from_strategy(strategy, rule_name)?;
+            let (selected, _) = self
+                .select_provider_or_wait(strategy, rule_name, &request, started, may_wait)
+                .await?;
 
             info!(
                 model = %request.model,
@@ -355,6 +1514,22 @@ impl Provider for Router {
         {
             Ok(response) => return Ok(response),
             Err(err) => {
+                // A per-class policy takes precedence over alternatives and fallbacks
+                let err = match self
+                    .apply_error_policy(
+                        &decision.on_error,
+                        &primary_provider,
+                        &request,
+                        strategy_ref,
+                        rule_name,
+                        err,
+                    )
+                    .await
+                {
+                    PolicyOutcome::Settled(result) => return result,
+                    PolicyOutcome::Continue(err) => err,
+                };
+
                 // Store error details for determining switch reason in fallback logic
                 is_rate_limit_error = matches!(err, Error::RateLimitExceeded { .. });
 
@@ -363,10 +1538,23 @@ impl Provider for Router {
                     && is_rate_limit_error
                 {
                     // Keep trying alternatives until we find one that works or run out
-                    while let Ok(alternative) =
-                        self.select_provider_from_strategy(strategy_ref.unwrap(), rule_name)
-                    {
-                        if tried_providers.contains(&alternative) {
+                    loop {
+                        let (alternative, waited) = match self
+                            .select_provider_or_wait(
+                                strategy_ref.unwrap(),
+                                rule_name,
+                                &request,
+                                started,
+                                may_wait,
+                            )
+                            .await
+                        {
+                            Ok(selected) => selected,
+                            // The wait queue gave up; report when to retry
+                            Err(err @ Error::RateLimitExceeded { .. }) => return Err(err),
+                            Err(_) => break,
+                        };
+                        if tried_providers.contains(&alternative) && !waited {
                             // Already tried this provider, no more alternatives available
                             break;
                         }
@@ -374,10 +1562,13 @@ impl Provider for Router {
                         info!(
                             original = %tried_providers[0],
                             alternative = %alternative,
+                            waited,
                             "Rate limit detected, switching to alternative provider"
                         );
 
-                        tried_providers.push(alternative.clone());
+                        if !tried_providers.contains(&alternative) {
+                            tried_providers.push(alternative.clone());
+                        }
 
                         // Clone request to inject notification
                         let mut alternative_request = request.clone();
@@ -477,6 +1668,12 @@ impl Provider for Router {
                         error = %err,
                         "Fallback provider failed"
                     );
+                    if let Some((class, FallbackAction::Fail)) =
+                        error_policy(&decision.on_error, &err)
+                    {
+                        info!(fallback = %fallback, class = %class, "Failing fast per error policy");
+                        return Err(err);
+                    }
                 }
             }
         }
@@ -490,23 +1687,59 @@ impl Provider for Router {
 
     async fn stream(
         &self,
-        request: NormalizedRequest,
+        mut request: NormalizedRequest,
     ) -> Result<Box<dyn Stream<Item = Result<NormalizedStreamEvent>> + Send + Unpin>> {
+        // Resolve virtual model aliases: stream from the first target whose circuit allows it
+        let alias_targets = self.resolve_model_alias(&mut request);
+        if !alias_targets.is_empty() {
+            for target in &alias_targets {
+                let Some(provider) = self.providers.get(&target.provider) else {
+                    continue;
+                };
+                let target_request = Self::request_for_target(&request, target);
+                if Self::check_scope(&target.provider, &target_request).is_err() {
+                    continue;
+                }
+                if !self.circuit_allows(
+                    &target.provider,
+                    &self.get_circuit_breaker(&target.provider),
+                ) {
+                    continue;
+                }
+                tracing::info!(
+                    alias = %request.model,
+                    provider = %target.provider,
+                    model = %target.model,
+                    "Resolved model alias for streaming request"
+                );
+                return provider.stream(target_request).await;
+            }
+            return Err(Error::Provider(format!(
+                "No available target for model alias '{}'",
+                request.model
+            )));
+        }
+
         // Create routing context
-        let context = RoutingContext::new();
+        let context = RoutingContext::for_request(&request);
 
-        // Find route
-        let decision = self
-            .route_table
-            .find_route(&request, &context)
-            .ok_or_else(|| {
-                Error::Provider(format!("No route found for model '{}'", request.model))
-            })?;
+        // Find route (only providers the request's scope allows)
+        let decision = self.find_route(&request, &context)?;
 
         // Determine primary provider (from strategy or direct)
         let primary_provider = if let Some(strategy) = &decision.strategy {
             let rule_name = decision.matched_rule.as_deref().unwrap_or("unknown");
-            let selected = self.select_provider_from_strategy(strategy, rule_name)?;
+            let state = self.get_strategy_state(rule_name);
+            Self::refresh_quotas(&self.providers, strategy, &state);
+            let selected = match state.select_provider(strategy) {
+                Ok(selected) => selected,
+                Err(StrategyError::AllProvidersRateLimited)
+                    if self.rate_limit_queue.is_some() && decision.fallbacks.is_empty() =>
+                {
+                    return self.deferred_stream(strategy, state, request);
+                }
+                Err(e) => return Err(strategy_error(e)),
+            };
 
             tracing::info!(
                 model = %request.model,
@@ -537,7 +1770,7 @@ impl Provider for Router {
         // Note: Circuit breaker check for streaming
         let circuit_breaker = self.get_circuit_breaker(&primary_provider);
 
-        if !circuit_breaker.allow_request() {
+        if !self.circuit_allows(&primary_provider, &circuit_breaker) {
             tracing::warn!(
                 provider = %primary_provider,
                 state = ?circuit_breaker.state(),
@@ -547,7 +1780,9 @@ impl Provider for Router {
             // Try fallbacks for streaming
             for fallback in &decision.fallbacks {
                 let fallback_cb = self.get_circuit_breaker(fallback);
-                if fallback_cb.allow_request() {
+                if Self::check_scope(fallback, &request).is_ok()
+                    && self.circuit_allows(fallback, &fallback_cb)
+                {
                     let provider = self.providers.get(fallback).ok_or_else(|| {
                         Error::Provider(format!("Fallback provider '{}' not found", fallback))
                     })?;
@@ -580,6 +1815,7 @@ impl Provider for Router {
         }
 
         // Use primary/selected provider for streaming
+        Self::check_scope(&primary_provider, &request)?;
         let provider = self
             .providers
             .get(&primary_provider)
@@ -591,8 +1827,19 @@ impl Provider for Router {
             "Starting streaming request"
         );
 
+        // Keep a copy for the error policy only if the rule has one
+        let policy_request = (!decision.on_error.is_empty()).then(|| request.clone());
+
         // TODO: Wrap stream to track success/failure and update circuit breaker
-        provider.stream(request).await
+        let result = provider.stream(request).await;
+        self.record_quota_metrics(&primary_provider);
+        match (result, policy_request) {
+            (Err(err), Some(request)) => {
+                self.apply_stream_error_policy(&decision.on_error, &primary_provider, request, err)
+                    .await
+            }
+            (result, _) => result,
+        }
     }
 
     fn capabilities(&self) -> ProviderCapabilities {
@@ -606,6 +1853,50 @@ impl Provider for Router {
     }
 }
 
+/// Readiness for `/readyz`: ready while at least one provider would take
+/// traffic (or when no providers are configured, e.g. passthrough with client
+/// credentials)
+impl ReadinessChecker for Router {
+    fn is_ready(&self) -> bool {
+        self.providers.is_empty() || self.providers.keys().any(|id| self.is_available(id))
+    }
+
+    fn get_provider_statuses(&self) -> Vec<ProviderStatus> {
+        let mut ids: Vec<&String> = self.providers.keys().collect();
+        ids.sort();
+        ids.into_iter()
+            .map(|id| {
+                let metrics = self.health_monitor.get_metrics(id);
+                let status = if self.is_available(id) {
+                    metrics.as_ref().map_or(HealthStatus::Unknown, |m| m.status)
+                } else {
+                    HealthStatus::Unhealthy
+                };
+                ProviderStatus {
+                    name: id.clone(),
+                    status: serde_json::to_value(status)
+                        .ok()
+                        .and_then(|v| v.as_str().map(str::to_string))
+                        .unwrap_or_default(),
+                    success_rate: metrics
+                        .filter(|m| m.total_count > 0)
+                        .map(|m| m.success_rate),
+                    circuit: self.circuit_breakers.get(id).map(|cb| {
+                        serde_json::to_value(cb.state())
+                            .ok()
+                            .and_then(|v| v.as_str().map(str::to_string))
+                            .unwrap_or_default()
+                    }),
+                    last_probe: self
+                        .probe_results
+                        .get(id)
+                        .map(|r| format!("{} ({}ms)", r.outcome, r.latency_ms)),
+                }
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -690,6 +1981,8 @@ mod tests {
             strategy: None,
             primary: Some("test-provider".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule]);
@@ -716,14 +2009,54 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_router_fallback_on_primary_failure() {
+    async fn test_router_fallback_on_primary_failure() {
+        use crate::router::{RoutingRule, RuleMatcher};
+
+        // Create mock providers
+        let mut mock_primary = MockTestProvider::new();
+        mock_primary
+            .expect_send()
+            .returning(|_| Err(Error::Provider("Primary failed".to_string())));
+
+        let mut mock_fallback = MockTestProvider::new();
+        mock_fallback
+            .expect_send()
+            .returning(|_| Ok(create_test_response()));
+
+        let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+        providers.insert("primary".to_string(), Arc::new(mock_primary));
+        providers.insert("fallback".to_string(), Arc::new(mock_fallback));
+
+        // Create routing rule with fallback
+        let rule = RoutingRule {
+            priority: 10,
+            name: Some("test-rule".to_string()),
+            matcher: RuleMatcher::Always,
+            strategy: None,
+            primary: Some("primary".to_string()),
+            fallbacks: vec!["fallback".to_string()],
+            on_error: Default::default(),
+            schedule: None,
+        };
+
+        let route_table = RouteTable::with_rules(vec![rule]);
+        let router = Router::with_defaults(route_table, providers);
+
+        // Send request - should succeed via fallback
+        let request = create_test_request("test-model");
+        let response = router.send(request).await.unwrap();
+
+        assert_eq!(response.model, "test-model");
+    }
+
+    #[tokio::test]
+    async fn test_router_respects_request_scope() {
         use crate::router::{RoutingRule, RuleMatcher};
+        use lunaroute_core::identity::RequestScope;
 
-        // Create mock providers
+        // The primary must never be called for a key scoped to the fallback
         let mut mock_primary = MockTestProvider::new();
-        mock_primary
-            .expect_send()
-            .returning(|_| Err(Error::Provider("Primary failed".to_string())));
+        mock_primary.expect_send().never();
 
         let mut mock_fallback = MockTestProvider::new();
         mock_fallback
@@ -734,7 +2067,6 @@ mod tests {
         providers.insert("primary".to_string(), Arc::new(mock_primary));
         providers.insert("fallback".to_string(), Arc::new(mock_fallback));
 
-        // Create routing rule with fallback
         let rule = RoutingRule {
             priority: 10,
             name: Some("test-rule".to_string()),
@@ -742,16 +2074,37 @@ mod tests {
             strategy: None,
             primary: Some("primary".to_string()),
             fallbacks: vec!["fallback".to_string()],
+            on_error: Default::default(),
+            schedule: None,
         };
+        let router = Router::with_defaults(RouteTable::with_rules(vec![rule]), providers);
 
-        let route_table = RouteTable::with_rules(vec![rule]);
-        let router = Router::with_defaults(route_table, providers);
-
-        // Send request - should succeed via fallback
-        let request = create_test_request("test-model");
-        let response = router.send(request).await.unwrap();
-
-        assert_eq!(response.model, "test-model");
+        let mut request = create_test_request("test-model");
+        RequestScope {
+            providers: vec!["fallback".to_string()],
+            models: vec![],
+        }
+        .attach(&mut request);
+        assert!(router.send(request).await.is_ok());
+
+        // No routed provider is in scope
+        let mut request = create_test_request("test-model");
+        RequestScope {
+            providers: vec!["other".to_string()],
+            models: vec![],
+        }
+        .attach(&mut request);
+        let err = router.send(request).await.unwrap_err();
+        assert!(err.to_string().contains("No provider the API key may use"));
+
+        // Model scopes apply to the model actually sent
+        let mut request = create_test_request("test-model");
+        RequestScope {
+            providers: vec![],
+            models: vec!["claude-*".to_string()],
+        }
+        .attach(&mut request);
+        assert!(router.send(request).await.is_err());
     }
 
     #[tokio::test]
@@ -780,6 +2133,8 @@ mod tests {
             strategy: None,
             primary: Some("primary".to_string()),
             fallbacks: vec!["fallback".to_string()],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule]);
@@ -818,6 +2173,8 @@ mod tests {
             strategy: None,
             primary: Some("test-provider".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule]);
@@ -874,6 +2231,8 @@ mod tests {
             strategy: None,
             primary: Some("test-provider".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule]);
@@ -958,6 +2317,8 @@ mod tests {
             }),
             primary: None,
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule]);
@@ -1023,6 +2384,8 @@ mod tests {
             }),
             primary: None,
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule]);
@@ -1075,6 +2438,8 @@ mod tests {
             }),
             primary: None,
             fallbacks: vec!["p3".to_string()],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule]);
@@ -1127,6 +2492,8 @@ mod tests {
             }),
             primary: None,
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule]);
@@ -1180,6 +2547,8 @@ mod tests {
             }),
             primary: None,
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule]);
@@ -1247,6 +2616,8 @@ mod tests {
             }),
             primary: None,
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule]);
@@ -1275,6 +2646,8 @@ mod tests {
             strategy: None,
             primary: None,
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         // Validation should fail
@@ -1330,6 +2703,8 @@ mod tests {
             }),
             primary: None,
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         // Rule 2: claude models go to p3 only
@@ -1340,6 +2715,8 @@ mod tests {
             strategy: None,
             primary: Some("p3".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let route_table = RouteTable::with_rules(vec![rule1, rule2]);
@@ -1362,4 +2739,767 @@ mod tests {
         assert_eq!(p2_calls.load(Ordering::SeqCst), 2); // gpt requests
         assert_eq!(p3_calls.load(Ordering::SeqCst), 4); // claude requests
     }
+
+    #[tokio::test]
+    async fn test_router_model_alias_targets_with_fallback() {
+        use crate::model_alias::{ModelAlias, ParameterOverrides};
+
+        let mut mock_primary = MockTestProvider::new();
+        mock_primary
+            .expect_send()
+            .returning(|_| Err(Error::Provider("Primary failed".to_string())));
+
+        let mut mock_backup = MockTestProvider::new();
+        mock_backup.expect_send().returning(|req| {
+            assert_eq!(req.model, "backup-model");
+            assert_eq!(req.max_tokens, Some(50));
+            let mut response = create_test_response();
+            response.model = req.model;
+            Ok(response)
+        });
+
+        let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+        providers.insert("primary".to_string(), Arc::new(mock_primary));
+        providers.insert("backup".to_string(), Arc::new(mock_backup));
+
+        let mut aliases = HashMap::new();
+        aliases.insert(
+            "team-default".to_string(),
+            ModelAlias {
+                description: None,
+                targets: vec![
+                    AliasTarget {
+                        provider: "primary".to_string(),
+                        model: "primary-model".to_string(),
+                        params: ParameterOverrides::default(),
+                    },
+                    AliasTarget {
+                        provider: "backup".to_string(),
+                        model: "backup-model".to_string(),
+                        params: ParameterOverrides {
+                            max_tokens: Some(50),
+                            ..Default::default()
+                        },
+                    },
+                ],
+            },
+        );
+        let mut rewrites = HashMap::new();
+        rewrites.insert("retired-model".to_string(), "team-default".to_string());
+        let table = ModelAliasTable::new(aliases, rewrites).unwrap();
+
+        // Empty route table: alias resolution must not consult it
+        let router =
+            Router::with_defaults(RouteTable::new(), providers).with_model_aliases(Arc::new(table));
+
+        let response = router
+            .send(create_test_request("retired-model"))
+            .await
+            .unwrap();
+        assert_eq!(response.model, "backup-model");
+    }
+
+    #[tokio::test]
+    async fn test_router_explain_reports_rules_and_skipped_providers() {
+        use crate::router::{RoutingRule, RuleMatcher};
+        use std::time::Duration;
+
+        let mut mock_primary = MockTestProvider::new();
+        mock_primary
+            .expect_send()
+            .returning(|_| Err(Error::Provider("Primary failed".to_string())));
+        let mut mock_fallback = MockTestProvider::new();
+        mock_fallback
+            .expect_send()
+            .returning(|_| Ok(create_test_response()));
+
+        let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+        providers.insert("primary".to_string(), Arc::new(mock_primary));
+        providers.insert("fallback".to_string(), Arc::new(mock_fallback));
+
+        let rules = vec![
+            RoutingRule {
+                priority: 20,
+                name: Some("gpt".to_string()),
+                matcher: RuleMatcher::model_pattern("^gpt-.*"),
+                strategy: None,
+                primary: Some("fallback".to_string()),
+                fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
+            },
+            RoutingRule {
+                priority: 10,
+                name: Some("claude".to_string()),
+                matcher: RuleMatcher::model_pattern("^claude-.*"),
+                strategy: None,
+                primary: Some("primary".to_string()),
+                fallbacks: vec!["missing".to_string(), "fallback".to_string()],
+                on_error: Default::default(),
+                schedule: None,
+            },
+            RoutingRule {
+                priority: 0,
+                name: Some("default".to_string()),
+                matcher: RuleMatcher::Always,
+                strategy: None,
+                primary: Some("fallback".to_string()),
+                fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
+            },
+        ];
+        let router = Router::new(
+            RouteTable::with_rules(rules),
+            providers,
+            HealthMonitorConfig::default(),
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                success_threshold: 1,
+                timeout: Duration::from_secs(60),
+            },
+            None,
+            None,
+        );
+
+        // Trip the primary's circuit breaker
+        let request = create_test_request("claude-sonnet");
+        router.send(request.clone()).await.unwrap();
+
+        let explanation = router.explain(&request, &RoutingContext::new());
+        let outcomes: Vec<_> = explanation.rules.iter().map(|r| r.outcome).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                RuleOutcome::NotMatched,
+                RuleOutcome::Matched,
+                RuleOutcome::NotEvaluated
+            ]
+        );
+        assert_eq!(explanation.matched_rule.as_deref(), Some("claude"));
+
+        let skipped: Vec<_> = explanation
+            .candidates
+            .iter()
+            .map(|c| (c.provider.as_str(), c.skipped.clone()))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                ("primary", Some(SkipReason::CircuitOpen)),
+                ("missing", Some(SkipReason::NotRegistered)),
+                ("fallback", None),
+            ]
+        );
+        assert_eq!(explanation.selected_provider.as_deref(), Some("fallback"));
+
+        // Explaining must not move the circuit breaker out of Open
+        let explanation = router.explain(&request, &RoutingContext::new());
+        assert_eq!(
+            explanation.candidates[0].circuit,
+            Some(crate::CircuitState::Open)
+        );
+    }
+
+    #[test]
+    fn test_explain_reports_rule_schedules() {
+        use crate::router::{RoutingRule, RuleMatcher};
+        use crate::schedule::Schedule;
+
+        let maintenance: Schedule =
+            serde_yaml::from_str("dates: [\"2026-03-14\"]\ntimes: [\"02:00-04:00\"]").unwrap();
+        let rules = vec![
+            RoutingRule {
+                priority: 10,
+                name: Some("maintenance".to_string()),
+                matcher: RuleMatcher::Always,
+                strategy: None,
+                primary: Some("backup".to_string()),
+                fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: Some(maintenance),
+            },
+            RoutingRule {
+                priority: 0,
+                name: Some("default".to_string()),
+                matcher: RuleMatcher::Always,
+                strategy: None,
+                primary: Some("primary".to_string()),
+                fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
+            },
+        ];
+        let router = Router::with_defaults(RouteTable::with_rules(rules), HashMap::new());
+        let request = create_test_request("model");
+        let at = |s: &str| {
+            chrono::DateTime::parse_from_rfc3339(s)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+
+        let context = RoutingContext::new().with_time(at("2026-03-14T03:00:00Z"));
+        let explanation = router.explain(&request, &context);
+        assert_eq!(explanation.matched_rule.as_deref(), Some("maintenance"));
+        assert_eq!(
+            explanation.rules[0].schedule.as_deref(),
+            Some("02:00-04:00 2026-03-14 UTC (active)")
+        );
+
+        let context = RoutingContext::new().with_time(at("2026-03-14T05:00:00Z"));
+        let explanation = router.explain(&request, &context);
+        assert_eq!(explanation.rules[0].outcome, RuleOutcome::Inactive);
+        assert_eq!(
+            explanation.rules[0].schedule.as_deref(),
+            Some("02:00-04:00 2026-03-14 UTC (inactive)")
+        );
+        assert_eq!(explanation.matched_rule.as_deref(), Some("default"));
+        assert!(explanation.rules[1].schedule.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_router_explain_strategy_peek_and_alias() {
+        use crate::model_alias::{ModelAlias, ParameterOverrides};
+        use crate::router::{RoutingRule, RuleMatcher};
+
+        let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+        for id in ["p1", "p2"] {
+            let mut mock = MockTestProvider::new();
+            mock.expect_send().returning(|_| Ok(create_test_response()));
+            providers.insert(id.to_string(), Arc::new(mock));
+        }
+
+        let rule = RoutingRule {
+            priority: 10,
+            name: Some("rr".to_string()),
+            matcher: RuleMatcher::Always,
+            strategy: Some(RoutingStrategy::RoundRobin {
+                providers: vec!["p1".to_string(), "p2".to_string()],
+            }),
+            primary: None,
+            fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
+        };
+
+        let mut aliases = HashMap::new();
+        aliases.insert(
+            "fast".to_string(),
+            ModelAlias {
+                description: None,
+                targets: vec![AliasTarget {
+                    provider: "p2".to_string(),
+                    model: "real-model".to_string(),
+                    params: ParameterOverrides::default(),
+                }],
+            },
+        );
+        let table = ModelAliasTable::new(aliases, HashMap::new()).unwrap();
+        let router = Router::with_defaults(RouteTable::with_rules(vec![rule]), providers)
+            .with_model_aliases(Arc::new(table));
+
+        let request = create_test_request("some-model");
+        router.send(request.clone()).await.unwrap();
+
+        // The next round-robin pick is p2, and peeking twice does not advance it
+        for _ in 0..2 {
+            let explanation = router.explain(&request, &RoutingContext::new());
+            let strategy = explanation.strategy.unwrap();
+            assert_eq!(strategy.kind, "round-robin");
+            assert_eq!(strategy.selected.as_deref(), Some("p2"));
+            assert_eq!(explanation.candidates[0].role, CandidateRole::Strategy);
+        }
+
+        let explanation = router.explain(&create_test_request("fast"), &RoutingContext::new());
+        assert_eq!(explanation.steps[0].stage, "alias");
+        assert!(
+            explanation
+                .rules
+                .iter()
+                .all(|r| r.outcome == RuleOutcome::NotEvaluated)
+        );
+        assert_eq!(explanation.candidates[0].role, CandidateRole::AliasTarget);
+        assert_eq!(explanation.candidates[0].model, "real-model");
+        assert_eq!(explanation.selected_provider.as_deref(), Some("p2"));
+    }
+
+    #[tokio::test]
+    async fn test_router_state_survives_restart() {
+        use crate::router::{RoutingRule, RuleMatcher};
+        use lunaroute_storage::FileStateStore;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.json");
+
+        let rule = || RoutingRule {
+            priority: 10,
+            name: Some("limits".to_string()),
+            matcher: RuleMatcher::Always,
+            strategy: Some(RoutingStrategy::LimitsAlternative {
+                primary_providers: vec!["primary".to_string()],
+                alternative_providers: vec!["backup".to_string()],
+                exponential_backoff_base_secs: 60,
+                quota_headroom_percent: 5,
+            }),
+            primary: None,
+            fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
+        };
+        let providers = |primary_calls: usize| {
+            let mut primary = MockTestProvider::new();
+            primary.expect_send().times(primary_calls).returning(|_| {
+                Err(Error::RateLimitExceeded {
+                    retry_after_secs: Some(600),
+                })
+            });
+            let mut backup = MockTestProvider::new();
+            backup
+                .expect_send()
+                .returning(|_| Ok(create_test_response()));
+            let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+            providers.insert("primary".to_string(), Arc::new(primary));
+            providers.insert("backup".to_string(), Arc::new(backup));
+            providers
+        };
+
+        // First process: primary answers 429 with a 10 minute retry-after
+        {
+            let store = Arc::new(FileStateStore::new(&path).await.unwrap());
+            let router = Router::with_defaults(RouteTable::with_rules(vec![rule()]), providers(1))
+                .with_state_store(store);
+            router.send(create_test_request("model")).await.unwrap();
+        }
+
+        // Second process: the backoff is restored, so primary is not called again
+        let store = Arc::new(FileStateStore::new(&path).await.unwrap());
+        let router = Router::with_defaults(RouteTable::with_rules(vec![rule()]), providers(0))
+            .with_state_store(store);
+        assert!(router.restore_state().await.unwrap() >= 1);
+
+        let explanation = router.explain(&create_test_request("model"), &RoutingContext::new());
+        assert!(matches!(
+            explanation.candidates[0].skipped,
+            Some(SkipReason::RateLimited { retry_after_secs }) if retry_after_secs > 590
+        ));
+        router.send(create_test_request("model")).await.unwrap();
+
+        // A replica sharing the store picks the backoff up on its next sync
+        let shared: Arc<dyn StateStore> = Arc::new(
+            FileStateStore::new(dir.path().join("shared.json"))
+                .await
+                .unwrap(),
+        );
+        let replica_a = Router::with_defaults(RouteTable::with_rules(vec![rule()]), providers(1))
+            .with_state_store(shared.clone());
+        let replica_b = Router::with_defaults(RouteTable::with_rules(vec![rule()]), providers(0))
+            .with_state_store(shared);
+        replica_a.send(create_test_request("model")).await.unwrap();
+        assert!(replica_b.sync_state().await.unwrap() >= 1);
+        replica_b.send(create_test_request("model")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_health_probes_recover_circuit_without_user_traffic() {
+        use crate::router::{RoutingRule, RuleMatcher};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // The first call fails, every later one succeeds
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        let mut mock = MockTestProvider::new();
+        mock.expect_send().returning(move |_| {
+            if calls_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(Error::Provider("connection refused".to_string()))
+            } else {
+                Ok(create_test_response())
+            }
+        });
+        let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+        providers.insert("primary".to_string(), Arc::new(mock));
+
+        let rule = RoutingRule {
+            priority: 0,
+            name: None,
+            matcher: RuleMatcher::Always,
+            strategy: None,
+            primary: Some("primary".to_string()),
+            fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
+        };
+        let router = Router::new(
+            RouteTable::with_rules(vec![rule]),
+            providers,
+            HealthMonitorConfig::default(),
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                success_threshold: 2,
+                timeout: Duration::from_millis(50),
+            },
+            None,
+            None,
+        );
+        let target = ProbeTarget::completion("primary", "probe-model");
+        let timeout = Duration::from_secs(1);
+
+        // A failed probe opens the circuit
+        let result = router.probe_provider(&target, timeout).await;
+        assert!(matches!(result.outcome, ProbeOutcome::Failed { .. }));
+        assert!(!router.is_ready());
+        let statuses = router.get_provider_statuses();
+        assert_eq!(statuses[0].status, "unhealthy");
+        assert_eq!(statuses[0].circuit.as_deref(), Some("open"));
+        assert!(
+            statuses[0]
+                .last_probe
+                .as_deref()
+                .unwrap()
+                .starts_with("failed: ")
+        );
+
+        // Before the timeout the prober leaves the circuit alone
+        let result = router.probe_provider(&target, timeout).await;
+        assert_eq!(result.outcome, ProbeOutcome::CircuitOpen);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // After the timeout, user requests still don't test the provider
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(router.send(create_test_request("model")).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // The prober moves it through half-open back to closed
+        let result = router.probe_provider(&target, timeout).await;
+        assert_eq!(result.outcome, ProbeOutcome::Ok);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(router.is_ready());
+        assert_eq!(
+            router.get_provider_statuses()[0].circuit.as_deref(),
+            Some("closed")
+        );
+
+        router.send(create_test_request("model")).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_health_probe_skips_without_check_or_model() {
+        let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+        providers.insert("primary".to_string(), Arc::new(MockTestProvider::new()));
+        let router = Router::with_defaults(RouteTable::new(), providers);
+
+        let result = router
+            .probe_provider(&ProbeTarget::models("primary"), Duration::from_secs(1))
+            .await;
+        assert!(!result.ran());
+        let result = router
+            .probe_provider(&ProbeTarget::models("missing"), Duration::from_secs(1))
+            .await;
+        assert!(!result.ran());
+        assert!(router.is_ready());
+    }
+
+    #[tokio::test]
+    async fn test_limits_alternative_diverts_on_reported_quota() {
+        use crate::router::{RoutingRule, RuleMatcher};
+        use lunaroute_core::quota::ProviderQuota;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Provider reporting a fixed remaining request quota
+        struct QuotaProvider {
+            remaining: &'static str,
+            calls: AtomicUsize,
+        }
+
+        #[async_trait]
+        impl Provider for QuotaProvider {
+            async fn send(&self, _request: NormalizedRequest) -> Result<NormalizedResponse> {
+                self.calls.fetch_add(1, Ordering::SeqCst);
+                Ok(create_test_response())
+            }
+
+            async fn stream(
+                &self,
+                _request: NormalizedRequest,
+            ) -> Result<Box<dyn Stream<Item = Result<NormalizedStreamEvent>> + Send + Unpin>>
+            {
+                unimplemented!()
+            }
+
+            fn capabilities(&self) -> ProviderCapabilities {
+                ProviderCapabilities {
+                    supports_streaming: false,
+                    supports_tools: false,
+                    supports_vision: false,
+                }
+            }
+
+            fn quota(&self) -> Option<ProviderQuota> {
+                ProviderQuota::from_headers(vec![
+                    ("anthropic-ratelimit-requests-limit", "1000"),
+                    ("anthropic-ratelimit-requests-remaining", self.remaining),
+                ])
+            }
+        }
+
+        let primary = Arc::new(QuotaProvider {
+            remaining: "20",
+            calls: AtomicUsize::new(0),
+        });
+        let alternative = Arc::new(QuotaProvider {
+            remaining: "900",
+            calls: AtomicUsize::new(0),
+        });
+        let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+        providers.insert("primary".to_string(), primary.clone());
+        providers.insert("alternative".to_string(), alternative.clone());
+
+        let rule = RoutingRule {
+            priority: 0,
+            name: Some("limits".to_string()),
+            matcher: RuleMatcher::Always,
+            strategy: Some(RoutingStrategy::LimitsAlternative {
+                primary_providers: vec!["primary".to_string()],
+                alternative_providers: vec!["alternative".to_string()],
+                exponential_backoff_base_secs: 60,
+                quota_headroom_percent: 5,
+            }),
+            primary: None,
+            fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
+        };
+        let metrics = Arc::new(Metrics::new().unwrap());
+        let router = Router::new(
+            RouteTable::with_rules(vec![rule]),
+            providers,
+            HealthMonitorConfig::default(),
+            CircuitBreakerConfig::default(),
+            Some(metrics.clone()),
+            None,
+        );
+
+        // 20 of 1000 requests left on the primary (2%): no 429 needed to divert
+        let explanation = router.explain(&create_test_request("model"), &RoutingContext::new());
+        assert_eq!(
+            explanation.candidates[0].skipped,
+            Some(SkipReason::QuotaLow {
+                kind: "requests".to_string()
+            })
+        );
+        assert_eq!(
+            explanation.selected_provider.as_deref(),
+            Some("alternative")
+        );
+
+        router.send(create_test_request("model")).await.unwrap();
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 0);
+        assert_eq!(alternative.calls.load(Ordering::SeqCst), 1);
+
+        let remaining = metrics
+            .provider_quota_remaining
+            .with_label_values(&["alternative", "requests"])
+            .get();
+        assert_eq!(remaining, 900.0);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_queue_parks_until_reset() {
+        use crate::router::{RoutingRule, RuleMatcher};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Provider that answers 429 (retry after 1s) to its first request
+        fn rate_limited_once() -> MockTestProvider {
+            let calls = AtomicUsize::new(0);
+            let mut mock = MockTestProvider::new();
+            mock.expect_send().returning(move |_| {
+                if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(Error::RateLimitExceeded {
+                        retry_after_secs: Some(1),
+                    })
+                } else {
+                    Ok(create_test_response())
+                }
+            });
+            mock.expect_stream()
+                .returning(|_| Ok(Box::new(tokio_stream::empty())));
+            mock
+        }
+
+        let router = |queue: bool| {
+            let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+            providers.insert("p1".to_string(), Arc::new(rate_limited_once()));
+            providers.insert("p2".to_string(), Arc::new(rate_limited_once()));
+            let rule = RoutingRule {
+                priority: 0,
+                name: Some("limits".to_string()),
+                matcher: RuleMatcher::Always,
+                strategy: Some(RoutingStrategy::LimitsAlternative {
+                    primary_providers: vec!["p1".to_string()],
+                    alternative_providers: vec!["p2".to_string()],
+                    exponential_backoff_base_secs: 60,
+                    quota_headroom_percent: 5,
+                }),
+                primary: None,
+                fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
+            };
+            let router = Router::with_defaults(RouteTable::with_rules(vec![rule]), providers);
+            if queue {
+                router.with_rate_limit_queue(RateLimitQueueConfig::default())
+            } else {
+                router
+            }
+        };
+
+        // Without a queue both 429s fail the request
+        assert!(
+            router(false)
+                .send(create_test_request("model"))
+                .await
+                .is_err()
+        );
+
+        // With a queue the request waits out the 1s backoff and succeeds
+        let queued = router(true);
+        let started = Instant::now();
+        queued.send(create_test_request("model")).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(900));
+
+        // Streaming requests get their stream immediately and start after the wait
+        let state = queued.get_strategy_state("limits");
+        state.record_rate_limit("p1", Some(1), 60);
+        state.record_rate_limit("p2", Some(1), 60);
+        let started = Instant::now();
+        let stream = queued.stream(create_test_request("model")).await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(500));
+        let events: Vec<_> = futures::StreamExt::collect(stream).await;
+        assert!(events.is_empty());
+        assert!(started.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_queue_rejects_waits_beyond_max() {
+        use crate::router::{RoutingRule, RuleMatcher};
+
+        let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+        providers.insert("p1".to_string(), Arc::new(MockTestProvider::new()));
+        let rule = RoutingRule {
+            priority: 0,
+            name: Some("limits".to_string()),
+            matcher: RuleMatcher::Always,
+            strategy: Some(RoutingStrategy::LimitsAlternative {
+                primary_providers: vec!["p1".to_string()],
+                alternative_providers: vec!["p1".to_string()],
+                exponential_backoff_base_secs: 60,
+                quota_headroom_percent: 5,
+            }),
+            primary: None,
+            fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
+        };
+        let router = Router::with_defaults(RouteTable::with_rules(vec![rule]), providers)
+            .with_rate_limit_queue(RateLimitQueueConfig {
+                max_wait: Duration::from_secs(5),
+                max_queued: 10,
+            });
+        router
+            .get_strategy_state("limits")
+            .record_rate_limit("p1", Some(600), 60);
+
+        let err = router.send(create_test_request("model")).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::RateLimitExceeded {
+                retry_after_secs: Some(secs)
+            } if secs > 590
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_error_policies_fail_switch_and_retry() {
+        use crate::router::{RoutingRule, RuleMatcher};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let router = |primary: MockTestProvider, backup: MockTestProvider| {
+            let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+            providers.insert("primary".to_string(), Arc::new(primary));
+            providers.insert("backup".to_string(), Arc::new(backup));
+            let on_error: ErrorPolicies = serde_yaml::from_str(
+                r#"
+auth: { action: fail }
+content_filter: { action: switch, provider: backup, model: backup-model }
+overloaded: { action: retry, max_retries: 2, backoff_ms: 1 }
+"#,
+            )
+            .unwrap();
+            let rule = RoutingRule {
+                priority: 0,
+                name: Some("policies".to_string()),
+                matcher: RuleMatcher::Always,
+                strategy: None,
+                primary: Some("primary".to_string()),
+                fallbacks: vec!["backup".to_string()],
+                on_error,
+                schedule: None,
+            };
+            Router::with_defaults(RouteTable::with_rules(vec![rule]), providers)
+        };
+
+        // auth: fail fast, the fallback is never tried
+        let mut primary = MockTestProvider::new();
+        primary.expect_send().returning(|_| {
+            Err(Error::Provider(
+                r#"Provider error: 401 - {"error":{"type":"authentication_error"}}"#.to_string(),
+            ))
+        });
+        let mut backup = MockTestProvider::new();
+        backup.expect_send().times(0);
+        let err = router(primary, backup)
+            .send(create_test_request("model"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("authentication_error"));
+
+        // content_filter: switch to the configured provider and model
+        let mut primary = MockTestProvider::new();
+        primary.expect_send().returning(|_| {
+            Err(Error::Provider(
+                r#"Provider error: 400 - {"error":{"code":"content_filter"}}"#.to_string(),
+            ))
+        });
+        let mut backup = MockTestProvider::new();
+        backup
+            .expect_send()
+            .withf(|request| request.model == "backup-model")
+            .times(1)
+            .returning(|_| Ok(create_test_response()));
+        router(primary, backup)
+            .send(create_test_request("model"))
+            .await
+            .unwrap();
+
+        // overloaded: retry the same provider before falling back
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut primary = MockTestProvider::new();
+        let primary_calls = calls.clone();
+        primary.expect_send().returning(move |_| {
+            if primary_calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(Error::Provider(
+                    r#"Provider error: 529 - {"error":{"type":"overloaded_error"}}"#.to_string(),
+                ))
+            } else {
+                Ok(create_test_response())
+            }
+        });
+        let mut backup = MockTestProvider::new();
+        backup.expect_send().times(0);
+        router(primary, backup)
+            .send(create_test_request("model"))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
 }
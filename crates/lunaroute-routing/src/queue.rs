@@ -0,0 +1,245 @@
+//! Wait queue for rate-limited routes
+//!
+//! When a limits-alternative strategy finds every provider in rate-limit
+//! backoff, the request normally fails. With a [`RateLimitQueue`] configured
+//! (see [`Router::with_rate_limit_queue`](crate::Router::with_rate_limit_queue))
+//! the request instead parks until the earliest known reset time — from
+//! `Retry-After` or the provider's rate-limit headers — and then retries
+//! selection, as long as that fits within the configured maximum wait.
+//!
+//! Waiters that become ready together are released round-robin across
+//! sessions, so one agent with many parked requests cannot starve another.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+/// Normalized request metadata keys used to group waiters (first match wins)
+pub const FAIRNESS_METADATA_KEYS: [&str; 2] = ["session_id", "user"];
+
+/// Wait queue limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitQueueConfig {
+    /// Longest a request may wait in total before failing
+    pub max_wait: Duration,
+    /// Most requests parked at once; further requests fail immediately
+    pub max_queued: usize,
+}
+
+impl Default for RateLimitQueueConfig {
+    fn default() -> Self {
+        Self {
+            max_wait: Duration::from_secs(30),
+            max_queued: 100,
+        }
+    }
+}
+
+/// Why a request was not parked
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum QueueRejection {
+    /// The earliest reset is beyond the remaining wait budget
+    #[error("earliest rate-limit reset in {0:?} exceeds the maximum wait")]
+    WaitTooLong(Duration),
+    /// `max_queued` requests are already waiting
+    #[error("rate-limit wait queue is full")]
+    Full,
+}
+
+struct Waiter {
+    ready_at: Instant,
+    release: oneshot::Sender<()>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    /// Sessions with parked requests, in release order
+    sessions: VecDeque<String>,
+    /// Parked requests per session, oldest first
+    waiters: HashMap<String, VecDeque<Waiter>>,
+    queued: usize,
+}
+
+/// Bounded, session-fair wait queue for rate-limited requests
+pub struct RateLimitQueue {
+    config: RateLimitQueueConfig,
+    state: Mutex<QueueState>,
+}
+
+impl RateLimitQueue {
+    /// Create an empty queue
+    pub fn new(config: RateLimitQueueConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(QueueState::default()),
+        }
+    }
+
+    /// Queue limits
+    pub fn config(&self) -> &RateLimitQueueConfig {
+        &self.config
+    }
+
+    /// Number of parked requests
+    pub fn len(&self) -> usize {
+        self.state.lock().map(|s| s.queued).unwrap_or(0)
+    }
+
+    /// Whether no request is parked
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Park until `wait` has elapsed and this request's turn comes up
+    ///
+    /// `budget` is what is left of the request's total wait allowance.
+    pub async fn wait(
+        self: &Arc<Self>,
+        session: &str,
+        wait: Duration,
+        budget: Duration,
+    ) -> Result<(), QueueRejection> {
+        if wait > budget {
+            return Err(QueueRejection::WaitTooLong(wait));
+        }
+
+        let (release, released) = oneshot::channel();
+        let ready_at = Instant::now() + wait;
+        {
+            let mut state = self.state.lock().map_err(|_| QueueRejection::Full)?;
+            if state.queued >= self.config.max_queued {
+                return Err(QueueRejection::Full);
+            }
+            let state = &mut *state;
+            let waiters = state.waiters.entry(session.to_string()).or_default();
+            if waiters.is_empty() {
+                state.sessions.push_back(session.to_string());
+            }
+            waiters.push_back(Waiter { ready_at, release });
+            state.queued += 1;
+        }
+
+        let queue = Arc::clone(self);
+        tokio::spawn(async move {
+            tokio::time::sleep_until(ready_at).await;
+            queue.release_ready();
+        });
+
+        // The sender is only dropped after being used, so either result means
+        // this request may go
+        let _ = released.await;
+        Ok(())
+    }
+
+    /// Release every ready waiter, one per session per round
+    fn release_ready(&self) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        loop {
+            let mut released = 0;
+            for _ in 0..state.sessions.len() {
+                let Some(session) = state.sessions.pop_front() else {
+                    break;
+                };
+                let waiters = state.waiters.entry(session.clone()).or_default();
+                if waiters.front().is_some_and(|w| w.ready_at <= now)
+                    && let Some(waiter) = waiters.pop_front()
+                {
+                    let _ = waiter.release.send(());
+                    released += 1;
+                }
+                if state.waiters.get(&session).is_some_and(|w| !w.is_empty()) {
+                    state.sessions.push_back(session);
+                } else {
+                    state.waiters.remove(&session);
+                }
+            }
+            state.queued -= released;
+            if released == 0 {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_waits_until_reset() {
+        let queue = Arc::new(RateLimitQueue::new(RateLimitQueueConfig::default()));
+        let started = Instant::now();
+        queue
+            .wait("s1", Duration::from_secs(5), Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert!(started.elapsed() >= Duration::from_secs(5));
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rejects_long_waits_and_full_queue() {
+        let queue = Arc::new(RateLimitQueue::new(RateLimitQueueConfig {
+            max_wait: Duration::from_secs(30),
+            max_queued: 1,
+        }));
+        assert_eq!(
+            queue
+                .wait("s1", Duration::from_secs(31), Duration::from_secs(30))
+                .await,
+            Err(QueueRejection::WaitTooLong(Duration::from_secs(31)))
+        );
+
+        let parked = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                queue
+                    .wait("s1", Duration::from_secs(10), Duration::from_secs(30))
+                    .await
+            })
+        };
+        tokio::task::yield_now().await;
+        assert_eq!(queue.len(), 1);
+        assert_eq!(
+            queue
+                .wait("s2", Duration::from_secs(1), Duration::from_secs(30))
+                .await,
+            Err(QueueRejection::Full)
+        );
+        parked.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_releases_round_robin_across_sessions() {
+        let queue = Arc::new(RateLimitQueue::new(RateLimitQueueConfig::default()));
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        // Session "a" parks three requests before "b" parks one
+        let mut tasks = Vec::new();
+        for (session, n) in [("a", 1), ("a", 2), ("a", 3), ("b", 1)] {
+            let queue = queue.clone();
+            let order = order.clone();
+            tasks.push(tokio::spawn(async move {
+                queue
+                    .wait(session, Duration::from_secs(2), Duration::from_secs(30))
+                    .await
+                    .unwrap();
+                order.lock().unwrap().push(format!("{}{}", session, n));
+            }));
+            tokio::task::yield_now().await;
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let order = order.lock().unwrap();
+        let b = order.iter().position(|s| s == "b1").unwrap();
+        let a3 = order.iter().position(|s| s == "a3").unwrap();
+        assert!(b < a3, "b1 should not wait behind all of a: {:?}", order);
+    }
+}
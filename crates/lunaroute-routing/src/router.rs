@@ -4,9 +4,16 @@
 //! - Model name patterns (e.g., gpt-.* → OpenAI)
 //! - Listener type (OpenAI endpoint → OpenAI provider)
 //! - Header overrides (X-Luna-Provider)
+//! - Caller identity (JWT or client-certificate subject, email, groups, tenant)
 //! - Fallback chains for automatic failover
+//! - Per-error-class fallback policies
+//! - Schedules that activate rules by day, time and date
 
+use crate::fallback_policy::{ErrorPolicies, validate_policies};
+use crate::schedule::Schedule;
 use crate::strategy::RoutingStrategy;
+use chrono::{DateTime, Utc};
+use lunaroute_core::identity::{ClientIdentity, wildcard_match};
 use lunaroute_core::normalized::NormalizedRequest;
 use once_cell::sync::OnceCell;
 use regex::Regex;
@@ -31,6 +38,10 @@ pub struct RoutingContext {
     pub provider_override: Option<String>,
     /// Additional headers that might influence routing
     pub headers: HashMap<String, String>,
+    /// Time to evaluate rule schedules at (None = now)
+    pub at: Option<DateTime<Utc>>,
+    /// Authenticated caller, when ingress established one
+    pub identity: Option<ClientIdentity>,
 }
 
 impl RoutingContext {
@@ -40,6 +51,16 @@ impl RoutingContext {
             listener: None,
             provider_override: None,
             headers: HashMap::new(),
+            at: None,
+            identity: None,
+        }
+    }
+
+    /// Context carrying the identity attached to a normalized request
+    pub fn for_request(request: &NormalizedRequest) -> Self {
+        Self {
+            identity: ClientIdentity::from_request(request),
+            ..Self::new()
         }
     }
 
@@ -60,6 +81,23 @@ impl RoutingContext {
         self.headers.insert(key.into(), value.into());
         self
     }
+
+    /// Evaluate rule schedules at a fixed time instead of now
+    pub fn with_time(mut self, at: DateTime<Utc>) -> Self {
+        self.at = Some(at);
+        self
+    }
+
+    /// Set the caller identity
+    pub fn with_identity(mut self, identity: ClientIdentity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Time rule schedules are evaluated at
+    pub fn time(&self) -> DateTime<Utc> {
+        self.at.unwrap_or_else(Utc::now)
+    }
 }
 
 impl Default for RoutingContext {
@@ -94,6 +132,14 @@ pub struct RoutingRule {
     /// Fallback providers (tried in order if primary/strategy providers fail)
     #[serde(default)]
     pub fallbacks: Vec<String>,
+
+    /// Per-error-class actions taking precedence over `fallbacks`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub on_error: ErrorPolicies,
+
+    /// When the rule is active (None = always)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Schedule>,
 }
 
 impl RoutingRule {
@@ -109,6 +155,8 @@ impl RoutingRule {
             strategy.validate().map_err(|e| e.to_string())?;
         }
 
+        validate_policies(&self.on_error)?;
+
         Ok(())
     }
 
@@ -131,6 +179,13 @@ impl RoutingRule {
 
         ids
     }
+
+    /// Whether the rule's schedule (if any) is active at the given time
+    pub fn is_active_at(&self, at: DateTime<Utc>) -> bool {
+        self.schedule
+            .as_ref()
+            .is_none_or(|schedule| schedule.is_active_at(at))
+    }
 }
 
 /// Matcher for routing rules
@@ -151,6 +206,19 @@ pub enum RuleMatcher {
     /// Match if provider override header is present
     #[serde(rename = "override")]
     ProviderOverride,
+    /// Match the authenticated caller; every field that is set must match.
+    /// `subject` and `email` accept `*` wildcards.
+    #[serde(rename = "identity")]
+    Identity {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subject: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        email: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tenant: Option<String>,
+    },
     /// Always matches (catch-all/default rule)
     #[serde(rename = "always")]
     Always,
@@ -168,6 +236,17 @@ impl Clone for RuleMatcher {
                 listener: *listener,
             },
             RuleMatcher::ProviderOverride => RuleMatcher::ProviderOverride,
+            RuleMatcher::Identity {
+                subject,
+                email,
+                group,
+                tenant,
+            } => RuleMatcher::Identity {
+                subject: subject.clone(),
+                email: email.clone(),
+                group: group.clone(),
+                tenant: tenant.clone(),
+            },
             RuleMatcher::Always => RuleMatcher::Always,
         }
     }
@@ -183,7 +262,7 @@ impl RuleMatcher {
     }
 
     /// Check if this matcher matches the given request and context
-    fn matches(&self, request: &NormalizedRequest, context: &RoutingContext) -> bool {
+    pub(crate) fn matches(&self, request: &NormalizedRequest, context: &RoutingContext) -> bool {
         match self {
             RuleMatcher::ModelPattern { pattern, compiled } => {
                 // Get or compile regex (cached for performance)
@@ -212,6 +291,26 @@ impl RuleMatcher {
                 // Match if override is present
                 context.provider_override.is_some()
             }
+            RuleMatcher::Identity {
+                subject,
+                email,
+                group,
+                tenant,
+            } => context.identity.as_ref().is_some_and(|identity| {
+                subject
+                    .as_ref()
+                    .is_none_or(|p| wildcard_match(p, &identity.subject))
+                    && email.as_ref().is_none_or(|p| {
+                        identity
+                            .email
+                            .as_ref()
+                            .is_some_and(|e| wildcard_match(p, e))
+                    })
+                    && group.as_ref().is_none_or(|g| identity.in_group(g))
+                    && tenant
+                        .as_ref()
+                        .is_none_or(|t| identity.tenant.as_ref() == Some(t))
+            }),
             RuleMatcher::Always => {
                 // Always matches
                 true
@@ -220,6 +319,38 @@ impl RuleMatcher {
     }
 }
 
+impl std::fmt::Display for RuleMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleMatcher::ModelPattern { pattern, .. } => write!(f, "model =~ /{}/", pattern),
+            RuleMatcher::Listener { listener } => write!(f, "listener == {:?}", listener),
+            RuleMatcher::ProviderOverride => write!(f, "provider override present"),
+            RuleMatcher::Identity {
+                subject,
+                email,
+                group,
+                tenant,
+            } => {
+                let conditions: Vec<String> = [
+                    ("subject", subject),
+                    ("email", email),
+                    ("group", group),
+                    ("tenant", tenant),
+                ]
+                .into_iter()
+                .filter_map(|(name, value)| value.as_ref().map(|v| format!("{} == {}", name, v)))
+                .collect();
+                if conditions.is_empty() {
+                    write!(f, "identity present")
+                } else {
+                    write!(f, "identity {}", conditions.join(", "))
+                }
+            }
+            RuleMatcher::Always => write!(f, "always"),
+        }
+    }
+}
+
 /// Routing decision containing target provider(s) and fallbacks
 #[derive(Debug, Clone)]
 pub struct RoutingDecision {
@@ -231,6 +362,8 @@ pub struct RoutingDecision {
     pub fallbacks: Vec<String>,
     /// The rule that matched (for logging/debugging)
     pub matched_rule: Option<String>,
+    /// Per-error-class actions of the matched rule
+    pub on_error: ErrorPolicies,
 }
 
 impl RoutingDecision {
@@ -243,6 +376,28 @@ impl RoutingDecision {
     pub fn get_primary(&self) -> Option<&str> {
         self.primary.as_deref()
     }
+
+    /// The decision narrowed to the providers `keep` accepts (None if none
+    /// remain); a rejected primary is replaced by the first kept fallback
+    pub fn retain_providers(self, keep: impl Fn(&str) -> bool) -> Option<Self> {
+        let mut fallbacks: Vec<String> = self.fallbacks.into_iter().filter(|f| keep(f)).collect();
+        let strategy = self.strategy.and_then(|s| s.retain_providers(&keep));
+        let primary = match self.primary.filter(|p| keep(p)) {
+            Some(primary) => Some(primary),
+            None if strategy.is_none() && !fallbacks.is_empty() => Some(fallbacks.remove(0)),
+            None => None,
+        };
+        if strategy.is_none() && primary.is_none() {
+            return None;
+        }
+        Some(Self {
+            strategy,
+            primary,
+            fallbacks,
+            matched_rule: self.matched_rule,
+            on_error: self.on_error,
+        })
+    }
 }
 
 /// Route table for managing routing rules
@@ -286,12 +441,14 @@ impl RouteTable {
                 primary: Some(provider.clone()),
                 fallbacks: vec![],
                 matched_rule: Some("provider_override".to_string()),
+                on_error: Default::default(),
             });
         }
 
-        // Priority 2: Find first matching rule
+        // Priority 2: Find first matching rule whose schedule is active
+        let at = context.time();
         for rule in &self.rules {
-            if rule.matcher.matches(request, context) {
+            if rule.matcher.matches(request, context) && rule.is_active_at(at) {
                 let rule_name = rule
                     .name
                     .clone()
@@ -328,6 +485,7 @@ impl RouteTable {
                     primary,
                     fallbacks: rule.fallbacks.clone(),
                     matched_rule: Some(rule_name),
+                    on_error: rule.on_error.clone(),
                 });
             }
         }
@@ -423,6 +581,34 @@ mod tests {
         assert!(!matcher.matches(&request, &context2));
     }
 
+    #[test]
+    fn test_rule_matcher_identity() {
+        let matcher: RuleMatcher = serde_json::from_value(serde_json::json!({
+            "type": "identity",
+            "email": "*@example.com",
+            "group": "ml",
+        }))
+        .unwrap();
+        assert_eq!(
+            matcher.to_string(),
+            "identity email == *@example.com, group == ml"
+        );
+
+        let mut identity = ClientIdentity::jwt("user-1");
+        identity.email = Some("alice@example.com".to_string());
+        identity.groups = vec!["eng".to_string(), "ml".to_string()];
+
+        // The identity travels in the request metadata
+        let mut request = create_test_request("any-model");
+        identity.attach(&mut request);
+        assert!(matcher.matches(&request, &RoutingContext::for_request(&request)));
+
+        let request = create_test_request("any-model");
+        assert!(!matcher.matches(&request, &RoutingContext::new()));
+        identity.groups.clear();
+        assert!(!matcher.matches(&request, &RoutingContext::new().with_identity(identity)));
+    }
+
     #[test]
     fn test_rule_matcher_always() {
         let matcher = RuleMatcher::Always;
@@ -444,6 +630,8 @@ mod tests {
             strategy: None,
             primary: Some("provider1".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         table.add_rule(rule);
@@ -460,6 +648,8 @@ mod tests {
                 strategy: None,
                 primary: Some("provider1".to_string()),
                 fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
             },
             RoutingRule {
                 priority: 10,
@@ -468,6 +658,8 @@ mod tests {
                 strategy: None,
                 primary: Some("provider2".to_string()),
                 fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
             },
         ];
 
@@ -486,6 +678,8 @@ mod tests {
             strategy: None,
             primary: Some("provider1".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
         let table = RouteTable::with_rules(vec![rule]);
 
@@ -506,6 +700,8 @@ mod tests {
             strategy: None,
             primary: Some("openai".to_string()),
             fallbacks: vec!["openai_backup".to_string()],
+            on_error: Default::default(),
+            schedule: None,
         };
         let table = RouteTable::with_rules(vec![rule]);
 
@@ -529,6 +725,8 @@ mod tests {
             strategy: None,
             primary: Some("anthropic".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
         let table = RouteTable::with_rules(vec![rule]);
 
@@ -549,6 +747,8 @@ mod tests {
                 strategy: None,
                 primary: Some("provider1".to_string()),
                 fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
             },
             RoutingRule {
                 priority: 10,
@@ -557,6 +757,8 @@ mod tests {
                 strategy: None,
                 primary: Some("provider2".to_string()),
                 fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
             },
         ];
         let table = RouteTable::with_rules(rules);
@@ -579,6 +781,8 @@ mod tests {
             strategy: None,
             primary: Some("openai".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
         let table = RouteTable::with_rules(vec![rule]);
 
@@ -589,6 +793,50 @@ mod tests {
         assert!(decision.is_none());
     }
 
+    #[test]
+    fn test_find_route_schedule() {
+        let overnight: Schedule =
+            serde_yaml::from_str("times: [\"22:00-06:00\"]\ndays: [mon, tue, wed, thu, fri]")
+                .unwrap();
+        let rules = vec![
+            RoutingRule {
+                priority: 20,
+                name: Some("overnight".to_string()),
+                matcher: RuleMatcher::Always,
+                strategy: None,
+                primary: Some("cheap".to_string()),
+                fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: Some(overnight),
+            },
+            RoutingRule {
+                priority: 10,
+                name: Some("default".to_string()),
+                matcher: RuleMatcher::Always,
+                strategy: None,
+                primary: Some("premium".to_string()),
+                fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
+            },
+        ];
+        let table = RouteTable::with_rules(rules);
+        let request = create_test_request("any-model");
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        // Tuesday 23:30 UTC
+        let context = RoutingContext::new().with_time(at("2026-01-06T23:30:00Z"));
+        let decision = table.find_route(&request, &context).unwrap();
+        assert_eq!(decision.primary.as_deref(), Some("cheap"));
+
+        // Tuesday 12:00 UTC, and Saturday 23:30 UTC
+        for time in ["2026-01-06T12:00:00Z", "2026-01-10T23:30:00Z"] {
+            let context = RoutingContext::new().with_time(at(time));
+            let decision = table.find_route(&request, &context).unwrap();
+            assert_eq!(decision.primary.as_deref(), Some("premium"), "{}", time);
+        }
+    }
+
     #[test]
     fn test_route_table_default() {
         let table = RouteTable::default();
@@ -619,6 +867,8 @@ mod tests {
             strategy: None,
             primary: Some("openai".to_string()),
             fallbacks: vec!["openai_backup".to_string()],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let json = serde_json::to_string(&rule).unwrap();
@@ -648,6 +898,8 @@ mod tests {
             strategy: None,
             primary: Some("anthropic".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
 
         let json = serde_json::to_string(&rule).unwrap();
@@ -731,6 +983,8 @@ mod tests {
                 strategy: None,
                 primary: Some("openai".to_string()),
                 fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
             },
             RoutingRule {
                 priority: 5,
@@ -739,6 +993,8 @@ mod tests {
                 strategy: None,
                 primary: Some("default".to_string()),
                 fallbacks: vec![],
+                on_error: Default::default(),
+                schedule: None,
             },
         ];
 
@@ -836,6 +1092,8 @@ mod tests {
             strategy: None,
             primary: Some("provider1".to_string()),
             fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
         };
         let table = RouteTable::with_rules(vec![rule]);
 
@@ -885,6 +1143,8 @@ mod tests {
             strategy: None,
             primary: Some("primary_only".to_string()),
             fallbacks: vec![], // Empty fallbacks
+            on_error: Default::default(),
+            schedule: None,
         };
         let table = RouteTable::with_rules(vec![rule]);
 
@@ -0,0 +1,305 @@
+//! Schedule-based rule activation
+//!
+//! A routing rule with a `schedule` only matches while the schedule is active,
+//! so a higher-priority rule can take over for part of the day or week and the
+//! regular rules apply the rest of the time. Typical uses are sending batch
+//! traffic to a cheaper model overnight, or routing around a provider during an
+//! announced maintenance window.
+//!
+//! ```yaml
+//! schedule:
+//!   timezone: America/New_York
+//!   days: [mon, tue, wed, thu, fri]
+//!   times: ["22:00-06:00"]
+//!   dates: ["2026-12-24..2027-01-02"]
+//! ```
+//!
+//! Every condition that is set must hold; an empty list places no constraint.
+//! A time range that wraps past midnight belongs to the day it starts on, so
+//! `days: [fri]` with `times: ["22:00-06:00"]` covers Friday night until 06:00
+//! on Saturday.
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// When a routing rule is active
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    /// IANA timezone the days, times and dates are evaluated in (default UTC)
+    #[serde(default = "default_timezone")]
+    pub timezone: Tz,
+    /// Days of the week (`mon`, `tuesday`, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<Weekday>,
+    /// Time-of-day ranges (`HH:MM-HH:MM`, end exclusive)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub times: Vec<TimeRange>,
+    /// Calendar date ranges (`YYYY-MM-DD` or `YYYY-MM-DD..YYYY-MM-DD`, inclusive)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dates: Vec<DateRange>,
+}
+
+fn default_timezone() -> Tz {
+    Tz::UTC
+}
+
+impl Schedule {
+    /// Whether the schedule is active at the given instant
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        let local = now.with_timezone(&self.timezone);
+        let time = local.time();
+        let today = local.date_naive();
+
+        let day_matches = |day: NaiveDate| {
+            (self.days.is_empty() || self.days.contains(&day.weekday()))
+                && (self.dates.is_empty() || self.dates.iter().any(|d| d.contains(day)))
+        };
+
+        // Each matching time range is checked against the day it started on
+        if self.times.is_empty() {
+            day_matches(today)
+        } else {
+            self.times
+                .iter()
+                .filter_map(|range| range.start_day(time, today))
+                .any(day_matches)
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if !self.days.is_empty() {
+            let days: Vec<String> = self.days.iter().map(|d| d.to_string()).collect();
+            parts.push(days.join(","));
+        }
+        if !self.times.is_empty() {
+            let times: Vec<String> = self.times.iter().map(|t| t.to_string()).collect();
+            parts.push(times.join(","));
+        }
+        if !self.dates.is_empty() {
+            let dates: Vec<String> = self.dates.iter().map(|d| d.to_string()).collect();
+            parts.push(dates.join(","));
+        }
+        if parts.is_empty() {
+            parts.push("always".to_string());
+        }
+        write!(f, "{} {}", parts.join(" "), self.timezone)
+    }
+}
+
+/// Time-of-day range; wraps past midnight when `end` is not after `start`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeRange {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeRange {
+    /// Day the range containing `time` started on, if `time` is in range
+    fn start_day(&self, time: NaiveTime, today: NaiveDate) -> Option<NaiveDate> {
+        if self.start < self.end {
+            (self.start <= time && time < self.end).then_some(today)
+        } else if time >= self.start {
+            Some(today)
+        } else if time < self.end {
+            today.pred_opt()
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("invalid time range '{}', expected HH:MM-HH:MM", s))?;
+        let parse = |t: &str| {
+            let t = t.trim();
+            // "24:00" is accepted as the end of the day
+            let t = if t == "24:00" { "00:00" } else { t };
+            NaiveTime::parse_from_str(t, "%H:%M")
+                .map_err(|_| format!("invalid time '{}' in range '{}'", t, s))
+        };
+        let range = Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if range.start == range.end {
+            return Err(format!(
+                "time range '{}' is empty; omit 'times' for the whole day",
+                s
+            ));
+        }
+        Ok(range)
+    }
+}
+
+impl TryFrom<String> for TimeRange {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<TimeRange> for String {
+    fn from(range: TimeRange) -> Self {
+        range.to_string()
+    }
+}
+
+impl fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// Inclusive calendar date range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    fn contains(&self, day: NaiveDate) -> bool {
+        self.start <= day && day <= self.end
+    }
+}
+
+impl FromStr for DateRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once("..").unwrap_or((s, s));
+        let parse = |d: &str| {
+            NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d")
+                .map_err(|_| format!("invalid date '{}' in range '{}'", d.trim(), s))
+        };
+        let range = Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if range.start > range.end {
+            return Err(format!("date range '{}' ends before it starts", s));
+        }
+        Ok(range)
+    }
+}
+
+impl TryFrom<String> for DateRange {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<DateRange> for String {
+    fn from(range: DateRange) -> Self {
+        range.to_string()
+    }
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}..{}", self.start, self.end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_overnight_weekday_window_in_timezone() {
+        let schedule: Schedule = serde_yaml::from_str(
+            r#"
+timezone: America/New_York
+days: [fri]
+times: ["22:00-06:00"]
+"#,
+        )
+        .unwrap();
+
+        // Friday 23:00 and Saturday 05:00 in New York (UTC-5 in January)
+        assert!(schedule.is_active_at(at("2026-01-10T04:00:00Z")));
+        assert!(schedule.is_active_at(at("2026-01-10T10:00:00Z")));
+        // Saturday 07:00, Saturday 23:00, Friday 21:00
+        assert!(!schedule.is_active_at(at("2026-01-10T12:00:00Z")));
+        assert!(!schedule.is_active_at(at("2026-01-11T04:00:00Z")));
+        assert!(!schedule.is_active_at(at("2026-01-10T02:00:00Z")));
+        assert_eq!(schedule.to_string(), "Fri 22:00-06:00 America/New_York");
+    }
+
+    #[test]
+    fn test_overlapping_ranges_check_each_start_day() {
+        let schedule: Schedule = serde_yaml::from_str(
+            r#"
+days: [sat]
+times: ["22:00-06:00", "00:00-12:00"]
+"#,
+        )
+        .unwrap();
+
+        // Saturday 01:00 is in Friday's overnight range and Saturday's morning
+        assert!(schedule.is_active_at(at("2026-01-10T01:00:00Z")));
+        // Sunday 01:00 is in Saturday's overnight range
+        assert!(schedule.is_active_at(at("2026-01-11T01:00:00Z")));
+        // Friday 23:00 and Sunday 08:00
+        assert!(!schedule.is_active_at(at("2026-01-09T23:00:00Z")));
+        assert!(!schedule.is_active_at(at("2026-01-11T08:00:00Z")));
+    }
+
+    #[test]
+    fn test_date_ranges_and_parsing() {
+        let schedule: Schedule = serde_yaml::from_str(
+            r#"
+dates: ["2026-03-14", "2026-12-24..2026-12-26"]
+times: ["02:00-04:00", "20:00-24:00"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(schedule.timezone, Tz::UTC);
+        assert!(schedule.is_active_at(at("2026-03-14T03:00:00Z")));
+        assert!(schedule.is_active_at(at("2026-12-26T23:59:00Z")));
+        assert!(!schedule.is_active_at(at("2026-03-14T05:00:00Z")));
+        assert!(!schedule.is_active_at(at("2026-03-15T03:00:00Z")));
+
+        let always = Schedule {
+            timezone: Tz::UTC,
+            days: vec![],
+            times: vec![],
+            dates: vec![],
+        };
+        assert!(always.is_active_at(Utc::now()));
+
+        assert!("09:00-09:00".parse::<TimeRange>().is_err());
+        assert!("9am-5pm".parse::<TimeRange>().is_err());
+        assert!("2026-02-01..2026-01-01".parse::<DateRange>().is_err());
+        assert!(serde_yaml::from_str::<Schedule>("timezone: Mars/Olympus").is_err());
+    }
+}
@@ -61,7 +61,9 @@
 //! assert!(strategy.validate().is_ok()); // Valid
 //! ```
 
+use crate::persistence::{RateLimitSnapshot, instant_to_ms, ms_to_instant};
 use dashmap::DashMap;
+use lunaroute_core::quota::{ProviderQuota, QuotaKind};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -115,6 +117,11 @@ fn default_backoff_base() -> u64 {
     60
 }
 
+/// Default quota headroom (percent of the upstream limit)
+fn default_quota_headroom() -> u8 {
+    5
+}
+
 /// Routing strategy configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -142,6 +149,12 @@ pub enum RoutingStrategy {
         /// Used only when retry-after header is missing
         #[serde(default = "default_backoff_base")]
         exponential_backoff_base_secs: u64,
+        /// Divert traffic once a provider's remaining quota (from its
+        /// rate-limit response headers) drops below this percent of the
+        /// limit, before it starts returning 429s (default: 5, 0 = only when
+        /// exhausted)
+        #[serde(default = "default_quota_headroom")]
+        quota_headroom_percent: u8,
     },
 }
 
@@ -177,6 +190,38 @@ impl RoutingStrategy {
         }
     }
 
+    /// The strategy narrowed to the providers `keep` accepts (None if none remain)
+    pub fn retain_providers(&self, keep: impl Fn(&str) -> bool) -> Option<Self> {
+        let retain =
+            |ids: &[String]| -> Vec<String> { ids.iter().filter(|id| keep(id)).cloned().collect() };
+        let narrowed = match self {
+            RoutingStrategy::RoundRobin { providers } => RoutingStrategy::RoundRobin {
+                providers: retain(providers),
+            },
+            RoutingStrategy::WeightedRoundRobin { providers } => {
+                RoutingStrategy::WeightedRoundRobin {
+                    providers: providers
+                        .iter()
+                        .filter(|p| p.weight > 0 && keep(&p.id))
+                        .cloned()
+                        .collect(),
+                }
+            }
+            RoutingStrategy::LimitsAlternative {
+                primary_providers,
+                alternative_providers,
+                exponential_backoff_base_secs,
+                quota_headroom_percent,
+            } => RoutingStrategy::LimitsAlternative {
+                primary_providers: retain(primary_providers),
+                alternative_providers: retain(alternative_providers),
+                exponential_backoff_base_secs: *exponential_backoff_base_secs,
+                quota_headroom_percent: *quota_headroom_percent,
+            },
+        };
+        (!narrowed.provider_ids().is_empty()).then_some(narrowed)
+    }
+
     /// Validate the strategy configuration
     pub fn validate(&self) -> Result<(), StrategyError> {
         match self {
@@ -207,6 +252,7 @@ impl RoutingStrategy {
                 primary_providers,
                 alternative_providers,
                 exponential_backoff_base_secs,
+                quota_headroom_percent,
             } => {
                 if primary_providers.is_empty() {
                     return Err(StrategyError::InvalidLimitsAlternative(
@@ -223,6 +269,11 @@ impl RoutingStrategy {
                         "exponential_backoff_base_secs must be greater than 0".to_string(),
                     ));
                 }
+                if *quota_headroom_percent > 100 {
+                    return Err(StrategyError::InvalidLimitsAlternative(
+                        "quota_headroom_percent cannot exceed 100".to_string(),
+                    ));
+                }
                 Ok(())
             }
         }
@@ -237,6 +288,8 @@ pub struct StrategyState {
     weighted_state: Arc<WeightedRoundRobinState>,
     /// Rate limit states per provider (lock-free concurrent access)
     rate_limit_states: Arc<DashMap<String, RateLimitState>>,
+    /// Latest upstream quota per provider, from rate-limit response headers
+    quotas: Arc<DashMap<String, ProviderQuota>>,
 }
 
 impl StrategyState {
@@ -246,7 +299,24 @@ impl StrategyState {
             round_robin_counter: AtomicUsize::new(0),
             weighted_state: Arc::new(WeightedRoundRobinState::new()),
             rate_limit_states: Arc::new(DashMap::new()),
+            quotas: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Record the latest upstream quota reported by a provider
+    pub fn record_quota(&self, provider_id: &str, quota: ProviderQuota) {
+        if self.quotas.len() >= MAX_RATE_LIMIT_ENTRIES && !self.quotas.contains_key(provider_id) {
+            return;
         }
+        self.quotas.insert(provider_id.to_string(), quota);
+    }
+
+    /// Rate-limit window in which a provider is within `headroom_percent`
+    /// of its upstream limit, if any
+    pub fn quota_low(&self, provider_id: &str, headroom_percent: u8) -> Option<QuotaKind> {
+        self.quotas
+            .get(provider_id)
+            .and_then(|quota| quota.low_window(headroom_percent))
     }
 
     /// Record a rate limit event for a provider
@@ -329,8 +399,88 @@ impl StrategyState {
             .unwrap_or(false)
     }
 
+    /// Capture active rate-limit backoffs for persistence
+    pub fn rate_limit_snapshots(&self, rule: &str) -> Vec<RateLimitSnapshot> {
+        self.rate_limit_states
+            .iter()
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| RateLimitSnapshot {
+                rule: rule.to_string(),
+                provider: entry.provider_id.clone(),
+                until_ms: instant_to_ms(entry.rate_limited_until),
+                consecutive_rate_limits: entry.consecutive_rate_limits,
+                last_rate_limit_ms: instant_to_ms(entry.last_rate_limit),
+            })
+            .collect()
+    }
+
+    /// Restore a persisted rate-limit backoff
+    ///
+    /// Expired snapshots and snapshots that end before the local backoff are
+    /// ignored. Returns true if the local backoff changed.
+    pub fn restore_rate_limit(&self, snapshot: &RateLimitSnapshot) -> bool {
+        if snapshot.is_expired() {
+            return false;
+        }
+        let Some(until) = ms_to_instant(snapshot.until_ms) else {
+            return false;
+        };
+        match self.rate_limit_states.get(&snapshot.provider) {
+            Some(local) if local.rate_limited_until >= until => return false,
+            None if self.rate_limit_states.len() >= MAX_RATE_LIMIT_ENTRIES => return false,
+            _ => {}
+        }
+        let now = Instant::now();
+        self.rate_limit_states.insert(
+            snapshot.provider.clone(),
+            RateLimitState {
+                provider_id: snapshot.provider.clone(),
+                rate_limited_until: until,
+                consecutive_rate_limits: snapshot.consecutive_rate_limits,
+                last_rate_limit: ms_to_instant(snapshot.last_rate_limit_ms).unwrap_or(now),
+            },
+        );
+        true
+    }
+
     /// Select next provider using the strategy
     pub fn select_provider(&self, strategy: &RoutingStrategy) -> Result<String, StrategyError> {
+        self.pick_provider(strategy, true)
+    }
+
+    /// Return the provider the next [`select_provider`](Self::select_provider)
+    /// call would choose, without advancing round-robin counters
+    pub fn peek_provider(&self, strategy: &RoutingStrategy) -> Result<String, StrategyError> {
+        self.pick_provider(strategy, false)
+    }
+
+    /// Time until the first of a strategy's providers leaves rate-limit
+    /// backoff (None if none of them is rate-limited)
+    pub fn earliest_rate_limit_reset(&self, strategy: &RoutingStrategy) -> Option<Duration> {
+        strategy
+            .provider_ids()
+            .into_iter()
+            .filter_map(|id| self.rate_limit_remaining(id))
+            .min()
+    }
+
+    /// Time remaining until a rate-limited provider may be retried
+    pub fn rate_limit_remaining(&self, provider_id: &str) -> Option<Duration> {
+        self.rate_limit_states
+            .get(provider_id)
+            .filter(|state| !state.is_expired())
+            .map(|state| {
+                state
+                    .rate_limited_until
+                    .saturating_duration_since(Instant::now())
+            })
+    }
+
+    fn pick_provider(
+        &self,
+        strategy: &RoutingStrategy,
+        advance: bool,
+    ) -> Result<String, StrategyError> {
         match strategy {
             RoutingStrategy::RoundRobin { providers } => {
                 if providers.is_empty() {
@@ -338,44 +488,45 @@ impl StrategyState {
                 }
 
                 // Use wrapping_add with AcqRel ordering for thread safety
-                let index = self
-                    .round_robin_counter
-                    .fetch_update(Ordering::AcqRel, Ordering::Acquire, |x| {
-                        Some(x.wrapping_add(1))
-                    })
-                    .unwrap();
+                let index = if advance {
+                    self.round_robin_counter
+                        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |x| {
+                            Some(x.wrapping_add(1))
+                        })
+                        .unwrap()
+                } else {
+                    self.round_robin_counter.load(Ordering::Acquire)
+                };
                 let provider_index = index % providers.len();
                 Ok(providers[provider_index].clone())
             }
 
             RoutingStrategy::WeightedRoundRobin { providers } => {
-                self.weighted_state.select_provider(providers)
+                self.weighted_state.select_provider(providers, advance)
             }
 
             RoutingStrategy::LimitsAlternative {
                 primary_providers,
                 alternative_providers,
+                quota_headroom_percent,
                 ..
             } => {
                 // Clean up expired rate limit states
-                self.clear_expired_rate_limits();
-
-                // Try primary providers first
-                for provider_id in primary_providers {
-                    if !self.is_rate_limited(provider_id) {
-                        return Ok(provider_id.clone());
-                    }
-                }
-
-                // All primaries rate-limited, try alternatives
-                for provider_id in alternative_providers {
-                    if !self.is_rate_limited(provider_id) {
-                        return Ok(provider_id.clone());
-                    }
+                if advance {
+                    self.clear_expired_rate_limits();
                 }
 
-                // All providers rate-limited
-                Err(StrategyError::AllProvidersRateLimited)
+                // Primaries first, then alternatives; skip providers close to
+                // their upstream quota while any provider has headroom
+                let ordered = || primary_providers.iter().chain(alternative_providers);
+                ordered()
+                    .find(|p| {
+                        !self.is_rate_limited(p)
+                            && self.quota_low(p, *quota_headroom_percent).is_none()
+                    })
+                    .or_else(|| ordered().find(|p| !self.is_rate_limited(p)))
+                    .cloned()
+                    .ok_or(StrategyError::AllProvidersRateLimited)
             }
         }
     }
@@ -422,7 +573,11 @@ impl WeightedRoundRobinState {
 
     /// Select provider using weighted round-robin algorithm
     /// Uses smooth weighted round-robin (Nginx algorithm)
-    fn select_provider(&self, providers: &[WeightedProvider]) -> Result<String, StrategyError> {
+    fn select_provider(
+        &self,
+        providers: &[WeightedProvider],
+        advance: bool,
+    ) -> Result<String, StrategyError> {
         if providers.is_empty() {
             return Err(StrategyError::EmptyProviderList);
         }
@@ -438,12 +593,15 @@ impl WeightedRoundRobinState {
         }
 
         // Get current position and increment with wrapping and proper ordering
-        let position = self
-            .current_position
-            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |x| {
-                Some(x.wrapping_add(1))
-            })
-            .unwrap();
+        let position = if advance {
+            self.current_position
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |x| {
+                    Some(x.wrapping_add(1))
+                })
+                .unwrap()
+        } else {
+            self.current_position.load(Ordering::Acquire)
+        };
 
         // Map position to provider based on cumulative weights
         let normalized_position = (position % total_weight as usize) as u32;
@@ -501,6 +659,64 @@ mod tests {
         assert_eq!(state.select_provider(&strategy).unwrap(), "p2");
     }
 
+    #[test]
+    fn test_peek_provider_does_not_advance() {
+        let strategy = RoutingStrategy::RoundRobin {
+            providers: vec!["p1".to_string(), "p2".to_string()],
+        };
+        let weighted = RoutingStrategy::WeightedRoundRobin {
+            providers: vec![
+                WeightedProvider {
+                    id: "a".to_string(),
+                    weight: 1,
+                },
+                WeightedProvider {
+                    id: "b".to_string(),
+                    weight: 1,
+                },
+            ],
+        };
+
+        let state = StrategyState::new();
+        assert_eq!(state.peek_provider(&strategy).unwrap(), "p1");
+        assert_eq!(state.peek_provider(&strategy).unwrap(), "p1");
+        assert_eq!(state.select_provider(&strategy).unwrap(), "p1");
+        assert_eq!(state.peek_provider(&strategy).unwrap(), "p2");
+
+        assert_eq!(state.peek_provider(&weighted).unwrap(), "a");
+        assert_eq!(state.select_provider(&weighted).unwrap(), "a");
+        assert_eq!(state.peek_provider(&weighted).unwrap(), "b");
+    }
+
+    #[test]
+    fn test_rate_limit_snapshot_restore() {
+        let state = StrategyState::new();
+        state.record_rate_limit("p1", Some(600), 60);
+
+        let snapshots = state.rate_limit_snapshots("rule");
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].provider, "p1");
+        assert_eq!(snapshots[0].rule, "rule");
+
+        let restored = StrategyState::new();
+        assert!(restored.restore_rate_limit(&snapshots[0]));
+        assert!(restored.is_rate_limited("p1"));
+        assert!(restored.rate_limit_remaining("p1").unwrap() > Duration::from_secs(590));
+
+        // A shorter backoff from another instance does not shorten ours
+        let mut shorter = snapshots[0].clone();
+        shorter.until_ms -= 300_000;
+        assert!(!restored.restore_rate_limit(&shorter));
+        assert!(restored.rate_limit_remaining("p1").unwrap() > Duration::from_secs(590));
+
+        // Expired backoffs are not restored
+        let mut expired = snapshots[0].clone();
+        expired.until_ms = 1;
+        let restored = StrategyState::new();
+        assert!(!restored.restore_rate_limit(&expired));
+        assert!(!restored.is_rate_limited("p1"));
+    }
+
     #[test]
     fn test_round_robin_single_provider() {
         let strategy = RoutingStrategy::RoundRobin {
@@ -718,6 +934,7 @@ mod tests {
             round_robin_counter: std::sync::atomic::AtomicUsize::new(usize::MAX - 1),
             weighted_state: Arc::new(WeightedRoundRobinState::new()),
             rate_limit_states: Arc::new(DashMap::new()),
+            quotas: Arc::new(DashMap::new()),
         };
 
         // Should not panic even when wrapping
@@ -932,6 +1149,7 @@ mod tests {
             primary_providers: vec!["p1".to_string()],
             alternative_providers: vec!["p2".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         };
         assert!(strategy.validate().is_ok());
 
@@ -940,6 +1158,7 @@ mod tests {
             primary_providers: vec![],
             alternative_providers: vec!["p2".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         };
         assert!(strategy.validate().is_err());
 
@@ -948,6 +1167,7 @@ mod tests {
             primary_providers: vec!["p1".to_string()],
             alternative_providers: vec![],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         };
         assert!(strategy.validate().is_err());
 
@@ -956,6 +1176,7 @@ mod tests {
             primary_providers: vec!["p1".to_string()],
             alternative_providers: vec!["p2".to_string()],
             exponential_backoff_base_secs: 0,
+            quota_headroom_percent: 5,
         };
         assert!(strategy.validate().is_err());
     }
@@ -966,6 +1187,7 @@ mod tests {
             primary_providers: vec!["primary1".to_string(), "primary2".to_string()],
             alternative_providers: vec!["alt1".to_string(), "alt2".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         };
 
         let state = StrategyState::new();
@@ -995,6 +1217,7 @@ mod tests {
         state.record_rate_limit("alt2", Some(60), 60);
 
         // All providers rate-limited, should return error
+        assert!(state.earliest_rate_limit_reset(&strategy).unwrap() <= Duration::from_secs(60));
         let result = state.select_provider(&strategy);
         assert!(result.is_err());
         assert!(matches!(
@@ -1003,12 +1226,56 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_limits_alternative_diverts_on_low_quota() {
+        let strategy = RoutingStrategy::LimitsAlternative {
+            primary_providers: vec!["primary".to_string()],
+            alternative_providers: vec!["alt".to_string()],
+            exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 10,
+        };
+        let quota = |remaining: &str| {
+            ProviderQuota::from_headers(vec![
+                ("x-ratelimit-limit-requests", "100"),
+                ("x-ratelimit-remaining-requests", remaining),
+                ("x-ratelimit-reset-requests", "1m"),
+            ])
+            .unwrap()
+        };
+
+        let state = StrategyState::new();
+        state.record_quota("primary", quota("50"));
+        assert_eq!(state.select_provider(&strategy).unwrap(), "primary");
+
+        // 9 of 100 requests left: divert before the first 429
+        state.record_quota("primary", quota("9"));
+        assert_eq!(state.quota_low("primary", 10), Some(QuotaKind::Requests));
+        assert_eq!(state.select_provider(&strategy).unwrap(), "alt");
+
+        // Every provider is low: still prefer the primary over failing
+        state.record_quota("alt", quota("0"));
+        assert_eq!(state.select_provider(&strategy).unwrap(), "primary");
+
+        // A rate-limited provider is never picked, even with headroom elsewhere low
+        state.record_rate_limit("primary", Some(60), 60);
+        assert_eq!(state.select_provider(&strategy).unwrap(), "alt");
+
+        let invalid = RoutingStrategy::LimitsAlternative {
+            primary_providers: vec!["primary".to_string()],
+            alternative_providers: vec!["alt".to_string()],
+            exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 101,
+        };
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn test_limits_alternative_provider_ids() {
         let strategy = RoutingStrategy::LimitsAlternative {
             primary_providers: vec!["p1".to_string(), "p2".to_string()],
             alternative_providers: vec!["a1".to_string(), "a2".to_string()],
             exponential_backoff_base_secs: 60,
+            quota_headroom_percent: 5,
         };
 
         let ids = strategy.provider_ids();
@@ -140,6 +140,8 @@ async fn test_routing_with_fallback_recovery() {
         strategy: None,
         primary: Some("primary".to_string()),
         fallbacks: vec!["fallback".to_string()],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -181,6 +183,8 @@ async fn test_circuit_breaker_opens_and_closes() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -234,6 +238,8 @@ async fn test_health_monitoring_tracks_failures() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -293,6 +299,8 @@ async fn test_multiple_fallbacks_in_sequence() {
         strategy: None,
         primary: Some("primary".to_string()),
         fallbacks: vec!["fallback1".to_string(), "fallback2".to_string()],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -338,6 +346,8 @@ async fn test_concurrent_requests() {
         strategy: None,
         primary: Some("test-provider".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -389,6 +399,8 @@ async fn test_model_based_routing() {
             strategy: None,
             primary: Some("openai".to_string()),
             fallbacks: vec!["anthropic".to_string()],
+            on_error: Default::default(),
+            schedule: None,
         },
         RoutingRule {
             priority: 10,
@@ -397,6 +409,8 @@ async fn test_model_based_routing() {
             strategy: None,
             primary: Some("anthropic".to_string()),
             fallbacks: vec!["openai".to_string()],
+            on_error: Default::default(),
+            schedule: None,
         },
     ];
 
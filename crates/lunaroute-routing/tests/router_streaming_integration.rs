@@ -174,6 +174,8 @@ async fn test_streaming_basic_routing() {
         strategy: None,
         primary: Some("primary".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -222,6 +224,8 @@ async fn test_streaming_fallback_with_circuit_breaker() {
         strategy: None,
         primary: Some("primary".to_string()),
         fallbacks: vec!["fallback".to_string()],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -285,6 +289,8 @@ async fn test_streaming_error_handling() {
         strategy: None,
         primary: Some("primary".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -316,6 +322,8 @@ async fn test_streaming_circuit_breaker_blocks() {
         strategy: None,
         primary: Some("primary".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -399,6 +407,8 @@ async fn test_streaming_multiple_chunks() {
         strategy: None,
         primary: Some("test".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -466,6 +476,8 @@ async fn test_streaming_with_usage_tracking() {
         strategy: None,
         primary: Some("test".to_string()),
         fallbacks: vec![],
+        on_error: Default::default(),
+        schedule: None,
     }];
 
     let route_table = RouteTable::with_rules(rules);
@@ -0,0 +1,1400 @@
+//! Admin API
+//!
+//! Operational endpoints served next to the proxy API:
+//! - `POST /admin/routing/explain`: dry-run routing for a sample request or a
+//!   recorded request ID, without sending any traffic upstream
+//! - `GET/POST /admin/keys`, `POST /admin/keys/{id}/rotate`,
+//!   `DELETE /admin/keys/{id}`: manage ingress API keys (requires
+//!   `admin.token`)
+//! - `GET /admin/budgets`: current usage of every budget by every subject
+//! - `/admin/tenants...`: create, list, suspend and delete tenants, upload
+//!   and validate their configs, view config history and issue their API
+//!   keys (multi-tenant mode; requires `admin.token`)
+//!
+//! When `admin.token` is configured, requests must carry
+//! `Authorization: Bearer <token>`.
+
+use crate::config::{ApiDialect, ServerConfig};
+use axum::{
+    Json,
+    extract::Path,
+    extract::{Query, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+};
+use chrono::{DateTime, Utc};
+use lunaroute_core::config_store::ConfigStore;
+use lunaroute_core::tenant::{TenantId, TenantStatus};
+use lunaroute_ingress::auth::{ApiKey, ApiKeyError, ApiKeyStore, NewApiKey};
+use lunaroute_ingress::budget::BudgetTracker;
+use lunaroute_ingress::tenant::TenantResolver;
+use lunaroute_ingress::{ProviderRegistry, ProviderType};
+use lunaroute_routing::{
+    ExperimentSet, ModelAliasTable, Router, RoutingContext, RoutingExplanation,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Shared state for admin handlers
+pub struct AdminState {
+    /// Router used by non-passthrough handlers
+    pub router: Arc<Router>,
+    /// Whether ingress handlers run in passthrough mode (route table unused)
+    pub passthrough: bool,
+    /// Configured API dialect (picks the listener when a request does not say)
+    pub api_dialect: ApiDialect,
+    /// Provider registry for markers, experiments and aliases
+    pub provider_registry: Arc<ProviderRegistry>,
+    /// Model alias table
+    pub model_aliases: Option<Arc<ModelAliasTable>>,
+    /// A/B experiments
+    pub experiments: Option<Arc<ExperimentSet>>,
+    /// JSONL sessions directory for recorded request lookup
+    pub sessions_dir: Option<PathBuf>,
+    /// Bearer token required for admin requests (None = open)
+    pub token: Option<String>,
+    /// Ingress API keys (None = API key auth disabled)
+    pub api_keys: Option<Arc<ApiKeyStore>>,
+    /// Budget tracker (None = budgets disabled)
+    pub budgets: Option<Arc<BudgetTracker>>,
+    /// Tenant resolver (None = not in multi-tenant mode)
+    pub tenants: Option<Arc<TenantResolver>>,
+}
+
+/// Body of `POST /admin/routing/explain`
+#[derive(Debug, Default, Deserialize)]
+pub struct ExplainRequest {
+    /// Inline request body (Anthropic or OpenAI format)
+    #[serde(default)]
+    pub request: Option<serde_json::Value>,
+    /// Recorded request to replay instead of an inline body
+    #[serde(default)]
+    pub request_id: Option<String>,
+    /// Session containing `request_id` (narrows the search)
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Listener that receives the request: "anthropic" or "openai"
+    #[serde(default)]
+    pub listener: Option<String>,
+    /// Headers the request would carry (header-keyed experiments, session IDs)
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Time to evaluate rule schedules at (default now)
+    #[serde(default)]
+    pub at: Option<DateTime<Utc>>,
+    /// Caller identity to evaluate `identity` rules against
+    #[serde(default)]
+    pub identity: Option<lunaroute_core::identity::ClientIdentity>,
+}
+
+/// Body of `POST /admin/keys/{id}/rotate`
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateKeyRequest {
+    /// How long the old key keeps working (default 24h)
+    #[serde(default = "default_rotation_grace_secs")]
+    pub grace_secs: u64,
+}
+
+fn default_rotation_grace_secs() -> u64 {
+    86400
+}
+
+/// Body of `POST /admin/tenants`
+#[derive(Debug, Deserialize)]
+pub struct CreateTenantRequest {
+    /// Tenant name (subdomain label and header value)
+    pub name: String,
+    /// Initial configuration
+    #[serde(default)]
+    pub config: Option<serde_json::Value>,
+}
+
+/// Query of `PUT /admin/tenants/{tenant}/config`
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigUploadQuery {
+    /// Validate without storing
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Build the admin API router
+pub fn admin_router(state: Arc<AdminState>) -> axum::Router {
+    axum::Router::new()
+        .route("/admin/routing/explain", post(explain_route))
+        .route("/admin/keys", get(list_keys).post(create_key))
+        .route("/admin/keys/{id}", delete(revoke_key))
+        .route("/admin/keys/{id}/rotate", post(rotate_key))
+        .route("/admin/budgets", get(list_budgets))
+        .route("/admin/tenants", get(list_tenants).post(create_tenant))
+        .route(
+            "/admin/tenants/{tenant}",
+            get(get_tenant).delete(delete_tenant),
+        )
+        .route("/admin/tenants/{tenant}/suspend", post(suspend_tenant))
+        .route("/admin/tenants/{tenant}/resume", post(resume_tenant))
+        .route(
+            "/admin/tenants/{tenant}/config",
+            get(get_tenant_config).put(put_tenant_config),
+        )
+        .route(
+            "/admin/tenants/{tenant}/config/history",
+            get(tenant_config_history),
+        )
+        .route(
+            "/admin/tenants/{tenant}/keys",
+            get(list_tenant_keys).post(create_tenant_key),
+        )
+        .with_state(state)
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(serde_json::json!({ "error": message.into() }))).into_response()
+}
+
+/// Check the bearer token in constant time
+fn authorized(state: &AdminState, headers: &HeaderMap) -> bool {
+    let Some(expected) = &state.token else {
+        return true;
+    };
+    let Some(provided) = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Key store for key management requests, or the error response
+///
+/// Key management always needs `admin.token`: an open admin API would let
+/// anyone who reaches the port mint keys.
+fn key_store<'a>(
+    state: &'a AdminState,
+    headers: &HeaderMap,
+) -> Result<&'a ApiKeyStore, (StatusCode, &'static str)> {
+    if state.token.is_none() {
+        return Err((StatusCode::FORBIDDEN, "Set admin.token to manage API keys"));
+    }
+    if !authorized(state, headers) {
+        return Err((StatusCode::UNAUTHORIZED, "Missing or invalid admin token"));
+    }
+    state.api_keys.as_deref().ok_or((
+        StatusCode::NOT_FOUND,
+        "API key authentication is not enabled",
+    ))
+}
+
+/// Usage of every budget by every subject that has used it
+async fn list_budgets(State(state): State<Arc<AdminState>>, headers: HeaderMap) -> Response {
+    if !authorized(&state, &headers) {
+        return error(StatusCode::UNAUTHORIZED, "Missing or invalid admin token");
+    }
+    let Some(budgets) = &state.budgets else {
+        return error(StatusCode::NOT_FOUND, "Budgets are not enabled");
+    };
+    match budgets.status().await {
+        Ok(status) => Json(status).into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+fn key_error(err: ApiKeyError) -> Response {
+    let status = match err {
+        ApiKeyError::NotFound(_) => StatusCode::NOT_FOUND,
+        ApiKeyError::InvalidMetadata(_) | ApiKeyError::Expired | ApiKeyError::Revoked => {
+            StatusCode::BAD_REQUEST
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    error(status, err.to_string())
+}
+
+/// List API keys (metadata only)
+async fn list_keys(State(state): State<Arc<AdminState>>, headers: HeaderMap) -> Response {
+    let keys = match key_store(&state, &headers) {
+        Ok(keys) => keys,
+        Err((status, message)) => return error(status, message),
+    };
+    match keys.list().await {
+        Ok(list) => Json(list).into_response(),
+        Err(e) => key_error(e),
+    }
+}
+
+/// Issue a key; the token is only ever returned here
+async fn create_key(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Json(body): Json<NewApiKey>,
+) -> Response {
+    let keys = match key_store(&state, &headers) {
+        Ok(keys) => keys,
+        Err((status, message)) => return error(status, message),
+    };
+    match keys.create(body).await {
+        Ok((token, key)) => (
+            StatusCode::CREATED,
+            Json(serde_json::json!({ "key": token, "api_key": key })),
+        )
+            .into_response(),
+        Err(e) => key_error(e),
+    }
+}
+
+/// Replace a key, keeping the old one valid for a grace period
+async fn rotate_key(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    body: Option<Json<RotateKeyRequest>>,
+) -> Response {
+    let keys = match key_store(&state, &headers) {
+        Ok(keys) => keys,
+        Err((status, message)) => return error(status, message),
+    };
+    let grace = body
+        .map(|Json(b)| b.grace_secs)
+        .unwrap_or_else(default_rotation_grace_secs);
+    match keys
+        .rotate(&id, std::time::Duration::from_secs(grace))
+        .await
+    {
+        Ok((token, key)) => {
+            Json(serde_json::json!({ "key": token, "api_key": key })).into_response()
+        }
+        Err(e) => key_error(e),
+    }
+}
+
+/// Revoke a key immediately
+async fn revoke_key(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    let keys = match key_store(&state, &headers) {
+        Ok(keys) => keys,
+        Err((status, message)) => return error(status, message),
+    };
+    match keys.revoke(&id).await {
+        Ok(key) => Json(key).into_response(),
+        Err(e) => key_error(e),
+    }
+}
+
+/// Tenant resolver for tenant administration, or the error response
+///
+/// Like key management, tenant administration always needs `admin.token`.
+fn tenant_admin<'a>(
+    state: &'a AdminState,
+    headers: &HeaderMap,
+) -> Result<&'a TenantResolver, (StatusCode, &'static str)> {
+    if state.token.is_none() {
+        return Err((StatusCode::FORBIDDEN, "Set admin.token to manage tenants"));
+    }
+    if !authorized(state, headers) {
+        return Err((StatusCode::UNAUTHORIZED, "Missing or invalid admin token"));
+    }
+    state
+        .tenants
+        .as_deref()
+        .ok_or((StatusCode::NOT_FOUND, "Multi-tenant mode is not enabled"))
+}
+
+fn tenant_error(err: lunaroute_core::Error) -> Response {
+    use lunaroute_core::Error;
+    let status = match err {
+        Error::TenantNotFound(_) | Error::ConfigNotFound => StatusCode::NOT_FOUND,
+        Error::TenantExists(_) => StatusCode::CONFLICT,
+        Error::InvalidTenant(_) | Error::ConfigValidation(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    error(status, err.to_string())
+}
+
+/// Check a tenant config with the store and as a server config
+///
+/// `tenant_id` scopes `vault:` references; it is `None` before the tenant
+/// exists, so a new tenant's vault references are set with a config update.
+async fn validate_tenant_config(
+    store: &dyn ConfigStore,
+    tenant_id: Option<TenantId>,
+    config: &serde_json::Value,
+) -> lunaroute_core::Result<()> {
+    store.validate_config(config).await?;
+    let parsed: ServerConfig = serde_json::from_value(config.clone())
+        .map_err(|e| lunaroute_core::Error::ConfigValidation(e.to_string()))?;
+    parsed
+        .providers
+        .validate_extra_providers()
+        .and_then(|()| parsed.providers.validate_tenant_providers(tenant_id))
+        .map_err(lunaroute_core::Error::ConfigValidation)
+}
+
+/// Resolve the `{tenant}` path segment (a tenant ID or name)
+async fn path_tenant(
+    tenants: &TenantResolver,
+    reference: &str,
+) -> lunaroute_core::Result<TenantId> {
+    TenantId::resolve(reference, tenants.config_store().as_ref()).await
+}
+
+/// A tenant's registration, or just its ID when it only has a configuration
+async fn tenant_json(
+    store: &dyn ConfigStore,
+    tenant_id: TenantId,
+) -> lunaroute_core::Result<serde_json::Value> {
+    Ok(match store.get_tenant(tenant_id).await? {
+        Some(tenant) => serde_json::to_value(tenant)?,
+        None => serde_json::json!({ "tenant_id": tenant_id }),
+    })
+}
+
+/// List tenants, oldest first
+async fn list_tenants(State(state): State<Arc<AdminState>>, headers: HeaderMap) -> Response {
+    let tenants = match tenant_admin(&state, &headers) {
+        Ok(tenants) => tenants,
+        Err((status, message)) => return error(status, message),
+    };
+    let store = tenants.config_store().as_ref();
+    let ids = match store.list_tenants().await {
+        Ok(ids) => ids,
+        Err(e) => return tenant_error(e),
+    };
+    let mut list = Vec::with_capacity(ids.len());
+    for tenant_id in ids {
+        match tenant_json(store, tenant_id).await {
+            Ok(tenant) => list.push(tenant),
+            Err(e) => return tenant_error(e),
+        }
+    }
+    Json(list).into_response()
+}
+
+/// Register a tenant, optionally with its first configuration
+async fn create_tenant(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Json(body): Json<CreateTenantRequest>,
+) -> Response {
+    let tenants = match tenant_admin(&state, &headers) {
+        Ok(tenants) => tenants,
+        Err((status, message)) => return error(status, message),
+    };
+    let store = tenants.config_store().as_ref();
+    if let Some(config) = &body.config
+        && let Err(e) = validate_tenant_config(store, None, config).await
+    {
+        return tenant_error(e);
+    }
+    let tenant = match store.create_tenant(&body.name).await {
+        Ok(tenant) => tenant,
+        Err(e) => return tenant_error(e),
+    };
+    if let Some(config) = body.config
+        && let Err(e) = store.update_config(Some(tenant.tenant_id), config).await
+    {
+        // Don't leave a tenant behind without the config it was created with
+        if let Err(rollback) = store.delete_tenant(tenant.tenant_id).await {
+            warn!(
+                "Failed to remove tenant {} after its config was rejected: {}",
+                tenant.tenant_id, rollback
+            );
+        }
+        return tenant_error(e);
+    }
+    // Requests may have cached the name as unknown
+    tenants.forget_name(&tenant.name);
+    (StatusCode::CREATED, Json(tenant)).into_response()
+}
+
+async fn get_tenant(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(reference): Path<String>,
+) -> Response {
+    let tenants = match tenant_admin(&state, &headers) {
+        Ok(tenants) => tenants,
+        Err((status, message)) => return error(status, message),
+    };
+    let tenant_id = match path_tenant(tenants, &reference).await {
+        Ok(tenant_id) => tenant_id,
+        Err(e) => return tenant_error(e),
+    };
+    match tenants.config_store().get_tenant(tenant_id).await {
+        Ok(Some(tenant)) => Json(tenant).into_response(),
+        Ok(None) => error(
+            StatusCode::NOT_FOUND,
+            format!("Tenant not registered: {}", tenant_id),
+        ),
+        Err(e) => tenant_error(e),
+    }
+}
+
+/// Delete a tenant with its configuration and history
+///
+/// The tenant's API keys are revoked first; they stay in the key store as
+/// revoked records.
+async fn delete_tenant(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(reference): Path<String>,
+) -> Response {
+    let tenants = match tenant_admin(&state, &headers) {
+        Ok(tenants) => tenants,
+        Err((status, message)) => return error(status, message),
+    };
+    let tenant_id = match path_tenant(tenants, &reference).await {
+        Ok(tenant_id) => tenant_id,
+        Err(e) => return tenant_error(e),
+    };
+    let store = tenants.config_store();
+    let name = match store.get_tenant(tenant_id).await {
+        Ok(tenant) => tenant.map(|tenant| tenant.name),
+        Err(e) => return tenant_error(e),
+    };
+    // Revoke the tenant's keys first: keys naming it by name stop resolving
+    // once it is gone, and a failure must leave the tenant in place
+    if let Some(keys) = state.api_keys.as_deref() {
+        let owned = match owned_keys(tenants, tenant_id, keys).await {
+            Ok(owned) => owned,
+            Err(e) => return key_error(e),
+        };
+        for key in owned.iter().filter(|key| key.revoked_at.is_none()) {
+            if let Err(e) = keys.revoke(&key.id).await {
+                return key_error(e);
+            }
+        }
+    }
+    if let Err(e) = store.delete_tenant(tenant_id).await {
+        return tenant_error(e);
+    }
+    tenants.invalidate(tenant_id);
+    if let Some(name) = name {
+        tenants.forget_name(&name);
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+async fn set_tenant_status(
+    state: &AdminState,
+    headers: &HeaderMap,
+    reference: &str,
+    status: TenantStatus,
+) -> Response {
+    let tenants = match tenant_admin(state, headers) {
+        Ok(tenants) => tenants,
+        Err((status, message)) => return error(status, message),
+    };
+    let tenant_id = match path_tenant(tenants, reference).await {
+        Ok(tenant_id) => tenant_id,
+        Err(e) => return tenant_error(e),
+    };
+    let store = tenants.config_store();
+    if let Err(e) = store.set_tenant_status(tenant_id, status).await {
+        return tenant_error(e);
+    }
+    // Takes effect here now, and on other replicas within tenancy.cache_ttl_secs
+    tenants.invalidate(tenant_id);
+    match tenant_json(store.as_ref(), tenant_id).await {
+        Ok(tenant) => Json(tenant).into_response(),
+        Err(e) => tenant_error(e),
+    }
+}
+
+/// Refuse a tenant's requests, keeping its configuration and keys
+async fn suspend_tenant(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(reference): Path<String>,
+) -> Response {
+    set_tenant_status(&state, &headers, &reference, TenantStatus::Suspended).await
+}
+
+/// Serve a suspended tenant's requests again
+async fn resume_tenant(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(reference): Path<String>,
+) -> Response {
+    set_tenant_status(&state, &headers, &reference, TenantStatus::Active).await
+}
+
+async fn get_tenant_config(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(reference): Path<String>,
+) -> Response {
+    let tenants = match tenant_admin(&state, &headers) {
+        Ok(tenants) => tenants,
+        Err((status, message)) => return error(status, message),
+    };
+    let tenant_id = match path_tenant(tenants, &reference).await {
+        Ok(tenant_id) => tenant_id,
+        Err(e) => return tenant_error(e),
+    };
+    match tenants.config_store().get_config(Some(tenant_id)).await {
+        Ok(config) => Json(config).into_response(),
+        Err(e) => tenant_error(e),
+    }
+}
+
+/// Validate and store a tenant's configuration (`?dry_run=true` only validates)
+///
+/// Replicas rebuild the tenant's providers from the change notification.
+async fn put_tenant_config(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(reference): Path<String>,
+    Query(query): Query<ConfigUploadQuery>,
+    Json(config): Json<serde_json::Value>,
+) -> Response {
+    let tenants = match tenant_admin(&state, &headers) {
+        Ok(tenants) => tenants,
+        Err((status, message)) => return error(status, message),
+    };
+    let tenant_id = match path_tenant(tenants, &reference).await {
+        Ok(tenant_id) => tenant_id,
+        Err(e) => return tenant_error(e),
+    };
+    let store = tenants.config_store();
+    if let Err(e) = validate_tenant_config(store.as_ref(), Some(tenant_id), &config).await {
+        return tenant_error(e);
+    }
+    if query.dry_run {
+        return Json(serde_json::json!({ "tenant_id": tenant_id, "valid": true })).into_response();
+    }
+    if let Err(e) = store.update_config(Some(tenant_id), config).await {
+        return tenant_error(e);
+    }
+    tenants.invalidate(tenant_id);
+    let version = match store.config_history(tenant_id).await {
+        Ok(history) => history.first().map(|v| v.version),
+        Err(e) => return tenant_error(e),
+    };
+    Json(serde_json::json!({ "tenant_id": tenant_id, "version": version })).into_response()
+}
+
+/// Stored versions of a tenant's configuration, newest first
+async fn tenant_config_history(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(reference): Path<String>,
+) -> Response {
+    let tenants = match tenant_admin(&state, &headers) {
+        Ok(tenants) => tenants,
+        Err((status, message)) => return error(status, message),
+    };
+    let tenant_id = match path_tenant(tenants, &reference).await {
+        Ok(tenant_id) => tenant_id,
+        Err(e) => return tenant_error(e),
+    };
+    match tenants.config_store().config_history(tenant_id).await {
+        Ok(history) => Json(history).into_response(),
+        Err(e) => tenant_error(e),
+    }
+}
+
+/// Tenant and key store for a tenant's key requests, or the error response
+async fn tenant_keys<'a>(
+    state: &'a AdminState,
+    headers: &HeaderMap,
+    reference: &str,
+) -> Result<(&'a TenantResolver, TenantId, &'a ApiKeyStore), Response> {
+    let tenants =
+        tenant_admin(state, headers).map_err(|(status, message)| error(status, message))?;
+    let keys = key_store(state, headers).map_err(|(status, message)| error(status, message))?;
+    let tenant_id = path_tenant(tenants, reference)
+        .await
+        .map_err(tenant_error)?;
+    Ok((tenants, tenant_id, keys))
+}
+
+/// A tenant's API keys (metadata only)
+async fn list_tenant_keys(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(reference): Path<String>,
+) -> Response {
+    let (tenants, tenant_id, keys) = match tenant_keys(&state, &headers, &reference).await {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+    match owned_keys(tenants, tenant_id, keys).await {
+        Ok(owned) => Json(owned).into_response(),
+        Err(e) => key_error(e),
+    }
+}
+
+/// Keys bound to a tenant, which they name by ID or by name
+async fn owned_keys(
+    tenants: &TenantResolver,
+    tenant_id: TenantId,
+    keys: &ApiKeyStore,
+) -> Result<Vec<ApiKey>, ApiKeyError> {
+    let mut owned = Vec::new();
+    for key in keys.list().await? {
+        if let Some(tenant) = &key.tenant
+            && let Ok(Some(id)) = tenants.resolve(tenant).await
+            && id == tenant_id
+        {
+            owned.push(key);
+        }
+    }
+    Ok(owned)
+}
+
+/// Issue an API key bound to the tenant (any `tenant` in the body is replaced)
+async fn create_tenant_key(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(reference): Path<String>,
+    Json(mut body): Json<NewApiKey>,
+) -> Response {
+    let (_, tenant_id, keys) = match tenant_keys(&state, &headers, &reference).await {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+    body.tenant = Some(tenant_id.to_string());
+    match keys.create(body).await {
+        Ok((token, key)) => (
+            StatusCode::CREATED,
+            Json(serde_json::json!({ "key": token, "api_key": key })),
+        )
+            .into_response(),
+        Err(e) => key_error(e),
+    }
+}
+
+/// Pick the listener dialect for a request
+fn listener_for(
+    requested: Option<&str>,
+    dialect: ApiDialect,
+    req: &serde_json::Value,
+) -> Result<ProviderType, String> {
+    match requested.map(str::to_ascii_lowercase).as_deref() {
+        Some("anthropic") => Ok(ProviderType::Anthropic),
+        Some("openai") => Ok(ProviderType::OpenAI),
+        Some(other) => Err(format!(
+            "Unknown listener '{}' (expected 'anthropic' or 'openai')",
+            other
+        )),
+        None => Ok(match dialect {
+            ApiDialect::Anthropic => ProviderType::Anthropic,
+            ApiDialect::OpenAI => ProviderType::OpenAI,
+            ApiDialect::Both => {
+                let model = req.get("model").and_then(|m| m.as_str()).unwrap_or("");
+                if model.starts_with("claude") {
+                    ProviderType::Anthropic
+                } else {
+                    ProviderType::OpenAI
+                }
+            }
+        }),
+    }
+}
+
+/// Explain how a request would be routed
+async fn explain_route(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Json(body): Json<ExplainRequest>,
+) -> Response {
+    if !authorized(&state, &headers) {
+        return error(StatusCode::UNAUTHORIZED, "Missing or invalid admin token");
+    }
+
+    let (request, recorded_listener) = match (body.request, &body.request_id) {
+        (Some(request), _) => (request, None),
+        (None, Some(request_id)) => {
+            let Some(dir) = &state.sessions_dir else {
+                return error(
+                    StatusCode::BAD_REQUEST,
+                    "request_id lookup requires JSONL session recording",
+                );
+            };
+            match lunaroute_session::find_recorded_request(
+                dir,
+                request_id,
+                body.session_id.as_deref(),
+            )
+            .await
+            {
+                Ok(Some(recorded)) => (recorded.request_json, recorded.listener),
+                Ok(None) => {
+                    return error(
+                        StatusCode::NOT_FOUND,
+                        format!("Request '{}' not found in recorded sessions", request_id),
+                    );
+                }
+                Err(e) => {
+                    return error(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to search recorded sessions: {}", e),
+                    );
+                }
+            }
+        }
+        (None, None) => {
+            return error(
+                StatusCode::BAD_REQUEST,
+                "Provide either 'request' or 'request_id'",
+            );
+        }
+    };
+
+    let listener = match listener_for(
+        body.listener.as_deref().or(recorded_listener.as_deref()),
+        state.api_dialect,
+        &request,
+    ) {
+        Ok(listener) => listener,
+        Err(e) => return error(StatusCode::BAD_REQUEST, e),
+    };
+
+    let result = if state.passthrough {
+        let mut request_headers = HeaderMap::new();
+        for (name, value) in &body.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                request_headers.insert(name, value);
+            }
+        }
+        lunaroute_ingress::explain::explain_passthrough(
+            request,
+            listener,
+            Some(&state.provider_registry),
+            state.model_aliases.as_deref(),
+            state.experiments.as_deref(),
+            &request_headers,
+        )
+        .map_err(|e| e.to_string())
+    } else {
+        explain_routed(&state.router, request, listener, body.at, body.identity)
+    };
+
+    match result {
+        Ok(explanation) => Json(explanation).into_response(),
+        Err(e) => error(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+/// Normalize the request and run it through the router's dry run
+fn explain_routed(
+    router: &Router,
+    request: serde_json::Value,
+    listener: ProviderType,
+    at: Option<DateTime<Utc>>,
+    identity: Option<lunaroute_core::identity::ClientIdentity>,
+) -> Result<RoutingExplanation, String> {
+    let mut normalized = match listener {
+        ProviderType::Anthropic => serde_json::from_value(request)
+            .map_err(|e| format!("Invalid Anthropic request: {}", e))
+            .and_then(|req| {
+                lunaroute_ingress::anthropic::to_normalized(req).map_err(|e| e.to_string())
+            }),
+        ProviderType::OpenAI => serde_json::from_value(request)
+            .map_err(|e| format!("Invalid OpenAI request: {}", e))
+            .and_then(|req| {
+                lunaroute_ingress::openai::to_normalized(req).map_err(|e| e.to_string())
+            }),
+    }?;
+
+    // Normalized handlers route with the caller identity as their only
+    // context, so the dry run does too
+    if let Some(identity) = identity {
+        identity.attach(&mut normalized);
+    }
+    let mut context = RoutingContext::for_request(&normalized);
+    context.at = at;
+    Ok(router.explain(&normalized, &context))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lunaroute_routing::{RouteTable, RoutingRule, RuleMatcher};
+    use serde_json::json;
+
+    fn state(passthrough: bool, token: Option<&str>) -> Arc<AdminState> {
+        let rule = RoutingRule {
+            priority: 10,
+            name: Some("claude-to-anthropic".to_string()),
+            matcher: RuleMatcher::model_pattern("^claude-.*"),
+            strategy: None,
+            primary: Some("anthropic".to_string()),
+            fallbacks: vec!["openai".to_string()],
+            on_error: Default::default(),
+            schedule: None,
+        };
+        Arc::new(AdminState {
+            router: Arc::new(Router::with_defaults(
+                RouteTable::with_rules(vec![rule]),
+                HashMap::new(),
+            )),
+            passthrough,
+            api_dialect: ApiDialect::Anthropic,
+            provider_registry: Arc::new(ProviderRegistry::new()),
+            model_aliases: None,
+            experiments: None,
+            sessions_dir: None,
+            token: token.map(str::to_string),
+            api_keys: None,
+            budgets: None,
+            tenants: None,
+        })
+    }
+
+    fn sample() -> ExplainRequest {
+        ExplainRequest {
+            request: Some(json!({
+                "model": "claude-sonnet-4-5",
+                "max_tokens": 100,
+                "messages": [{"role": "user", "content": "hi"}],
+            })),
+            ..Default::default()
+        }
+    }
+
+    async fn explain(state: Arc<AdminState>, headers: HeaderMap, body: ExplainRequest) -> Response {
+        explain_route(State(state), headers, Json(body)).await
+    }
+
+    #[tokio::test]
+    async fn test_explain_routed_request() {
+        let response = explain(state(false, None), HeaderMap::new(), sample()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let explanation: RoutingExplanation = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            explanation.matched_rule.as_deref(),
+            Some("claude-to-anthropic")
+        );
+        // No providers are registered with the router, so every candidate is skipped
+        assert_eq!(explanation.candidates.len(), 2);
+        assert!(explanation.selected_provider.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_explain_passthrough_and_auth() {
+        let state = state(true, Some("secret"));
+
+        let response = explain(state.clone(), HeaderMap::new(), sample()).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        let response = explain(state.clone(), headers.clone(), sample()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let explanation: RoutingExplanation = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(explanation.selected_provider.as_deref(), Some("anthropic"));
+
+        let response = explain(state, headers, ExplainRequest::default()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_key_management() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = lunaroute_storage::FileStateStore::new(dir.path().join("state.json"))
+            .await
+            .unwrap();
+        let keys = Arc::new(ApiKeyStore::new(Arc::new(store)));
+        let with_keys = |token: Option<&str>| {
+            let base = state(false, token);
+            Arc::new(AdminState {
+                router: base.router.clone(),
+                passthrough: false,
+                api_dialect: ApiDialect::Anthropic,
+                provider_registry: base.provider_registry.clone(),
+                model_aliases: None,
+                experiments: None,
+                sessions_dir: None,
+                token: token.map(str::to_string),
+                api_keys: Some(keys.clone()),
+                budgets: None,
+                tenants: None,
+            })
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        async fn json(response: Response) -> serde_json::Value {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice(&bytes).unwrap()
+        }
+
+        // Key management is refused without an admin token configured
+        let response = list_keys(State(with_keys(None)), HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let state = with_keys(Some("secret"));
+        let response = create_key(
+            State(state.clone()),
+            headers.clone(),
+            Json(NewApiKey {
+                name: "ci".to_string(),
+                ..Default::default()
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let created = json(response).await;
+        let token = created["key"].as_str().unwrap().to_string();
+        let id = created["api_key"]["id"].as_str().unwrap().to_string();
+        assert!(created["api_key"].get("hash").is_none());
+
+        let response = rotate_key(
+            State(state.clone()),
+            headers.clone(),
+            Path(id.clone()),
+            Some(Json(RotateKeyRequest { grace_secs: 60 })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let rotated = json(response).await;
+        assert_ne!(rotated["key"].as_str().unwrap(), token);
+        assert!(keys.verify(&token).await.is_ok());
+
+        let response = revoke_key(State(state.clone()), headers.clone(), Path(id)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(keys.verify(&token).await.is_err());
+
+        let response = list_keys(State(state.clone()), headers.clone()).await;
+        assert_eq!(json(response).await.as_array().unwrap().len(), 2);
+
+        let response = revoke_key(State(state), headers, Path("missing".to_string())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    /// In-memory tenant registry and config store
+    #[derive(Default)]
+    struct MemoryTenants {
+        tenants: std::sync::Mutex<Vec<lunaroute_core::tenant::TenantInfo>>,
+        configs: std::sync::Mutex<HashMap<TenantId, Vec<serde_json::Value>>>,
+        fail_updates: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl ConfigStore for MemoryTenants {
+        async fn get_config(
+            &self,
+            tenant_id: Option<TenantId>,
+        ) -> lunaroute_core::Result<serde_json::Value> {
+            let configs = self.configs.lock().unwrap();
+            tenant_id
+                .and_then(|id| configs.get(&id)?.last().cloned())
+                .ok_or(lunaroute_core::Error::ConfigNotFound)
+        }
+
+        async fn update_config(
+            &self,
+            tenant_id: Option<TenantId>,
+            config: serde_json::Value,
+        ) -> lunaroute_core::Result<()> {
+            if self.fail_updates.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(lunaroute_core::Error::Database("unavailable".to_string()));
+            }
+            let tenant_id = tenant_id.unwrap();
+            self.configs
+                .lock()
+                .unwrap()
+                .entry(tenant_id)
+                .or_default()
+                .push(config);
+            Ok(())
+        }
+
+        async fn watch_changes(
+            &self,
+            _tenant_id: Option<TenantId>,
+        ) -> lunaroute_core::Result<lunaroute_core::config_store::ConfigChangeStream<'_>> {
+            Ok(Box::pin(futures::stream::empty()))
+        }
+
+        async fn validate_config(&self, config: &serde_json::Value) -> lunaroute_core::Result<()> {
+            if config.is_object() {
+                Ok(())
+            } else {
+                Err(lunaroute_core::Error::ConfigValidation(
+                    "Configuration must be a JSON object".to_string(),
+                ))
+            }
+        }
+
+        async fn list_tenants(&self) -> lunaroute_core::Result<Vec<TenantId>> {
+            Ok(self
+                .tenants
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|t| t.tenant_id)
+                .collect())
+        }
+
+        async fn lookup_tenant(&self, name: &str) -> lunaroute_core::Result<Option<TenantId>> {
+            Ok(self
+                .tenants
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|t| t.name == name)
+                .map(|t| t.tenant_id))
+        }
+
+        async fn create_tenant(
+            &self,
+            name: &str,
+        ) -> lunaroute_core::Result<lunaroute_core::tenant::TenantInfo> {
+            lunaroute_core::tenant::validate_tenant_name(name)?;
+            let mut tenants = self.tenants.lock().unwrap();
+            if tenants.iter().any(|t| t.name == name) {
+                return Err(lunaroute_core::Error::TenantExists(name.to_string()));
+            }
+            let tenant = lunaroute_core::tenant::TenantInfo {
+                tenant_id: TenantId::new(),
+                name: name.to_string(),
+                status: TenantStatus::Active,
+                created_at: Utc::now(),
+            };
+            tenants.push(tenant.clone());
+            Ok(tenant)
+        }
+
+        async fn get_tenant(
+            &self,
+            tenant_id: TenantId,
+        ) -> lunaroute_core::Result<Option<lunaroute_core::tenant::TenantInfo>> {
+            Ok(self
+                .tenants
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|t| t.tenant_id == tenant_id)
+                .cloned())
+        }
+
+        async fn set_tenant_status(
+            &self,
+            tenant_id: TenantId,
+            status: TenantStatus,
+        ) -> lunaroute_core::Result<()> {
+            let mut tenants = self.tenants.lock().unwrap();
+            let tenant = tenants
+                .iter_mut()
+                .find(|t| t.tenant_id == tenant_id)
+                .ok_or_else(|| lunaroute_core::Error::TenantNotFound(tenant_id.to_string()))?;
+            tenant.status = status;
+            Ok(())
+        }
+
+        async fn delete_tenant(&self, tenant_id: TenantId) -> lunaroute_core::Result<()> {
+            self.configs.lock().unwrap().remove(&tenant_id);
+            let mut tenants = self.tenants.lock().unwrap();
+            let before = tenants.len();
+            tenants.retain(|t| t.tenant_id != tenant_id);
+            if tenants.len() == before {
+                return Err(lunaroute_core::Error::TenantNotFound(tenant_id.to_string()));
+            }
+            Ok(())
+        }
+
+        async fn config_history(
+            &self,
+            tenant_id: TenantId,
+        ) -> lunaroute_core::Result<Vec<lunaroute_core::config_store::ConfigVersion>> {
+            let configs = self.configs.lock().unwrap();
+            Ok(configs
+                .get(&tenant_id)
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(i, config)| lunaroute_core::config_store::ConfigVersion {
+                    version: i as u32 + 1,
+                    config: config.clone(),
+                    changed_by: None,
+                    changed_at: Utc::now(),
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tenant_administration() {
+        async fn json(response: Response) -> serde_json::Value {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice(&bytes).unwrap()
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let state_store = lunaroute_storage::FileStateStore::new(dir.path().join("state.json"))
+            .await
+            .unwrap();
+        let keys = Arc::new(ApiKeyStore::new(Arc::new(state_store)));
+        let store = Arc::new(MemoryTenants::default());
+        let resolver =
+            Arc::new(TenantResolver::new(Default::default(), store.clone(), None).unwrap());
+        let base = state(false, Some("secret"));
+        let state = Arc::new(AdminState {
+            router: base.router.clone(),
+            passthrough: false,
+            api_dialect: ApiDialect::Anthropic,
+            provider_registry: base.provider_registry.clone(),
+            model_aliases: None,
+            experiments: None,
+            sessions_dir: None,
+            token: Some("secret".to_string()),
+            api_keys: Some(keys.clone()),
+            budgets: None,
+            tenants: Some(resolver.clone()),
+        });
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        let config =
+            json!({ "providers": { "openai": { "enabled": true, "api_key": "sk-test" } } });
+
+        let response = list_tenants(State(state.clone()), HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Configs are checked before the tenant is created
+        let response = create_tenant(
+            State(state.clone()),
+            headers.clone(),
+            Json(CreateTenantRequest {
+                name: "acme".to_string(),
+                config: Some(json!({ "providers": "none" })),
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // A config the store fails to save takes the new tenant with it
+        store
+            .fail_updates
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        let response = create_tenant(
+            State(state.clone()),
+            headers.clone(),
+            Json(CreateTenantRequest {
+                name: "acme".to_string(),
+                config: Some(config.clone()),
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(store.tenants.lock().unwrap().is_empty());
+        store
+            .fail_updates
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+
+        let response = create_tenant(
+            State(state.clone()),
+            headers.clone(),
+            Json(CreateTenantRequest {
+                name: "acme".to_string(),
+                config: Some(config.clone()),
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let tenant_id =
+            TenantId::from_string(json(response).await["tenant_id"].as_str().unwrap()).unwrap();
+        let response = create_tenant(
+            State(state.clone()),
+            headers.clone(),
+            Json(CreateTenantRequest {
+                name: "acme".to_string(),
+                config: None,
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let response = list_tenants(State(state.clone()), headers.clone()).await;
+        let list = json(response).await;
+        assert_eq!(list[0]["name"], "acme");
+        assert_eq!(list[0]["status"], "active");
+
+        // Dry runs validate without storing a version
+        let response = put_tenant_config(
+            State(state.clone()),
+            headers.clone(),
+            Path("acme".to_string()),
+            Query(ConfigUploadQuery { dry_run: true }),
+            Json(json!({ "api_dialect": "openai" })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Tenants may not point the server at its own commands or files
+        let response = put_tenant_config(
+            State(state.clone()),
+            headers.clone(),
+            Path("acme".to_string()),
+            Query(ConfigUploadQuery::default()),
+            Json(json!({
+                "providers": { "openai": { "enabled": true, "api_key_command": "cat /etc/passwd" } }
+            })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = put_tenant_config(
+            State(state.clone()),
+            headers.clone(),
+            Path(tenant_id.to_string()),
+            Query(ConfigUploadQuery::default()),
+            Json(json!({ "api_dialect": "openai" })),
+        )
+        .await;
+        assert_eq!(json(response).await["version"], 2);
+        let response =
+            tenant_config_history(State(state.clone()), headers.clone(), Path("acme".into())).await;
+        let history = json(response).await;
+        assert_eq!(history.as_array().unwrap().len(), 2);
+        assert_eq!(history[0]["config"]["api_dialect"], "openai");
+
+        // Suspended tenants no longer resolve for requests
+        assert!(resolver.context(tenant_id).await.is_ok());
+        let response =
+            suspend_tenant(State(state.clone()), headers.clone(), Path("acme".into())).await;
+        assert_eq!(json(response).await["status"], "suspended");
+        assert!(matches!(
+            resolver.context(tenant_id).await,
+            Err(lunaroute_core::Error::TenantSuspended(_))
+        ));
+        resume_tenant(State(state.clone()), headers.clone(), Path("acme".into())).await;
+        assert!(resolver.context(tenant_id).await.is_ok());
+
+        // Keys issued for the tenant are bound to it
+        let response = create_tenant_key(
+            State(state.clone()),
+            headers.clone(),
+            Path("acme".into()),
+            Json(NewApiKey {
+                name: "ci".to_string(),
+                tenant: Some("globex".to_string()),
+                ..Default::default()
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let token = json(response).await["key"].as_str().unwrap().to_string();
+        let key = keys.verify(&token).await.unwrap();
+        assert_eq!(key.tenant, Some(tenant_id.to_string()));
+        keys.create(NewApiKey {
+            name: "other".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let response =
+            list_tenant_keys(State(state.clone()), headers.clone(), Path("acme".into())).await;
+        assert_eq!(json(response).await.as_array().unwrap().len(), 1);
+
+        // Deleting the tenant revokes its keys, and only its keys
+        let response =
+            delete_tenant(State(state.clone()), headers.clone(), Path("acme".into())).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(matches!(
+            keys.verify(&token).await,
+            Err(ApiKeyError::Revoked)
+        ));
+        let active = keys.list().await.unwrap();
+        assert_eq!(
+            active
+                .iter()
+                .filter(|key| key.revoked_at.is_none())
+                .map(|key| key.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["other"]
+        );
+        let response = get_tenant(State(state.clone()), headers.clone(), Path("acme".into())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = get_tenant_config(State(state), headers, Path(tenant_id.to_string())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_budget_status() {
+        let response = list_budgets(State(state(false, None)), HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = lunaroute_storage::FileStateStore::new(dir.path().join("state.json"))
+            .await
+            .unwrap();
+        let config: lunaroute_ingress::budget::BudgetConfig = serde_yaml::from_str(
+            r#"
+enabled: true
+budgets:
+  - { name: acme, subject: tenant, ids: [acme], metric: requests, window: daily, limit: 10 }
+"#,
+        )
+        .unwrap();
+        let base = state(false, Some("secret"));
+        let state = Arc::new(AdminState {
+            router: base.router.clone(),
+            passthrough: false,
+            api_dialect: ApiDialect::Anthropic,
+            provider_registry: base.provider_registry.clone(),
+            model_aliases: None,
+            experiments: None,
+            sessions_dir: None,
+            token: Some("secret".to_string()),
+            api_keys: None,
+            budgets: Some(Arc::new(BudgetTracker::new(config, Arc::new(store), 1024))),
+            tenants: None,
+        });
+
+        let response = list_budgets(State(state.clone()), HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        let response = list_budgets(State(state), headers).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let status: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(status[0]["subject_id"], "acme");
+        assert_eq!(status[0]["used"], 0.0);
+        assert_eq!(status[0]["state"], "ok");
+    }
+}
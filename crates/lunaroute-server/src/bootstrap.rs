@@ -46,7 +46,8 @@ pub struct BootstrapConfig {
     #[serde(default)]
     pub source: ConfigSource,
 
-    /// Path to configuration file (for file-based mode)
+    /// Path to configuration file (for file-based mode, and for the
+    /// server-wide settings of multi-tenant database mode)
     /// Default: ~/.lunaroute/config.yaml
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file_path: Option<PathBuf>,
@@ -57,8 +58,8 @@ pub struct BootstrapConfig {
     pub database_url: Option<String>,
 
     /// Tenant ID (for database-backed mode)
-    /// If None in multi-tenant database mode, the server will use tenant extraction
-    /// middleware to determine the tenant from the request.
+    /// If None in multi-tenant database mode, the server resolves the tenant of
+    /// each request (see the `tenancy` config section).
     /// If Some, the server runs in single-tenant mode with database-backed config.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tenant_id: Option<uuid::Uuid>,
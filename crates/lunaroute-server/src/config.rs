@@ -53,6 +53,34 @@ pub struct ServerConfig {
     /// UI/Dashboard server configuration
     #[serde(default)]
     pub ui: lunaroute_ui::UiConfig,
+
+    /// Admin API configuration
+    #[serde(default)]
+    pub admin: AdminConfig,
+
+    /// Routing state persistence (circuit breakers, health, rate limits)
+    #[serde(default)]
+    pub state: StateConfig,
+
+    /// Active provider health probes feeding the router and /readyz
+    #[serde(default)]
+    pub health_probes: HealthProbesConfig,
+
+    /// Ingress authentication (LunaRoute-issued API keys)
+    #[serde(default)]
+    pub auth: AuthConfig,
+
+    /// Ingress rate limits (global, per API key, per client IP)
+    #[serde(default)]
+    pub rate_limits: lunaroute_ingress::rate_limit::RateLimitConfig,
+
+    /// Token, request and dollar budgets per key, user, tenant or project
+    #[serde(default)]
+    pub budgets: lunaroute_ingress::budget::BudgetConfig,
+
+    /// How requests are mapped to tenants (multi-tenant database mode only)
+    #[serde(default)]
+    pub tenancy: lunaroute_ingress::tenant::TenancyConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +100,9 @@ impl Default for ProvidersConfig {
             // No api_key = passthrough (will use env vars or client headers)
             openai: Some(ProviderSettings {
                 api_key: None,
+                api_keys: Vec::new(),
+                key_selection: Default::default(),
+                key_source: Default::default(),
                 base_url: None,
                 enabled: true,
                 http_client: None,
@@ -79,11 +110,15 @@ impl Default for ProvidersConfig {
                 request_body: None,
                 response_body: None,
                 codex_auth: None,
+                claude_oauth: None,
                 provider_type: None,
                 model: None,
             }),
             anthropic: Some(ProviderSettings {
                 api_key: None,
+                api_keys: Vec::new(),
+                key_selection: Default::default(),
+                key_source: Default::default(),
                 base_url: None,
                 enabled: true,
                 http_client: None,
@@ -91,6 +126,7 @@ impl Default for ProvidersConfig {
                 request_body: None,
                 response_body: None,
                 codex_auth: None,
+                claude_oauth: None,
                 provider_type: None,
                 model: None,
             }),
@@ -101,6 +137,13 @@ impl Default for ProvidersConfig {
 
 impl ProvidersConfig {
     pub fn validate_extra_providers(&self) -> Result<(), String> {
+        if self
+            .openai
+            .as_ref()
+            .is_some_and(|o| o.claude_oauth.is_some())
+        {
+            return Err("claude_oauth is only supported on Anthropic providers".to_string());
+        }
         for (name, settings) in &self.extra {
             if name == "openai" || name == "anthropic" {
                 return Err(format!(
@@ -115,6 +158,12 @@ impl ProvidersConfig {
                 ));
             }
             match settings.provider_type.as_deref() {
+                Some("openai") if settings.claude_oauth.is_some() => {
+                    return Err(format!(
+                        "Extra provider '{}' sets claude_oauth, which requires provider_type \"anthropic\"",
+                        name
+                    ));
+                }
                 Some("openai") | Some("anthropic") => {}
                 Some(other) => {
                     return Err(format!(
@@ -127,13 +176,79 @@ impl ProvidersConfig {
         }
         Ok(())
     }
+
+    /// Reject settings that run commands or read files on the server
+    ///
+    /// Tenant configs are written through the admin API, so they may only
+    /// carry inline keys: `api_key_command`, `api_key_file`, `codex_auth`
+    /// and `claude_oauth` would expose the server's own shell and files.
+    /// The vault is shared, so a tenant may only reference entries named
+    /// `vault:<tenant_id>/<name>`; without a tenant ID no reference is
+    /// allowed.
+    pub fn validate_tenant_providers(
+        &self,
+        tenant_id: Option<lunaroute_core::tenant::TenantId>,
+    ) -> Result<(), String> {
+        let providers = [("openai", &self.openai), ("anthropic", &self.anthropic)]
+            .into_iter()
+            .filter_map(|(name, settings)| settings.as_ref().map(|s| (name, s)))
+            .chain(self.extra.iter().map(|(name, s)| (name.as_str(), s)));
+        for (name, settings) in providers {
+            let field = if settings.key_source.api_key_command.is_some() {
+                "api_key_command"
+            } else if settings.key_source.api_key_file.is_some() {
+                "api_key_file"
+            } else if settings.codex_auth.is_some() {
+                "codex_auth"
+            } else if settings.claude_oauth.is_some() {
+                "claude_oauth"
+            } else {
+                let namespace = tenant_id.map(|id| format!("{}/", id));
+                let foreign = settings
+                    .api_key
+                    .iter()
+                    .chain(&settings.api_keys)
+                    .find_map(|k| {
+                        lunaroute_storage::vault::vault_ref(k).filter(|entry| {
+                            !namespace
+                                .as_ref()
+                                .is_some_and(|ns| entry.starts_with(ns.as_str()))
+                        })
+                    });
+                if let Some(entry) = foreign {
+                    return Err(format!(
+                        "Provider '{}' references vault entry '{}' outside the tenant's namespace",
+                        name, entry
+                    ));
+                }
+                continue;
+            };
+            return Err(format!(
+                "Provider '{}' sets {}, which is not allowed in tenant configs",
+                name, field
+            ));
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ProviderSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
 
+    /// Additional upstream keys pooled with `api_key` (e.g. one per org account)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+
+    /// How requests are spread across the key pool
+    #[serde(default)]
+    pub key_selection: lunaroute_egress::KeySelection,
+
+    /// `api_key_command` / `api_key_file` credential source
+    #[serde(default, flatten)]
+    pub key_source: ApiKeySourceSettings,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_url: Option<String>,
 
@@ -160,6 +275,10 @@ pub struct ProviderSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub codex_auth: Option<CodexAuthConfig>,
 
+    /// Claude subscription accounts from Claude Code credentials files (Anthropic only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claude_oauth: Option<ClaudeOAuthConfig>,
+
     /// Provider dialect type (e.g., "openai" or "anthropic").
     /// Required for extra providers. Inferred for built-in "openai" and "anthropic" keys.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -167,10 +286,159 @@ pub struct ProviderSettings {
 
     /// Model ID override. When targeted via LUNAROUTE marker,
     /// the request body's model field is rewritten to this value.
+    /// For virtual names shared across providers, use `routing.model_aliases`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
 }
 
+// Hand-written so API keys never reach logs through `{:?}`
+impl std::fmt::Debug for ProviderSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProviderSettings")
+            .field("api_key", &self.api_key.as_ref().map(|_| "[REDACTED]"))
+            .field(
+                "api_keys",
+                &format_args!("[{} redacted]", self.api_keys.len()),
+            )
+            .field("key_selection", &self.key_selection)
+            .field("key_source", &self.key_source)
+            .field("base_url", &self.base_url)
+            .field("enabled", &self.enabled)
+            .field("http_client", &self.http_client)
+            .field("request_headers", &self.request_headers)
+            .field("request_body", &self.request_body)
+            .field("response_body", &self.response_body)
+            .field("codex_auth", &self.codex_auth)
+            .field("claude_oauth", &self.claude_oauth)
+            .field("provider_type", &self.provider_type)
+            .field("model", &self.model)
+            .finish()
+    }
+}
+
+impl ProviderSettings {
+    /// Whether `api_key` or any pooled key is a `vault:` reference
+    fn has_vault_refs(&self) -> bool {
+        self.api_key
+            .iter()
+            .chain(&self.api_keys)
+            .any(|k| lunaroute_storage::vault::vault_ref(k).is_some())
+    }
+
+    /// Replace `vault:<name>` keys with the secrets stored in `vault`
+    fn resolve_vault_refs(
+        &mut self,
+        provider: &str,
+        vault: &lunaroute_storage::CredentialVault,
+    ) -> Result<(), String> {
+        let resolve = |key: &mut String| -> Result<(), String> {
+            if let Some(name) = lunaroute_storage::vault::vault_ref(key) {
+                *key = vault.get(name).map(str::to_string).ok_or_else(|| {
+                    format!(
+                        "Provider '{}' references missing vault entry '{}'",
+                        provider, name
+                    )
+                })?;
+            }
+            Ok(())
+        };
+        if let Some(key) = self.api_key.as_mut() {
+            resolve(key)?;
+        }
+        self.api_keys.iter_mut().try_for_each(resolve)
+    }
+
+    /// Upstream key pool when several keys or a command/file source are
+    /// configured (the source first, then `api_key`)
+    pub fn key_pool(&self, provider: &str) -> Option<lunaroute_egress::KeyPool> {
+        let source = self.key_source.secret_source();
+        let accounts = self
+            .claude_oauth
+            .as_ref()
+            .filter(|c| c.enabled)
+            .map(ClaudeOAuthConfig::accounts)
+            .unwrap_or_default();
+        if self.api_keys.is_empty() && source.is_none() && accounts.is_empty() {
+            return None;
+        }
+        let keys = self.api_key.iter().chain(&self.api_keys).cloned().collect();
+        let mut pool = lunaroute_egress::KeyPool::new(provider, keys, self.key_selection);
+        if let Some(source) = source {
+            pool = pool.with_source(std::sync::Arc::new(source));
+        }
+        for account in accounts {
+            pool = pool.with_oauth_account(std::sync::Arc::new(account));
+        }
+        Some(pool)
+    }
+}
+
+/// Obtain a provider key by running a command or reading a file
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ApiKeySourceSettings {
+    /// Shell command printing the key (e.g. `op read op://prod/anthropic/key`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_command: Option<String>,
+
+    /// File containing the key (used when no command is set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_file: Option<PathBuf>,
+
+    /// Seconds a fetched key is reused before the source is read again (default: 300)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_ttl_secs: Option<u64>,
+
+    /// Seconds `api_key_command` may run before it is killed (default: 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_command_timeout_secs: Option<u64>,
+}
+
+// Command arguments may carry tokens, so only the program name is shown
+impl std::fmt::Debug for ApiKeySourceSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKeySourceSettings")
+            .field(
+                "api_key_command",
+                &self
+                    .api_key_command
+                    .as_deref()
+                    .map(|c| c.split_whitespace().next().unwrap_or("")),
+            )
+            .field("api_key_file", &self.api_key_file)
+            .field("api_key_ttl_secs", &self.api_key_ttl_secs)
+            .field(
+                "api_key_command_timeout_secs",
+                &self.api_key_command_timeout_secs,
+            )
+            .finish()
+    }
+}
+
+impl ApiKeySourceSettings {
+    /// Whether a command or file source is configured
+    pub fn is_set(&self) -> bool {
+        self.api_key_command.is_some() || self.api_key_file.is_some()
+    }
+
+    /// Build the secret source, if one is configured
+    pub fn secret_source(&self) -> Option<lunaroute_core::secret_source::SecretSource> {
+        use lunaroute_core::secret_source::SecretSource;
+        let source = match (&self.api_key_command, &self.api_key_file) {
+            (Some(command), _) => SecretSource::command(command.clone()),
+            (None, Some(path)) => SecretSource::file(path.clone()),
+            (None, None) => return None,
+        };
+        let source = match self.api_key_ttl_secs {
+            Some(secs) => source.with_ttl(std::time::Duration::from_secs(secs)),
+            None => source,
+        };
+        Some(match self.api_key_command_timeout_secs {
+            Some(secs) => source.with_timeout(std::time::Duration::from_secs(secs)),
+            None => source,
+        })
+    }
+}
+
 /// HTTP client configuration settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpClientSettings {
@@ -287,6 +555,11 @@ pub struct HttpServerSettings {
     /// prompts, small enough to stop OOM attacks).
     #[serde(default = "default_max_request_body_bytes")]
     pub max_request_body_bytes: usize,
+
+    /// Terminate TLS on the proxy listener (PEM cert/key, optional client
+    /// CA for mTLS). Plain HTTP when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<lunaroute_core::tls::TlsSettings>,
 }
 
 impl Default for HttpServerSettings {
@@ -299,6 +572,7 @@ impl Default for HttpServerSettings {
             send_buffer_size: None,
             recv_buffer_size: None,
             max_request_body_bytes: default_max_request_body_bytes(),
+            tls: None,
         }
     }
 }
@@ -374,6 +648,77 @@ pub struct CodexAuthConfig {
     /// If not set, will try to read from auth.json or pass through client header
     #[serde(skip_serializing_if = "Option::is_none")]
     pub account_id: Option<String>,
+
+    /// Refresh the access token before it expires (or after a 401) and write
+    /// it back to auth_file (default: true)
+    #[serde(default = "default_true")]
+    pub auto_refresh: bool,
+
+    /// OAuth token endpoint (default: https://auth.openai.com/oauth/token)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_endpoint: Option<String>,
+
+    /// OAuth client ID used for refresh (default: Codex CLI's)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    /// Seconds before expiry to refresh the token (default: 300)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_before_expiry_secs: Option<u64>,
+}
+
+/// Claude subscription OAuth accounts (Anthropic only)
+///
+/// Each credentials file is one account; accounts are pooled with any API
+/// keys, and an account that hits its usage window is backed off until the
+/// window resets while the others take the load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeOAuthConfig {
+    /// Enable Claude subscription authentication
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Claude Code credentials files, one per account
+    /// (default: ~/.claude/.credentials.json)
+    #[serde(default = "default_claude_credentials_files")]
+    pub credentials_files: Vec<PathBuf>,
+
+    /// OAuth token endpoint (default: https://console.anthropic.com/v1/oauth/token)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_endpoint: Option<String>,
+
+    /// OAuth client ID used for refresh (default: Claude Code's)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    /// Seconds before expiry to refresh the token (default: 300)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_before_expiry_secs: Option<u64>,
+}
+
+impl ClaudeOAuthConfig {
+    /// One account per credentials file, sharing an HTTP client for refreshes
+    pub fn accounts(&self) -> Vec<lunaroute_egress::claude_oauth::ClaudeOAuthAccount> {
+        let client = reqwest::Client::new();
+        self.credentials_files
+            .iter()
+            .map(|file| {
+                let mut account =
+                    lunaroute_egress::claude_oauth::ClaudeOAuthAccount::new(file, client.clone());
+                if let Some(endpoint) = &self.token_endpoint {
+                    account = account.with_token_endpoint(endpoint);
+                }
+                if let Some(client_id) = &self.client_id {
+                    account = account.with_client_id(client_id);
+                }
+                if let Some(secs) = self.refresh_before_expiry_secs {
+                    account =
+                        account.with_refresh_before_expiry(std::time::Duration::from_secs(secs));
+                }
+                account
+            })
+            .collect()
+    }
 }
 
 // SessionRecordingConfig is now imported from lunaroute_session crate
@@ -418,6 +763,172 @@ fn default_bypass_enabled() -> bool {
     true
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminConfig {
+    /// Serve admin endpoints under /admin on the proxy port (default: true)
+    #[serde(default = "default_admin_enabled")]
+    pub enabled: bool,
+
+    /// Bearer token required for admin requests (default: None = no auth)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_admin_enabled(),
+            token: None,
+        }
+    }
+}
+
+fn default_admin_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// Require a LunaRoute API key on every proxy route, intercepted and
+    /// bypassed (default: false). Keys are stored in the `state` backend and
+    /// managed through the admin API.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Accept a TLS client certificate verified against
+    /// `http_server.tls.client_ca_path` in place of an API key (default:
+    /// false). The certificate subject becomes the caller's identity.
+    #[serde(default)]
+    pub client_certs: bool,
+
+    /// Accept bearer JWTs validated against an identity provider's JWKS in
+    /// place of an API key. Claims map to the caller's identity (subject,
+    /// email, groups, tenant).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt: Option<lunaroute_ingress::jwt::JwtConfig>,
+}
+
+/// Where routing state is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StateBackend {
+    /// Local JSON file (single instance)
+    #[default]
+    File,
+    /// PostgreSQL table shared by every instance
+    Postgres,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateConfig {
+    /// Persist routing state across restarts (default: true)
+    #[serde(default = "default_state_enabled")]
+    pub enabled: bool,
+
+    /// Storage backend (default: file)
+    #[serde(default)]
+    pub backend: StateBackend,
+
+    /// State file path for the file backend (default: ~/.lunaroute/state.json)
+    #[serde(default = "default_state_path")]
+    pub path: PathBuf,
+
+    /// PostgreSQL connection string for the postgres backend
+    #[serde(default)]
+    pub database_url: Option<String>,
+
+    /// How often state is synced, in seconds (default: 30). Each sync saves
+    /// health windows and, with a shared backend, adopts breaker and
+    /// rate-limit state from other instances. Breaker transitions and
+    /// rate-limit backoffs are saved immediately.
+    #[serde(default = "default_state_sync_interval")]
+    pub sync_interval_secs: u64,
+}
+
+impl Default for StateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_state_enabled(),
+            backend: StateBackend::default(),
+            path: default_state_path(),
+            database_url: None,
+            sync_interval_secs: default_state_sync_interval(),
+        }
+    }
+}
+
+fn default_state_enabled() -> bool {
+    true
+}
+
+fn default_state_path() -> PathBuf {
+    PathBuf::from("~/.lunaroute/state.json")
+}
+
+fn default_state_sync_interval() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthProbesConfig {
+    /// Probe providers in the background (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Seconds between probes of one provider (default: 30)
+    #[serde(default = "default_probe_interval")]
+    pub interval_secs: u64,
+
+    /// Random extra delay of up to this many seconds per probe (default: 5)
+    #[serde(default = "default_probe_jitter")]
+    pub jitter_secs: u64,
+
+    /// Per-probe timeout in seconds (default: 10)
+    #[serde(default = "default_probe_timeout")]
+    pub timeout_secs: u64,
+
+    /// Per-provider probe settings; providers not listed use the model
+    /// listing check
+    #[serde(default)]
+    pub providers: HashMap<String, ProviderProbeConfig>,
+}
+
+impl Default for HealthProbesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_probe_interval(),
+            jitter_secs: default_probe_jitter(),
+            timeout_secs: default_probe_timeout(),
+            providers: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderProbeConfig {
+    /// `models` (cheap model listing, default) or `completion` (one token)
+    #[serde(default)]
+    pub method: lunaroute_routing::ProbeMethod,
+
+    /// Model for completion probes, and the fallback when a provider has no
+    /// model listing check
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+fn default_probe_interval() -> u64 {
+    30
+}
+
+fn default_probe_jitter() -> u64 {
+    5
+}
+
+fn default_probe_timeout() -> u64 {
+    10
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RoutingConfig {
     #[serde(default)]
@@ -426,6 +937,123 @@ pub struct RoutingConfig {
     /// Provider switch notification configuration
     #[serde(default)]
     pub provider_switch_notification: Option<lunaroute_routing::ProviderSwitchNotificationConfig>,
+
+    /// Virtual model aliases (e.g. "fast", "team-default") resolving to ordered
+    /// provider/model targets. Aliases can also be selected with `[LUNAROUTE:alias]`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_aliases: HashMap<String, lunaroute_routing::ModelAlias>,
+
+    /// Retired model IDs rewritten to their successors (may point at an alias)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_rewrites: HashMap<String, String>,
+
+    /// A/B experiments splitting traffic across arms (evaluated in order)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub experiments: Vec<lunaroute_routing::Experiment>,
+
+    /// Park requests when every provider of a limits-alternative rule is rate-limited
+    #[serde(default)]
+    pub rate_limit_queue: RateLimitQueueSettings,
+
+    /// `[LUNAROUTE:...]` marker behaviour
+    #[serde(default)]
+    pub markers: MarkersConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkersConfig {
+    /// Remember a marker's selection per session until `[LUNAROUTE:clear]`
+    /// (default: false, a marker applies to its own turn only)
+    #[serde(default)]
+    pub sticky: bool,
+
+    /// Forget a sticky selection after this many seconds without use (default: 86400)
+    #[serde(default = "default_sticky_ttl")]
+    pub sticky_ttl_secs: u64,
+}
+
+impl Default for MarkersConfig {
+    fn default() -> Self {
+        Self {
+            sticky: false,
+            sticky_ttl_secs: default_sticky_ttl(),
+        }
+    }
+}
+
+fn default_sticky_ttl() -> u64 {
+    86400
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitQueueSettings {
+    /// Wait for the earliest rate-limit reset instead of failing (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Longest a request may be parked in total, in seconds (default: 30)
+    #[serde(default = "default_queue_max_wait")]
+    pub max_wait_secs: u64,
+
+    /// Most requests parked at once (default: 100)
+    #[serde(default = "default_queue_max_queued")]
+    pub max_queued: usize,
+}
+
+impl Default for RateLimitQueueSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_wait_secs: default_queue_max_wait(),
+            max_queued: default_queue_max_queued(),
+        }
+    }
+}
+
+impl RateLimitQueueSettings {
+    /// Router queue limits (None if the queue is disabled)
+    pub fn queue_config(&self) -> Option<lunaroute_routing::RateLimitQueueConfig> {
+        self.enabled
+            .then(|| lunaroute_routing::RateLimitQueueConfig {
+                max_wait: std::time::Duration::from_secs(self.max_wait_secs),
+                max_queued: self.max_queued,
+            })
+    }
+}
+
+fn default_queue_max_wait() -> u64 {
+    30
+}
+
+fn default_queue_max_queued() -> usize {
+    100
+}
+
+impl RoutingConfig {
+    /// Build the validated model alias table (None if no aliases or rewrites are configured)
+    pub fn model_alias_table(
+        &self,
+    ) -> Result<Option<lunaroute_routing::ModelAliasTable>, lunaroute_routing::ModelAliasError>
+    {
+        if self.model_aliases.is_empty() && self.model_rewrites.is_empty() {
+            return Ok(None);
+        }
+        lunaroute_routing::ModelAliasTable::new(
+            self.model_aliases.clone(),
+            self.model_rewrites.clone(),
+        )
+        .map(Some)
+    }
+
+    /// Build the validated experiment set (None if no experiments are configured)
+    pub fn experiment_set(
+        &self,
+    ) -> Result<Option<lunaroute_routing::ExperimentSet>, lunaroute_routing::ExperimentError> {
+        if self.experiments.is_empty() {
+            return Ok(None);
+        }
+        lunaroute_routing::ExperimentSet::new(self.experiments.clone()).map(Some)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -453,6 +1081,13 @@ impl Default for ServerConfig {
             bypass: BypassConfig::default(),
             session_stats_max_sessions: Some(100),
             ui: lunaroute_ui::UiConfig::default(),
+            admin: AdminConfig::default(),
+            state: StateConfig::default(),
+            health_probes: HealthProbesConfig::default(),
+            auth: AuthConfig::default(),
+            rate_limits: lunaroute_ingress::rate_limit::RateLimitConfig::default(),
+            budgets: lunaroute_ingress::budget::BudgetConfig::default(),
+            tenancy: lunaroute_ingress::tenant::TenancyConfig::default(),
         }
     }
 }
@@ -558,6 +1193,55 @@ impl ServerConfig {
         Ok(config)
     }
 
+    /// Resolve `api_key: "vault:<name>"` references against the credential vault.
+    ///
+    /// The server runs this on its own config at startup and on each tenant's
+    /// config whenever that tenant's providers are (re)built; the vault file
+    /// is re-read each time so rotated secrets are picked up. The vault is
+    /// only opened when a reference exists.
+    pub fn resolve_vault_refs(&mut self) -> Result<(), String> {
+        if !self.providers_mut().any(|(_, p)| p.has_vault_refs()) {
+            return Ok(());
+        }
+        let passphrase = std::env::var(lunaroute_storage::vault::VAULT_PASSPHRASE_ENV)
+            .ok()
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| {
+                format!(
+                    "Provider keys reference the vault but {} is not set",
+                    lunaroute_storage::vault::VAULT_PASSPHRASE_ENV
+                )
+            })?;
+        let path = lunaroute_storage::vault::default_vault_path();
+        let vault = lunaroute_storage::CredentialVault::open(&path, &passphrase)
+            .map_err(|e| format!("Failed to open vault {}: {}", path.display(), e))?;
+        self.resolve_vault_refs_from(&vault)
+    }
+
+    fn resolve_vault_refs_from(
+        &mut self,
+        vault: &lunaroute_storage::CredentialVault,
+    ) -> Result<(), String> {
+        self.providers_mut()
+            .try_for_each(|(name, p)| p.resolve_vault_refs(name, vault))
+    }
+
+    fn providers_mut(&mut self) -> impl Iterator<Item = (&str, &mut ProviderSettings)> {
+        let providers = &mut self.providers;
+        providers
+            .openai
+            .as_mut()
+            .map(|p| ("openai", p))
+            .into_iter()
+            .chain(providers.anthropic.as_mut().map(|p| ("anthropic", p)))
+            .chain(
+                providers
+                    .extra
+                    .iter_mut()
+                    .map(|(name, p)| (name.as_str(), p)),
+            )
+    }
+
     /// Merge environment variables into config (env vars take precedence)
     pub fn merge_env(&mut self) {
         // API dialect
@@ -577,6 +1261,9 @@ impl ServerConfig {
         if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
             let provider = self.providers.openai.get_or_insert(ProviderSettings {
                 api_key: None,
+                api_keys: Vec::new(),
+                key_selection: Default::default(),
+                key_source: Default::default(),
                 base_url: None,
                 enabled: true,
                 http_client: None,
@@ -584,6 +1271,7 @@ impl ServerConfig {
                 request_body: None,
                 response_body: None,
                 codex_auth: None,
+                claude_oauth: None,
                 provider_type: None,
                 model: None,
             });
@@ -593,6 +1281,9 @@ impl ServerConfig {
         if let Ok(api_key) = std::env::var("ANTHROPIC_API_KEY") {
             let provider = self.providers.anthropic.get_or_insert(ProviderSettings {
                 api_key: None,
+                api_keys: Vec::new(),
+                key_selection: Default::default(),
+                key_source: Default::default(),
                 base_url: None,
                 enabled: true,
                 http_client: None,
@@ -600,6 +1291,7 @@ impl ServerConfig {
                 request_body: None,
                 response_body: None,
                 codex_auth: None,
+                claude_oauth: None,
                 provider_type: None,
                 model: None,
             });
@@ -766,6 +1458,12 @@ fn default_codex_auth_file() -> PathBuf {
     PathBuf::from("~/.codex/auth.json")
 }
 
+fn default_claude_credentials_files() -> Vec<PathBuf> {
+    vec![PathBuf::from(
+        lunaroute_egress::claude_oauth::DEFAULT_CREDENTIALS_FILE,
+    )]
+}
+
 fn default_codex_token_field() -> String {
     "tokens.access_token".to_string()
 }
@@ -843,6 +1541,9 @@ mod tests {
     fn test_to_http_client_config_clamps_max_retries_to_10() {
         let provider = ProviderSettings {
             api_key: Some("test-key".to_string()),
+            api_keys: Vec::new(),
+            key_selection: Default::default(),
+            key_source: Default::default(),
             base_url: None,
             enabled: true,
             http_client: Some(HttpClientSettings {
@@ -858,6 +1559,7 @@ mod tests {
             request_body: None,
             response_body: None,
             codex_auth: None,
+            claude_oauth: None,
             provider_type: None,
             model: None,
         };
@@ -873,6 +1575,9 @@ mod tests {
     fn test_to_http_client_config_preserves_max_retries_under_10() {
         let provider = ProviderSettings {
             api_key: Some("test-key".to_string()),
+            api_keys: Vec::new(),
+            key_selection: Default::default(),
+            key_source: Default::default(),
             base_url: None,
             enabled: true,
             http_client: Some(HttpClientSettings {
@@ -888,6 +1593,7 @@ mod tests {
             request_body: None,
             response_body: None,
             codex_auth: None,
+            claude_oauth: None,
             provider_type: None,
             model: None,
         };
@@ -942,6 +1648,29 @@ max_request_body_bytes: 5242880
         assert_eq!(settings.max_request_body_bytes, 5 * 1024 * 1024);
     }
 
+    #[test]
+    fn test_http_server_settings_yaml_tls() {
+        let yaml = r#"
+tls:
+  cert_path: /etc/lunaroute/server.pem
+  key_path: /etc/lunaroute/server.key
+  client_ca_path: /etc/lunaroute/team-ca.pem
+  client_cert_mode: optional
+"#;
+        let settings: HttpServerSettings = serde_yaml::from_str(yaml).unwrap();
+        let tls = settings.tls.unwrap();
+        assert_eq!(
+            tls.key_path,
+            std::path::PathBuf::from("/etc/lunaroute/server.key")
+        );
+        assert_eq!(
+            tls.client_cert_mode,
+            lunaroute_core::tls::ClientCertMode::Optional
+        );
+        assert!(tls.reload);
+        assert!(HttpServerSettings::default().tls.is_none());
+    }
+
     #[test]
     #[serial]
     fn test_merge_http_client_env_all_variables() {
@@ -958,6 +1687,9 @@ max_request_body_bytes: 5242880
 
         let mut provider = ProviderSettings {
             api_key: Some("test-key".to_string()),
+            api_keys: Vec::new(),
+            key_selection: Default::default(),
+            key_source: Default::default(),
             base_url: None,
             enabled: true,
             http_client: None,
@@ -965,6 +1697,7 @@ max_request_body_bytes: 5242880
             request_body: None,
             response_body: None,
             codex_auth: None,
+            claude_oauth: None,
             provider_type: None,
             model: None,
         };
@@ -1004,6 +1737,9 @@ max_request_body_bytes: 5242880
 
         let mut provider = ProviderSettings {
             api_key: Some("test-key".to_string()),
+            api_keys: Vec::new(),
+            key_selection: Default::default(),
+            key_source: Default::default(),
             base_url: None,
             enabled: true,
             http_client: None,
@@ -1011,6 +1747,7 @@ max_request_body_bytes: 5242880
             request_body: None,
             response_body: None,
             codex_auth: None,
+            claude_oauth: None,
             provider_type: None,
             model: None,
         };
@@ -1043,6 +1780,9 @@ max_request_body_bytes: 5242880
 
         let mut provider = ProviderSettings {
             api_key: Some("test-key".to_string()),
+            api_keys: Vec::new(),
+            key_selection: Default::default(),
+            key_source: Default::default(),
             base_url: None,
             enabled: true,
             http_client: None,
@@ -1050,6 +1790,7 @@ max_request_body_bytes: 5242880
             request_body: None,
             response_body: None,
             codex_auth: None,
+            claude_oauth: None,
             provider_type: None,
             model: None,
         };
@@ -1190,6 +1931,9 @@ providers:
     fn test_merge_http_client_env_creates_http_client_if_missing() {
         let mut provider = ProviderSettings {
             api_key: Some("test-key".to_string()),
+            api_keys: Vec::new(),
+            key_selection: Default::default(),
+            key_source: Default::default(),
             base_url: None,
             enabled: true,
             http_client: None, // Start with None
@@ -1197,6 +1941,7 @@ providers:
             request_body: None,
             response_body: None,
             codex_auth: None,
+            claude_oauth: None,
             provider_type: None,
             model: None,
         };
@@ -1223,6 +1968,9 @@ providers:
     fn test_merge_http_client_env_preserves_existing_values() {
         let mut provider = ProviderSettings {
             api_key: Some("test-key".to_string()),
+            api_keys: Vec::new(),
+            key_selection: Default::default(),
+            key_source: Default::default(),
             base_url: None,
             enabled: true,
             http_client: Some(HttpClientSettings {
@@ -1238,6 +1986,7 @@ providers:
             request_body: None,
             response_body: None,
             codex_auth: None,
+            claude_oauth: None,
             provider_type: None,
             model: None,
         };
@@ -1302,6 +2051,9 @@ mod provider_config_tests {
                 "sonnet".to_string(),
                 ProviderSettings {
                     api_key: Some("key".to_string()),
+                    api_keys: Vec::new(),
+                    key_selection: Default::default(),
+                    key_source: Default::default(),
                     base_url: None,
                     enabled: true,
                     http_client: None,
@@ -1309,6 +2061,7 @@ mod provider_config_tests {
                     request_body: None,
                     response_body: None,
                     codex_auth: None,
+                    claude_oauth: None,
                     provider_type: Some("anthropic".to_string()),
                     model: Some("claude-sonnet-4-20250514".to_string()),
                 },
@@ -1327,6 +2080,9 @@ mod provider_config_tests {
                 "sonnet".to_string(),
                 ProviderSettings {
                     api_key: Some("key".to_string()),
+                    api_keys: Vec::new(),
+                    key_selection: Default::default(),
+                    key_source: Default::default(),
                     base_url: None,
                     enabled: true,
                     http_client: None,
@@ -1334,6 +2090,7 @@ mod provider_config_tests {
                     request_body: None,
                     response_body: None,
                     codex_auth: None,
+                    claude_oauth: None,
                     provider_type: None,
                     model: None,
                 },
@@ -1352,6 +2109,9 @@ mod provider_config_tests {
                 "openai".to_string(),
                 ProviderSettings {
                     api_key: Some("key".to_string()),
+                    api_keys: Vec::new(),
+                    key_selection: Default::default(),
+                    key_source: Default::default(),
                     base_url: None,
                     enabled: true,
                     http_client: None,
@@ -1359,6 +2119,7 @@ mod provider_config_tests {
                     request_body: None,
                     response_body: None,
                     codex_auth: None,
+                    claude_oauth: None,
                     provider_type: Some("openai".to_string()),
                     model: None,
                 },
@@ -1393,4 +2154,436 @@ sonnet:
         assert_eq!(sonnet.provider_type.as_deref(), Some("anthropic"));
         assert_eq!(sonnet.model.as_deref(), Some("claude-sonnet-4-20250514"));
     }
+
+    #[test]
+    fn test_yaml_deserialization_with_model_aliases() {
+        let yaml = r#"
+model_aliases:
+  fast:
+    description: "Cheap and quick"
+    targets:
+      - provider: anthropic
+        model: claude-haiku-4-5
+        params:
+          max_tokens: 4096
+      - provider: openai
+        model: gpt-5-mini
+model_rewrites:
+  claude-3-haiku-20240307: fast
+"#;
+        let routing: RoutingConfig = serde_yaml::from_str(yaml).expect("should deserialize");
+        let table = routing.model_alias_table().unwrap().unwrap();
+        let resolution = table.resolve("claude-3-haiku-20240307");
+        assert_eq!(resolution.alias.as_deref(), Some("fast"));
+        assert_eq!(resolution.targets[0].params.max_tokens, Some(4096));
+
+        assert!(
+            RoutingConfig::default()
+                .model_alias_table()
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_yaml_deserialization_with_sticky_markers() {
+        let routing: RoutingConfig =
+            serde_yaml::from_str("markers:\n  sticky: true\n").expect("should deserialize");
+        assert!(routing.markers.sticky);
+        assert_eq!(routing.markers.sticky_ttl_secs, 86400);
+        assert!(!RoutingConfig::default().markers.sticky);
+    }
+
+    #[test]
+    fn test_yaml_deserialization_with_rate_limit_queue() {
+        let yaml = r#"
+rate_limit_queue:
+  enabled: true
+  max_wait_secs: 45
+"#;
+        let routing: RoutingConfig = serde_yaml::from_str(yaml).expect("should deserialize");
+        let queue = routing.rate_limit_queue.queue_config().unwrap();
+        assert_eq!(queue.max_wait, std::time::Duration::from_secs(45));
+        assert_eq!(queue.max_queued, 100);
+
+        assert!(
+            RoutingConfig::default()
+                .rate_limit_queue
+                .queue_config()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_yaml_deserialization_with_experiments() {
+        let yaml = r#"
+experiments:
+  - name: sonnet-vs-gpt5
+    models: [claude-sonnet-4-5]
+    sticky_key: header
+    header: x-user-id
+    arms:
+      - id: control
+        weight: 50
+      - id: gpt5
+        weight: 25
+        provider: openai
+        model: gpt-5
+      - id: terse
+        weight: 25
+        system_prompt:
+          mode: prepend
+          text: "Answer in one paragraph."
+        params:
+          temperature: 0.2
+"#;
+        let routing: RoutingConfig = serde_yaml::from_str(yaml).expect("should deserialize");
+        let set = routing.experiment_set().unwrap().unwrap();
+        let experiment = &set.experiments()[0];
+        assert!(experiment.enabled);
+        assert_eq!(experiment.sticky_key, lunaroute_routing::StickyKey::Header);
+        assert!(experiment.arms[0].is_control());
+        assert_eq!(experiment.arms[1].provider.as_deref(), Some("openai"));
+        assert_eq!(
+            experiment.arms[2].system_prompt.as_ref().unwrap().mode,
+            lunaroute_routing::PatchMode::Prepend
+        );
+
+        assert!(RoutingConfig::default().experiment_set().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_yaml_deserialization_with_state() {
+        let config = ServerConfig::default();
+        assert!(config.state.enabled);
+        assert_eq!(config.state.backend, StateBackend::File);
+
+        let yaml = r#"
+state:
+  backend: postgres
+  database_url: postgres://localhost/lunaroute
+  sync_interval_secs: 5
+"#;
+        let config: ServerConfig = serde_yaml::from_str(yaml).expect("should deserialize");
+        assert_eq!(config.state.backend, StateBackend::Postgres);
+        assert_eq!(
+            config.state.database_url.as_deref(),
+            Some("postgres://localhost/lunaroute")
+        );
+        assert_eq!(config.state.sync_interval_secs, 5);
+        assert_eq!(config.state.path, PathBuf::from("~/.lunaroute/state.json"));
+    }
+
+    #[test]
+    fn test_yaml_deserialization_with_health_probes() {
+        let config = ServerConfig::default();
+        assert!(!config.health_probes.enabled);
+
+        let yaml = r#"
+health_probes:
+  enabled: true
+  interval_secs: 15
+  providers:
+    anthropic:
+      method: completion
+      model: claude-haiku-4-5
+"#;
+        let config: ServerConfig = serde_yaml::from_str(yaml).expect("should deserialize");
+        assert!(config.health_probes.enabled);
+        assert_eq!(config.health_probes.interval_secs, 15);
+        assert_eq!(config.health_probes.jitter_secs, 5);
+        let anthropic = &config.health_probes.providers["anthropic"];
+        assert_eq!(anthropic.method, lunaroute_routing::ProbeMethod::Completion);
+        assert_eq!(anthropic.model.as_deref(), Some("claude-haiku-4-5"));
+    }
+
+    #[test]
+    fn test_yaml_deserialization_with_auth() {
+        assert!(!ServerConfig::default().auth.enabled);
+
+        let config: ServerConfig =
+            serde_yaml::from_str("auth:\n  enabled: true\n").expect("should deserialize");
+        assert!(config.auth.enabled);
+    }
+
+    #[test]
+    fn test_yaml_deserialization_with_rate_limits() {
+        assert!(!ServerConfig::default().rate_limits.is_active());
+
+        let yaml = r#"
+rate_limits:
+  enabled: true
+  per_key:
+    requests_per_minute: 60
+    concurrent_streams: 4
+  per_ip:
+    input_tokens_per_minute: 100000
+"#;
+        let config: ServerConfig = serde_yaml::from_str(yaml).expect("should deserialize");
+        let limits = &config.rate_limits;
+        assert!(limits.is_active());
+        assert_eq!(limits.per_key.requests_per_minute, Some(60));
+        assert_eq!(limits.per_key.concurrent_streams, Some(4));
+        assert_eq!(limits.per_ip.input_tokens_per_minute, Some(100000));
+        assert!(limits.global.requests_per_minute.is_none());
+        assert!(!limits.trust_forwarded_for);
+    }
+
+    #[test]
+    fn test_yaml_deserialization_with_budgets() {
+        use lunaroute_ingress::budget::{BudgetAction, BudgetSubject, BudgetWindow};
+        assert!(!ServerConfig::default().budgets.is_active());
+
+        let yaml = r#"
+budgets:
+  enabled: true
+  webhook_url: http://localhost:9000/hooks
+  prices:
+    - { model: "claude-sonnet-*", input_per_million: 3.0, output_per_million: 15.0 }
+  budgets:
+    - name: tenant-monthly
+      subject: tenant
+      metric: dollars
+      window: monthly
+      limit: 500
+      warn_at: [0.5, 0.9]
+      action: reroute
+      reroute_model: claude-haiku-4-5
+"#;
+        let config: ServerConfig = serde_yaml::from_str(yaml).expect("should deserialize");
+        let budgets = &config.budgets;
+        assert!(budgets.is_active());
+        budgets.validate().unwrap();
+        let budget = &budgets.budgets[0];
+        assert_eq!(budget.subject, BudgetSubject::Tenant);
+        assert_eq!(budget.window, BudgetWindow::Monthly);
+        assert_eq!(budget.action, BudgetAction::Reroute);
+        assert_eq!(
+            budgets
+                .price("claude-sonnet-4-5")
+                .unwrap()
+                .output_per_million,
+            15.0
+        );
+        assert!(budgets.price("gpt-5").is_none());
+    }
+
+    #[test]
+    fn test_yaml_deserialization_with_key_pool() {
+        let yaml = r#"
+providers:
+  anthropic:
+    api_key: sk-ant-primary
+    api_keys: [sk-ant-org2, sk-ant-org3]
+    key_selection: least_used
+  openai:
+    api_key: sk-single
+"#;
+        let config: ServerConfig = serde_yaml::from_str(yaml).expect("should deserialize");
+        let anthropic = config.providers.anthropic.as_ref().unwrap();
+        assert_eq!(
+            anthropic.key_selection,
+            lunaroute_egress::KeySelection::LeastUsed
+        );
+        let pool = anthropic.key_pool("anthropic").unwrap();
+        assert_eq!(pool.len(), 3);
+        assert_eq!(
+            pool.select().unwrap().secret(),
+            "sk-ant-primary",
+            "api_key comes first in the pool"
+        );
+
+        // A single key keeps the plain connector setup
+        assert!(
+            config
+                .providers
+                .openai
+                .as_ref()
+                .unwrap()
+                .key_pool("openai")
+                .is_none()
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_yaml_deserialization_with_key_source() {
+        let yaml = r#"
+providers:
+  anthropic:
+    api_key_command: "printf sk-ant-from-cmd"
+    api_key_ttl_secs: 60
+    api_key_command_timeout_secs: 5
+"#;
+        let config: ServerConfig = serde_yaml::from_str(yaml).expect("should deserialize");
+        let anthropic = config.providers.anthropic.as_ref().unwrap();
+        assert!(anthropic.key_source.is_set());
+        assert_eq!(anthropic.key_source.api_key_ttl_secs, Some(60));
+
+        // A command source alone is enough to build a pool
+        let pool = anthropic.key_pool("anthropic").unwrap();
+        assert_eq!(pool.len(), 1);
+        assert_eq!(
+            pool.select_fresh().await.unwrap().secret(),
+            "sk-ant-from-cmd"
+        );
+        assert!(!format!("{:?}", config).contains("sk-ant-from-cmd"));
+    }
+
+    #[test]
+    fn test_yaml_deserialization_with_claude_oauth() {
+        let yaml = r#"
+providers:
+  anthropic:
+    claude_oauth:
+      enabled: true
+      credentials_files:
+        - /home/alice/.claude/.credentials.json
+        - /home/bob/.claude/.credentials.json
+      refresh_before_expiry_secs: 120
+"#;
+        let config: ServerConfig = serde_yaml::from_str(yaml).expect("should deserialize");
+        let anthropic = config.providers.anthropic.as_ref().unwrap();
+        let pool = anthropic.key_pool("anthropic").unwrap();
+        assert_eq!(pool.len(), 2);
+        config.providers.validate_extra_providers().unwrap();
+
+        let defaults: ClaudeOAuthConfig = serde_yaml::from_str("enabled: true").unwrap();
+        assert_eq!(
+            defaults.credentials_files,
+            vec![PathBuf::from("~/.claude/.credentials.json")]
+        );
+
+        let openai: ServerConfig = serde_yaml::from_str(
+            r#"
+providers:
+  openai:
+    claude_oauth:
+      enabled: true
+"#,
+        )
+        .unwrap();
+        assert!(openai.providers.validate_extra_providers().is_err());
+    }
+
+    #[test]
+    fn test_tenant_providers_reject_local_credentials() {
+        let tenant_id = lunaroute_core::tenant::TenantId::new();
+        let inline: ServerConfig = serde_yaml::from_str(&format!(
+            "providers:\n  anthropic:\n    api_keys: [sk-ant-1, \"vault:{}/org2\"]\n",
+            tenant_id
+        ))
+        .unwrap();
+        inline
+            .providers
+            .validate_tenant_providers(Some(tenant_id))
+            .unwrap();
+
+        for settings in [
+            "api_key_command: cat /etc/passwd",
+            "api_key_file: /etc/lunaroute/key",
+            "codex_auth:\n      enabled: true",
+            "claude_oauth:\n      enabled: true",
+        ] {
+            let yaml = format!(
+                "providers:\n  team:\n    provider_type: anthropic\n    {}\n",
+                settings
+            );
+            let config: ServerConfig = serde_yaml::from_str(&yaml).unwrap();
+            let err = config
+                .providers
+                .validate_tenant_providers(Some(tenant_id))
+                .unwrap_err();
+            assert!(err.contains("not allowed in tenant configs"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_tenant_providers_scope_vault_refs() {
+        let (tenant_a, tenant_b) = (
+            lunaroute_core::tenant::TenantId::new(),
+            lunaroute_core::tenant::TenantId::new(),
+        );
+        let foreign: ServerConfig = serde_yaml::from_str(&format!(
+            "providers:\n  openai:\n    api_key: \"vault:{}/openai\"\n",
+            tenant_b
+        ))
+        .unwrap();
+        let err = foreign
+            .providers
+            .validate_tenant_providers(Some(tenant_a))
+            .unwrap_err();
+        assert!(err.contains("outside the tenant's namespace"), "{}", err);
+        foreign
+            .providers
+            .validate_tenant_providers(Some(tenant_b))
+            .unwrap();
+
+        // Un-namespaced entries belong to the server, and nothing is
+        // allowed before the tenant has an ID
+        let global: ServerConfig =
+            serde_yaml::from_str("providers:\n  openai:\n    api_keys: [\"vault:openai\"]\n")
+                .unwrap();
+        assert!(
+            global
+                .providers
+                .validate_tenant_providers(Some(tenant_a))
+                .is_err()
+        );
+        assert!(foreign.providers.validate_tenant_providers(None).is_err());
+    }
+
+    #[test]
+    fn test_resolve_vault_refs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let params = lunaroute_storage::KeyDerivationParams {
+            memory_size: 256,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let mut vault = lunaroute_storage::CredentialVault::open_with_params(
+            dir.path().join("vault.enc"),
+            "pw",
+            params,
+        )
+        .unwrap();
+        vault.add("anthropic-prod", "sk-ant-from-vault").unwrap();
+        vault.add("org2", "sk-ant-org2").unwrap();
+
+        let yaml = r#"
+providers:
+  anthropic:
+    api_key: "vault:anthropic-prod"
+    api_keys: ["vault:org2", sk-ant-literal]
+  openai:
+    api_key: sk-plain
+"#;
+        let mut config: ServerConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(
+            config
+                .providers
+                .anthropic
+                .as_ref()
+                .unwrap()
+                .has_vault_refs()
+        );
+        config.resolve_vault_refs_from(&vault).unwrap();
+
+        let anthropic = config.providers.anthropic.as_ref().unwrap();
+        assert_eq!(anthropic.api_key.as_deref(), Some("sk-ant-from-vault"));
+        assert_eq!(anthropic.api_keys, vec!["sk-ant-org2", "sk-ant-literal"]);
+        assert_eq!(
+            config.providers.openai.as_ref().unwrap().api_key.as_deref(),
+            Some("sk-plain")
+        );
+
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("sk-ant-from-vault"));
+        assert!(!debug.contains("sk-plain"));
+
+        let mut missing: ServerConfig =
+            serde_yaml::from_str("providers:\n  openai:\n    api_key: \"vault:nope\"\n").unwrap();
+        let err = missing.resolve_vault_refs_from(&vault).unwrap_err();
+        assert!(err.contains("'nope'"));
+    }
 }
@@ -48,36 +48,27 @@
 //!   }'
 //! ```
 
+mod admin;
 mod app;
 mod bootstrap;
 mod config;
+mod providers;
 mod session_factory;
 mod session_stats;
+mod tenants;
 
 use clap::{Parser, Subcommand};
 use config::{ApiDialect, ServerConfig};
-use futures::StreamExt;
 use lunaroute_config_file::FileConfigStore;
-use lunaroute_core::provider::Provider;
-use lunaroute_core::{
-    config_store::ConfigStore,
-    error::Error as CoreError,
-    normalized::{NormalizedRequest, NormalizedResponse, NormalizedStreamEvent},
-    session_store::SessionStore,
-};
-use lunaroute_egress::{
-    anthropic::{AnthropicConfig, AnthropicConnector},
-    openai::{OpenAIConfig, OpenAIConnector, RequestBodyModConfig, ResponseBodyModConfig},
-};
-use lunaroute_ingress::{BypassProvider, anthropic as anthropic_ingress, openai, with_bypass};
+use lunaroute_core::{config_store::ConfigStore, session_store::SessionStore};
+use lunaroute_ingress::with_bypass;
 use lunaroute_observability::{HealthState, Metrics, health_router};
-use lunaroute_routing::{PathClassifier, RouteTable, Router, RoutingRule, RuleMatcher};
-use std::collections::HashMap;
+use lunaroute_routing::{HealthProbeConfig, PathClassifier, ProbeTarget, RouteTable, Router};
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tracing::{Level, debug, info, warn};
+use tracing::{Level, info, warn};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 const VERSION: &str = env!("VERSION");
@@ -193,140 +184,6 @@ enum Commands {
         port: Option<u16>,
     },
 }
-
-/// Logging provider that prints all requests and responses to stdout
-struct LoggingProvider {
-    inner: Arc<dyn Provider>,
-    provider_name: String,
-}
-
-impl LoggingProvider {
-    fn new(inner: Arc<dyn Provider>, provider_name: String) -> Self {
-        Self {
-            inner,
-            provider_name,
-        }
-    }
-}
-
-#[async_trait::async_trait]
-impl Provider for LoggingProvider {
-    async fn send(&self, request: NormalizedRequest) -> Result<NormalizedResponse, CoreError> {
-        info!("┌─────────────────────────────────────────────────────────");
-        info!("│ REQUEST to {} (non-streaming)", self.provider_name);
-        info!("├─────────────────────────────────────────────────────────");
-        info!("│ Model: {}", request.model);
-        info!("│ Messages: {} messages", request.messages.len());
-        debug!(
-            "│ Full request:\n{}",
-            serde_json::to_string_pretty(&request)
-                .unwrap_or_else(|e| format!("Serialization error: {}", e))
-        );
-        info!("└─────────────────────────────────────────────────────────");
-
-        let response = self.inner.send(request).await?;
-
-        info!("┌─────────────────────────────────────────────────────────");
-        info!("│ RESPONSE from {} (non-streaming)", self.provider_name);
-        info!("├─────────────────────────────────────────────────────────");
-        if !response.choices.is_empty() {
-            let message = &response.choices[0].message;
-            // MessageContent is an enum, check if it's text
-            if let lunaroute_core::normalized::MessageContent::Text(text) = &message.content {
-                debug!("│ Content: {}", text);
-            }
-        }
-        info!(
-            "│ Tokens: input={}, output={}, total={}",
-            response.usage.prompt_tokens,
-            response.usage.completion_tokens,
-            response.usage.prompt_tokens + response.usage.completion_tokens
-        );
-        debug!(
-            "│ Full response:\n{}",
-            serde_json::to_string_pretty(&response)
-                .unwrap_or_else(|e| format!("Serialization error: {}", e))
-        );
-        info!("└─────────────────────────────────────────────────────────");
-
-        Ok(response)
-    }
-
-    async fn stream(
-        &self,
-        request: NormalizedRequest,
-    ) -> Result<
-        Box<dyn futures::Stream<Item = Result<NormalizedStreamEvent, CoreError>> + Send + Unpin>,
-        CoreError,
-    > {
-        info!("┌─────────────────────────────────────────────────────────");
-        info!("│ REQUEST to {} (streaming)", self.provider_name);
-        info!("├─────────────────────────────────────────────────────────");
-        info!("│ Model: {}", request.model);
-        info!("│ Messages: {} messages", request.messages.len());
-        debug!(
-            "│ Full request:\n{}",
-            serde_json::to_string_pretty(&request)
-                .unwrap_or_else(|e| format!("Serialization error: {}", e))
-        );
-        info!("└─────────────────────────────────────────────────────────");
-
-        let stream = self.inner.stream(request).await?;
-        let provider_name = self.provider_name.clone();
-
-        info!("┌─────────────────────────────────────────────────────────");
-        info!("│ STREAMING from {}", provider_name);
-        info!("└─────────────────────────────────────────────────────────");
-
-        // Wrap stream to log each event
-        let logged_stream = stream.map(move |event| {
-            if let Ok(ref evt) = event {
-                match evt {
-                    NormalizedStreamEvent::Start { .. } => {
-                        debug!("│ 🟢 Stream started");
-                    }
-                    NormalizedStreamEvent::Delta { delta, .. } => {
-                        if let Some(ref content) = delta.content {
-                            debug!("│ 📝 {}", content);
-                        }
-                    }
-                    NormalizedStreamEvent::ToolCallDelta { function, .. } => {
-                        if let Some(func) = function {
-                            if let Some(name) = &func.name {
-                                debug!("│ 🔧 Tool call: {}", name);
-                            }
-                            if let Some(args) = &func.arguments {
-                                debug!("│ 🔧 Tool args delta: {}", args);
-                            }
-                        }
-                    }
-                    NormalizedStreamEvent::Usage { usage } => {
-                        info!(
-                            "│ 📊 Usage: input={}, output={}, total={}",
-                            usage.prompt_tokens,
-                            usage.completion_tokens,
-                            usage.prompt_tokens + usage.completion_tokens
-                        );
-                    }
-                    NormalizedStreamEvent::End { finish_reason } => {
-                        info!("│ 🏁 Stream ended: {:?}", finish_reason);
-                    }
-                    NormalizedStreamEvent::Error { error } => {
-                        warn!("│ ❌ Stream error: {}", error);
-                    }
-                }
-            }
-            event
-        });
-
-        Ok(Box::new(logged_stream))
-    }
-
-    fn capabilities(&self) -> lunaroute_core::provider::ProviderCapabilities {
-        self.inner.capabilities()
-    }
-}
-
 /// Handle the 'env' subcommand - start server in background and output export commands
 fn handle_env_command(
     host: Option<String>,
@@ -576,17 +433,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     info!("✓ Multi-tenant database mode (tenant from request)");
                 }
 
-                // Load config from database
-                let config_json = pg_store
-                    .get_config(tenant_id)
-                    .await
-                    .map_err(|e| format!("Failed to load config from database: {}", e))?;
+                if tenant_id.is_some() {
+                    // Load config from database
+                    let config_json = pg_store
+                        .get_config(tenant_id)
+                        .await
+                        .map_err(|e| format!("Failed to load config from database: {}", e))?;
 
-                // Parse config JSON into ServerConfig
-                config = serde_json::from_value(config_json)
-                    .map_err(|e| format!("Failed to parse config: {}", e))?;
+                    // Parse config JSON into ServerConfig
+                    config = serde_json::from_value(config_json)
+                        .map_err(|e| format!("Failed to parse config: {}", e))?;
 
-                info!("✓ Loaded configuration from database");
+                    info!("✓ Loaded configuration from database");
+                } else {
+                    // Server-wide settings (listeners, auth, tenancy) come from the
+                    // config file; tenant configs are loaded per request
+                    let file_path = cli
+                        .config
+                        .clone()
+                        .or_else(|| {
+                            bootstrap
+                                .file_path
+                                .as_ref()
+                                .map(|p| p.to_string_lossy().to_string())
+                        })
+                        .unwrap_or_else(|| "~/.lunaroute/config.yaml".to_string());
+                    config = match ServerConfig::from_file(&file_path) {
+                        Ok(c) => {
+                            info!("✓ Loaded server configuration from: {}", file_path);
+                            c
+                        }
+                        Err(e) => {
+                            warn!("Failed to load config from {}: {}", file_path, e);
+                            info!("Using default server configuration");
+                            ServerConfig::default()
+                        }
+                    };
+                }
 
                 config_store = Some(Arc::new(pg_store));
             }
@@ -605,6 +488,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Merge environment variables (they override config file/database)
     config.merge_env();
 
+    // Swap `vault:<name>` provider keys for their secrets
+    config.resolve_vault_refs()?;
+
     // ============================================================================
     // PHASE 7: Initialize session store
     // ============================================================================
@@ -628,6 +514,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
+    // In multi-tenant database mode, each request's tenant is resolved at ingress
+    let tenant_resolver = match (&config_store, tenant_id) {
+        (Some(store), None) if matches!(bootstrap.source, bootstrap::ConfigSource::Database) => {
+            Some(Arc::new(
+                lunaroute_ingress::tenant::TenantResolver::new(
+                    config.tenancy.clone(),
+                    store.clone(),
+                    session_store.clone(),
+                )
+                .map_err(|e| format!("Invalid tenancy config: {}", e))?
+                .with_auth(config.auth.enabled),
+            ))
+        }
+        _ => None,
+    };
+
     // Create AppState if both stores are available
     // Clone session_store for passthrough routers before creating AppState
     let session_store_for_passthrough = session_store.clone().map(|s| {
@@ -707,341 +609,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("📋 Request/response logging enabled (stdout)");
     }
 
-    // Setup providers
-    let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
-
-    // Store raw connectors for passthrough mode
-    let mut anthropic_connector: Option<Arc<AnthropicConnector>> = None;
-    let mut openai_connector: Option<Arc<OpenAIConnector>> = None;
-
-    // OpenAI provider
-    if let Some(openai_config) = &config.providers.openai
-        && openai_config.enabled
-    {
-        // Get API key (empty string if not configured - will use client's header)
-        let api_key = openai_config.api_key.clone().unwrap_or_default();
-
-        if api_key.is_empty() {
-            info!("✓ OpenAI provider enabled (no API key - will use client auth)");
-        } else {
-            info!("✓ OpenAI provider enabled");
-        }
-
-        let base_url = openai_config
-            .base_url
-            .clone()
-            .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
-
-        // Use http_client config from YAML if provided, otherwise use defaults
-        let client_config = openai_config
-            .http_client
-            .as_ref()
-            .map(|c| c.to_http_client_config())
-            .unwrap_or_default();
-
-        let mut provider_config = OpenAIConfig {
-            api_key: api_key.clone(),
-            base_url,
-            organization: None,
-            client_config,
-            custom_headers: None,
-            request_body_config: None,
-            response_body_config: None,
-            codex_auth: openai_config.codex_auth.as_ref().map(|c| {
-                lunaroute_egress::openai::CodexAuthConfig {
-                    enabled: c.enabled,
-                    auth_file: c.auth_file.clone(),
-                    token_field: c.token_field.clone(),
-                    account_id: c.account_id.clone(),
-                }
-            }),
-            switch_notification_message: None,
-        };
-
-        // Wire custom headers and body modifications
-        if let Some(headers_config) = &openai_config.request_headers {
-            provider_config.custom_headers = Some(headers_config.headers.clone());
-        }
-        if let Some(request_body) = &openai_config.request_body {
-            provider_config.request_body_config = Some(RequestBodyModConfig {
-                defaults: request_body.defaults.clone(),
-                overrides: request_body.overrides.clone(),
-                prepend_messages: request_body.prepend_messages.clone(),
-            });
-        }
-        if let Some(response_body) = &openai_config.response_body {
-            provider_config.response_body_config = Some(ResponseBodyModConfig {
-                enabled: response_body.enabled,
-                metadata_namespace: response_body.metadata_namespace.clone(),
-                fields: response_body.fields.clone(),
-                extension_fields: response_body.extension_fields.clone(),
-            });
-        }
-
-        let conn = OpenAIConnector::new(provider_config).await?;
-
-        // Build the provider stack (order matters!)
-        // 1. Start with connector
-        // 2. Wrap with session recording if enabled
-        // 3. Wrap with logging if enabled
-        let connector = Arc::new(conn);
-        openai_connector = Some(connector.clone()); // Save for passthrough
-        // Session recording is now handled via async multi-writer in passthrough mode
-        let provider: Arc<dyn Provider> = if config.logging.log_requests {
-            info!("  Request/response logging: enabled");
-            Arc::new(LoggingProvider::new(
-                connector.clone(),
-                "OpenAI".to_string(),
-            ))
-        } else {
-            connector
-        };
-
-        providers.insert("openai".to_string(), provider);
-    }
-
-    // Anthropic provider
-    if let Some(anthropic_config) = &config.providers.anthropic
-        && anthropic_config.enabled
-    {
-        // Get API key (empty string if not configured - will use client's header)
-        let api_key = anthropic_config.api_key.clone().unwrap_or_default();
-
-        if api_key.is_empty() {
-            info!("✓ Anthropic provider enabled (no API key - will use client auth)");
-        } else {
-            info!("✓ Anthropic provider enabled");
-        }
-
-        let base_url = anthropic_config
-            .base_url
-            .clone()
-            .unwrap_or_else(|| "https://api.anthropic.com".to_string());
-
-        // Use http_client config from YAML if provided, otherwise use defaults
-        let client_config = anthropic_config
-            .http_client
-            .as_ref()
-            .map(|c| c.to_http_client_config())
-            .unwrap_or_default();
-
-        let provider_config = AnthropicConfig {
-            api_key: api_key.clone(),
-            base_url,
-            api_version: "2023-06-01".to_string(),
-            client_config,
-            switch_notification_message: None,
-        };
-        let conn = AnthropicConnector::new(provider_config)?;
-
-        // Build the provider stack (order matters!)
-        // Session recording is now handled via async multi-writer in passthrough mode
-        let connector = Arc::new(conn);
-        anthropic_connector = Some(connector.clone()); // Save for passthrough
-        let provider: Arc<dyn Provider> = if config.logging.log_requests {
-            info!("  Request/response logging: enabled");
-            Arc::new(LoggingProvider::new(
-                connector.clone(),
-                "Anthropic".to_string(),
-            ))
-        } else {
-            connector
-        };
-
-        providers.insert("anthropic".to_string(), provider);
-    }
-
-    // Build ProviderRegistry for marker-based routing
-    let mut provider_registry = lunaroute_ingress::ProviderRegistry::new();
-
-    // Add built-in providers
-    if let Some(ref connector) = openai_connector {
-        provider_registry.insert(
-            "openai".to_string(),
-            lunaroute_ingress::ProviderEntry {
-                connector_type: lunaroute_ingress::ProviderType::OpenAI,
-                openai_connector: Some(connector.clone()),
-                anthropic_connector: None,
-                model_override: config
-                    .providers
-                    .openai
-                    .as_ref()
-                    .and_then(|p| p.model.clone()),
-            },
-        );
-    }
-    if let Some(ref connector) = anthropic_connector {
-        provider_registry.insert(
-            "anthropic".to_string(),
-            lunaroute_ingress::ProviderEntry {
-                connector_type: lunaroute_ingress::ProviderType::Anthropic,
-                openai_connector: None,
-                anthropic_connector: Some(connector.clone()),
-                model_override: config
-                    .providers
-                    .anthropic
-                    .as_ref()
-                    .and_then(|p| p.model.clone()),
-            },
-        );
-    }
-
-    // Validate and build extra providers
-    config
-        .providers
-        .validate_extra_providers()
-        .map_err(|e| anyhow::anyhow!("Invalid provider config: {}", e))?;
-
-    for (name, settings) in &config.providers.extra {
-        if !settings.enabled {
-            info!("  Extra provider '{}': disabled, skipping", name);
-            continue;
-        }
-
-        let provider_type_str = settings.provider_type.as_deref().unwrap(); // validated above
-        let api_key = settings.api_key.clone().unwrap_or_default();
-
-        match provider_type_str {
-            "anthropic" => {
-                let base_url = settings
-                    .base_url
-                    .clone()
-                    .unwrap_or_else(|| "https://api.anthropic.com".to_string());
-                let client_config = settings
-                    .http_client
-                    .as_ref()
-                    .map(|c| c.to_http_client_config())
-                    .unwrap_or_default();
-                let connector_config = lunaroute_egress::anthropic::AnthropicConfig {
-                    api_key,
-                    base_url,
-                    api_version: "2023-06-01".to_string(),
-                    client_config,
-                    switch_notification_message: None,
-                };
-                let conn = lunaroute_egress::anthropic::AnthropicConnector::new(connector_config)?;
-                info!(
-                    "  Extra provider '{}': anthropic, model_override={:?}",
-                    name, settings.model
-                );
-                provider_registry.insert(
-                    name.clone(),
-                    lunaroute_ingress::ProviderEntry {
-                        connector_type: lunaroute_ingress::ProviderType::Anthropic,
-                        openai_connector: None,
-                        anthropic_connector: Some(Arc::new(conn)),
-                        model_override: settings.model.clone(),
-                    },
-                );
-            }
-            "openai" => {
-                let base_url = settings
-                    .base_url
-                    .clone()
-                    .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
-                let client_config = settings
-                    .http_client
-                    .as_ref()
-                    .map(|c| c.to_http_client_config())
-                    .unwrap_or_default();
-                let mut connector_config = lunaroute_egress::openai::OpenAIConfig {
-                    api_key,
-                    base_url,
-                    organization: None,
-                    client_config,
-                    custom_headers: None,
-                    request_body_config: None,
-                    response_body_config: None,
-                    codex_auth: None,
-                    switch_notification_message: None,
-                };
-                if let Some(headers_config) = &settings.request_headers {
-                    connector_config.custom_headers = Some(headers_config.headers.clone());
-                }
-                let conn = lunaroute_egress::openai::OpenAIConnector::new(connector_config).await?;
-                info!(
-                    "  Extra provider '{}': openai, model_override={:?}",
-                    name, settings.model
-                );
-                provider_registry.insert(
-                    name.clone(),
-                    lunaroute_ingress::ProviderEntry {
-                        connector_type: lunaroute_ingress::ProviderType::OpenAI,
-                        openai_connector: Some(Arc::new(conn)),
-                        anthropic_connector: None,
-                        model_override: settings.model.clone(),
-                    },
-                );
-            }
-            _ => unreachable!(), // validated above
-        }
-    }
-
-    let provider_registry = Arc::new(provider_registry);
-    if !provider_registry.is_empty() {
-        info!(
-            "📋 Provider registry: {} providers ({} for marker routing)",
-            provider_registry.len(),
-            config.providers.extra.len()
-        );
-    }
-
-    // Allow starting without providers in certain scenarios (e.g., passthrough with client-provided keys)
-    if providers.is_empty() {
-        warn!("⚠️  No providers configured - requests will fail unless using passthrough mode");
-        warn!("    To configure providers, either:");
-        warn!("    - Set OPENAI_API_KEY or ANTHROPIC_API_KEY environment variables, or");
-        warn!("    - Add api_key field to provider configuration in config file");
-    }
+    // Setup providers, the marker-routing registry, model aliases and experiments
+    let provider_set = providers::ProviderSet::build(&config).await?;
 
     // Create routing rules
-    let mut rules = vec![];
-
-    // Route GPT models to OpenAI with Anthropic fallback
-    if providers.contains_key("openai") {
-        rules.push(RoutingRule {
-            priority: 10,
-            name: Some("gpt-to-openai".to_string()),
-            matcher: RuleMatcher::model_pattern("^gpt-.*"),
-            strategy: None,
-            primary: Some("openai".to_string()),
-            fallbacks: if providers.contains_key("anthropic") {
-                vec!["anthropic".to_string()]
-            } else {
-                vec![]
-            },
-        });
-    }
-
-    // Route Claude models to Anthropic with OpenAI fallback
-    if providers.contains_key("anthropic") {
-        rules.push(RoutingRule {
-            priority: 10,
-            name: Some("claude-to-anthropic".to_string()),
-            matcher: RuleMatcher::model_pattern("^claude-.*"),
-            strategy: None,
-            primary: Some("anthropic".to_string()),
-            fallbacks: if providers.contains_key("openai") {
-                vec!["openai".to_string()]
-            } else {
-                vec![]
-            },
-        });
-    }
-
-    // Default fallback route (catches all)
-    rules.push(RoutingRule {
-        priority: 1,
-        name: Some("default-route".to_string()),
-        matcher: RuleMatcher::Always,
-        strategy: None,
-        primary: Some(if providers.contains_key("openai") {
-            "openai".to_string()
-        } else {
-            "anthropic".to_string()
-        }),
-        fallbacks: vec![],
-    });
+    let rules = provider_set.default_rules();
 
     info!("📋 Created {} routing rules", rules.len());
     for rule in &rules {
@@ -1052,107 +624,144 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Detect passthrough mode BEFORE creating router: dialect matches the only enabled provider
-    // This skips normalization for optimal performance and 100% API fidelity
-    let is_anthropic_passthrough = config.api_dialect == ApiDialect::Anthropic
-        && anthropic_connector.is_some()
-        && providers.len() == 1
-        && providers.contains_key("anthropic");
-
-    let is_openai_passthrough = config.api_dialect == ApiDialect::OpenAI
-        && openai_connector.is_some()
-        && providers.len() == 1
-        && providers.contains_key("openai");
-
-    // For dual-dialect mode, enable passthrough if BOTH connectors are available
-    // This allows OpenAI→OpenAI and Anthropic→Anthropic passthrough simultaneously
-    let is_dual_passthrough = config.api_dialect == ApiDialect::Both
-        && openai_connector.is_some()
-        && anthropic_connector.is_some();
-
-    let is_passthrough = is_anthropic_passthrough || is_openai_passthrough || is_dual_passthrough;
-
-    // Capture bypass provider info BEFORE connectors are moved
-    let bypass_provider_info = if config.bypass.enabled {
-        // Determine which provider to use for bypass
-        let provider_name = if let Some(name) = &config.bypass.provider {
-            Some(name.as_str())
-        } else if openai_connector.is_some() {
-            Some("openai")
-        } else if anthropic_connector.is_some() {
-            Some("anthropic")
-        } else {
-            None
-        };
-
-        match provider_name {
-            Some("openai") if openai_connector.is_some() => {
-                // Use base domain without /v1 path for bypass, since request paths
-                // already include the full path (e.g., /v1/embeddings)
-                let base_url = config
-                    .providers
-                    .openai
-                    .as_ref()
-                    .and_then(|p| p.base_url.clone())
-                    .unwrap_or_else(|| "https://api.openai.com".to_string());
-                // Strip trailing /v1 if present to avoid double-pathing (/v1/v1/...)
-                let base_url = base_url
-                    .trim_end_matches('/')
-                    .trim_end_matches("/v1")
-                    .to_string();
-                let api_key = config
-                    .providers
-                    .openai
-                    .as_ref()
-                    .and_then(|p| p.api_key.clone())
-                    .unwrap_or_default();
-
-                Some(("openai".to_string(), base_url, api_key))
-            }
-            Some("anthropic") if anthropic_connector.is_some() => {
-                // Use base domain without /v1 path for bypass, since request paths
-                // already include the full path (e.g., /v1/messages/count_tokens)
-                let base_url = config
-                    .providers
-                    .anthropic
-                    .as_ref()
-                    .and_then(|p| p.base_url.clone())
-                    .unwrap_or_else(|| "https://api.anthropic.com".to_string());
-                // Strip trailing /v1 if present to avoid double-pathing (/v1/v1/...)
-                let base_url = base_url
-                    .trim_end_matches('/')
-                    .trim_end_matches("/v1")
-                    .to_string();
-                let api_key = config
-                    .providers
-                    .anthropic
-                    .as_ref()
-                    .and_then(|p| p.api_key.clone())
-                    .unwrap_or_default();
-
-                Some(("anthropic".to_string(), base_url, api_key))
-            }
-            _ => None,
-        }
-    } else {
-        None
-    };
+    let passthrough = provider_set.passthrough(config.api_dialect);
+    let is_passthrough = passthrough.any();
 
     // Initialize observability (needed before router creation)
     info!("📊 Initializing observability (metrics, health endpoints)");
     let metrics = Arc::new(Metrics::new()?);
-    let health_state = HealthState::new(metrics.clone());
+    provider_set.report_quarantines(&metrics);
 
     // Create router with routing table (not needed in passthrough mode, but keep for consistency)
     let route_table = RouteTable::with_rules(rules);
-    let router = Arc::new(Router::new(
+    let mut router = Router::new(
         route_table,
-        providers,
+        provider_set.providers.clone(),
         lunaroute_routing::HealthMonitorConfig::default(),
         lunaroute_routing::CircuitBreakerConfig::default(),
         Some(metrics.clone()),
         config.routing.provider_switch_notification.clone(),
+    );
+    if let Some(table) = &provider_set.model_aliases {
+        router = router.with_model_aliases(table.clone());
+    }
+    if let Some(queue) = config.routing.rate_limit_queue.queue_config() {
+        info!(
+            "⏳ Rate-limit queue enabled (max wait {}s, max {} queued)",
+            queue.max_wait.as_secs(),
+            queue.max_queued
+        );
+        router = router.with_rate_limit_queue(queue);
+    }
+    // Routing state, ingress API keys, rate limits and budgets share one state store
+    let rate_limits_active = config.rate_limits.is_active();
+    let budgets_active = config.budgets.is_active();
+    if budgets_active {
+        config
+            .budgets
+            .validate()
+            .map_err(|e| format!("Invalid budgets: {}", e))?;
+    }
+    let state_store =
+        if config.state.enabled || config.auth.enabled || rate_limits_active || budgets_active {
+            match open_state_store(&config.state).await {
+                Ok(store) => Some(store),
+                Err(e) if config.auth.enabled => {
+                    return Err(
+                        format!("API key authentication needs the state store: {}", e).into(),
+                    );
+                }
+                Err(e) if rate_limits_active => {
+                    return Err(format!("Rate limiting needs the state store: {}", e).into());
+                }
+                Err(e) if budgets_active => {
+                    return Err(format!("Budgets need the state store: {}", e).into());
+                }
+                Err(e) => {
+                    warn!(
+                        "⚠️  Failed to open routing state store: {} (state will not persist)",
+                        e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+    if config.state.enabled
+        && let Some(store) = &state_store
+    {
+        router = router.with_state_store(store.clone());
+    }
+    let rate_limiter = state_store
+        .clone()
+        .filter(|_| rate_limits_active)
+        .map(|store| {
+            Arc::new(lunaroute_ingress::rate_limit::RateLimiter::new(
+                config.rate_limits.clone(),
+                store,
+                config.http_server.max_request_body_bytes,
+            ))
+        });
+    let budget_tracker = state_store.clone().filter(|_| budgets_active).map(|store| {
+        Arc::new(
+            lunaroute_ingress::budget::BudgetTracker::new(
+                config.budgets.clone(),
+                store,
+                config.http_server.max_request_body_bytes,
+            )
+            .with_metrics(metrics.clone()),
+        )
+    });
+    let api_keys = state_store
+        .filter(|_| config.auth.enabled)
+        .map(|store| Arc::new(lunaroute_ingress::auth::ApiKeyStore::new(store)));
+    let router = Arc::new(router);
+    let router_handle = router.clone();
+    match router.restore_state().await {
+        Ok(0) => {}
+        Ok(restored) => info!(
+            "💾 Restored {} routing state entries (breakers, health, rate limits)",
+            restored
+        ),
+        Err(e) => warn!("⚠️  Failed to restore routing state: {}", e),
+    }
+    router.spawn_state_sync(std::time::Duration::from_secs(
+        config.state.sync_interval_secs.max(1),
     ));
 
+    // /readyz reports the router's per-provider availability
+    let health_state = HealthState::with_readiness_checker(metrics.clone(), router.clone());
+    if config.health_probes.enabled {
+        let probes = &config.health_probes;
+        let targets: Vec<ProbeTarget> = router
+            .provider_ids()
+            .into_iter()
+            .map(|id| {
+                let settings = probes.providers.get(&id).cloned().unwrap_or_default();
+                ProbeTarget {
+                    provider: id,
+                    method: settings.method,
+                    model: settings.model,
+                }
+            })
+            .collect();
+        info!(
+            "🩺 Health probes enabled for {} provider(s) every {}s (+ up to {}s jitter)",
+            targets.len(),
+            probes.interval_secs,
+            probes.jitter_secs
+        );
+        router.spawn_health_probes(
+            targets,
+            HealthProbeConfig {
+                interval: std::time::Duration::from_secs(probes.interval_secs.max(1)),
+                jitter: std::time::Duration::from_secs(probes.jitter_secs),
+                timeout: std::time::Duration::from_secs(probes.timeout_secs.max(1)),
+            },
+        );
+    }
+
     if !is_passthrough {
         info!("✓ Router created with health monitoring and circuit breakers");
         info!("   Circuit breaker: 3 failures → open, 1 success → close");
@@ -1176,98 +785,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Create ingress router based on selected dialect
-    let api_router = match config.api_dialect {
-        ApiDialect::OpenAI => {
-            info!("📡 API dialect: OpenAI (/v1/chat/completions)");
-            if let (true, Some(connector)) = (is_openai_passthrough, openai_connector) {
-                info!("⚡ Passthrough mode: OpenAI→OpenAI (no normalization)");
-                openai::passthrough_router(
-                    connector,
-                    Some(stats_tracker_clone),
-                    Some(metrics.clone()),
-                    session_store_for_passthrough.clone(),
-                    config.http_server.sse_keepalive_interval_secs,
-                    config.http_server.sse_keepalive_enabled,
-                    Some(provider_registry.clone()),
-                )
-            } else if let Some(session_store) = session_store_for_passthrough.clone() {
-                openai::router_with_session_store(router, session_store, "openai", "openai")
-            } else {
-                openai::router(router)
-            }
-        }
-        ApiDialect::Anthropic => {
-            info!("📡 API dialect: Anthropic (/v1/messages)");
-            if is_anthropic_passthrough {
-                if let Some(connector) = anthropic_connector {
-                    info!("⚡ Passthrough mode: Anthropic→Anthropic (no normalization)");
-                    anthropic_ingress::passthrough_router(
-                        connector,
-                        Some(stats_tracker_clone),
-                        Some(metrics.clone()),
-                        session_store_for_passthrough.clone(),
-                        config.http_server.sse_keepalive_interval_secs,
-                        config.http_server.sse_keepalive_enabled,
-                        Some(provider_registry.clone()),
-                    )
-                } else {
-                    if let Some(session_store) = session_store_for_passthrough.clone() {
-                        anthropic_ingress::router_with_session_store(
-                            router,
-                            session_store,
-                            "anthropic",
-                            "anthropic",
-                        )
-                    } else {
-                        anthropic_ingress::router(router)
-                    }
-                }
-            } else if let Some(session_store) = session_store_for_passthrough.clone() {
-                anthropic_ingress::router_with_session_store(
-                    router,
-                    session_store,
-                    "anthropic",
-                    "anthropic",
-                )
-            } else {
-                anthropic_ingress::router(router)
-            }
-        }
-        ApiDialect::Both => {
-            info!("📡 API dialect: Both (OpenAI + Anthropic)");
-            info!("   - OpenAI format:   /v1/chat/completions");
-            info!("   - Anthropic format: /v1/messages");
-
-            if is_dual_passthrough {
-                info!(
-                    "⚡ Dual passthrough mode: OpenAI→OpenAI + Anthropic→Anthropic (no normalization)"
-                );
-                info!("   Routes determined by model prefix:");
-                info!("   - gpt-* models    → OpenAI provider (passthrough)");
-                info!("   - claude-* models → Anthropic provider (passthrough)");
-
-                lunaroute_ingress::multi_dialect::passthrough_router(
-                    openai_connector,
-                    anthropic_connector,
-                    Some(stats_tracker_clone),
-                    Some(metrics.clone()),
-                    session_store_for_passthrough.clone(),
-                    config.http_server.sse_keepalive_interval_secs,
-                    config.http_server.sse_keepalive_enabled,
-                    Some(provider_registry.clone()),
-                )
-            } else {
-                info!("🔄 Dual dialect with routing (normalization may occur)");
-                if let Some(session_store) = session_store_for_passthrough.clone() {
-                    lunaroute_ingress::multi_dialect::router_with_session_store(
-                        router,
-                        session_store,
-                    )
-                } else {
-                    lunaroute_ingress::multi_dialect::router(router)
-                }
-            }
+    let api_router = match &tenant_resolver {
+        // In multi-tenant mode each tenant is served by its own providers and router
+        Some(resolver) => {
+            let runtimes = Arc::new(tenants::TenantRuntimes::new(
+                resolver.clone(),
+                metrics.clone(),
+                stats_tracker_clone,
+                session_store_for_passthrough.clone(),
+                std::time::Duration::from_secs(config.tenancy.idle_timeout_secs),
+            ));
+            runtimes.spawn_eviction();
+            info!(
+                "🏢 Per-tenant providers and routers (idle tenants dropped after {}s)",
+                config.tenancy.idle_timeout_secs
+            );
+            axum::Router::new()
+                .fallback(tenants::dispatch)
+                .with_state(runtimes)
         }
+        None => providers::proxy_router(
+            &config,
+            &provider_set,
+            &passthrough,
+            router,
+            stats_tracker_clone,
+            metrics.clone(),
+            session_store_for_passthrough.clone(),
+        ),
     };
 
     // Initialize bypass functionality (if enabled)
@@ -1281,29 +826,153 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("   Bypassed paths: /v1/embeddings, /v1/audio/*, /v1/images/*, and others");
     }
 
-    // Create bypass provider from captured info
-    let bypass_provider = bypass_provider_info.map(|(name, base_url, api_key)| {
-        info!("   Bypass provider: {} ({})", name, base_url);
-        Arc::new(BypassProvider::new(
-            base_url,
-            api_key,
-            name,
-            Arc::new(reqwest::Client::new()),
-            config.http_server.max_request_body_bytes,
-        ))
-    });
+    let bypass_provider = provider_set.bypass_provider(&config);
 
     if config.bypass.enabled && bypass_provider.is_none() {
         warn!("⚠️  Bypass enabled but no valid provider configured. Bypass will be disabled.");
     }
 
-    // Wrap api_router with bypass functionality
-    let api_router = with_bypass(api_router, bypass_provider, path_classifier);
+    // Admin API (routing explain)
+    let admin_router = config.admin.enabled.then(|| {
+        let sessions_dir = config
+            .session_recording
+            .jsonl
+            .as_ref()
+            .filter(|jsonl| config.session_recording.enabled && jsonl.enabled)
+            .map(|jsonl| {
+                PathBuf::from(shellexpand::tilde(&jsonl.directory.to_string_lossy()).to_string())
+            });
+        admin::admin_router(Arc::new(admin::AdminState {
+            router: router_handle.clone(),
+            passthrough: is_passthrough,
+            api_dialect: config.api_dialect,
+            provider_registry: provider_set.registry.clone(),
+            model_aliases: provider_set.model_aliases.clone(),
+            experiments: provider_set.experiments.clone(),
+            sessions_dir,
+            token: config.admin.token.clone(),
+            api_keys: api_keys.clone(),
+            budgets: budget_tracker.clone(),
+            tenants: tenant_resolver.clone(),
+        }))
+    });
+    if admin_router.is_some() {
+        info!(
+            "🛠️  Admin API enabled: POST /admin/routing/explain{}",
+            if config.admin.token.is_some() {
+                " (token required)"
+            } else {
+                ""
+            }
+        );
+    }
+
+    // Wrap api_router with bypass functionality (tenants bypass through their own providers)
+    let api_router = if tenant_resolver.is_some() {
+        api_router
+    } else {
+        with_bypass(api_router, bypass_provider, path_classifier)
+    };
+
+    // Budgets and rate limits sit inside the API key layer so they see the key
+    let api_router = match budget_tracker.clone() {
+        Some(tracker) => {
+            info!(
+                "💰 Budgets enabled ({} configured{})",
+                config.budgets.budgets.len(),
+                if config.budgets.session_cap.is_some() {
+                    ", per-conversation cap"
+                } else {
+                    ""
+                }
+            );
+            api_router.layer(axum::middleware::from_fn_with_state(
+                tracker,
+                lunaroute_ingress::budget::budget_middleware,
+            ))
+        }
+        None => api_router,
+    };
+    let api_router = match rate_limiter {
+        Some(limiter) => {
+            info!("🚦 Ingress rate limiting enabled");
+            if !config.auth.enabled && !config.rate_limits.per_key.is_empty() {
+                warn!("⚠️  rate_limits.per_key has no effect without auth.enabled");
+            }
+            api_router.layer(axum::middleware::from_fn_with_state(
+                limiter,
+                lunaroute_ingress::rate_limit::rate_limit_middleware,
+            ))
+        }
+        None => api_router,
+    };
+
+    // Resolve tenants after authentication, so key and JWT tenants are known
+    let api_router = match &tenant_resolver {
+        Some(resolver) => {
+            info!(
+                "🏢 Resolving tenants from: {}",
+                config
+                    .tenancy
+                    .sources
+                    .iter()
+                    .map(|source| source.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            api_router.layer(axum::middleware::from_fn_with_state(
+                resolver.clone(),
+                lunaroute_ingress::tenant::tenant_middleware,
+            ))
+        }
+        None => api_router,
+    };
+
+    // Require LunaRoute API keys on every proxy route (intercepted and bypassed)
+    let api_router = match api_keys {
+        Some(keys) => {
+            info!("🔑 API key authentication enabled for all proxy routes");
+            if config.admin.token.is_none() || !config.admin.enabled {
+                warn!(
+                    "⚠️  API keys can only be managed through the admin API with admin.token set"
+                );
+            }
+            let jwt = match &config.auth.jwt {
+                Some(jwt) => {
+                    let verifier = lunaroute_ingress::jwt::JwtVerifier::new(
+                        jwt.clone(),
+                        reqwest::Client::new(),
+                    )
+                    .map_err(|e| anyhow::anyhow!("Invalid auth.jwt config: {}", e))?;
+                    info!(
+                        "🔑 Bearer JWTs from {} accepted in place of API keys",
+                        jwt.issuer
+                    );
+                    Some(Arc::new(verifier))
+                }
+                None => None,
+            };
+            api_router.layer(axum::middleware::from_fn_with_state(
+                Arc::new(lunaroute_ingress::auth::ApiKeyAuth {
+                    keys,
+                    max_body_bytes: config.http_server.max_request_body_bytes,
+                    client_certs: config.auth.client_certs,
+                    jwt,
+                }),
+                lunaroute_ingress::auth::api_key_middleware,
+            ))
+        }
+        None => api_router,
+    };
 
     // Create health/metrics router
     let health_router = health_router(health_state);
 
     // Combine routers
+    let api_router = match admin_router {
+        Some(admin) => api_router.merge(admin),
+        None => api_router,
+    };
     let app = api_router
         .merge(health_router)
         .layer(axum::extract::DefaultBodyLimit::max(
@@ -1312,7 +981,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Start server
     let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
-    let listener = TcpListener::bind(addr).await?;
+    let tls_settings = config.http_server.tls.clone();
+    if config.auth.client_certs
+        && tls_settings
+            .as_ref()
+            .is_none_or(|tls| tls.client_ca_path.is_none())
+    {
+        warn!("⚠️  auth.client_certs has no effect without http_server.tls.client_ca_path");
+    }
+    let listener = match &tls_settings {
+        Some(tls) => {
+            let server_tls = lunaroute_ingress::tls::ServerTls::load(tls)?;
+            info!("🔐 TLS enabled (certificate: {})", tls.cert_path.display());
+            if let Some(ca) = &tls.client_ca_path {
+                info!(
+                    "   Client certificates {:?}, verified against {}",
+                    tls.client_cert_mode,
+                    ca.display()
+                );
+            }
+            ProxyListener::Tls(lunaroute_ingress::tls::TlsListener::bind(addr, server_tls).await?)
+        }
+        None => ProxyListener::Plain(TcpListener::bind(addr).await?),
+    };
+    let scheme = match listener {
+        ProxyListener::Plain(_) => "http",
+        ProxyListener::Tls(_) => "https",
+    };
 
     // Apply HTTP server TCP settings
     let http_config = &config.http_server;
@@ -1344,36 +1039,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("    TCP keepalive: {}s", http_config.tcp_keepalive_secs);
 
     info!("");
-    info!("✅ LunaRoute gateway listening on http://{}", addr);
+    info!("✅ LunaRoute gateway listening on {}://{}", scheme, addr);
     info!("   API endpoints:");
     match config.api_dialect {
         ApiDialect::OpenAI => {
-            info!("   - OpenAI API: http://{}/v1/chat/completions", addr);
+            info!("   - OpenAI API: {}://{}/v1/chat/completions", scheme, addr);
         }
         ApiDialect::Anthropic => {
-            info!("   - Anthropic API: http://{}/v1/messages", addr);
+            info!("   - Anthropic API: {}://{}/v1/messages", scheme, addr);
             info!(
-                "   💡 For Claude Code: export ANTHROPIC_BASE_URL=http://{}",
-                addr
+                "   💡 For Claude Code: export ANTHROPIC_BASE_URL={}://{}",
+                scheme, addr
             );
         }
         ApiDialect::Both => {
-            info!("   - OpenAI API:      http://{}/v1/chat/completions", addr);
-            info!("   - Anthropic API:   http://{}/v1/messages", addr);
             info!(
-                "   💡 For Claude Code: export ANTHROPIC_BASE_URL=http://{}",
-                addr
+                "   - OpenAI API:      {}://{}/v1/chat/completions",
+                scheme, addr
             );
+            info!("   - Anthropic API:   {}://{}/v1/messages", scheme, addr);
             info!(
-                "   💡 For Codex:       export OPENAI_BASE_URL=http://{}",
-                addr
+                "   💡 For Claude Code: export ANTHROPIC_BASE_URL={}://{}",
+                scheme, addr
+            );
+            info!(
+                "   💡 For Codex:       export OPENAI_BASE_URL={}://{}",
+                scheme, addr
             );
         }
     }
     info!("   Observability:");
-    info!("   - Health check:       http://{}/healthz", addr);
-    info!("   - Readiness check:    http://{}/readyz", addr);
-    info!("   - Prometheus metrics: http://{}/metrics", addr);
+    info!("   - Health check:       {}://{}/healthz", scheme, addr);
+    info!("   - Readiness check:    {}://{}/readyz", scheme, addr);
+    info!("   - Prometheus metrics: {}://{}/metrics", scheme, addr);
     info!("");
 
     // Setup graceful shutdown handler
@@ -1394,9 +1092,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         if let Some(sqlite_config) = &config.session_recording.sqlite {
             let db_path = sqlite_config.path.to_string_lossy().to_string();
             let ui_config = config.ui.clone();
+            let budgets = budget_tracker.clone();
 
             tokio::spawn(async move {
-                match start_ui_server(ui_config, db_path).await {
+                match start_ui_server(ui_config, db_path, budgets).await {
                     Ok(_) => info!("UI server stopped"),
                     Err(e) => warn!("UI server error: {}", e),
                 }
@@ -1407,9 +1106,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    // Connection info gives the rate limiter the client IP (and, over TLS,
+    // the client certificate identity to auth and session recording)
+    match listener {
+        ProxyListener::Plain(listener) => {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(shutdown_signal())
+            .await?;
+        }
+        ProxyListener::Tls(listener) => {
+            let app = app.layer(axum::middleware::from_fn(
+                lunaroute_ingress::tls::tls_connect_info_middleware,
+            ));
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<lunaroute_ingress::tls::TlsConnectInfo>(),
+            )
+            .with_graceful_shutdown(shutdown_signal())
+            .await?;
+        }
+    }
+
+    // Save routing state so the next start honours open breakers and backoffs
+    if let Err(e) = router_handle.persist_state().await {
+        warn!("Failed to persist routing state during shutdown: {}", e);
+    }
 
     // Flush session events before exit to ensure all pending events are written
     if let Some(ref store) = session_store_for_passthrough {
@@ -1432,8 +1156,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Open the routing state store selected by the `state` config section
+async fn open_state_store(
+    config: &config::StateConfig,
+) -> anyhow::Result<Arc<dyn lunaroute_storage::StateStore>> {
+    match config.backend {
+        config::StateBackend::File => {
+            let path =
+                PathBuf::from(shellexpand::tilde(&config.path.to_string_lossy()).to_string());
+            let store = lunaroute_storage::FileStateStore::new(&path).await?;
+            info!("💾 Routing state file: {}", path.display());
+            Ok(Arc::new(store))
+        }
+        config::StateBackend::Postgres => {
+            let url = config.database_url.as_deref().ok_or_else(|| {
+                anyhow::anyhow!("state.database_url is required for the postgres backend")
+            })?;
+            let store = Arc::new(lunaroute_state_postgres::PostgresStateStore::new(url).await?);
+            store
+                .clone()
+                .start_auto_purge(std::time::Duration::from_secs(300));
+            info!("💾 Routing state shared via PostgreSQL");
+            Ok(store)
+        }
+    }
+}
+
+/// The proxy's bound listener
+enum ProxyListener {
+    Plain(TcpListener),
+    Tls(lunaroute_ingress::tls::TlsListener),
+}
+
 /// Start the UI server
-async fn start_ui_server(config: lunaroute_ui::UiConfig, db_path: String) -> anyhow::Result<()> {
+async fn start_ui_server(
+    config: lunaroute_ui::UiConfig,
+    db_path: String,
+    budgets: Option<Arc<lunaroute_ingress::budget::BudgetTracker>>,
+) -> anyhow::Result<()> {
     // Connect to SQLite database using SqliteConnectOptions to handle Windows paths correctly
     // (URL-based connection like "sqlite://C:\..." breaks on Windows due to backslash parsing)
     let pool = sqlx::sqlite::SqlitePoolOptions::new()
@@ -1446,14 +1206,56 @@ async fn start_ui_server(config: lunaroute_ui::UiConfig, db_path: String) -> any
         .await?;
 
     let pool = std::sync::Arc::new(pool);
+    let tls = config.tls.clone();
+    let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
 
     // Create and start UI server
-    let ui_server = lunaroute_ui::UiServer::new(config, pool);
-    ui_server.serve().await?;
+    let mut ui_server = lunaroute_ui::UiServer::new(config, pool);
+    if let Some(tracker) = budgets {
+        ui_server = ui_server.with_budgets(Arc::new(move || {
+            let tracker = tracker.clone();
+            Box::pin(async move { budget_usage(&tracker).await })
+        }));
+    }
+    match tls {
+        Some(tls) => {
+            let server_tls = lunaroute_ingress::tls::ServerTls::load(&tls)?;
+            let listener = lunaroute_ingress::tls::TlsListener::bind(addr, server_tls).await?;
+            ui_server.serve_on(listener).await?;
+        }
+        None => ui_server.serve().await?,
+    }
 
     Ok(())
 }
 
+/// Budget usage in the UI's format
+async fn budget_usage(
+    tracker: &lunaroute_ingress::budget::BudgetTracker,
+) -> Vec<lunaroute_ui::models::BudgetUsage> {
+    let status = match tracker.status().await {
+        Ok(status) => status,
+        Err(e) => {
+            warn!("Failed to read budget usage: {}", e);
+            return vec![];
+        }
+    };
+    status
+        .into_iter()
+        .map(|s| lunaroute_ui::models::BudgetUsage {
+            budget: s.budget,
+            subject: s.subject.as_str().to_string(),
+            subject_id: s.subject_id,
+            metric: s.metric.as_str().to_string(),
+            window: s.window.as_str().to_string(),
+            limit: s.limit,
+            used: s.used,
+            state: s.state.as_str().to_string(),
+            resets_at: s.resets_at.to_rfc3339(),
+        })
+        .collect()
+}
+
 /// Wait for shutdown signal (SIGINT or SIGTERM)
 async fn shutdown_signal() {
     use tokio::signal;
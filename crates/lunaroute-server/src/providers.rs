@@ -0,0 +1,897 @@
+//! Providers and routing built from a `ServerConfig`
+//!
+//! The server builds one [`ProviderSet`] from its own configuration at
+//! startup. In multi-tenant mode every tenant gets another one, built from
+//! the tenant's configuration (see [`crate::tenants`]).
+
+use crate::config::{self, ApiDialect, ServerConfig};
+use crate::session_stats::SessionStatsTracker;
+use futures::StreamExt;
+use lunaroute_core::provider::Provider;
+use lunaroute_core::{
+    error::Error as CoreError,
+    normalized::{NormalizedRequest, NormalizedResponse, NormalizedStreamEvent},
+    session_store::SessionStore,
+};
+use lunaroute_egress::{
+    KeyPool,
+    anthropic::{AnthropicConfig, AnthropicConnector},
+    openai::{OpenAIConfig, OpenAIConnector, RequestBodyModConfig, ResponseBodyModConfig},
+};
+use lunaroute_ingress::{BypassProvider, anthropic as anthropic_ingress, openai};
+use lunaroute_observability::Metrics;
+use lunaroute_routing::{ExperimentSet, ModelAliasTable, Router, RoutingRule, RuleMatcher};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Logging provider that prints all requests and responses to stdout
+struct LoggingProvider {
+    inner: Arc<dyn Provider>,
+    provider_name: String,
+}
+
+impl LoggingProvider {
+    fn new(inner: Arc<dyn Provider>, provider_name: String) -> Self {
+        Self {
+            inner,
+            provider_name,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Provider for LoggingProvider {
+    async fn send(&self, request: NormalizedRequest) -> Result<NormalizedResponse, CoreError> {
+        info!("┌─────────────────────────────────────────────────────────");
+        info!("│ REQUEST to {} (non-streaming)", self.provider_name);
+        info!("├─────────────────────────────────────────────────────────");
+        info!("│ Model: {}", request.model);
+        info!("│ Messages: {} messages", request.messages.len());
+        debug!(
+            "│ Full request:\n{}",
+            serde_json::to_string_pretty(&request)
+                .unwrap_or_else(|e| format!("Serialization error: {}", e))
+        );
+        info!("└─────────────────────────────────────────────────────────");
+
+        let response = self.inner.send(request).await?;
+
+        info!("┌─────────────────────────────────────────────────────────");
+        info!("│ RESPONSE from {} (non-streaming)", self.provider_name);
+        info!("├─────────────────────────────────────────────────────────");
+        if !response.choices.is_empty() {
+            let message = &response.choices[0].message;
+            // MessageContent is an enum, check if it's text
+            if let lunaroute_core::normalized::MessageContent::Text(text) = &message.content {
+                debug!("│ Content: {}", text);
+            }
+        }
+        info!(
+            "│ Tokens: input={}, output={}, total={}",
+            response.usage.prompt_tokens,
+            response.usage.completion_tokens,
+            response.usage.prompt_tokens + response.usage.completion_tokens
+        );
+        debug!(
+            "│ Full response:\n{}",
+            serde_json::to_string_pretty(&response)
+                .unwrap_or_else(|e| format!("Serialization error: {}", e))
+        );
+        info!("└─────────────────────────────────────────────────────────");
+
+        Ok(response)
+    }
+
+    async fn stream(
+        &self,
+        request: NormalizedRequest,
+    ) -> Result<
+        Box<dyn futures::Stream<Item = Result<NormalizedStreamEvent, CoreError>> + Send + Unpin>,
+        CoreError,
+    > {
+        info!("┌─────────────────────────────────────────────────────────");
+        info!("│ REQUEST to {} (streaming)", self.provider_name);
+        info!("├─────────────────────────────────────────────────────────");
+        info!("│ Model: {}", request.model);
+        info!("│ Messages: {} messages", request.messages.len());
+        debug!(
+            "│ Full request:\n{}",
+            serde_json::to_string_pretty(&request)
+                .unwrap_or_else(|e| format!("Serialization error: {}", e))
+        );
+        info!("└─────────────────────────────────────────────────────────");
+
+        let stream = self.inner.stream(request).await?;
+        let provider_name = self.provider_name.clone();
+
+        info!("┌─────────────────────────────────────────────────────────");
+        info!("│ STREAMING from {}", provider_name);
+        info!("└─────────────────────────────────────────────────────────");
+
+        // Wrap stream to log each event
+        let logged_stream = stream.map(move |event| {
+            if let Ok(ref evt) = event {
+                match evt {
+                    NormalizedStreamEvent::Start { .. } => {
+                        debug!("│ 🟢 Stream started");
+                    }
+                    NormalizedStreamEvent::Delta { delta, .. } => {
+                        if let Some(ref content) = delta.content {
+                            debug!("│ 📝 {}", content);
+                        }
+                    }
+                    NormalizedStreamEvent::ToolCallDelta { function, .. } => {
+                        if let Some(func) = function {
+                            if let Some(name) = &func.name {
+                                debug!("│ 🔧 Tool call: {}", name);
+                            }
+                            if let Some(args) = &func.arguments {
+                                debug!("│ 🔧 Tool args delta: {}", args);
+                            }
+                        }
+                    }
+                    NormalizedStreamEvent::Usage { usage } => {
+                        info!(
+                            "│ 📊 Usage: input={}, output={}, total={}",
+                            usage.prompt_tokens,
+                            usage.completion_tokens,
+                            usage.prompt_tokens + usage.completion_tokens
+                        );
+                    }
+                    NormalizedStreamEvent::End { finish_reason } => {
+                        info!("│ 🏁 Stream ended: {:?}", finish_reason);
+                    }
+                    NormalizedStreamEvent::Error { error } => {
+                        warn!("│ ❌ Stream error: {}", error);
+                    }
+                }
+            }
+            event
+        });
+
+        Ok(Box::new(logged_stream))
+    }
+
+    fn capabilities(&self) -> lunaroute_core::provider::ProviderCapabilities {
+        self.inner.capabilities()
+    }
+}
+
+/// Connectors and routing tables for one configuration
+pub(crate) struct ProviderSet {
+    /// Providers available to the router, by name
+    pub providers: HashMap<String, Arc<dyn Provider>>,
+    /// Raw connectors for passthrough mode
+    pub openai_connector: Option<Arc<OpenAIConnector>>,
+    pub anthropic_connector: Option<Arc<AnthropicConnector>>,
+    /// Providers addressable by LUNAROUTE markers
+    pub registry: Arc<lunaroute_ingress::ProviderRegistry>,
+    /// Upstream key pools
+    pub key_pools: Vec<Arc<KeyPool>>,
+    pub model_aliases: Option<Arc<ModelAliasTable>>,
+    pub experiments: Option<Arc<ExperimentSet>>,
+}
+
+/// Passthrough modes a dialect allows for a provider set
+pub(crate) struct Passthrough {
+    /// Anthropic dialect served by the only provider, Anthropic
+    pub anthropic: bool,
+    /// OpenAI dialect served by the only provider, OpenAI
+    pub openai: bool,
+    /// Both dialects, each passed through to its own provider
+    pub dual: bool,
+}
+
+impl Passthrough {
+    pub(crate) fn any(&self) -> bool {
+        self.anthropic || self.openai || self.dual
+    }
+}
+
+impl ProviderSet {
+    /// Build providers, the marker-routing registry, model aliases and experiments
+    pub(crate) async fn build(config: &ServerConfig) -> anyhow::Result<Self> {
+        // Setup providers
+        let mut providers: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+
+        // Store raw connectors for passthrough mode
+        let mut anthropic_connector: Option<Arc<AnthropicConnector>> = None;
+        let mut openai_connector: Option<Arc<OpenAIConnector>> = None;
+
+        // Upstream key pools, wired to quarantine metrics once those exist
+        let mut key_pools: Vec<Arc<KeyPool>> = Vec::new();
+
+        // OpenAI provider
+        if let Some(openai_config) = &config.providers.openai
+            && openai_config.enabled
+        {
+            // Get API key (empty string if not configured - will use client's header)
+            let api_key = openai_config.api_key.clone().unwrap_or_default();
+
+            if api_key.is_empty()
+                && openai_config.api_keys.is_empty()
+                && !openai_config.key_source.is_set()
+            {
+                info!("✓ OpenAI provider enabled (no API key - will use client auth)");
+            } else {
+                info!("✓ OpenAI provider enabled");
+            }
+
+            let base_url = openai_config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
+
+            // Use http_client config from YAML if provided, otherwise use defaults
+            let client_config = openai_config
+                .http_client
+                .as_ref()
+                .map(|c| c.to_http_client_config())
+                .unwrap_or_default();
+
+            let mut provider_config = OpenAIConfig {
+                api_key: api_key.clone(),
+                base_url,
+                organization: None,
+                client_config,
+                custom_headers: None,
+                request_body_config: None,
+                response_body_config: None,
+                codex_auth: openai_config.codex_auth.as_ref().map(|c| {
+                    let defaults = lunaroute_egress::openai::CodexAuthConfig::default();
+                    lunaroute_egress::openai::CodexAuthConfig {
+                        enabled: c.enabled,
+                        auth_file: c.auth_file.clone(),
+                        token_field: c.token_field.clone(),
+                        account_id: c.account_id.clone(),
+                        auto_refresh: c.auto_refresh,
+                        token_endpoint: c.token_endpoint.clone().unwrap_or(defaults.token_endpoint),
+                        client_id: c.client_id.clone().unwrap_or(defaults.client_id),
+                        refresh_before_expiry: c
+                            .refresh_before_expiry_secs
+                            .map(std::time::Duration::from_secs)
+                            .unwrap_or(defaults.refresh_before_expiry),
+                    }
+                }),
+                switch_notification_message: None,
+            };
+
+            // Wire custom headers and body modifications
+            if let Some(headers_config) = &openai_config.request_headers {
+                provider_config.custom_headers = Some(headers_config.headers.clone());
+            }
+            if let Some(request_body) = &openai_config.request_body {
+                provider_config.request_body_config = Some(RequestBodyModConfig {
+                    defaults: request_body.defaults.clone(),
+                    overrides: request_body.overrides.clone(),
+                    prepend_messages: request_body.prepend_messages.clone(),
+                });
+            }
+            if let Some(response_body) = &openai_config.response_body {
+                provider_config.response_body_config = Some(ResponseBodyModConfig {
+                    enabled: response_body.enabled,
+                    metadata_namespace: response_body.metadata_namespace.clone(),
+                    fields: response_body.fields.clone(),
+                    extension_fields: response_body.extension_fields.clone(),
+                });
+            }
+
+            let mut conn = OpenAIConnector::new(provider_config).await?;
+            if let Some(pool) = provider_key_pool("openai", openai_config, &mut key_pools) {
+                conn = conn.with_key_pool(pool);
+            }
+
+            // Build the provider stack (order matters!)
+            // 1. Start with connector
+            // 2. Wrap with session recording if enabled
+            // 3. Wrap with logging if enabled
+            let connector = Arc::new(conn);
+            openai_connector = Some(connector.clone()); // Save for passthrough
+            // Session recording is now handled via async multi-writer in passthrough mode
+            let provider: Arc<dyn Provider> = if config.logging.log_requests {
+                info!("  Request/response logging: enabled");
+                Arc::new(LoggingProvider::new(
+                    connector.clone(),
+                    "OpenAI".to_string(),
+                ))
+            } else {
+                connector
+            };
+
+            providers.insert("openai".to_string(), provider);
+        }
+
+        // Anthropic provider
+        if let Some(anthropic_config) = &config.providers.anthropic
+            && anthropic_config.enabled
+        {
+            // Get API key (empty string if not configured - will use client's header)
+            let api_key = anthropic_config.api_key.clone().unwrap_or_default();
+
+            if api_key.is_empty()
+                && anthropic_config.api_keys.is_empty()
+                && !anthropic_config.key_source.is_set()
+                && !anthropic_config
+                    .claude_oauth
+                    .as_ref()
+                    .is_some_and(|c| c.enabled)
+            {
+                info!("✓ Anthropic provider enabled (no API key - will use client auth)");
+            } else {
+                info!("✓ Anthropic provider enabled");
+            }
+
+            let base_url = anthropic_config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| "https://api.anthropic.com".to_string());
+
+            // Use http_client config from YAML if provided, otherwise use defaults
+            let client_config = anthropic_config
+                .http_client
+                .as_ref()
+                .map(|c| c.to_http_client_config())
+                .unwrap_or_default();
+
+            let provider_config = AnthropicConfig {
+                api_key: api_key.clone(),
+                base_url,
+                api_version: "2023-06-01".to_string(),
+                client_config,
+                switch_notification_message: None,
+            };
+            let mut conn = AnthropicConnector::new(provider_config)?;
+            if let Some(pool) = provider_key_pool("anthropic", anthropic_config, &mut key_pools) {
+                conn = conn.with_key_pool(pool);
+            }
+
+            // Build the provider stack (order matters!)
+            // Session recording is now handled via async multi-writer in passthrough mode
+            let connector = Arc::new(conn);
+            anthropic_connector = Some(connector.clone()); // Save for passthrough
+            let provider: Arc<dyn Provider> = if config.logging.log_requests {
+                info!("  Request/response logging: enabled");
+                Arc::new(LoggingProvider::new(
+                    connector.clone(),
+                    "Anthropic".to_string(),
+                ))
+            } else {
+                connector
+            };
+
+            providers.insert("anthropic".to_string(), provider);
+        }
+
+        // Build ProviderRegistry for marker-based routing
+        let mut provider_registry = lunaroute_ingress::ProviderRegistry::new();
+
+        // Add built-in providers
+        if let Some(ref connector) = openai_connector {
+            provider_registry.insert(
+                "openai".to_string(),
+                lunaroute_ingress::ProviderEntry {
+                    connector_type: lunaroute_ingress::ProviderType::OpenAI,
+                    openai_connector: Some(connector.clone()),
+                    anthropic_connector: None,
+                    model_override: config
+                        .providers
+                        .openai
+                        .as_ref()
+                        .and_then(|p| p.model.clone()),
+                },
+            );
+        }
+        if let Some(ref connector) = anthropic_connector {
+            provider_registry.insert(
+                "anthropic".to_string(),
+                lunaroute_ingress::ProviderEntry {
+                    connector_type: lunaroute_ingress::ProviderType::Anthropic,
+                    openai_connector: None,
+                    anthropic_connector: Some(connector.clone()),
+                    model_override: config
+                        .providers
+                        .anthropic
+                        .as_ref()
+                        .and_then(|p| p.model.clone()),
+                },
+            );
+        }
+
+        // Validate and build extra providers
+        config
+            .providers
+            .validate_extra_providers()
+            .map_err(|e| anyhow::anyhow!("Invalid provider config: {}", e))?;
+
+        for (name, settings) in &config.providers.extra {
+            if !settings.enabled {
+                info!("  Extra provider '{}': disabled, skipping", name);
+                continue;
+            }
+
+            let provider_type_str = settings.provider_type.as_deref().unwrap(); // validated above
+            let api_key = settings.api_key.clone().unwrap_or_default();
+
+            match provider_type_str {
+                "anthropic" => {
+                    let base_url = settings
+                        .base_url
+                        .clone()
+                        .unwrap_or_else(|| "https://api.anthropic.com".to_string());
+                    let client_config = settings
+                        .http_client
+                        .as_ref()
+                        .map(|c| c.to_http_client_config())
+                        .unwrap_or_default();
+                    let connector_config = lunaroute_egress::anthropic::AnthropicConfig {
+                        api_key,
+                        base_url,
+                        api_version: "2023-06-01".to_string(),
+                        client_config,
+                        switch_notification_message: None,
+                    };
+                    let mut conn =
+                        lunaroute_egress::anthropic::AnthropicConnector::new(connector_config)?;
+                    if let Some(pool) = provider_key_pool(name, settings, &mut key_pools) {
+                        conn = conn.with_key_pool(pool);
+                    }
+                    info!(
+                        "  Extra provider '{}': anthropic, model_override={:?}",
+                        name, settings.model
+                    );
+                    provider_registry.insert(
+                        name.clone(),
+                        lunaroute_ingress::ProviderEntry {
+                            connector_type: lunaroute_ingress::ProviderType::Anthropic,
+                            openai_connector: None,
+                            anthropic_connector: Some(Arc::new(conn)),
+                            model_override: settings.model.clone(),
+                        },
+                    );
+                }
+                "openai" => {
+                    let base_url = settings
+                        .base_url
+                        .clone()
+                        .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
+                    let client_config = settings
+                        .http_client
+                        .as_ref()
+                        .map(|c| c.to_http_client_config())
+                        .unwrap_or_default();
+                    let mut connector_config = lunaroute_egress::openai::OpenAIConfig {
+                        api_key,
+                        base_url,
+                        organization: None,
+                        client_config,
+                        custom_headers: None,
+                        request_body_config: None,
+                        response_body_config: None,
+                        codex_auth: None,
+                        switch_notification_message: None,
+                    };
+                    if let Some(headers_config) = &settings.request_headers {
+                        connector_config.custom_headers = Some(headers_config.headers.clone());
+                    }
+                    let mut conn =
+                        lunaroute_egress::openai::OpenAIConnector::new(connector_config).await?;
+                    if let Some(pool) = provider_key_pool(name, settings, &mut key_pools) {
+                        conn = conn.with_key_pool(pool);
+                    }
+                    info!(
+                        "  Extra provider '{}': openai, model_override={:?}",
+                        name, settings.model
+                    );
+                    provider_registry.insert(
+                        name.clone(),
+                        lunaroute_ingress::ProviderEntry {
+                            connector_type: lunaroute_ingress::ProviderType::OpenAI,
+                            openai_connector: Some(Arc::new(conn)),
+                            anthropic_connector: None,
+                            model_override: settings.model.clone(),
+                        },
+                    );
+                }
+                _ => unreachable!(), // validated above
+            }
+        }
+
+        // Build model alias table (virtual model names and retired-model rewrites)
+        let model_alias_table = config
+            .routing
+            .model_alias_table()
+            .map_err(|e| anyhow::anyhow!("Invalid model alias config: {}", e))?
+            .map(Arc::new);
+        if let Some(table) = &model_alias_table {
+            for (name, alias) in table.aliases() {
+                for target in &alias.targets {
+                    if !provider_registry.contains_key(&target.provider) {
+                        warn!(
+                            "Model alias '{}' targets unknown provider '{}'",
+                            name, target.provider
+                        );
+                    }
+                }
+            }
+            info!(
+                "📋 Model aliases: {} aliases, {} rewrites",
+                config.routing.model_aliases.len(),
+                config.routing.model_rewrites.len()
+            );
+        }
+
+        // Build A/B experiments
+        let experiment_set = config
+            .routing
+            .experiment_set()
+            .map_err(|e| anyhow::anyhow!("Invalid experiment config: {}", e))?
+            .map(Arc::new);
+        if let Some(set) = &experiment_set {
+            for experiment in set.experiments() {
+                for arm in &experiment.arms {
+                    if let Some(provider) = &arm.provider
+                        && !provider_registry.contains_key(provider)
+                    {
+                        warn!(
+                            "Experiment '{}' arm '{}' targets unknown provider '{}'",
+                            experiment.name, arm.id, provider
+                        );
+                    }
+                }
+                info!(
+                    "🧪 Experiment '{}': {} arms{}",
+                    experiment.name,
+                    experiment.arms.len(),
+                    if experiment.enabled {
+                        ""
+                    } else {
+                        " (disabled)"
+                    }
+                );
+            }
+        }
+
+        let provider_registry = Arc::new(provider_registry);
+        if !provider_registry.is_empty() {
+            info!(
+                "📋 Provider registry: {} providers ({} for marker routing)",
+                provider_registry.len(),
+                config.providers.extra.len()
+            );
+        }
+
+        // Allow starting without providers in certain scenarios (e.g., passthrough with client-provided keys)
+        if providers.is_empty() {
+            warn!("⚠️  No providers configured - requests will fail unless using passthrough mode");
+            warn!("    To configure providers, either:");
+            warn!("    - Set OPENAI_API_KEY or ANTHROPIC_API_KEY environment variables, or");
+            warn!("    - Add api_key field to provider configuration in config file");
+        }
+
+        Ok(Self {
+            providers,
+            openai_connector,
+            anthropic_connector,
+            registry: provider_registry,
+            key_pools,
+            model_aliases: model_alias_table,
+            experiments: experiment_set,
+        })
+    }
+
+    /// Default routing rules: gpt-* to OpenAI, claude-* to Anthropic, each
+    /// falling back to the other, and a catch-all
+    pub(crate) fn default_rules(&self) -> Vec<RoutingRule> {
+        // Create routing rules
+        let mut rules = vec![];
+
+        // Route GPT models to OpenAI with Anthropic fallback
+        if self.providers.contains_key("openai") {
+            rules.push(RoutingRule {
+                priority: 10,
+                name: Some("gpt-to-openai".to_string()),
+                matcher: RuleMatcher::model_pattern("^gpt-.*"),
+                strategy: None,
+                primary: Some("openai".to_string()),
+                fallbacks: if self.providers.contains_key("anthropic") {
+                    vec!["anthropic".to_string()]
+                } else {
+                    vec![]
+                },
+                on_error: Default::default(),
+                schedule: None,
+            });
+        }
+
+        // Route Claude models to Anthropic with OpenAI fallback
+        if self.providers.contains_key("anthropic") {
+            rules.push(RoutingRule {
+                priority: 10,
+                name: Some("claude-to-anthropic".to_string()),
+                matcher: RuleMatcher::model_pattern("^claude-.*"),
+                strategy: None,
+                primary: Some("anthropic".to_string()),
+                fallbacks: if self.providers.contains_key("openai") {
+                    vec!["openai".to_string()]
+                } else {
+                    vec![]
+                },
+                on_error: Default::default(),
+                schedule: None,
+            });
+        }
+
+        // Default fallback route (catches all)
+        rules.push(RoutingRule {
+            priority: 1,
+            name: Some("default-route".to_string()),
+            matcher: RuleMatcher::Always,
+            strategy: None,
+            primary: Some(if self.providers.contains_key("openai") {
+                "openai".to_string()
+            } else {
+                "anthropic".to_string()
+            }),
+            fallbacks: vec![],
+            on_error: Default::default(),
+            schedule: None,
+        });
+        rules
+    }
+
+    /// Detect passthrough mode: the dialect matches the only enabled provider
+    ///
+    /// This skips normalization for optimal performance and 100% API fidelity.
+    pub(crate) fn passthrough(&self, dialect: ApiDialect) -> Passthrough {
+        let only = |name: &str| self.providers.len() == 1 && self.providers.contains_key(name);
+        Passthrough {
+            anthropic: dialect == ApiDialect::Anthropic
+                && self.anthropic_connector.is_some()
+                && only("anthropic"),
+            openai: dialect == ApiDialect::OpenAI
+                && self.openai_connector.is_some()
+                && only("openai"),
+            // For dual-dialect mode, enable passthrough if BOTH connectors are available
+            // This allows OpenAI→OpenAI and Anthropic→Anthropic passthrough simultaneously
+            dual: dialect == ApiDialect::Both
+                && self.openai_connector.is_some()
+                && self.anthropic_connector.is_some(),
+        }
+    }
+
+    /// Provider for bypassed paths, when bypass is enabled
+    pub(crate) fn bypass_provider(&self, config: &ServerConfig) -> Option<Arc<BypassProvider>> {
+        let bypass_provider_info = if config.bypass.enabled {
+            // Determine which provider to use for bypass
+            let provider_name = if let Some(name) = &config.bypass.provider {
+                Some(name.as_str())
+            } else if self.openai_connector.is_some() {
+                Some("openai")
+            } else if self.anthropic_connector.is_some() {
+                Some("anthropic")
+            } else {
+                None
+            };
+
+            match provider_name {
+                Some("openai") if self.openai_connector.is_some() => {
+                    // Use base domain without /v1 path for bypass, since request paths
+                    // already include the full path (e.g., /v1/embeddings)
+                    let base_url = config
+                        .providers
+                        .openai
+                        .as_ref()
+                        .and_then(|p| p.base_url.clone())
+                        .unwrap_or_else(|| "https://api.openai.com".to_string());
+                    // Strip trailing /v1 if present to avoid double-pathing (/v1/v1/...)
+                    let base_url = base_url
+                        .trim_end_matches('/')
+                        .trim_end_matches("/v1")
+                        .to_string();
+                    let api_key = config
+                        .providers
+                        .openai
+                        .as_ref()
+                        .and_then(|p| p.api_key.clone())
+                        .unwrap_or_default();
+
+                    Some(("openai".to_string(), base_url, api_key))
+                }
+                Some("anthropic") if self.anthropic_connector.is_some() => {
+                    // Use base domain without /v1 path for bypass, since request paths
+                    // already include the full path (e.g., /v1/messages/count_tokens)
+                    let base_url = config
+                        .providers
+                        .anthropic
+                        .as_ref()
+                        .and_then(|p| p.base_url.clone())
+                        .unwrap_or_else(|| "https://api.anthropic.com".to_string());
+                    // Strip trailing /v1 if present to avoid double-pathing (/v1/v1/...)
+                    let base_url = base_url
+                        .trim_end_matches('/')
+                        .trim_end_matches("/v1")
+                        .to_string();
+                    let api_key = config
+                        .providers
+                        .anthropic
+                        .as_ref()
+                        .and_then(|p| p.api_key.clone())
+                        .unwrap_or_default();
+
+                    Some(("anthropic".to_string(), base_url, api_key))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        // Create bypass provider from captured info
+        bypass_provider_info.map(|(name, base_url, api_key)| {
+            info!("   Bypass provider: {} ({})", name, base_url);
+            Arc::new(BypassProvider::new(
+                base_url,
+                api_key,
+                name,
+                Arc::new(reqwest::Client::new()),
+                config.http_server.max_request_body_bytes,
+            ))
+        })
+    }
+
+    /// Count upstream key quarantines in `metrics`
+    pub(crate) fn report_quarantines(&self, metrics: &Arc<Metrics>) {
+        for pool in &self.key_pools {
+            let metrics = metrics.clone();
+            pool.on_quarantine(Arc::new(move |provider, key| {
+                metrics.record_upstream_key_quarantine(provider, key)
+            }));
+        }
+    }
+}
+
+/// Ingress routes for the configured dialect, exposing the model alias
+/// table, experiments and sticky markers to the handlers
+pub(crate) fn proxy_router(
+    config: &ServerConfig,
+    set: &ProviderSet,
+    passthrough: &Passthrough,
+    router: Arc<Router>,
+    stats_tracker: Arc<SessionStatsTracker>,
+    metrics: Arc<Metrics>,
+    session_store: Option<Arc<dyn SessionStore>>,
+) -> axum::Router {
+    // Create ingress router based on selected dialect
+    let api_router = match config.api_dialect {
+        ApiDialect::OpenAI => {
+            info!("📡 API dialect: OpenAI (/v1/chat/completions)");
+            if let (true, Some(connector)) = (passthrough.openai, set.openai_connector.clone()) {
+                info!("⚡ Passthrough mode: OpenAI→OpenAI (no normalization)");
+                openai::passthrough_router(
+                    connector,
+                    Some(stats_tracker),
+                    Some(metrics.clone()),
+                    session_store.clone(),
+                    config.http_server.sse_keepalive_interval_secs,
+                    config.http_server.sse_keepalive_enabled,
+                    Some(set.registry.clone()),
+                )
+            } else if let Some(session_store) = session_store.clone() {
+                openai::router_with_session_store(router, session_store, "openai", "openai")
+            } else {
+                openai::router(router)
+            }
+        }
+        ApiDialect::Anthropic => {
+            info!("📡 API dialect: Anthropic (/v1/messages)");
+            if passthrough.anthropic {
+                if let Some(connector) = set.anthropic_connector.clone() {
+                    info!("⚡ Passthrough mode: Anthropic→Anthropic (no normalization)");
+                    anthropic_ingress::passthrough_router(
+                        connector,
+                        Some(stats_tracker),
+                        Some(metrics.clone()),
+                        session_store.clone(),
+                        config.http_server.sse_keepalive_interval_secs,
+                        config.http_server.sse_keepalive_enabled,
+                        Some(set.registry.clone()),
+                    )
+                } else {
+                    if let Some(session_store) = session_store.clone() {
+                        anthropic_ingress::router_with_session_store(
+                            router,
+                            session_store,
+                            "anthropic",
+                            "anthropic",
+                        )
+                    } else {
+                        anthropic_ingress::router(router)
+                    }
+                }
+            } else if let Some(session_store) = session_store.clone() {
+                anthropic_ingress::router_with_session_store(
+                    router,
+                    session_store,
+                    "anthropic",
+                    "anthropic",
+                )
+            } else {
+                anthropic_ingress::router(router)
+            }
+        }
+        ApiDialect::Both => {
+            info!("📡 API dialect: Both (OpenAI + Anthropic)");
+            info!("   - OpenAI format:   /v1/chat/completions");
+            info!("   - Anthropic format: /v1/messages");
+
+            if passthrough.dual {
+                info!(
+                    "⚡ Dual passthrough mode: OpenAI→OpenAI + Anthropic→Anthropic (no normalization)"
+                );
+                info!("   Routes determined by model prefix:");
+                info!("   - gpt-* models    → OpenAI provider (passthrough)");
+                info!("   - claude-* models → Anthropic provider (passthrough)");
+
+                lunaroute_ingress::multi_dialect::passthrough_router(
+                    set.openai_connector.clone(),
+                    set.anthropic_connector.clone(),
+                    Some(stats_tracker),
+                    Some(metrics.clone()),
+                    session_store.clone(),
+                    config.http_server.sse_keepalive_interval_secs,
+                    config.http_server.sse_keepalive_enabled,
+                    Some(set.registry.clone()),
+                )
+            } else {
+                info!("🔄 Dual dialect with routing (normalization may occur)");
+                if let Some(session_store) = session_store.clone() {
+                    lunaroute_ingress::multi_dialect::router_with_session_store(
+                        router,
+                        session_store,
+                    )
+                } else {
+                    lunaroute_ingress::multi_dialect::router(router)
+                }
+            }
+        }
+    };
+    // Expose the model alias table to ingress handlers (alias resolution, /v1/models)
+    let api_router = match set.model_aliases.clone() {
+        Some(table) => api_router.layer(axum::Extension(table)),
+        None => api_router,
+    };
+    let api_router = match set.experiments.clone() {
+        Some(experiments) => api_router.layer(axum::Extension(experiments)),
+        None => api_router,
+    };
+    if config.routing.markers.sticky {
+        info!(
+            "📌 Sticky LUNAROUTE markers enabled (ttl {}s)",
+            config.routing.markers.sticky_ttl_secs
+        );
+        api_router.layer(axum::Extension(Arc::new(
+            lunaroute_ingress::marker::StickyMarkers::new(std::time::Duration::from_secs(
+                config.routing.markers.sticky_ttl_secs,
+            )),
+        )))
+    } else {
+        api_router
+    }
+}
+
+/// Build the upstream key pool for a provider configured with `api_keys`
+fn provider_key_pool(
+    name: &str,
+    settings: &config::ProviderSettings,
+    pools: &mut Vec<Arc<KeyPool>>,
+) -> Option<Arc<KeyPool>> {
+    let pool = Arc::new(settings.key_pool(name)?);
+    info!(
+        "  Upstream key pool: {} keys ({:?})",
+        pool.len(),
+        settings.key_selection
+    );
+    pools.push(pool.clone());
+    Some(pool)
+}
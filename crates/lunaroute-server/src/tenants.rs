@@ -0,0 +1,564 @@
+//! Per-tenant providers and routers (multi-tenant mode)
+//!
+//! Every tenant gets its own [`ProviderSet`], route table and [`Router`],
+//! and with them its own health and circuit breaker state. They are built
+//! from the tenant's configuration on its first request and rebuilt when the
+//! config store reports a change; a failed rebuild keeps the previous set.
+//! One change stream follows every tenant, so the store needs a single
+//! listener however many tenants are cached.
+//! Tenants without requests for `tenancy.idle_timeout_secs` are dropped and
+//! rebuilt on their next request.
+
+use crate::config::ServerConfig;
+use crate::providers::{self, ProviderSet};
+use crate::session_stats::SessionStatsTracker;
+use axum::{
+    extract::{Request, State},
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt;
+use lunaroute_core::config_store::ConfigStore;
+use lunaroute_core::session_store::SessionStore;
+use lunaroute_core::tenant::{TenantContext, TenantId};
+use lunaroute_ingress::tenant::TenantResolver;
+use lunaroute_ingress::{IngressError, with_bypass};
+use lunaroute_observability::Metrics;
+use lunaroute_routing::{PathClassifier, RouteTable, Router};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tower::ServiceExt;
+use tracing::{debug, info, warn};
+
+/// A tenant's router and the ingress routes serving it
+pub(crate) struct TenantRuntime {
+    /// Routes requests across the tenant's providers
+    router: Arc<Router>,
+    api: axum::Router,
+}
+
+struct Entry {
+    runtime: Arc<TenantRuntime>,
+    last_used: Instant,
+}
+
+/// Builds, caches and rebuilds tenant runtimes
+pub(crate) struct TenantRuntimes {
+    resolver: Arc<TenantResolver>,
+    config_store: Arc<dyn ConfigStore>,
+    metrics: Arc<Metrics>,
+    stats_tracker: Arc<SessionStatsTracker>,
+    session_store: Option<Arc<dyn SessionStore>>,
+    idle_timeout: Duration,
+    entries: Mutex<HashMap<TenantId, Entry>>,
+    /// Follows config changes for all tenants while any runtime is cached
+    watcher: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Drop for TenantRuntimes {
+    fn drop(&mut self) {
+        if let Some(watcher) = self.watcher.get_mut().unwrap().take() {
+            watcher.abort();
+        }
+    }
+}
+
+impl TenantRuntimes {
+    pub(crate) fn new(
+        resolver: Arc<TenantResolver>,
+        metrics: Arc<Metrics>,
+        stats_tracker: Arc<SessionStatsTracker>,
+        session_store: Option<Arc<dyn SessionStore>>,
+        idle_timeout: Duration,
+    ) -> Self {
+        Self {
+            config_store: resolver.config_store().clone(),
+            resolver,
+            metrics,
+            stats_tracker,
+            session_store,
+            idle_timeout,
+            entries: Mutex::new(HashMap::new()),
+            watcher: Mutex::new(None),
+        }
+    }
+
+    /// Runtime for the tenant of `context`, building it on first use
+    pub(crate) async fn get(
+        self: &Arc<Self>,
+        context: &TenantContext,
+    ) -> anyhow::Result<Arc<TenantRuntime>> {
+        let tenant_id = context
+            .tenant_id
+            .ok_or_else(|| anyhow::anyhow!("request has no tenant"))?;
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&tenant_id) {
+            entry.last_used = Instant::now();
+            return Ok(entry.runtime.clone());
+        }
+
+        let runtime = Arc::new(self.build(tenant_id, &context.config).await?);
+        info!(
+            "🏢 Built providers and router for tenant {} ({})",
+            tenant_id,
+            runtime.router.provider_ids().join(", ")
+        );
+        self.ensure_watching();
+        // A concurrent first request may have won; keep its runtime
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.entry(tenant_id).or_insert_with(|| Entry {
+            runtime,
+            last_used: Instant::now(),
+        });
+        Ok(entry.runtime.clone())
+    }
+
+    /// Build a runtime from a tenant's stored configuration
+    async fn build(
+        &self,
+        tenant_id: TenantId,
+        config: &serde_json::Value,
+    ) -> anyhow::Result<TenantRuntime> {
+        let config: ServerConfig = serde_json::from_value(config.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to parse tenant config: {}", e))?;
+        // Configs written before the admin API checked this are refused here
+        config
+            .providers
+            .validate_tenant_providers(Some(tenant_id))
+            .map_err(|e| anyhow::anyhow!("Tenant config: {}", e))?;
+        // Opening the vault derives its key; keep that off the runtime threads
+        let config = tokio::task::spawn_blocking(move || {
+            let mut config = config;
+            config.resolve_vault_refs().map(|()| config)
+        })
+        .await?
+        .map_err(|e| anyhow::anyhow!("Tenant config: {}", e))?;
+        let set = ProviderSet::build(&config).await?;
+        set.report_quarantines(&self.metrics);
+
+        // Routing state is not persisted: the state store is keyed by
+        // provider name, which tenants share
+        let mut router = Router::new(
+            RouteTable::with_rules(set.default_rules()),
+            set.providers.clone(),
+            lunaroute_routing::HealthMonitorConfig::default(),
+            lunaroute_routing::CircuitBreakerConfig::default(),
+            Some(self.metrics.clone()),
+            config.routing.provider_switch_notification.clone(),
+        );
+        if let Some(table) = &set.model_aliases {
+            router = router.with_model_aliases(table.clone());
+        }
+        if let Some(queue) = config.routing.rate_limit_queue.queue_config() {
+            router = router.with_rate_limit_queue(queue);
+        }
+        let router = Arc::new(router);
+
+        let api = providers::proxy_router(
+            &config,
+            &set,
+            &set.passthrough(config.api_dialect),
+            router.clone(),
+            self.stats_tracker.clone(),
+            self.metrics.clone(),
+            self.session_store.clone(),
+        );
+        let api = with_bypass(
+            api,
+            set.bypass_provider(&config),
+            Arc::new(PathClassifier::new(config.bypass.enabled)),
+        );
+        Ok(TenantRuntime { router, api })
+    }
+
+    /// Rebuild a tenant's runtime from its latest configuration
+    async fn reload(&self, tenant_id: TenantId, version: u32) {
+        self.resolver.invalidate(tenant_id);
+        let runtime = match self.resolver.context(tenant_id).await {
+            Ok(context) => self.build(tenant_id, &context.config).await,
+            Err(e) => Err(e.into()),
+        };
+        match runtime {
+            Ok(runtime) => {
+                if let Some(entry) = self.entries.lock().unwrap().get_mut(&tenant_id) {
+                    entry.runtime = Arc::new(runtime);
+                    info!(
+                        "🔄 Rebuilt providers and router for tenant {} (config version {}: {})",
+                        tenant_id,
+                        version,
+                        entry.runtime.router.provider_ids().join(", ")
+                    );
+                }
+            }
+            Err(e) => warn!(
+                "⚠️  Keeping previous config for tenant {} (version {} failed): {}",
+                tenant_id, version, e
+            ),
+        }
+    }
+
+    /// Start the shared config change watcher unless it is running
+    fn ensure_watching(self: &Arc<Self>) {
+        let mut watcher = self.watcher.lock().unwrap();
+        if watcher.as_ref().is_none_or(|w| w.is_finished()) {
+            *watcher = Some(self.watch());
+        }
+    }
+
+    /// Follow config changes for all tenants, reloading cached runtimes
+    fn watch(self: &Arc<Self>) -> JoinHandle<()> {
+        let runtimes: Weak<Self> = Arc::downgrade(self);
+        let config_store = self.config_store.clone();
+        tokio::spawn(async move {
+            let mut changes = match config_store.watch_changes(None).await {
+                Ok(changes) => changes,
+                Err(e) => {
+                    warn!("⚠️  Tenant config changes will not be picked up: {}", e);
+                    return;
+                }
+            };
+            while let Some(change) = changes.next().await {
+                let Some(runtimes) = runtimes.upgrade() else {
+                    return;
+                };
+                match change {
+                    Ok(change) => {
+                        let Some(tenant_id) = change.tenant_id else {
+                            continue;
+                        };
+                        let cached = runtimes.entries.lock().unwrap().contains_key(&tenant_id);
+                        if cached {
+                            runtimes.reload(tenant_id, change.version).await;
+                        } else {
+                            runtimes.resolver.invalidate(tenant_id);
+                        }
+                    }
+                    Err(e) => warn!("Tenant config change stream error: {}", e),
+                }
+            }
+            // The stream ended and changes may have been missed; rebuild
+            // (and watch again) on the next request
+            if let Some(runtimes) = runtimes.upgrade() {
+                runtimes.watcher.lock().unwrap().take();
+                runtimes.entries.lock().unwrap().clear();
+            }
+        })
+    }
+
+    /// Drop runtimes idle for longer than the idle timeout
+    fn evict_idle(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|tenant_id, entry| {
+            let idle = entry.last_used.elapsed() >= self.idle_timeout;
+            if idle {
+                debug!("Evicting idle tenant {}", tenant_id);
+            }
+            !idle
+        });
+        before - entries.len()
+    }
+
+    /// Periodically evict idle tenants
+    pub(crate) fn spawn_eviction(self: &Arc<Self>) {
+        let runtimes = Arc::downgrade(self);
+        let period = (self.idle_timeout / 4).clamp(Duration::from_secs(1), Duration::from_secs(60));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let Some(runtimes) = runtimes.upgrade() else {
+                    return;
+                };
+                let evicted = runtimes.evict_idle();
+                if evicted > 0 {
+                    info!("🏢 Dropped {} idle tenant(s)", evicted);
+                }
+            }
+        });
+    }
+}
+
+/// Serve a request with its tenant's runtime
+///
+/// Runs behind [`lunaroute_ingress::tenant::tenant_middleware`], which
+/// attaches the tenant's context.
+pub(crate) async fn dispatch(
+    State(runtimes): State<Arc<TenantRuntimes>>,
+    req: Request,
+) -> Response {
+    let Some(context) = req.extensions().get::<Arc<TenantContext>>().cloned() else {
+        return IngressError::Internal("request has no tenant".to_string()).into_response();
+    };
+    match runtimes.get(&context).await {
+        Ok(runtime) => match runtime.api.clone().oneshot(req).await {
+            Ok(response) => response,
+            Err(never) => match never {},
+        },
+        Err(e) => {
+            warn!(
+                "Tenant {:?} has an unusable config: {}",
+                context.tenant_id, e
+            );
+            IngressError::Internal("tenant configuration is invalid".to_string()).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lunaroute_core::config_store::{ConfigChange, ConfigChangeStream};
+    use lunaroute_ingress::tenant::TenancyConfig;
+    use tokio::sync::broadcast;
+
+    /// Config store holding one tenant whose config changes on demand
+    struct Store {
+        config: Mutex<serde_json::Value>,
+        changes: broadcast::Sender<ConfigChange>,
+    }
+
+    #[async_trait::async_trait]
+    impl ConfigStore for Store {
+        async fn get_config(
+            &self,
+            _tenant_id: Option<TenantId>,
+        ) -> lunaroute_core::Result<serde_json::Value> {
+            Ok(self.config.lock().unwrap().clone())
+        }
+
+        async fn update_config(
+            &self,
+            tenant_id: Option<TenantId>,
+            config: serde_json::Value,
+        ) -> lunaroute_core::Result<()> {
+            *self.config.lock().unwrap() = config;
+            let _ = self.changes.send(ConfigChange {
+                tenant_id,
+                timestamp: chrono::Utc::now(),
+                version: 2,
+            });
+            Ok(())
+        }
+
+        async fn watch_changes(
+            &self,
+            _tenant_id: Option<TenantId>,
+        ) -> lunaroute_core::Result<ConfigChangeStream<'_>> {
+            let changes = self.changes.subscribe();
+            Ok(Box::pin(futures::stream::unfold(
+                changes,
+                |mut changes| async move { changes.recv().await.ok().map(|c| (Ok(c), changes)) },
+            )))
+        }
+
+        async fn validate_config(&self, _config: &serde_json::Value) -> lunaroute_core::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn provider_config(provider: &str) -> serde_json::Value {
+        serde_json::json!({
+            "providers": { provider: { "enabled": true, "api_key": "sk-test" } }
+        })
+    }
+
+    fn runtimes(store: Arc<Store>, idle_timeout: Duration) -> Arc<TenantRuntimes> {
+        let resolver =
+            Arc::new(TenantResolver::new(TenancyConfig::default(), store.clone(), None).unwrap());
+        Arc::new(TenantRuntimes::new(
+            resolver,
+            Arc::new(Metrics::new().unwrap()),
+            Arc::new(SessionStatsTracker::new(Default::default())),
+            None,
+            idle_timeout,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_tenant_runtime_rebuilt_on_config_change() {
+        let store = Arc::new(Store {
+            config: Mutex::new(provider_config("openai")),
+            changes: broadcast::channel(4).0,
+        });
+        let runtimes = runtimes(store.clone(), Duration::from_secs(900));
+        let tenant_id = TenantId::new();
+        let context = runtimes.resolver.context(tenant_id).await.unwrap();
+
+        let first = runtimes.get(&context).await.unwrap();
+        assert_eq!(first.router.provider_ids(), vec!["openai"]);
+        while store.changes.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert!(Arc::ptr_eq(&first, &runtimes.get(&context).await.unwrap()));
+
+        store
+            .update_config(Some(tenant_id), provider_config("anthropic"))
+            .await
+            .unwrap();
+        let rebuilt = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let runtime = runtimes.get(&context).await.unwrap();
+                if !Arc::ptr_eq(&runtime, &first) {
+                    return runtime;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("runtime was not rebuilt");
+        assert_eq!(rebuilt.router.provider_ids(), vec!["anthropic"]);
+    }
+
+    #[tokio::test]
+    async fn test_tenants_share_one_change_listener() {
+        let store = Arc::new(Store {
+            config: Mutex::new(provider_config("openai")),
+            changes: broadcast::channel(4).0,
+        });
+        let runtimes = runtimes(store.clone(), Duration::from_secs(900));
+        for _ in 0..3 {
+            let context = runtimes.resolver.context(TenantId::new()).await.unwrap();
+            runtimes.get(&context).await.unwrap();
+        }
+        while store.changes.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(store.changes.receiver_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_config_rejects_local_credentials() {
+        let store = Arc::new(Store {
+            config: Mutex::new(serde_json::json!({
+                "providers": { "openai": { "enabled": true, "api_key_file": "/etc/shadow" } }
+            })),
+            changes: broadcast::channel(4).0,
+        });
+        let runtimes = runtimes(store, Duration::from_secs(900));
+        let context = runtimes.resolver.context(TenantId::new()).await.unwrap();
+
+        let err = runtimes.get(&context).await.err().unwrap();
+        assert!(err.to_string().contains("api_key_file"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_config_rejects_other_tenants_vault_refs() {
+        let other_tenant = TenantId::new();
+        let store = Arc::new(Store {
+            config: Mutex::new(serde_json::json!({
+                "providers": {
+                    "openai": { "enabled": true, "api_key": format!("vault:{}/openai", other_tenant) }
+                }
+            })),
+            changes: broadcast::channel(4).0,
+        });
+        let runtimes = runtimes(store, Duration::from_secs(900));
+        let context = runtimes.resolver.context(TenantId::new()).await.unwrap();
+
+        let err = runtimes.get(&context).await.err().unwrap();
+        assert!(
+            err.to_string().contains("outside the tenant's namespace"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_tenant_vault_refs_resolved() {
+        use lunaroute_storage::vault::{VAULT_PASSPHRASE_ENV, VAULT_PATH_ENV};
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let tenant_id = TenantId::new();
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault_path = dir.path().join("vault.enc");
+        let params = lunaroute_storage::KeyDerivationParams {
+            memory_size: 256,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let mut vault =
+            lunaroute_storage::CredentialVault::open_with_params(&vault_path, "pw", params)
+                .unwrap();
+        vault
+            .add(&format!("{}/openai", tenant_id), "sk-from-vault")
+            .unwrap();
+        vault.save().unwrap();
+        unsafe {
+            std::env::set_var(VAULT_PATH_ENV, &vault_path);
+            std::env::set_var(VAULT_PASSPHRASE_ENV, "pw");
+        }
+
+        let upstream = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("authorization", "Bearer sk-from-vault"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "gpt-4",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "hi" },
+                    "finish_reason": "stop"
+                }],
+                "usage": { "prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2 }
+            })))
+            .expect(1)
+            .mount(&upstream)
+            .await;
+
+        let store = Arc::new(Store {
+            config: Mutex::new(serde_json::json!({
+                "providers": {
+                    "openai": {
+                        "enabled": true,
+                        "api_key": format!("vault:{}/openai", tenant_id),
+                        "base_url": upstream.uri()
+                    }
+                }
+            })),
+            changes: broadcast::channel(4).0,
+        });
+        let runtimes = runtimes(store, Duration::from_secs(900));
+        let context = runtimes.resolver.context(tenant_id).await.unwrap();
+        let runtime = runtimes.get(&context).await;
+        unsafe {
+            std::env::remove_var(VAULT_PATH_ENV);
+            std::env::remove_var(VAULT_PASSPHRASE_ENV);
+        }
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(
+                serde_json::json!({
+                    "model": "gpt-4",
+                    "messages": [{ "role": "user", "content": "hello" }]
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = runtime.unwrap().api.clone().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_idle_tenants_evicted() {
+        let store = Arc::new(Store {
+            config: Mutex::new(provider_config("openai")),
+            changes: broadcast::channel(4).0,
+        });
+        let runtimes = runtimes(store, Duration::ZERO);
+        let context = runtimes.resolver.context(TenantId::new()).await.unwrap();
+
+        let first = runtimes.get(&context).await.unwrap();
+        assert_eq!(runtimes.evict_idle(), 1);
+        assert!(!Arc::ptr_eq(&first, &runtimes.get(&context).await.unwrap()));
+    }
+}
@@ -237,6 +237,47 @@ pub const MIGRATIONS: &[Migration] = &[
             ON tool_call_executions(tenant_id, created_at, session_id, tool_call_id)
         "#,
     },
+    Migration {
+        version: 10,
+        description: "Add experiment assignment columns to sessions",
+        up_sql: r#"
+            ALTER TABLE sessions
+                ADD COLUMN IF NOT EXISTS experiment TEXT,
+                ADD COLUMN IF NOT EXISTS experiment_arm TEXT
+        "#,
+    },
+    Migration {
+        version: 11,
+        description: "Create sessions experiment index",
+        up_sql: r#"
+            CREATE INDEX IF NOT EXISTS idx_sessions_tenant_experiment
+            ON sessions(tenant_id, experiment, experiment_arm, created_at DESC)
+        "#,
+    },
+    Migration {
+        version: 12,
+        description: "Add upstream key attribution to sessions",
+        up_sql: r#"
+            ALTER TABLE sessions
+                ADD COLUMN IF NOT EXISTS upstream_key TEXT
+        "#,
+    },
+    Migration {
+        version: 13,
+        description: "Create sessions upstream key index",
+        up_sql: r#"
+            CREATE INDEX IF NOT EXISTS idx_sessions_tenant_upstream_key
+            ON sessions(tenant_id, upstream_key, created_at DESC)
+        "#,
+    },
+    Migration {
+        version: 14,
+        description: "Add client identity to sessions",
+        up_sql: r#"
+            ALTER TABLE sessions
+                ADD COLUMN IF NOT EXISTS client_identity TEXT
+        "#,
+    },
 ];
 
 /// Run all pending migrations
@@ -492,6 +533,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::explicit_counter_loop)]
     fn test_migrations_are_sequential() {
         let mut expected_version = 1;
         for migration in MIGRATIONS {
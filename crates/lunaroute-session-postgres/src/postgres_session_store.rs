@@ -191,8 +191,8 @@ impl PostgresSessionStore {
                 INSERT INTO sessions (
                     tenant_id, session_id, request_id, started_at, created_at,
                     model_requested, provider, listener, is_streaming,
-                    client_ip, user_agent
-                ) VALUES ($1, $2, $3, $4, $4, $5, $6, $7, $8, $9::INET, $10)
+                    client_ip, user_agent, experiment, experiment_arm, client_identity
+                ) VALUES ($1, $2, $3, $4, $4, $5, $6, $7, $8, $9::INET, $10, $11, $12, $13)
                 ON CONFLICT (tenant_id, created_at, session_id) DO NOTHING
                 "#,
             )
@@ -206,6 +206,9 @@ impl PostgresSessionStore {
             .bind(is_streaming)
             .bind(&metadata.client_ip)
             .bind(&metadata.user_agent)
+            .bind(&metadata.experiment)
+            .bind(&metadata.experiment_arm)
+            .bind(&metadata.client_identity)
             .execute(&*self.pool)
             .await
             .map_err(|e| Error::SessionStore(format!("Failed to insert started event: {}", e)))?;
@@ -500,6 +503,23 @@ impl PostgresSessionStore {
         tenant_id: TenantId,
         event: &SessionEvent,
     ) -> Result<()> {
+        if let SessionEvent::StatsUpdated {
+            session_id,
+            upstream_key: Some(upstream_key),
+            ..
+        } = event
+        {
+            sqlx::query(
+                "UPDATE sessions SET upstream_key = $3 WHERE tenant_id = $1 AND session_id = $2",
+            )
+            .bind(tenant_id.as_uuid())
+            .bind(session_id)
+            .bind(upstream_key)
+            .execute(&*self.pool)
+            .await
+            .ok();
+        }
+
         if let SessionEvent::StatsUpdated {
             session_id,
             token_updates: Some(tokens),
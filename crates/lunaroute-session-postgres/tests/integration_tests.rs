@@ -116,6 +116,9 @@ async fn test_write_started_event() {
             api_version: None,
             request_headers: HashMap::new(),
             session_tags: vec![],
+            experiment: None,
+            experiment_arm: None,
+            client_identity: None,
         },
     };
 
@@ -152,6 +155,9 @@ async fn test_write_and_retrieve_session() {
             api_version: None,
             request_headers: HashMap::new(),
             session_tags: vec![],
+            experiment: None,
+            experiment_arm: None,
+            client_identity: None,
         },
     };
 
@@ -197,6 +203,9 @@ async fn test_complete_session_workflow() {
             api_version: None,
             request_headers: HashMap::new(),
             session_tags: vec![],
+            experiment: None,
+            experiment_arm: None,
+            client_identity: None,
         },
     };
 
@@ -343,6 +352,9 @@ async fn test_streaming_session_workflow() {
             api_version: None,
             request_headers: HashMap::new(),
             session_tags: vec![],
+            experiment: None,
+            experiment_arm: None,
+            client_identity: None,
         },
     };
 
@@ -468,6 +480,9 @@ async fn test_tool_call_recording() {
             api_version: None,
             request_headers: HashMap::new(),
             session_tags: vec![],
+            experiment: None,
+            experiment_arm: None,
+            client_identity: None,
         },
     };
 
@@ -580,6 +595,9 @@ async fn test_tenant_isolation() {
             api_version: None,
             request_headers: HashMap::new(),
             session_tags: vec![],
+            experiment: None,
+            experiment_arm: None,
+            client_identity: None,
         },
     };
 
@@ -603,6 +621,9 @@ async fn test_tenant_isolation() {
             api_version: None,
             request_headers: HashMap::new(),
             session_tags: vec![],
+            experiment: None,
+            experiment_arm: None,
+            client_identity: None,
         },
     };
 
@@ -643,6 +664,9 @@ async fn test_requires_tenant_id() {
             api_version: None,
             request_headers: HashMap::new(),
             session_tags: vec![],
+            experiment: None,
+            experiment_arm: None,
+            client_identity: None,
         },
     };
 
@@ -687,6 +711,9 @@ async fn test_search_sessions() {
                 api_version: None,
                 request_headers: HashMap::new(),
                 session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
 
@@ -729,6 +756,9 @@ async fn test_list_sessions_pagination() {
                 api_version: None,
                 request_headers: HashMap::new(),
                 session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
 
@@ -801,6 +831,9 @@ async fn test_concurrent_writes() {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             };
 
@@ -72,7 +72,7 @@ pub async fn scan_sessions(dir: &Path) -> Result<Vec<SessionFile>> {
 fn scan_directory_recursive<'a>(
     dir: &'a Path,
     session_files: &'a mut Vec<SessionFile>,
-) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + 'a>> {
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
     Box::pin(async move {
         let mut entries = fs::read_dir(dir)
             .await
@@ -169,6 +169,69 @@ async fn read_session_events(path: &Path) -> Result<Vec<SessionEvent>> {
     Ok(events)
 }
 
+/// A request body recovered from JSONL session logs
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub session_id: String,
+    pub request_id: String,
+    /// Listener that received the request ("anthropic" or "openai")
+    pub listener: Option<String>,
+    pub request_json: serde_json::Value,
+}
+
+/// Find a recorded request body by request ID
+///
+/// Searches newest sessions first. When `session_id` is given only that
+/// session's log is read; otherwise every session under `dir` is scanned.
+pub async fn find_recorded_request(
+    dir: &Path,
+    request_id: &str,
+    session_id: Option<&str>,
+) -> Result<Option<RecordedRequest>> {
+    let mut files = scan_sessions(dir).await?;
+    files.reverse();
+
+    for file in files {
+        if session_id.is_some_and(|id| id != file.session_id) {
+            continue;
+        }
+
+        let f = fs::File::open(&file.path)
+            .await
+            .context("Failed to open file")?;
+        let mut lines = BufReader::new(f).lines();
+        let mut listener = None;
+
+        while let Some(line) = lines.next_line().await.context("Failed to read line")? {
+            let Ok(event) = serde_json::from_str::<SessionEvent>(&line) else {
+                continue;
+            };
+            match event {
+                SessionEvent::Started {
+                    listener: started_listener,
+                    ..
+                } => listener = Some(started_listener),
+                SessionEvent::RequestRecorded {
+                    session_id,
+                    request_id: recorded_id,
+                    request_json,
+                    ..
+                } if recorded_id == request_id => {
+                    return Ok(Some(RecordedRequest {
+                        session_id,
+                        request_id: recorded_id,
+                        listener,
+                        request_json,
+                    }));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(None)
+}
+
 /// Check if a session exists in the database
 #[cfg(feature = "sqlite-writer")]
 async fn session_exists(db_path: &Path, session_id: &str) -> Result<bool> {
@@ -574,6 +574,9 @@ mod tests {
                 api_version: Some("v1".to_string()),
                 request_headers: HashMap::new(),
                 session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
 
@@ -611,6 +614,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::Completed {
@@ -721,6 +727,9 @@ mod tests {
                 api_version: None,
                 request_headers: HashMap::new(),
                 session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
 
@@ -772,6 +781,9 @@ mod tests {
                     api_version: Some("2023-06-01".to_string()),
                     request_headers: HashMap::new(),
                     session_tags: vec!["test".to_string(), "streaming".to_string()],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::StreamStarted {
@@ -891,6 +903,9 @@ mod tests {
                 api_version: None,
                 request_headers: HashMap::new(),
                 session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
         writer.write_event(&event1).await.unwrap();
@@ -910,6 +925,9 @@ mod tests {
                 api_version: None,
                 request_headers: HashMap::new(),
                 session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
         writer.write_event(&event2).await.unwrap();
@@ -929,6 +947,9 @@ mod tests {
                 api_version: None,
                 request_headers: HashMap::new(),
                 session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
         writer.write_event(&event3).await.unwrap();
@@ -948,6 +969,9 @@ mod tests {
                 api_version: None,
                 request_headers: HashMap::new(),
                 session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
         writer.write_event(&event4).await.unwrap();
@@ -1004,6 +1028,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             };
             writer.write_event(&event).await.unwrap();
@@ -1051,6 +1078,9 @@ mod tests {
                 api_version: Some("v1".to_string()),
                 request_headers: HashMap::new(),
                 session_tags: vec!["sensitive".to_string()],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
 
@@ -1147,6 +1177,9 @@ mod tests {
                 api_version: None,
                 request_headers: HashMap::new(),
                 session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
 
@@ -1214,6 +1247,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             };
             writer.write_event(&event).await.unwrap();
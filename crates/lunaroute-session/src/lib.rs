@@ -64,4 +64,7 @@ pub use writer::{
 pub use sqlite_writer::SqliteWriter;
 
 #[cfg(feature = "sqlite-writer")]
-pub use import::{ImportConfig, ImportResult, SessionFile, import_sessions, scan_sessions};
+pub use import::{
+    ImportConfig, ImportResult, RecordedRequest, SessionFile, find_recorded_request,
+    import_sessions, scan_sessions,
+};
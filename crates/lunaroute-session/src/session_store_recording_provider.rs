@@ -1,5 +1,6 @@
 //! Provider wrapper that records normalized routed requests through SessionStore.
 
+use crate::TenantScopedStore;
 use crate::events::{
     FinalSessionStats, PerformanceMetrics, RequestStats, ResponseStats, SessionEvent,
     SessionMetadata, StreamingStats, TokenStats, TokenTotals, ToolStats, ToolUsageSummary,
@@ -8,12 +9,14 @@ use async_trait::async_trait;
 use futures::Stream;
 use lunaroute_core::{
     Result,
+    identity::ClientIdentity,
     normalized::{
         ContentPart, FinishReason, MessageContent, NormalizedRequest, NormalizedResponse,
         NormalizedStreamEvent, Usage,
     },
     provider::{Provider, ProviderCapabilities},
     session_store::SessionStore,
+    tenant::TenantId,
 };
 use std::collections::HashMap;
 use std::pin::Pin;
@@ -56,14 +59,27 @@ impl SessionStoreRecordingProvider {
         }
     }
 
+    /// Store to record a request into: scoped to the request's tenant when
+    /// ingress attached one
+    fn store_for(&self, request: &NormalizedRequest) -> Arc<dyn SessionStore> {
+        match TenantId::from_request(request) {
+            Some(tenant) => Arc::new(TenantScopedStore::new(
+                self.session_store.clone(),
+                Some(tenant),
+            )),
+            None => self.session_store.clone(),
+        }
+    }
+
     async fn record_started(
         &self,
+        store: &Arc<dyn SessionStore>,
         session_id: String,
         request_id: String,
         request: &NormalizedRequest,
     ) {
         write_event(
-            self.session_store.clone(),
+            store.clone(),
             SessionEvent::Started {
                 session_id,
                 request_id,
@@ -78,6 +94,10 @@ impl SessionStoreRecordingProvider {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: Vec::new(),
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: ClientIdentity::from_request(request)
+                        .map(|identity| identity.subject),
                 },
             },
         )
@@ -86,6 +106,7 @@ impl SessionStoreRecordingProvider {
 
     async fn record_request(
         &self,
+        store: &Arc<dyn SessionStore>,
         session_id: String,
         request_id: String,
         request: &NormalizedRequest,
@@ -100,7 +121,7 @@ impl SessionStoreRecordingProvider {
                 .any(|message| matches!(message.role, lunaroute_core::normalized::Role::System));
 
         write_event(
-            self.session_store.clone(),
+            store.clone(),
             SessionEvent::RequestRecorded {
                 session_id,
                 request_id,
@@ -123,6 +144,7 @@ impl SessionStoreRecordingProvider {
 
     async fn record_response(
         &self,
+        store: &Arc<dyn SessionStore>,
         session_id: String,
         request_id: String,
         response: &NormalizedResponse,
@@ -132,7 +154,7 @@ impl SessionStoreRecordingProvider {
         let response_size_bytes = response_json.to_string().len();
 
         write_event(
-            self.session_store.clone(),
+            store.clone(),
             SessionEvent::ResponseRecorded {
                 session_id,
                 request_id,
@@ -158,9 +180,9 @@ impl SessionStoreRecordingProvider {
         .await;
     }
 
-    async fn record_completed(&self, record: CompletionRecord) {
+    async fn record_completed(&self, store: &Arc<dyn SessionStore>, record: CompletionRecord) {
         write_event(
-            self.session_store.clone(),
+            store.clone(),
             SessionEvent::Completed {
                 session_id: record.session_id,
                 request_id: record.request_id,
@@ -190,11 +212,18 @@ impl Provider for SessionStoreRecordingProvider {
         let session_id = uuid::Uuid::new_v4().to_string();
         let request_id = uuid::Uuid::new_v4().to_string();
         let started = Instant::now();
+        let store = self.store_for(&request);
 
-        self.record_started(session_id.clone(), request_id.clone(), &request)
-            .await;
-        self.record_request(session_id.clone(), request_id.clone(), &request, 0.0)
+        self.record_started(&store, session_id.clone(), request_id.clone(), &request)
             .await;
+        self.record_request(
+            &store,
+            session_id.clone(),
+            request_id.clone(),
+            &request,
+            0.0,
+        )
+        .await;
 
         let result = self.inner.send(request).await;
         let total_duration_ms = elapsed_ms(started);
@@ -202,6 +231,7 @@ impl Provider for SessionStoreRecordingProvider {
         match &result {
             Ok(response) => {
                 self.record_response(
+                    &store,
                     session_id.clone(),
                     request_id.clone(),
                     response,
@@ -209,31 +239,37 @@ impl Provider for SessionStoreRecordingProvider {
                 )
                 .await;
 
-                self.record_completed(CompletionRecord {
-                    session_id,
-                    request_id,
-                    success: true,
-                    error: None,
-                    finish_reason: response_finish_reason(response),
-                    total_duration_ms,
-                    tokens: totals_from_usage(response.usage, &response.model),
-                    tool_summary: tool_summary_from_response(response),
-                    streaming_stats: None,
-                })
+                self.record_completed(
+                    &store,
+                    CompletionRecord {
+                        session_id,
+                        request_id,
+                        success: true,
+                        error: None,
+                        finish_reason: response_finish_reason(response),
+                        total_duration_ms,
+                        tokens: totals_from_usage(response.usage, &response.model),
+                        tool_summary: tool_summary_from_response(response),
+                        streaming_stats: None,
+                    },
+                )
                 .await;
             }
             Err(error) => {
-                self.record_completed(CompletionRecord {
-                    session_id,
-                    request_id,
-                    success: false,
-                    error: Some(error.to_string()),
-                    finish_reason: None,
-                    total_duration_ms,
-                    tokens: TokenTotals::default(),
-                    tool_summary: ToolUsageSummary::default(),
-                    streaming_stats: None,
-                })
+                self.record_completed(
+                    &store,
+                    CompletionRecord {
+                        session_id,
+                        request_id,
+                        success: false,
+                        error: Some(error.to_string()),
+                        finish_reason: None,
+                        total_duration_ms,
+                        tokens: TokenTotals::default(),
+                        tool_summary: ToolUsageSummary::default(),
+                        streaming_stats: None,
+                    },
+                )
                 .await;
             }
         }
@@ -249,16 +285,23 @@ impl Provider for SessionStoreRecordingProvider {
         let request_id = uuid::Uuid::new_v4().to_string();
         let started = Instant::now();
         let requested_model = request.model.clone();
+        let store = self.store_for(&request);
 
-        self.record_started(session_id.clone(), request_id.clone(), &request)
-            .await;
-        self.record_request(session_id.clone(), request_id.clone(), &request, 0.0)
+        self.record_started(&store, session_id.clone(), request_id.clone(), &request)
             .await;
+        self.record_request(
+            &store,
+            session_id.clone(),
+            request_id.clone(),
+            &request,
+            0.0,
+        )
+        .await;
 
         match self.inner.stream(request).await {
             Ok(stream) => Ok(Box::new(SessionStoreRecordingStream {
                 inner: stream,
-                session_store: self.session_store.clone(),
+                session_store: store.clone(),
                 session_id,
                 request_id,
                 requested_model,
@@ -271,17 +314,20 @@ impl Provider for SessionStoreRecordingProvider {
                 completed: false,
             })),
             Err(error) => {
-                self.record_completed(CompletionRecord {
-                    session_id,
-                    request_id,
-                    success: false,
-                    error: Some(error.to_string()),
-                    finish_reason: None,
-                    total_duration_ms: elapsed_ms(started),
-                    tokens: TokenTotals::default(),
-                    tool_summary: ToolUsageSummary::default(),
-                    streaming_stats: None,
-                })
+                self.record_completed(
+                    &store,
+                    CompletionRecord {
+                        session_id,
+                        request_id,
+                        success: false,
+                        error: Some(error.to_string()),
+                        finish_reason: None,
+                        total_duration_ms: elapsed_ms(started),
+                        tokens: TokenTotals::default(),
+                        tool_summary: ToolUsageSummary::default(),
+                        streaming_stats: None,
+                    },
+                )
                 .await;
                 Err(error)
             }
@@ -173,6 +173,10 @@ impl SqliteWriter {
                 time_to_first_token_ms INTEGER,
                 chunk_count INTEGER,
                 streaming_duration_ms INTEGER,
+                experiment TEXT,
+                experiment_arm TEXT,
+                upstream_key TEXT,
+                client_identity TEXT,
                 created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
             )
             "#,
@@ -493,7 +497,7 @@ impl SqliteWriter {
 
     /// Run schema migrations to bring database up to current version
     async fn run_migrations(pool: &SqlitePool) -> WriterResult<()> {
-        const CURRENT_VERSION: i32 = 5;
+        const CURRENT_VERSION: i32 = 6;
 
         // Clean up any duplicate version entries (fix for previous bug where INSERT OR IGNORE could create duplicates)
         // Keep only the minimum version (the one we need to migrate from)
@@ -1128,9 +1132,110 @@ impl SqliteWriter {
                     WriterError::Database(format!("Migration 4->5 failed (version update): {}", e))
                 })?;
 
+            current_version = 5;
+        }
+
+        // Migration 5 -> 6: Record A/B experiment assignment on sessions
+        if current_version == 5 {
+            for column in ["experiment", "experiment_arm"] {
+                if !Self::column_exists(pool, "sessions", column).await? {
+                    sqlx::query(&format!("ALTER TABLE sessions ADD COLUMN {} TEXT", column))
+                        .execute(pool)
+                        .await
+                        .map_err(|e| {
+                            WriterError::Database(format!(
+                                "Migration 5->6 failed ({}): {}",
+                                column, e
+                            ))
+                        })?;
+                }
+            }
+
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_sessions_experiment ON sessions(experiment, experiment_arm)",
+            )
+            .execute(pool)
+            .await
+            .map_err(|e| {
+                WriterError::Database(format!("Migration 5->6 failed (index): {}", e))
+            })?;
+
+            sqlx::query("UPDATE schema_version SET version = 6")
+                .execute(pool)
+                .await
+                .map_err(|e| {
+                    WriterError::Database(format!("Migration 5->6 failed (version update): {}", e))
+                })?;
+
+            current_version = 6;
+        }
+
+        // Migration 6 -> 7: Attribute sessions to the upstream API key that served them
+        if current_version == 6 {
+            if !Self::column_exists(pool, "sessions", "upstream_key").await? {
+                sqlx::query("ALTER TABLE sessions ADD COLUMN upstream_key TEXT")
+                    .execute(pool)
+                    .await
+                    .map_err(|e| {
+                        WriterError::Database(format!(
+                            "Migration 6->7 failed (upstream_key): {}",
+                            e
+                        ))
+                    })?;
+            }
+
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_sessions_upstream_key ON sessions(upstream_key, created_at DESC)",
+            )
+            .execute(pool)
+            .await
+            .map_err(|e| {
+                WriterError::Database(format!("Migration 6->7 failed (index): {}", e))
+            })?;
+
+            sqlx::query("UPDATE schema_version SET version = 7")
+                .execute(pool)
+                .await
+                .map_err(|e| {
+                    WriterError::Database(format!("Migration 6->7 failed (version update): {}", e))
+                })?;
+
+            current_version = 7;
+        }
+
+        // Migration 7 -> 8: Record the authenticated caller (e.g. TLS client certificate)
+        if current_version == 7 {
+            if !Self::column_exists(pool, "sessions", "client_identity").await? {
+                sqlx::query("ALTER TABLE sessions ADD COLUMN client_identity TEXT")
+                    .execute(pool)
+                    .await
+                    .map_err(|e| {
+                        WriterError::Database(format!(
+                            "Migration 7->8 failed (client_identity): {}",
+                            e
+                        ))
+                    })?;
+            }
+
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_sessions_client_identity ON sessions(client_identity, created_at DESC)",
+            )
+            .execute(pool)
+            .await
+            .map_err(|e| {
+                WriterError::Database(format!("Migration 7->8 failed (index): {}", e))
+            })?;
+
+            sqlx::query("UPDATE schema_version SET version = 8")
+                .execute(pool)
+                .await
+                .map_err(|e| {
+                    WriterError::Database(format!("Migration 7->8 failed (version update): {}", e))
+                })?;
+
             #[allow(unused_assignments)]
             {
-                current_version = 5;
+                current_version = 8;
             }
         }
 
@@ -1568,8 +1673,8 @@ impl SessionWriter for SqliteWriter {
                 } => {
                     sqlx::query(
                         r#"
-                        INSERT INTO sessions (session_id, request_id, started_at, model_requested, provider, listener, client_ip, user_agent, is_streaming)
-                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                        INSERT INTO sessions (session_id, request_id, started_at, model_requested, provider, listener, client_ip, user_agent, is_streaming, experiment, experiment_arm, client_identity)
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                         ON CONFLICT(session_id) DO NOTHING
                         "#,
                     )
@@ -1582,6 +1687,9 @@ impl SessionWriter for SqliteWriter {
                     .bind(&metadata.client_ip)
                     .bind(&metadata.user_agent)
                     .bind(is_streaming)
+                    .bind(&metadata.experiment)
+                    .bind(&metadata.experiment_arm)
+                    .bind(&metadata.client_identity)
                     .execute(&mut *tx)
                     .await
                     .map_err(|e| WriterError::Database(e.to_string()))?;
@@ -1905,6 +2013,7 @@ impl SessionWriter for SqliteWriter {
                     content_blocks,
                     has_refusal,
                     user_agent,
+                    upstream_key,
                     ..
                 } => {
                     Self::handle_stats_updated(
@@ -1918,6 +2027,7 @@ impl SessionWriter for SqliteWriter {
                         *content_blocks,
                         *has_refusal,
                         user_agent.as_deref(),
+                        upstream_key.as_deref(),
                     )
                     .await?;
                 }
@@ -2057,6 +2167,7 @@ impl SqliteWriter {
         content_blocks: usize,
         has_refusal: bool,
         user_agent: Option<&str>,
+        upstream_key: Option<&str>,
     ) -> WriterResult<()> {
         // Update session tokens if provided (accumulates tokens across multiple requests)
         if let Some(tokens) = token_updates {
@@ -2128,6 +2239,21 @@ impl SqliteWriter {
             })?;
         }
 
+        // Attribute the session to the upstream key (latest request wins)
+        if let Some(upstream_key) = upstream_key {
+            sqlx::query("UPDATE sessions SET upstream_key = ? WHERE session_id = ?")
+                .bind(upstream_key)
+                .bind(session_id)
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| {
+                    WriterError::Database(format!(
+                        "Failed to update session upstream key for {}: {}",
+                        session_id, e
+                    ))
+                })?;
+        }
+
         // Insert/update tool calls if provided
         if let Some(tool_summary) = tool_call_updates
             && tool_summary.total_tool_calls > 0
@@ -2493,6 +2619,9 @@ mod tests {
                 api_version: None,
                 request_headers: HashMap::new(),
                 session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: None,
             },
         };
 
@@ -2519,7 +2648,137 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(version, 5);
+        assert_eq!(version, 8);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_writer_records_experiment_arm() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let writer = SqliteWriter::new(&db_path).await.unwrap();
+
+        let event = SessionEvent::Started {
+            session_id: "exp-session".to_string(),
+            request_id: "req-1".to_string(),
+            timestamp: Utc::now(),
+            model_requested: "claude-sonnet-4-5".to_string(),
+            provider: "anthropic".to_string(),
+            listener: "anthropic".to_string(),
+            is_streaming: false,
+            metadata: SessionMetadata {
+                client_ip: None,
+                user_agent: None,
+                api_version: None,
+                request_headers: HashMap::new(),
+                session_tags: vec![],
+                experiment: Some("sonnet-vs-gpt5".to_string()),
+                experiment_arm: Some("control".to_string()),
+                client_identity: None,
+            },
+        };
+        writer.write_event(&event).await.unwrap();
+
+        let (experiment, arm): (Option<String>, Option<String>) = sqlx::query_as(
+            "SELECT experiment, experiment_arm FROM sessions WHERE session_id = 'exp-session'",
+        )
+        .fetch_one(&writer.pool)
+        .await
+        .unwrap();
+
+        assert_eq!(experiment.as_deref(), Some("sonnet-vs-gpt5"));
+        assert_eq!(arm.as_deref(), Some("control"));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_writer_records_client_identity() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let writer = SqliteWriter::new(&db_path).await.unwrap();
+
+        let event = SessionEvent::Started {
+            session_id: "cert-session".to_string(),
+            request_id: "req-1".to_string(),
+            timestamp: Utc::now(),
+            model_requested: "claude-sonnet-4-5".to_string(),
+            provider: "anthropic".to_string(),
+            listener: "anthropic".to_string(),
+            is_streaming: false,
+            metadata: SessionMetadata {
+                client_ip: None,
+                user_agent: None,
+                api_version: None,
+                request_headers: HashMap::new(),
+                session_tags: vec![],
+                experiment: None,
+                experiment_arm: None,
+                client_identity: Some("alice".to_string()),
+            },
+        };
+        writer.write_event(&event).await.unwrap();
+
+        let identity: Option<String> = sqlx::query_scalar(
+            "SELECT client_identity FROM sessions WHERE session_id = 'cert-session'",
+        )
+        .fetch_one(&writer.pool)
+        .await
+        .unwrap();
+
+        assert_eq!(identity.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_writer_records_upstream_key() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let writer = SqliteWriter::new(&db_path).await.unwrap();
+
+        let events = vec![
+            SessionEvent::Started {
+                session_id: "key-session".to_string(),
+                request_id: "req-1".to_string(),
+                timestamp: Utc::now(),
+                model_requested: "claude-sonnet-4-5".to_string(),
+                provider: "anthropic".to_string(),
+                listener: "anthropic".to_string(),
+                is_streaming: true,
+                metadata: SessionMetadata {
+                    client_ip: None,
+                    user_agent: None,
+                    api_version: None,
+                    request_headers: HashMap::new(),
+                    session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
+                },
+            },
+            SessionEvent::StatsUpdated {
+                session_id: "key-session".to_string(),
+                request_id: "req-1".to_string(),
+                timestamp: Utc::now(),
+                token_updates: None,
+                tool_call_updates: None,
+                model_used: None,
+                response_size_bytes: 0,
+                content_blocks: 0,
+                has_refusal: false,
+                user_agent: None,
+                upstream_key: Some("key_0123456789ab".to_string()),
+            },
+        ];
+        writer.write_batch(&events).await.unwrap();
+
+        let upstream_key: Option<String> = sqlx::query_scalar(
+            "SELECT upstream_key FROM sessions WHERE session_id = 'key-session'",
+        )
+        .fetch_one(&writer.pool)
+        .await
+        .unwrap();
+
+        assert_eq!(upstream_key.as_deref(), Some("key_0123456789ab"));
     }
 
     #[tokio::test]
@@ -2544,6 +2803,9 @@ mod tests {
                     api_version: Some("v1".to_string()),
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::ResponseRecorded {
@@ -2627,6 +2889,9 @@ mod tests {
                     api_version: Some("2023-06-01".to_string()),
                     request_headers: HashMap::new(),
                     session_tags: vec!["streaming".to_string()],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::StreamStarted {
@@ -2770,6 +3035,9 @@ mod tests {
                         api_version: Some("v1".to_string()),
                         request_headers: HashMap::new(),
                         session_tags: vec![],
+                        experiment: None,
+                        experiment_arm: None,
+                        client_identity: None,
                     },
                 },
                 SessionEvent::ResponseRecorded {
@@ -2874,6 +3142,9 @@ mod tests {
                         api_version: None,
                         request_headers: HashMap::new(),
                         session_tags: vec![],
+                        experiment: None,
+                        experiment_arm: None,
+                        client_identity: None,
                     },
                 },
                 SessionEvent::ResponseRecorded {
@@ -3000,6 +3271,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             }];
             writer.write_batch(&events).await.unwrap();
@@ -3047,6 +3321,9 @@ mod tests {
                         api_version: None,
                         request_headers: HashMap::new(),
                         session_tags: vec![],
+                        experiment: None,
+                        experiment_arm: None,
+                        client_identity: None,
                     },
                 },
                 SessionEvent::RequestRecorded {
@@ -3153,6 +3430,9 @@ mod tests {
                         api_version: None,
                         request_headers: HashMap::new(),
                         session_tags: vec![],
+                        experiment: None,
+                        experiment_arm: None,
+                        client_identity: None,
                     },
                 },
                 SessionEvent::ResponseRecorded {
@@ -3238,6 +3518,9 @@ mod tests {
                         api_version: None,
                         request_headers: HashMap::new(),
                         session_tags: vec![],
+                        experiment: None,
+                        experiment_arm: None,
+                        client_identity: None,
                     },
                 },
                 SessionEvent::Completed {
@@ -3342,6 +3625,9 @@ mod tests {
                         api_version: None,
                         request_headers: HashMap::new(),
                         session_tags: vec![],
+                        experiment: None,
+                        experiment_arm: None,
+                        client_identity: None,
                     },
                 },
                 SessionEvent::Completed {
@@ -3441,6 +3727,9 @@ mod tests {
                         api_version: None,
                         request_headers: HashMap::new(),
                         session_tags: vec![],
+                        experiment: None,
+                        experiment_arm: None,
+                        client_identity: None,
                     },
                 },
                 SessionEvent::Completed {
@@ -3550,6 +3839,9 @@ mod tests {
                         api_version: None,
                         request_headers: HashMap::new(),
                         session_tags: vec![],
+                        experiment: None,
+                        experiment_arm: None,
+                        client_identity: None,
                     },
                 },
                 SessionEvent::ResponseRecorded {
@@ -3697,6 +3989,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             }];
             writer.write_batch(&events).await.unwrap();
@@ -3781,6 +4076,9 @@ mod tests {
                         api_version: None,
                         request_headers: HashMap::new(),
                         session_tags: vec![],
+                        experiment: None,
+                        experiment_arm: None,
+                        client_identity: None,
                     },
                 },
                 SessionEvent::ResponseRecorded {
@@ -3913,6 +4211,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::Completed {
@@ -3990,6 +4291,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::ResponseRecorded {
@@ -4126,6 +4430,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::Completed {
@@ -4261,6 +4568,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::Completed {
@@ -4415,6 +4725,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::ResponseRecorded {
@@ -4524,6 +4837,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::Completed {
@@ -4583,6 +4899,7 @@ mod tests {
             content_blocks: 1,
             has_refusal: false,
             user_agent: Some("test-client/1.0.0".to_string()),
+            upstream_key: None,
         };
 
         writer.write_event(&update_event).await.unwrap();
@@ -4628,6 +4945,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::Completed {
@@ -4699,6 +5019,7 @@ mod tests {
             content_blocks: 2,
             has_refusal: false,
             user_agent: None,
+            upstream_key: None,
         };
 
         writer.write_event(&update_event).await.unwrap();
@@ -4760,6 +5081,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::Completed {
@@ -4820,6 +5144,7 @@ mod tests {
             content_blocks: 1,
             has_refusal: false,
             user_agent: None,
+            upstream_key: None,
         };
 
         writer.write_event(&update_event).await.unwrap();
@@ -4859,6 +5184,7 @@ mod tests {
             content_blocks: 3,
             has_refusal: false,
             user_agent: Some("test-client/2.0.0".to_string()),
+            upstream_key: None,
         };
 
         writer.write_event(&update_event2).await.unwrap();
@@ -4904,6 +5230,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::Completed {
@@ -5109,6 +5438,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::StatsUpdated {
@@ -5134,6 +5466,7 @@ mod tests {
                 content_blocks: 1,
                 has_refusal: false,
                 user_agent: Some(long_ua),
+                upstream_key: None,
             },
         ];
 
@@ -5178,6 +5511,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::StatsUpdated {
@@ -5203,6 +5539,7 @@ mod tests {
                 content_blocks: 1,
                 has_refusal: false,
                 user_agent: Some(special_ua.to_string()),
+                upstream_key: None,
             },
         ];
 
@@ -5253,6 +5590,9 @@ mod tests {
                     api_version: None,
                     request_headers: HashMap::new(),
                     session_tags: vec![],
+                    experiment: None,
+                    experiment_arm: None,
+                    client_identity: None,
                 },
             },
             SessionEvent::StatsUpdated {
@@ -5278,6 +5618,7 @@ mod tests {
                 content_blocks: 1,
                 has_refusal: false,
                 user_agent: None, // No user agent
+                upstream_key: None,
             },
         ];
 
@@ -0,0 +1,27 @@
+//! PostgreSQL-backed shared state for multi-instance LunaRoute deployments
+//!
+//! This crate implements the `lunaroute-storage` `StateStore` trait on
+//! PostgreSQL, so several LunaRoute replicas behind a load balancer can share
+//! rate-limit backoffs, circuit breaker state and counters.
+//!
+//! # Features
+//! - Single `lunaroute_state` table (key, BYTEA value, optional expiry)
+//! - Atomic `increment` using row locks
+//! - TTL'd keys (expired keys are invisible and purged in the background)
+//! - `list_keys` by prefix backed by a `text_pattern_ops` index
+//! - Automatic schema migrations
+//!
+//! # Example
+//! ```no_run
+//! # use lunaroute_state_postgres::PostgresStateStore;
+//! # use lunaroute_storage::StateStore;
+//! # async fn example() -> lunaroute_storage::StorageResult<()> {
+//! let store = PostgresStateStore::new("postgres://localhost/lunaroute").await?;
+//! store.increment("requests:today", 1).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod postgres_state_store;
+
+pub use postgres_state_store::PostgresStateStore;
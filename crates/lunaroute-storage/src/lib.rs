@@ -7,6 +7,7 @@
 //! - Buffer pool for memory efficiency
 //! - Rolling file writer for streams
 //! - Session indexing for fast lookups
+//! - Encrypted credential vault for provider secrets
 
 pub mod atomic_writer;
 pub mod buffer_pool;
@@ -19,6 +20,7 @@ pub mod session;
 pub mod session_index;
 pub mod state;
 pub mod traits;
+pub mod vault;
 
 pub use atomic_writer::AtomicWriter;
 pub use buffer_pool::BufferPool;
@@ -36,3 +38,4 @@ pub use traits::{
     ConfigStore, RetentionPolicy, SessionData, SessionFilter, SessionInfo, SessionMetadata,
     SessionStore, StateStore, StorageError, StorageResult,
 };
+pub use vault::{CredentialVault, VaultEntryInfo};
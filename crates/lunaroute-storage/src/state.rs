@@ -2,9 +2,11 @@
 
 use crate::atomic_writer::AtomicWriter;
 use crate::traits::{StateStore, StorageError, StorageResult};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tokio::time::{Duration, interval};
 
@@ -14,10 +16,60 @@ const MAX_STATE_FILE_SIZE: u64 = 100 * 1024 * 1024;
 /// Maximum total state size in memory (500MB)
 const MAX_STATE_MEMORY_SIZE: usize = 500 * 1024 * 1024;
 
+/// Values plus expiry times (Unix ms) for keys set with a TTL
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StateMap {
+    entries: HashMap<String, Vec<u8>>,
+    #[serde(default)]
+    expires_at: HashMap<String, u64>,
+}
+
+impl StateMap {
+    fn is_live(&self, key: &str, now: u64) -> bool {
+        self.expires_at.get(key).is_none_or(|&at| at > now)
+    }
+
+    fn get(&self, key: &str, now: u64) -> Option<&Vec<u8>> {
+        self.entries.get(key).filter(|_| self.is_live(key, now))
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+        self.expires_at.remove(key);
+    }
+
+    fn purge_expired(&mut self, now: u64) {
+        let expired: Vec<String> = self
+            .expires_at
+            .iter()
+            .filter(|&(_, &at)| at <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            self.remove(&key);
+        }
+    }
+}
+
+/// On-disk formats: current (with expiry times) and legacy (plain map)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StateFile {
+    Current(StateMap),
+    Legacy(HashMap<String, Vec<u8>>),
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
 /// In-memory state store with periodic persistence
 pub struct FileStateStore {
     path: PathBuf,
-    state: Arc<RwLock<HashMap<String, Vec<u8>>>>,
+    state: Arc<RwLock<StateMap>>,
     persist_interval: Duration,
     max_state_size: usize,
 }
@@ -26,7 +78,7 @@ impl FileStateStore {
     /// Create a new file state store
     pub async fn new<P: AsRef<Path>>(path: P) -> StorageResult<Self> {
         let path = path.as_ref().to_path_buf();
-        let state = Arc::new(RwLock::new(HashMap::new()));
+        let state = Arc::new(RwLock::new(StateMap::default()));
 
         // Load existing state if present
         if path.exists() {
@@ -42,11 +94,19 @@ impl FileStateStore {
             }
 
             let content = std::fs::read_to_string(&path)?;
-            let loaded: HashMap<String, Vec<u8>> = serde_json::from_str(&content)
-                .map_err(|e| StorageError::Serialization(format!("Failed to load state: {}", e)))?;
+            let mut loaded = match serde_json::from_str(&content)
+                .map_err(|e| StorageError::Serialization(format!("Failed to load state: {}", e)))?
+            {
+                StateFile::Current(map) => map,
+                StateFile::Legacy(entries) => StateMap {
+                    entries,
+                    expires_at: HashMap::new(),
+                },
+            };
+            loaded.purge_expired(now_ms());
 
             // Validate total state size
-            let total_size: usize = loaded.values().map(|v| v.len()).sum();
+            let total_size = Self::calculate_state_size(&loaded.entries);
             if total_size > MAX_STATE_MEMORY_SIZE {
                 return Err(StorageError::InvalidData(format!(
                     "State data too large: {} bytes (max {} bytes)",
@@ -91,9 +151,10 @@ impl FileStateStore {
         })
     }
 
-    /// Serialize state for persistence
+    /// Serialize state for persistence, dropping expired keys first
     async fn serialize_state(&self) -> StorageResult<String> {
-        let state = self.state.read().await;
+        let mut state = self.state.write().await;
+        state.purge_expired(now_ms());
         serde_json::to_string_pretty(&*state)
             .map_err(|e| StorageError::Serialization(format!("Failed to serialize state: {}", e)))
     }
@@ -129,16 +190,30 @@ impl FileStateStore {
 impl StateStore for FileStateStore {
     async fn get(&self, key: &str) -> StorageResult<Option<Vec<u8>>> {
         let state = self.state.read().await;
-        Ok(state.get(key).cloned())
+        Ok(state.get(key, now_ms()).cloned())
     }
 
     async fn set(&self, key: &str, value: Vec<u8>) -> StorageResult<()> {
         let mut state = self.state.write().await;
 
         // Check size limit before inserting
-        Self::check_size_limit(&state, key, value.len(), self.max_state_size)?;
+        Self::check_size_limit(&state.entries, key, value.len(), self.max_state_size)?;
+
+        state.entries.insert(key.to_string(), value);
+        state.expires_at.remove(key);
+        Ok(())
+    }
+
+    async fn set_with_ttl(&self, key: &str, value: Vec<u8>, ttl: Duration) -> StorageResult<()> {
+        let mut state = self.state.write().await;
+
+        Self::check_size_limit(&state.entries, key, value.len(), self.max_state_size)?;
 
-        state.insert(key.to_string(), value);
+        state.entries.insert(key.to_string(), value);
+        state.expires_at.insert(
+            key.to_string(),
+            now_ms().saturating_add(ttl.as_millis() as u64),
+        );
         Ok(())
     }
 
@@ -150,14 +225,16 @@ impl StateStore for FileStateStore {
 
     async fn exists(&self, key: &str) -> StorageResult<bool> {
         let state = self.state.read().await;
-        Ok(state.contains_key(key))
+        Ok(state.get(key, now_ms()).is_some())
     }
 
     async fn list_keys(&self, prefix: &str) -> StorageResult<Vec<String>> {
         let state = self.state.read().await;
+        let now = now_ms();
         let keys: Vec<String> = state
+            .entries
             .keys()
-            .filter(|k| k.starts_with(prefix))
+            .filter(|k| k.starts_with(prefix) && state.is_live(k, now))
             .cloned()
             .collect();
         Ok(keys)
@@ -166,7 +243,13 @@ impl StateStore for FileStateStore {
     async fn increment(&self, key: &str, delta: i64) -> StorageResult<i64> {
         let mut state = self.state.write().await;
 
+        // An expired counter starts over without a TTL
+        if !state.is_live(key, now_ms()) {
+            state.remove(key);
+        }
+
         let current = state
+            .entries
             .get(key)
             .and_then(|v| {
                 if v.len() == 8 {
@@ -178,14 +261,46 @@ impl StateStore for FileStateStore {
             .unwrap_or(0);
 
         let new_value = current + delta;
-        state.insert(key.to_string(), new_value.to_le_bytes().to_vec());
+        state
+            .entries
+            .insert(key.to_string(), new_value.to_le_bytes().to_vec());
 
         Ok(new_value)
     }
 
+    async fn compare_and_swap(
+        &self,
+        key: &str,
+        expected: Option<&[u8]>,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    ) -> StorageResult<bool> {
+        let mut state = self.state.write().await;
+        let now = now_ms();
+        if state.get(key, now).map(Vec::as_slice) != expected {
+            return Ok(false);
+        }
+
+        Self::check_size_limit(&state.entries, key, value.len(), self.max_state_size)?;
+        state.entries.insert(key.to_string(), value);
+        match ttl {
+            Some(ttl) => {
+                state
+                    .expires_at
+                    .insert(key.to_string(), now.saturating_add(ttl.as_millis() as u64));
+            }
+            None => {
+                state.expires_at.remove(key);
+            }
+        }
+        Ok(true)
+    }
+
     async fn get_many(&self, keys: &[String]) -> StorageResult<Vec<Option<Vec<u8>>>> {
         let state = self.state.read().await;
-        let values: Vec<Option<Vec<u8>>> = keys.iter().map(|k| state.get(k).cloned()).collect();
+        let now = now_ms();
+        let values: Vec<Option<Vec<u8>>> =
+            keys.iter().map(|k| state.get(k, now).cloned()).collect();
         Ok(values)
     }
 
@@ -196,12 +311,12 @@ impl StateStore for FileStateStore {
         let new_items_size: usize = items
             .iter()
             .map(|(k, v)| {
-                let existing_size = state.get(k).map(|v| v.len()).unwrap_or(0);
+                let existing_size = state.entries.get(k).map(|v| v.len()).unwrap_or(0);
                 v.len().saturating_sub(existing_size)
             })
             .sum();
 
-        let current_size = Self::calculate_state_size(&state);
+        let current_size = Self::calculate_state_size(&state.entries);
         let projected_size = current_size + new_items_size;
 
         if projected_size > self.max_state_size {
@@ -212,7 +327,8 @@ impl StateStore for FileStateStore {
         }
 
         for (key, value) in items {
-            state.insert(key, value);
+            state.expires_at.remove(&key);
+            state.entries.insert(key, value);
         }
         Ok(())
     }
@@ -297,6 +413,55 @@ mod tests {
         assert_eq!(value3, 4);
     }
 
+    #[tokio::test]
+    async fn test_compare_and_swap() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = FileStateStore::new(temp_dir.path().join("state.json"))
+            .await
+            .unwrap();
+
+        assert!(
+            store
+                .compare_and_swap("k", None, b"a".to_vec(), None)
+                .await
+                .unwrap()
+        );
+        // Stale expectations are rejected
+        assert!(
+            !store
+                .compare_and_swap("k", None, b"b".to_vec(), None)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !store
+                .compare_and_swap("k", Some(b"x"), b"b".to_vec(), None)
+                .await
+                .unwrap()
+        );
+        assert!(
+            store
+                .compare_and_swap(
+                    "k",
+                    Some(b"a"),
+                    b"b".to_vec(),
+                    Some(Duration::from_millis(50))
+                )
+                .await
+                .unwrap()
+        );
+        assert_eq!(store.get("k").await.unwrap(), Some(b"b".to_vec()));
+
+        // An expired value counts as absent
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(
+            store
+                .compare_and_swap("k", None, b"c".to_vec(), None)
+                .await
+                .unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn test_get_many() {
         let temp_dir = TempDir::new().unwrap();
@@ -432,4 +597,49 @@ mod tests {
         let store2 = FileStateStore::new(&path).await.unwrap();
         assert_eq!(store2.get("key1").await.unwrap(), Some(b"value1".to_vec()));
     }
+
+    #[tokio::test]
+    async fn test_ttl_expiry_and_persist() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state.json");
+
+        {
+            let store = FileStateStore::new(&path).await.unwrap();
+            store
+                .set_with_ttl("short", b"v".to_vec(), Duration::from_millis(50))
+                .await
+                .unwrap();
+            store
+                .set_with_ttl("long", b"v".to_vec(), Duration::from_secs(600))
+                .await
+                .unwrap();
+            // Incrementing a live key keeps its TTL
+            assert_eq!(store.increment("short", 1).await.unwrap(), 1);
+            assert!(store.exists("short").await.unwrap());
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert!(!store.exists("short").await.unwrap());
+            assert_eq!(store.list_keys("").await.unwrap(), vec!["long".to_string()]);
+            store.persist().await.unwrap();
+        }
+
+        // The TTL survives a reload
+        let store = FileStateStore::new(&path).await.unwrap();
+        assert!(store.exists("long").await.unwrap());
+        assert!(store.get("short").await.unwrap().is_none());
+
+        // Overwriting without a TTL makes the key permanent
+        store.set("long", b"v2".to_vec()).await.unwrap();
+        assert!(store.state.read().await.expires_at.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_legacy_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state.json");
+        std::fs::write(&path, r#"{"key1": [118, 49]}"#).unwrap();
+
+        let store = FileStateStore::new(&path).await.unwrap();
+        assert_eq!(store.get("key1").await.unwrap(), Some(b"v1".to_vec()));
+    }
 }
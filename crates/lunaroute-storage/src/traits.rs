@@ -19,6 +19,9 @@ pub enum StorageError {
 
     #[error("Configuration error: {0}")]
     Config(String),
+
+    #[error("Backend error: {0}")]
+    Backend(String),
 }
 
 pub type StorageResult<T> = Result<T, StorageError>;
@@ -85,6 +88,17 @@ pub trait StateStore: Send + Sync {
     /// Set a value in the store
     async fn set(&self, key: &str, value: Vec<u8>) -> StorageResult<()>;
 
+    /// Set a value that expires after `ttl`
+    ///
+    /// Expired keys behave as if they had been deleted. A later `set` or
+    /// `set_many` on the key removes the TTL.
+    async fn set_with_ttl(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: std::time::Duration,
+    ) -> StorageResult<()>;
+
     /// Delete a value from the store
     async fn delete(&self, key: &str) -> StorageResult<()>;
 
@@ -95,8 +109,24 @@ pub trait StateStore: Send + Sync {
     async fn list_keys(&self, prefix: &str) -> StorageResult<Vec<String>>;
 
     /// Atomically increment a counter
+    ///
+    /// Counters are stored as 8-byte little-endian `i64`s. Incrementing a live
+    /// key keeps its TTL; an expired key starts over from zero.
     async fn increment(&self, key: &str, delta: i64) -> StorageResult<i64>;
 
+    /// Atomically replace a value if it still equals `expected`
+    ///
+    /// `expected: None` requires the key to be absent (or expired). The new
+    /// value expires after `ttl` (None = never). Returns whether the value was
+    /// replaced; callers re-read and retry on `false`.
+    async fn compare_and_swap(
+        &self,
+        key: &str,
+        expected: Option<&[u8]>,
+        value: Vec<u8>,
+        ttl: Option<std::time::Duration>,
+    ) -> StorageResult<bool>;
+
     /// Get multiple values at once
     async fn get_many(&self, keys: &[String]) -> StorageResult<Vec<Option<Vec<u8>>>>;
 
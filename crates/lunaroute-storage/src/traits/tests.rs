@@ -129,6 +129,9 @@ fn test_storage_error_variants() {
 
     let config = StorageError::Config("invalid config".to_string());
     assert!(config.to_string().contains("Configuration"));
+
+    let backend = StorageError::Backend("connection refused".to_string());
+    assert!(backend.to_string().contains("Backend"));
 }
 
 #[test]
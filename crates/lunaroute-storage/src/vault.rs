@@ -0,0 +1,364 @@
+//! Encrypted local credential vault
+//!
+//! Stores named provider secrets in a single file encrypted with AES-256-GCM
+//! under a key derived from a passphrase with Argon2id. Provider config can
+//! reference an entry as `api_key: "vault:<name>"`.
+//!
+//! File layout: `LRVAULT1` magic, 16-byte salt, Argon2 memory/iterations/
+//! parallelism (u32 LE each), then the nonce-prefixed ciphertext of a JSON
+//! map of entries. Tampering with the header changes the derived key, so it
+//! is authenticated implicitly by the GCM tag.
+
+use crate::atomic_writer::AtomicWriter;
+use crate::encryption::{
+    KeyDerivationParams, decrypt, derive_key_from_password, encrypt, generate_salt,
+};
+use crate::traits::{StorageError, StorageResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Prefix marking a config value as a vault reference
+pub const VAULT_REF_PREFIX: &str = "vault:";
+
+/// Environment variable holding the vault passphrase
+pub const VAULT_PASSPHRASE_ENV: &str = "LUNAROUTE_VAULT_PASSPHRASE";
+
+/// Environment variable overriding the vault location
+pub const VAULT_PATH_ENV: &str = "LUNAROUTE_VAULT_PATH";
+
+const MAGIC: &[u8; 8] = b"LRVAULT1";
+const HEADER_LEN: usize = 8 + 16 + 12;
+
+/// Vault entry name referenced by a config value, if it is a `vault:` reference
+pub fn vault_ref(value: &str) -> Option<&str> {
+    value.strip_prefix(VAULT_REF_PREFIX)
+}
+
+/// Vault location: `LUNAROUTE_VAULT_PATH`, else `~/.lunaroute/vault.enc`
+pub fn default_vault_path() -> PathBuf {
+    match std::env::var(VAULT_PATH_ENV) {
+        Ok(path) if !path.is_empty() => PathBuf::from(path),
+        _ => {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            PathBuf::from(home).join(".lunaroute").join("vault.enc")
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct VaultEntry {
+    secret: String,
+    created_at: u64,
+    updated_at: u64,
+}
+
+/// Entry metadata; never includes the secret
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultEntryInfo {
+    pub name: String,
+    /// Unix seconds
+    pub created_at: u64,
+    /// Unix seconds of the last rotation
+    pub updated_at: u64,
+}
+
+/// Decrypted view of the vault file
+pub struct CredentialVault {
+    path: PathBuf,
+    salt: [u8; 16],
+    params: KeyDerivationParams,
+    key: [u8; 32],
+    entries: BTreeMap<String, VaultEntry>,
+}
+
+impl fmt::Debug for CredentialVault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CredentialVault")
+            .field("path", &self.path)
+            .field("entries", &self.entries.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl CredentialVault {
+    /// Open the vault at `path`, or start an empty one if the file does not exist
+    pub fn open(path: impl AsRef<Path>, passphrase: &str) -> StorageResult<Self> {
+        Self::open_with_params(path, passphrase, KeyDerivationParams::default())
+    }
+
+    /// Like [`open`](Self::open); `params` only apply when creating a new vault
+    pub fn open_with_params(
+        path: impl AsRef<Path>,
+        passphrase: &str,
+        params: KeyDerivationParams,
+    ) -> StorageResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        if passphrase.is_empty() {
+            return Err(StorageError::Config(
+                "vault passphrase must not be empty".to_string(),
+            ));
+        }
+
+        if !path.exists() {
+            let salt = generate_salt();
+            let key = derive_key_from_password(passphrase, &salt, &params)?;
+            return Ok(Self {
+                path,
+                salt,
+                params,
+                key,
+                entries: BTreeMap::new(),
+            });
+        }
+
+        let bytes = std::fs::read(&path)?;
+        if bytes.len() < HEADER_LEN || &bytes[..8] != MAGIC {
+            return Err(StorageError::InvalidData(format!(
+                "{} is not a LunaRoute vault",
+                path.display()
+            )));
+        }
+        let mut salt = [0u8; 16];
+        salt.copy_from_slice(&bytes[8..24]);
+        let read_u32 = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let params = KeyDerivationParams {
+            memory_size: read_u32(24),
+            iterations: read_u32(28),
+            parallelism: read_u32(32),
+        };
+        let key = derive_key_from_password(passphrase, &salt, &params)?;
+        let plaintext = decrypt(&bytes[HEADER_LEN..], &key).map_err(|_| {
+            StorageError::Config("wrong vault passphrase or corrupted vault".to_string())
+        })?;
+        let entries = serde_json::from_slice(&plaintext)
+            .map_err(|e| StorageError::Serialization(format!("Invalid vault contents: {}", e)))?;
+
+        Ok(Self {
+            path,
+            salt,
+            params,
+            key,
+            entries,
+        })
+    }
+
+    /// Path of the vault file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Secret stored under `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).map(|e| e.secret.as_str())
+    }
+
+    /// Entry names and timestamps, sorted by name
+    pub fn list(&self) -> Vec<VaultEntryInfo> {
+        self.entries
+            .iter()
+            .map(|(name, e)| VaultEntryInfo {
+                name: name.clone(),
+                created_at: e.created_at,
+                updated_at: e.updated_at,
+            })
+            .collect()
+    }
+
+    /// Add a new secret; fails if `name` already exists
+    pub fn add(&mut self, name: &str, secret: &str) -> StorageResult<()> {
+        validate_name(name)?;
+        validate_secret(secret)?;
+        if self.entries.contains_key(name) {
+            return Err(StorageError::Config(format!(
+                "vault entry '{}' already exists (use rotate)",
+                name
+            )));
+        }
+        let now = now_secs();
+        self.entries.insert(
+            name.to_string(),
+            VaultEntry {
+                secret: secret.to_string(),
+                created_at: now,
+                updated_at: now,
+            },
+        );
+        Ok(())
+    }
+
+    /// Replace the secret of an existing entry
+    pub fn rotate(&mut self, name: &str, secret: &str) -> StorageResult<()> {
+        validate_secret(secret)?;
+        let entry = self
+            .entries
+            .get_mut(name)
+            .ok_or_else(|| StorageError::NotFound(format!("vault entry '{}'", name)))?;
+        entry.secret = secret.to_string();
+        entry.updated_at = now_secs();
+        Ok(())
+    }
+
+    /// Remove an entry
+    pub fn remove(&mut self, name: &str) -> StorageResult<()> {
+        self.entries
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| StorageError::NotFound(format!("vault entry '{}'", name)))
+    }
+
+    /// Encrypt and atomically write the vault (mode 0600)
+    pub fn save(&self) -> StorageResult<()> {
+        let plaintext = serde_json::to_vec(&self.entries)
+            .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        let ciphertext = encrypt(&plaintext, &self.key)?;
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&self.params.memory_size.to_le_bytes());
+        bytes.extend_from_slice(&self.params.iterations.to_le_bytes());
+        bytes.extend_from_slice(&self.params.parallelism.to_le_bytes());
+        bytes.extend_from_slice(&ciphertext);
+
+        let mut writer = AtomicWriter::new(&self.path)?;
+        writer.write(&bytes)?;
+        writer.commit()
+    }
+}
+
+/// Names may contain `/` so tenants get their own `<tenant_id>/` namespace
+fn validate_name(name: &str) -> StorageResult<()> {
+    let valid = !name.is_empty()
+        && name.split('/').all(|part| !part.is_empty())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::Config(format!(
+            "invalid vault entry name '{}' (use letters, digits, '-', '_', '.' or '/')",
+            name
+        )))
+    }
+}
+
+fn validate_secret(secret: &str) -> StorageResult<()> {
+    if secret.is_empty() {
+        return Err(StorageError::Config("secret must not be empty".to_string()));
+    }
+    Ok(())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // Cheap KDF settings so tests stay fast
+    fn test_params() -> KeyDerivationParams {
+        KeyDerivationParams {
+            memory_size: 256,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    fn open(path: &Path, passphrase: &str) -> StorageResult<CredentialVault> {
+        CredentialVault::open_with_params(path, passphrase, test_params())
+    }
+
+    #[test]
+    fn test_vault_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vault.enc");
+
+        let mut vault = open(&path, "hunter2").unwrap();
+        vault.add("anthropic-prod", "sk-ant-secret").unwrap();
+        vault.add("openai", "sk-openai-secret").unwrap();
+        vault.save().unwrap();
+
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!raw.windows(13).any(|w| w == b"sk-ant-secret"));
+
+        let vault = open(&path, "hunter2").unwrap();
+        assert_eq!(vault.get("anthropic-prod"), Some("sk-ant-secret"));
+        let names: Vec<_> = vault.list().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["anthropic-prod", "openai"]);
+    }
+
+    #[test]
+    fn test_vault_wrong_passphrase() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vault.enc");
+
+        let mut vault = open(&path, "right").unwrap();
+        vault.add("k", "secret").unwrap();
+        vault.save().unwrap();
+
+        let err = open(&path, "wrong").unwrap_err();
+        assert!(err.to_string().contains("wrong vault passphrase"));
+    }
+
+    #[test]
+    fn test_vault_rotate_and_remove() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vault.enc");
+
+        let mut vault = open(&path, "pw").unwrap();
+        vault.add("k", "old").unwrap();
+        assert!(vault.add("k", "again").is_err());
+        vault.rotate("k", "new").unwrap();
+        assert!(vault.rotate("missing", "x").is_err());
+        vault.save().unwrap();
+
+        let mut vault = open(&path, "pw").unwrap();
+        assert_eq!(vault.get("k"), Some("new"));
+        vault.remove("k").unwrap();
+        assert!(vault.remove("k").is_err());
+        assert!(vault.list().is_empty());
+    }
+
+    #[test]
+    fn test_vault_rejects_invalid_names_and_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("vault.enc");
+
+        let mut vault = open(&path, "pw").unwrap();
+        assert!(vault.add("has space", "x").is_err());
+        assert!(vault.add("", "x").is_err());
+        assert!(vault.add("tenant//key", "x").is_err());
+        vault.add("tenant/key", "x").unwrap();
+        assert!(vault.add("ok", "").is_err());
+        assert!(open(&path, "").is_err());
+
+        std::fs::write(&path, b"not a vault").unwrap();
+        assert!(open(&path, "pw").is_err());
+    }
+
+    #[test]
+    fn test_vault_debug_hides_secrets() {
+        let dir = TempDir::new().unwrap();
+        let mut vault = open(&dir.path().join("vault.enc"), "pw").unwrap();
+        vault.add("k", "sk-very-secret").unwrap();
+        let debug = format!("{:?}", vault);
+        assert!(debug.contains("\"k\""));
+        assert!(!debug.contains("sk-very-secret"));
+    }
+
+    #[test]
+    fn test_vault_ref() {
+        assert_eq!(vault_ref("vault:prod"), Some("prod"));
+        assert_eq!(vault_ref("sk-plain"), None);
+    }
+}
@@ -245,6 +245,17 @@ pub async fn hour_of_day_stats(
     Json(stats)
 }
 
+/// Per-arm comparison of A/B experiments
+pub async fn experiment_stats(
+    State(state): State<AppState>,
+    Query(params): Query<StatsQuery>,
+) -> Json<Vec<ExperimentArmStats>> {
+    let stats = queries::get_experiment_stats(&state.db, params.hours.unwrap_or(0))
+        .await
+        .unwrap_or_default();
+    Json(stats)
+}
+
 /// Spending statistics with per-model breakdown
 pub async fn spending_stats(
     State(state): State<AppState>,
@@ -260,6 +271,14 @@ pub async fn spending_stats(
     Json(stats)
 }
 
+/// Live budget usage (empty when budgets are not enabled)
+pub async fn budgets(State(state): State<AppState>) -> Json<Vec<BudgetUsage>> {
+    match &state.budgets {
+        Some(source) => Json(source().await),
+        None => Json(vec![]),
+    }
+}
+
 /// Tool usage statistics for a specific session
 pub async fn session_tool_stats(
     Path(session_id): Path<String>,
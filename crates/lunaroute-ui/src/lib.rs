@@ -14,11 +14,19 @@ pub mod stats;
 pub use server::{UiConfig, UiServer};
 
 use sqlx::SqlitePool;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 
+/// Live budget usage for the spending section, supplied by the proxy
+pub type BudgetSource =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Vec<models::BudgetUsage>> + Send>> + Send + Sync>;
+
 /// Shared application state for the UI server
 #[derive(Clone)]
 pub struct AppState {
     pub db: Arc<SqlitePool>,
     pub config: UiConfig,
+    /// Budget usage (None when budgets are not enabled)
+    pub budgets: Option<BudgetSource>,
 }
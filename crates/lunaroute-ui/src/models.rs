@@ -146,6 +146,23 @@ pub struct SpendingStats {
     pub by_model: Vec<ModelSpending>,
 }
 
+/// Live usage of one budget by one subject (key, user, tenant or project)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetUsage {
+    pub budget: String,
+    pub subject: String,
+    pub subject_id: String,
+    /// `tokens`, `requests` or `dollars`
+    pub metric: String,
+    /// `daily`, `monthly` or `rolling_30d`
+    pub window: String,
+    pub limit: f64,
+    pub used: f64,
+    /// `ok`, `warning` or `exceeded`
+    pub state: String,
+    pub resets_at: String,
+}
+
 /// Tool usage statistics for a session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionToolStats {
@@ -170,3 +187,32 @@ pub struct RawRequestResponse {
     pub request_id: String,
     pub session_id: String,
 }
+
+/// Per-arm comparison for an A/B experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentArmStats {
+    pub experiment: String,
+    pub arm: String,
+    pub session_count: i64,
+    pub request_count: i64,
+    pub avg_duration_ms: f64,
+    pub avg_provider_latency_ms: f64,
+    pub success_rate: f64,
+    pub total_input_tokens: i64,
+    pub total_output_tokens: i64,
+    pub total_thinking_tokens: i64,
+    pub avg_tokens_per_request: f64,
+    pub total_cost: f64,
+    pub avg_cost_per_session: f64,
+    pub tool_call_count: i64,
+    pub tool_failure_count: i64,
+    pub tool_failure_rate: f64,
+    pub finish_reasons: Vec<FinishReasonCount>,
+}
+
+/// Number of sessions ending with a given finish reason
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinishReasonCount {
+    pub finish_reason: String,
+    pub count: i64,
+}
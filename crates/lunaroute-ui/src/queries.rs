@@ -1137,3 +1137,193 @@ pub async fn get_session_tool_stats(
         by_tool,
     })
 }
+
+/// Get per-arm comparison statistics for A/B experiments.
+///
+/// `hours <= 0` means all time. Latency, success and finish reason come from
+/// the session rows; tokens and cost from per-request stats; tool failures
+/// from individual tool call executions.
+pub async fn get_experiment_stats(
+    pool: &SqlitePool,
+    hours: i64,
+) -> Result<Vec<ExperimentArmStats>> {
+    use std::collections::BTreeMap;
+
+    const TIME_FILTER: &str = "(? <= 0 OR s.started_at >= datetime('now', '-' || ? || ' hours'))";
+
+    let session_rows = sqlx::query(&format!(
+        r#"
+        SELECT
+            s.experiment AS experiment,
+            s.experiment_arm AS arm,
+            COUNT(*) AS session_count,
+            COALESCE(AVG(s.total_duration_ms), 0.0) AS avg_duration_ms,
+            COALESCE(AVG(s.provider_latency_ms), 0.0) AS avg_provider_latency_ms,
+            COALESCE(AVG(CASE WHEN s.success = 1 THEN 100.0 WHEN s.success = 0 THEN 0.0 END), 100.0) AS success_rate
+        FROM sessions s
+        WHERE s.experiment IS NOT NULL AND s.experiment_arm IS NOT NULL AND {}
+        GROUP BY s.experiment, s.experiment_arm
+        "#,
+        TIME_FILTER
+    ))
+    .bind(hours)
+    .bind(hours)
+    .fetch_all(pool)
+    .await?;
+
+    let mut arms: BTreeMap<(String, String), ExperimentArmStats> = BTreeMap::new();
+    for row in &session_rows {
+        let experiment: String = row.try_get("experiment")?;
+        let arm: String = row.try_get("arm")?;
+        arms.insert(
+            (experiment.clone(), arm.clone()),
+            ExperimentArmStats {
+                experiment,
+                arm,
+                session_count: row.try_get("session_count").unwrap_or(0),
+                request_count: 0,
+                avg_duration_ms: row.try_get("avg_duration_ms").unwrap_or(0.0),
+                avg_provider_latency_ms: row.try_get("avg_provider_latency_ms").unwrap_or(0.0),
+                success_rate: row.try_get("success_rate").unwrap_or(100.0),
+                total_input_tokens: 0,
+                total_output_tokens: 0,
+                total_thinking_tokens: 0,
+                avg_tokens_per_request: 0.0,
+                total_cost: 0.0,
+                avg_cost_per_session: 0.0,
+                tool_call_count: 0,
+                tool_failure_count: 0,
+                tool_failure_rate: 0.0,
+                finish_reasons: vec![],
+            },
+        );
+    }
+
+    // Tokens and cost, grouped by model so each request is priced correctly
+    let token_rows = sqlx::query(&format!(
+        r#"
+        SELECT
+            s.experiment AS experiment,
+            s.experiment_arm AS arm,
+            ss.model_name AS model_name,
+            COUNT(*) AS request_count,
+            COALESCE(SUM(ss.input_tokens), 0) AS input_tokens,
+            COALESCE(SUM(ss.output_tokens), 0) AS output_tokens,
+            COALESCE(SUM(ss.thinking_tokens), 0) AS thinking_tokens
+        FROM session_stats ss
+        INNER JOIN sessions s ON ss.session_id = s.session_id
+        WHERE s.experiment IS NOT NULL AND s.experiment_arm IS NOT NULL AND {}
+        GROUP BY s.experiment, s.experiment_arm, ss.model_name
+        "#,
+        TIME_FILTER
+    ))
+    .bind(hours)
+    .bind(hours)
+    .fetch_all(pool)
+    .await?;
+
+    let unique_models: Vec<String> = token_rows
+        .iter()
+        .filter_map(|r| r.try_get::<Option<String>, _>("model_name").ok().flatten())
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+    let pricing_map = crate::pricing::PRICING_FETCHER
+        .get_batch_pricing(&unique_models)
+        .await;
+
+    for row in &token_rows {
+        let key = (row.try_get("experiment")?, row.try_get("arm")?);
+        let Some(stats) = arms.get_mut(&key) else {
+            continue;
+        };
+        let model: Option<String> = row.try_get("model_name").ok().flatten();
+        let input: i64 = row.try_get("input_tokens").unwrap_or(0);
+        let output: i64 = row.try_get("output_tokens").unwrap_or(0);
+        let thinking: i64 = row.try_get("thinking_tokens").unwrap_or(0);
+
+        stats.request_count += row.try_get::<i64, _>("request_count").unwrap_or(0);
+        stats.total_input_tokens += input;
+        stats.total_output_tokens += output;
+        stats.total_thinking_tokens += thinking;
+        stats.total_cost += stats::calculate_cost_from_map(
+            input,
+            output,
+            thinking,
+            model.as_deref().unwrap_or(""),
+            &pricing_map,
+        );
+    }
+
+    let tool_rows = sqlx::query(&format!(
+        r#"
+        SELECT
+            s.experiment AS experiment,
+            s.experiment_arm AS arm,
+            COUNT(*) AS call_count,
+            COALESCE(SUM(CASE WHEN t.success = 0 THEN 1 ELSE 0 END), 0) AS failure_count
+        FROM tool_call_executions t
+        INNER JOIN sessions s ON t.session_id = s.session_id
+        WHERE s.experiment IS NOT NULL AND s.experiment_arm IS NOT NULL AND {}
+        GROUP BY s.experiment, s.experiment_arm
+        "#,
+        TIME_FILTER
+    ))
+    .bind(hours)
+    .bind(hours)
+    .fetch_all(pool)
+    .await?;
+
+    for row in &tool_rows {
+        let key = (row.try_get("experiment")?, row.try_get("arm")?);
+        if let Some(stats) = arms.get_mut(&key) {
+            stats.tool_call_count = row.try_get("call_count").unwrap_or(0);
+            stats.tool_failure_count = row.try_get("failure_count").unwrap_or(0);
+        }
+    }
+
+    let finish_rows = sqlx::query(&format!(
+        r#"
+        SELECT
+            s.experiment AS experiment,
+            s.experiment_arm AS arm,
+            COALESCE(s.finish_reason, 'unknown') AS finish_reason,
+            COUNT(*) AS count
+        FROM sessions s
+        WHERE s.experiment IS NOT NULL AND s.experiment_arm IS NOT NULL AND {}
+        GROUP BY s.experiment, s.experiment_arm, COALESCE(s.finish_reason, 'unknown')
+        ORDER BY count DESC
+        "#,
+        TIME_FILTER
+    ))
+    .bind(hours)
+    .bind(hours)
+    .fetch_all(pool)
+    .await?;
+
+    for row in &finish_rows {
+        let key = (row.try_get("experiment")?, row.try_get("arm")?);
+        if let Some(stats) = arms.get_mut(&key) {
+            stats.finish_reasons.push(FinishReasonCount {
+                finish_reason: row.try_get("finish_reason")?,
+                count: row.try_get("count").unwrap_or(0),
+            });
+        }
+    }
+
+    Ok(arms
+        .into_values()
+        .map(|mut stats| {
+            let total_tokens =
+                stats.total_input_tokens + stats.total_output_tokens + stats.total_thinking_tokens;
+            stats.avg_tokens_per_request = total_tokens as f64 / stats.request_count.max(1) as f64;
+            stats.avg_cost_per_session = stats.total_cost / stats.session_count.max(1) as f64;
+            stats.tool_failure_rate = if stats.tool_call_count > 0 {
+                stats.tool_failure_count as f64 / stats.tool_call_count as f64 * 100.0
+            } else {
+                0.0
+            };
+            stats
+        })
+        .collect())
+}
@@ -1,8 +1,8 @@
 //! Web UI server implementation
 
 use crate::handlers;
-use crate::AppState;
-use axum::{routing::get, Router};
+use crate::{AppState, BudgetSource};
+use axum::{routing::get, serve::Listener, Router};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use std::net::SocketAddr;
@@ -45,6 +45,11 @@ pub struct UiConfig {
     /// Path to sessions directory (for raw JSONL access)
     #[serde(default = "default_sessions_dir")]
     pub sessions_dir: Option<std::path::PathBuf>,
+
+    /// Serve HTTPS instead of HTTP (optionally requiring client certificates).
+    /// The proxy binds the TLS listener and hands it to [`UiServer::serve_on`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<lunaroute_core::tls::TlsSettings>,
 }
 
 fn default_enabled() -> bool {
@@ -77,6 +82,7 @@ impl Default for UiConfig {
             delete_enabled: false,
             log_requests: false,
             sessions_dir: default_sessions_dir(),
+            tls: None,
         }
     }
 }
@@ -85,6 +91,7 @@ impl Default for UiConfig {
 pub struct UiServer {
     config: UiConfig,
     db: Arc<SqlitePool>,
+    budgets: Option<BudgetSource>,
 }
 
 impl UiServer {
@@ -95,7 +102,17 @@ impl UiServer {
             config.sessions_dir = Some(expand_tilde(path));
         }
 
-        Self { config, db }
+        Self {
+            config,
+            db,
+            budgets: None,
+        }
+    }
+
+    /// Show live budget usage in the spending section
+    pub fn with_budgets(mut self, budgets: BudgetSource) -> Self {
+        self.budgets = Some(budgets);
+        self
     }
 
     /// Build the Axum router with all routes
@@ -103,6 +120,7 @@ impl UiServer {
         let state = AppState {
             db: self.db.clone(),
             config: self.config.clone(),
+            budgets: self.budgets.clone(),
         };
 
         let router = Router::new()
@@ -133,6 +151,11 @@ impl UiServer {
             .route("/api/stats/models", get(handlers::api::model_stats))
             .route("/api/stats/hours", get(handlers::api::hour_of_day_stats))
             .route("/api/stats/spending", get(handlers::api::spending_stats))
+            .route("/api/budgets", get(handlers::api::budgets))
+            .route(
+                "/api/stats/experiments",
+                get(handlers::api::experiment_stats),
+            )
             .route("/api/sessions", get(handlers::api::sessions_list))
             .route("/api/sessions/recent", get(handlers::api::recent_sessions))
             .route("/api/sessions/{id}", get(handlers::api::session_detail))
@@ -166,19 +189,36 @@ impl UiServer {
             info!("📊 UI server disabled in configuration");
             return Ok(());
         }
+        if self.config.tls.is_some() {
+            anyhow::bail!("UI TLS is configured; bind a TLS listener and use serve_on");
+        }
 
         let addr: SocketAddr = format!("{}:{}", self.config.host, self.config.port)
             .parse()
             .map_err(|e| anyhow::anyhow!("Invalid address: {}", e))?;
 
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        self.serve_on(listener).await
+    }
+
+    /// Serve the UI on an already-bound listener (e.g. one terminating TLS)
+    pub async fn serve_on<L>(self, listener: L) -> anyhow::Result<()>
+    where
+        L: Listener<Addr = SocketAddr>,
+    {
+        let addr = listener.local_addr()?;
+        let scheme = if self.config.tls.is_some() {
+            "https"
+        } else {
+            "http"
+        };
         let router = self.build_router();
 
-        info!("📊 LunaRoute UI server starting on http://{}", addr);
-        info!("   Dashboard:  http://{}/", addr);
-        info!("   Sessions:   http://{}/sessions", addr);
-        info!("   Analytics:  http://{}/analytics", addr);
+        info!("📊 LunaRoute UI server starting on {}://{}", scheme, addr);
+        info!("   Dashboard:  {}://{}/", scheme, addr);
+        info!("   Sessions:   {}://{}/sessions", scheme, addr);
+        info!("   Analytics:  {}://{}/analytics", scheme, addr);
 
-        let listener = tokio::net::TcpListener::bind(addr).await?;
         axum::serve(listener, router).await?;
 
         Ok(())